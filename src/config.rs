@@ -0,0 +1,185 @@
+//! Server Configuration
+//!
+//! The configuration is a JSON file passed with `--config <path>` or the
+//! `MCP_GDB_CONFIG` environment variable. Every field is optional.
+
+use crate::gdb::hooks::Hooks;
+use crate::mcp::limits::Limits;
+use crate::mcp::tools::{tool_profile, TOOL_PROFILES};
+use anyhow::{anyhow, Context, Result};
+use serde::Deserialize;
+use std::collections::BTreeMap;
+use std::path::{Path, PathBuf};
+
+/// Environment variable holding the configuration file path
+pub const CONFIG_ENV_VAR: &str = "MCP_GDB_CONFIG";
+
+/// MCP GDB Server configuration
+#[derive(Debug, Clone, Default, Deserialize)]
+#[serde(default)]
+pub struct ServerConfig {
+    pub audit: AuditConfig,
+    pub tools: ToolsConfig,
+    /// Ask the user (via MCP elicitation) before memory writes, register
+    /// changes and other destructive commands
+    pub confirm_destructive: bool,
+    /// Stop a session's GDB when no tool call has used it for this many
+    /// minutes (sessions can override it with `gdb_start`)
+    pub idle_timeout_minutes: Option<u64>,
+    /// Default GDB data directory of new sessions (`--data-directory`)
+    pub data_directory: Option<String>,
+    /// Source the bundled Python helpers into new sessions
+    pub python_helpers: bool,
+    /// Safety caps applied to every session (`gdb_start` can tighten them)
+    pub limits: Limits,
+    /// Named debugging setups applied with `gdb_start` `profile`
+    pub profiles: BTreeMap<String, TargetProfile>,
+    /// GDB commands every session runs after starting, connecting and loading
+    pub hooks: Hooks,
+    /// Directory of the per-executable notes of `gdb_notes_get` and
+    /// `gdb_notes_set` (the tools are disabled when unset)
+    pub notes_dir: Option<String>,
+    /// GNU assembler of `gdb_patch_code` (default: chosen from the target
+    /// architecture, e.g. `arm-none-eabi-as`)
+    pub assembler: Option<String>,
+    /// How often `gdb_continue` and `gdb_run` interrupt the target to sample
+    /// its PC while waiting out `max_runtime_ms` (default: 0, no sampling)
+    pub pc_sample_interval_ms: Option<u64>,
+}
+
+/// A named debugging setup, e.g. one board with its probe. Arguments given
+/// to `gdb_start` take precedence over the profile's values.
+#[derive(Debug, Clone, Default, Deserialize)]
+#[serde(default)]
+pub struct TargetProfile {
+    pub gdb_path: Option<String>,
+    pub architecture: Option<String>,
+    /// Remote target connected to after startup ("host:port" or a serial device)
+    pub remote: Option<String>,
+    /// GDB commands run after startup, before connecting to `remote`
+    pub init_commands: Vec<String>,
+    /// SVD file `gdb_break_vectors` names interrupts from by default
+    pub svd_file: Option<String>,
+    /// QMP socket of the QEMU instance (`unix:/path` or `host:port`),
+    /// used by `gdb_qemu_monitor` instead of GDB's `monitor` command
+    pub qmp: Option<String>,
+    pub sysroot: Option<String>,
+    /// Hooks run after the server-wide ones
+    pub hooks: Hooks,
+}
+
+/// Which tools are exposed through `tools/list` and how they are paged
+#[derive(Debug, Clone, Default, Deserialize)]
+#[serde(default)]
+pub struct ToolsConfig {
+    /// Named subset of tools to expose (see `TOOL_PROFILES`); all tools when unset
+    pub profile: Option<String>,
+    /// Tools exposed in addition to the profile
+    pub include: Vec<String>,
+    /// Tools hidden even if the profile contains them
+    pub exclude: Vec<String>,
+    /// Maximum number of tools per `tools/list` page (0 disables pagination)
+    pub page_size: usize,
+}
+
+/// Tool call audit log configuration
+#[derive(Debug, Clone, Deserialize)]
+#[serde(default)]
+pub struct AuditConfig {
+    /// JSONL file that every tool invocation is appended to
+    pub log_path: Option<String>,
+    /// Argument names whose values are replaced with "[REDACTED]", at any
+    /// nesting depth
+    pub redact_args: Vec<String>,
+    /// Number of recent entries kept in memory for `gdb_audit_log`
+    pub max_recent: usize,
+}
+
+impl Default for AuditConfig {
+    fn default() -> Self {
+        Self {
+            log_path: None,
+            redact_args: Vec::new(),
+            max_recent: 500,
+        }
+    }
+}
+
+impl ServerConfig {
+    /// Load configuration from a JSON file
+    pub fn load(path: &Path) -> Result<Self> {
+        let content = std::fs::read_to_string(path)
+            .with_context(|| format!("Failed to read config file {}", path.display()))?;
+        let config: Self = serde_json::from_str(&content)
+            .with_context(|| format!("Failed to parse config file {}", path.display()))?;
+
+        if let Some(profile) = &config.tools.profile {
+            if tool_profile(profile).is_none() {
+                return Err(anyhow!(
+                    "Unknown tool profile '{}' (expected one of: {})",
+                    profile,
+                    TOOL_PROFILES.join(", ")
+                ));
+            }
+        }
+        Ok(config)
+    }
+
+    /// Load configuration from the command line (`--config <path>`) or the
+    /// environment, falling back to defaults when neither is given
+    pub fn from_env() -> Result<Self> {
+        let mut args = std::env::args().skip(1);
+        let mut path = std::env::var(CONFIG_ENV_VAR).ok();
+
+        while let Some(arg) = args.next() {
+            if arg == "--config" {
+                path = Some(args.next().ok_or_else(|| anyhow!("--config requires a path"))?);
+            } else if let Some(p) = arg.strip_prefix("--config=") {
+                path = Some(p.to_string());
+            }
+        }
+
+        match path {
+            Some(p) => Self::load(Path::new(&p)),
+            None => Ok(Self::default()),
+        }
+    }
+}
+
+/// How the server talks to MCP clients
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Transport {
+    /// One client on stdin/stdout
+    Stdio,
+    /// Daemon accepting clients on a Unix socket (`--listen <path>`)
+    Listen(PathBuf),
+    /// Relay stdin/stdout to a daemon, starting it when needed
+    /// (`--connect <path>`)
+    Connect(PathBuf),
+    /// Make the tool calls of a recording and report divergences
+    /// (`--replay <file>`)
+    Replay(PathBuf),
+}
+
+impl Transport {
+    /// Select the transport from the command line
+    pub fn from_args() -> Result<Self> {
+        let mut args = std::env::args().skip(1);
+        let mut transport = Transport::Stdio;
+
+        while let Some(arg) = args.next() {
+            transport = match arg.as_str() {
+                "--listen" => Transport::Listen(args.next().ok_or_else(|| anyhow!("--listen requires a socket path"))?.into()),
+                "--connect" => Transport::Connect(args.next().ok_or_else(|| anyhow!("--connect requires a socket path"))?.into()),
+                "--replay" => Transport::Replay(args.next().ok_or_else(|| anyhow!("--replay requires a recording file"))?.into()),
+                _ => match (arg.strip_prefix("--listen="), arg.strip_prefix("--connect="), arg.strip_prefix("--replay=")) {
+                    (Some(p), _, _) => Transport::Listen(p.into()),
+                    (_, Some(p), _) => Transport::Connect(p.into()),
+                    (_, _, Some(p)) => Transport::Replay(p.into()),
+                    _ => continue,
+                },
+            };
+        }
+        Ok(transport)
+    }
+}
@@ -0,0 +1,490 @@
+//! Allocation Tracing
+//!
+//! Breakpoints on the first instruction of malloc, calloc, realloc and free
+//! are handled by the output reader thread without reporting a stop: the
+//! arguments and return address are read at entry, allocating functions are
+//! run to their return for the pointer, and the target is continued. The
+//! live allocations and per-caller totals give a lightweight leak check on
+//! targets where no external tooling is available.
+
+use crate::gdb::autoresume::CONDITION_TOKEN;
+use crate::gdb::parser::parse_address;
+use crate::gdb::types::*;
+use crate::util::now_ms;
+use serde::Serialize;
+use std::collections::{HashMap, VecDeque};
+
+/// Tokens of the commands the reader thread sends while tracing
+pub const ENTRY_TOKEN: u64 = CONDITION_TOKEN + 1;
+pub const FINISH_TOKEN: u64 = CONDITION_TOKEN + 2;
+pub const RETURN_TOKEN: u64 = CONDITION_TOKEN + 3;
+
+pub const DEFAULT_FUNCTIONS: &[&str] = &["malloc", "calloc", "realloc", "free"];
+
+/// Maximum number of events kept; older events are dropped
+const MAX_EVENTS: usize = 10_000;
+
+#[derive(Debug, Clone, Copy, PartialEq, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum AllocKind {
+    Malloc,
+    Calloc,
+    Realloc,
+    Free,
+}
+
+impl AllocKind {
+    pub fn from_function(name: &str) -> Option<Self> {
+        match name {
+            "malloc" => Some(AllocKind::Malloc),
+            "calloc" => Some(AllocKind::Calloc),
+            "realloc" => Some(AllocKind::Realloc),
+            "free" => Some(AllocKind::Free),
+            _ => None,
+        }
+    }
+
+    /// Values read at entry: the size (or pointer for free), the return
+    /// address and, for realloc, the old pointer
+    fn entry_expression(self, convention: &CallingConvention) -> String {
+        let [a0, a1] = convention.args;
+        let values = match self {
+            AllocKind::Malloc => vec![a0.to_string(), convention.caller.to_string()],
+            AllocKind::Calloc => vec![format!("({}) * ({})", a0, a1), convention.caller.to_string()],
+            AllocKind::Realloc => vec![a1.to_string(), convention.caller.to_string(), a0.to_string()],
+            AllocKind::Free => vec![a0.to_string(), convention.caller.to_string()],
+        };
+        let casts: Vec<String> = values.iter().map(|v| format!("(unsigned long long)({})", v)).collect();
+        format!("{{{}}}", casts.join(", "))
+    }
+}
+
+/// Where a function's arguments, return address and result are at its
+/// first instruction
+#[derive(Debug)]
+pub struct CallingConvention {
+    pub arch: &'static str,
+    /// Register that exists only on this architecture
+    pub probe: &'static str,
+    pub args: [&'static str; 2],
+    pub caller: &'static str,
+    pub result: &'static str,
+}
+
+pub const CONVENTIONS: &[CallingConvention] = &[
+    CallingConvention { arch: "x86-64", probe: "$rip", args: ["$rdi", "$rsi"], caller: "*(unsigned long long *)$sp", result: "$rax" },
+    CallingConvention {
+        arch: "i386",
+        probe: "$eip",
+        args: ["*(unsigned int *)($esp + 4)", "*(unsigned int *)($esp + 8)"],
+        caller: "*(unsigned int *)$esp",
+        result: "$eax",
+    },
+    CallingConvention { arch: "aarch64", probe: "$x30", args: ["$x0", "$x1"], caller: "$x30", result: "$x0" },
+    CallingConvention { arch: "riscv", probe: "$ra", args: ["$a0", "$a1"], caller: "$ra", result: "$a0" },
+    CallingConvention { arch: "arm", probe: "$lr", args: ["$r0", "$r1"], caller: "$lr", result: "$r0" },
+];
+
+/// A traced function and its breakpoint
+#[derive(Debug, Clone, Serialize)]
+pub struct TracedFunction {
+    pub function: String,
+    pub breakpoint: String,
+}
+
+/// One traced call
+#[derive(Debug, Clone, Serialize)]
+pub struct AllocEvent {
+    pub seq: u64,
+    pub kind: AllocKind,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub size: Option<u64>,
+    /// Returned pointer, or the freed one
+    pub ptr: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub old_ptr: Option<String>,
+    pub caller: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub thread_id: Option<String>,
+    /// Milliseconds since the Unix epoch
+    pub timestamp_ms: u64,
+}
+
+#[derive(Debug, Clone)]
+struct Allocation {
+    seq: u64,
+    size: u64,
+    caller: u64,
+}
+
+#[derive(Debug, Clone, Default)]
+struct CallerStats {
+    allocations: u64,
+    bytes: u64,
+    live: u64,
+    live_bytes: u64,
+}
+
+/// Totals of a trace
+#[derive(Debug, Clone, Default, Serialize)]
+pub struct AllocStats {
+    pub allocations: u64,
+    pub frees: u64,
+    pub bytes_allocated: u64,
+    pub bytes_freed: u64,
+    /// Frees of pointers not allocated while tracing
+    pub unmatched_frees: u64,
+    /// Calls whose result was lost because another stop intervened
+    pub lost: u64,
+    /// Calls whose arguments could not be read
+    pub failed: u64,
+}
+
+/// Totals of one call site
+#[derive(Debug, Clone, Serialize)]
+pub struct CallerSummary {
+    pub caller: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub symbol: Option<String>,
+    pub allocations: u64,
+    pub bytes: u64,
+    pub live: u64,
+    pub live_bytes: u64,
+}
+
+/// An allocation that has not been freed
+#[derive(Debug, Clone, Serialize)]
+pub struct LiveAllocation {
+    pub seq: u64,
+    pub ptr: String,
+    pub size: u64,
+    pub caller: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub symbol: Option<String>,
+}
+
+/// Result of the summary action of `gdb_alloc_trace`
+#[derive(Debug, Clone, Serialize)]
+pub struct AllocSummary {
+    pub active: bool,
+    #[serde(flatten)]
+    pub stats: AllocStats,
+    pub live_allocations: usize,
+    pub live_bytes: u64,
+    /// Call sites by bytes allocated
+    pub top_allocators: Vec<CallerSummary>,
+    /// Largest allocations not freed yet
+    pub leak_candidates: Vec<LiveAllocation>,
+}
+
+/// Progress of the call being traced
+#[derive(Debug, Clone, Default, PartialEq)]
+enum Phase {
+    #[default]
+    Idle,
+    /// Entry values requested
+    Entry { kind: AllocKind, thread: Option<String> },
+    /// Running to the return of the function
+    Finish { call: PendingCall },
+    /// Return value requested
+    Return { call: PendingCall },
+}
+
+#[derive(Debug, Clone, PartialEq)]
+struct PendingCall {
+    kind: AllocKind,
+    thread: Option<String>,
+    size: u64,
+    caller: u64,
+    old_ptr: Option<u64>,
+}
+
+/// Trace state, shared with the output reader thread
+#[derive(Debug, Default)]
+pub struct AllocTracer {
+    convention: Option<&'static CallingConvention>,
+    /// Breakpoint numbers of the traced functions
+    breakpoints: HashMap<String, AllocKind>,
+    phase: Phase,
+    live: HashMap<u64, Allocation>,
+    callers: HashMap<u64, CallerStats>,
+    events: VecDeque<AllocEvent>,
+    next_seq: u64,
+    stats: AllocStats,
+}
+
+impl AllocTracer {
+    /// Start tracing with the given breakpoints, discarding earlier results
+    pub fn start(&mut self, convention: &'static CallingConvention, breakpoints: HashMap<String, AllocKind>) {
+        *self = Self { convention: Some(convention), breakpoints, ..Self::default() };
+    }
+
+    /// Stop tracing and return the breakpoints to delete. Results are kept.
+    pub fn stop(&mut self) -> Vec<String> {
+        self.phase = Phase::Idle;
+        self.breakpoints.drain().map(|(number, _)| number).collect()
+    }
+
+    pub fn active(&self) -> bool {
+        !self.breakpoints.is_empty()
+    }
+
+    /// Command to send for a `*stopped` record, or None if the stop is to be
+    /// reported as usual
+    pub fn on_stop(&mut self, results: &[MiResult]) -> Option<String> {
+        let convention = self.convention?;
+        let reason = find_string(results, "reason").unwrap_or_default();
+        let thread = find_string(results, "thread-id");
+
+        if let Phase::Finish { call } = &self.phase {
+            if reason == "function-finished" && call.thread == thread {
+                let command = format!("{}-data-evaluate-expression {}\"(unsigned long long){}\"\n", RETURN_TOKEN, thread_options(&thread), convention.result);
+                self.phase = Phase::Return { call: call.clone() };
+                return Some(command);
+            }
+        }
+        if self.phase != Phase::Idle {
+            self.stats.lost += 1;
+            self.phase = Phase::Idle;
+        }
+
+        let kind = *find_string(results, "bkptno").and_then(|n| self.breakpoints.get(&n)).filter(|_| reason == "breakpoint-hit")?;
+        let command = format!("{}-data-evaluate-expression {}\"{}\"\n", ENTRY_TOKEN, thread_options(&thread), kind.entry_expression(convention));
+        self.phase = Phase::Entry { kind, thread };
+        Some(command)
+    }
+
+    /// Command to send for the response to a command of the tracer
+    pub fn on_response(&mut self, token: u64, response: &MiOutputRecord) -> Option<String> {
+        let MiOutputRecord::Result { class, results, .. } = response else {
+            return None;
+        };
+        let continue_command = Some("-exec-continue\n".to_string());
+        match (token, std::mem::take(&mut self.phase)) {
+            (ENTRY_TOKEN, Phase::Entry { kind, thread }) => {
+                let values = (*class == ResultClass::Done)
+                    .then(|| find_string(results, "value").map(|v| parse_array(&v)))
+                    .flatten()
+                    .filter(|values| values.len() >= 2);
+                let Some(values) = values else {
+                    self.stats.failed += 1;
+                    return continue_command;
+                };
+                if kind == AllocKind::Free {
+                    self.record_free(values[0], values[1], thread);
+                    return continue_command;
+                }
+                let command = format!("{}-exec-finish {}\n", FINISH_TOKEN, thread_options(&thread).trim_end());
+                self.phase = Phase::Finish {
+                    call: PendingCall { kind, thread, size: values[0], caller: values[1], old_ptr: values.get(2).copied() },
+                };
+                Some(command)
+            }
+            (FINISH_TOKEN, phase @ Phase::Finish { .. }) => {
+                if *class == ResultClass::Running {
+                    self.phase = phase;
+                    return None;
+                }
+                self.stats.lost += 1;
+                continue_command
+            }
+            (RETURN_TOKEN, Phase::Return { call }) => {
+                match find_string(results, "value").and_then(|v| parse_address(&v)).filter(|_| *class == ResultClass::Done) {
+                    Some(ptr) => self.record_allocation(call, ptr),
+                    None => self.stats.lost += 1,
+                }
+                continue_command
+            }
+            (_, phase) => {
+                self.phase = phase;
+                None
+            }
+        }
+    }
+
+    fn push_event(&mut self, event: AllocEvent) {
+        self.events.push_back(event);
+        if self.events.len() > MAX_EVENTS {
+            self.events.pop_front();
+        }
+    }
+
+    fn release(&mut self, ptr: u64) -> Option<Allocation> {
+        let allocation = self.live.remove(&ptr)?;
+        if let Some(stats) = self.callers.get_mut(&allocation.caller) {
+            stats.live -= 1;
+            stats.live_bytes -= allocation.size;
+        }
+        self.stats.bytes_freed += allocation.size;
+        Some(allocation)
+    }
+
+    fn record_free(&mut self, ptr: u64, caller: u64, thread: Option<String>) {
+        // free(NULL) does nothing
+        if ptr == 0 {
+            return;
+        }
+        self.stats.frees += 1;
+        if self.release(ptr).is_none() {
+            self.stats.unmatched_frees += 1;
+        }
+        let seq = self.next_seq();
+        self.push_event(AllocEvent {
+            seq,
+            kind: AllocKind::Free,
+            size: None,
+            ptr: format!("{:#x}", ptr),
+            old_ptr: None,
+            caller: format!("{:#x}", caller),
+            thread_id: thread,
+            timestamp_ms: now_ms(),
+        });
+    }
+
+    fn record_allocation(&mut self, call: PendingCall, ptr: u64) {
+        let seq = self.next_seq();
+        if let Some(old) = call.old_ptr.filter(|&old| old != 0) {
+            self.release(old);
+        }
+        if ptr != 0 {
+            self.stats.allocations += 1;
+            self.stats.bytes_allocated += call.size;
+            self.live.insert(ptr, Allocation { seq, size: call.size, caller: call.caller });
+            let stats = self.callers.entry(call.caller).or_default();
+            stats.allocations += 1;
+            stats.bytes += call.size;
+            stats.live += 1;
+            stats.live_bytes += call.size;
+        }
+        self.push_event(AllocEvent {
+            seq,
+            kind: call.kind,
+            size: Some(call.size),
+            ptr: format!("{:#x}", ptr),
+            old_ptr: call.old_ptr.map(|p| format!("{:#x}", p)),
+            caller: format!("{:#x}", call.caller),
+            thread_id: call.thread,
+            timestamp_ms: now_ms(),
+        });
+    }
+
+    fn next_seq(&mut self) -> u64 {
+        self.next_seq += 1;
+        self.next_seq
+    }
+
+    /// The most recent `limit` events, oldest first
+    pub fn events(&self, limit: usize) -> Vec<AllocEvent> {
+        self.events.iter().skip(self.events.len().saturating_sub(limit)).cloned().collect()
+    }
+
+    /// Totals with the `top` largest call sites and live allocations;
+    /// symbols are left for the caller to fill in
+    pub fn summary(&self, top: usize) -> AllocSummary {
+        let mut callers: Vec<(&u64, &CallerStats)> = self.callers.iter().collect();
+        callers.sort_by(|a, b| b.1.bytes.cmp(&a.1.bytes).then(a.0.cmp(b.0)));
+        let mut live: Vec<(&u64, &Allocation)> = self.live.iter().collect();
+        live.sort_by(|a, b| b.1.size.cmp(&a.1.size).then(a.1.seq.cmp(&b.1.seq)));
+
+        AllocSummary {
+            active: self.active(),
+            stats: self.stats.clone(),
+            live_allocations: self.live.len(),
+            live_bytes: self.live.values().map(|a| a.size).sum(),
+            top_allocators: callers.into_iter()
+                .take(top)
+                .map(|(caller, stats)| CallerSummary {
+                    caller: format!("{:#x}", caller),
+                    symbol: None,
+                    allocations: stats.allocations,
+                    bytes: stats.bytes,
+                    live: stats.live,
+                    live_bytes: stats.live_bytes,
+                })
+                .collect(),
+            leak_candidates: live.into_iter()
+                .take(top)
+                .map(|(ptr, allocation)| LiveAllocation {
+                    seq: allocation.seq,
+                    ptr: format!("{:#x}", ptr),
+                    size: allocation.size,
+                    caller: format!("{:#x}", allocation.caller),
+                    symbol: None,
+                })
+                .collect(),
+        }
+    }
+}
+
+fn thread_options(thread: &Option<String>) -> String {
+    thread.as_ref().map(|t| format!("--thread {} --frame 0 ", t)).unwrap_or_default()
+}
+
+/// Integers of a GDB array value such as `{16, 4198710}`
+fn parse_array(value: &str) -> Vec<u64> {
+    value.trim()
+        .trim_start_matches('{')
+        .trim_end_matches('}')
+        .split(',')
+        .map_while(|v| parse_address(v.trim()))
+        .collect()
+}
+
+fn find_string(results: &[MiResult], name: &str) -> Option<String> {
+    results.iter()
+        .find(|r| r.variable == name)
+        .and_then(|r| match &r.value {
+            MiValue::String(s) => Some(s.clone()),
+            _ => None,
+        })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::gdb::parser::MiParser;
+
+    fn record(line: &str) -> MiOutputRecord {
+        MiParser::new().parse_line(line).unwrap().unwrap()
+    }
+
+    fn stop(tracer: &mut AllocTracer, line: &str) -> Option<String> {
+        match record(line) {
+            MiOutputRecord::Async { results, .. } => tracer.on_stop(&results),
+            other => panic!("not an async record: {:?}", other),
+        }
+    }
+
+    #[test]
+    fn test_trace_malloc_and_free() {
+        let mut tracer = AllocTracer::default();
+        let breakpoints = HashMap::from([("1".to_string(), AllocKind::Malloc), ("2".to_string(), AllocKind::Free)]);
+        tracer.start(&CONVENTIONS[0], breakpoints);
+
+        let entry = stop(&mut tracer, r#"*stopped,reason="breakpoint-hit",bkptno="1",thread-id="1""#).unwrap();
+        assert!(entry.starts_with(&format!("{}-data-evaluate-expression --thread 1 --frame 0 ", ENTRY_TOKEN)));
+        let finish = tracer.on_response(ENTRY_TOKEN, &record(r#"^done,value="{24, 4198710}""#)).unwrap();
+        assert_eq!(finish, format!("{}-exec-finish --thread 1 --frame 0\n", FINISH_TOKEN));
+        assert_eq!(tracer.on_response(FINISH_TOKEN, &record("^running")), None);
+        let result = stop(&mut tracer, r#"*stopped,reason="function-finished",thread-id="1""#).unwrap();
+        assert!(result.contains("$rax"));
+        assert_eq!(tracer.on_response(RETURN_TOKEN, &record(r#"^done,value="4215456""#)).as_deref(), Some("-exec-continue\n"));
+
+        let summary = tracer.summary(10);
+        assert_eq!((summary.stats.allocations, summary.live_allocations, summary.live_bytes), (1, 1, 24));
+        assert_eq!(summary.leak_candidates[0].ptr, "0x4052a0");
+        assert_eq!(summary.top_allocators[0].caller, "0x401136");
+
+        stop(&mut tracer, r#"*stopped,reason="breakpoint-hit",bkptno="2",thread-id="1""#).unwrap();
+        tracer.on_response(ENTRY_TOKEN, &record(r#"^done,value="{4215456, 4198750}""#)).unwrap();
+        let summary = tracer.summary(10);
+        assert_eq!((summary.stats.frees, summary.live_allocations, summary.stats.bytes_freed), (1, 0, 24));
+
+        // Another stop while finishing loses the call and is reported
+        stop(&mut tracer, r#"*stopped,reason="breakpoint-hit",bkptno="1",thread-id="1""#).unwrap();
+        tracer.on_response(ENTRY_TOKEN, &record(r#"^done,value="{8, 4198710}""#)).unwrap();
+        assert_eq!(stop(&mut tracer, r#"*stopped,reason="signal-received",signal-name="SIGSEGV",thread-id="1""#), None);
+        assert_eq!(tracer.summary(10).stats.lost, 1);
+        assert_eq!(tracer.events(10).len(), 2);
+    }
+}
@@ -0,0 +1,258 @@
+//! Auto-Resume Rules
+//!
+//! Stops the caller is not interested in, such as a SIGUSR1 the program
+//! handles itself or a shared library being loaded, are continued from the
+//! output reader thread as soon as GDB reports them. They never count as a
+//! stop for the waiting tool call and are kept in a log instead.
+
+use crate::gdb::parser::{parse_frame, parse_value};
+use crate::gdb::types::*;
+use crate::util::now_ms;
+use serde::{Deserialize, Serialize};
+use std::collections::VecDeque;
+
+/// Token of the condition evaluations sent by the reader thread, far above
+/// the tokens of ordinary commands
+pub const CONDITION_TOKEN: u64 = 1 << 62;
+
+/// Maximum number of auto-resumed stops kept in the log
+const MAX_LOG: usize = 1000;
+
+/// Stop reasons a `reason` rule may match. Breakpoints and signals have
+/// their own rules; stepping and exit stops are never resumed.
+pub const RESUMABLE_REASONS: &[&str] = &[
+    "solib-event",
+    "fork",
+    "vfork",
+    "exec",
+    "syscall-entry",
+    "syscall-return",
+    "watchpoint-trigger",
+    "read-watchpoint-trigger",
+    "access-watchpoint-trigger",
+];
+
+/// Which stops a rule applies to
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(tag = "kind", rename_all = "snake_case")]
+pub enum StopMatcher {
+    Signal { name: String },
+    Breakpoint {
+        number: String,
+        /// Resume only while this expression is true in the stopped frame
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        condition: Option<String>,
+    },
+    Reason { reason: String },
+    /// A breakpoint whose own commands end with `continue`; GDB resumes
+    /// these stops itself, so they are only logged
+    Commands { number: String },
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct AutoResumeRule {
+    pub id: u64,
+    #[serde(flatten)]
+    pub matcher: StopMatcher,
+    /// Stops resumed by this rule
+    pub hits: u64,
+}
+
+/// A stop that was continued without being reported
+#[derive(Debug, Clone, Serialize)]
+pub struct AutoResumedStop {
+    pub rule: u64,
+    pub reason: StopReason,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub signal: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub breakpoint: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub thread_id: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub frame: Option<Frame>,
+    /// Milliseconds since the Unix epoch
+    pub timestamp_ms: u64,
+}
+
+/// What to do with a `*stopped` record
+#[derive(Debug, PartialEq)]
+pub enum Verdict {
+    Report,
+    Resume(u64),
+    /// Resume if the condition of the rule holds
+    Evaluate(u64, String),
+    /// Already being resumed by GDB; log it without reporting it
+    Resumed(u64),
+}
+
+/// Rules and the log of resumed stops, shared with the output reader thread
+#[derive(Debug, Default)]
+pub struct AutoResume {
+    rules: Vec<AutoResumeRule>,
+    next_id: u64,
+    log: VecDeque<AutoResumedStop>,
+    /// Stops resumed since the rules were last cleared, including those
+    /// dropped from the log
+    resumed: u64,
+    /// Stop held back while its rule's condition is evaluated
+    deferred: Option<(u64, MiOutputRecord)>,
+}
+
+impl AutoResume {
+    pub fn add(&mut self, matcher: StopMatcher) -> u64 {
+        self.next_id += 1;
+        self.rules.push(AutoResumeRule { id: self.next_id, matcher, hits: 0 });
+        self.next_id
+    }
+
+    pub fn remove(&mut self, id: u64) -> bool {
+        let before = self.rules.len();
+        self.rules.retain(|r| r.id != id);
+        self.rules.len() != before
+    }
+
+    pub fn clear(&mut self) {
+        self.rules.clear();
+        self.log.clear();
+        self.resumed = 0;
+    }
+
+    pub fn rules(&self) -> &[AutoResumeRule] {
+        &self.rules
+    }
+
+    pub fn resumed(&self) -> u64 {
+        self.resumed
+    }
+
+    /// Resumed stops, oldest first; the log is emptied when `drain` is set
+    pub fn log(&mut self, drain: bool) -> Vec<AutoResumedStop> {
+        if drain {
+            self.log.drain(..).collect()
+        } else {
+            self.log.iter().cloned().collect()
+        }
+    }
+
+    /// Decide whether a `*stopped` record is resumed
+    pub fn check(&self, results: &[MiResult]) -> Verdict {
+        let find = |name: &str| find_string(results, name);
+        let reason = find("reason").unwrap_or_default();
+        let signal = find("signal-name");
+        let breakpoint = find("bkptno");
+        for rule in &self.rules {
+            match &rule.matcher {
+                StopMatcher::Signal { name } if reason == "signal-received" && signal.as_ref() == Some(name) => {
+                    return Verdict::Resume(rule.id);
+                }
+                StopMatcher::Breakpoint { number, condition } if reason == "breakpoint-hit" && breakpoint.as_ref() == Some(number) => {
+                    return match condition {
+                        Some(condition) => Verdict::Evaluate(rule.id, condition.clone()),
+                        None => Verdict::Resume(rule.id),
+                    };
+                }
+                StopMatcher::Reason { reason: matched } if *matched == reason => {
+                    return Verdict::Resume(rule.id);
+                }
+                StopMatcher::Commands { number } if reason == "breakpoint-hit" && breakpoint.as_ref() == Some(number) => {
+                    return Verdict::Resumed(rule.id);
+                }
+                _ => {}
+            }
+        }
+        Verdict::Report
+    }
+
+    /// Log a stop resumed by rule `id`
+    pub fn record(&mut self, id: u64, results: &[MiResult]) {
+        if let Some(rule) = self.rules.iter_mut().find(|r| r.id == id) {
+            rule.hits += 1;
+        }
+        self.resumed += 1;
+        self.log.push_back(AutoResumedStop {
+            rule: id,
+            reason: StopReason::from(find_string(results, "reason").unwrap_or_default()),
+            signal: find_string(results, "signal-name"),
+            breakpoint: find_string(results, "bkptno"),
+            thread_id: find_string(results, "thread-id"),
+            frame: parse_frame(results),
+            timestamp_ms: now_ms(),
+        });
+        if self.log.len() > MAX_LOG {
+            self.log.pop_front();
+        }
+    }
+
+    /// Hold back a stop until its condition has been evaluated
+    pub fn defer(&mut self, id: u64, record: MiOutputRecord) {
+        self.deferred = Some((id, record));
+    }
+
+    pub fn take_deferred(&mut self) -> Option<(u64, MiOutputRecord)> {
+        self.deferred.take()
+    }
+}
+
+/// Whether the response to a condition evaluation means the condition held.
+/// Errors and values that are not integers count as false, so the stop is
+/// reported.
+pub fn condition_holds(response: &MiOutputRecord) -> bool {
+    match response {
+        MiOutputRecord::Result { class: ResultClass::Done, results, .. } => find_string(results, "value")
+            .and_then(|v| parse_value(&v).integer)
+            .is_some_and(|n| n != 0),
+        _ => false,
+    }
+}
+
+fn find_string(results: &[MiResult], name: &str) -> Option<String> {
+    results.iter()
+        .find(|r| r.variable == name)
+        .and_then(|r| match &r.value {
+            MiValue::String(s) => Some(s.clone()),
+            _ => None,
+        })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::gdb::parser::MiParser;
+
+    fn stopped(line: &str) -> Vec<MiResult> {
+        match MiParser::new().parse_line(line).unwrap() {
+            Some(MiOutputRecord::Async { results, .. }) => results,
+            other => panic!("not an async record: {:?}", other),
+        }
+    }
+
+    #[test]
+    fn test_check_and_record() {
+        let mut rules = AutoResume::default();
+        let usr1 = rules.add(StopMatcher::Signal { name: "SIGUSR1".to_string() });
+        let bp = rules.add(StopMatcher::Breakpoint { number: "2".to_string(), condition: Some("n > 3".to_string()) });
+
+        let signal = stopped(r#"*stopped,reason="signal-received",signal-name="SIGUSR1",signal-meaning="User defined signal 1",thread-id="1""#);
+        assert_eq!(rules.check(&signal), Verdict::Resume(usr1));
+        let segv = stopped(r#"*stopped,reason="signal-received",signal-name="SIGSEGV",thread-id="1""#);
+        assert_eq!(rules.check(&segv), Verdict::Report);
+        let hit = stopped(r#"*stopped,reason="breakpoint-hit",disp="keep",bkptno="2",thread-id="1""#);
+        assert_eq!(rules.check(&hit), Verdict::Evaluate(bp, "n > 3".to_string()));
+
+        let override_rule = rules.add(StopMatcher::Commands { number: "3".to_string() });
+        let override_hit = stopped(r#"*stopped,reason="breakpoint-hit",disp="keep",bkptno="3",thread-id="1""#);
+        assert_eq!(rules.check(&override_hit), Verdict::Resumed(override_rule));
+
+        rules.record(usr1, &signal);
+        assert_eq!(rules.rules()[0].hits, 1);
+        assert_eq!(rules.log(true)[0].signal.as_deref(), Some("SIGUSR1"));
+        assert!(rules.log(false).is_empty());
+        assert_eq!(rules.resumed(), 1);
+
+        let done = MiParser::new().parse_line(r#"^done,value="1 '\001'""#).unwrap().unwrap();
+        assert!(condition_holds(&done));
+        let error = MiParser::new().parse_line(r#"^error,msg="No symbol \"n\" in current context.""#).unwrap().unwrap();
+        assert!(!condition_holds(&error));
+    }
+}
@@ -0,0 +1,112 @@
+//! Debugger Backends
+//!
+//! `GdbClient` drives any debugger that speaks GDB/MI. A backend describes
+//! how to launch a particular debugger and which GDB-specific features it
+//! provides, so the same client works with GDB and with LLDB through
+//! `lldb-mi` (the only option on macOS, where GDB cannot debug natively).
+//! With `lldb-mi` only the common MI subset is used: operations that need
+//! GDB's CLI, its Python API or GDB-only MI commands are checked against
+//! [`DebuggerBackend::supports`] and fail with a "not supported" error.
+
+use serde::{Deserialize, Serialize};
+
+/// Features that not every MI debugger provides
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum BackendFeature {
+    /// GDB CLI commands through `interpreter-exec console`
+    /// (skip, info symbol, info line, solib settings)
+    GdbCli,
+    /// `-catch-load` / `-catch-unload`
+    Catchpoints,
+    /// `-list-target-features`
+    TargetFeatures,
+    /// GDB's own MI extensions and settings: `-gdb-set` of GDB settings,
+    /// `-break-insert -g`, `-symbol-info-*` and `--skip-unavailable`
+    GdbMi,
+}
+
+/// A debugger that can be driven over GDB/MI
+pub trait DebuggerBackend: Send + Sync {
+    /// Short name used in messages and the `backend` tool parameter
+    fn name(&self) -> &'static str;
+    /// Executable used when no path is given
+    fn default_path(&self) -> &'static str;
+    /// Command-line arguments that select the MI interpreter
+    fn default_args(&self) -> Vec<String>;
+    /// MI commands (without the leading '-') sent after startup
+    fn init_commands(&self) -> &'static [&'static str];
+    /// Whether the debugger supports a feature
+    fn supports(&self, feature: BackendFeature) -> bool;
+}
+
+/// GDB (or gdb-multiarch)
+pub struct GdbBackend;
+
+impl DebuggerBackend for GdbBackend {
+    fn name(&self) -> &'static str {
+        "gdb"
+    }
+
+    fn default_path(&self) -> &'static str {
+        "gdb-multiarch"
+    }
+
+    fn default_args(&self) -> Vec<String> {
+        vec!["--interpreter=mi2".to_string()]
+    }
+
+    fn init_commands(&self) -> &'static [&'static str] {
+        &["gdb-set mi-async on", "gdb-set pagination off", "gdb-set confirm off"]
+    }
+
+    fn supports(&self, _feature: BackendFeature) -> bool {
+        true
+    }
+}
+
+/// LLDB through `lldb-mi`
+pub struct LldbBackend;
+
+impl DebuggerBackend for LldbBackend {
+    fn name(&self) -> &'static str {
+        "lldb"
+    }
+
+    fn default_path(&self) -> &'static str {
+        "lldb-mi"
+    }
+
+    fn default_args(&self) -> Vec<String> {
+        vec!["--interpreter".to_string()]
+    }
+
+    fn init_commands(&self) -> &'static [&'static str] {
+        // lldb-mi runs execution commands asynchronously and never pages
+        &[]
+    }
+
+    fn supports(&self, feature: BackendFeature) -> bool {
+        match feature {
+            BackendFeature::GdbCli | BackendFeature::Catchpoints | BackendFeature::TargetFeatures | BackendFeature::GdbMi => false,
+        }
+    }
+}
+
+/// Selectable debugger backend
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum BackendKind {
+    #[default]
+    Gdb,
+    Lldb,
+}
+
+impl BackendKind {
+    /// Get the backend implementation
+    pub fn backend(self) -> &'static dyn DebuggerBackend {
+        match self {
+            BackendKind::Gdb => &GdbBackend,
+            BackendKind::Lldb => &LldbBackend,
+        }
+    }
+}
@@ -0,0 +1,207 @@
+//! Basic Blocks
+//!
+//! A whole function's disassembly is mostly noise when the question is what
+//! the code around the PC does next. `gdb_basic_block` cuts the function at
+//! jump targets and after jumps and returns, and shows only the block
+//! holding the PC with where it can go next. Calls return to the following
+//! instruction, so they do not end a block.
+
+use crate::gdb::parser::{branch_kind, parse_address, parse_branch_target};
+use crate::gdb::types::{AsmInstruction, BranchKind};
+use serde::Serialize;
+use std::collections::HashSet;
+
+/// How control leaves a block
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Edge {
+    /// The block's jump is taken
+    Taken,
+    /// Execution continues with the next instruction
+    Fallthrough,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct Successor {
+    pub edge: Edge,
+    pub address: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub symbol: Option<String>,
+}
+
+/// The basic block containing an address
+#[derive(Debug, Clone, Serialize)]
+pub struct BasicBlock {
+    pub function: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub pc: Option<String>,
+    pub instructions: Vec<AsmInstruction>,
+    /// Where control goes after the block; empty after a return or an
+    /// indirect jump
+    pub successors: Vec<Successor>,
+    /// Jumps in the function that lead to the start of the block
+    pub entered_from: Vec<String>,
+    /// The block ends in a return
+    pub returns: bool,
+}
+
+/// Target of a direct branch, whether or not GDB annotated it with a symbol
+pub fn branch_destination(inst: &str) -> Option<u64> {
+    let plain = inst.split('<').next().unwrap_or(inst).trim_end();
+    parse_branch_target(plain)
+}
+
+/// Jumps that are always taken (no fallthrough)
+fn unconditional(inst: &str) -> bool {
+    let inst = inst.trim().to_ascii_lowercase();
+    let mut words = inst.split_whitespace();
+    let mnemonic = words.next().unwrap_or_default();
+    let first_operand = words.next().unwrap_or_default().split(',').next().unwrap_or_default();
+    let base = mnemonic.trim_end_matches(".w").trim_end_matches(".n");
+    matches!(base, "jmp" | "jmpq" | "ljmp" | "b" | "bx" | "br" | "j" | "c.j" | "jr" | "c.jr")
+        || (matches!(base, "jal" | "jalr") && matches!(first_operand, "zero" | "x0"))
+        || (matches!(base, "mov" | "ldr") && first_operand == "pc")
+}
+
+/// The block of `instructions` (one function, in address order) holding
+/// `address`, as a range of indexes
+pub fn block_range(instructions: &[AsmInstruction], address: u64) -> Option<(usize, usize)> {
+    let addresses: Vec<u64> = instructions.iter().map(|i| parse_address(&i.address).unwrap_or(0)).collect();
+    let current = addresses.iter().rposition(|&a| a <= address)?;
+
+    let targets: HashSet<u64> = instructions.iter()
+        .filter(|i| branch_kind(&i.inst) == Some(BranchKind::Jump))
+        .filter_map(|i| branch_destination(&i.inst))
+        .collect();
+    let ends_block = |i: usize| matches!(branch_kind(&instructions[i].inst), Some(BranchKind::Jump | BranchKind::Return));
+    let leader = |i: usize| i == 0 || targets.contains(&addresses[i]) || ends_block(i - 1);
+
+    let start = (0..=current).rev().find(|&i| leader(i))?;
+    let end = (current..instructions.len())
+        .find(|&i| ends_block(i) || (i + 1 < instructions.len() && leader(i + 1)))
+        .unwrap_or(instructions.len() - 1);
+    Some((start, end))
+}
+
+/// Cut the block holding `address` out of a function's disassembly
+pub fn basic_block(function: String, instructions: &[AsmInstruction], address: u64, pc: Option<u64>) -> Option<BasicBlock> {
+    let (start, end) = block_range(instructions, address)?;
+    let block = instructions[start..=end].to_vec();
+    let last = block.last()?;
+    let kind = branch_kind(&last.inst);
+    let next = instructions.get(end + 1);
+
+    let mut successors = Vec::new();
+    if kind == Some(BranchKind::Jump) {
+        if let Some(target) = branch_destination(&last.inst) {
+            let symbol = last.target_symbol.clone().or_else(|| annotation(&last.inst));
+            successors.push(Successor { edge: Edge::Taken, address: format!("{:#x}", target), symbol });
+        }
+    }
+    if kind != Some(BranchKind::Return) && !(kind == Some(BranchKind::Jump) && unconditional(&last.inst)) {
+        if let Some(next) = next {
+            let symbol = next.func_name.as_ref().map(|f| format!("{}+{}", f, next.offset.unwrap_or(0)));
+            successors.push(Successor { edge: Edge::Fallthrough, address: next.address.clone(), symbol });
+        }
+    }
+
+    let start_address = parse_address(&block[0].address);
+    let entered_from = instructions.iter()
+        .filter(|i| branch_kind(&i.inst) == Some(BranchKind::Jump) && branch_destination(&i.inst) == start_address)
+        .map(|i| i.address.clone())
+        .collect();
+
+    Some(BasicBlock {
+        function,
+        pc: pc.map(|pc| format!("{:#x}", pc)),
+        returns: kind == Some(BranchKind::Return),
+        instructions: block,
+        successors,
+        entered_from,
+    })
+}
+
+/// Symbol GDB printed after a branch target (`<main+16>`)
+fn annotation(inst: &str) -> Option<String> {
+    let (_, rest) = inst.split_once('<')?;
+    rest.split_once('>').map(|(symbol, _)| symbol.to_string())
+}
+
+impl BasicBlock {
+    /// Listing in the style of `disassemble`, the PC marked with `=>`
+    pub fn render(&self) -> String {
+        let pc = self.pc.as_deref().and_then(parse_address);
+        let mut lines = vec![format!("Basic block in {}:", self.function)];
+        if !self.entered_from.is_empty() {
+            lines.push(format!("   ; entered from {}", self.entered_from.join(", ")));
+        }
+        for insn in &self.instructions {
+            let marker = if pc.is_some() && parse_address(&insn.address) == pc { "=> " } else { "   " };
+            let offset = insn.offset.map(|o| format!(" <+{}>", o)).unwrap_or_default();
+            let target = insn.target_symbol.as_ref().map(|s| format!(" <{}>", s)).unwrap_or_default();
+            lines.push(format!("{}{}{}:\t{}{}", marker, insn.address, offset, insn.inst, target));
+        }
+        if self.returns {
+            lines.push("   ; returns".to_string());
+        } else if self.successors.is_empty() {
+            lines.push("   ; indirect jump".to_string());
+        }
+        for successor in &self.successors {
+            let edge = match successor.edge {
+                Edge::Taken => "taken",
+                Edge::Fallthrough => "fallthrough",
+            };
+            let symbol = successor.symbol.as_ref().map(|s| format!(" <{}>", s)).unwrap_or_default();
+            lines.push(format!("   ; {} -> {}{}", edge, successor.address, symbol));
+        }
+        lines.join("\n")
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn insn(address: u64, offset: u64, inst: &str) -> AsmInstruction {
+        AsmInstruction {
+            address: format!("{:#x}", address),
+            func_name: Some("main".to_string()),
+            offset: Some(offset),
+            inst: inst.to_string(),
+            target_symbol: None,
+        }
+    }
+
+    #[test]
+    fn test_basic_block() {
+        let function = vec![
+            insn(0x1000, 0, "push   %rbp"),
+            insn(0x1001, 1, "cmp    $0x3,%edi"),
+            insn(0x1004, 4, "jle    0x100d <main+13>"),
+            insn(0x1006, 6, "call   0x2000 <puts>"),
+            insn(0x100b, 11, "jmp    0x1010 <main+16>"),
+            insn(0x100d, 13, "mov    $0x1,%eax"),
+            insn(0x1010, 16, "pop    %rbp"),
+            insn(0x1011, 17, "ret"),
+        ];
+        assert_eq!(block_range(&function, 0x1001), Some((0, 2)));
+        assert_eq!(block_range(&function, 0x1006), Some((3, 4)));
+        // 0x100d is a jump target, 0x1010 another
+        assert_eq!(block_range(&function, 0x100d), Some((5, 5)));
+        assert_eq!(block_range(&function, 0x1011), Some((6, 7)));
+
+        let block = basic_block("main".to_string(), &function, 0x1001, Some(0x1001)).unwrap();
+        assert_eq!(block.successors, vec![
+            Successor { edge: Edge::Taken, address: "0x100d".to_string(), symbol: Some("main+13".to_string()) },
+            Successor { edge: Edge::Fallthrough, address: "0x1006".to_string(), symbol: Some("main+6".to_string()) },
+        ]);
+        assert!(block.render().contains("=> 0x1001 <+1>:\tcmp    $0x3,%edi"));
+
+        let block = basic_block("main".to_string(), &function, 0x1006, None).unwrap();
+        assert_eq!(block.successors.len(), 1);
+        let block = basic_block("main".to_string(), &function, 0x1010, None).unwrap();
+        assert!(block.returns && block.successors.is_empty());
+        assert_eq!(block.entered_from, vec!["0x100b".to_string()]);
+        assert!(block.render().ends_with("; returns"));
+    }
+}
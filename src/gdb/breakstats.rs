@@ -0,0 +1,140 @@
+//! Conditional Breakpoint Statistics
+//!
+//! GDB's hit count (`times`) only grows when a breakpoint's condition is
+//! true, so a condition that is evaluated a million times and stops twice
+//! looks harmless while every evaluation traps into the debugger. Conditions
+//! given to `break_insert` with `count_evaluations` are wrapped so that each
+//! evaluation also bumps a convenience variable:
+//!
+//! ```text
+//! ($mcp_cond_evals_3 = $mcp_cond_evals_3 + 1, (n > 1000))
+//! ```
+//!
+//! The wrapper is removed again when breakpoints are parsed, so listings and
+//! exported scripts show the condition as it was given.
+
+use crate::gdb::types::Breakpoint;
+use serde::Serialize;
+
+/// Prefix of the convenience variables counting condition evaluations
+pub const COUNTER_PREFIX: &str = "$mcp_cond_evals_";
+
+/// Evaluations before a breakpoint can be reported as hot
+const HOT_MIN_EVALUATIONS: u64 = 1000;
+
+/// Evaluations per stop above which a breakpoint is reported as hot
+const HOT_EVALUATIONS_PER_STOP: u64 = 100;
+
+/// Name of the counter variable with the given id
+pub fn counter_variable(id: u64) -> String {
+    format!("{}{}", COUNTER_PREFIX, id)
+}
+
+/// Wrap a condition so that evaluating it increments `counter`
+pub fn counting_condition(counter: &str, condition: &str) -> String {
+    format!("({counter} = {counter} + 1, ({condition}))")
+}
+
+/// Split a wrapped condition into its counter and the original condition
+pub fn split_counting_condition(condition: &str) -> Option<(String, String)> {
+    let rest = condition.strip_prefix('(')?.strip_suffix("))")?;
+    let (counter, rest) = rest.split_once(" = ")?;
+    if !counter.starts_with(COUNTER_PREFIX) {
+        return None;
+    }
+    let original = rest.strip_prefix(&format!("{} + 1, (", counter))?;
+    Some((counter.to_string(), original.to_string()))
+}
+
+/// Move the counter of a wrapped condition into `evaluation_counter`
+pub fn unwrap_condition(bp: &mut Breakpoint) {
+    if let Some((counter, original)) = bp.condition.as_deref().and_then(split_counting_condition) {
+        bp.condition = Some(original);
+        bp.evaluation_counter = Some(counter);
+    }
+}
+
+/// Evaluation and stop counts of one conditional breakpoint
+#[derive(Debug, Clone, Serialize)]
+pub struct BreakStats {
+    pub number: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub location: Option<String>,
+    pub condition: String,
+    /// Times the condition was evaluated (None if it is not counted, e.g.
+    /// for a breakpoint set without `count_evaluations` or outside the server)
+    pub evaluations: Option<u64>,
+    /// Times the condition was true and the program stopped (GDB's hit count)
+    pub stops: u64,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub evaluations_per_stop: Option<f64>,
+    pub hot: bool,
+}
+
+impl BreakStats {
+    pub fn new(bp: &Breakpoint, evaluations: Option<u64>) -> Self {
+        let location = match (&bp.file, bp.line) {
+            (Some(file), Some(line)) => Some(format!("{}:{}", file, line)),
+            _ => bp.original_location.clone().or_else(|| bp.func.clone()).or_else(|| bp.addr.clone()),
+        };
+        let hot = evaluations.is_some_and(|evaluations| {
+            evaluations >= HOT_MIN_EVALUATIONS && evaluations >= HOT_EVALUATIONS_PER_STOP * bp.times.max(1)
+        });
+        Self {
+            number: bp.number.clone(),
+            location,
+            condition: bp.condition.clone().unwrap_or_default(),
+            evaluations,
+            stops: bp.times,
+            evaluations_per_stop: evaluations.filter(|_| bp.times > 0).map(|e| e as f64 / bp.times as f64),
+            hot,
+        }
+    }
+
+    pub fn warning(&self) -> Option<String> {
+        if !self.hot {
+            return None;
+        }
+        Some(format!(
+            "Breakpoint {}{} evaluated its condition '{}' {} times but stopped {} time(s). Every evaluation traps into GDB, which can make the program orders of magnitude slower; consider moving the breakpoint, a watchpoint, or `set breakpoint condition-evaluation target` on a remote stub that supports it.",
+            self.number,
+            self.location.as_deref().map(|l| format!(" at {}", l)).unwrap_or_default(),
+            self.condition,
+            self.evaluations.unwrap_or(0),
+            self.stops
+        ))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_counting_condition() {
+        let counter = counter_variable(3);
+        let wrapped = counting_condition(&counter, "(n > 1) && p != 0");
+        assert_eq!(wrapped, "($mcp_cond_evals_3 = $mcp_cond_evals_3 + 1, ((n > 1) && p != 0))");
+        assert_eq!(split_counting_condition(&wrapped), Some((counter.clone(), "(n > 1) && p != 0".to_string())));
+        assert_eq!(split_counting_condition("(n > 1)"), None);
+
+        let mut bp = Breakpoint {
+            number: "2".to_string(),
+            file: Some("loop.c".to_string()),
+            line: Some(12),
+            times: 2,
+            condition: Some(wrapped),
+            ..Default::default()
+        };
+        unwrap_condition(&mut bp);
+        assert_eq!(bp.condition.as_deref(), Some("(n > 1) && p != 0"));
+        assert_eq!(bp.evaluation_counter.as_deref(), Some("$mcp_cond_evals_3"));
+
+        let stats = BreakStats::new(&bp, Some(52_000));
+        assert!(stats.hot);
+        assert_eq!(stats.evaluations_per_stop, Some(26_000.0));
+        assert!(stats.warning().unwrap().contains("Breakpoint 2 at loop.c:12"));
+        assert!(!BreakStats::new(&bp, Some(150)).hot);
+        assert!(!BreakStats::new(&bp, None).hot);
+    }
+}
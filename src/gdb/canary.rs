@@ -0,0 +1,187 @@
+//! Stack Protector Checks
+//!
+//! Code built with `-fstack-protector` copies a guard value into each
+//! protected frame on entry and calls `__stack_chk_fail` if it changed on
+//! return. Finding which bytes replaced the canary usually identifies the
+//! overflowing buffer, so the slot is located from the function prologue
+//! (the store right after the guard is loaded) and compared with the guard.
+
+use crate::gdb::parser::parse_address;
+use crate::gdb::types::AsmInstruction;
+use regex::Regex;
+use serde::Serialize;
+use std::sync::OnceLock;
+
+/// Functions that report a smashed stack; the checked frame is their caller
+pub const STACK_CHK_FAIL: &[&str] = &["__stack_chk_fail", "__stack_chk_fail_local"];
+
+/// Where the guard value lives, tried in order: the TLS slot of x86-64 and
+/// i386 glibc, then the global used by other targets
+pub const GUARD_SOURCES: &[(&str, &str)] = &[
+    ("%fs:0x28", "*(unsigned long long *)((unsigned long long)$fs_base + 0x28)"),
+    ("%gs:0x14", "*(unsigned int *)((unsigned long long)$gs_base + 0x14)"),
+    ("__stack_chk_guard", "(unsigned long long)__stack_chk_guard"),
+];
+
+/// Instructions after the guard load searched for the canary store
+const STORE_WINDOW: usize = 6;
+
+/// The stack slot the prologue stores the canary in, as `base + offset`
+#[derive(Debug, Clone, PartialEq)]
+pub struct CanaryStore {
+    pub base: String,
+    pub offset: i64,
+    /// Address of the storing instruction
+    pub address: String,
+}
+
+impl CanaryStore {
+    /// GDB expression for the slot address
+    pub fn expression(&self) -> String {
+        let sign = if self.offset < 0 { '-' } else { '+' };
+        format!("(unsigned long long)${} {} {}", self.base, sign, self.offset.unsigned_abs())
+    }
+}
+
+/// A canary byte that differs from the guard
+#[derive(Debug, Clone, Serialize)]
+pub struct ByteChange {
+    pub offset: usize,
+    pub expected: String,
+    pub actual: String,
+}
+
+/// Result of `gdb_stack_guard_check`
+#[derive(Debug, Clone, Serialize)]
+pub struct StackGuardCheck {
+    pub level: u64,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub function: Option<String>,
+    /// "intact", "corrupted" or "not_found"
+    pub status: &'static str,
+    pub guard_source: &'static str,
+    pub guard: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub slot: Option<String>,
+    /// How the slot was found: "prologue" or "scan"
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub slot_source: Option<&'static str>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub canary: Option<String>,
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub overwritten: Vec<ByteChange>,
+    /// The canary bytes as ASCII, which often shows the overflowing data
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub canary_ascii: Option<String>,
+    /// Bytes just below the slot, where the overflowing buffer usually ends
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub preceding: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub preceding_ascii: Option<String>,
+}
+
+fn is_guard_load(insn: &AsmInstruction) -> bool {
+    let text = insn.inst.as_str();
+    ["fs:0x28", "gs:0x14", "__stack_chk_guard"].iter().any(|marker| text.contains(marker))
+        || insn.target_symbol.as_deref().is_some_and(|s| s.starts_with("__stack_chk_guard"))
+}
+
+/// Destination of a store of a register to `base + offset`, in AT&T, Intel
+/// or Arm syntax
+fn parse_store(inst: &str) -> Option<(String, i64)> {
+    static STORES: OnceLock<[Regex; 3]> = OnceLock::new();
+    let [att, intel, arm] = STORES.get_or_init(|| {
+        [
+            Regex::new(r"^mov[lq]?\s+%\w+,(?P<off>-?(?:0x[0-9a-f]+|\d+))?\(%(?P<base>\w+)\)").unwrap(),
+            Regex::new(r"^mov\s+(?:[DQ]WORD PTR )?\[(?P<base>\w+)(?P<off>[+-](?:0x[0-9a-f]+|\d+))?\],\w+").unwrap(),
+            Regex::new(r"^stu?r\s+\w+,\s*\[(?P<base>\w+)(?:,\s*#(?P<off>-?(?:0x[0-9a-f]+|\d+)))?\]").unwrap(),
+        ]
+    });
+    let caps = [att, intel, arm].iter().find_map(|re| re.captures(inst.trim()))?;
+    let offset = match caps.name("off").map(|m| m.as_str()) {
+        None => 0,
+        Some(off) => {
+            let (negative, magnitude) = match off.strip_prefix('-') {
+                Some(rest) => (true, rest),
+                None => (false, off.trim_start_matches('+')),
+            };
+            let value = parse_address(magnitude)? as i64;
+            if negative { -value } else { value }
+        }
+    };
+    Some((caps["base"].to_string(), offset))
+}
+
+/// Find the canary store that follows the guard load in a prologue
+pub fn find_canary_store(instructions: &[AsmInstruction]) -> Option<CanaryStore> {
+    let load = instructions.iter().position(is_guard_load)?;
+    instructions[load + 1..].iter()
+        .take(STORE_WINDOW)
+        .find_map(|insn| {
+            let (base, offset) = parse_store(&insn.inst)?;
+            Some(CanaryStore { base, offset, address: insn.address.clone() })
+        })
+}
+
+/// The frame address (CFA) from `info frame`: "Stack level 1, frame at 0x7ffe3a10:"
+pub fn parse_frame_address(info_frame: &str) -> Option<u64> {
+    let rest = info_frame.split("frame at ").nth(1)?;
+    parse_address(rest.split(|c: char| c == ':' || c.is_whitespace()).next()?)
+}
+
+/// Compare canary bytes read from the slot with the guard bytes
+pub fn changed_bytes(expected: &[u8], actual: &[u8]) -> Vec<ByteChange> {
+    expected.iter()
+        .zip(actual)
+        .enumerate()
+        .filter(|(_, (e, a))| e != a)
+        .map(|(offset, (e, a))| ByteChange { offset, expected: format!("{:02x}", e), actual: format!("{:02x}", a) })
+        .collect()
+}
+
+/// Printable bytes as ASCII, others as '.'
+pub fn ascii(bytes: &[u8]) -> String {
+    bytes.iter().map(|&b| if b.is_ascii_graphic() || b == b' ' { b as char } else { '.' }).collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn insns(lines: &[&str]) -> Vec<AsmInstruction> {
+        lines.iter()
+            .enumerate()
+            .map(|(i, inst)| AsmInstruction {
+                address: format!("{:#x}", 0x401000 + i * 4),
+                func_name: None,
+                offset: None,
+                inst: inst.to_string(),
+                target_symbol: None,
+            })
+            .collect()
+    }
+
+    #[test]
+    fn test_find_canary_store() {
+        let att = insns(&["push   %rbp", "mov    %rsp,%rbp", "sub    $0x30,%rsp", "mov    %fs:0x28,%rax", "mov    %rax,-0x8(%rbp)", "xor    %eax,%eax"]);
+        let store = find_canary_store(&att).unwrap();
+        assert_eq!((store.base.as_str(), store.offset, store.address.as_str()), ("rbp", -8, "0x401010"));
+        assert_eq!(store.expression(), "(unsigned long long)$rbp - 8");
+
+        let intel = insns(&["mov    rax,QWORD PTR fs:0x28", "mov    QWORD PTR [rsp+0x18],rax"]);
+        assert_eq!(find_canary_store(&intel).map(|s| (s.base, s.offset)), Some(("rsp".to_string(), 0x18)));
+
+        let arm = insns(&["ldr x0, [x0, #4040]  // <__stack_chk_guard>", "ldr x1, [x0]", "str x1, [sp, #40]"]);
+        assert_eq!(find_canary_store(&arm).map(|s| (s.base, s.offset)), Some(("sp".to_string(), 40)));
+
+        assert!(find_canary_store(&insns(&["push   %rbp", "mov    %rax,-0x8(%rbp)"])).is_none());
+    }
+
+    #[test]
+    fn test_changed_bytes() {
+        assert_eq!(parse_frame_address("Stack level 1, frame at 0x7fffffffe0d0:\n rip = 0x401156"), Some(0x7fffffffe0d0));
+        let changes = changed_bytes(&[0x00, 0x1f, 0x9c, 0x4e], &[0x00, 0x41, 0x41, 0x4e]);
+        assert_eq!(changes.iter().map(|c| c.offset).collect::<Vec<_>>(), [1, 2]);
+        assert_eq!(ascii(&[0x00, 0x41, 0x41, 0x4e]), ".AAN");
+    }
+}
@@ -2,44 +2,152 @@
 //!
 //! Manages GDB process lifecycle and communication via Machine Interface (MI).
 
+use crate::gdb::alloctrace::{AllocKind, AllocSummary, AllocTracer, CallingConvention, TracedFunction, CONVENTIONS, DEFAULT_FUNCTIONS, ENTRY_TOKEN, FINISH_TOKEN, RETURN_TOKEN};
+use crate::gdb::autoresume::{condition_holds, AutoResume, Verdict, CONDITION_TOKEN};
+use crate::gdb::backend::BackendFeature;
+use crate::gdb::basicblock::{basic_block, BasicBlock};
+use crate::gdb::breakstats::{counter_variable, counting_condition, BreakStats};
+use crate::gdb::convenience::{parse_show_convenience, unquote, ConvenienceSnapshot, ConvenienceValue, STATUS_SETTINGS};
+use crate::gdb::ctxswitch::{switch_counts, trim_string_padding, ContextSwitch, ContextSwitchTrace, SwitchTraceSpec, TaskRef};
+use crate::gdb::canary::{ascii, changed_bytes, find_canary_store, parse_frame_address, StackGuardCheck, GUARD_SOURCES, STACK_CHK_FAIL};
+use crate::gdb::managed::{KillSwitch, ManagedProcesses};
+use crate::gdb::mitrace::{MiExchange, MiTrace};
+use crate::gdb::optimized::mark_inlined_frames;
+use crate::gdb::patch::{assemble, padding, CodePatch, PatchArch, PatchCode};
+use crate::gdb::pcsample::PcSample;
+use crate::gdb::qemu::{monitor_error, parse_snapshots, Qmp, SnapshotMark, VmSnapshot};
+use crate::gdb::registers::{truncate, DecodedRegister, RegisterSource, RegisterSpec};
 use crate::gdb::parser::{
-    parse_breakpoint, parse_breakpoint_list, parse_frame, parse_memory_content,
-    parse_register_names, parse_register_values, parse_stack_frames, parse_thread_ids,
-    parse_variable, parse_variable_children, parse_watchpoint, MiParser,
+    branch_kind, count_calls, parse_address, parse_asm_instructions, parse_branch_target, parse_breakpoint, parse_breakpoint_list, parse_call_history, parse_frame, parse_function_symbols, parse_gdb_version,
+    parse_hex_bytes, parse_info_functions, parse_memory_content,
+    parse_register_names, parse_register_values, parse_value, parse_source_files, parse_source_line_blocks, parse_symbol_lines, parse_stack_frames, parse_stack_variables, parse_struct_fields, parse_thread_ids, parse_thread_info,
+    parse_var_changes, parse_variable, parse_variable_children, parse_watchpoint, variable_symbols, MiParser,
 };
+use crate::gdb::script::build_script;
+use crate::gdb::stackscan::{code_range, decode_words, parse_info_files, parse_proc_mappings, StackCandidate, StackScan};
+use crate::gdb::diagnostics::{DiagnosticSource, Diagnostics, Severity};
+use crate::gdb::endian::{elf_endian, parse_show_endian, Endian, EndianCheck};
+use crate::gdb::evalhistory::{EvalHistory, EvalQuery, Evaluation, EvaluationView};
+use crate::gdb::errno::{errno_info, is_libc};
+use crate::gdb::fpunwind::{walk as walk_frames, FallbackUnwind, FrameLayout, HeuristicFrame};
+use crate::gdb::helpers::{parse_helper_output, HelperDir};
+use crate::gdb::hooks::HookPoint;
+use crate::gdb::inferior::{parse_show_args, with_stderr_redirect, InferiorTty, ProgramExit, ProgramOutput};
+use crate::gdb::smp::{cpu_index, CoreRegister, CoreState, SmpState};
+use crate::gdb::signals::{code_meaning, signal_name, FAULT_SIGNALS};
+use crate::gdb::softwatch::{is_hardware_limit, mapping_protection, page_span, signal_handling, RegionWatch, RegionWrite, WatchMode, DEFAULT_PAGE_SIZE, PROT_READ, PROT_WRITE};
+use crate::gdb::sourceverify::{line_table_md5s, mtime_secs, verify_source, SourceVerification};
+use crate::gdb::symbols::{read_elf, reports_no_debug_symbols, SymbolCheck};
+use crate::gdb::timeline::Timeline;
+use crate::gdb::tracker::{stop_event, SessionTracker};
 use crate::gdb::types::*;
 use crate::gdb::types::WatchpointType;
+use crate::gdb::vectors::{decode_vectors, group_by_handler, vector_selected, HandlerBreakpoint, VectorBreakpoints, VTOR};
+use crate::gdb::x86::{decode_gdt, decode_idt, parse_descriptor_register, parse_xp_output, walk, DescriptorTable, DescriptorTableDump, PageWalk, PagingMode};
+use crate::util::to_hex;
 use anyhow::{anyhow, Result};
-use std::collections::HashMap;
+use std::collections::{HashMap, HashSet};
 use std::io::{BufRead, BufReader, Write};
+use std::path::Path;
 use std::process::{Child, ChildStdin, ChildStdout, Command, Stdio};
 use std::sync::atomic::{AtomicU64, Ordering};
 use std::sync::mpsc::{self, Receiver, Sender};
-use std::sync::{Arc, Mutex};
+use std::sync::{Arc, Mutex, MutexGuard};
 use std::thread::{self, JoinHandle};
-use std::time::Duration;
+use std::time::{Duration, Instant};
 use tracing::{debug, error, info, warn};
 
+/// Bookkeeping used to resynchronize with GDB after a command timed out
+#[derive(Debug, Default)]
+struct MiSync {
+    /// Tokens of timed-out commands whose responses have not arrived yet
+    stale_tokens: HashSet<u64>,
+    /// Number of `(gdb)` prompts seen
+    prompts: u64,
+}
+
 /// GDB Client for managing debugging sessions
 pub struct GdbClient {
     /// GDB process
     process: Option<Child>,
-    /// Standard input to GDB
-    stdin: Option<ChildStdin>,
+    /// Standard input to GDB, shared with the [`Interrupter`]
+    stdin: Option<Arc<Mutex<ChildStdin>>>,
     /// Token counter for MI commands
     token_counter: AtomicU64,
     /// Configuration
     config: GdbConfig,
     /// Pending responses by token
     pending_responses: Arc<Mutex<HashMap<u64, Sender<MiOutputRecord>>>>,
-    /// Event receiver
-    event_rx: Option<Receiver<GdbEvent>>,
+    /// Event receiver, handed out by [`GdbClient::event_receiver`]
+    #[allow(dead_code)] // no tool consumes the event stream yet
+    event_rx: Mutex<Option<Receiver<GdbEvent>>>,
     /// Event sender (cloned for background thread)
     event_tx: Sender<GdbEvent>,
     /// Output reader thread handle
     reader_handle: Option<JoinHandle<()>>,
     /// Session state
     state: Arc<Mutex<GdbSessionState>>,
+    /// Bookkeeping derived from async records
+    tracker: Arc<Mutex<SessionTracker>>,
+    /// CLI-equivalent commands executed during the session
+    history: Vec<String>,
+    /// Expressions evaluated through `gdb_evaluate`, with their values
+    eval_history: EvalHistory,
+    /// Named memory snapshots
+    snapshots: HashMap<String, MemorySnapshot>,
+    /// Applied code patches, oldest first, with their original bytes
+    patches: Vec<CodePatch>,
+    next_patch_id: u64,
+    /// VM snapshots saved through the QEMU monitor in this session
+    vm_snapshots: Vec<SnapshotMark>,
+    /// Variable objects kept by `var_watch`, in creation order
+    watches: Vec<VarWatch>,
+    /// Helper processes started for this session
+    managed: ManagedProcesses,
+    /// Console stream output captured for `console_command`; `None` while
+    /// no command is capturing, so output between commands is not kept
+    console_output: Arc<Mutex<Option<String>>>,
+    /// Timed-out command tracking
+    sync: Arc<Mutex<MiSync>>,
+    /// Rules for stops continued by the reader thread
+    auto_resume: Arc<Mutex<AutoResume>>,
+    /// Allocation trace driven by the reader thread
+    alloc_tracer: Arc<Mutex<AllocTracer>>,
+    /// Events of the session in order, served as `gdb://events`
+    timeline: Arc<Mutex<Timeline>>,
+    /// Extracted Python helpers, removed with the session
+    helper_dir: Option<HelperDir>,
+    /// Id of the next condition evaluation counter
+    next_condition_counter: u64,
+    /// Warnings from stderr and the MI log stream
+    diagnostics: Arc<Mutex<Diagnostics>>,
+    /// Terminals capturing a native program's output (opened on first run)
+    inferior_tty: Option<InferiorTty>,
+    /// MI commands sent during verbose tool calls
+    mi_trace: Arc<Mutex<MiTrace>>,
+}
+
+/// Sends events from the reader threads to the event channel, recording
+/// them in the timeline on the way
+#[derive(Clone)]
+struct EventSink {
+    tx: Sender<GdbEvent>,
+    timeline: Arc<Mutex<Timeline>>,
+    diagnostics: Arc<Mutex<Diagnostics>>,
+}
+
+impl EventSink {
+    /// Returns whether the event channel is still open
+    fn send(&self, event: GdbEvent) -> bool {
+        self.timeline.lock().unwrap().push((&event).into());
+        self.tx.send(event).is_ok()
+    }
+
+    /// Send a stderr or log stream line, keeping it as a diagnostic
+    fn send_log(&self, source: DiagnosticSource, content: String) -> bool {
+        self.diagnostics.lock().unwrap().push(source, &content);
+        self.send(GdbEvent::Output { channel: OutputChannel::Log, content })
+    }
 }
 
 impl GdbClient {
@@ -52,10 +160,29 @@ impl GdbClient {
             token_counter: AtomicU64::new(1),
             config,
             pending_responses: Arc::new(Mutex::new(HashMap::new())),
-            event_rx: Some(event_rx),
+            event_rx: Mutex::new(Some(event_rx)),
             event_tx,
             reader_handle: None,
             state: Arc::new(Mutex::new(GdbSessionState::default())),
+            tracker: Arc::new(Mutex::new(SessionTracker::default())),
+            history: Vec::new(),
+            eval_history: EvalHistory::default(),
+            snapshots: HashMap::new(),
+            patches: Vec::new(),
+            next_patch_id: 1,
+            vm_snapshots: Vec::new(),
+            watches: Vec::new(),
+            managed: ManagedProcesses::default(),
+            console_output: Arc::new(Mutex::new(None)),
+            sync: Arc::new(Mutex::new(MiSync::default())),
+            auto_resume: Arc::new(Mutex::new(AutoResume::default())),
+            alloc_tracer: Arc::new(Mutex::new(AllocTracer::default())),
+            helper_dir: None,
+            next_condition_counter: 1,
+            diagnostics: Arc::new(Mutex::new(Diagnostics::default())),
+            inferior_tty: None,
+            mi_trace: Arc::new(Mutex::new(MiTrace::default())),
+            timeline: Arc::new(Mutex::new(Timeline::default())),
         }
     }
 
@@ -68,81 +195,218 @@ impl GdbClient {
         info!("Starting GDB: {}", self.config.gdb_path);
 
         let mut cmd = Command::new(&self.config.gdb_path);
-        cmd.args(&self.config.gdb_args)
+        cmd.args(&self.config.gdb_args);
+        if let Some(dir) = &self.config.data_directory {
+            cmd.arg(format!("--data-directory={}", dir));
+        }
+        cmd
+            .envs(&self.config.env)
             .stdin(Stdio::piped())
             .stdout(Stdio::piped())
             .stderr(Stdio::piped());
 
-        let mut process = cmd.spawn()?;
+        if let Some(dir) = &self.config.working_dir {
+            cmd.current_dir(dir);
+        }
+
+        let mut process = cmd.spawn()
+            .map_err(|e| anyhow!("Failed to spawn {}: {}", self.config.gdb_path, e))?;
         
         let stdin = process.stdin.take().ok_or_else(|| anyhow!("Failed to get stdin"))?;
         let stdout = process.stdout.take().ok_or_else(|| anyhow!("Failed to get stdout"))?;
         let stderr = process.stderr.take().ok_or_else(|| anyhow!("Failed to get stderr"))?;
 
-        self.stdin = Some(stdin);
+        let stdin = Arc::new(Mutex::new(stdin));
+        self.stdin = Some(Arc::clone(&stdin));
         self.process = Some(process);
 
         // Start output reader thread
         let pending = Arc::clone(&self.pending_responses);
-        let event_tx = self.event_tx.clone();
+        let event_tx = self.event_sink();
         let state = Arc::clone(&self.state);
+        let tracker = Arc::clone(&self.tracker);
+        let console_output = Arc::clone(&self.console_output);
+        let sync = Arc::clone(&self.sync);
+        let auto_resume = Arc::clone(&self.auto_resume);
+        let alloc_tracer = Arc::clone(&self.alloc_tracer);
         
         let stdout_reader = BufReader::new(stdout);
         let reader_handle = thread::spawn(move || {
-            Self::read_output_loop(stdout_reader, pending, event_tx, state);
+            Self::read_output_loop(stdout_reader, pending, event_tx, state, tracker, console_output, sync, stdin, auto_resume, alloc_tracer);
         });
         self.reader_handle = Some(reader_handle);
 
         // Start stderr reader thread
-        let event_tx_stderr = self.event_tx.clone();
+        let event_tx_stderr = self.event_sink();
         thread::spawn(move || {
             let reader = BufReader::new(stderr);
-            for line in reader.lines() {
-                if let Ok(line) = line {
-                    debug!("GDB stderr: {}", line);
-                    let _ = event_tx_stderr.send(GdbEvent::Output {
-                        channel: OutputChannel::Log,
-                        content: line,
-                    });
-                }
+            for line in reader.lines().map_while(Result::ok) {
+                debug!("GDB stderr: {}", line);
+                let _ = event_tx_stderr.send_log(DiagnosticSource::Stderr, line);
             }
         });
 
-        // Wait for initial (gdb) prompt
-        thread::sleep(Duration::from_millis(500));
+        self.wait_for_prompt()?;
 
         // Initialize GDB
         self.initialize()?;
+        self.detect_version();
+        if self.config.python_helpers {
+            self.load_python_helpers();
+        }
 
         {
             let mut state = self.state.lock().unwrap();
             state.connected = true;
         }
+        self.run_hooks(HookPoint::Start);
 
         info!("GDB started successfully");
         Ok(())
     }
 
-    /// Initialize GDB with necessary settings
+    /// Run the configured hook commands of `point`. A failing command does
+    /// not fail the operation; it is reported as a critical diagnostic.
+    fn run_hooks(&mut self, point: HookPoint) {
+        for command in self.config.hooks.commands(point).to_vec() {
+            match self.console_command(&command) {
+                Ok(_) => self.record_history(command),
+                Err(e) => {
+                    warn!("{} hook '{}' failed: {:#}", point.name(), command, e);
+                    self.diagnostics.lock().unwrap().push_with_severity(
+                        DiagnosticSource::Hook,
+                        Severity::Critical,
+                        &format!("{} hook '{}' failed: {:#}", point.name(), command, e),
+                    );
+                }
+            }
+        }
+    }
+
+    /// Wait for the first `(gdb)` prompt, which the debugger prints once it
+    /// has read its startup files and is ready for commands. Commands sent
+    /// earlier can be interleaved with the banner.
+    fn wait_for_prompt(&mut self) -> Result<()> {
+        let start = std::time::Instant::now();
+        loop {
+            if self.sync.lock().unwrap().prompts > 0 {
+                return Ok(());
+            }
+            if let Some(Ok(Some(status))) = self.process.as_mut().map(|p| p.try_wait()) {
+                return Err(anyhow!("{} exited during startup ({})", self.config.gdb_path, status));
+            }
+            if start.elapsed().as_millis() as u64 > self.config.startup_timeout_ms {
+                return Err(anyhow!(
+                    "{} did not become ready within {} ms",
+                    self.config.gdb_path,
+                    self.config.startup_timeout_ms
+                ));
+            }
+            thread::sleep(Duration::from_millis(10));
+        }
+    }
+
+    /// Initialize the debugger with the backend's settings
+    /// (async mode, no pagination, no confirmations for GDB)
     fn initialize(&mut self) -> Result<()> {
-        // Enable async mode
-        self.send_command("gdb-set mi-async on")?;
-        
-        // Set pagination off
-        self.send_command("gdb-set pagination off")?;
-        
-        // Set confirmations off
-        self.send_command("gdb-set confirm off")?;
+        for command in self.config.backend.backend().init_commands() {
+            self.send_command(command)?;
+        }
         
         Ok(())
     }
 
+    /// Extract the bundled Python helpers and `source` them. A GDB built
+    /// without Python only gets a warning in the log.
+    fn load_python_helpers(&mut self) {
+        if !self.config.backend.backend().supports(BackendFeature::GdbCli) {
+            warn!("Python helpers need GDB's CLI; skipping them");
+            return;
+        }
+        if let Err(e) = self.console_command("python import gdb") {
+            warn!("GDB has no Python support, skipping the helpers: {}", e);
+            return;
+        }
+        let dir = match HelperDir::extract(&std::env::temp_dir()) {
+            Ok(dir) => dir,
+            Err(e) => {
+                warn!("Could not extract the Python helpers: {:#}", e);
+                return;
+            }
+        };
+
+        let mut commands = Vec::new();
+        for (helper, script) in dir.scripts() {
+            match self.console_command(&format!("source {}", script.display())) {
+                Ok(_) => commands.extend(helper.commands.iter().map(|c| c.to_string())),
+                Err(e) => warn!("Failed to load {}: {}", helper.file, e),
+            }
+        }
+        info!("Loaded Python helpers from {}: {}", dir.path().display(), commands.join(", "));
+        self.state.lock().unwrap().python_helpers = commands;
+        self.helper_dir = Some(dir);
+    }
+
+    /// Run a command defined by a loaded Python helper and parse its JSON
+    pub fn python_helper(&mut self, command: &str, arguments: &str) -> Result<serde_json::Value> {
+        if !self.state.lock().unwrap().python_helpers.iter().any(|c| c == command) {
+            return Err(anyhow!(
+                "Python helper command '{}' is not loaded; start the session with python_helpers enabled",
+                command
+            ));
+        }
+        let output = self.console_command(format!("{} {}", command, arguments).trim_end())?;
+        parse_helper_output(&output)
+    }
+
+    /// Record the debugger version so newer MI commands can be avoided on
+    /// older releases
+    fn detect_version(&mut self) {
+        self.capture_console_output();
+        let response = self.send_command("gdb-version");
+        let output = self.take_console_output();
+
+        let version = match response {
+            Ok(MiOutputRecord::Result { class: ResultClass::Done, .. }) => parse_gdb_version(&output),
+            _ => None,
+        };
+        match &version {
+            Some(v) => info!("Debugger version {}.{} ({})", v.major, v.minor, v.banner),
+            None => warn!("Could not determine the debugger version; assuming a current release"),
+        }
+        self.state.lock().unwrap().gdb_version = version;
+    }
+
+    /// Whether the debugger provides an MI feature. An unknown GDB version
+    /// is assumed to be current; other backends have none of them.
+    pub fn has_mi_feature(&self, feature: MiFeature) -> bool {
+        self.config.backend.backend().supports(BackendFeature::GdbMi)
+            && self.state.lock().unwrap().gdb_version.as_ref().is_none_or(|v| v.supports(feature))
+    }
+
+    /// Fail unless the debugger backend supports a feature
+    pub fn require(&self, feature: BackendFeature, what: &str) -> Result<()> {
+        let backend = self.config.backend.backend();
+        if backend.supports(feature) {
+            Ok(())
+        } else {
+            Err(anyhow!("{} is not supported by the {} backend", what, backend.name()))
+        }
+    }
+
     /// Read output loop (runs in background thread)
+    #[allow(clippy::too_many_arguments)]
     fn read_output_loop(
         reader: BufReader<ChildStdout>,
         pending: Arc<Mutex<HashMap<u64, Sender<MiOutputRecord>>>>,
-        event_tx: Sender<GdbEvent>,
+        event_tx: EventSink,
         state: Arc<Mutex<GdbSessionState>>,
+        tracker: Arc<Mutex<SessionTracker>>,
+        console_output: Arc<Mutex<Option<String>>>,
+        sync: Arc<Mutex<MiSync>>,
+        stdin: Arc<Mutex<ChildStdin>>,
+        auto_resume: Arc<Mutex<AutoResume>>,
+        alloc_tracer: Arc<Mutex<AllocTracer>>,
     ) {
         let parser = crate::gdb::parser::MiParser::new();
         
@@ -153,22 +417,54 @@ impl GdbClient {
                     
                     match parser.parse_line(&line) {
                         Ok(Some(record)) => {
+                            if let MiOutputRecord::Result { token: Some(token @ (ENTRY_TOKEN | FINISH_TOKEN | RETURN_TOKEN)), .. } = &record {
+                                let command = alloc_tracer.lock().unwrap().on_response(*token, &record);
+                                Self::send_trace_command(command, &stdin);
+                                continue;
+                            }
+                            if let MiOutputRecord::Async { class: AsyncClass::Stopped, results, .. } = &record {
+                                let command = alloc_tracer.lock().unwrap().on_stop(results);
+                                if command.is_some() {
+                                    Self::send_trace_command(command, &stdin);
+                                    continue;
+                                }
+                            }
+                            if let MiOutputRecord::Result { token: Some(CONDITION_TOKEN), .. } = &record {
+                                Self::finish_auto_resume(&record, &stdin, &auto_resume, &event_tx, &state, &tracker);
+                                continue;
+                            }
+                            if Self::auto_resume_stop(&record, &stdin, &auto_resume) {
+                                continue;
+                            }
+
                             // Check if this is a response to a pending command
-                            if let MiOutputRecord::Result { token, .. } = &record {
-                                if let Some(tok) = token {
-                                    let pending_map = pending.lock().unwrap();
-                                    if let Some(tx) = pending_map.get(tok) {
-                                        let _ = tx.send(record);
-                                        continue;
-                                    }
+                            if let MiOutputRecord::Result { token: Some(tok), .. } = &record {
+                                let pending_map = pending.lock().unwrap();
+                                if let Some(tx) = pending_map.get(tok) {
+                                    let _ = tx.send(record);
+                                    continue;
+                                }
+                                if sync.lock().unwrap().stale_tokens.remove(tok) {
+                                    debug!("Discarded late response to timed-out command {}", tok);
+                                    continue;
+                                }
+                            }
+
+                            // Remote monitor output may arrive on either stream
+                            if let MiOutputRecord::Console(content) | MiOutputRecord::Target(content) = &record {
+                                if let Some(output) = console_output.lock().unwrap().as_mut() {
+                                    output.push_str(content);
                                 }
                             }
                             
                             // Process async records and notifications
-                            Self::handle_async_record(&record, &event_tx, &state);
+                            Self::handle_async_record(&record, &event_tx, &state, &tracker);
                         }
                         Ok(None) => {
-                            // Empty line or (gdb) prompt - ignore
+                            // Empty line or (gdb) prompt
+                            if line.trim() == "(gdb)" {
+                                sync.lock().unwrap().prompts += 1;
+                            }
                         }
                         Err(e) => {
                             warn!("Failed to parse line: {} - {}", line, e);
@@ -182,14 +478,91 @@ impl GdbClient {
             }
         }
         
+        // GDB exited: fail commands still waiting for a response
+        state.lock().unwrap().connected = false;
+        pending.lock().unwrap().clear();
         info!("GDB output reader stopped");
     }
 
+    /// Send a command of the allocation tracer
+    fn send_trace_command(command: Option<String>, stdin: &Mutex<ChildStdin>) {
+        if let Some(command) = command {
+            if let Err(e) = write_locked(stdin, &command) {
+                warn!("Failed to continue allocation trace: {}", e);
+            }
+        }
+    }
+
+    /// Continue, or hold back while its condition is evaluated, a `*stopped`
+    /// record matched by an auto-resume rule. Returns false when the record
+    /// is to be handled as usual.
+    fn auto_resume_stop(record: &MiOutputRecord, stdin: &Mutex<ChildStdin>, auto_resume: &Mutex<AutoResume>) -> bool {
+        let MiOutputRecord::Async { class: AsyncClass::Stopped, results, .. } = record else {
+            return false;
+        };
+        let mut rules = auto_resume.lock().unwrap();
+        match rules.check(results) {
+            Verdict::Report => false,
+            Verdict::Resume(id) => {
+                if let Err(e) = write_locked(stdin, "-exec-continue\n") {
+                    warn!("Failed to auto-resume: {}", e);
+                    return false;
+                }
+                rules.record(id, results);
+                true
+            }
+            Verdict::Evaluate(id, condition) => {
+                let thread = results.iter()
+                    .find(|r| r.variable == "thread-id")
+                    .and_then(|r| match &r.value {
+                        MiValue::String(s) => Some(format!("--thread {} --frame 0 ", s)),
+                        _ => None,
+                    })
+                    .unwrap_or_default();
+                let escaped = condition.replace('\\', "\\\\").replace('"', "\\\"");
+                let command = format!("{}-data-evaluate-expression {}\"{}\"\n", CONDITION_TOKEN, thread, escaped);
+                if let Err(e) = write_locked(stdin, &command) {
+                    warn!("Failed to evaluate auto-resume condition: {}", e);
+                    return false;
+                }
+                rules.defer(id, record.clone());
+                true
+            }
+            Verdict::Resumed(id) => {
+                rules.record(id, results);
+                true
+            }
+        }
+    }
+
+    /// Resume the stop held back for a condition if the condition held,
+    /// otherwise report it
+    fn finish_auto_resume(
+        response: &MiOutputRecord,
+        stdin: &Mutex<ChildStdin>,
+        auto_resume: &Mutex<AutoResume>,
+        event_tx: &EventSink,
+        state: &Arc<Mutex<GdbSessionState>>,
+        tracker: &Arc<Mutex<SessionTracker>>,
+    ) {
+        let Some((id, stop)) = auto_resume.lock().unwrap().take_deferred() else {
+            return;
+        };
+        if condition_holds(response) && write_locked(stdin, "-exec-continue\n").is_ok() {
+            if let MiOutputRecord::Async { results, .. } = &stop {
+                auto_resume.lock().unwrap().record(id, results);
+            }
+        } else {
+            Self::handle_async_record(&stop, event_tx, state, tracker);
+        }
+    }
+
     /// Handle async records and notifications
     fn handle_async_record(
         record: &MiOutputRecord,
-        event_tx: &Sender<GdbEvent>,
+        event_tx: &EventSink,
         state: &Arc<Mutex<GdbSessionState>>,
+        tracker: &Arc<Mutex<SessionTracker>>,
     ) {
         match record {
             MiOutputRecord::Async { class, results, .. } => {
@@ -220,9 +593,24 @@ impl GdbClient {
                         {
                             let mut state = state.lock().unwrap();
                             state.running = false;
+                            state.stop_count += 1;
                             state.current_thread = thread_id.clone();
+                            // GDB selects the innermost frame at every stop
+                            state.current_frame = None;
+                            if let Some(exit) = ProgramExit::from_stop(&reason, results) {
+                                state.program_exit = Some(exit);
+                            }
+                            if state.pc_sampling {
+                                let stop = stop_event(&reason, results, frame.as_ref(), thread_id.as_deref());
+                                if is_interrupt_stop(&stop) {
+                                    state.pc_sample = Some(stop);
+                                    return;
+                                }
+                            }
                         }
 
+                        tracker.lock().unwrap().record_stop(&reason, results, frame.as_ref(), thread_id.as_deref());
+
                         let _ = event_tx.send(GdbEvent::Stopped {
                             reason,
                             frame,
@@ -253,11 +641,21 @@ impl GdbClient {
                 match class {
                     NotificationClass::BreakpointCreated => {
                         if let Some(bp) = parse_breakpoint(results) {
+                            tracker.lock().unwrap().record_breakpoint(bp.clone(), None);
                             let _ = event_tx.send(GdbEvent::BreakpointCreated { breakpoint: bp });
                         }
                     }
                     NotificationClass::BreakpointModified => {
                         if let Some(bp) = parse_breakpoint(results) {
+                            let mut tracker = tracker.lock().unwrap();
+                            let was_pending = tracker.breakpoints().iter()
+                                .any(|b| b.breakpoint.number == bp.number && b.pending);
+                            let tracked = tracker.record_breakpoint(bp.clone(), None);
+                            if was_pending && !tracked.pending {
+                                info!("Pending breakpoint {} resolved to {}", bp.number,
+                                    tracked.actual_location.as_deref().unwrap_or("?"));
+                            }
+                            drop(tracker);
                             let _ = event_tx.send(GdbEvent::BreakpointModified { breakpoint: bp });
                         }
                     }
@@ -272,6 +670,7 @@ impl GdbClient {
                                 }
                             });
                         if let Some(num) = number {
+                            tracker.lock().unwrap().remove_breakpoint(&num);
                             let _ = event_tx.send(GdbEvent::BreakpointDeleted { number: num });
                         }
                     }
@@ -353,10 +752,7 @@ impl GdbClient {
                 });
             }
             MiOutputRecord::Log(content) => {
-                let _ = event_tx.send(GdbEvent::Output {
-                    channel: OutputChannel::Log,
-                    content: content.clone(),
-                });
+                let _ = event_tx.send_log(DiagnosticSource::Log, content.clone());
             }
             _ => {}
         }
@@ -364,7 +760,44 @@ impl GdbClient {
 
     /// Send an MI command and wait for response
     pub fn send_command(&mut self, command: &str) -> Result<MiOutputRecord> {
-        let stdin = self.stdin.as_mut().ok_or_else(|| anyhow!("GDB not running"))?;
+        self.send_command_with_timeout(command, self.config.timeout_ms)
+    }
+
+    /// Send an MI command and wait up to `timeout_ms` for the response
+    pub fn send_command_with_timeout(&mut self, command: &str, timeout_ms: u64) -> Result<MiOutputRecord> {
+        let started = Instant::now();
+        let response = self.exchange(command, timeout_ms);
+
+        let mut trace = self.mi_trace.lock().unwrap();
+        if trace.active() {
+            let (class, error) = match &response {
+                Ok(MiOutputRecord::Result { class, results, .. }) => {
+                    let error = (*class == ResultClass::Error).then(|| {
+                        results.iter()
+                            .find(|r| r.variable == "msg")
+                            .and_then(|r| MiParser::extract_string(&r.value))
+                            .unwrap_or_default()
+                    });
+                    (Some(class.clone()), error)
+                }
+                Ok(_) => (None, None),
+                Err(e) => (None, Some(format!("{:#}", e))),
+            };
+            trace.record(MiExchange {
+                command: format!("-{}", command),
+                class,
+                error,
+                elapsed_ms: started.elapsed().as_millis() as u64,
+            });
+        }
+        drop(trace);
+        response
+    }
+
+    fn exchange(&mut self, command: &str, timeout_ms: u64) -> Result<MiOutputRecord> {
+        if self.stdin.is_none() {
+            return Err(anyhow!("GDB not running"));
+        }
         
         let token = self.token_counter.fetch_add(1, Ordering::SeqCst);
         
@@ -381,13 +814,16 @@ impl GdbClient {
         let full_command = format!("{}-{}\n", token, command);
         debug!("Sending command: {}", full_command.trim());
         
-        stdin.write_all(full_command.as_bytes())?;
-        stdin.flush()?;
+        self.write_input(&full_command)?;
         
         // Wait for response with timeout
-        let timeout = Duration::from_millis(self.config.timeout_ms);
-        let response = rx.recv_timeout(timeout)
-            .map_err(|_| anyhow!("Timeout waiting for GDB response"))?;
+        let timeout = Duration::from_millis(timeout_ms);
+        let response = rx.recv_timeout(timeout);
+        if response.is_err() {
+            // Mark the token stale before unregistering it so a late response
+            // is drained by the reader instead of being treated as async output
+            self.sync.lock().unwrap().stale_tokens.insert(token);
+        }
         
         // Cleanup pending
         {
@@ -395,27 +831,122 @@ impl GdbClient {
             pending.remove(&token);
         }
         
-        Ok(response)
+        match response {
+            Ok(response) => Ok(response),
+            Err(mpsc::RecvTimeoutError::Disconnected) => Err(anyhow!("GDB exited before answering: {}", command)),
+            // The response raced with the timeout
+            Err(mpsc::RecvTimeoutError::Timeout) => match rx.try_recv() {
+                Ok(response) => {
+                    self.sync.lock().unwrap().stale_tokens.remove(&token);
+                    Ok(response)
+                }
+                Err(_) => Err(self.recover_from_timeout(token, command)),
+            },
+        }
+    }
+
+    /// Resynchronize after a command timed out: optionally interrupt GDB,
+    /// then wait for the stale command's result record so the following
+    /// command starts from a clean state
+    fn recover_from_timeout(&mut self, token: u64, command: &str) -> anyhow::Error {
+        warn!("Command -{} (token {}) timed out, resynchronizing", command, token);
+        if self.config.interrupt_on_timeout {
+            if let Err(e) = self.send_sigint() {
+                warn!("Failed to interrupt GDB: {}", e);
+            }
+        }
+
+        let start = std::time::Instant::now();
+        while start.elapsed() < Duration::from_millis(2000) {
+            // The reader drops the token when its result record arrives;
+            // prompts are no signal, async records are followed by them too
+            if !self.sync.lock().unwrap().stale_tokens.contains(&token) {
+                info!("Resynchronized with GDB after timeout of -{}", command);
+                return anyhow!(
+                    "Timeout waiting for GDB response to -{}; the command was abandoned and the session resynchronized",
+                    command
+                );
+            }
+            thread::sleep(Duration::from_millis(10));
+        }
+
+        anyhow!(
+            "Timeout waiting for GDB response to -{}; GDB is still busy (use gdb_health to check, or gdb_stop to restart)",
+            command
+        )
+    }
+
+    /// Start collecting console and target stream output
+    fn capture_console_output(&self) {
+        *self.console_output.lock().unwrap() = Some(String::new());
+    }
+
+    /// Stop collecting stream output and return what was collected
+    fn take_console_output(&self) -> String {
+        self.console_output.lock().unwrap().take().unwrap_or_default()
+    }
+
+    /// Run a CLI command through `interpreter-exec console` and return the
+    /// console output it produced
+    pub fn console_command(&mut self, command: &str) -> Result<String> {
+        self.console_command_in(command, &FrameContext::default())
+    }
+
+    /// Run a CLI command in a thread and frame other than the selected one.
+    /// Fails up front on backends without GDB's CLI.
+    pub fn console_command_in(&mut self, command: &str, context: &FrameContext) -> Result<String> {
+        let mut words = command.split_whitespace();
+        let name = match words.next() {
+            Some(prefix @ ("info" | "show" | "set" | "maint" | "maintenance")) => format!("{} {}", prefix, words.next().unwrap_or("")),
+            first => first.unwrap_or("").to_string(),
+        };
+        self.require(BackendFeature::GdbCli, &format!("'{}'", name.trim_end()))?;
+        self.capture_console_output();
+
+        let options = self.context_options(context);
+        let escaped = command.replace('\\', "\\\\").replace('"', "\\\"");
+        let response = self.send_command(&format!("interpreter-exec {}console \"{}\"", options, escaped));
+        let output = self.take_console_output();
+        let response = response?;
+
+        match response {
+            MiOutputRecord::Result { class: ResultClass::Done, .. }
+            | MiOutputRecord::Result { class: ResultClass::Running, .. } => Ok(output),
+            MiOutputRecord::Result { class: ResultClass::Error, results, .. } => {
+                Err(mi_error(&results, &format!("'{}' failed", command)))
+            }
+            _ => Err(anyhow!("Unexpected response")),
+        }
     }
 
     /// Send a command without waiting for response (fire and forget)
+    #[allow(dead_code)] // kept for commands whose result record is irrelevant
     pub fn send_command_async(&mut self, command: &str) -> Result<()> {
-        let stdin = self.stdin.as_mut().ok_or_else(|| anyhow!("GDB not running"))?;
-        
         let token = self.token_counter.fetch_add(1, Ordering::SeqCst);
         let full_command = format!("{}-{}\n", token, command);
         
         debug!("Sending async command: {}", full_command.trim());
+        self.mi_trace.lock().unwrap().record(MiExchange {
+            command: format!("-{}", command),
+            class: None,
+            error: None,
+            elapsed_ms: 0,
+        });
         
-        stdin.write_all(full_command.as_bytes())?;
-        stdin.flush()?;
-        
-        Ok(())
+        self.write_input(&full_command)
+    }
+
+    /// Write to GDB's stdin. The lock is held only for the write so the
+    /// [`Interrupter`] can send while a command waits for its response.
+    fn write_input(&self, input: &str) -> Result<()> {
+        let stdin = self.stdin.as_ref().ok_or_else(|| anyhow!("GDB not running"))?;
+        write_locked(stdin, input)
     }
 
     /// Get the event receiver
+    #[allow(dead_code)] // no tool consumes the event stream yet
     pub fn event_receiver(&mut self) -> Option<Receiver<GdbEvent>> {
-        self.event_rx.take()
+        self.event_rx.lock().unwrap().take()
     }
 
     /// Get current session state
@@ -423,19 +954,180 @@ impl GdbClient {
         self.state.lock().unwrap().clone()
     }
 
+    /// Note the target profile the session was started with
+    pub fn set_profile(&mut self, name: &str, svd_file: Option<String>, qmp: Option<String>) {
+        let mut state = self.state.lock().unwrap();
+        state.profile = Some(name.to_string());
+        state.svd_file = svd_file;
+        state.qmp = qmp;
+    }
+
+    /// Record a CLI-equivalent command in the session history
+    pub fn record_history(&mut self, command: impl Into<String>) {
+        self.history.push(command.into());
+    }
+
+    /// Keep an evaluation's result in the evaluation history, tagged with
+    /// the current stop generation
+    pub fn record_evaluation(&mut self, expression: &str, context: &FrameContext, result: Result<&str, String>) {
+        let state = self.state();
+        let location = self.tracker().last_stop()
+            .and_then(|s| s.frame.as_ref())
+            .map(|f| match &f.func {
+                Some(func) => format!("{} ({})", func, f.addr),
+                None => f.addr.clone(),
+            });
+        let (value, error) = match result {
+            Ok(value) => (Some(value.to_string()), None),
+            Err(error) => (None, Some(error)),
+        };
+        self.eval_history.record(Evaluation {
+            seq: 0,
+            expression: expression.to_string(),
+            value,
+            error,
+            generation: state.stop_count,
+            thread_id: context.thread.clone().or(state.current_thread),
+            frame_level: context.frame,
+            location,
+            timestamp_ms: crate::util::now_ms(),
+        });
+    }
+
+    /// Evaluations matching `query`, newest first, with the current stop
+    /// generation and the number of evaluations kept
+    pub fn eval_history(&self, query: &EvalQuery) -> (Vec<EvaluationView>, u64, usize) {
+        let generation = self.state().stop_count;
+        (self.eval_history.query(query, generation), generation, self.eval_history.len())
+    }
+
+    pub fn clear_eval_history(&mut self) {
+        self.eval_history.clear();
+    }
+
+    /// Get the session tracker
+    pub fn tracker(&self) -> MutexGuard<'_, SessionTracker> {
+        self.tracker.lock().unwrap()
+    }
+
+    /// The session's event timeline, for reading without the client lock
+    pub fn timeline(&self) -> Arc<Mutex<Timeline>> {
+        Arc::clone(&self.timeline)
+    }
+
+    /// Warnings GDB printed on stderr or the MI log stream
+    pub fn diagnostics(&self) -> Arc<Mutex<Diagnostics>> {
+        Arc::clone(&self.diagnostics)
+    }
+
+    /// Trace of the MI commands sent during verbose tool calls
+    pub fn mi_trace(&self) -> Arc<Mutex<MiTrace>> {
+        Arc::clone(&self.mi_trace)
+    }
+
+    fn event_sink(&self) -> EventSink {
+        EventSink {
+            tx: self.event_tx.clone(),
+            timeline: Arc::clone(&self.timeline),
+            diagnostics: Arc::clone(&self.diagnostics),
+        }
+    }
+
+    /// Rules for stops that are continued without being reported
+    pub fn auto_resume(&self) -> MutexGuard<'_, AutoResume> {
+        self.auto_resume.lock().unwrap()
+    }
+
+    /// Allocation trace state
+    pub fn alloc_tracer(&self) -> MutexGuard<'_, AllocTracer> {
+        self.alloc_tracer.lock().unwrap()
+    }
+
     /// Check if GDB is running
+    #[allow(dead_code)] // tools check the session state instead
     pub fn is_running(&self) -> bool {
         self.process.is_some()
     }
 
+    /// Check that the GDB process is alive and responsive, probing the
+    /// remote target too when connected and stopped
+    pub fn health_check(&mut self, timeout_ms: u64) -> HealthReport {
+        let state = self.state();
+        let mut report = HealthReport {
+            running: state.running,
+            target: state.target.clone(),
+            ..Default::default()
+        };
+
+        match self.process.as_mut().map(|p| p.try_wait()) {
+            None => {
+                report.errors.push("GDB process not started".to_string());
+                return report;
+            }
+            Some(Ok(Some(status))) => {
+                report.exit_status = Some(status.to_string());
+                report.errors.push(format!("GDB process exited: {}", status));
+                return report;
+            }
+            Some(Ok(None)) => report.process_alive = true,
+            Some(Err(e)) => report.errors.push(format!("Failed to query GDB process: {}", e)),
+        }
+
+        let started = std::time::Instant::now();
+        match self.send_command_with_timeout("list-features", timeout_ms) {
+            Ok(_) => {
+                report.responsive = true;
+                report.latency_ms = Some(started.elapsed().as_millis() as u64);
+            }
+            Err(e) => report.errors.push(format!("GDB did not respond: {}", e)),
+        }
+
+        if report.responsive && state.target.is_some() && !state.running {
+            let probe = self.send_command_with_timeout("data-evaluate-expression $pc", timeout_ms);
+            let ok = matches!(probe, Ok(MiOutputRecord::Result { class: ResultClass::Done, .. }));
+            if !ok {
+                report.errors.push("Remote target did not answer a register read".to_string());
+            }
+            report.target_responsive = Some(ok);
+        }
+
+        report
+    }
+
+    /// Shut the session down cleanly: a remote target is interrupted and
+    /// disconnected so the stub stays usable, then GDB exits (killing any
+    /// local inferior)
+    pub fn shutdown(&mut self) {
+        let state = self.state();
+        if self.process.is_some() && state.target_remote {
+            if state.running {
+                let _ = self.exec_interrupt();
+            }
+            if let Err(e) = self.send_command_with_timeout("target-disconnect", 2000) {
+                warn!("Failed to disconnect from remote target during shutdown: {}", e);
+            }
+        }
+        let _ = self.stop();
+    }
+
+    /// Helper processes started for this session
+    pub fn managed(&self) -> &ManagedProcesses {
+        &self.managed
+    }
+
+    /// Handle for force-terminating GDB and its helpers while the client
+    /// is busy
+    pub fn kill_switch(&self) -> Option<KillSwitch> {
+        let pid = self.process.as_ref()?.id();
+        Some(KillSwitch::new(pid, self.managed.clone()))
+    }
+
     /// Stop the GDB process
     pub fn stop(&mut self) -> Result<()> {
+        self.managed.kill_all();
         if let Some(mut process) = self.process.take() {
             // Try to exit GDB gracefully first
-            if let Some(stdin) = self.stdin.as_mut() {
-                let _ = stdin.write_all(b"-gdb-exit\n");
-                let _ = stdin.flush();
-            }
+            let _ = self.write_input("-gdb-exit\n");
             
             // Wait a bit for graceful exit
             thread::sleep(Duration::from_millis(500));
@@ -445,6 +1137,7 @@ impl GdbClient {
             let _ = process.wait();
             
             self.stdin = None;
+            self.helper_dir = None;
             
             {
                 let mut state = self.state.lock().unwrap();
@@ -464,30 +1157,144 @@ impl Drop for GdbClient {
     }
 }
 
+/// Whether a stop looks like the result of interrupting the target
+fn is_interrupt_stop(stop: &StopEvent) -> bool {
+    match &stop.reason {
+        StopReason::SignalReceived => stop.signal.as_ref()
+            .and_then(|s| s.name.as_deref())
+            .is_none_or(|name| name == "SIGINT" || name == "SIGTRAP" || name == "0"),
+        StopReason::Unknown(_) => true,
+        _ => false,
+    }
+}
+
+/// Whether an error says the command needs a stopped target
+fn is_target_running_error(error: &anyhow::Error) -> bool {
+    error.downcast_ref::<GdbError>().is_some_and(|e| e.code == "target_running")
+}
+
+/// Interrupts the target from outside the [`GdbClient`], so a stop can be
+/// requested while another call holds the client and waits for the target
+#[derive(Clone)]
+pub struct Interrupter {
+    stdin: Arc<Mutex<ChildStdin>>,
+    pid: u32,
+    state: Arc<Mutex<GdbSessionState>>,
+}
+
+impl Interrupter {
+    /// Interrupt the target and wait for it to stop
+    ///
+    /// Uses `-exec-interrupt` when the target runs in the background. On
+    /// all-stop targets without async support GDB does not read commands
+    /// while the target runs, so SIGINT is sent to GDB instead. The
+    /// `-exec-interrupt` response is not waited for: the client may be
+    /// reading the response of another command.
+    pub fn interrupt(&self) -> Result<InterruptMethod> {
+        let (stops, target_async) = {
+            let state = self.state.lock().unwrap();
+            (state.stop_count, state.target_async)
+        };
+
+        if target_async != Some(false) {
+            write_locked(&self.stdin, "-exec-interrupt\n")?;
+            if wait_for_stop_count(&self.state, stops, 5000).is_ok() {
+                return Ok(InterruptMethod::Mi);
+            }
+            debug!("-exec-interrupt did not stop the target, falling back to SIGINT");
+        }
+
+        sigint(self.pid)?;
+        wait_for_stop_count(&self.state, stops, 5000)
+            .map_err(|_| anyhow!("Target did not stop after SIGINT"))?;
+        Ok(InterruptMethod::Signal)
+    }
+}
+
+fn write_locked(stdin: &Mutex<ChildStdin>, input: &str) -> Result<()> {
+    let mut stdin = stdin.lock().unwrap();
+    stdin.write_all(input.as_bytes())?;
+    stdin.flush()?;
+    Ok(())
+}
+
+/// Wait until more than `since` `*stopped` records have been seen
+fn wait_for_stop_count(state: &Mutex<GdbSessionState>, since: u64, timeout_ms: u64) -> Result<()> {
+    let start = std::time::Instant::now();
+    loop {
+        let state = state.lock().unwrap();
+        if state.stop_count > since {
+            return Ok(());
+        }
+        if !state.connected {
+            return Err(anyhow!("GDB exited while waiting for the target to stop"));
+        }
+        drop(state);
+
+        if start.elapsed().as_millis() as u64 > timeout_ms {
+            return Err(anyhow!("Timeout waiting for target to stop"));
+        }
+
+        std::thread::sleep(std::time::Duration::from_millis(10));
+    }
+}
+
+#[cfg(unix)]
+fn sigint(pid: u32) -> Result<()> {
+    // SAFETY: kill(2) has no memory safety requirements
+    if unsafe { libc::kill(pid as libc::pid_t, libc::SIGINT) } != 0 {
+        return Err(anyhow!("Failed to send SIGINT to GDB: {}", std::io::Error::last_os_error()));
+    }
+    Ok(())
+}
+
+#[cfg(not(unix))]
+fn sigint(_pid: u32) -> Result<()> {
+    Err(anyhow!("Interrupting without -exec-interrupt is only supported on Unix"))
+}
+
+/// How long to wait for `count` instruction steps to finish. Each step
+/// is a round trip to the target, which is slow over remote stubs.
+fn instruction_step_timeout(count: u64) -> u64 {
+    5000u64.saturating_add(count.saturating_mul(50))
+}
+
+/// Build the error for an `^error` result, classified by GDB's message
+fn mi_error(results: &[MiResult], context: &str) -> anyhow::Error {
+    let msg = results.iter()
+        .find(|r| r.variable == "msg")
+        .and_then(|r| MiParser::extract_string(&r.value))
+        .unwrap_or_else(|| "Unknown error".to_string());
+    GdbError::from_mi(context, &msg).into()
+}
+
 /// High-level GDB operations
 impl GdbClient {
-    /// Load an executable file
-    pub fn file_exec_and_symbols(&mut self, file: &str) -> Result<()> {
-        let response = self.send_command(&format!("file-exec-and-symbols {}", file))?;
-        
-        match response {
+    /// Load an executable and its symbols. Returns the symbol check if GDB
+    /// found no debugging symbols in it.
+    pub fn file_exec_and_symbols(&mut self, file: &str) -> Result<Option<SymbolCheck>> {
+        self.capture_console_output();
+        let diagnostics_seen = self.diagnostics.lock().unwrap().last_seq();
+        let response = self.send_command(&format!("file-exec-and-symbols {}", file));
+        let output = self.take_console_output();
+
+        match response? {
             MiOutputRecord::Result { class: ResultClass::Done, .. } => {
-                let mut state = self.state.lock().unwrap();
-                state.executable = Some(file.to_string());
-                Ok(())
+                let stripped = reports_no_debug_symbols(&output)
+                    || self.diagnostics.lock().unwrap().critical_since(diagnostics_seen)
+                        .iter().any(|line| reports_no_debug_symbols(&line.text));
+                let check = stripped.then(|| SymbolCheck::without_debug_info(file, read_elf(file).ok().as_deref()));
+                {
+                    let mut state = self.state.lock().unwrap();
+                    state.executable = Some(file.to_string());
+                    state.symbols_warning = check.as_ref().map(SymbolCheck::warning);
+                }
+                self.refresh_endian();
+                self.run_hooks(HookPoint::Load);
+                Ok(check)
             }
             MiOutputRecord::Result { class: ResultClass::Error, results, .. } => {
-                let msg = results.iter()
-                    .find(|r| r.variable == "msg")
-                    .and_then(|r| {
-                        if let MiValue::String(s) = &r.value {
-                            Some(s.clone())
-                        } else {
-                            None
-                        }
-                    })
-                    .unwrap_or_else(|| "Unknown error".to_string());
-                Err(anyhow!("Failed to load file: {}", msg))
+                Err(mi_error(&results, "Failed to load file"))
             }
             _ => Err(anyhow!("Unexpected response")),
         }
@@ -500,22 +1307,18 @@ impl GdbClient {
         match response {
             MiOutputRecord::Result { class: ResultClass::Connected, .. } |
             MiOutputRecord::Result { class: ResultClass::Done, .. } => {
-                let mut state = self.state.lock().unwrap();
-                state.target_remote = true;
+                {
+                    let mut state = self.state.lock().unwrap();
+                    state.target_remote = true;
+                    state.target = Some(format!("remote {}", target));
+                }
+                self.detect_target_async();
+                self.refresh_endian();
+                self.run_hooks(HookPoint::Connect);
                 Ok(())
             }
             MiOutputRecord::Result { class: ResultClass::Error, results, .. } => {
-                let msg = results.iter()
-                    .find(|r| r.variable == "msg")
-                    .and_then(|r| {
-                        if let MiValue::String(s) = &r.value {
-                            Some(s.clone())
-                        } else {
-                            None
-                        }
-                    })
-                    .unwrap_or_else(|| "Unknown error".to_string());
-                Err(anyhow!("Failed to connect to target: {}", msg))
+                Err(mi_error(&results, "Failed to connect to target"))
             }
             _ => Err(anyhow!("Unexpected response")),
         }
@@ -528,22 +1331,18 @@ impl GdbClient {
         match response {
             MiOutputRecord::Result { class: ResultClass::Connected, .. } |
             MiOutputRecord::Result { class: ResultClass::Done, .. } => {
-                let mut state = self.state.lock().unwrap();
-                state.target_remote = true;
+                {
+                    let mut state = self.state.lock().unwrap();
+                    state.target_remote = true;
+                    state.target = Some(format!("extended-remote {}", target));
+                }
+                self.detect_target_async();
+                self.refresh_endian();
+                self.run_hooks(HookPoint::Connect);
                 Ok(())
             }
             MiOutputRecord::Result { class: ResultClass::Error, results, .. } => {
-                let msg = results.iter()
-                    .find(|r| r.variable == "msg")
-                    .and_then(|r| {
-                        if let MiValue::String(s) = &r.value {
-                            Some(s.clone())
-                        } else {
-                            None
-                        }
-                    })
-                    .unwrap_or_else(|| "Unknown error".to_string());
-                Err(anyhow!("Failed to connect to extended-remote target: {}", msg))
+                Err(mi_error(&results, "Failed to connect to extended-remote target"))
             }
             _ => Err(anyhow!("Unexpected response")),
         }
@@ -555,28 +1354,62 @@ impl GdbClient {
         
         match response {
             MiOutputRecord::Result { class: ResultClass::Done, .. } => {
-                let mut state = self.state.lock().unwrap();
-                state.target_remote = false;
+                {
+                    let mut state = self.state.lock().unwrap();
+                    state.target_remote = false;
+                    state.target = None;
+                    state.target_async = None;
+                }
+                self.managed.kill_all();
                 Ok(())
             }
             MiOutputRecord::Result { class: ResultClass::Error, results, .. } => {
-                let msg = results.iter()
-                    .find(|r| r.variable == "msg")
-                    .and_then(|r| {
-                        if let MiValue::String(s) = &r.value {
-                            Some(s.clone())
-                        } else {
-                            None
-                        }
-                    })
-                    .unwrap_or_else(|| "Unknown error".to_string());
-                Err(anyhow!("Failed to disconnect: {}", msg))
+                Err(mi_error(&results, "Failed to disconnect"))
+            }
+            _ => Err(anyhow!("Unexpected response")),
+        }
+    }
+
+    /// Attach to a running process
+    pub fn target_attach(&mut self, pid: u32) -> Result<()> {
+        let stops = self.state().stop_count;
+        let response = self.send_command(&format!("target-attach {}", pid))?;
+
+        match response {
+            MiOutputRecord::Result { class: ResultClass::Done, .. } => {
+                {
+                    let mut state = self.state.lock().unwrap();
+                    state.target = Some(format!("process {}", pid));
+                }
+                // The attached process stops right away
+                self.wait_for_stop(stops, 10000)
+            }
+            MiOutputRecord::Result { class: ResultClass::Error, results, .. } => {
+                Err(mi_error(&results, &format!("Failed to attach to process {}", pid)))
+            }
+            _ => Err(anyhow!("Unexpected response")),
+        }
+    }
+
+    /// Change a GDB setting (`set <name> <value>`)
+    pub fn gdb_set(&mut self, name: &str, value: &str) -> Result<()> {
+        self.require(BackendFeature::GdbMi, &format!("'set {}'", name))?;
+        let response = self.send_command(&format!("gdb-set {} {}", name, value))?;
+
+        match response {
+            MiOutputRecord::Result { class: ResultClass::Done, .. } => {
+                self.record_history(format!("set {} {}", name, value));
+                Ok(())
+            }
+            MiOutputRecord::Result { class: ResultClass::Error, results, .. } => {
+                Err(mi_error(&results, &format!("Failed to set {}", name)))
             }
             _ => Err(anyhow!("Unexpected response")),
         }
     }
 
     /// Set architecture
+    #[allow(dead_code)] // architectures are set through GdbConfig at startup
     pub fn set_architecture(&mut self, arch: &str) -> Result<()> {
         let response = self.send_command(&format!("gdb-set architecture {}", arch))?;
         
@@ -587,30 +1420,51 @@ impl GdbClient {
                 Ok(())
             }
             MiOutputRecord::Result { class: ResultClass::Error, results, .. } => {
-                let msg = results.iter()
-                    .find(|r| r.variable == "msg")
-                    .and_then(|r| {
-                        if let MiValue::String(s) = &r.value {
-                            Some(s.clone())
-                        } else {
-                            None
-                        }
-                    })
-                    .unwrap_or_else(|| "Unknown error".to_string());
-                Err(anyhow!("Failed to set architecture: {}", msg))
+                Err(mi_error(&results, "Failed to set architecture"))
             }
             _ => Err(anyhow!("Unexpected response")),
         }
     }
 
-    /// Insert a breakpoint
-    pub fn break_insert(&mut self, location: &str, temporary: bool, condition: Option<&str>) -> Result<Breakpoint> {
+    /// Insert a breakpoint. A condition is wrapped to count its
+    /// evaluations for `break_stats`, unless GDB rejects the wrapper (e.g.
+    /// in a language without a comma operator).
+    pub fn break_insert(&mut self, location: &str, options: &BreakInsertOptions) -> Result<Breakpoint> {
+        let Some(condition) = options.condition.as_deref().filter(|_| options.count_evaluations) else {
+            return self.insert_breakpoint(location, options);
+        };
+        self.require(BackendFeature::GdbCli, "Counting condition evaluations")?;
+        let counter = counter_variable(self.next_condition_counter);
+        self.next_condition_counter += 1;
+        self.data_evaluate_expression(&format!("{} = 0", counter))
+            .map_err(|e| anyhow!("Failed to create the evaluation counter {}: {}", counter, e))?;
+        let counted = BreakInsertOptions {
+            condition: Some(counting_condition(&counter, condition)),
+            ..options.clone()
+        };
+        self.insert_breakpoint(location, &counted)
+    }
+
+    fn insert_breakpoint(&mut self, location: &str, options: &BreakInsertOptions) -> Result<Breakpoint> {
         let mut cmd = String::from("break-insert");
-        if temporary {
+        if options.temporary {
             cmd.push_str(" -t");
         }
-        if let Some(cond) = condition {
-            cmd.push_str(&format!(" -c \"{}\"", cond));
+        if options.pending {
+            cmd.push_str(" -f");
+        }
+        if let Some(cond) = &options.condition {
+            cmd.push_str(&format!(" -c \"{}\"", cond.replace('\\', "\\\\").replace('"', "\\\"")));
+        }
+        if let Some(ignore) = options.ignore_count {
+            cmd.push_str(&format!(" -i {}", ignore));
+        }
+        if let Some(thread) = &options.thread {
+            cmd.push_str(&format!(" -p {}", thread));
+        }
+        if let Some(inferior) = options.inferior {
+            self.require(BackendFeature::GdbMi, "Restricting a breakpoint to an inferior")?;
+            cmd.push_str(&format!(" -g i{}", inferior));
         }
         cmd.push_str(&format!(" {}", location));
         
@@ -618,44 +1472,87 @@ impl GdbClient {
         
         match response {
             MiOutputRecord::Result { class: ResultClass::Done, results, .. } => {
-                parse_breakpoint(&results)
-                    .ok_or_else(|| anyhow!("Failed to parse breakpoint response"))
+                // GDB does not send notifications for MI-issued changes
+                let bp = parse_breakpoint(&results)
+                    .ok_or_else(|| anyhow!("Failed to parse breakpoint response"))?;
+                self.tracker().record_breakpoint(bp.clone(), Some(location));
+                Ok(bp)
             }
             MiOutputRecord::Result { class: ResultClass::Error, results, .. } => {
-                let msg = results.iter()
-                    .find(|r| r.variable == "msg")
-                    .and_then(|r| {
-                        if let MiValue::String(s) = &r.value {
-                            Some(s.clone())
-                        } else {
-                            None
-                        }
-                    })
-                    .unwrap_or_else(|| "Unknown error".to_string());
-                Err(anyhow!("Failed to insert breakpoint: {}", msg))
+                Err(mi_error(&results, "Failed to insert breakpoint"))
             }
             _ => Err(anyhow!("Unexpected response")),
         }
     }
 
+    /// Set breakpoints on the handlers of a Cortex-M vector table, one per
+    /// distinct handler address. Without a base, the table is found through
+    /// VTOR. Without a selection, every vector except Reset is covered.
+    /// At most `max_new` breakpoints are inserted; further handlers get an
+    /// error instead.
+    pub fn break_vectors(
+        &mut self,
+        base: Option<u64>,
+        count: usize,
+        interrupts: &[(usize, String)],
+        selection: &[String],
+        max_new: Option<usize>,
+    ) -> Result<VectorBreakpoints> {
+        let base = match base {
+            Some(base) => base,
+            None => self.evaluate_u64(&format!("*(unsigned int *){:#x}", VTOR))?,
+        };
+        let memory = self.data_read_memory(&format!("{:#x}", base), count as u64 * 4)?;
+        let bytes = parse_hex_bytes(&memory.data.concat());
+        let vectors: Vec<_> = decode_vectors(&bytes, interrupts)
+            .into_iter()
+            .filter(|v| if selection.is_empty() { v.name != "Reset" } else { vector_selected(v, selection) })
+            .collect();
+
+        let mut handlers = Vec::new();
+        let mut inserted_count = 0;
+        for (address, names) in group_by_handler(&vectors) {
+            let location = format!("*{:#x}", address);
+            let existing = self.tracker().find_breakpoints(&location).into_iter().next();
+            let mut handler = HandlerBreakpoint {
+                address: format!("{:#x}", address),
+                function: None,
+                vectors: names,
+                breakpoint: None,
+                existing: existing.is_some(),
+                error: None,
+            };
+            let inserted = match existing {
+                Some(tracked) => Ok(tracked.breakpoint),
+                None if max_new.is_some_and(|max| inserted_count >= max) => {
+                    Err(anyhow!("Not inserted: the session's breakpoint limit is reached"))
+                }
+                None => {
+                    inserted_count += 1;
+                    self.break_insert(&location, &BreakInsertOptions::default())
+                }
+            };
+            match inserted {
+                Ok(bp) => {
+                    handler.function = bp.func;
+                    handler.breakpoint = Some(bp.number);
+                }
+                Err(e) => handler.error = Some(e.to_string()),
+            }
+            handlers.push(handler);
+        }
+
+        Ok(VectorBreakpoints { base: format!("{:#x}", base), handlers })
+    }
+
     /// Delete a breakpoint
     pub fn break_delete(&mut self, number: &str) -> Result<()> {
         let response = self.send_command(&format!("break-delete {}", number))?;
         
         match response {
-            MiOutputRecord::Result { class: ResultClass::Done, .. } => Ok(()),
+            MiOutputRecord::Result { class: ResultClass::Done, .. } => self.refresh_breakpoints(),
             MiOutputRecord::Result { class: ResultClass::Error, results, .. } => {
-                let msg = results.iter()
-                    .find(|r| r.variable == "msg")
-                    .and_then(|r| {
-                        if let MiValue::String(s) = &r.value {
-                            Some(s.clone())
-                        } else {
-                            None
-                        }
-                    })
-                    .unwrap_or_else(|| "Unknown error".to_string());
-                Err(anyhow!("Failed to delete breakpoint: {}", msg))
+                Err(mi_error(&results, "Failed to delete breakpoint"))
             }
             _ => Err(anyhow!("Unexpected response")),
         }
@@ -666,7 +1563,8 @@ impl GdbClient {
         let response = self.send_command(&format!("break-enable {}", number))?;
         
         match response {
-            MiOutputRecord::Result { class: ResultClass::Done, .. } => Ok(()),
+            MiOutputRecord::Result { class: ResultClass::Done, .. } => self.refresh_breakpoints(),
+            MiOutputRecord::Result { class: ResultClass::Error, results, .. } => Err(mi_error(&results, "Failed to enable breakpoint")),
             _ => Err(anyhow!("Failed to enable breakpoint")),
         }
     }
@@ -676,23 +1574,138 @@ impl GdbClient {
         let response = self.send_command(&format!("break-disable {}", number))?;
         
         match response {
-            MiOutputRecord::Result { class: ResultClass::Done, .. } => Ok(()),
+            MiOutputRecord::Result { class: ResultClass::Done, .. } => self.refresh_breakpoints(),
+            MiOutputRecord::Result { class: ResultClass::Error, results, .. } => Err(mi_error(&results, "Failed to disable breakpoint")),
             _ => Err(anyhow!("Failed to disable breakpoint")),
         }
     }
 
-    /// List breakpoints
+    /// Enable all breakpoints
+    pub fn break_enable_all(&mut self) -> Result<()> {
+        let response = self.send_command("break-enable")?;
+        
+        match response {
+            MiOutputRecord::Result { class: ResultClass::Done, .. } => self.refresh_breakpoints(),
+            MiOutputRecord::Result { class: ResultClass::Error, results, .. } => Err(mi_error(&results, "Failed to enable breakpoints")),
+            _ => Err(anyhow!("Failed to enable breakpoints")),
+        }
+    }
+
+    /// Disable all breakpoints
+    pub fn break_disable_all(&mut self) -> Result<()> {
+        let response = self.send_command("break-disable")?;
+        
+        match response {
+            MiOutputRecord::Result { class: ResultClass::Done, .. } => self.refresh_breakpoints(),
+            MiOutputRecord::Result { class: ResultClass::Error, results, .. } => Err(mi_error(&results, "Failed to disable breakpoints")),
+            _ => Err(anyhow!("Failed to disable breakpoints")),
+        }
+    }
+
+    /// Disable every enabled watchpoint, remembering them for
+    /// `watch_resume`. Returns the numbers disabled.
+    pub fn watch_suspend(&mut self) -> Result<Vec<String>> {
+        let numbers: Vec<String> = self.break_list()?
+            .into_iter()
+            .filter(|bp| bp.enabled && bp.breakpoint_type.contains("watchpoint"))
+            .map(|bp| bp.number)
+            .collect();
+        if !numbers.is_empty() {
+            self.break_disable(&numbers.join(" "))?;
+        }
+        let mut state = self.state.lock().unwrap();
+        for number in &numbers {
+            if !state.suspended_watchpoints.contains(number) {
+                state.suspended_watchpoints.push(number.clone());
+            }
+        }
+        Ok(numbers)
+    }
+
+    /// Re-enable the watchpoints suspended by `watch_suspend` that still
+    /// exist. Returns the numbers enabled.
+    pub fn watch_resume(&mut self) -> Result<Vec<String>> {
+        let suspended = self.state().suspended_watchpoints;
+        let existing = self.break_list()?;
+        let numbers: Vec<String> = suspended
+            .into_iter()
+            .filter(|number| existing.iter().any(|bp| &bp.number == number))
+            .collect();
+        if !numbers.is_empty() {
+            self.break_enable(&numbers.join(" "))?;
+        }
+        self.state.lock().unwrap().suspended_watchpoints.clear();
+        Ok(numbers)
+    }
+
+    /// Ignore the next `count` hits of a breakpoint
+    pub fn break_after(&mut self, number: &str, count: u64) -> Result<()> {
+        let response = self.send_command(&format!("break-after {} {}", number, count))?;
+
+        match response {
+            MiOutputRecord::Result { class: ResultClass::Done, .. } => self.refresh_breakpoints(),
+            MiOutputRecord::Result { class: ResultClass::Error, results, .. } => Err(mi_error(&results, "Failed to set ignore count")),
+            _ => Err(anyhow!("Failed to set ignore count")),
+        }
+    }
+
+    /// Replace the commands GDB runs when a breakpoint is hit
+    pub fn break_commands(&mut self, number: &str, commands: &[String]) -> Result<()> {
+        let quoted: Vec<String> = commands.iter()
+            .map(|c| format!("\"{}\"", c.replace('\\', "\\\\").replace('"', "\\\"")))
+            .collect();
+        let response = self.send_command(&format!("break-commands {} {}", number, quoted.join(" ")))?;
+
+        match response {
+            MiOutputRecord::Result { class: ResultClass::Done, .. } => self.refresh_breakpoints(),
+            MiOutputRecord::Result { class: ResultClass::Error, results, .. } => Err(mi_error(&results, "Failed to set breakpoint commands")),
+            _ => Err(anyhow!("Failed to set breakpoint commands")),
+        }
+    }
+
+    /// List breakpoints, resynchronizing the tracked breakpoints
     pub fn break_list(&mut self) -> Result<Vec<Breakpoint>> {
         let response = self.send_command("break-list")?;
         
         match response {
             MiOutputRecord::Result { class: ResultClass::Done, results, .. } => {
-                Ok(parse_breakpoint_list(&results))
+                let breakpoints = parse_breakpoint_list(&results);
+                self.tracker().sync_breakpoints(breakpoints.clone());
+                Ok(breakpoints)
             }
             _ => Ok(Vec::new()),
         }
     }
 
+    /// Evaluation and stop counts of the conditional breakpoints (or of one)
+    pub fn break_stats(&mut self, number: Option<&str>) -> Result<Vec<BreakStats>> {
+        let breakpoints = self.break_list()?;
+        let mut stats = Vec::new();
+        for bp in breakpoints.iter().filter(|bp| bp.condition.is_some() && number.is_none_or(|n| n == bp.number)) {
+            let evaluations = match &bp.evaluation_counter {
+                Some(counter) => self.evaluate_u64(counter).ok(),
+                None => None,
+            };
+            stats.push(BreakStats::new(bp, evaluations));
+        }
+        Ok(stats)
+    }
+
+    /// Re-read the breakpoint table after a change GDB does not notify about
+    fn refresh_breakpoints(&mut self) -> Result<()> {
+        self.break_list().map(|_| ())
+    }
+
+    /// Export the session as a GDB command script
+    pub fn export_script(&mut self, include_history: bool) -> Result<String> {
+        let breakpoints = self.break_list()?;
+        let mut state = self.state();
+        if state.architecture.is_none() {
+            state.architecture = self.config.architecture.clone();
+        }
+        Ok(build_script(&state, &breakpoints, &self.history, include_history))
+    }
+
     /// Insert a watchpoint
     pub fn watch_insert(&mut self, wp_type: WatchpointType, location: &str) -> Result<Watchpoint> {
         let type_arg = match wp_type {
@@ -711,66 +1724,398 @@ impl GdbClient {
         
         match response {
             MiOutputRecord::Result { class: ResultClass::Done, results, .. } => {
-                parse_watchpoint(&results, wp_type)
-                    .ok_or_else(|| anyhow!("Failed to parse watchpoint response"))
+                let wp = parse_watchpoint(&results, wp_type)
+                    .ok_or_else(|| anyhow!("Failed to parse watchpoint response"))?;
+                self.refresh_breakpoints()?;
+                Ok(wp)
             }
             MiOutputRecord::Result { class: ResultClass::Error, results, .. } => {
-                let msg = results.iter()
-                    .find(|r| r.variable == "msg")
-                    .and_then(|r| MiParser::extract_string(&r.value))
-                    .unwrap_or_else(|| "Unknown error".to_string());
-                Err(anyhow!("Failed to insert watchpoint: {}", msg))
+                Err(mi_error(&results, "Failed to insert watchpoint"))
+            }
+            _ => Err(anyhow!("Unexpected response")),
+        }
+    }
+
+    /// Insert a catchpoint for shared library load or unload events
+    pub fn catch_library(&mut self, unload: bool, regex: Option<&str>, temporary: bool) -> Result<Breakpoint> {
+        self.require(BackendFeature::Catchpoints, "Library catchpoints")?;
+        let mut cmd = String::from(if unload { "catch-unload" } else { "catch-load" });
+        if temporary {
+            cmd.push_str(" -t");
+        }
+        cmd.push_str(&format!(" \"{}\"", regex.unwrap_or("")));
+
+        let response = self.send_command(&cmd)?;
+
+        match response {
+            MiOutputRecord::Result { class: ResultClass::Done, results, .. } => {
+                let bp = parse_breakpoint(&results)
+                    .ok_or_else(|| anyhow!("Failed to parse catchpoint response"))?;
+                self.tracker().record_breakpoint(bp.clone(), None);
+                Ok(bp)
+            }
+            MiOutputRecord::Result { class: ResultClass::Error, results, .. } => {
+                Err(mi_error(&results, "Failed to insert catchpoint"))
             }
             _ => Err(anyhow!("Unexpected response")),
         }
     }
 
+    /// Stop whenever shared libraries are loaded or unloaded
+    pub fn set_stop_on_solib_events(&mut self, enabled: bool) -> Result<()> {
+        self.require(BackendFeature::GdbCli, "stop-on-solib-events")?;
+        let value = if enabled { 1 } else { 0 };
+        let response = self.send_command(&format!("gdb-set stop-on-solib-events {}", value))?;
+
+        match response {
+            MiOutputRecord::Result { class: ResultClass::Done, .. } => {
+                self.record_history(format!("set stop-on-solib-events {}", value));
+                Ok(())
+            }
+            MiOutputRecord::Result { class: ResultClass::Error, results, .. } => Err(mi_error(&results, "Failed to set stop-on-solib-events")),
+            _ => Err(anyhow!("Failed to set stop-on-solib-events")),
+        }
+    }
+
     /// Start execution
     pub fn exec_run(&mut self) -> Result<()> {
-        let response = self.send_command("exec-run")?;
+        let args = self.capture_program_output();
+        let stops = {
+            let mut state = self.state.lock().unwrap();
+            state.program_exit = None;
+            state.stop_count
+        };
+        let response = self.send_command("exec-run");
+        if let Some(args) = args {
+            // The redirection only applies to this run
+            let _ = self.send_command(&format!("exec-arguments {}", args));
+        }
         
-        match response {
+        match response? {
             MiOutputRecord::Result { class: ResultClass::Running, .. } => {
+                // A program that exits at once may already have stopped
                 let mut state = self.state.lock().unwrap();
-                state.running = true;
+                state.running = state.stop_count == stops;
                 Ok(())
             }
             MiOutputRecord::Result { class: ResultClass::Error, results, .. } => {
-                let msg = results.iter()
-                    .find(|r| r.variable == "msg")
-                    .and_then(|r| {
-                        if let MiValue::String(s) = &r.value {
-                            Some(s.clone())
-                        } else {
-                            None
-                        }
-                    })
-                    .unwrap_or_else(|| "Unknown error".to_string());
-                Err(anyhow!("Failed to run: {}", msg))
+                Err(mi_error(&results, "Failed to run"))
             }
             _ => Err(anyhow!("Unexpected response")),
         }
     }
 
+    /// Give a native program its own terminal before `-exec-run`, and send
+    /// its stderr to a second one when the startup shell can redirect it.
+    /// Returns the arguments to restore after the run, if they were changed.
+    fn capture_program_output(&mut self) -> Option<String> {
+        if self.state().target_remote || !self.config.backend.backend().supports(BackendFeature::GdbCli) {
+            return None;
+        }
+        if self.inferior_tty.is_none() {
+            match InferiorTty::open() {
+                Ok(tty) => self.inferior_tty = Some(tty),
+                Err(e) => {
+                    warn!("Program output goes to GDB's stdout: {:#}", e);
+                    return None;
+                }
+            }
+        }
+        let (tty, stderr_tty) = self.inferior_tty.as_ref().map(|t| (t.tty().to_string(), t.stderr_tty().to_string()))?;
+        if let Err(e) = self.send_command(&format!("inferior-tty-set {}", tty)) {
+            warn!("Failed to set the program's terminal: {:#}", e);
+            return None;
+        }
+
+        let shell = self.console_command("show startup-with-shell").is_ok_and(|out| out.contains(" on"));
+        let args = self.console_command("show args").ok().and_then(|out| parse_show_args(&out));
+        let redirect = match args {
+            Some(args) if shell && !args.contains("2>") => self
+                .send_command(&format!("exec-arguments {}", with_stderr_redirect(&args, &stderr_tty)))
+                .is_ok()
+                .then_some(args),
+            _ => None,
+        };
+        if let Some(tty) = self.inferior_tty.as_mut() {
+            tty.start_run(redirect.is_some());
+        }
+        redirect
+    }
+
+    /// Output and exit status of the program started with `-exec-run`
+    pub fn program_output(&self, clear: bool) -> Result<ProgramOutput> {
+        let tty = self.inferior_tty.as_ref()
+            .ok_or_else(|| anyhow!("No program output has been captured; it is only captured for native programs started with gdb_run"))?;
+        let state = self.state();
+        let output = tty.output(state.running, state.program_exit);
+        if clear {
+            tty.clear();
+        }
+        Ok(output)
+    }
+
     /// Continue execution
     pub fn exec_continue(&mut self) -> Result<()> {
+        let stops = self.state().stop_count;
+        self.exec_continue_no_wait()?;
+        self.wait_for_stop(stops, 60000)
+    }
+
+    /// Start (`run`) or resume execution and wait up to `max_runtime_ms`
+    /// for the target to stop, interrupting it when the budget runs out.
+    /// Returns whether the target had to be interrupted.
+    pub fn exec_with_budget(&mut self, run: bool, max_runtime_ms: u64) -> Result<bool> {
+        let stops = self.state().stop_count;
+        if run {
+            self.exec_run()?;
+        } else {
+            self.exec_continue_no_wait()?;
+        }
+
+        if self.wait_for_stop(stops, max_runtime_ms).is_ok() {
+            return Ok(false);
+        }
+        self.exec_interrupt()?;
+        Ok(true)
+    }
+
+    /// [`exec_with_budget`](Self::exec_with_budget), interrupting the target
+    /// every `interval_ms` to sample its PC and resuming it. Returns whether
+    /// the budget ran out, and the samples; the last one is where the target
+    /// was left stopped. An interval of 0 disables sampling. Sample stops
+    /// are kept out of the stop history and timeline; only the final
+    /// interrupt is recorded as a stop.
+    pub fn exec_with_sampling(&mut self, run: bool, max_runtime_ms: u64, interval_ms: u64) -> Result<(bool, Vec<PcSample>)> {
+        if interval_ms == 0 {
+            return Ok((self.exec_with_budget(run, max_runtime_ms)?, Vec::new()));
+        }
+        let started = Instant::now();
+        let budget = Duration::from_millis(max_runtime_ms);
+        let mut samples = Vec::new();
+        let mut stops = self.state().stop_count;
+        if run {
+            self.exec_run()?;
+        } else {
+            self.exec_continue_no_wait()?;
+        }
+
+        loop {
+            let remaining = budget.saturating_sub(started.elapsed()).as_millis() as u64;
+            let last = remaining <= interval_ms;
+            if self.wait_for_stop(stops, interval_ms.min(remaining)).is_ok() {
+                return Ok((false, samples));
+            }
+            self.state.lock().unwrap().pc_sampling = !last;
+            let interrupted = self.exec_interrupt();
+            let sample = {
+                let mut state = self.state.lock().unwrap();
+                state.pc_sampling = false;
+                state.pc_sample.take()
+            };
+            interrupted?;
+            // A stop other than our interrupt (e.g. a breakpoint hit just
+            // before it) ends the run as if no interrupt had been sent
+            let stop = sample.or_else(|| self.tracker().last_stop().cloned());
+            let Some(stop) = stop.filter(is_interrupt_stop) else {
+                return Ok((false, samples));
+            };
+            if let Some(frame) = &stop.frame {
+                samples.push(PcSample {
+                    elapsed_ms: started.elapsed().as_millis() as u64,
+                    addr: frame.addr.clone(),
+                    func: frame.func.clone(),
+                    thread_id: stop.thread_id.clone(),
+                });
+            }
+            if last {
+                return Ok((true, samples));
+            }
+            stops = self.state().stop_count;
+            self.exec_continue_no_wait()?;
+        }
+    }
+
+    /// Continue with every breakpoint except `number` disabled, so only that
+    /// breakpoint (or a signal or exit) stops the target. The breakpoints
+    /// are restored afterwards. Returns whether the target had to be
+    /// interrupted after `max_runtime_ms`.
+    pub fn continue_to_breakpoint(&mut self, number: &str, max_runtime_ms: u64) -> Result<bool> {
+        let breakpoints = self.break_list()?;
+        let target = breakpoints.iter()
+            .find(|b| b.number == number)
+            .ok_or_else(|| anyhow!("No breakpoint number {}", number))?;
+        let enable_target = !target.enabled;
+        let others = breakpoints.iter()
+            .filter(|b| b.enabled && b.number != number)
+            .map(|b| b.number.as_str())
+            .collect::<Vec<_>>()
+            .join(" ");
+
+        if !others.is_empty() {
+            self.break_disable(&others)?;
+        }
+        let mut run = if enable_target { self.break_enable(number) } else { Ok(()) }
+            .and_then(|_| self.exec_with_budget(false, max_runtime_ms));
+
+        if !others.is_empty() {
+            run = self.break_enable(&others).and(run);
+        }
+        // A temporary breakpoint is gone once hit
+        if enable_target && self.tracker().breakpoint(number).is_some() {
+            run = self.break_disable(number).and(run);
+        }
+        run
+    }
+
+    /// Continue until the `count`th hit of a breakpoint, resuming at the
+    /// earlier hits and logging each one. The breakpoint is `number` (other
+    /// breakpoints are disabled as with `continue_to_breakpoint`) or the
+    /// first one hit. Any other stop ends the run, as does `max_runtime_ms`
+    /// running out for all the continues together.
+    pub fn continue_hits(&mut self, number: Option<&str>, count: u64, max_runtime_ms: u64) -> Result<HitCountRun> {
+        let deadline = Instant::now() + Duration::from_millis(max_runtime_ms);
+        let mut breakpoint = number.map(|n| n.to_string());
+        let mut hits = Vec::new();
+
+        let stopped_because = loop {
+            let remaining = deadline.saturating_duration_since(Instant::now()).as_millis() as u64;
+            let interrupted = match number {
+                Some(number) => self.continue_to_breakpoint(number, remaining)?,
+                None => self.exec_with_budget(false, remaining)?,
+            };
+            if interrupted {
+                break "timeout".to_string();
+            }
+
+            let stop = self.last_stop();
+            let hit = stop.as_ref()
+                .filter(|s| s.reason == StopReason::BreakpointHit)
+                .and_then(|_| self.tracker().breakpoint_hits(None, 1).pop());
+            let Some(hit) = hit else {
+                break stop.map(|s| s.reason.as_str().to_string()).unwrap_or_else(|| "unknown".to_string());
+            };
+            if breakpoint.as_ref().is_some_and(|b| *b != hit.number) {
+                break format!("breakpoint {} hit", hit.number);
+            }
+            breakpoint = Some(hit.number.clone());
+
+            let frame = hit.frame.as_ref();
+            hits.push(HitLogEntry {
+                hit: hits.len() as u64 + 1,
+                hit_count: hit.hit_count,
+                addr: frame.map(|f| f.addr.clone()).unwrap_or_default(),
+                func: frame.and_then(|f| f.func.clone()),
+                file: frame.and_then(|f| f.file.clone()),
+                line: frame.and_then(|f| f.line),
+                thread_id: hit.thread_id.clone(),
+            });
+            if hits.len() as u64 >= count {
+                break "completed".to_string();
+            }
+        };
+
+        Ok(HitCountRun { breakpoint, requested: count, stopped_because, hits })
+    }
+
+    /// Break on the context switch function and log the outgoing and
+    /// incoming task of each hit, resuming until `max_switches` switches,
+    /// another stop, or `max_runtime_ms` for the whole trace. The breakpoint
+    /// is deleted afterwards.
+    pub fn trace_context_switches(&mut self, spec: &SwitchTraceSpec, max_switches: u64, max_runtime_ms: u64) -> Result<ContextSwitchTrace> {
+        let convention = CONVENTIONS.iter().find(|c| self.data_evaluate_expression(c.probe).is_ok());
+        let argument = |given: &Option<String>, index: usize| {
+            given.clone()
+                .or_else(|| convention.map(|c| c.args[index].to_string()))
+                .ok_or_else(|| anyhow!("Unknown calling convention for this target; pass the prev and next expressions"))
+        };
+        let prev = argument(&spec.prev, 0)?;
+        let next = argument(&spec.next, 1)?;
+
+        let breakpoint = self.break_insert(&format!("*{}", spec.symbol), &BreakInsertOptions::default())
+            .map_err(|e| anyhow!("Cannot break on {}: {}", spec.symbol, e))?;
+        let mut switches = Vec::new();
+        let traced = self.collect_switches(&breakpoint.number, spec, [&prev, &next], max_switches, max_runtime_ms, &mut switches);
+        let deleted = self.break_delete(&breakpoint.number);
+        let stopped_because = traced?;
+        deleted?;
+
+        Ok(ContextSwitchTrace {
+            symbol: spec.symbol.clone(),
+            prev_expression: prev,
+            next_expression: next,
+            tasks: switch_counts(&switches),
+            switches,
+            stopped_because,
+        })
+    }
+
+    /// Resume and read both tasks at each hit of `number`; returns why the
+    /// trace ended
+    fn collect_switches(
+        &mut self,
+        number: &str,
+        spec: &SwitchTraceSpec,
+        [prev, next]: [&str; 2],
+        max_switches: u64,
+        max_runtime_ms: u64,
+        switches: &mut Vec<ContextSwitch>,
+    ) -> Result<String> {
+        let deadline = Instant::now() + Duration::from_millis(max_runtime_ms);
+        loop {
+            let remaining = deadline.saturating_duration_since(Instant::now()).as_millis() as u64;
+            if self.exec_with_budget(false, remaining)? {
+                return Ok("timeout".to_string());
+            }
+
+            let stop = self.last_stop();
+            let hit = stop.as_ref()
+                .filter(|s| s.reason == StopReason::BreakpointHit)
+                .and_then(|_| self.tracker().breakpoint_hits(None, 1).pop());
+            let Some(hit) = hit else {
+                return Ok(stop.map(|s| s.reason.as_str().to_string()).unwrap_or_else(|| "unknown".to_string()));
+            };
+            if hit.number != number {
+                return Ok(format!("breakpoint {} hit", hit.number));
+            }
+
+            switches.push(ContextSwitch {
+                switch: switches.len() as u64 + 1,
+                thread_id: hit.thread_id.clone(),
+                prev: self.read_task(spec, prev),
+                next: self.read_task(spec, next),
+            });
+            if switches.len() as u64 >= max_switches {
+                return Ok("completed".to_string());
+            }
+        }
+    }
+
+    /// Value and fields of one task; values that cannot be read are shown
+    /// as `<error>`
+    fn read_task(&mut self, spec: &SwitchTraceSpec, task: &str) -> TaskRef {
+        let mut read = |expression: &str| match self.data_evaluate_expression(expression) {
+            Ok(value) => trim_string_padding(&value),
+            Err(e) => format!("<{}>", e),
+        };
+        let value = read(task);
+        let fields = spec.fields.iter()
+            .map(|field| (field.clone(), read(&spec.field_expression(task, field))))
+            .collect();
+        TaskRef { value, fields }
+    }
+
+    /// Resume execution without waiting for the target to stop
+    fn exec_continue_no_wait(&mut self) -> Result<()> {
         let response = self.send_command("exec-continue")?;
         
         match response {
             MiOutputRecord::Result { class: ResultClass::Running, .. } => {
-                {
-                    let mut state = self.state.lock().unwrap();
-                    state.running = true;
-                }
-                self.wait_for_stop(60000)?;
+                let mut state = self.state.lock().unwrap();
+                state.running = true;
                 Ok(())
             }
             MiOutputRecord::Result { class: ResultClass::Error, results, .. } => {
-                let msg = results.iter()
-                    .find(|r| r.variable == "msg")
-                    .and_then(|r| MiParser::extract_string(&r.value))
-                    .unwrap_or_else(|| "Unknown error".to_string());
-                Err(anyhow!("Failed to continue: {}", msg))
+                Err(mi_error(&results, "Failed to continue"))
             }
             _ => Err(anyhow!("Unexpected response")),
         }
@@ -778,20 +2123,17 @@ impl GdbClient {
 
     /// Step over
     pub fn exec_next(&mut self) -> Result<()> {
+        let stops = self.state().stop_count;
         let response = self.send_command("exec-next")?;
         
         match response {
             MiOutputRecord::Result { class: ResultClass::Running, .. } => {
-                self.wait_for_stop(5000)?;
+                self.wait_for_stop(stops, 5000)?;
                 Ok(())
             }
             MiOutputRecord::Result { class: ResultClass::Done, .. } => Ok(()),
             MiOutputRecord::Result { class: ResultClass::Error, results, .. } => {
-                let msg = results.iter()
-                    .find(|r| r.variable == "msg")
-                    .and_then(|r| MiParser::extract_string(&r.value))
-                    .unwrap_or_else(|| "Unknown error".to_string());
-                Err(anyhow!("Failed to step: {}", msg))
+                Err(mi_error(&results, "Failed to step"))
             }
             other => {
                 debug!("Unexpected step response: {:?}", other);
@@ -802,20 +2144,17 @@ impl GdbClient {
 
     /// Step into
     pub fn exec_step(&mut self) -> Result<()> {
+        let stops = self.state().stop_count;
         let response = self.send_command("exec-step")?;
         
         match response {
             MiOutputRecord::Result { class: ResultClass::Running, .. } => {
-                self.wait_for_stop(5000)?;
+                self.wait_for_stop(stops, 5000)?;
                 Ok(())
             }
             MiOutputRecord::Result { class: ResultClass::Done, .. } => Ok(()),
             MiOutputRecord::Result { class: ResultClass::Error, results, .. } => {
-                let msg = results.iter()
-                    .find(|r| r.variable == "msg")
-                    .and_then(|r| MiParser::extract_string(&r.value))
-                    .unwrap_or_else(|| "Unknown error".to_string());
-                Err(anyhow!("Failed to step: {}", msg))
+                Err(mi_error(&results, "Failed to step"))
             }
             other => {
                 debug!("Unexpected step response: {:?}", other);
@@ -826,20 +2165,27 @@ impl GdbClient {
 
     /// Step one instruction (assembly level)
     pub fn exec_step_instruction(&mut self) -> Result<()> {
-        let response = self.send_command("exec-step-instruction")?;
+        self.exec_step_instructions(1)
+    }
+
+    /// Step `count` instructions with a single command. GDB reports one
+    /// `*stopped` at the end, or earlier if a breakpoint or signal stops it.
+    pub fn exec_step_instructions(&mut self, count: u64) -> Result<()> {
+        let stops = self.state().stop_count;
+        let command = match count {
+            1 => "exec-step-instruction".to_string(),
+            n => format!("exec-step-instruction {}", n),
+        };
+        let response = self.send_command(&command)?;
         
         match response {
             MiOutputRecord::Result { class: ResultClass::Running, .. } => {
-                self.wait_for_stop(5000)?;
+                self.wait_for_stop(stops, instruction_step_timeout(count))?;
                 Ok(())
             }
             MiOutputRecord::Result { class: ResultClass::Done, .. } => Ok(()),
             MiOutputRecord::Result { class: ResultClass::Error, results, .. } => {
-                let msg = results.iter()
-                    .find(|r| r.variable == "msg")
-                    .and_then(|r| MiParser::extract_string(&r.value))
-                    .unwrap_or_else(|| "Unknown error".to_string());
-                Err(anyhow!("Failed to step instruction: {}", msg))
+                Err(mi_error(&results, "Failed to step instruction"))
             }
             other => {
                 debug!("Unexpected step response: {:?}", other);
@@ -848,22 +2194,24 @@ impl GdbClient {
         }
     }
 
-    /// Next one instruction (assembly level)
-    pub fn exec_next_instruction(&mut self) -> Result<()> {
-        let response = self.send_command("exec-next-instruction")?;
+    /// Next `count` instructions with a single command. GDB reports one
+    /// `*stopped` at the end, or earlier if a breakpoint or signal stops it.
+    pub fn exec_next_instructions(&mut self, count: u64) -> Result<()> {
+        let stops = self.state().stop_count;
+        let command = match count {
+            1 => "exec-next-instruction".to_string(),
+            n => format!("exec-next-instruction {}", n),
+        };
+        let response = self.send_command(&command)?;
         
         match response {
             MiOutputRecord::Result { class: ResultClass::Running, .. } => {
-                self.wait_for_stop(5000)?;
+                self.wait_for_stop(stops, instruction_step_timeout(count))?;
                 Ok(())
             }
             MiOutputRecord::Result { class: ResultClass::Done, .. } => Ok(()),
             MiOutputRecord::Result { class: ResultClass::Error, results, .. } => {
-                let msg = results.iter()
-                    .find(|r| r.variable == "msg")
-                    .and_then(|r| MiParser::extract_string(&r.value))
-                    .unwrap_or_else(|| "Unknown error".to_string());
-                Err(anyhow!("Failed to next instruction: {}", msg))
+                Err(mi_error(&results, "Failed to next instruction"))
             }
             other => {
                 debug!("Unexpected next response: {:?}", other);
@@ -872,22 +2220,538 @@ impl GdbClient {
         }
     }
 
-    /// Wait for the target to stop
-    fn wait_for_stop(&self, timeout_ms: u64) -> Result<()> {
-        let start = std::time::Instant::now();
-        loop {
-            let state = self.state.lock().unwrap();
-            if !state.running {
-                return Ok(());
+    /// Repeat a stepping command up to `count` times, recording where each
+    /// step stopped
+    ///
+    /// Like GDB's `next N`, stepping ends early when a step stops for any
+    /// reason other than finishing the step (a breakpoint, a signal, the
+    /// program exiting). Errors end the sequence but keep the stops so far.
+    pub fn step_repeat(&mut self, step: fn(&mut Self) -> Result<()>, count: u64) -> StepSequence {
+        let mut stops = Vec::new();
+        let mut stopped_because = "completed".to_string();
+        let mut error = None;
+        let mut completed = 0;
+
+        while completed < count {
+            let stops_before = self.state().stop_count;
+            if let Err(e) = step(self) {
+                stopped_because = "error".to_string();
+                error = Some(e.to_string());
+                break;
             }
-            drop(state);
-            
-            if start.elapsed().as_millis() as u64 > timeout_ms {
-                return Err(anyhow!("Timeout waiting for target to stop"));
+            completed += 1;
+
+            // A step answered with ^done and no *stopped record leaves the
+            // previous step's stop as the last one; don't report it twice
+            if self.state().stop_count == stops_before {
+                stopped_because = "no-stop".to_string();
+                break;
+            }
+            let stop = self.tracker().last_stop().cloned();
+            if let Some(frame) = stop.as_ref().and_then(|s| s.frame.as_ref()) {
+                stops.push(StepTraceEntry {
+                    step: completed,
+                    addr: frame.addr.clone(),
+                    func: frame.func.clone(),
+                    file: frame.file.clone(),
+                    line: frame.line,
+                });
+            }
+            if let Some(stop) = stop.filter(|s| s.reason != StopReason::EndSteppingRange) {
+                stopped_because = stop.reason.as_str().to_string();
+                break;
             }
-            
-            std::thread::sleep(std::time::Duration::from_millis(50));
         }
+
+        StepSequence {
+            requested: count,
+            completed,
+            stopped_because,
+            error,
+            stops,
+        }
+    }
+
+    /// Single-step instructions until the value produced by `read` changes
+    ///
+    /// Stepping also ends when `max_steps` or `timeout_ms` is exhausted, or
+    /// when the target stops for a reason that prevents further stepping.
+    pub fn step_until_change<F>(
+        &mut self,
+        mut read: F,
+        max_steps: u64,
+        timeout_ms: u64,
+        trace_limit: usize,
+    ) -> Result<StepUntilChange>
+    where
+        F: FnMut(&mut Self) -> Result<String>,
+    {
+        let start = std::time::Instant::now();
+        let before = read(self)?;
+        let mut after = before.clone();
+        let mut trace = std::collections::VecDeque::new();
+        let mut steps = 0;
+        let mut stopped_because = "max_steps".to_string();
+
+        while steps < max_steps {
+            if start.elapsed().as_millis() as u64 > timeout_ms {
+                stopped_because = "timeout".to_string();
+                break;
+            }
+
+            self.exec_step_instruction()?;
+            steps += 1;
+
+            let stop = self.tracker().last_stop().cloned();
+            if let Some(frame) = stop.as_ref().and_then(|s| s.frame.as_ref()) {
+                trace.push_back(StepTraceEntry {
+                    step: steps,
+                    addr: frame.addr.clone(),
+                    func: frame.func.clone(),
+                    file: frame.file.clone(),
+                    line: frame.line,
+                });
+                if trace.len() > trace_limit {
+                    trace.pop_front();
+                }
+            }
+            if let Some(stop) = stop.filter(|s| s.reason.is_terminal()) {
+                stopped_because = stop.reason.as_str().to_string();
+                break;
+            }
+
+            after = read(self)?;
+            if after != before {
+                stopped_because = "changed".to_string();
+                break;
+            }
+        }
+
+        Ok(StepUntilChange {
+            changed: after != before,
+            stopped_because,
+            steps,
+            before,
+            after,
+            trace: trace.into_iter().collect(),
+        })
+    }
+
+    /// Resume until `[addr, addr + length)` of `address` is written. `auto`
+    /// tries a hardware watchpoint, then page guards, then single-stepping;
+    /// the whole watch gets `max_runtime_ms`, and stepping at most
+    /// `max_steps` instructions.
+    pub fn watch_region(&mut self, address: &str, length: u64, mode: WatchMode, max_runtime_ms: u64, max_steps: u64) -> Result<RegionWatch> {
+        let addr = self.evaluate_u64(&format!("(unsigned long)({})", address))?;
+        let started = Instant::now();
+        let deadline = started + Duration::from_millis(max_runtime_ms);
+        let before = self.read_region(addr, length)?;
+        let mut watch = RegionWatch {
+            address: format!("{:#x}", addr),
+            length,
+            mode,
+            fallbacks: Vec::new(),
+            tradeoff: String::new(),
+            write: None,
+            stopped_because: String::new(),
+            elapsed_ms: 0,
+            steps: None,
+            faults: None,
+            false_positives: None,
+        };
+
+        let candidates: &[WatchMode] = match mode {
+            WatchMode::Auto => &[WatchMode::Hardware, WatchMode::Guard, WatchMode::Step],
+            _ => std::slice::from_ref(&mode),
+        };
+        for (i, &candidate) in candidates.iter().enumerate() {
+            watch.mode = candidate;
+            let watched = match candidate {
+                WatchMode::Hardware => self.watch_region_hardware(addr, &before, deadline, &mut watch),
+                WatchMode::Guard => self.watch_region_guard(addr, &before, deadline, &mut watch),
+                _ => self.watch_region_step(addr, &before, deadline, max_steps, &mut watch),
+            };
+            match watched {
+                Ok(()) => break,
+                // Only a mode that never resumed the target can fall back
+                Err(e) if i + 1 < candidates.len() && watch.stopped_because.is_empty() => {
+                    watch.fallbacks.push(format!("{}: {}", candidate.as_str(), e));
+                }
+                Err(e) => return Err(e),
+            }
+        }
+        watch.tradeoff = watch.mode.tradeoff().to_string();
+        watch.elapsed_ms = started.elapsed().as_millis() as u64;
+        Ok(watch)
+    }
+
+    fn read_region(&mut self, addr: u64, length: u64) -> Result<Vec<u8>> {
+        let memory = self.data_read_memory(&format!("{:#x}", addr), length)?;
+        Ok(parse_hex_bytes(&memory.data.concat()))
+    }
+
+    /// Watch with a temporary hardware watchpoint on the whole region.
+    /// Fails before resuming when GDB could only make it a software one.
+    fn watch_region_hardware(&mut self, addr: u64, before: &[u8], deadline: Instant, watch: &mut RegionWatch) -> Result<()> {
+        let length = before.len();
+        let wp = self.watch_insert(WatchpointType::Write, &format!("*(char (*)[{}]){:#x}", length, addr))?;
+        let software = self.break_list()
+            .map(|list| list.iter().any(|b| b.number == wp.number && !b.breakpoint_type.starts_with("hw")))
+            .unwrap_or(false);
+        let run = if software {
+            Err(anyhow!("no hardware watchpoint can cover {} bytes", length))
+        } else {
+            let remaining = deadline.saturating_duration_since(Instant::now()).as_millis() as u64;
+            self.exec_with_budget(false, remaining)
+        };
+        let deleted = self.break_delete(&wp.number);
+        let interrupted = run.map_err(|e| match is_hardware_limit(&e.to_string()) || software {
+            true => anyhow!("hardware watchpoint unavailable: {}", e),
+            false => e,
+        })?;
+        deleted?;
+
+        let stop = self.last_stop();
+        watch.stopped_because = match &stop {
+            _ if interrupted => "timeout".to_string(),
+            Some(s) if s.reason == StopReason::WatchpointTrigger => "written".to_string(),
+            Some(s) => s.reason.as_str().to_string(),
+            None => "unknown".to_string(),
+        };
+        if watch.stopped_because == "written" {
+            let after = self.read_region(addr, length as u64)?;
+            watch.write = Some(RegionWrite::new(addr, before, &after, None, None, stop.as_ref().and_then(|s| s.frame.as_ref())));
+        }
+        Ok(())
+    }
+
+    /// Watch by write-protecting the region's pages with `mprotect` in the
+    /// inferior. A write faults; the pages are unprotected, the writing
+    /// instruction is stepped with the SIGSEGV discarded, and the pages are
+    /// guarded again unless the write hit the region.
+    fn watch_region_guard(&mut self, addr: u64, before: &[u8], deadline: Instant, watch: &mut RegionWatch) -> Result<()> {
+        self.evaluate_u64("(unsigned long)&mprotect")
+            .map_err(|e| anyhow!("mprotect is not available in the target ({}); guard mode needs a Linux process", e))?;
+        let page_size = self.evaluate_u64("(unsigned long)getpagesize()").ok()
+            .filter(|p| p.is_power_of_two())
+            .unwrap_or(DEFAULT_PAGE_SIZE);
+        let (start, span) = page_span(addr, before.len() as u64, page_size);
+        let original = self.console_command("info proc mappings").ok()
+            .and_then(|output| mapping_protection(&output, addr))
+            .unwrap_or(PROT_READ | PROT_WRITE);
+        let handling = self.console_command("info signals SIGSEGV").ok()
+            .and_then(|output| signal_handling(&output, "SIGSEGV"))
+            .unwrap_or_else(|| "stop print pass".to_string());
+
+        self.console_command("handle SIGSEGV stop print nopass")?;
+        let guarded = self.run_guarded(addr, before, (start, span, original), deadline, watch);
+        let unprotected = self.mprotect(start, span, original);
+        let restored = self.console_command(&format!("handle SIGSEGV {}", handling));
+        guarded?;
+        unprotected?;
+        restored?;
+        Ok(())
+    }
+
+    fn run_guarded(&mut self, addr: u64, before: &[u8], (start, span, original): (u64, u64, u64), deadline: Instant, watch: &mut RegionWatch) -> Result<()> {
+        let region = addr..addr + before.len() as u64;
+        let (mut faults, mut false_positives) = (0, 0);
+        let stopped_because = loop {
+            self.mprotect(start, span, original & !PROT_WRITE)?;
+            let remaining = deadline.saturating_duration_since(Instant::now()).as_millis() as u64;
+            if self.exec_with_budget(false, remaining)? {
+                break "timeout".to_string();
+            }
+
+            let stop = self.last_stop();
+            let fault = stop.as_ref()
+                .and_then(|s| s.signal.as_ref())
+                .filter(|signal| signal.name.as_deref() == Some("SIGSEGV"))
+                .and_then(|signal| signal.fault_address.as_deref())
+                .and_then(parse_address)
+                .filter(|fault| (start..start + span).contains(fault));
+            let Some(fault) = fault else {
+                break stop.map(|s| s.reason.as_str().to_string()).unwrap_or_else(|| "unknown".to_string());
+            };
+            faults += 1;
+
+            self.mprotect(start, span, original)?;
+            self.exec_step_instruction()?;
+            if region.contains(&fault) {
+                let frame = stop.and_then(|s| s.frame);
+                let after = self.read_region(addr, before.len() as u64)?;
+                let instruction = frame.as_ref().map(|f| f.addr.clone());
+                watch.write = Some(RegionWrite::new(addr, before, &after, Some(fault), instruction, frame.as_ref()));
+                break "written".to_string();
+            }
+            false_positives += 1;
+        };
+        watch.stopped_because = stopped_because;
+        watch.faults = Some(faults);
+        watch.false_positives = Some(false_positives);
+        Ok(())
+    }
+
+    fn mprotect(&mut self, start: u64, length: u64, protection: u64) -> Result<()> {
+        let result = self.evaluate_i64(&format!("(int)mprotect((void *){:#x}, {}, {})", start, length, protection))?;
+        if result != 0 {
+            return Err(anyhow!("mprotect({:#x}, {}, {}) failed in the target", start, length, protection));
+        }
+        Ok(())
+    }
+
+    /// Watch by single-stepping and comparing the region after each step
+    fn watch_region_step(&mut self, addr: u64, before: &[u8], deadline: Instant, max_steps: u64, watch: &mut RegionWatch) -> Result<()> {
+        let length = before.len() as u64;
+        let start_pc = self.evaluate_u64("$pc").ok().map(|pc| format!("{:#x}", pc));
+        let remaining = deadline.saturating_duration_since(Instant::now()).as_millis() as u64;
+        let result = self.step_until_change(
+            |c| c.read_region(addr, length).map(|bytes| to_hex(&bytes)),
+            max_steps,
+            remaining,
+            2,
+        )?;
+
+        watch.steps = Some(result.steps);
+        watch.stopped_because = match result.stopped_because.as_str() {
+            "changed" => "written".to_string(),
+            other => other.to_string(),
+        };
+        if result.changed {
+            // The last entry is where stepping ended; the one before it is
+            // the writing instruction
+            let writer = result.trace.len().checked_sub(2).map(|i| &result.trace[i]);
+            let instruction = writer.map(|entry| entry.addr.clone()).or(start_pc);
+            let frame = writer.map(|entry| Frame {
+                level: 0,
+                addr: entry.addr.clone(),
+                func: entry.func.clone(),
+                file: entry.file.clone(),
+                fullname: None,
+                line: entry.line,
+                arch: None,
+                from: None,
+                inlined: false,
+            });
+            let after = parse_hex_bytes(&result.after);
+            watch.write = Some(RegionWrite::new(addr, before, &after, None, instruction, frame.as_ref()));
+        }
+        Ok(())
+    }
+
+    /// Resume under `record btrace` until the next stop (or until
+    /// `max_runtime_ms` runs out) and count the functions called on the way.
+    /// At most `max_segments` segments of the call history are read.
+    pub fn call_summary(&mut self, max_runtime_ms: u64, max_segments: u64) -> Result<CallSummary> {
+        self.console_command("record btrace")
+            .map_err(|e| anyhow!("{}; gdb_call_summary needs branch tracing (record btrace)", e))?;
+
+        let traced = self.exec_with_budget(false, max_runtime_ms).and_then(|interrupted| {
+            let output = self.console_command(&format!("record function-call-history /c 1,{}", max_segments + 1))
+                .or_else(|e| if e.to_string().contains("No trace") { Ok(String::new()) } else { Err(e) })?;
+            Ok((interrupted, parse_call_history(&output)))
+        });
+        // Always stop recording, so later execution runs at full speed
+        let stopped = self.console_command("record stop");
+        let (interrupted, mut segments) = traced?;
+        stopped?;
+
+        let truncated = segments.len() as u64 > max_segments;
+        segments.truncate(max_segments as usize);
+        let functions = count_calls(&segments);
+        Ok(CallSummary {
+            total_calls: functions.iter().map(|f| f.calls).sum(),
+            functions,
+            segments: segments.len() as u64,
+            truncated,
+            interrupted,
+        })
+    }
+
+    /// Single-step instructions until the next jump, call or return,
+    /// classifying each instruction from its disassembly. The branch itself
+    /// is executed when `execute_branch` is set, so the result shows where
+    /// it went.
+    pub fn step_until_branch(
+        &mut self,
+        execute_branch: bool,
+        max_steps: u64,
+        timeout_ms: u64,
+        trace_limit: usize,
+    ) -> Result<StepUntilBranch> {
+        let start = std::time::Instant::now();
+        // Straight-line code is disassembled a block at a time instead of
+        // once per step
+        let mut instructions: HashMap<u64, AsmInstruction> = HashMap::new();
+        let mut pc = self.evaluate_u64("(unsigned long long)$pc")?;
+        let mut path = std::collections::VecDeque::new();
+        let mut steps = 0;
+        let mut result = StepUntilBranch {
+            stopped_because: "max_steps".to_string(),
+            steps: 0,
+            path: Vec::new(),
+            branch: None,
+            kind: None,
+            target: None,
+        };
+
+        loop {
+            if !instructions.contains_key(&pc) {
+                instructions.extend(self.disassemble_range(pc, pc + 64)?
+                    .into_iter()
+                    .filter_map(|insn| parse_address(&insn.address).map(|addr| (addr, insn))));
+            }
+            let insn = instructions.get(&pc)
+                .ok_or_else(|| anyhow!("Could not disassemble the instruction at {:#x}", pc))?;
+            let step = AsmStep {
+                addr: format!("{:#x}", pc),
+                inst: insn.inst.clone(),
+                func: insn.func_name.clone(),
+            };
+
+            if let Some(kind) = branch_kind(&insn.inst) {
+                result.stopped_because = "branch".to_string();
+                result.branch = Some(step);
+                result.kind = Some(kind);
+                if execute_branch {
+                    self.exec_step_instruction()?;
+                    steps += 1;
+                    result.target = Some(format!("{:#x}", self.evaluate_u64("(unsigned long long)$pc")?));
+                }
+                break;
+            }
+            if steps >= max_steps {
+                break;
+            }
+            if start.elapsed().as_millis() as u64 > timeout_ms {
+                result.stopped_because = "timeout".to_string();
+                break;
+            }
+
+            self.exec_step_instruction()?;
+            steps += 1;
+            path.push_back(step);
+            if path.len() > trace_limit {
+                path.pop_front();
+            }
+
+            let stop = self.tracker().last_stop().cloned();
+            if let Some(stop) = stop.as_ref().filter(|s| s.reason.is_terminal()) {
+                result.stopped_because = stop.reason.as_str().to_string();
+                break;
+            }
+            pc = match stop.and_then(|s| s.frame).and_then(|f| parse_address(&f.addr)) {
+                Some(addr) => addr,
+                None => self.evaluate_u64("(unsigned long long)$pc")?,
+            };
+        }
+
+        result.steps = steps;
+        result.path = path.into_iter().collect();
+        Ok(result)
+    }
+
+    /// Step `count` instructions, reporting the executed instructions and
+    /// how the watched registers and memory changed
+    pub fn step_instructions(&mut self, step: fn(&mut Self, u64) -> Result<()>, count: u64, watch: &StepWatch) -> Result<InstructionStep> {
+        let before = self.read_watched(watch);
+        let mut executed = Vec::new();
+        if watch.instructions {
+            // Each instruction is disassembled before it runs, so they are
+            // stepped one at a time
+            for _ in 0..count {
+                let pc = self.evaluate_u64("(unsigned long long)$pc")?;
+                let insn = self.disassemble_range(pc, pc + 1)?.into_iter().next();
+                executed.push(AsmStep {
+                    addr: format!("{:#x}", pc),
+                    inst: insn.as_ref().map(|i| i.inst.clone()).unwrap_or_else(|| "<unknown>".to_string()),
+                    func: insn.and_then(|i| i.func_name),
+                });
+                step(self, 1)?;
+            }
+        } else {
+            step(self, count)?;
+        }
+        let after = self.read_watched(watch);
+
+        let names = watch.registers.iter().map(|r| format!("${}", r))
+            .chain(watch.memory.iter().map(|(addr, length)| format!("{}[{}]", addr, length)));
+        let mut values: Vec<WatchedValue> = names.zip(before.into_iter().zip(after))
+            .map(|(name, (before, after))| {
+                let error = before.as_ref().err().or(after.as_ref().err()).cloned();
+                let (before, after) = (before.ok(), after.ok());
+                WatchedValue { name, changed: error.is_none() && before != after, before, after, error }
+            })
+            .collect();
+        let memory = values.split_off(watch.registers.len());
+
+        Ok(InstructionStep {
+            count,
+            pc: self.data_evaluate_expression("$pc")?,
+            executed,
+            registers: values,
+            memory,
+        })
+    }
+
+    /// Read the registers, then the memory ranges, of a watch
+    fn read_watched(&mut self, watch: &StepWatch) -> Vec<Result<String, String>> {
+        let mut values = Vec::new();
+        for register in &watch.registers {
+            values.push(self.data_evaluate_expression(&format!("${}", register)).map_err(|e| e.to_string()));
+        }
+        for (addr, length) in &watch.memory {
+            values.push(self.data_read_memory(addr, *length).map(|m| m.data.concat()).map_err(|e| e.to_string()));
+        }
+        values
+    }
+
+    /// Disassemble the instructions in `[start, end)`
+    fn disassemble_range(&mut self, start: u64, end: u64) -> Result<Vec<AsmInstruction>> {
+        let response = self.send_command(&format!("data-disassemble -s {:#x} -e {:#x} -- 0", start, end))?;
+        match response {
+            MiOutputRecord::Result { class: ResultClass::Done, results, .. } => Ok(parse_asm_instructions(&results)),
+            MiOutputRecord::Result { class: ResultClass::Error, results, .. } => {
+                Err(mi_error(&results, &format!("Failed to disassemble {:#x}", start)))
+            }
+            _ => Err(anyhow!("Unexpected response")),
+        }
+    }
+
+    /// Wait for the target to stop
+    ///
+    /// `since` is the stop count observed before the run-control command was
+    /// sent, so a `*stopped` record that arrives before this is called is not
+    /// missed.
+    fn wait_for_stop(&self, since: u64, timeout_ms: u64) -> Result<()> {
+        wait_for_stop_count(&self.state, since, timeout_ms)
+    }
+
+    /// Skip a function or file when stepping
+    pub fn skip(&mut self, kind: SkipKind, pattern: &str) -> Result<String> {
+        self.require(BackendFeature::GdbCli, "skip")?;
+        let command = format!("{} {}", kind.command(), pattern);
+        let output = self.console_command(&command)?;
+        self.record_history(command);
+        Ok(output)
+    }
+
+    /// List skip entries (`info skip`)
+    pub fn skip_list(&mut self) -> Result<String> {
+        self.require(BackendFeature::GdbCli, "skip")?;
+        self.console_command("info skip")
+    }
+
+    /// Delete skip entries, or all of them when `numbers` is None
+    pub fn skip_delete(&mut self, numbers: Option<&str>) -> Result<()> {
+        self.require(BackendFeature::GdbCli, "skip")?;
+        let command = match numbers {
+            Some(n) => format!("skip delete {}", n),
+            None => "skip delete".to_string(),
+        };
+        self.console_command(&command)?;
+        self.record_history(command);
+        Ok(())
     }
 
     /// Step out
@@ -896,36 +2760,166 @@ impl GdbClient {
         
         match response {
             MiOutputRecord::Result { class: ResultClass::Running, .. } => Ok(()),
+            MiOutputRecord::Result { class: ResultClass::Error, results, .. } => Err(mi_error(&results, "Failed to finish")),
             _ => Err(anyhow!("Failed to finish")),
         }
     }
 
-    /// Interrupt execution
-    pub fn exec_interrupt(&mut self) -> Result<()> {
-        let response = self.send_command("exec-interrupt")?;
-        
-        match response {
-            MiOutputRecord::Result { class: ResultClass::Done, .. } => {
-                let mut state = self.state.lock().unwrap();
-                state.running = false;
-                Ok(())
+    /// Finish `frames` functions in a row, waiting up to `timeout_ms` for
+    /// each to return. Stops early when anything other than the return
+    /// stops the target.
+    pub fn finish_frames(&mut self, frames: u64, timeout_ms: u64) -> FinishSequence {
+        let mut returns = Vec::new();
+        let mut stopped_because = "completed".to_string();
+        let mut error = None;
+
+        while (returns.len() as u64) < frames {
+            let returned_from = self.stack_info_frame().ok().flatten().and_then(|f| f.func);
+            let stops = self.state().stop_count;
+            if let Err(e) = self.exec_finish() {
+                stopped_because = "error".to_string();
+                error = Some(e.to_string());
+                break;
             }
-            _ => Err(anyhow!("Failed to interrupt")),
+            if self.wait_for_stop(stops, timeout_ms).is_err() {
+                stopped_because = "timeout".to_string();
+                if let Err(e) = self.exec_interrupt() {
+                    error = Some(format!("Failed to interrupt the target: {}", e));
+                }
+                break;
+            }
+
+            let Some(stop) = self.tracker().last_stop().cloned() else {
+                break;
+            };
+            if stop.reason != StopReason::FunctionFinished {
+                stopped_because = stop.reason.as_str().to_string();
+                break;
+            }
+            let frame = stop.frame.as_ref();
+            returns.push(FinishReturn {
+                returned_from,
+                return_value: stop.return_value,
+                addr: frame.map(|f| f.addr.clone()).unwrap_or_default(),
+                func: frame.and_then(|f| f.func.clone()),
+                file: frame.and_then(|f| f.file.clone()),
+                line: frame.and_then(|f| f.line),
+            });
+        }
+
+        FinishSequence { requested: frames, completed: returns.len() as u64, stopped_because, error, returns }
+    }
+
+    /// Interrupt execution
+    pub fn exec_interrupt(&mut self) -> Result<InterruptMethod> {
+        self.interrupter().ok_or_else(|| anyhow!("GDB not running"))?.interrupt()
+    }
+
+    /// Handle for interrupting the target without holding the client
+    pub fn interrupter(&self) -> Option<Interrupter> {
+        Some(Interrupter {
+            stdin: Arc::clone(self.stdin.as_ref()?),
+            pid: self.process.as_ref()?.id(),
+            state: Arc::clone(&self.state),
+        })
+    }
+
+    /// Send SIGINT to the GDB process
+    fn send_sigint(&self) -> Result<()> {
+        let pid = self.process.as_ref().ok_or_else(|| anyhow!("GDB not running"))?.id();
+        sigint(pid)
+    }
+
+    /// Probe whether the connected target supports background execution
+    fn detect_target_async(&mut self) {
+        if self.require(BackendFeature::TargetFeatures, "list-target-features").is_err() {
+            return;
         }
+        let supported = match self.send_command("list-target-features") {
+            Ok(MiOutputRecord::Result { class: ResultClass::Done, results, .. }) => results.iter()
+                .find(|r| r.variable == "features")
+                .and_then(|r| MiParser::extract_list(&r.value))
+                .map(|features| features.iter().any(|f| MiParser::extract_string(f).as_deref() == Some("async"))),
+            _ => None,
+        };
+        debug!("Target async support: {:?}", supported);
+        self.state.lock().unwrap().target_async = supported;
     }
 
     /// Get stack trace
-    pub fn stack_list_frames(&mut self) -> Result<Vec<Frame>> {
-        let response = self.send_command("stack-list-frames")?;
+    pub fn stack_list_frames(&mut self, range: Option<(u64, u64)>) -> Result<Vec<Frame>> {
+        // One frame past the range tells whether the last one is inlined
+        let cmd = match range {
+            Some((low, high)) => format!("stack-list-frames {} {}", low, high.saturating_add(1).min(i32::MAX as u64)),
+            None => "stack-list-frames".to_string(),
+        };
+        let response = self.send_command(&cmd)?;
         
         match response {
             MiOutputRecord::Result { class: ResultClass::Done, results, .. } => {
-                Ok(parse_stack_frames(&results))
+                let mut frames = parse_stack_frames(&results);
+                mark_inlined_frames(&mut frames);
+                if let Some((_, high)) = range {
+                    frames.retain(|f| f.level <= high);
+                }
+                Ok(frames)
             }
             _ => Ok(Vec::new()),
         }
     }
 
+    /// Get the stack depth, counting at most `max_depth` frames when given
+    pub fn stack_info_depth(&mut self, max_depth: Option<u64>) -> Result<u64> {
+        let cmd = match max_depth {
+            Some(max) => format!("stack-info-depth {}", max),
+            None => "stack-info-depth".to_string(),
+        };
+        let response = self.send_command(&cmd)?;
+
+        match response {
+            MiOutputRecord::Result { class: ResultClass::Done, results, .. } => {
+                results.iter()
+                    .find(|r| r.variable == "depth")
+                    .and_then(|r| MiParser::extract_string(&r.value))
+                    .and_then(|s| s.parse().ok())
+                    .ok_or_else(|| anyhow!("Failed to parse stack depth"))
+            }
+            MiOutputRecord::Result { class: ResultClass::Error, results, .. } => Err(mi_error(&results, "Failed to get stack depth")),
+            _ => Err(anyhow!("Failed to get stack depth")),
+        }
+    }
+
+    /// List the arguments and locals of a frame (the selected one by default)
+    pub fn stack_list_variables(&mut self, context: &FrameContext) -> Result<(Vec<Argument>, Vec<Argument>)> {
+        let options = self.context_options(context);
+        let response = self.send_command(&format!("stack-list-variables {}--all-values", options))?;
+
+        match response {
+            MiOutputRecord::Result { class: ResultClass::Done, results, .. } => {
+                Ok(parse_stack_variables(&results))
+            }
+            MiOutputRecord::Result { class: ResultClass::Error, results, .. } => Err(mi_error(&results, "Failed to list frame variables")),
+            _ => Err(anyhow!("Failed to list frame variables")),
+        }
+    }
+
+    /// Walk the innermost `max_frames` frames collecting arguments and locals.
+    /// Each frame is read with `--frame`, so the selected frame is left as is.
+    pub fn backtrace_full(&mut self, max_frames: u64) -> Result<Vec<BacktraceFrame>> {
+        let frames = self.stack_list_frames(Some((0, max_frames.saturating_sub(1))))?;
+
+        let mut backtrace = Vec::with_capacity(frames.len());
+        for frame in frames {
+            let context = FrameContext { thread: None, frame: Some(frame.level) };
+            let (args, locals, error) = match self.stack_list_variables(&context) {
+                Ok((args, locals)) => (args, locals, None),
+                Err(e) => (Vec::new(), Vec::new(), Some(e.to_string())),
+            };
+            backtrace.push(BacktraceFrame { frame, args, locals, error });
+        }
+        Ok(backtrace)
+    }
+
     /// Get current frame
     pub fn stack_info_frame(&mut self) -> Result<Option<Frame>> {
         let response = self.send_command("stack-info-frame")?;
@@ -948,10 +2942,32 @@ impl GdbClient {
                 state.current_frame = Some(level);
                 Ok(())
             }
+            MiOutputRecord::Result { class: ResultClass::Error, results, .. } => Err(mi_error(&results, "Failed to select frame")),
             _ => Err(anyhow!("Failed to select frame")),
         }
     }
 
+    /// Move the selected frame by `offset` levels (positive = up/outward)
+    /// and return the newly selected frame
+    pub fn stack_move_frame(&mut self, offset: i64) -> Result<Frame> {
+        let current = self.stack_info_frame()?
+            .ok_or_else(|| anyhow!("No frame selected"))?
+            .level;
+
+        let target = current as i64 + offset;
+        if target < 0 {
+            return Err(anyhow!("Bottom (innermost) frame selected; cannot go down {} frame(s)", -offset));
+        }
+        let target = target as u64;
+        if self.stack_info_depth(Some(target + 1))? <= target {
+            return Err(anyhow!("Initial frame selected; cannot go up {} frame(s)", offset));
+        }
+
+        self.stack_select_frame(target)?;
+        self.stack_info_frame()?
+            .ok_or_else(|| anyhow!("No frame information available"))
+    }
+
     /// List threads
     pub fn thread_list_ids(&mut self) -> Result<Vec<String>> {
         let response = self.send_command("thread-list-ids")?;
@@ -974,10 +2990,218 @@ impl GdbClient {
                 state.current_thread = Some(id.to_string());
                 Ok(())
             }
+            MiOutputRecord::Result { class: ResultClass::Error, results, .. } => Err(mi_error(&results, "Failed to select thread")),
             _ => Err(anyhow!("Failed to select thread")),
         }
     }
 
+    /// Threads with their state and frame, and the selected thread
+    pub fn thread_info(&mut self) -> Result<(Vec<Thread>, Option<String>)> {
+        let response = self.send_command("thread-info")?;
+
+        match response {
+            MiOutputRecord::Result { class: ResultClass::Done, results, .. } => {
+                let current = results.iter()
+                    .find(|r| r.variable == "current-thread-id")
+                    .and_then(|r| MiParser::extract_string(&r.value));
+                Ok((parse_thread_info(&results), current))
+            }
+            MiOutputRecord::Result { class: ResultClass::Error, results, .. } => Err(mi_error(&results, "Failed to list threads")),
+            _ => Err(anyhow!("Failed to list threads")),
+        }
+    }
+
+    /// Registers and innermost `depth` frames of each core (GDB thread),
+    /// all of them unless `threads` is given, without changing the selection
+    pub fn smp_state(&mut self, registers: &[String], depth: u64, threads: Option<&[String]>) -> Result<SmpState> {
+        let (all, current) = self.thread_info()?;
+        if all.is_empty() {
+            return Err(anyhow!("The target has no threads; is it connected and stopped?"));
+        }
+        let mut cores = Vec::new();
+        for thread in all.into_iter().filter(|t| threads.is_none_or(|ids| ids.contains(&t.id))) {
+            let mut core = CoreState {
+                cpu: cpu_index(&thread),
+                selected: current.as_deref() == Some(thread.id.as_str()),
+                thread_id: thread.id,
+                target_id: thread.target_id,
+                state: thread.state,
+                registers: Vec::new(),
+                frames: Vec::new(),
+                error: None,
+            };
+            if core.state == ThreadState::Running {
+                core.error = Some("running".to_string());
+                cores.push(core);
+                continue;
+            }
+
+            let context = FrameContext { thread: Some(core.thread_id.clone()), frame: None };
+            core.registers = registers.iter()
+                .map(|name| CoreRegister {
+                    name: name.clone(),
+                    value: self.data_evaluate_expression_in(&format!("(unsigned long long)${}", name), &context)
+                        .ok()
+                        .and_then(|v| parse_value(&v).integer)
+                        .map(|v| format!("{:#x}", v)),
+                })
+                .collect();
+            match self.send_command(&format!("stack-list-frames --thread {} 0 {}", core.thread_id, depth.saturating_sub(1)))? {
+                MiOutputRecord::Result { class: ResultClass::Done, results, .. } => core.frames = parse_stack_frames(&results),
+                MiOutputRecord::Result { class: ResultClass::Error, results, .. } => {
+                    core.error = Some(mi_error(&results, "Failed to list frames").to_string());
+                }
+                _ => {}
+            }
+            cores.push(core);
+        }
+
+        // `Mode for locking scheduler during execution is "replay".`
+        let scheduler_locking = self.console_command("show scheduler-locking").ok()
+            .and_then(|output| output.split('"').nth(1).map(|mode| mode.to_string()));
+        Ok(SmpState { cores, pinned_thread: self.state().pinned_thread, scheduler_locking })
+    }
+
+    /// Restrict run control to one thread with GDB's scheduler locking
+    /// (`on`: continuing and stepping, `step`: stepping only), or let all
+    /// of them run again with `off`
+    pub fn smp_pin(&mut self, thread: Option<&str>, mode: &str) -> Result<Option<String>> {
+        let thread = match thread {
+            Some(thread) => {
+                self.thread_select(thread)?;
+                Some(thread.to_string())
+            }
+            None => self.thread_info()?.1,
+        };
+        self.gdb_set("scheduler-locking", mode)?;
+        let pinned = if mode == "off" { None } else { thread };
+        self.state.lock().unwrap().pinned_thread = pinned.clone();
+        Ok(pinned)
+    }
+
+    /// Whether an x86 target runs in long mode (EFER.LMA), judged by the
+    /// pointer size if EFER is not available
+    fn x86_long_mode(&mut self) -> bool {
+        match self.evaluate_u64("(unsigned long long)$efer") {
+            Ok(efer) => efer & (1 << 10) != 0,
+            Err(_) => self.evaluate_u64("sizeof(void *)").is_ok_and(|size| size == 8),
+        }
+    }
+
+    /// Read and decode a descriptor table. Without an explicit base and
+    /// limit, GDTR/IDTR are taken from QEMU's `info registers`.
+    pub fn x86_descriptor_table(&mut self, table: DescriptorTable, base: Option<u64>, limit: Option<u64>, max_entries: u64) -> Result<DescriptorTableDump> {
+        let (base, limit) = match (base, limit) {
+            (Some(base), Some(limit)) => (base, limit),
+            _ => {
+                let output = self.console_command("monitor info registers")?;
+                let (register_base, register_limit) = parse_descriptor_register(&output, table)
+                    .ok_or_else(|| anyhow!("Could not find {:?} in the QEMU monitor output; pass base and limit", table))?;
+                (base.unwrap_or(register_base), limit.unwrap_or(register_limit))
+            }
+        };
+
+        let long_mode = self.x86_long_mode();
+        let entry_size = if table == DescriptorTable::Idt && long_mode { 16 } else { 8 };
+        let table_size = limit + 1;
+        let size = table_size.min(max_entries * entry_size);
+        let memory = self.data_read_memory(&format!("{:#x}", base), size)?;
+        let bytes = parse_hex_bytes(&memory.data.concat());
+        let entries = match table {
+            DescriptorTable::Gdt => decode_gdt(&bytes, long_mode),
+            DescriptorTable::Idt => decode_idt(&bytes, long_mode),
+        };
+
+        Ok(DescriptorTableDump {
+            table,
+            base: format!("{:#x}", base),
+            limit: format!("{:#x}", limit),
+            long_mode,
+            entries,
+            truncated: size < table_size,
+        })
+    }
+
+    /// Translate a virtual address by walking the page tables in physical
+    /// memory, read through QEMU's `xp` monitor command
+    pub fn x86_page_walk(&mut self, vaddr: u64, cr3: Option<u64>) -> Result<PageWalk> {
+        let cr0 = self.evaluate_u64("(unsigned long long)$cr0")?;
+        let cr4 = self.evaluate_u64("(unsigned long long)$cr4")?;
+        let efer = self.evaluate_u64("(unsigned long long)$efer").unwrap_or(0);
+        let cr3 = match cr3 {
+            Some(cr3) => cr3,
+            None => self.evaluate_u64("(unsigned long long)$cr3")?,
+        };
+        let mode = PagingMode::detect(cr0, cr4, efer)
+            .ok_or_else(|| anyhow!("Paging is disabled (CR0.PG is clear)"))?;
+
+        walk(vaddr, cr3, mode, |addr, size| {
+            let unit = if size == 4 { 'w' } else { 'g' };
+            let output = self.console_command(&format!("monitor xp /1{}x {:#x}", unit, addr))?;
+            parse_xp_output(&output).ok_or_else(|| anyhow!("Unexpected QEMU monitor output: {}", output.trim()))
+        })
+    }
+
+    /// Run a QEMU human monitor command through GDB's `monitor` command,
+    /// or over QMP when `qmp` (a socket path or host:port) is given
+    pub fn qemu_monitor(&mut self, command: &str, qmp: Option<&str>) -> Result<String> {
+        let output = match qmp {
+            Some(address) => Qmp::connect(address)?.human_command(command)?,
+            None => self.console_command(&format!("monitor {}", command))?,
+        };
+        match monitor_error(&output) {
+            Some(error) => Err(anyhow!("QEMU monitor: {}", error)),
+            None => Ok(output),
+        }
+    }
+
+    /// Save a VM snapshot, remembering the frame and breakpoints of the
+    /// session at the time
+    pub fn vm_snapshot_save(&mut self, name: &str, qmp: Option<&str>) -> Result<SnapshotMark> {
+        self.qemu_monitor(&format!("savevm {}", name), qmp)?;
+        let mark = SnapshotMark {
+            name: name.to_string(),
+            frame: self.stack_info_frame().ok().flatten(),
+            breakpoints: self.break_list().map(|b| b.into_iter().map(|b| b.number).collect()).unwrap_or_default(),
+            saved_ms: crate::util::now_ms(),
+        };
+        self.vm_snapshots.retain(|m| m.name != name);
+        self.vm_snapshots.push(mark.clone());
+        Ok(mark)
+    }
+
+    /// Restore a VM snapshot. GDB keeps cached registers and memory until
+    /// the target runs, so they are flushed and the frame is read again.
+    /// Returns the new frame and the mark of the snapshot if this session
+    /// saved it.
+    pub fn vm_snapshot_load(&mut self, name: &str, qmp: Option<&str>) -> Result<(Option<Frame>, Option<SnapshotMark>)> {
+        self.qemu_monitor(&format!("loadvm {}", name), qmp)?;
+        if self.console_command("maintenance flush register-cache").is_err() {
+            self.console_command("flushregs")?;
+        }
+        // Older GDBs have no dcache flush; the register flush is what matters
+        let _ = self.console_command("maintenance flush dcache");
+
+        let frame = self.stack_info_frame()?;
+        if let Some(stop) = self.tracker().last_stop_mut() {
+            stop.frame = frame.clone();
+        }
+        Ok((frame, self.vm_snapshots.iter().find(|m| m.name == name).cloned()))
+    }
+
+    /// Delete a VM snapshot from the disks
+    pub fn vm_snapshot_delete(&mut self, name: &str, qmp: Option<&str>) -> Result<()> {
+        self.qemu_monitor(&format!("delvm {}", name), qmp)?;
+        self.vm_snapshots.retain(|m| m.name != name);
+        Ok(())
+    }
+
+    /// Snapshots on the VM's disks, and those saved in this session
+    pub fn vm_snapshots(&mut self, qmp: Option<&str>) -> Result<(Vec<VmSnapshot>, Vec<SnapshotMark>)> {
+        let output = self.qemu_monitor("info snapshots", qmp)?;
+        Ok((parse_snapshots(&output), self.vm_snapshots.clone()))
+    }
+
     /// Read memory
     pub fn data_read_memory(&mut self, addr: &str, count: u64) -> Result<MemoryContent> {
         let response = self.send_command(&format!("data-read-memory-bytes {} {}", addr, count))?;
@@ -987,13 +3211,156 @@ impl GdbClient {
                 parse_memory_content(&results)
                     .ok_or_else(|| anyhow!("Failed to parse memory content"))
             }
+            MiOutputRecord::Result { class: ResultClass::Error, results, .. } => Err(mi_error(&results, "Failed to read memory")),
             _ => Err(anyhow!("Failed to read memory")),
         }
     }
 
+    /// Sample memory every `interval` for `duration` while the target runs.
+    /// Memory is read in the background where the target allows it,
+    /// otherwise the target is briefly interrupted for each sample. Polling
+    /// ends early if the target stops by itself. A target that was stopped
+    /// when polling began is resumed and stopped again at the end.
+    pub fn poll_memory(&mut self, addr: &str, length: u64, interval: Duration, duration: Duration) -> Result<MemoryPoll> {
+        let was_running = self.state().running;
+        if !was_running {
+            self.exec_continue_no_wait()?;
+        }
+
+        let started = std::time::Instant::now();
+        let mut poll = MemoryPoll {
+            addr: addr.to_string(),
+            length,
+            method: PollMethod::Background,
+            samples: Vec::new(),
+            stopped: None,
+        };
+        loop {
+            if !self.state().running {
+                poll.stopped = self.last_stop();
+                break;
+            }
+
+            let memory = match poll.method {
+                PollMethod::Background => match self.data_read_memory(addr, length) {
+                    Ok(memory) => Some(memory),
+                    Err(e) if is_target_running_error(&e) => {
+                        debug!("Memory cannot be read while running, interrupting for samples");
+                        poll.method = PollMethod::Interrupt;
+                        None
+                    }
+                    Err(e) => return Err(e),
+                },
+                PollMethod::Interrupt => None,
+            };
+            let memory = match memory {
+                Some(memory) => memory,
+                None => {
+                    self.exec_interrupt()?;
+                    let memory = self.data_read_memory(addr, length)?;
+                    // A stop other than our interrupt (e.g. a breakpoint hit
+                    // just before it) ends polling
+                    let last_stop = self.tracker().last_stop().cloned();
+                    if last_stop.as_ref().is_some_and(|stop| !is_interrupt_stop(stop)) {
+                        poll.stopped = self.last_stop();
+                        break;
+                    }
+                    self.exec_continue_no_wait()?;
+                    memory
+                }
+            };
+
+            let data = memory.data.concat();
+            let changed = poll.samples.last().is_some_and(|last| last.data != data);
+            poll.samples.push(MemorySample { elapsed_ms: started.elapsed().as_millis() as u64, data, changed });
+
+            let next = interval * poll.samples.len() as u32;
+            if next >= duration {
+                break;
+            }
+            thread::sleep(next.saturating_sub(started.elapsed()));
+        }
+
+        if !was_running && poll.stopped.is_none() && self.state().running {
+            self.exec_interrupt()?;
+        }
+        Ok(poll)
+    }
+
+    /// Snapshot memory regions under a name, replacing any previous snapshot
+    pub fn memory_snapshot(&mut self, name: &str, regions: &[(String, u64)]) -> Result<MemorySnapshot> {
+        let mut captured = Vec::new();
+        for (expression, length) in regions {
+            let mem = self.data_read_memory(expression, *length)?;
+            captured.push(MemorySnapshotRegion {
+                expression: expression.clone(),
+                addr: mem.addr,
+                bytes: parse_hex_bytes(&mem.data.concat()),
+            });
+        }
+
+        let snapshot = MemorySnapshot {
+            name: name.to_string(),
+            timestamp_ms: crate::util::now_ms(),
+            regions: captured,
+        };
+        self.snapshots.insert(name.to_string(), snapshot.clone());
+        Ok(snapshot)
+    }
+
+    /// Compare a snapshot against the current memory contents
+    ///
+    /// Regions are re-read at their resolved addresses. When `update` is set
+    /// the snapshot is replaced by the current contents.
+    pub fn memory_diff(&mut self, name: &str, update: bool) -> Result<Vec<MemoryByteChange>> {
+        let snapshot = self.snapshots.get(name).cloned()
+            .ok_or_else(|| anyhow!("No memory snapshot named '{}'", name))?;
+
+        let mut changes = Vec::new();
+        let mut current_regions = Vec::new();
+        for region in &snapshot.regions {
+            let mem = self.data_read_memory(&region.addr, region.bytes.len() as u64)?;
+            let current = parse_hex_bytes(&mem.data.concat());
+            let base = parse_address(&region.addr).unwrap_or(0);
+
+            for (offset, (old, new)) in region.bytes.iter().zip(current.iter()).enumerate() {
+                if old != new {
+                    changes.push(MemoryByteChange {
+                        addr: format!("0x{:x}", base + offset as u64),
+                        region: region.expression.clone(),
+                        offset: offset as u64,
+                        old: format!("{:02x}", old),
+                        new: format!("{:02x}", new),
+                    });
+                }
+            }
+
+            current_regions.push(MemorySnapshotRegion {
+                bytes: current,
+                ..region.clone()
+            });
+        }
+
+        if update {
+            self.snapshots.insert(name.to_string(), MemorySnapshot {
+                name: name.to_string(),
+                timestamp_ms: crate::util::now_ms(),
+                regions: current_regions,
+            });
+        }
+
+        Ok(changes)
+    }
+
     /// Evaluate expression
     pub fn data_evaluate_expression(&mut self, expr: &str) -> Result<String> {
-        let response = self.send_command(&format!("data-evaluate-expression \"{}\"", expr))?;
+        self.data_evaluate_expression_in(expr, &FrameContext::default())
+    }
+
+    /// Evaluate expression in a thread and frame other than the selected one
+    pub fn data_evaluate_expression_in(&mut self, expr: &str, context: &FrameContext) -> Result<String> {
+        let options = self.context_options(context);
+        let response = self.send_command(&format!("data-evaluate-expression {}\"{}\"", options, expr))?;
         
         match response {
             MiOutputRecord::Result { class: ResultClass::Done, results, .. } => {
@@ -1008,13 +3375,863 @@ impl GdbClient {
                     });
                 value.ok_or_else(|| anyhow!("No value in response"))
             }
+            MiOutputRecord::Result { class: ResultClass::Error, results, .. } => Err(mi_error(&results, "Failed to evaluate expression")),
             _ => Err(anyhow!("Failed to evaluate expression")),
         }
     }
 
-    /// List registers
-    pub fn data_list_register_names(&mut self) -> Result<Vec<String>> {
-        let response = self.send_command("data-list-register-names")?;
+    /// Evaluate an expression and break its value down into type, number,
+    /// annotation and address
+    pub fn evaluate_typed(&mut self, expr: &str, context: &FrameContext) -> Result<TypedValue> {
+        let value = self.data_evaluate_expression_in(expr, context)?;
+        let value_type = self.console_command_in(&format!("whatis {}", expr), context)
+            .ok()
+            .and_then(|out| out.trim().strip_prefix("type = ").map(|t| t.to_string()));
+
+        let mut parsed = parse_value(&value);
+        // Enumerators print by name; ask for the number behind them
+        if parsed.integer.is_none() && parsed.float.is_none() && !value.starts_with(['{', '[', '"', '<']) {
+            if let Ok(number) = self.data_evaluate_expression_in(&format!("(long long) ({})", expr), context) {
+                parsed.integer = parse_value(&number).integer;
+            }
+        }
+        let address = self.data_evaluate_expression_in(&format!("&({})", expr), context)
+            .ok()
+            .and_then(|a| parse_value(&a).integer)
+            .map(|a| format!("{:#x}", a));
+
+        Ok(TypedValue {
+            expression: expr.to_string(),
+            value,
+            value_type,
+            parsed,
+            address,
+        })
+    }
+
+    /// Evaluate an expression, typically a `__thread` variable, in each of
+    /// `threads` (default: all threads) without changing the selection
+    pub fn tls_read(&mut self, expr: &str, threads: Option<Vec<String>>) -> Result<Vec<ThreadValue>> {
+        let threads = match threads {
+            Some(threads) => threads,
+            None => self.thread_list_ids()?,
+        };
+        if threads.is_empty() {
+            return Err(anyhow!("The program has no threads; is it running?"));
+        }
+        Ok(threads.into_iter()
+            .map(|thread_id| {
+                let context = FrameContext { thread: Some(thread_id.clone()), frame: None };
+                match self.data_evaluate_expression_in(expr, &context) {
+                    Ok(value) => {
+                        let address = self.data_evaluate_expression_in(&format!("&({})", expr), &context)
+                            .ok()
+                            .and_then(|a| parse_value(&a).integer)
+                            .map(|a| format!("{:#x}", a));
+                        ThreadValue { thread_id, value: Some(value), address, error: None }
+                    }
+                    Err(e) => ThreadValue { thread_id, value: None, address: None, error: Some(e.to_string()) },
+                }
+            })
+            .collect())
+    }
+
+    /// Read and decode registers. Registers the target does not have are
+    /// reported with an error instead of failing the dump.
+    pub fn read_registers(&mut self, specs: &[RegisterSpec]) -> Vec<DecodedRegister> {
+        specs.iter()
+            .map(|spec| match self.read_profile_register(spec) {
+                Ok((value, bits)) => spec.decode(value, bits),
+                Err(e) => spec.unavailable(e.to_string()),
+            })
+            .collect()
+    }
+
+    /// Assign `expression` to the convenience variable `$name` (checked
+    /// with `convenience::validate_name`) and return the value it got
+    pub fn convenience_set(&mut self, name: &str, expression: &str) -> Result<String> {
+        let value = self.data_evaluate_expression(&format!("${} = ({})", name, expression))?;
+        self.state.lock().unwrap().convenience_variables.insert(name.to_string(), expression.to_string());
+        Ok(value)
+    }
+
+    /// Values of the named convenience variables, or of all variables GDB
+    /// holds (`show convenience`) when no names are given
+    pub fn convenience_get(&mut self, names: &[String]) -> Result<Vec<ConvenienceValue>> {
+        let assigned = self.state().convenience_variables;
+        let mut values = if names.is_empty() {
+            parse_show_convenience(&self.console_command("show convenience")?)
+        } else {
+            names.iter()
+                .map(|name| {
+                    let name = name.trim().trim_start_matches('$').to_string();
+                    match self.data_evaluate_expression(&format!("${}", name)) {
+                        Ok(value) => ConvenienceValue { value: (value != "void").then_some(value), name, expression: None, error: None },
+                        Err(e) => ConvenienceValue { name, value: None, expression: None, error: Some(e.to_string()) },
+                    }
+                })
+                .collect()
+        };
+        for value in &mut values {
+            value.expression = assigned.get(&value.name).cloned();
+        }
+        Ok(values)
+    }
+
+    /// `$_exitcode`, `$_siginfo` of a signal stop, a few settings through
+    /// `$_gdb_setting_str`, and the variables set in this session. Values
+    /// that cannot be read are left out.
+    pub fn convenience_snapshot(&mut self) -> ConvenienceSnapshot {
+        let exitcode = self.data_evaluate_expression("$_exitcode").ok()
+            .and_then(|value| value.trim().parse().ok());
+        let signal_stop = self.tracker().last_stop().is_some_and(|s| s.reason == StopReason::SignalReceived);
+        let siginfo = if signal_stop { self.siginfo().ok() } else { None };
+        let settings = STATUS_SETTINGS.iter()
+            .map_while(|setting| {
+                // GDB before 10 has no $_gdb_setting_str; stop at the first failure
+                let value = self.data_evaluate_expression(&format!("$_gdb_setting_str(\"{}\")", setting)).ok()?;
+                Some((setting.to_string(), unquote(&value)))
+            })
+            .collect();
+        let names: Vec<String> = self.state().convenience_variables.into_keys().collect();
+        let variables = if names.is_empty() { Vec::new() } else { self.convenience_get(&names).unwrap_or_default() };
+        ConvenienceSnapshot { exitcode, siginfo, settings, variables }
+    }
+
+    /// Read and decode `$_siginfo` for the current thread
+    pub fn siginfo(&mut self) -> Result<SigInfo> {
+        let raw = self.data_evaluate_expression("$_siginfo")?;
+        let signo = self.evaluate_i64("$_siginfo.si_signo")?;
+        let name = signal_name(signo).map(|s| s.to_string());
+        let code = self.evaluate_i64("$_siginfo.si_code")?;
+        let signal = name.as_deref().unwrap_or("");
+
+        let fault_address = if FAULT_SIGNALS.contains(&signal) && code > 0 {
+            self.evaluate_u64("(unsigned long)$_siginfo._sifields._sigfault.si_addr")
+                .ok()
+                .map(|addr| format!("{:#x}", addr))
+        } else {
+            None
+        };
+        // Signals sent by another process carry the sender
+        let (sender_pid, sender_uid) = if code <= 0 {
+            (
+                self.evaluate_i64("$_siginfo._sifields._kill.si_pid").ok(),
+                self.evaluate_i64("$_siginfo._sifields._kill.si_uid").ok(),
+            )
+        } else {
+            (None, None)
+        };
+
+        Ok(SigInfo {
+            signo,
+            errno: self.evaluate_i64("$_siginfo.si_errno").unwrap_or(0),
+            code,
+            cause: code_meaning(signal, code).map(|s| s.to_string()),
+            name,
+            fault_address,
+            sender_pid,
+            sender_uid,
+            raw,
+        })
+    }
+
+    /// The most recent stop. Signal stops are completed with the faulting
+    /// address and cause from `$_siginfo` the first time they are asked for.
+    pub fn last_stop(&mut self) -> Option<StopEvent> {
+        let stop = self.tracker().last_stop().cloned()?;
+        let Some(signal) = &stop.signal else {
+            return Some(stop);
+        };
+        let fault = signal.name.as_deref().is_some_and(|name| FAULT_SIGNALS.contains(&name));
+        if !fault || signal.cause.is_some() || stop.reason != StopReason::SignalReceived {
+            return Some(stop);
+        }
+
+        match self.siginfo() {
+            Ok(info) => {
+                let mut tracker = self.tracker();
+                let last = tracker.last_stop_mut()?;
+                if let Some(signal) = last.signal.as_mut() {
+                    signal.fault_address = info.fault_address;
+                    signal.cause = info.cause;
+                }
+                Some(last.clone())
+            }
+            Err(e) => {
+                debug!("$_siginfo is not available: {}", e);
+                Some(stop)
+            }
+        }
+    }
+
+    /// Read `errno`. Without debug info for the C library the value is read
+    /// through `__errno_location()`, which calls into the target. With
+    /// `with_stack`, also report the C library call on the stack, if any.
+    pub fn errno(&mut self, with_stack: bool) -> Result<ErrnoValue> {
+        const SOURCES: &[&str] = &["errno", "*((int *(*)(void))__errno_location)()"];
+        let mut last_error = None;
+        let mut read = None;
+        for source in SOURCES {
+            match self.evaluate_i64(source) {
+                Ok(value) => {
+                    read = Some((value, source));
+                    break;
+                }
+                Err(e) => last_error = Some(e),
+            }
+        }
+        let Some((value, source)) = read else {
+            return Err(last_error.unwrap_or_else(|| anyhow!("errno is not available")));
+        };
+
+        let libc_call = if with_stack { self.libc_call()? } else { None };
+        let info = errno_info(value);
+        Ok(ErrnoValue {
+            value,
+            name: info.map(|(name, _)| name.to_string()),
+            description: info.map(|(_, description)| description.to_string()),
+            source: source.to_string(),
+            libc_call,
+        })
+    }
+
+    /// The outermost of the C library frames at the top of the stack: the
+    /// function the program called
+    fn libc_call(&mut self) -> Result<Option<LibcCall>> {
+        let frames = self.stack_list_frames(None)?;
+        let depth = frames.iter().take_while(|f| f.from.as_deref().is_some_and(is_libc)).count();
+        if depth == 0 {
+            return Ok(None);
+        }
+        let entry = &frames[depth - 1];
+        Ok(Some(LibcCall {
+            function: entry.func.clone(),
+            library: entry.from.clone().unwrap_or_default(),
+            caller: frames.get(depth).cloned(),
+        }))
+    }
+
+    /// Evaluate an expression that yields a signed integer
+    pub fn evaluate_i64(&mut self, expr: &str) -> Result<i64> {
+        let value = self.data_evaluate_expression(expr)?;
+        value.trim().parse().map_err(|_| anyhow!("'{}' is not an integer: {}", expr, value))
+    }
+
+    /// Evaluate an expression that yields an integer
+    pub fn evaluate_u64(&mut self, expr: &str) -> Result<u64> {
+        let value = self.data_evaluate_expression(expr)?;
+        parse_address(&value).ok_or_else(|| anyhow!("'{}' is not an integer: {}", expr, value))
+    }
+
+    /// Read a register's raw value and width in bits
+    pub fn read_profile_register(&mut self, spec: &RegisterSpec) -> Result<(u64, u32)> {
+        let value = self.evaluate_u64(&spec.source.expression())?;
+        let bits = match spec.source {
+            RegisterSource::Memory(_) => 32,
+            RegisterSource::Register(name) => self.data_evaluate_expression(&format!("sizeof(${})", name))
+                .ok()
+                .and_then(|size| size.trim().parse::<u32>().ok())
+                .filter(|&size| (1..=8).contains(&size))
+                .map_or(64, |size| size * 8),
+        };
+        // Signed registers come back sign-extended by the cast
+        Ok((truncate(value, bits), bits))
+    }
+
+    /// Instruction set of the target, found by probing registers that exist
+    /// only on one architecture. On Arm, Thumb is used on M-profile cores
+    /// and when the T bit of CPSR is set, unless `thumb` says otherwise.
+    pub fn instruction_set(&mut self, thumb: Option<bool>) -> Result<PatchArch> {
+        if self.data_evaluate_expression("$rip").is_ok() {
+            return Ok(PatchArch::X86_64);
+        }
+        if self.data_evaluate_expression("$eip").is_ok() {
+            return Ok(PatchArch::I386);
+        }
+        if self.data_evaluate_expression("$x30").is_ok() {
+            return Ok(PatchArch::AArch64);
+        }
+        if self.data_evaluate_expression("$ra").is_ok() {
+            let wide = self.evaluate_u64("sizeof($ra)").map_or(true, |size| size == 8);
+            return Ok(if wide { PatchArch::Riscv64 } else { PatchArch::Riscv32 });
+        }
+        if self.data_evaluate_expression("$lr").is_ok() {
+            let thumb = match thumb {
+                Some(thumb) => thumb,
+                None => self.data_evaluate_expression("$xpsr").is_ok()
+                    || self.evaluate_u64("$cpsr").is_ok_and(|cpsr| cpsr & 0x20 != 0),
+            };
+            return Ok(if thumb { PatchArch::Thumb } else { PatchArch::Arm });
+        }
+        Err(anyhow!("Code patching supports x86-64, i386, AArch64, RISC-V and Arm targets"))
+    }
+
+    /// Write assembled instructions or NOPs at `address`, padding with NOPs
+    /// up to the next instruction boundary when `pad` is set, and keep the
+    /// original bytes for `patch_revert`
+    pub fn patch_code(&mut self, address: &str, code: &PatchCode, assembler: Option<&str>, thumb: Option<bool>, pad: bool) -> Result<CodePatch> {
+        let start = self.evaluate_u64(address)?;
+        let arch = self.instruction_set(thumb)?;
+        let (mut bytes, source) = match code {
+            PatchCode::Assemble { source, intel_syntax } => (assemble(arch, assembler, source, *intel_syntax)?, source.clone()),
+            PatchCode::Nops(count) => (arch.nop().repeat(*count), format!("{} nop(s)", count)),
+        };
+
+        let mut added = 0;
+        if pad {
+            // Enough to see the instruction the patch ends in
+            let boundaries: Vec<u64> = self.disassemble_range(start, start + bytes.len() as u64 + 16)?
+                .iter()
+                .filter_map(|i| parse_address(&i.address))
+                .collect();
+            added = padding(start, bytes.len() as u64, &boundaries, arch.nop())?;
+            bytes.extend(arch.nop().iter().cycle().take(added));
+        }
+
+        let original = self.data_read_memory(&format!("{:#x}", start), bytes.len() as u64)?.data.concat();
+        let patched = to_hex(&bytes);
+        self.write_code(start, &patched)?;
+
+        let disassembly = self.disassemble_range(start, start + bytes.len() as u64)
+            .map(|instructions| instructions.into_iter().map(|i| format!("{}: {}", i.address, i.inst)).collect())
+            .unwrap_or_default();
+        let patch = CodePatch {
+            id: self.next_patch_id,
+            address: format!("{:#x}", start),
+            source,
+            arch,
+            original,
+            patched,
+            padding: added,
+            disassembly,
+        };
+        self.next_patch_id += 1;
+        self.patches.push(patch.clone());
+        Ok(patch)
+    }
+
+    /// Restore the original bytes of patch `id`, or of the latest patch.
+    /// A patch overlapped by a later one cannot be reverted before it.
+    pub fn patch_revert(&mut self, id: Option<u64>) -> Result<CodePatch> {
+        let index = match id {
+            Some(id) => self.patches.iter().position(|p| p.id == id).ok_or_else(|| anyhow!("No code patch {}", id))?,
+            None => self.patches.len().checked_sub(1).ok_or_else(|| anyhow!("No code patches to revert"))?,
+        };
+        let patch = self.patches[index].clone();
+        if let Some(later) = self.patches[index + 1..].iter().find(|p| p.overlaps(&patch)) {
+            return Err(anyhow!("Patch {} overlaps the later patch {}; revert that one first", patch.id, later.id));
+        }
+        self.write_code(patch.start(), &patch.original)?;
+        self.patches.remove(index);
+        Ok(patch)
+    }
+
+    /// Applied code patches, oldest first
+    pub fn patches(&self) -> &[CodePatch] {
+        &self.patches
+    }
+
+    /// Write hex bytes to code memory and check that they were written,
+    /// since flash and other read-only memory can ignore the write
+    fn write_code(&mut self, start: u64, hex: &str) -> Result<()> {
+        let address = format!("{:#x}", start);
+        let response = self.send_command(&format!("data-write-memory-bytes {} {}", address, hex))?;
+        if let MiOutputRecord::Result { class: ResultClass::Error, results, .. } = response {
+            return Err(mi_error(&results, &format!("Failed to write code at {}", address)));
+        }
+        self.record_history(format!("interpreter-exec mi \"-data-write-memory-bytes {} {}\"", address, hex));
+        let written = self.data_read_memory(&address, (hex.len() / 2) as u64)?.data.concat();
+        if !written.eq_ignore_ascii_case(hex) {
+            return Err(anyhow!(
+                "Memory at {} did not change after the write; it may be flash or other read-only memory.",
+                address
+            ));
+        }
+        Ok(())
+    }
+
+    /// Disassemble the whole function containing `location`, resolving
+    /// unannotated branch/call targets to symbols
+    pub fn disassemble_function(&mut self, location: &str) -> Result<FunctionDisassembly> {
+        let response = self.send_command(&format!("data-disassemble -a {} -- 0", location))?;
+        let mut instructions = match response {
+            MiOutputRecord::Result { class: ResultClass::Done, results, .. } => parse_asm_instructions(&results),
+            MiOutputRecord::Result { class: ResultClass::Error, results, .. } => {
+                return Err(mi_error(&results, &format!("Failed to disassemble {}", location)));
+            }
+            _ => return Err(anyhow!("Unexpected response")),
+        };
+
+        // Branch targets are named with GDB's `info symbol`
+        let resolve_symbols = self.config.backend.backend().supports(BackendFeature::GdbCli);
+        let mut symbols: HashMap<u64, Option<String>> = HashMap::new();
+        for insn in instructions.iter_mut() {
+            let Some(target) = parse_branch_target(&insn.inst).filter(|_| resolve_symbols) else {
+                continue;
+            };
+            let symbol = symbols.entry(target).or_insert_with(|| self.symbol_at(target));
+            insn.target_symbol = symbol.clone();
+        }
+
+        let function = instructions.first()
+            .and_then(|insn| insn.func_name.clone())
+            .unwrap_or_else(|| location.to_string());
+        let pc = self.data_evaluate_expression("(unsigned long long)$pc")
+            .ok()
+            .and_then(|v| parse_address(&v));
+
+        Ok(FunctionDisassembly { function, pc, instructions })
+    }
+
+    /// The basic block holding `location` (default: the PC). Outside any
+    /// known function the block starts at the address itself.
+    pub fn basic_block(&mut self, location: Option<&str>) -> Result<BasicBlock> {
+        let pc = self.evaluate_u64("(unsigned long long)$pc").ok();
+        let address = match location {
+            Some(location) => self.evaluate_u64(&format!("(unsigned long long)({})", location))?,
+            None => pc.ok_or_else(|| anyhow!("The program has no PC; start it or pass a location"))?,
+        };
+        let (function, instructions) = match self.disassemble_function(&format!("{:#x}", address)) {
+            Ok(disassembly) => (disassembly.function, disassembly.instructions),
+            Err(_) => ("??".to_string(), self.disassemble_range(address, address + 64)?),
+        };
+        basic_block(function, &instructions, address, pc).ok_or_else(|| anyhow!("No instructions at {:#x}", address))
+    }
+
+    /// Backtrace found by following frame records from `fp` (default: the
+    /// layout's frame pointer register), for when GDB's unwinder gives up
+    pub fn unwind_fallback(&mut self, arch: PatchArch, layout: FrameLayout, pc: Option<&str>, fp: Option<&str>, max_frames: usize) -> Result<FallbackUnwind> {
+        let pc = self.evaluate_u64(&format!("(unsigned long long)({})", pc.unwrap_or("$pc")))?;
+        let fp_expr = fp.map(|fp| fp.to_string()).unwrap_or_else(|| format!("${}", layout.fp_register));
+        let fp = self.evaluate_u64(&format!("(unsigned long long)({})", fp_expr))?;
+        // Thumb addresses carry the mode in bit 0
+        let code = |address: u64| if matches!(arch, PatchArch::Arm | PatchArch::Thumb) { address & !1 } else { address };
+        let function = |symbol: Option<String>| symbol.map(|s| s.split('+').next().unwrap_or_default().to_string());
+
+        // A link register pointing into the current function is left over
+        // from a call it made, and EXC_RETURN values are not code addresses
+        let current = function(self.symbol_at(code(pc)));
+        let lr = layout.link_register.clone()
+            .and_then(|register| self.evaluate_u64(&format!("(unsigned long long)${}", register)).ok())
+            .filter(|&lr| !(arch == PatchArch::Thumb && lr >= 0xffff_ff00))
+            .filter(|&lr| current.is_none() || function(self.symbol_at(code(lr))) != current);
+
+        let word = if layout.word_size == 8 { "unsigned long long" } else { "unsigned int" };
+        let (found, stopped_because) = walk_frames(&layout, pc, fp, lr, max_frames, |address| {
+            self.evaluate_u64(&format!("*({} *){:#x}", word, address))
+        });
+        let frames = found.into_iter()
+            .enumerate()
+            .map(|(level, (pc, fp, source))| HeuristicFrame {
+                level,
+                pc: format!("{:#x}", pc),
+                symbol: self.symbol_at(code(pc)),
+                frame_pointer: fp.map(|fp| format!("{:#x}", fp)),
+                source,
+            })
+            .collect();
+        let gdb_frames = self.stack_list_frames(None).map(|frames| frames.len()).unwrap_or(0);
+        Ok(FallbackUnwind { heuristic: true, arch, layout, gdb_frames, frames, stopped_because })
+    }
+
+    /// Symbol containing an address as `function+offset`, from `info symbol`
+    fn symbol_at(&mut self, address: u64) -> Option<String> {
+        self.console_command(&format!("info symbol {:#x}", address))
+            .ok()
+            .map(|out| out.trim().to_string())
+            .filter(|out| !out.is_empty() && !out.starts_with("No symbol"))
+            .map(|out| out.split(" in section").next().unwrap_or(&out).replace(" + ", "+"))
+    }
+
+    /// Trace calls to allocation functions with breakpoints on their first
+    /// instruction. Returns the breakpoint of each function, the functions
+    /// that could not be found, and the calling convention assumed.
+    pub fn alloc_trace_start(&mut self, functions: &[String]) -> Result<(Vec<TracedFunction>, Vec<String>, &'static CallingConvention)> {
+        if self.alloc_tracer().active() {
+            return Err(anyhow!("An allocation trace is already running; stop it first"));
+        }
+        let convention = CONVENTIONS.iter()
+            .find(|c| self.data_evaluate_expression(c.probe).is_ok())
+            .ok_or_else(|| anyhow!("Allocation tracing supports x86-64, i386, AArch64, RISC-V and Arm targets"))?;
+
+        let mut breakpoints = HashMap::new();
+        let mut inserted = Vec::new();
+        let mut missing = Vec::new();
+        for function in functions {
+            let kind = AllocKind::from_function(function)
+                .ok_or_else(|| anyhow!("Cannot trace '{}'; supported functions are {}", function, DEFAULT_FUNCTIONS.join(", ")))?;
+            match self.break_insert(&format!("*{}", function), &BreakInsertOptions::default()) {
+                Ok(bp) => {
+                    breakpoints.insert(bp.number.clone(), kind);
+                    inserted.push(TracedFunction { function: function.clone(), breakpoint: bp.number });
+                }
+                Err(_) => missing.push(function.clone()),
+            }
+        }
+        if breakpoints.is_empty() {
+            return Err(anyhow!("None of {} could be found; start the program first so the C library is loaded", functions.join(", ")));
+        }
+        self.alloc_tracer().start(convention, breakpoints);
+        Ok((inserted, missing, convention))
+    }
+
+    /// Stop tracing and delete its breakpoints; the results are kept
+    pub fn alloc_trace_stop(&mut self) -> Result<()> {
+        let numbers = self.alloc_tracer().stop();
+        if numbers.is_empty() {
+            return Err(anyhow!("No allocation trace is running"));
+        }
+        for number in numbers {
+            if let Err(e) = self.break_delete(&number) {
+                warn!("Failed to delete allocation trace breakpoint {}: {}", number, e);
+            }
+        }
+        Ok(())
+    }
+
+    /// Summary of the allocation trace with call sites resolved to symbols
+    pub fn alloc_trace_summary(&mut self, top: usize) -> AllocSummary {
+        let mut summary = self.alloc_tracer().summary(top);
+        if self.config.backend.backend().supports(BackendFeature::GdbCli) {
+            let mut symbols: HashMap<String, Option<String>> = HashMap::new();
+            let mut resolve = |client: &mut Self, caller: &str| {
+                symbols.entry(caller.to_string())
+                    .or_insert_with(|| parse_address(caller).and_then(|address| client.symbol_at(address)))
+                    .clone()
+            };
+            for caller in summary.top_allocators.iter_mut() {
+                caller.symbol = resolve(self, &caller.caller);
+            }
+            for allocation in summary.leak_candidates.iter_mut() {
+                allocation.symbol = resolve(self, &allocation.caller);
+            }
+        }
+        summary
+    }
+
+    /// Whether the target is big-endian, by `show endian`
+    fn big_endian(&mut self) -> bool {
+        self.console_command("show endian")
+            .ok()
+            .and_then(|out| parse_show_endian(&out))
+            .is_some_and(|(endian, _)| endian == Endian::Big)
+    }
+
+    /// Compare GDB's byte order with the executable's ELF header and keep
+    /// the warning, if any, in the session state
+    pub fn endian_check(&mut self) -> Result<EndianCheck> {
+        let output = self.console_command("show endian")?;
+        let (gdb, automatic) = parse_show_endian(&output)
+            .ok_or_else(|| anyhow!("Unexpected output from show endian: {}", output.trim()))?;
+        let executable = self.state.lock().unwrap().executable.clone();
+        let elf = executable.as_deref().and_then(|path| elf_endian(Path::new(path)).ok().flatten());
+        let check = EndianCheck::new(gdb, automatic, executable, elf);
+        self.state.lock().unwrap().endian_warning = check.warning();
+        Ok(check)
+    }
+
+    /// Set the byte order: "little", "big" or "auto"
+    pub fn set_endian(&mut self, endian: &str) -> Result<EndianCheck> {
+        self.console_command(&format!("set endian {}", endian))?;
+        self.endian_check()
+    }
+
+    /// Re-check the byte order after the executable or target changed
+    fn refresh_endian(&mut self) {
+        if !self.config.backend.backend().supports(BackendFeature::GdbCli) {
+            return;
+        }
+        if let Err(e) = self.endian_check() {
+            debug!("Byte order check failed: {}", e);
+        }
+    }
+
+    /// Compare the stack canary of a frame with the guard value. Without a
+    /// level the caller of `__stack_chk_fail` is checked if it is on the
+    /// stack, otherwise the selected frame.
+    pub fn stack_guard_check(&mut self, level: Option<u64>, preceding_bytes: u64) -> Result<StackGuardCheck> {
+        let frames = self.stack_list_frames(Some((0, 32)))?;
+        let level = level.unwrap_or_else(|| {
+            frames.iter()
+                .find(|f| f.func.as_deref().is_some_and(|func| STACK_CHK_FAIL.contains(&func)))
+                .map_or(0, |f| f.level + 1)
+        });
+        let frame = frames.iter()
+            .find(|f| f.level == level)
+            .ok_or_else(|| anyhow!("No frame at level {}", level))?
+            .clone();
+        let context = FrameContext { thread: None, frame: Some(level) };
+        let evaluate = |client: &mut Self, expr: &str| -> Result<u64> {
+            let value = client.data_evaluate_expression_in(expr, &context)?;
+            parse_address(&value).ok_or_else(|| anyhow!("'{}' is not an integer: {}", expr, value))
+        };
+
+        let word_size = self.evaluate_u64("sizeof(void *)")? as usize;
+        let (guard_source, guard) = GUARD_SOURCES.iter()
+            .find_map(|(source, expr)| evaluate(self, expr).ok().map(|value| (*source, value)))
+            .ok_or_else(|| anyhow!("No stack protector guard found ($fs_base, $gs_base or __stack_chk_guard); was the program built with -fstack-protector?"))?;
+        let guard_bytes = if self.big_endian() {
+            guard.to_be_bytes()[8 - word_size..].to_vec()
+        } else {
+            guard.to_le_bytes()[..word_size].to_vec()
+        };
+
+        // The slot is where the prologue stores the guard; failing that, the
+        // guard value is searched for between SP and the frame address
+        let stored = self.disassemble_function(&format!("*{}", frame.addr))
+            .ok()
+            .and_then(|disassembly| find_canary_store(&disassembly.instructions));
+        let slot = match stored {
+            Some(store) => Some((evaluate(self, &store.expression())?, "prologue")),
+            None => {
+                let sp = evaluate(self, "(unsigned long long)$sp")?;
+                let cfa = self.console_command_in("info frame", &context).ok().and_then(|out| parse_frame_address(&out));
+                match cfa.filter(|&cfa| cfa > sp && cfa - sp <= 64 * 1024) {
+                    Some(cfa) => {
+                        let mem = self.data_read_memory(&format!("{:#x}", sp), cfa - sp)?;
+                        parse_hex_bytes(&mem.data.concat())
+                            .chunks_exact(word_size)
+                            .position(|word| word == guard_bytes.as_slice())
+                            .map(|i| (sp + (i * word_size) as u64, "scan"))
+                    }
+                    None => None,
+                }
+            }
+        };
+
+        let mut check = StackGuardCheck {
+            level,
+            function: frame.func.clone(),
+            status: "not_found",
+            guard_source,
+            guard: format!("{:#x}", guard),
+            slot: None,
+            slot_source: None,
+            canary: None,
+            overwritten: Vec::new(),
+            canary_ascii: None,
+            preceding: None,
+            preceding_ascii: None,
+        };
+        let Some((slot, slot_source)) = slot else {
+            return Ok(check);
+        };
+
+        let preceding_bytes = preceding_bytes.min(slot);
+        let mem = self.data_read_memory(&format!("{:#x}", slot - preceding_bytes), preceding_bytes + word_size as u64)?;
+        let bytes = parse_hex_bytes(&mem.data.concat());
+        let (preceding, canary) = bytes.split_at((preceding_bytes as usize).min(bytes.len()));
+
+        check.overwritten = changed_bytes(&guard_bytes, canary);
+        check.status = if check.overwritten.is_empty() { "intact" } else { "corrupted" };
+        check.slot = Some(format!("{:#x}", slot));
+        check.slot_source = Some(slot_source);
+        check.canary = Some(to_hex(canary));
+        if !check.overwritten.is_empty() {
+            check.canary_ascii = Some(ascii(canary));
+            check.preceding = Some(to_hex(preceding));
+            check.preceding_ascii = Some(ascii(preceding));
+        }
+        Ok(check)
+    }
+
+    /// Read `words` stack words upward from `start` (default: SP) and report
+    /// those that point into executable code, resolved to symbols
+    pub fn stack_scan(&mut self, start: Option<&str>, words: u64) -> Result<StackScan> {
+        let start = match start {
+            Some(start) => self.evaluate_u64(&format!("(unsigned long long)({})", start))?,
+            None => self.evaluate_u64("(unsigned long long)$sp")?,
+        };
+        let word_size = match self.evaluate_u64("sizeof(void *)")? {
+            size @ (2 | 4 | 8) => size as usize,
+            size => return Err(anyhow!("Unsupported pointer size {}", size)),
+        };
+        let big_endian = self.big_endian();
+
+        let mappings = self.console_command("info proc mappings").map(|out| parse_proc_mappings(&out)).unwrap_or_default();
+        let (ranges, code_source) = if mappings.is_empty() {
+            (parse_info_files(&self.console_command("info files")?), "sections")
+        } else {
+            (mappings, "mappings")
+        };
+        if ranges.is_empty() {
+            return Err(anyhow!("No executable code ranges found; is a program loaded?"));
+        }
+
+        let mem = self.data_read_memory(&format!("{:#x}", start), words * word_size as u64)?;
+        let values = decode_words(&parse_hex_bytes(&mem.data.concat()), word_size, big_endian);
+
+        let resolve_symbols = self.config.backend.backend().supports(BackendFeature::GdbCli);
+        let mut symbols: HashMap<u64, Option<String>> = HashMap::new();
+        let mut candidates = Vec::new();
+        for (i, value) in values.iter().enumerate() {
+            let Some(range) = code_range(&ranges, *value) else {
+                continue;
+            };
+            let symbol = if resolve_symbols {
+                symbols.entry(*value & !1).or_insert_with(|| self.symbol_at(*value & !1)).clone()
+            } else {
+                None
+            };
+            let offset = (i * word_size) as u64;
+            candidates.push(StackCandidate {
+                slot: format!("{:#x}", start + offset),
+                offset,
+                value: format!("{:#x}", value),
+                function_start: symbol.as_ref().is_some_and(|s| !s.contains('+')),
+                symbol,
+                region: range.name.clone(),
+            });
+        }
+
+        Ok(StackScan { start: format!("{:#x}", start), word_size, words: values.len(), code_source, candidates })
+    }
+
+    /// Line table of a source file (`-symbol-list-lines`)
+    pub fn symbol_list_lines(&mut self, file: &str) -> Result<Vec<LineTableEntry>> {
+        let response = self.send_command(&format!("symbol-list-lines \"{}\"", file))?;
+
+        match response {
+            MiOutputRecord::Result { class: ResultClass::Done, results, .. } => {
+                let mut table = parse_symbol_lines(&results);
+                for entry in table.iter_mut() {
+                    entry.file = Some(file.to_string());
+                }
+                Ok(table)
+            }
+            MiOutputRecord::Result { class: ResultClass::Error, results, .. } => {
+                Err(mi_error(&results, &format!("Failed to list lines of {}", file)))
+            }
+            _ => Err(anyhow!("Unexpected response")),
+        }
+    }
+
+    /// Compare the executable's source files with the files on disk
+    pub fn source_verify(&mut self) -> Result<SourceVerification> {
+        let executable = self.state().executable
+            .ok_or_else(|| anyhow!("No executable is loaded; use gdb_load_file first"))?;
+        let response = self.send_command("file-list-exec-source-files")?;
+        let files = match response {
+            MiOutputRecord::Result { class: ResultClass::Done, results, .. } => parse_source_files(&results),
+            MiOutputRecord::Result { class: ResultClass::Error, results, .. } => {
+                return Err(mi_error(&results, "Failed to list source files"));
+            }
+            _ => return Err(anyhow!("Unexpected response")),
+        };
+
+        let binary_mtime = mtime_secs(Path::new(&executable));
+        let md5s = line_table_md5s(&executable);
+        let files: Vec<_> = files.iter()
+            .map(|(file, fullname)| {
+                let path = fullname.as_deref().unwrap_or(file);
+                // Line table paths can be relative to the compilation directory
+                let expected = md5s.get(path).or_else(|| {
+                    md5s.iter().find(|(key, _)| path.ends_with(&format!("/{}", key))).map(|(_, md5)| md5)
+                });
+                verify_source(file, path, binary_mtime, expected.map(String::as_str))
+            })
+            .collect();
+
+        Ok(SourceVerification {
+            executable,
+            checksums: md5s.len(),
+            stale: files.iter().filter(|f| f.stale()).count(),
+            files,
+        })
+    }
+
+    /// Line table of the function containing `location`, in address order
+    pub fn function_line_table(&mut self, location: &str) -> Result<Vec<LineTableEntry>> {
+        let response = self.send_command(&format!("data-disassemble -a {} -- 1", location))?;
+
+        match response {
+            MiOutputRecord::Result { class: ResultClass::Done, results, .. } => {
+                Ok(parse_source_line_blocks(&results))
+            }
+            MiOutputRecord::Result { class: ResultClass::Error, results, .. } => {
+                Err(mi_error(&results, &format!("Failed to get line table of {}", location)))
+            }
+            _ => Err(anyhow!("Unexpected response")),
+        }
+    }
+
+    /// Run until `max_runtime_ms` runs out or the target stops for another
+    /// reason, recording which lines of `table` execute. A temporary
+    /// breakpoint goes on every block of every line and is removed by its
+    /// first hit, so each line costs at most one stop per block.
+    pub fn line_coverage(&mut self, table: &[LineTableEntry], max_runtime_ms: u64) -> Result<LineCoverage> {
+        let mut lines_at: HashMap<u64, Vec<u64>> = HashMap::new();
+        for entry in table {
+            for addr in entry.addresses.iter().filter_map(|a| parse_address(a)) {
+                lines_at.entry(addr).or_default().push(entry.line);
+            }
+        }
+
+        let temporary = BreakInsertOptions { temporary: true, ..Default::default() };
+        let mut numbers = Vec::new();
+        let mut inserted = Ok(());
+        for addr in lines_at.keys() {
+            match self.break_insert(&format!("*{:#x}", addr), &temporary) {
+                Ok(bp) => numbers.push(bp.number),
+                Err(e) => {
+                    inserted = Err(e);
+                    break;
+                }
+            }
+        }
+
+        let mut hit = HashSet::new();
+        let run = inserted.and_then(|_| {
+            let start = std::time::Instant::now();
+            let mut remaining_blocks = lines_at.len();
+            loop {
+                let budget = max_runtime_ms.saturating_sub(start.elapsed().as_millis() as u64);
+                if budget == 0 || self.exec_with_budget(false, budget)? {
+                    return Ok("timeout".to_string());
+                }
+                let stop = self.last_stop();
+                let reason = stop.as_ref().map(|s| s.reason.as_str()).unwrap_or("unknown").to_string();
+                let lines = stop.as_ref()
+                    .filter(|s| s.reason == StopReason::BreakpointHit)
+                    .and_then(|s| s.frame.as_ref())
+                    .and_then(|f| parse_address(&f.addr))
+                    .and_then(|addr| lines_at.get(&addr));
+                let Some(lines) = lines else {
+                    return Ok(reason);
+                };
+                hit.extend(lines.iter().copied());
+                remaining_blocks -= 1;
+                if remaining_blocks == 0 {
+                    return Ok("all_lines_hit".to_string());
+                }
+            }
+        });
+
+        // Remove the breakpoints that were not hit
+        let existing = self.break_list()?;
+        let left: Vec<String> = numbers.into_iter().filter(|n| existing.iter().any(|b| &b.number == n)).collect();
+        if !left.is_empty() {
+            self.break_delete(&left.join(" "))?;
+        }
+        let stopped_because = run?;
+
+        let mut lines: Vec<u64> = table.iter().map(|e| e.line).collect();
+        lines.sort_unstable();
+        lines.dedup();
+        let (hit, not_hit) = lines.into_iter().partition(|line| hit.contains(line));
+        Ok(LineCoverage { hit, not_hit, stopped_because })
+    }
+
+    /// Describe where the code for a source location starts and ends (`info line`)
+    pub fn info_line(&mut self, location: &str) -> Result<String> {
+        self.require(BackendFeature::GdbCli, "info line")?;
+        self.console_command(&format!("info line {}", location))
+    }
+
+    /// List registers
+    pub fn data_list_register_names(&mut self) -> Result<Vec<String>> {
+        let response = self.send_command("data-list-register-names")?;
         
         match response {
             MiOutputRecord::Result { class: ResultClass::Done, results, .. } => {
@@ -1024,9 +4241,99 @@ impl GdbClient {
         }
     }
 
+    /// Functions whose name matches `pattern` (all functions when None).
+    /// Falls back to `info functions` on GDB releases without
+    /// `-symbol-info-functions`.
+    pub fn function_symbols(&mut self, pattern: Option<&str>, include_nondebug: bool) -> Result<Vec<FunctionSymbol>> {
+        if !self.has_mi_feature(MiFeature::SymbolInfo) {
+            self.require(BackendFeature::GdbCli, "info functions")?;
+            let command = match pattern {
+                Some(p) => format!("info functions {}", p),
+                None => "info functions".to_string(),
+            };
+            let mut functions = parse_info_functions(&self.console_command(&command)?);
+            if !include_nondebug {
+                functions.retain(|f| f.address.is_none());
+            }
+            return Ok(functions);
+        }
+
+        let mut command = String::from("symbol-info-functions");
+        if include_nondebug {
+            command.push_str(" --include-nondebug");
+        }
+        if let Some(p) = pattern {
+            command.push_str(&format!(" --name \"{}\"", p.replace('\\', "\\\\").replace('"', "\\\"")));
+        }
+        let response = self.send_command(&command)?;
+
+        match response {
+            MiOutputRecord::Result { class: ResultClass::Done, results, .. } => Ok(parse_function_symbols(&results)),
+            MiOutputRecord::Result { class: ResultClass::Error, results, .. } => {
+                Err(mi_error(&results, "Failed to list functions"))
+            }
+            _ => Err(anyhow!("Unexpected response")),
+        }
+    }
+
+    /// List global and static variables whose names match `pattern`
+    pub fn variable_symbols(&mut self, pattern: Option<&str>, include_nondebug: bool) -> Result<Vec<GlobalVariable>> {
+        if !self.has_mi_feature(MiFeature::SymbolInfo) {
+            self.require(BackendFeature::GdbCli, "info variables")?;
+            let command = match pattern {
+                Some(p) => format!("info variables {}", p),
+                None => "info variables".to_string(),
+            };
+            let mut variables = variable_symbols(parse_info_functions(&self.console_command(&command)?));
+            if !include_nondebug {
+                variables.retain(|v| v.address.is_none());
+            }
+            return Ok(variables);
+        }
+
+        let mut command = String::from("symbol-info-variables");
+        if include_nondebug {
+            command.push_str(" --include-nondebug");
+        }
+        if let Some(p) = pattern {
+            command.push_str(&format!(" --name \"{}\"", p.replace('\\', "\\\\").replace('"', "\\\"")));
+        }
+        let response = self.send_command(&command)?;
+
+        match response {
+            MiOutputRecord::Result { class: ResultClass::Done, results, .. } => Ok(variable_symbols(parse_function_symbols(&results))),
+            MiOutputRecord::Result { class: ResultClass::Error, results, .. } => {
+                Err(mi_error(&results, "Failed to list variables"))
+            }
+            _ => Err(anyhow!("Unexpected response")),
+        }
+    }
+
+    /// Read the current value of each variable with debug info. Statics are
+    /// qualified with their file so same-named statics are told apart.
+    pub fn read_globals(&mut self, variables: &mut [GlobalVariable]) {
+        for variable in variables.iter_mut().filter(|v| v.address.is_none()) {
+            let file = variable.file.as_deref().map(|f| f.rsplit(['/', '\\']).next().unwrap_or(f));
+            let expr = match file {
+                Some(file) => format!("'{}'::{}", file, variable.name),
+                None => variable.name.clone(),
+            };
+            match self.data_evaluate_expression(&expr) {
+                Ok(value) => variable.value = Some(value),
+                Err(e) => variable.error = Some(e.to_string()),
+            }
+        }
+    }
+
     /// Get register values
     pub fn data_list_register_values(&mut self) -> Result<Vec<Register>> {
-        let response = self.send_command("data-list-register-values --skip-unavailable")?;
+        // Older releases fail the whole command on an unavailable register
+        let command = if self.has_mi_feature(MiFeature::SkipUnavailable) {
+            "data-list-register-values --skip-unavailable x"
+        } else {
+            "data-list-register-values x"
+        };
+        let response = self.send_command(command)?;
         
         match response {
             MiOutputRecord::Result { class: ResultClass::Done, results, .. } => {
@@ -1036,14 +4343,28 @@ impl GdbClient {
         }
     }
 
-    /// Create variable object
-    pub fn var_create(&mut self, name: &str, frame_addr: Option<&str>) -> Result<Variable> {
-        let mut cmd = format!("var-create - * \"{}\"", name);
-        if let Some(addr) = frame_addr {
-            cmd = format!("var-create --frame {} - * \"{}\"", addr, name);
+    /// `--thread`/`--frame` options for a context. GDB requires `--thread`
+    /// alongside `--frame`, so the selected thread fills in when only a frame
+    /// is given.
+    fn context_options(&self, context: &FrameContext) -> String {
+        let thread = context.thread.clone()
+            .or_else(|| context.frame.and_then(|_| self.state.lock().unwrap().current_thread.clone()));
+        let mut options = String::new();
+        if let Some(thread) = thread {
+            options.push_str(&format!("--thread {} ", thread));
         }
-        
-        let response = self.send_command(&cmd)?;
+        if let Some(frame) = context.frame {
+            options.push_str(&format!("--frame {} ", frame));
+        }
+        options
+    }
+
+    /// Create variable object, bound to the frame it is created in unless
+    /// `floating` is set
+    pub fn var_create(&mut self, name: &str, context: &FrameContext, floating: bool) -> Result<Variable> {
+        let options = self.context_options(context);
+        let frame = if floating { "@" } else { "*" };
+        let response = self.send_command(&format!("var-create {}- {} \"{}\"", options, frame, name))?;
         
         match response {
             MiOutputRecord::Result { class: ResultClass::Done, results, .. } => {
@@ -1051,11 +4372,7 @@ impl GdbClient {
                     .ok_or_else(|| anyhow!("Failed to parse variable"))
             }
             MiOutputRecord::Result { class: ResultClass::Error, results, .. } => {
-                let msg = results.iter()
-                    .find(|r| r.variable == "msg")
-                    .and_then(|r| MiParser::extract_string(&r.value))
-                    .unwrap_or_else(|| "Unknown error".to_string());
-                Err(anyhow!("Failed to create variable: {}", msg))
+                Err(mi_error(&results, "Failed to create variable"))
             }
             _ => Err(anyhow!("Unexpected response")),
         }
@@ -1067,10 +4384,123 @@ impl GdbClient {
         
         match response {
             MiOutputRecord::Result { class: ResultClass::Done, .. } => Ok(()),
+            MiOutputRecord::Result { class: ResultClass::Error, results, .. } => Err(mi_error(&results, "Failed to delete variable")),
             _ => Err(anyhow!("Failed to delete variable")),
         }
     }
 
+    /// Create a variable object that is kept until `var_unwatch`, so
+    /// `var_update` can report its changes
+    pub fn var_watch(&mut self, expression: &str, context: &FrameContext, floating: bool) -> Result<VarWatch> {
+        let var = self.var_create(expression, context, floating)?;
+        let watch = VarWatch {
+            name: var.name,
+            expression: expression.to_string(),
+            var_type: var.var_type,
+            value: var.value,
+            floating,
+            format: None,
+            frozen: false,
+        };
+        self.watches.push(watch.clone());
+        Ok(watch)
+    }
+
+    /// Variable objects kept by `var_watch`
+    pub fn watches(&self) -> &[VarWatch] {
+        &self.watches
+    }
+
+    /// Delete a watch by variable object name or expression, or every
+    /// watch when `name` is None. Returns the deleted watches.
+    pub fn var_unwatch(&mut self, name: Option<&str>) -> Result<Vec<VarWatch>> {
+        let (deleted, kept) = std::mem::take(&mut self.watches)
+            .into_iter()
+            .partition::<Vec<_>, _>(|w| name.is_none_or(|n| w.name == n || w.expression == n));
+        self.watches = kept;
+        for watch in &deleted {
+            self.var_delete(&watch.name)?;
+        }
+        Ok(deleted)
+    }
+
+    /// Find a watch by variable object name or expression
+    fn watch_mut(&mut self, name: &str) -> Result<&mut VarWatch> {
+        self.watches.iter_mut()
+            .find(|w| w.name == name || w.expression == name)
+            .ok_or_else(|| anyhow!("No watch named '{}'", name))
+    }
+
+    /// Set the display format of a watch (`natural`, `hexadecimal`,
+    /// `decimal`, `binary`, `octal` or `zero-hexadecimal`) and return its
+    /// value in that format
+    pub fn var_set_format(&mut self, name: &str, format: &str) -> Result<VarWatch> {
+        let varobj = self.watch_mut(name)?.name.clone();
+        let response = self.send_command(&format!("var-set-format {} {}", varobj, format))?;
+        let value = match response {
+            MiOutputRecord::Result { class: ResultClass::Done, results, .. } => results.iter()
+                .find(|r| r.variable == "value")
+                .and_then(|r| MiParser::extract_string(&r.value)),
+            MiOutputRecord::Result { class: ResultClass::Error, results, .. } => {
+                return Err(mi_error(&results, "Failed to set variable format"));
+            }
+            _ => return Err(anyhow!("Unexpected response")),
+        };
+
+        let watch = self.watch_mut(name)?;
+        watch.format = Some(format.to_string());
+        if value.is_some() {
+            watch.value = value;
+        }
+        Ok(watch.clone())
+    }
+
+    /// Freeze or thaw a watch. A frozen watch keeps its value through
+    /// `var_update` until it is thawed.
+    pub fn var_set_frozen(&mut self, name: &str, frozen: bool) -> Result<VarWatch> {
+        let varobj = self.watch_mut(name)?.name.clone();
+        let response = self.send_command(&format!("var-set-frozen {} {}", varobj, frozen as u8))?;
+        match response {
+            MiOutputRecord::Result { class: ResultClass::Done, .. } => {}
+            MiOutputRecord::Result { class: ResultClass::Error, results, .. } => {
+                return Err(mi_error(&results, "Failed to freeze variable"));
+            }
+            _ => return Err(anyhow!("Unexpected response")),
+        }
+
+        let watch = self.watch_mut(name)?;
+        watch.frozen = frozen;
+        Ok(watch.clone())
+    }
+
+    /// Update every variable object and report the ones whose value, scope
+    /// or type changed since the last update
+    pub fn var_update(&mut self) -> Result<Vec<VarChange>> {
+        let response = self.send_command("var-update --all-values *")?;
+        let mut changes = match response {
+            MiOutputRecord::Result { class: ResultClass::Done, results, .. } => parse_var_changes(&results),
+            MiOutputRecord::Result { class: ResultClass::Error, results, .. } => {
+                return Err(mi_error(&results, "Failed to update variables"));
+            }
+            _ => return Err(anyhow!("Unexpected response")),
+        };
+
+        for change in changes.iter_mut() {
+            // Children are named after their parent, e.g. "var1.field"
+            let root = change.name.split('.').next().unwrap_or(&change.name);
+            if let Some(watch) = self.watches.iter_mut().find(|w| w.name == root) {
+                change.expression = Some(watch.expression.clone());
+                if watch.name == change.name {
+                    watch.value = change.value.clone();
+                    if change.new_type.is_some() {
+                        watch.var_type = change.new_type.clone();
+                    }
+                }
+            }
+        }
+        Ok(changes)
+    }
+
     /// Evaluate variable
     pub fn var_evaluate_expression(&mut self, name: &str) -> Result<String> {
         let response = self.send_command(&format!("var-evaluate-expression {}", name))?;
@@ -1088,11 +4518,142 @@ impl GdbClient {
                     });
                 value.ok_or_else(|| anyhow!("No value in response"))
             }
+            MiOutputRecord::Result { class: ResultClass::Error, results, .. } => Err(mi_error(&results, "Failed to evaluate variable")),
             _ => Err(anyhow!("Failed to evaluate variable")),
         }
     }
 
+    /// Decode the memory at `address` as `type_name`, member by member.
+    /// Nesting stops after `max_depth` levels and arrays list at most
+    /// `max_elements` elements; pointers are not followed.
+    pub fn decode_struct(&mut self, address: &str, type_name: &str, max_depth: usize, max_elements: u64) -> Result<StructDecode> {
+        let base = self.evaluate_u64(&format!("(unsigned long long)({})", address))?;
+        let root = self.var_create(&format!("*({} *){:#x}", type_name, base), &FrameContext::default(), false)?;
+        let fields = self.decode_fields(&root.name, base, max_depth, max_elements);
+        let _ = self.var_delete(&root.name);
+
+        Ok(StructDecode {
+            address: format!("{:#x}", base),
+            type_name: type_name.to_string(),
+            size: self.evaluate_u64(&format!("sizeof({})", type_name)).ok(),
+            fields: fields?,
+        })
+    }
+
+    fn decode_fields(&mut self, varobj: &str, base: u64, depth: usize, max_elements: u64) -> Result<Vec<StructField>> {
+        let response = self.send_command(&format!("var-list-children --all-values {} 0 {}", varobj, max_elements))?;
+        let listed = match response {
+            MiOutputRecord::Result { class: ResultClass::Done, results, .. } => parse_struct_fields(&results),
+            MiOutputRecord::Result { class: ResultClass::Error, results, .. } => return Err(mi_error(&results, "Failed to list members")),
+            _ => return Err(anyhow!("Unexpected response")),
+        };
+
+        let mut fields = Vec::new();
+        for mut field in listed {
+            // C++ access specifiers are pseudo-members without a type
+            if field.var_type.is_none() {
+                fields.extend(self.decode_fields(&field.varobj, base, depth, max_elements)?);
+                continue;
+            }
+            if let Ok(path) = self.var_path_expression(&field.varobj) {
+                field.offset = self.evaluate_u64(&format!("(unsigned long long)&({})", path)).ok().map(|addr| addr.wrapping_sub(base));
+                field.size = self.evaluate_u64(&format!("sizeof({})", path)).ok();
+            }
+
+            let pointer = field.var_type.as_deref().is_some_and(|t| t.trim_end().ends_with('*') || t.contains("(*)"));
+            if field.children > 0 && !pointer {
+                if depth > 0 {
+                    field.fields = self.decode_fields(&field.varobj, base, depth - 1, max_elements)?;
+                }
+                field.omitted = field.children.saturating_sub(field.fields.len() as u64);
+                // The aggregate is described by its fields
+                field.value = None;
+            }
+            fields.push(field);
+        }
+        Ok(fields)
+    }
+
+    /// Follow a chain of `next` pointers from `head`, reading the requested
+    /// fields of each node. Stops at a null pointer, when a node repeats,
+    /// or after `max_nodes` nodes.
+    pub fn walk_list(&mut self, head: &str, options: &ListWalkOptions) -> Result<ListWalk> {
+        let link_type = self.console_command(&format!("whatis {}", head))?
+            .trim()
+            .strip_prefix("type = ")
+            .map(|t| t.to_string())
+            .ok_or_else(|| anyhow!("Could not get the type of '{}'", head))?;
+        if !link_type.ends_with('*') {
+            return Err(anyhow!("'{}' is a {}, not a pointer; pass its address", head, link_type));
+        }
+        let next_of = |link: u64| format!("(unsigned long long)(({}){:#x})->{}", link_type, link, options.next);
+
+        let head_addr = self.evaluate_u64(&format!("(unsigned long long)({})", head))?;
+        // Offset of the link inside an intrusive list's nodes
+        let (node_type, link_offset, mut link) = match &options.container {
+            Some((container, member)) => {
+                let offset = self.evaluate_u64(&format!("(unsigned long long)&(({} *)0)->{}", container, member))?;
+                (format!("{} *", container), offset, self.evaluate_u64(&next_of(head_addr))?)
+            }
+            None => (link_type.clone(), 0, head_addr),
+        };
+
+        let mut nodes: Vec<ListNode> = Vec::new();
+        let mut seen: HashMap<u64, usize> = HashMap::new();
+        let (stopped_because, cycle_to, error) = loop {
+            if link == 0 {
+                break ("end", None, None);
+            }
+            if options.container.is_some() && link == head_addr {
+                break ("head", None, None);
+            }
+            if let Some(&index) = seen.get(&link) {
+                break ("cycle", Some(index), None);
+            }
+            if nodes.len() >= options.max_nodes {
+                break ("max_nodes", None, None);
+            }
+
+            let address = link.wrapping_sub(link_offset);
+            let node = format!("(({}){:#x})", node_type, address);
+            let mut entry = ListNode { index: nodes.len(), address: format!("{:#x}", address), fields: Default::default(), value: None };
+            if options.fields.is_empty() {
+                entry.value = Some(self.data_evaluate_expression(&format!("*{}", node))
+                    .unwrap_or_else(|e| format!("<error: {}>", e)));
+            }
+            for field in &options.fields {
+                let value = self.data_evaluate_expression(&format!("{}->{}", node, field))
+                    .unwrap_or_else(|e| format!("<error: {}>", e));
+                entry.fields.insert(field.clone(), value);
+            }
+            seen.insert(link, nodes.len());
+            nodes.push(entry);
+
+            match self.evaluate_u64(&next_of(link)) {
+                Ok(next) => link = next,
+                Err(e) => break ("error", None, Some(e.to_string())),
+            }
+        };
+
+        Ok(ListWalk { nodes, stopped_because: stopped_because.to_string(), cycle_to, error })
+    }
+
+    /// Expression that names a variable object's value (`-var-info-path-expression`)
+    fn var_path_expression(&mut self, name: &str) -> Result<String> {
+        let response = self.send_command(&format!("var-info-path-expression {}", name))?;
+
+        match response {
+            MiOutputRecord::Result { class: ResultClass::Done, results, .. } => results.iter()
+                .find(|r| r.variable == "path_expr")
+                .and_then(|r| MiParser::extract_string(&r.value))
+                .ok_or_else(|| anyhow!("No path expression in response")),
+            MiOutputRecord::Result { class: ResultClass::Error, results, .. } => Err(mi_error(&results, "Failed to get path expression")),
+            _ => Err(anyhow!("Unexpected response")),
+        }
+    }
+
     /// List children of a variable
+    #[allow(dead_code)] // tools expand variable objects with parse_struct_fields
     pub fn var_list_children(&mut self, name: &str) -> Result<Vec<Variable>> {
         let response = self.send_command(&format!("var-list-children --all-values {}", name))?;
         
@@ -0,0 +1,190 @@
+//! Cross-Session Comparison
+//!
+//! Reads the same state from two sessions and reports what differs, for
+//! triage such as a good board against a bad one or old firmware against
+//! new. Each side is read independently, so addresses are resolved by the
+//! session's own symbols.
+
+use crate::gdb::client::GdbClient;
+use crate::gdb::parser::parse_hex_bytes;
+use crate::util::to_hex;
+use anyhow::Result;
+use serde::Serialize;
+use std::collections::BTreeMap;
+
+/// Largest memory range `gdb_compare` reads from each session
+pub const MAX_COMPARE_BYTES: u64 = 64 * 1024;
+
+/// Most differing byte ranges reported
+const MAX_RANGES: usize = 256;
+
+/// What to read from each session
+#[derive(Debug, Clone)]
+pub enum CompareTarget {
+    /// Registers by name; all registers when empty
+    Registers(Vec<String>),
+    Memory { address: String, length: u64 },
+    Expression(String),
+}
+
+/// State read from one session
+#[derive(Debug, Clone)]
+pub enum Reading {
+    Registers(BTreeMap<String, String>),
+    Memory { address: String, bytes: Vec<u8> },
+    Value(String),
+}
+
+impl CompareTarget {
+    pub fn read(&self, client: &mut GdbClient) -> Result<Reading> {
+        Ok(match self {
+            CompareTarget::Registers(wanted) => {
+                let names = client.data_list_register_names()?;
+                let values = client.data_list_register_values()?;
+                let registers = values.into_iter()
+                    .filter_map(|reg| {
+                        let name = names.get(reg.number as usize).filter(|n| !n.is_empty())?;
+                        (wanted.is_empty() || wanted.contains(name)).then(|| (name.clone(), reg.value))
+                    })
+                    .collect();
+                Reading::Registers(registers)
+            }
+            CompareTarget::Memory { address, length } => {
+                let mem = client.data_read_memory(address, *length)?;
+                Reading::Memory { address: mem.addr, bytes: parse_hex_bytes(&mem.data.concat()) }
+            }
+            CompareTarget::Expression(expression) => Reading::Value(client.data_evaluate_expression(expression)?),
+        })
+    }
+}
+
+/// A register whose value differs, or that only one session has
+#[derive(Debug, Clone, Serialize)]
+pub struct RegisterDifference {
+    pub name: String,
+    pub a: Option<String>,
+    pub b: Option<String>,
+}
+
+/// A run of differing bytes
+#[derive(Debug, Clone, Serialize)]
+pub struct ByteRangeDifference {
+    pub offset: u64,
+    pub length: u64,
+    /// Bytes of each session as hex
+    pub a: String,
+    pub b: String,
+}
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(tag = "what", rename_all = "snake_case")]
+pub enum Comparison {
+    Registers {
+        identical: bool,
+        compared: usize,
+        differences: Vec<RegisterDifference>,
+    },
+    Memory {
+        identical: bool,
+        a_address: String,
+        b_address: String,
+        length: u64,
+        differing_bytes: u64,
+        ranges: Vec<ByteRangeDifference>,
+        /// More ranges differ than are listed
+        truncated: bool,
+    },
+    Expression {
+        identical: bool,
+        a: String,
+        b: String,
+    },
+}
+
+impl Comparison {
+    pub fn identical(&self) -> bool {
+        match self {
+            Comparison::Registers { identical, .. }
+            | Comparison::Memory { identical, .. }
+            | Comparison::Expression { identical, .. } => *identical,
+        }
+    }
+}
+
+/// Compare the readings of two sessions made with the same target
+pub fn compare(a: Reading, b: Reading) -> Comparison {
+    match (a, b) {
+        (Reading::Registers(a), Reading::Registers(b)) => {
+            let names: std::collections::BTreeSet<&String> = a.keys().chain(b.keys()).collect();
+            let differences: Vec<RegisterDifference> = names.iter()
+                .filter(|name| a.get(**name) != b.get(**name))
+                .map(|name| RegisterDifference { name: (*name).clone(), a: a.get(*name).cloned(), b: b.get(*name).cloned() })
+                .collect();
+            Comparison::Registers { identical: differences.is_empty(), compared: names.len(), differences }
+        }
+        (Reading::Memory { address: a_address, bytes: a }, Reading::Memory { address: b_address, bytes: b }) => {
+            let (ranges, differing_bytes) = byte_ranges(&a, &b);
+            let truncated = ranges.len() > MAX_RANGES;
+            let ranges = ranges.into_iter()
+                .take(MAX_RANGES)
+                .map(|(start, end)| ByteRangeDifference {
+                    offset: start as u64,
+                    length: (end - start) as u64,
+                    a: to_hex(a.get(start..end.min(a.len())).unwrap_or_default()),
+                    b: to_hex(b.get(start..end.min(b.len())).unwrap_or_default()),
+                })
+                .collect();
+            Comparison::Memory {
+                identical: differing_bytes == 0,
+                a_address,
+                b_address,
+                length: a.len().max(b.len()) as u64,
+                differing_bytes,
+                ranges,
+                truncated,
+            }
+        }
+        (Reading::Value(a), Reading::Value(b)) => Comparison::Expression { identical: a == b, a, b },
+        _ => unreachable!("both sessions are read with the same target"),
+    }
+}
+
+/// Ranges `[start, end)` where the buffers differ, counting a byte missing
+/// from the shorter buffer as different, and the number of differing bytes
+fn byte_ranges(a: &[u8], b: &[u8]) -> (Vec<(usize, usize)>, u64) {
+    let mut ranges: Vec<(usize, usize)> = Vec::new();
+    let mut count = 0;
+    for i in 0..a.len().max(b.len()) {
+        if a.get(i) == b.get(i) {
+            continue;
+        }
+        count += 1;
+        match ranges.last_mut() {
+            Some((_, end)) if *end == i => *end = i + 1,
+            _ => ranges.push((i, i + 1)),
+        }
+    }
+    (ranges, count)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_compare_memory_and_registers() {
+        let a = Reading::Memory { address: "0x1000".to_string(), bytes: vec![1, 2, 3, 4, 5, 6] };
+        let b = Reading::Memory { address: "0x2000".to_string(), bytes: vec![1, 9, 9, 4, 5, 7] };
+        let Comparison::Memory { differing_bytes, ranges, identical, .. } = compare(a, b) else { panic!() };
+        assert!(!identical);
+        assert_eq!(differing_bytes, 3);
+        assert_eq!(ranges.iter().map(|r| (r.offset, r.length)).collect::<Vec<_>>(), [(1, 2), (5, 1)]);
+        assert_eq!((ranges[0].a.as_str(), ranges[0].b.as_str()), ("0203", "0909"));
+
+        let regs = |pairs: &[(&str, &str)]| Reading::Registers(pairs.iter().map(|(n, v)| (n.to_string(), v.to_string())).collect());
+        let comparison = compare(regs(&[("pc", "0x10"), ("sp", "0x20")]), regs(&[("pc", "0x10"), ("sp", "0x28"), ("lr", "0x4")]));
+        let Comparison::Registers { compared, differences, .. } = comparison else { panic!() };
+        assert_eq!(compared, 3);
+        assert_eq!(differences.iter().map(|d| d.name.as_str()).collect::<Vec<_>>(), ["lr", "sp"]);
+    }
+}
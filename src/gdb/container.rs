@@ -0,0 +1,125 @@
+//! Container Targets
+//!
+//! Locating processes inside Docker or Podman containers. A containerized
+//! process can be debugged from the host by attaching to its host PID with
+//! the sysroot pointed at `/proc/<pid>/root`, or through a gdbserver
+//! started inside the container.
+
+use anyhow::{anyhow, Context, Result};
+use std::fs;
+use std::process::Command;
+
+/// Container runtimes accepted by `gdb_container_attach`
+pub const RUNTIMES: &[&str] = &["docker", "podman"];
+
+/// Run a container runtime command and return its trimmed stdout
+fn runtime_output(runtime: &str, args: &[&str]) -> Result<String> {
+    let output = Command::new(runtime)
+        .args(args)
+        .output()
+        .with_context(|| format!("Failed to run {}", runtime))?;
+    if !output.status.success() {
+        return Err(anyhow!(
+            "{} {} failed: {}",
+            runtime,
+            args.join(" "),
+            String::from_utf8_lossy(&output.stderr).trim()
+        ));
+    }
+    Ok(String::from_utf8_lossy(&output.stdout).trim().to_string())
+}
+
+/// Host PID of the container's init process
+pub fn init_pid(runtime: &str, container: &str) -> Result<u32> {
+    let pid = runtime_output(runtime, &["inspect", "--format", "{{.State.Pid}}", container])?;
+    match pid.parse::<u32>() {
+        Ok(0) => Err(anyhow!("Container {} is not running", container)),
+        Ok(pid) => Ok(pid),
+        Err(_) => Err(anyhow!("Unexpected PID '{}' for container {}", pid, container)),
+    }
+}
+
+/// IP address of the container on its first network
+pub fn ip_address(runtime: &str, container: &str) -> Result<String> {
+    let ips = runtime_output(
+        runtime,
+        &["inspect", "--format", "{{range .NetworkSettings.Networks}}{{.IPAddress}} {{end}}", container],
+    )?;
+    ips.split_whitespace()
+        .next()
+        .map(|ip| ip.to_string())
+        .ok_or_else(|| anyhow!("Container {} has no IP address; pass host to use a published port", container))
+}
+
+/// PID of a process as seen inside the container: a numeric `process` is
+/// used as is, a name is looked up with `pidof`, and no process means the
+/// container's init process (PID 1)
+pub fn container_pid(runtime: &str, container: &str, process: Option<&str>) -> Result<u32> {
+    match process {
+        None => Ok(1),
+        Some(p) if p.parse::<u32>().is_ok() => Ok(p.parse()?),
+        Some(name) => {
+            let pid = runtime_output(runtime, &["exec", container, "pidof", "-s", name])
+                .with_context(|| format!("No process named {} in container {}", name, container))?;
+            pid.parse().map_err(|_| anyhow!("Unexpected pidof output '{}'", pid))
+        }
+    }
+}
+
+/// Host PID of a process in the same PID namespace as `init_pid`. The
+/// process is given by its PID inside the container or by name; no process
+/// means the init process itself.
+pub fn host_pid(init_pid: u32, process: Option<&str>) -> Result<u32> {
+    let Some(process) = process else {
+        return Ok(init_pid);
+    };
+    let namespace = fs::read_link(format!("/proc/{}/ns/pid", init_pid))
+        .with_context(|| format!("Cannot read the PID namespace of {}", init_pid))?;
+    let wanted_pid = process.parse::<u32>().ok();
+
+    let mut matches = Vec::new();
+    for entry in fs::read_dir("/proc")?.flatten() {
+        let Some(pid) = entry.file_name().to_str().and_then(|n| n.parse::<u32>().ok()) else {
+            continue;
+        };
+        if fs::read_link(format!("/proc/{}/ns/pid", pid)).ok().as_ref() != Some(&namespace) {
+            continue;
+        }
+        let found = match wanted_pid {
+            Some(wanted) => fs::read_to_string(format!("/proc/{}/status", pid))
+                .ok()
+                .and_then(|s| parse_nspid(&s))
+                .and_then(|ids| ids.last().copied())
+                == Some(wanted),
+            None => fs::read_to_string(format!("/proc/{}/comm", pid)).is_ok_and(|c| c.trim() == process),
+        };
+        if found {
+            matches.push(pid);
+        }
+    }
+
+    matches.sort_unstable();
+    matches
+        .first()
+        .copied()
+        .ok_or_else(|| anyhow!("No process {} in the container (host PID namespace of {})", process, init_pid))
+}
+
+/// PIDs from the `NSpid:` line of `/proc/<pid>/status`, outermost
+/// namespace first
+fn parse_nspid(status: &str) -> Option<Vec<u32>> {
+    let line = status.lines().find_map(|l| l.strip_prefix("NSpid:"))?;
+    line.split_whitespace().map(|p| p.parse().ok()).collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_nspid() {
+        let status = "Name:\tnginx\nTgid:\t4242\nPid:\t4242\nNSpid:\t4242\t7\nPPid:\t4200\n";
+        assert_eq!(parse_nspid(status), Some(vec![4242, 7]));
+        assert_eq!(parse_nspid("Name:\tbash\nPid:\t1\n"), None);
+    }
+}
@@ -0,0 +1,136 @@
+//! Convenience Variables
+//!
+//! GDB's `$`-variables hold values between commands, so an address worked
+//! out once (`$node = list->head->next`) can be reused in later expressions
+//! instead of repeating the whole chain. GDB's own conveniences also answer
+//! questions about the session: `$_exitcode`, `$_siginfo`, and the settings
+//! behind `$_gdb_setting_str`.
+
+use crate::gdb::types::SigInfo;
+use serde::Serialize;
+use std::collections::BTreeMap;
+
+/// Settings shown by `gdb_status`, read with `$_gdb_setting_str`
+pub const STATUS_SETTINGS: &[&str] = &[
+    "scheduler-locking",
+    "non-stop",
+    "follow-fork-mode",
+    "detach-on-fork",
+    "can-use-hw-watchpoints",
+];
+
+/// Names GDB maintains itself
+const RESERVED_NAMES: &[&str] = &["bpnum", "tpnum", "exitcode", "cdir", "cwd"];
+
+/// Register aliases GDB accepts on every architecture
+const STANDARD_REGISTERS: &[&str] = &["pc", "sp", "fp", "ps"];
+
+/// Check a variable name (with or without the `$`) and return it without
+/// the `$`. Registers are rejected, since assigning one changes the target.
+pub fn validate_name(name: &str, registers: &[String]) -> Result<String, String> {
+    let name = name.trim().trim_start_matches('$');
+    let mut chars = name.chars();
+    let valid = chars.next().is_some_and(|c| c.is_ascii_alphabetic() || c == '_')
+        && chars.all(|c| c.is_ascii_alphanumeric() || c == '_');
+    if !valid {
+        return Err(format!("'{}' is not a variable name; use letters, digits and _", name));
+    }
+    if name.starts_with('_') || RESERVED_NAMES.contains(&name) || name.starts_with("trace_") {
+        return Err(format!("${} belongs to GDB", name));
+    }
+    if STANDARD_REGISTERS.contains(&name) || registers.iter().any(|r| r == name) {
+        return Err(format!("${} is a register; assigning it would change the target", name));
+    }
+    Ok(name.to_string())
+}
+
+/// A convenience variable and its value
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct ConvenienceValue {
+    pub name: String,
+    /// None when the variable is void (never assigned)
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub value: Option<String>,
+    /// Expression assigned by `gdb_convenience_set` in this session
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub expression: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub error: Option<String>,
+}
+
+/// Variables listed by `show convenience`, without GDB's internal functions
+pub fn parse_show_convenience(output: &str) -> Vec<ConvenienceValue> {
+    output.lines()
+        .filter_map(|line| {
+            let (name, value) = line.split_once(" = ")?;
+            let name = name.trim().strip_prefix('$')?;
+            let value = value.trim();
+            if value.starts_with("<internal function") || name.contains(char::is_whitespace) {
+                return None;
+            }
+            Some(ConvenienceValue {
+                name: name.to_string(),
+                value: (value != "void").then(|| value.to_string()),
+                expression: None,
+                error: None,
+            })
+        })
+        .collect()
+}
+
+/// Strip the quotes of a string value, e.g. `"replay"` from `$_gdb_setting_str`
+pub fn unquote(value: &str) -> String {
+    let value = value.trim();
+    value.strip_prefix('"').and_then(|v| v.strip_suffix('"')).unwrap_or(value).to_string()
+}
+
+/// GDB's conveniences of interest, for `gdb_status`
+#[derive(Debug, Clone, Default, Serialize)]
+pub struct ConvenienceSnapshot {
+    /// `$_exitcode`, once the program has exited
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub exitcode: Option<i64>,
+    /// `$_siginfo`, when the program stopped for a signal
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub siginfo: Option<SigInfo>,
+    /// `$_gdb_setting_str` of `STATUS_SETTINGS` (GDB 10 and later)
+    #[serde(skip_serializing_if = "BTreeMap::is_empty")]
+    pub settings: BTreeMap<String, String>,
+    /// Variables set with `gdb_convenience_set`
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub variables: Vec<ConvenienceValue>,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_convenience_names() {
+        let registers = vec!["rax".to_string(), "rip".to_string()];
+        assert_eq!(validate_name("$node", &registers), Ok("node".to_string()));
+        assert_eq!(validate_name("base_2", &registers), Ok("base_2".to_string()));
+        assert!(validate_name("$rax", &registers).unwrap_err().contains("register"));
+        assert!(validate_name("$pc", &registers).is_err());
+        assert!(validate_name("$_exitcode", &registers).unwrap_err().contains("belongs to GDB"));
+        assert!(validate_name("$1", &registers).is_err());
+        assert!(validate_name("a b", &registers).is_err());
+
+        let output = [
+            "$node = (struct node *) 0x4052a0",
+            "$count = 3",
+            "$_exitcode = void",
+            "$_gdb_setting_str = <internal function _gdb_setting_str>",
+            "$bpnum = 1",
+        ].join("\n");
+        let values = parse_show_convenience(&output);
+        let names: Vec<&str> = values.iter().map(|v| v.name.as_str()).collect();
+        assert_eq!(names, vec!["node", "count", "_exitcode", "bpnum"]);
+        assert_eq!(values[0].value.as_deref(), Some("(struct node *) 0x4052a0"));
+        assert_eq!(values[2].value, None);
+        assert!(parse_show_convenience("No debugger convenience values now defined.").is_empty());
+
+        assert_eq!(unquote("\"off\""), "off");
+        assert_eq!(unquote("on"), "on");
+    }
+}
@@ -0,0 +1,179 @@
+//! Context Switch Tracing
+//!
+//! Scheduler bugs show up as the wrong task running, or the right one never
+//! getting the CPU. Seeing that by hand means a breakpoint on the kernel's
+//! switch function and hundreds of continues. `gdb_trace_context_switch`
+//! breaks on the switch function (`__switch_to` by default), reads the
+//! outgoing and incoming task at each hit, resumes, and returns the log.
+
+use serde::Serialize;
+use std::collections::BTreeMap;
+
+/// Switch function of Linux; its first two arguments are the previous and
+/// next `struct task_struct *`
+pub const DEFAULT_SWITCH_SYMBOL: &str = "__switch_to";
+
+/// Task fields read when a task type is given but no fields
+pub const DEFAULT_TASK_FIELDS: &[&str] = &["pid", "comm"];
+
+/// What to read at each switch
+#[derive(Debug, Clone)]
+pub struct SwitchTraceSpec {
+    pub symbol: String,
+    /// Outgoing task (default: the first argument register)
+    pub prev: Option<String>,
+    /// Incoming task (default: the second argument register)
+    pub next: Option<String>,
+    /// Type the task expressions point to, e.g. `struct task_struct`
+    pub task_type: Option<String>,
+    /// Fields read through the task pointer
+    pub fields: Vec<String>,
+}
+
+impl SwitchTraceSpec {
+    /// Expression of a field of the task `task`
+    pub fn field_expression(&self, task: &str, field: &str) -> String {
+        match &self.task_type {
+            Some(ty) => format!("(({} *)({}))->{}", ty, task, field),
+            None => format!("({})->{}", task, field),
+        }
+    }
+}
+
+/// One side of a switch
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct TaskRef {
+    /// Value of the prev/next expression, usually the task pointer
+    pub value: String,
+    #[serde(skip_serializing_if = "BTreeMap::is_empty")]
+    pub fields: BTreeMap<String, String>,
+}
+
+impl TaskRef {
+    /// `comm="bash" pid=123`-style label: the fields when there are any,
+    /// else the value
+    pub fn label(&self) -> String {
+        if self.fields.is_empty() {
+            return self.value.clone();
+        }
+        self.fields.iter()
+            .map(|(name, value)| format!("{}={}", name, value))
+            .collect::<Vec<_>>()
+            .join(" ")
+    }
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct ContextSwitch {
+    /// Switch number, counting from 1
+    pub switch: u64,
+    /// Thread (core, on QEMU) the switch happened on
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub thread_id: Option<String>,
+    pub prev: TaskRef,
+    pub next: TaskRef,
+}
+
+/// How often a task was switched to
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct TaskCount {
+    pub task: String,
+    pub switched_in: u64,
+}
+
+/// Result of `gdb_trace_context_switch`
+#[derive(Debug, Clone, Serialize)]
+pub struct ContextSwitchTrace {
+    pub symbol: String,
+    pub prev_expression: String,
+    pub next_expression: String,
+    pub switches: Vec<ContextSwitch>,
+    /// Tasks by the number of times they were switched to, most first
+    pub tasks: Vec<TaskCount>,
+    /// Why tracing ended: "completed" (max_switches reached), "timeout", or
+    /// the stop that interrupted it
+    pub stopped_because: String,
+}
+
+impl ContextSwitchTrace {
+    /// One line per switch: `#1 [thread 1] prev -> next`
+    pub fn render(&self) -> String {
+        self.switches.iter()
+            .map(|s| {
+                let thread = s.thread_id.as_ref().map(|t| format!(" [thread {}]", t)).unwrap_or_default();
+                format!("#{}{} {} -> {}", s.switch, thread, s.prev.label(), s.next.label())
+            })
+            .collect::<Vec<_>>()
+            .join("\n")
+    }
+}
+
+/// Count the switches into each task
+pub fn switch_counts(switches: &[ContextSwitch]) -> Vec<TaskCount> {
+    let mut counts: Vec<TaskCount> = Vec::new();
+    for switch in switches {
+        let task = switch.next.label();
+        match counts.iter_mut().find(|c| c.task == task) {
+            Some(count) => count.switched_in += 1,
+            None => counts.push(TaskCount { task, switched_in: 1 }),
+        }
+    }
+    counts.sort_by_key(|c| std::cmp::Reverse(c.switched_in));
+    counts
+}
+
+/// Drop the NUL padding GDB prints after a fixed-size string such as
+/// `comm`: `"bash", '\000' <repeats 11 times>` becomes `"bash"`
+pub fn trim_string_padding(value: &str) -> String {
+    match value.split_once(", '\\000'") {
+        Some((text, _)) if text.starts_with('"') => text.to_string(),
+        _ => value.to_string(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn task(pid: &str, comm: &str) -> TaskRef {
+        TaskRef {
+            value: "0xffff888003a8c000".to_string(),
+            fields: [("comm".to_string(), comm.to_string()), ("pid".to_string(), pid.to_string())].into_iter().collect(),
+        }
+    }
+
+    #[test]
+    fn test_switch_log() {
+        assert_eq!(trim_string_padding(r#""kworker/0:1", '\000' <repeats 4 times>"#), r#""kworker/0:1""#);
+        assert_eq!(trim_string_padding("42"), "42");
+
+        let spec = SwitchTraceSpec {
+            symbol: DEFAULT_SWITCH_SYMBOL.to_string(),
+            prev: None,
+            next: None,
+            task_type: Some("struct task_struct".to_string()),
+            fields: vec!["pid".to_string()],
+        };
+        assert_eq!(spec.field_expression("$rdi", "pid"), "((struct task_struct *)($rdi))->pid");
+
+        let switch = |n, prev, next| ContextSwitch { switch: n, thread_id: Some("1".to_string()), prev, next };
+        let switches = vec![
+            switch(1, task("0", "\"swapper/0\""), task("123", "\"bash\"")),
+            switch(2, task("123", "\"bash\""), task("0", "\"swapper/0\"")),
+            switch(3, task("0", "\"swapper/0\""), task("123", "\"bash\"")),
+        ];
+        assert_eq!(switch_counts(&switches), vec![
+            TaskCount { task: "comm=\"bash\" pid=123".to_string(), switched_in: 2 },
+            TaskCount { task: "comm=\"swapper/0\" pid=0".to_string(), switched_in: 1 },
+        ]);
+        let trace = ContextSwitchTrace {
+            symbol: spec.symbol,
+            prev_expression: "$rdi".to_string(),
+            next_expression: "$rsi".to_string(),
+            tasks: switch_counts(&switches),
+            switches,
+            stopped_because: "completed".to_string(),
+        };
+        assert!(trace.render().starts_with("#1 [thread 1] comm=\"swapper/0\" pid=0 -> comm=\"bash\" pid=123\n"));
+    }
+}
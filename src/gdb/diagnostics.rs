@@ -0,0 +1,149 @@
+//! GDB Diagnostics
+//!
+//! GDB prints many of the warnings that explain later failures ("no
+//! debugging symbols found", a shared library whose symbols could not be
+//! loaded, a dropped remote connection) on stderr or the MI log stream, not
+//! in command results. The lines are kept per session so they can be read
+//! back with `gdb_diagnostics`, and critical ones are attached to the result
+//! of the tool call they happened during.
+
+use crate::util::now_ms;
+use serde::{Deserialize, Serialize};
+use std::collections::VecDeque;
+
+/// Maximum number of lines kept per session
+const MAX_LINES: usize = 1000;
+
+/// Messages that usually explain a failure of a later tool call
+const CRITICAL_PATTERNS: &[&str] = &[
+    "no debugging symbols found",
+    "could not load shared library symbols",
+    "error while mapping shared library sections",
+    "unable to find dynamic linker breakpoint",
+    "missing separate debuginfo",
+    "remote communication error",
+    "remote connection closed",
+    "connection timed out",
+    "cannot insert breakpoint",
+    "cannot access memory",
+    "python exception",
+    "architecture of file not recognized",
+    "is not in executable format",
+];
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Severity {
+    Info,
+    Warning,
+    Critical,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum DiagnosticSource {
+    /// GDB's standard error
+    Stderr,
+    /// The MI log stream (`&"..."` records)
+    Log,
+    /// A lifecycle hook command that failed
+    Hook,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct DiagnosticLine {
+    pub seq: u64,
+    pub timestamp_ms: u64,
+    pub source: DiagnosticSource,
+    pub severity: Severity,
+    pub text: String,
+}
+
+/// Recent diagnostic lines of a session
+#[derive(Debug, Default)]
+pub struct Diagnostics {
+    lines: VecDeque<DiagnosticLine>,
+    next_seq: u64,
+}
+
+impl Diagnostics {
+    /// Record a line. Log stream lines are only kept when they are warnings,
+    /// as the stream also echoes every CLI command.
+    pub fn push(&mut self, source: DiagnosticSource, text: &str) {
+        let text = text.trim_end();
+        let severity = classify(text);
+        if text.is_empty() || (source == DiagnosticSource::Log && severity == Severity::Info) {
+            return;
+        }
+        self.push_with_severity(source, severity, text);
+    }
+
+    /// Record a line whose severity is already known
+    pub fn push_with_severity(&mut self, source: DiagnosticSource, severity: Severity, text: &str) {
+        self.next_seq += 1;
+        self.lines.push_back(DiagnosticLine {
+            seq: self.next_seq,
+            timestamp_ms: now_ms(),
+            source,
+            severity,
+            text: text.to_string(),
+        });
+        if self.lines.len() > MAX_LINES {
+            self.lines.pop_front();
+        }
+    }
+
+    /// Sequence number of the latest line (0 if none)
+    pub fn last_seq(&self) -> u64 {
+        self.next_seq
+    }
+
+    /// The last `limit` lines at or above `severity`, oldest first
+    pub fn recent(&self, severity: Severity, limit: usize) -> Vec<DiagnosticLine> {
+        let mut lines: Vec<_> = self.lines.iter().rev().filter(|l| l.severity >= severity).take(limit).cloned().collect();
+        lines.reverse();
+        lines
+    }
+
+    /// Critical lines recorded after `seq`
+    pub fn critical_since(&self, seq: u64) -> Vec<DiagnosticLine> {
+        self.lines.iter().filter(|l| l.seq > seq && l.severity == Severity::Critical).cloned().collect()
+    }
+
+    pub fn clear(&mut self) {
+        self.lines.clear();
+    }
+}
+
+fn classify(text: &str) -> Severity {
+    let lower = text.to_lowercase();
+    if CRITICAL_PATTERNS.iter().any(|pattern| lower.contains(pattern)) {
+        Severity::Critical
+    } else if lower.starts_with("warning:") || lower.starts_with("error") || lower.contains("warning: ") {
+        Severity::Warning
+    } else {
+        Severity::Info
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_diagnostics() {
+        let mut diagnostics = Diagnostics::default();
+        diagnostics.push(DiagnosticSource::Log, "info frame\n");
+        diagnostics.push(DiagnosticSource::Stderr, "Reading symbols from ./a.out...");
+        let mark = diagnostics.last_seq();
+        diagnostics.push(DiagnosticSource::Stderr, "(No debugging symbols found in ./a.out)");
+        diagnostics.push(DiagnosticSource::Log, "warning: Source file is more recent than executable.\n");
+
+        assert_eq!(diagnostics.recent(Severity::Info, 10).len(), 3);
+        assert_eq!(diagnostics.recent(Severity::Warning, 10).len(), 2);
+        let critical = diagnostics.critical_since(mark);
+        assert_eq!(critical.len(), 1);
+        assert!(critical[0].text.contains("No debugging symbols"));
+        assert!(diagnostics.critical_since(diagnostics.last_seq()).is_empty());
+    }
+}
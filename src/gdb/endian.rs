@@ -0,0 +1,113 @@
+//! Byte Order Checks
+//!
+//! A wrong `set endian` (or a stale one left from an earlier target) makes
+//! every multi-byte read come back byte-swapped without any error. GDB's
+//! setting is compared with the EI_DATA byte of the executable's ELF header
+//! after loading a file and after connecting to a target.
+
+use anyhow::{Context, Result};
+use serde::Serialize;
+use std::io::Read;
+use std::path::Path;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Endian {
+    Little,
+    Big,
+}
+
+impl std::fmt::Display for Endian {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(match self {
+            Endian::Little => "little",
+            Endian::Big => "big",
+        })
+    }
+}
+
+/// Result of `gdb_endian`
+#[derive(Debug, Clone, Serialize)]
+pub struct EndianCheck {
+    /// Byte order GDB uses for the target
+    pub gdb: Endian,
+    /// GDB picks the byte order itself rather than a `set endian`
+    pub automatic: bool,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub executable: Option<String>,
+    /// Byte order from the executable's ELF header
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub elf: Option<Endian>,
+    pub mismatch: bool,
+}
+
+impl EndianCheck {
+    pub fn new(gdb: Endian, automatic: bool, executable: Option<String>, elf: Option<Endian>) -> Self {
+        Self { gdb, automatic, executable, elf, mismatch: elf.is_some_and(|elf| elf != gdb) }
+    }
+
+    pub fn warning(&self) -> Option<String> {
+        let elf = self.elf.filter(|_| self.mismatch)?;
+        Some(format!(
+            "GDB uses {} endian but {} is a {}-endian ELF file, so memory and register values will appear byte-swapped. Fix it with gdb_endian set={} (or set=auto).",
+            self.gdb,
+            self.executable.as_deref().unwrap_or("the executable"),
+            elf,
+            elf
+        ))
+    }
+}
+
+/// Parse `show endian`: "The target endianness is set automatically
+/// (currently little endian)." or "The target is set to big endian."
+pub fn parse_show_endian(output: &str) -> Option<(Endian, bool)> {
+    let endian = if output.contains("little endian") {
+        Endian::Little
+    } else if output.contains("big endian") {
+        Endian::Big
+    } else {
+        return None;
+    };
+    Some((endian, output.contains("automatically")))
+}
+
+/// Byte order of an ELF file, or None if it is not an ELF file
+pub fn elf_endian(path: &Path) -> Result<Option<Endian>> {
+    let mut header = [0u8; 6];
+    std::fs::File::open(path)
+        .and_then(|mut file| file.read_exact(&mut header))
+        .with_context(|| format!("Failed to read {}", path.display()))?;
+    Ok(elf_header_endian(&header))
+}
+
+fn elf_header_endian(header: &[u8]) -> Option<Endian> {
+    if !header.starts_with(b"\x7fELF") {
+        return None;
+    }
+    match header.get(5) {
+        Some(1) => Some(Endian::Little),
+        Some(2) => Some(Endian::Big),
+        _ => None,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_endian_check() {
+        assert_eq!(
+            parse_show_endian("The target endianness is set automatically (currently little endian).\n"),
+            Some((Endian::Little, true))
+        );
+        assert_eq!(parse_show_endian("The target is set to big endian.\n"), Some((Endian::Big, false)));
+        assert_eq!(elf_header_endian(b"\x7fELF\x01\x02"), Some(Endian::Big));
+        assert_eq!(elf_header_endian(b"MZ\x90\x00\x03\x00"), None);
+
+        let check = EndianCheck::new(Endian::Big, false, Some("fw.elf".to_string()), Some(Endian::Little));
+        assert!(check.mismatch);
+        assert!(check.warning().unwrap().contains("fw.elf is a little-endian ELF"));
+        assert!(EndianCheck::new(Endian::Little, true, None, None).warning().is_none());
+    }
+}
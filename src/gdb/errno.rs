@@ -0,0 +1,127 @@
+//! errno Values
+//!
+//! Linux errno names and their `strerror` descriptions, so a failed libc
+//! call can be explained without the caller looking the number up. The
+//! numbers are those of the generic Linux ABI used by x86, ARM, AArch64 and
+//! RISC-V; MIPS, Alpha, SPARC and PA-RISC number them differently.
+
+/// errno number, name and description
+const ERRNO: &[(i64, &str, &str)] = &[
+    (1, "EPERM", "Operation not permitted"),
+    (2, "ENOENT", "No such file or directory"),
+    (3, "ESRCH", "No such process"),
+    (4, "EINTR", "Interrupted system call"),
+    (5, "EIO", "Input/output error"),
+    (6, "ENXIO", "No such device or address"),
+    (7, "E2BIG", "Argument list too long"),
+    (8, "ENOEXEC", "Exec format error"),
+    (9, "EBADF", "Bad file descriptor"),
+    (10, "ECHILD", "No child processes"),
+    (11, "EAGAIN", "Resource temporarily unavailable"),
+    (12, "ENOMEM", "Cannot allocate memory"),
+    (13, "EACCES", "Permission denied"),
+    (14, "EFAULT", "Bad address"),
+    (15, "ENOTBLK", "Block device required"),
+    (16, "EBUSY", "Device or resource busy"),
+    (17, "EEXIST", "File exists"),
+    (18, "EXDEV", "Invalid cross-device link"),
+    (19, "ENODEV", "No such device"),
+    (20, "ENOTDIR", "Not a directory"),
+    (21, "EISDIR", "Is a directory"),
+    (22, "EINVAL", "Invalid argument"),
+    (23, "ENFILE", "Too many open files in system"),
+    (24, "EMFILE", "Too many open files"),
+    (25, "ENOTTY", "Inappropriate ioctl for device"),
+    (26, "ETXTBSY", "Text file busy"),
+    (27, "EFBIG", "File too large"),
+    (28, "ENOSPC", "No space left on device"),
+    (29, "ESPIPE", "Illegal seek"),
+    (30, "EROFS", "Read-only file system"),
+    (31, "EMLINK", "Too many links"),
+    (32, "EPIPE", "Broken pipe"),
+    (33, "EDOM", "Numerical argument out of domain"),
+    (34, "ERANGE", "Numerical result out of range"),
+    (35, "EDEADLK", "Resource deadlock avoided"),
+    (36, "ENAMETOOLONG", "File name too long"),
+    (37, "ENOLCK", "No locks available"),
+    (38, "ENOSYS", "Function not implemented"),
+    (39, "ENOTEMPTY", "Directory not empty"),
+    (40, "ELOOP", "Too many levels of symbolic links"),
+    (42, "ENOMSG", "No message of desired type"),
+    (43, "EIDRM", "Identifier removed"),
+    (60, "ENOSTR", "Device not a stream"),
+    (61, "ENODATA", "No data available"),
+    (62, "ETIME", "Timer expired"),
+    (63, "ENOSR", "Out of streams resources"),
+    (67, "ENOLINK", "Link has been severed"),
+    (71, "EPROTO", "Protocol error"),
+    (72, "EMULTIHOP", "Multihop attempted"),
+    (74, "EBADMSG", "Bad message"),
+    (75, "EOVERFLOW", "Value too large for defined data type"),
+    (84, "EILSEQ", "Invalid or incomplete multibyte or wide character"),
+    (87, "EUSERS", "Too many users"),
+    (88, "ENOTSOCK", "Socket operation on non-socket"),
+    (89, "EDESTADDRREQ", "Destination address required"),
+    (90, "EMSGSIZE", "Message too long"),
+    (91, "EPROTOTYPE", "Protocol wrong type for socket"),
+    (92, "ENOPROTOOPT", "Protocol not available"),
+    (93, "EPROTONOSUPPORT", "Protocol not supported"),
+    (94, "ESOCKTNOSUPPORT", "Socket type not supported"),
+    (95, "EOPNOTSUPP", "Operation not supported"),
+    (96, "EPFNOSUPPORT", "Protocol family not supported"),
+    (97, "EAFNOSUPPORT", "Address family not supported by protocol"),
+    (98, "EADDRINUSE", "Address already in use"),
+    (99, "EADDRNOTAVAIL", "Cannot assign requested address"),
+    (100, "ENETDOWN", "Network is down"),
+    (101, "ENETUNREACH", "Network is unreachable"),
+    (102, "ENETRESET", "Network dropped connection on reset"),
+    (103, "ECONNABORTED", "Software caused connection abort"),
+    (104, "ECONNRESET", "Connection reset by peer"),
+    (105, "ENOBUFS", "No buffer space available"),
+    (106, "EISCONN", "Transport endpoint is already connected"),
+    (107, "ENOTCONN", "Transport endpoint is not connected"),
+    (108, "ESHUTDOWN", "Cannot send after transport endpoint shutdown"),
+    (109, "ETOOMANYREFS", "Too many references: cannot splice"),
+    (110, "ETIMEDOUT", "Connection timed out"),
+    (111, "ECONNREFUSED", "Connection refused"),
+    (112, "EHOSTDOWN", "Host is down"),
+    (113, "EHOSTUNREACH", "No route to host"),
+    (114, "EALREADY", "Operation already in progress"),
+    (115, "EINPROGRESS", "Operation now in progress"),
+    (116, "ESTALE", "Stale file handle"),
+    (122, "EDQUOT", "Disk quota exceeded"),
+    (125, "ECANCELED", "Operation canceled"),
+    (126, "ENOKEY", "Required key not available"),
+    (127, "EKEYEXPIRED", "Key has expired"),
+    (128, "EKEYREVOKED", "Key has been revoked"),
+    (129, "EKEYREJECTED", "Key was rejected by service"),
+    (130, "EOWNERDEAD", "Owner died"),
+    (131, "ENOTRECOVERABLE", "State not recoverable"),
+    (132, "ERFKILL", "Operation not possible due to RF-kill"),
+    (133, "EHWPOISON", "Memory page has hardware error"),
+];
+
+/// Name and description of an errno value
+pub fn errno_info(errno: i64) -> Option<(&'static str, &'static str)> {
+    ERRNO.iter().find(|(n, _, _)| *n == errno).map(|(_, name, description)| (*name, *description))
+}
+
+/// Whether a frame's code comes from the C library
+pub fn is_libc(library: &str) -> bool {
+    let file = library.rsplit('/').next().unwrap_or(library);
+    ["libc.so", "libc-", "libpthread", "ld-linux", "libc.musl", "ld-musl"].iter().any(|prefix| file.starts_with(prefix))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_errno_info() {
+        assert_eq!(errno_info(2), Some(("ENOENT", "No such file or directory")));
+        assert_eq!(errno_info(13).map(|(name, _)| name), Some("EACCES"));
+        assert_eq!(errno_info(0), None);
+        assert!(is_libc("/lib/x86_64-linux-gnu/libc.so.6"));
+        assert!(!is_libc("/usr/lib/libcurl.so.4"));
+    }
+}
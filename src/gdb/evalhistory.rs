@@ -0,0 +1,144 @@
+//! Evaluation History
+//!
+//! Values read earlier in a session are often needed again after the target
+//! has moved on: "what was `len` at the first stop?" cannot be answered by
+//! evaluating it again. Every `gdb_evaluate` is kept with the stop generation
+//! it was read at (the number of stops seen before it), so `gdb_eval_history`
+//! can return earlier observations and tell which are still current.
+
+use serde::Serialize;
+use std::collections::VecDeque;
+
+/// Evaluations kept per session; the oldest are dropped first
+const MAX_EVALUATIONS: usize = 1000;
+
+/// One evaluated expression
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct Evaluation {
+    /// Position in the session's history, counting from 1
+    pub seq: u64,
+    pub expression: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub value: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub error: Option<String>,
+    /// Stops seen before the evaluation; values with the same generation
+    /// were read while the program was stopped at the same place
+    pub generation: u64,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub thread_id: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub frame_level: Option<u64>,
+    /// Where the program was stopped, "func (addr)" or the address
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub location: Option<String>,
+    /// Milliseconds since the Unix epoch
+    pub timestamp_ms: u64,
+}
+
+/// An evaluation as returned by `gdb_eval_history`
+#[derive(Debug, Clone, Serialize)]
+pub struct EvaluationView {
+    #[serde(flatten)]
+    pub evaluation: Evaluation,
+    /// The program has not run since the value was read
+    pub current: bool,
+}
+
+/// Which evaluations to return
+#[derive(Debug, Clone, Default)]
+pub struct EvalQuery {
+    /// Only expressions containing this text
+    pub expression: Option<String>,
+    /// Only evaluations of this generation
+    pub generation: Option<u64>,
+    /// Only evaluations that succeeded
+    pub values_only: bool,
+    pub limit: usize,
+}
+
+#[derive(Debug, Default)]
+pub struct EvalHistory {
+    entries: VecDeque<Evaluation>,
+    next_seq: u64,
+}
+
+impl EvalHistory {
+    /// Add an evaluation, numbering it
+    pub fn record(&mut self, mut evaluation: Evaluation) {
+        self.next_seq += 1;
+        evaluation.seq = self.next_seq;
+        self.entries.push_back(evaluation);
+        if self.entries.len() > MAX_EVALUATIONS {
+            self.entries.pop_front();
+        }
+    }
+
+    /// Matching evaluations, newest first, marked current when their
+    /// generation is `current_generation`
+    pub fn query(&self, query: &EvalQuery, current_generation: u64) -> Vec<EvaluationView> {
+        self.entries.iter()
+            .rev()
+            .filter(|e| query.expression.as_ref().is_none_or(|text| e.expression.contains(text.as_str())))
+            .filter(|e| query.generation.is_none_or(|g| e.generation == g))
+            .filter(|e| !query.values_only || e.value.is_some())
+            .take(query.limit)
+            .map(|e| EvaluationView { evaluation: e.clone(), current: e.generation == current_generation })
+            .collect()
+    }
+
+    pub fn len(&self) -> usize {
+        self.entries.len()
+    }
+
+    pub fn clear(&mut self) {
+        self.entries.clear();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn evaluation(expression: &str, value: Option<&str>, generation: u64) -> Evaluation {
+        Evaluation {
+            seq: 0,
+            expression: expression.to_string(),
+            value: value.map(|v| v.to_string()),
+            error: value.is_none().then(|| "No symbol \"x\" in current context.".to_string()),
+            generation,
+            thread_id: Some("1".to_string()),
+            frame_level: None,
+            location: Some("parse (0x401136)".to_string()),
+            timestamp_ms: 0,
+        }
+    }
+
+    #[test]
+    fn test_query() {
+        let mut history = EvalHistory::default();
+        history.record(evaluation("len", Some("3"), 1));
+        history.record(evaluation("buf[len]", Some("0 '\\000'"), 1));
+        history.record(evaluation("x", None, 2));
+        history.record(evaluation("len", Some("4"), 2));
+        assert_eq!(history.len(), 4);
+
+        let all = history.query(&EvalQuery { limit: 10, ..Default::default() }, 2);
+        assert_eq!(all.iter().map(|v| v.evaluation.seq).collect::<Vec<_>>(), vec![4, 3, 2, 1]);
+        assert!(all[0].current && !all[3].current);
+
+        let len = history.query(&EvalQuery { expression: Some("len".to_string()), limit: 10, ..Default::default() }, 2);
+        assert_eq!(len.len(), 3);
+        let first_stop = history.query(&EvalQuery { generation: Some(1), limit: 10, ..Default::default() }, 2);
+        assert_eq!(first_stop.len(), 2);
+        let values = history.query(&EvalQuery { values_only: true, limit: 1, ..Default::default() }, 2);
+        assert_eq!(values[0].evaluation.value.as_deref(), Some("4"));
+
+        for _ in 0..MAX_EVALUATIONS {
+            history.record(evaluation("len", Some("5"), 3));
+        }
+        assert_eq!(history.len(), MAX_EVALUATIONS);
+        history.clear();
+        assert_eq!(history.len(), 0);
+    }
+}
@@ -0,0 +1,119 @@
+//! Expression Diffs
+//!
+//! The most common loop of an agent is to evaluate a few expressions, step,
+//! and evaluate them again to see what the step did. `gdb_expr_diff` does
+//! the three calls in one: it reads the expressions, runs one step, next or
+//! continue, reads them again and reports which values changed.
+
+use crate::gdb::client::GdbClient;
+use serde::{Deserialize, Serialize};
+
+/// Run-control action performed between the two readings
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum DiffAction {
+    Step,
+    #[default]
+    Next,
+    Continue,
+}
+
+impl DiffAction {
+    pub fn as_str(self) -> &'static str {
+        match self {
+            DiffAction::Step => "step",
+            DiffAction::Next => "next",
+            DiffAction::Continue => "continue",
+        }
+    }
+}
+
+/// Value of an expression, or why it could not be evaluated
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Reading {
+    Value(String),
+    Error(String),
+}
+
+/// One expression before and after the action
+#[derive(Debug, Clone, Serialize)]
+pub struct ExprChange {
+    pub expression: String,
+    pub before: Reading,
+    pub after: Reading,
+    pub changed: bool,
+}
+
+/// Result of `gdb_expr_diff`
+#[derive(Debug, Clone, Serialize)]
+pub struct ExprDiff {
+    pub action: &'static str,
+    /// Whether a continue had to be interrupted after its time budget
+    pub interrupted: bool,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub stop_reason: Option<String>,
+    pub changed: usize,
+    pub expressions: Vec<ExprChange>,
+}
+
+/// Evaluate each expression in the selected frame
+pub fn read_expressions(client: &mut GdbClient, expressions: &[String]) -> Vec<Reading> {
+    expressions
+        .iter()
+        .map(|expr| match client.data_evaluate_expression(expr) {
+            Ok(value) => Reading::Value(value),
+            Err(e) => Reading::Error(format!("{:#}", e)),
+        })
+        .collect()
+}
+
+/// Pair up the readings taken before and after the action
+pub fn diff(expressions: &[String], before: Vec<Reading>, after: Vec<Reading>) -> Vec<ExprChange> {
+    expressions
+        .iter()
+        .zip(before.into_iter().zip(after))
+        .map(|(expression, (before, after))| ExprChange {
+            expression: expression.clone(),
+            changed: before != after,
+            before,
+            after,
+        })
+        .collect()
+}
+
+impl ExprChange {
+    /// `expr: before -> after`, for the summary line
+    pub fn describe(&self) -> String {
+        let show = |reading: &Reading| match reading {
+            Reading::Value(value) => value.clone(),
+            Reading::Error(e) => format!("<error: {}>", e),
+        };
+        format!("{}: {} -> {}", self.expression, show(&self.before), show(&self.after))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_diff() {
+        let expressions = vec!["i".to_string(), "sum".to_string(), "p->next".to_string()];
+        let before = vec![
+            Reading::Value("1".to_string()),
+            Reading::Value("10".to_string()),
+            Reading::Error("Cannot access memory at address 0x8".to_string()),
+        ];
+        let after = vec![
+            Reading::Value("2".to_string()),
+            Reading::Value("10".to_string()),
+            Reading::Value("0x0".to_string()),
+        ];
+        let changes = diff(&expressions, before, after);
+        assert_eq!(changes.iter().map(|c| c.changed).collect::<Vec<_>>(), [true, false, true]);
+        assert_eq!(changes[0].describe(), "i: 1 -> 2");
+        assert_eq!(serde_json::to_value(&changes[2].before).unwrap()["error"], "Cannot access memory at address 0x8");
+        assert_eq!(serde_json::from_str::<DiffAction>("\"continue\"").unwrap(), DiffAction::Continue);
+    }
+}
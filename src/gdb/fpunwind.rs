@@ -0,0 +1,185 @@
+//! Frame Pointer Unwinding
+//!
+//! GDB's unwinder needs CFI or a recognizable prologue; hand-written
+//! assembly, stripped firmware and corrupted stacks often give it neither,
+//! and the backtrace ends after a frame or two. Code built with frame
+//! pointers keeps a chain of frame records (saved frame pointer and return
+//! address) on the stack, which can be followed by hand. The result is a
+//! heuristic: a function that does not set up a frame record is skipped.
+
+use crate::gdb::patch::PatchArch;
+use anyhow::Result;
+use serde::Serialize;
+
+/// Where the frame record lives relative to the frame pointer
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct FrameLayout {
+    /// Register holding the frame pointer (without `$`)
+    pub fp_register: String,
+    /// Offset of the caller's saved frame pointer from the frame pointer
+    pub saved_fp_offset: i64,
+    /// Offset of the return address from the frame pointer
+    pub return_offset: i64,
+    pub word_size: u64,
+    /// Register holding the return address of a leaf function
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub link_register: Option<String>,
+}
+
+impl FrameLayout {
+    /// The usual frame record of GCC and Clang with `-fno-omit-frame-pointer`
+    pub fn for_arch(arch: PatchArch) -> Self {
+        let layout = |fp: &str, saved_fp_offset, return_offset, word_size, link: Option<&str>| FrameLayout {
+            fp_register: fp.to_string(),
+            saved_fp_offset,
+            return_offset,
+            word_size,
+            link_register: link.map(|l| l.to_string()),
+        };
+        match arch {
+            PatchArch::X86_64 => layout("rbp", 0, 8, 8, None),
+            PatchArch::I386 => layout("ebp", 0, 4, 4, None),
+            PatchArch::AArch64 => layout("x29", 0, 8, 8, Some("x30")),
+            // The frame pointer points just above the saved ra and s0
+            PatchArch::Riscv64 => layout("s0", -16, -8, 8, Some("ra")),
+            PatchArch::Riscv32 => layout("s0", -8, -4, 4, Some("ra")),
+            // `push {r7, lr}; add r7, sp, #0` in Thumb code
+            PatchArch::Thumb => layout("r7", 0, 4, 4, Some("lr")),
+            PatchArch::Arm => layout("r11", 0, 4, 4, Some("lr")),
+        }
+    }
+}
+
+/// Where a frame's address was found
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum FrameSource {
+    Pc,
+    LinkRegister,
+    FrameRecord,
+}
+
+/// A frame found by the walk
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct HeuristicFrame {
+    pub level: usize,
+    pub pc: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub symbol: Option<String>,
+    /// Frame pointer whose record gave the address
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub frame_pointer: Option<String>,
+    pub source: FrameSource,
+}
+
+/// Result of `gdb_unwind_fallback`
+#[derive(Debug, Clone, Serialize)]
+pub struct FallbackUnwind {
+    /// Always true: frames are guessed from frame records
+    pub heuristic: bool,
+    pub arch: PatchArch,
+    pub layout: FrameLayout,
+    /// Frames GDB's own unwinder found, for comparison
+    pub gdb_frames: usize,
+    pub frames: Vec<HeuristicFrame>,
+    /// Why the walk ended
+    pub stopped_because: String,
+}
+
+/// Follow the frame record chain from `fp`. `lr` is the link register when
+/// it may hold the caller of a leaf function. `read` reads one word.
+/// Returns (pc, frame pointer, source) of each frame and why the walk ended.
+pub fn walk(
+    layout: &FrameLayout,
+    pc: u64,
+    fp: u64,
+    lr: Option<u64>,
+    max_frames: usize,
+    mut read: impl FnMut(u64) -> Result<u64>,
+) -> (Vec<(u64, Option<u64>, FrameSource)>, String) {
+    let mut frames = vec![(pc, None, FrameSource::Pc)];
+    let at = |fp: u64, offset: i64| fp.checked_add_signed(offset);
+    let mut fp = fp;
+    let mut pending_lr = lr;
+
+    let reason = loop {
+        if frames.len() >= max_frames {
+            break format!("reached max_frames ({})", max_frames);
+        }
+        if fp == 0 {
+            break "frame pointer is 0 (outermost frame)".to_string();
+        }
+        if !fp.is_multiple_of(layout.word_size) {
+            break format!("frame pointer {:#x} is not word aligned", fp);
+        }
+        let (Some(ra_slot), Some(fp_slot)) = (at(fp, layout.return_offset), at(fp, layout.saved_fp_offset)) else {
+            break format!("frame record at {:#x} is outside the address space", fp);
+        };
+        let (ra, saved_fp) = match (read(ra_slot), read(fp_slot)) {
+            (Ok(ra), Ok(saved_fp)) => (ra, saved_fp),
+            (Err(e), _) | (_, Err(e)) => break format!("failed to read the frame record at {:#x}: {}", fp, e),
+        };
+        if let Some(lr) = pending_lr.take() {
+            if lr != ra && lr != 0 {
+                frames.push((lr, None, FrameSource::LinkRegister));
+                if frames.len() >= max_frames {
+                    continue;
+                }
+            }
+        }
+        if ra == 0 {
+            break format!("return address at {:#x} is 0 (outermost frame)", ra_slot);
+        }
+        frames.push((ra, Some(fp), FrameSource::FrameRecord));
+        // The stack grows down, so callers' frames are at higher addresses
+        if saved_fp != 0 && saved_fp <= fp {
+            break format!("saved frame pointer {:#x} is not above {:#x}; the chain is broken or ends here", saved_fp, fp);
+        }
+        fp = saved_fp;
+    };
+    (frames, reason)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use anyhow::anyhow;
+    use std::collections::HashMap;
+
+    #[test]
+    fn test_walk() {
+        // main -> parse -> leaf, leaf without a frame record
+        let stack: HashMap<u64, u64> = [
+            (0x7f00, 0x7f40), (0x7f08, 0x401200), // parse's record
+            (0x7f40, 0), (0x7f48, 0x401050),      // main's record
+        ].into_iter().collect();
+        let read = |addr: u64| stack.get(&addr).copied().ok_or_else(|| anyhow!("Cannot access memory at address {:#x}", addr));
+
+        let layout = FrameLayout::for_arch(PatchArch::AArch64);
+        let (frames, reason) = walk(&layout, 0x401300, 0x7f00, Some(0x401180), 32, read);
+        let pcs: Vec<(u64, FrameSource)> = frames.iter().map(|&(pc, _, source)| (pc, source)).collect();
+        assert_eq!(pcs, vec![
+            (0x401300, FrameSource::Pc),
+            (0x401180, FrameSource::LinkRegister),
+            (0x401200, FrameSource::FrameRecord),
+            (0x401050, FrameSource::FrameRecord),
+        ]);
+        assert!(reason.contains("frame pointer is 0"));
+
+        // The link register is the same as the first record's return address
+        let (frames, _) = walk(&layout, 0x401300, 0x7f00, Some(0x401200), 32, read);
+        assert_eq!(frames.len(), 3);
+
+        let (frames, reason) = walk(&layout, 0x401300, 0x7f00, None, 2, read);
+        assert_eq!(frames.len(), 2);
+        assert!(reason.contains("max_frames"));
+
+        let (_, reason) = walk(&layout, 0x401300, 0x7f04, None, 32, read);
+        assert!(reason.contains("not word aligned"));
+        let (_, reason) = walk(&layout, 0x401300, 0x8000, None, 32, read);
+        assert!(reason.contains("Cannot access memory"));
+
+        let riscv = FrameLayout::for_arch(PatchArch::Riscv32);
+        assert_eq!((riscv.saved_fp_offset, riscv.return_offset), (-8, -4));
+    }
+}
@@ -0,0 +1,101 @@
+//! Bundled GDB Python Helpers
+//!
+//! A few Python scripts (a struct-to-JSON dumper, a glibc heap walker and a
+//! FreeRTOS task lister) are compiled into the binary. When a session is
+//! started with `python_helpers`, they are written to a temporary directory
+//! that lives as long as the session and `source`d into GDB, so their
+//! commands can be used with `gdb_command` without installing anything on
+//! the host.
+
+use anyhow::{Context, Result};
+use std::path::{Path, PathBuf};
+
+/// A helper script and the GDB commands it defines
+#[derive(Debug)]
+pub struct PythonHelper {
+    pub file: &'static str,
+    pub commands: &'static [&'static str],
+    pub source: &'static str,
+}
+
+pub const PYTHON_HELPERS: &[PythonHelper] = &[
+    PythonHelper {
+        file: "mcp_dump.py",
+        commands: &["mcp-dump"],
+        source: include_str!("python/mcp_dump.py"),
+    },
+    PythonHelper {
+        file: "mcp_heap.py",
+        commands: &["mcp-heap"],
+        source: include_str!("python/mcp_heap.py"),
+    },
+    PythonHelper {
+        file: "mcp_freertos.py",
+        commands: &["mcp-freertos-tasks"],
+        source: include_str!("python/mcp_freertos.py"),
+    },
+];
+
+/// Parse the JSON a helper command prints on its last output line
+pub fn parse_helper_output(output: &str) -> Result<serde_json::Value> {
+    let line = output.lines().rev().find(|line| !line.trim().is_empty()).unwrap_or("");
+    serde_json::from_str(line.trim()).with_context(|| format!("Helper printed no JSON: {}", output.trim()))
+}
+
+/// Temporary directory holding the extracted helpers, removed on drop
+#[derive(Debug)]
+pub struct HelperDir {
+    path: PathBuf,
+}
+
+impl HelperDir {
+    /// Write every helper into a new directory under `parent`
+    pub fn extract(parent: &Path) -> Result<Self> {
+        let path = parent.join(format!("mcp-gdb-helpers-{}", uuid::Uuid::new_v4()));
+        std::fs::create_dir_all(&path)
+            .with_context(|| format!("Failed to create {}", path.display()))?;
+        let dir = Self { path };
+        for helper in PYTHON_HELPERS {
+            let file = dir.path.join(helper.file);
+            std::fs::write(&file, helper.source)
+                .with_context(|| format!("Failed to write {}", file.display()))?;
+        }
+        Ok(dir)
+    }
+
+    pub fn path(&self) -> &Path {
+        &self.path
+    }
+
+    /// Helpers with the path of their extracted script
+    pub fn scripts(&self) -> impl Iterator<Item = (&'static PythonHelper, PathBuf)> + '_ {
+        PYTHON_HELPERS.iter().map(|helper| (helper, self.path.join(helper.file)))
+    }
+}
+
+impl Drop for HelperDir {
+    fn drop(&mut self) {
+        let _ = std::fs::remove_dir_all(&self.path);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_helper_dir() {
+        let dir = HelperDir::extract(&std::env::temp_dir()).unwrap();
+        let path = dir.path().to_path_buf();
+        for (helper, script) in dir.scripts() {
+            let content = std::fs::read_to_string(&script).unwrap();
+            assert!(helper.commands.iter().all(|command| content.contains(&format!("\"{}\"", command))));
+        }
+        drop(dir);
+        assert!(!path.exists());
+
+        let value = parse_helper_output("warning: no debug info\n{\"tasks\": []}\n\n").unwrap();
+        assert_eq!(value["tasks"], serde_json::json!([]));
+        assert!(parse_helper_output("No symbol \"x\" in current context.\n").is_err());
+    }
+}
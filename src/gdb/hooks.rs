@@ -0,0 +1,73 @@
+//! Lifecycle Hooks
+//!
+//! Boards usually need the same commands at the same points of every
+//! session (`monitor arm semihosting enable` after connecting, `set mem
+//! inaccessible-by-default off` after starting), and they are easy to
+//! forget. Hooks from the configuration are run by the client itself at
+//! those points, whichever tool caused them.
+
+use serde::Deserialize;
+
+/// GDB commands run at points of a session's life
+#[derive(Debug, Clone, Default, PartialEq, Eq, Deserialize)]
+#[serde(default)]
+pub struct Hooks {
+    /// After GDB has started
+    pub on_start: Vec<String>,
+    /// After connecting to a remote target
+    pub on_connect: Vec<String>,
+    /// After loading an executable
+    pub on_load: Vec<String>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum HookPoint {
+    Start,
+    Connect,
+    Load,
+}
+
+impl HookPoint {
+    pub fn name(self) -> &'static str {
+        match self {
+            HookPoint::Start => "on_start",
+            HookPoint::Connect => "on_connect",
+            HookPoint::Load => "on_load",
+        }
+    }
+}
+
+impl Hooks {
+    pub fn commands(&self, point: HookPoint) -> &[String] {
+        match point {
+            HookPoint::Start => &self.on_start,
+            HookPoint::Connect => &self.on_connect,
+            HookPoint::Load => &self.on_load,
+        }
+    }
+
+    /// These hooks followed by `other`'s
+    pub fn followed_by(&self, other: &Hooks) -> Hooks {
+        let join = |a: &[String], b: &[String]| a.iter().chain(b).cloned().collect();
+        Hooks {
+            on_start: join(&self.on_start, &other.on_start),
+            on_connect: join(&self.on_connect, &other.on_connect),
+            on_load: join(&self.on_load, &other.on_load),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_hooks() {
+        let global: Hooks = serde_json::from_str(r#"{"on_connect": ["monitor reset halt"]}"#).unwrap();
+        let board = Hooks { on_connect: vec!["monitor arm semihosting enable".to_string()], ..Default::default() };
+        let hooks = global.followed_by(&board);
+        assert_eq!(hooks.commands(HookPoint::Connect), ["monitor reset halt", "monitor arm semihosting enable"]);
+        assert!(hooks.commands(HookPoint::Load).is_empty());
+        assert_eq!(HookPoint::Start.name(), "on_start");
+    }
+}
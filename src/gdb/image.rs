@@ -0,0 +1,150 @@
+//! Framebuffer Images
+//!
+//! Turns raw pixel memory into a PNG so a framebuffer or bitmap can be
+//! returned as MCP image content. Multi-byte channel formats are named in
+//! memory byte order (`bgra8888` is B, G, R, A); 16-bit formats are
+//! little-endian words. Alpha is dropped since framebuffers often leave it
+//! undefined.
+
+use anyhow::{anyhow, Result};
+use base64::Engine;
+
+/// Largest image that will be decoded, in pixels
+pub const MAX_PIXELS: u64 = 4096 * 4096;
+
+/// Layout of one pixel in memory
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum PixelFormat {
+    Rgb888,
+    Bgr888,
+    Rgba8888,
+    Bgra8888,
+    Argb8888,
+    Abgr8888,
+    Rgb565,
+    Bgr565,
+    Gray8,
+    /// One bit per pixel, most significant bit first, set bits white
+    Mono1,
+}
+
+/// Names accepted by [`PixelFormat::parse`]
+pub const PIXEL_FORMATS: &[&str] = &[
+    "rgb888", "bgr888", "rgba8888", "bgra8888", "argb8888", "abgr8888", "rgb565", "bgr565", "gray8", "mono1",
+];
+
+impl PixelFormat {
+    pub fn parse(name: &str) -> Option<Self> {
+        Some(match name.to_ascii_lowercase().as_str() {
+            "rgb888" => PixelFormat::Rgb888,
+            "bgr888" => PixelFormat::Bgr888,
+            "rgba8888" => PixelFormat::Rgba8888,
+            "bgra8888" => PixelFormat::Bgra8888,
+            "argb8888" => PixelFormat::Argb8888,
+            "abgr8888" => PixelFormat::Abgr8888,
+            "rgb565" => PixelFormat::Rgb565,
+            "bgr565" => PixelFormat::Bgr565,
+            "gray8" => PixelFormat::Gray8,
+            "mono1" => PixelFormat::Mono1,
+            _ => return None,
+        })
+    }
+
+    pub fn bits_per_pixel(&self) -> u64 {
+        match self {
+            PixelFormat::Rgb888 | PixelFormat::Bgr888 => 24,
+            PixelFormat::Rgba8888 | PixelFormat::Bgra8888 | PixelFormat::Argb8888 | PixelFormat::Abgr8888 => 32,
+            PixelFormat::Rgb565 | PixelFormat::Bgr565 => 16,
+            PixelFormat::Gray8 => 8,
+            PixelFormat::Mono1 => 1,
+        }
+    }
+
+    /// Bytes in a row of `width` pixels without padding
+    pub fn row_bytes(&self, width: u64) -> u64 {
+        (width * self.bits_per_pixel()).div_ceil(8)
+    }
+
+    /// RGB value of pixel `x` in a row
+    fn pixel(&self, row: &[u8], x: usize) -> [u8; 3] {
+        let bytes = |n: usize| &row[x * n..x * n + n];
+        let rgb565 = |word: u16| {
+            let expand = |v: u16, bits: u32| ((v << (8 - bits)) | (v >> (2 * bits - 8))) as u8;
+            [expand(word >> 11, 5), expand((word >> 5) & 0x3f, 6), expand(word & 0x1f, 5)]
+        };
+        match self {
+            PixelFormat::Rgb888 => { let p = bytes(3); [p[0], p[1], p[2]] }
+            PixelFormat::Bgr888 => { let p = bytes(3); [p[2], p[1], p[0]] }
+            PixelFormat::Rgba8888 => { let p = bytes(4); [p[0], p[1], p[2]] }
+            PixelFormat::Bgra8888 => { let p = bytes(4); [p[2], p[1], p[0]] }
+            PixelFormat::Argb8888 => { let p = bytes(4); [p[1], p[2], p[3]] }
+            PixelFormat::Abgr8888 => { let p = bytes(4); [p[3], p[2], p[1]] }
+            PixelFormat::Rgb565 => { let p = bytes(2); rgb565(u16::from_le_bytes([p[0], p[1]])) }
+            PixelFormat::Bgr565 => {
+                let p = bytes(2);
+                let [b, g, r] = rgb565(u16::from_le_bytes([p[0], p[1]]));
+                [r, g, b]
+            }
+            PixelFormat::Gray8 => { let v = row[x]; [v, v, v] }
+            PixelFormat::Mono1 => {
+                let v = if row[x / 8] & (0x80 >> (x % 8)) != 0 { 0xff } else { 0 };
+                [v, v, v]
+            }
+        }
+    }
+}
+
+/// Decode `height` rows of `stride` bytes into 8-bit RGB
+pub fn decode_pixels(bytes: &[u8], format: PixelFormat, width: u64, height: u64, stride: u64) -> Result<Vec<u8>> {
+    if width == 0 || height == 0 {
+        return Err(anyhow!("width and height must be positive"));
+    }
+    if stride < format.row_bytes(width) {
+        return Err(anyhow!("stride {} is smaller than a row of {} pixels ({} bytes)", stride, width, format.row_bytes(width)));
+    }
+    let needed = stride * (height - 1) + format.row_bytes(width);
+    if (bytes.len() as u64) < needed {
+        return Err(anyhow!("Read {} bytes but the image needs {}", bytes.len(), needed));
+    }
+
+    let mut rgb = Vec::with_capacity((width * height * 3) as usize);
+    for y in 0..height {
+        let row = &bytes[(y * stride) as usize..];
+        for x in 0..width as usize {
+            rgb.extend_from_slice(&format.pixel(row, x));
+        }
+    }
+    Ok(rgb)
+}
+
+/// Encode 8-bit RGB pixels as a base64 PNG
+pub fn encode_png(width: u64, height: u64, rgb: &[u8]) -> Result<String> {
+    let mut png = Vec::new();
+    {
+        let mut encoder = png::Encoder::new(&mut png, width as u32, height as u32);
+        encoder.set_color(png::ColorType::Rgb);
+        encoder.set_depth(png::BitDepth::Eight);
+        let mut writer = encoder.write_header()?;
+        writer.write_image_data(rgb)?;
+    }
+    Ok(base64::engine::general_purpose::STANDARD.encode(png))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_decode_pixels() {
+        // 2x2 RGB565 with a padded stride: red, green / blue, white
+        let bytes = [0x00, 0xf8, 0xe0, 0x07, 0xaa, 0xaa, 0x1f, 0x00, 0xff, 0xff, 0xaa, 0xaa];
+        let rgb = decode_pixels(&bytes, PixelFormat::Rgb565, 2, 2, 6).unwrap();
+        assert_eq!(rgb, [255, 0, 0, 0, 255, 0, 0, 0, 255, 255, 255, 255]);
+
+        let rgb = decode_pixels(&[0b1010_0000], PixelFormat::Mono1, 3, 1, 1).unwrap();
+        assert_eq!(rgb, [255, 255, 255, 0, 0, 0, 255, 255, 255]);
+
+        assert!(decode_pixels(&bytes, PixelFormat::Bgra8888, 2, 2, 8).is_err());
+        assert!(encode_png(2, 1, &[255, 0, 0, 0, 0, 255]).unwrap().starts_with("iVBORw0KGgo"));
+    }
+}
@@ -0,0 +1,278 @@
+//! Program Output Capture
+//!
+//! A native program started with `-exec-run` inherits GDB's standard
+//! streams, so whatever it prints lands in the middle of the MI output and
+//! can break parsing. Programs are instead given a pseudo-terminal of their
+//! own (`-inferior-tty-set`), and standard error is redirected to a second
+//! one through the startup shell. Both are read into separate buffers that
+//! `gdb_program_output` returns together with the exit status.
+
+use crate::gdb::parser::MiParser;
+use crate::gdb::types::{MiResult, StopReason};
+use anyhow::Result;
+use serde::{Deserialize, Serialize};
+use std::collections::VecDeque;
+use std::sync::{Arc, Mutex};
+
+/// Bytes kept per stream; older output is dropped
+const MAX_BYTES: usize = 1024 * 1024;
+
+/// How the program exited
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ProgramExit {
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub code: Option<i32>,
+    /// Signal that terminated the program
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub signal: Option<String>,
+}
+
+impl ProgramExit {
+    /// Exit status of an `exited*` stop. GDB prints the exit code in octal.
+    pub fn from_stop(reason: &StopReason, results: &[MiResult]) -> Option<Self> {
+        let find = |name: &str| results.iter().find(|r| r.variable == name).and_then(|r| MiParser::extract_string(&r.value));
+        match reason {
+            StopReason::ExitedNormally => Some(Self { code: Some(0), signal: None }),
+            StopReason::Exited => Some(Self {
+                code: find("exit-code").and_then(|c| i32::from_str_radix(&c, 8).ok()),
+                signal: None,
+            }),
+            StopReason::ExitedSignalled => Some(Self { code: None, signal: find("signal-name") }),
+            _ => None,
+        }
+    }
+
+    pub fn describe(&self) -> String {
+        match (&self.signal, self.code) {
+            (Some(signal), _) => format!("Program terminated with signal {}", signal),
+            (None, Some(code)) => format!("Program exited with code {}", code),
+            (None, None) => "Program exited".to_string(),
+        }
+    }
+}
+
+/// Output of one stream, keeping the last `MAX_BYTES`
+#[derive(Debug, Default)]
+struct StreamBuffer {
+    data: VecDeque<u8>,
+    dropped: usize,
+}
+
+impl StreamBuffer {
+    fn push(&mut self, bytes: &[u8]) {
+        self.data.extend(bytes);
+        let excess = self.data.len().saturating_sub(MAX_BYTES);
+        self.data.drain(..excess);
+        self.dropped += excess;
+    }
+
+    fn text(&self) -> String {
+        let (a, b) = self.data.as_slices();
+        String::from_utf8_lossy(&[a, b].concat()).to_string()
+    }
+
+    fn clear(&mut self) {
+        self.data.clear();
+        self.dropped = 0;
+    }
+}
+
+#[derive(Debug, Default)]
+struct Buffers {
+    stdout: StreamBuffer,
+    stderr: StreamBuffer,
+}
+
+/// Result of `gdb_program_output`
+#[derive(Debug, Clone, Serialize)]
+pub struct ProgramOutput {
+    pub running: bool,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub exit: Option<ProgramExit>,
+    pub stdout: String,
+    pub stderr: String,
+    /// Whether stderr has its own buffer; if not it is part of stdout
+    pub stderr_separated: bool,
+    /// Bytes dropped from the start of each buffer
+    #[serde(skip_serializing_if = "is_zero")]
+    pub stdout_dropped: usize,
+    #[serde(skip_serializing_if = "is_zero")]
+    pub stderr_dropped: usize,
+}
+
+fn is_zero(n: &usize) -> bool {
+    *n == 0
+}
+
+/// Terminals for the program's output and the buffers they are read into
+pub struct InferiorTty {
+    stdout: pty::Pty,
+    stderr: pty::Pty,
+    buffers: Arc<Mutex<Buffers>>,
+    /// Whether the last run redirected stderr to its own terminal
+    stderr_separated: bool,
+}
+
+impl InferiorTty {
+    pub fn open() -> Result<Self> {
+        let buffers = Arc::new(Mutex::new(Buffers::default()));
+        let out = Arc::clone(&buffers);
+        let stdout = pty::Pty::open(move |bytes| out.lock().unwrap().stdout.push(bytes))?;
+        let err = Arc::clone(&buffers);
+        let stderr = pty::Pty::open(move |bytes| err.lock().unwrap().stderr.push(bytes))?;
+        Ok(Self { stdout, stderr, buffers, stderr_separated: false })
+    }
+
+    /// Terminal to give the program with `-inferior-tty-set`
+    pub fn tty(&self) -> &str {
+        &self.stdout.path
+    }
+
+    /// Terminal standard error is redirected to
+    pub fn stderr_tty(&self) -> &str {
+        &self.stderr.path
+    }
+
+    /// Forget the previous run's output
+    pub fn start_run(&mut self, stderr_separated: bool) {
+        let mut buffers = self.buffers.lock().unwrap();
+        buffers.stdout.clear();
+        buffers.stderr.clear();
+        self.stderr_separated = stderr_separated;
+    }
+
+    pub fn output(&self, running: bool, exit: Option<ProgramExit>) -> ProgramOutput {
+        let buffers = self.buffers.lock().unwrap();
+        ProgramOutput {
+            running,
+            exit,
+            stdout: buffers.stdout.text(),
+            stderr: buffers.stderr.text(),
+            stderr_separated: self.stderr_separated,
+            stdout_dropped: buffers.stdout.dropped,
+            stderr_dropped: buffers.stderr.dropped,
+        }
+    }
+
+    pub fn clear(&self) {
+        let mut buffers = self.buffers.lock().unwrap();
+        buffers.stdout.clear();
+        buffers.stderr.clear();
+    }
+}
+
+/// Program arguments from `show args`
+pub fn parse_show_args(output: &str) -> Option<String> {
+    let start = output.find('"')?;
+    let end = output.rfind('"')?;
+    (end > start).then(|| output[start + 1..end].to_string())
+}
+
+/// Arguments that also send standard error to `tty`. The startup shell
+/// applies the redirection, so it needs `startup-with-shell` on.
+pub fn with_stderr_redirect(args: &str, tty: &str) -> String {
+    format!("{} 2>{}", args, tty).trim_start().to_string()
+}
+
+#[cfg(unix)]
+mod pty {
+    use anyhow::{anyhow, Result};
+    use std::ffi::CStr;
+    use std::fs::{File, OpenOptions};
+    use std::io::Read;
+    use std::os::unix::fs::OpenOptionsExt;
+    use std::os::unix::io::{AsRawFd, FromRawFd};
+
+    /// A pseudo-terminal whose output is read on a background thread
+    pub struct Pty {
+        pub path: String,
+        /// Kept open so that reading the master does not fail between runs
+        _slave: File,
+    }
+
+    impl Pty {
+        pub fn open(mut sink: impl FnMut(&[u8]) + Send + 'static) -> Result<Self> {
+            // SAFETY: the fd returned by posix_openpt is owned by `master`,
+            // and ptsname's static buffer is copied before any other call
+            let (mut master, path) = unsafe {
+                let fd = libc::posix_openpt(libc::O_RDWR | libc::O_NOCTTY);
+                if fd < 0 {
+                    return Err(anyhow!("Failed to open a pseudo-terminal: {}", std::io::Error::last_os_error()));
+                }
+                let master = File::from_raw_fd(fd);
+                if libc::grantpt(fd) != 0 || libc::unlockpt(fd) != 0 {
+                    return Err(anyhow!("Failed to unlock the pseudo-terminal: {}", std::io::Error::last_os_error()));
+                }
+                let name = libc::ptsname(fd);
+                if name.is_null() {
+                    return Err(anyhow!("Failed to get the pseudo-terminal name"));
+                }
+                (master, CStr::from_ptr(name).to_string_lossy().to_string())
+            };
+
+            let slave = OpenOptions::new().read(true).write(true).custom_flags(libc::O_NOCTTY).open(&path)?;
+            // Raw mode, so "\n" is not turned into "\r\n"
+            // SAFETY: termios is plain data filled in by tcgetattr
+            unsafe {
+                let mut termios: libc::termios = std::mem::zeroed();
+                if libc::tcgetattr(slave.as_raw_fd(), &mut termios) == 0 {
+                    libc::cfmakeraw(&mut termios);
+                    libc::tcsetattr(slave.as_raw_fd(), libc::TCSANOW, &termios);
+                }
+            }
+
+            std::thread::spawn(move || {
+                let mut buf = [0u8; 4096];
+                // Fails with EIO once every slave descriptor is closed
+                while let Ok(n) = master.read(&mut buf) {
+                    if n == 0 {
+                        break;
+                    }
+                    sink(&buf[..n]);
+                }
+            });
+            Ok(Self { path, _slave: slave })
+        }
+    }
+}
+
+#[cfg(not(unix))]
+mod pty {
+    use anyhow::{anyhow, Result};
+
+    pub struct Pty {
+        pub path: String,
+    }
+
+    impl Pty {
+        pub fn open(_sink: impl FnMut(&[u8]) + Send + 'static) -> Result<Self> {
+            Err(anyhow!("Capturing program output is only supported on Unix"))
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::gdb::types::MiOutputRecord;
+
+    #[test]
+    fn test_program_exit() {
+        let record = MiParser::new().parse_line(r#"*stopped,reason="exited",exit-code="012""#).unwrap().unwrap();
+        let MiOutputRecord::Async { results, .. } = record else { panic!("not a stop") };
+        let exit = ProgramExit::from_stop(&StopReason::Exited, &results).unwrap();
+        assert_eq!(exit.code, Some(10));
+        assert_eq!(exit.describe(), "Program exited with code 10");
+        assert_eq!(ProgramExit::from_stop(&StopReason::BreakpointHit, &results), None);
+
+        let args = parse_show_args("Argument list to give program being debugged when it is started is \"-v \"x\"\".\n");
+        assert_eq!(args.as_deref(), Some("-v \"x\""));
+        assert_eq!(with_stderr_redirect("", "/dev/pts/3"), "2>/dev/pts/3");
+
+        let mut buffer = StreamBuffer::default();
+        buffer.push(&vec![b'a'; MAX_BYTES]);
+        buffer.push(b"end");
+        assert_eq!(buffer.dropped, 3);
+        assert!(buffer.text().ends_with("aend"));
+    }
+}
@@ -0,0 +1,187 @@
+//! Log Locations
+//!
+//! Going from "here is the error text" to breakpoints at the places it
+//! mentions means copying `file:line` pairs out of compiler warnings,
+//! sanitizer reports or backtraces one by one. The source locations of a
+//! pasted log are extracted here so `gdb_break_from_log` can set all of
+//! them in one call.
+
+use regex::Regex;
+use serde::{Deserialize, Serialize};
+use std::sync::OnceLock;
+
+/// Longest log line kept as a location's context
+const MAX_CONTEXT: usize = 200;
+
+/// Path fragments of runtime and system code that is rarely worth a
+/// breakpoint (sanitizer runtimes, libc, the Rust standard library)
+const SYSTEM_PATHS: &[&str] = &["/usr/include/", "/usr/lib/", "libsanitizer", "compiler-rt", "/rustc/", "glibc", "/.cargo/registry/"];
+
+/// What kind of log line a location came from
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum LogLineKind {
+    Error,
+    Warning,
+    Note,
+    /// `runtime error:` of UBSan
+    RuntimeError,
+    /// A stack frame of a sanitizer report, backtrace or Valgrind error
+    Frame,
+    /// Any other `file:line`
+    Mention,
+}
+
+impl LogLineKind {
+    pub fn as_str(self) -> &'static str {
+        match self {
+            LogLineKind::Error => "error",
+            LogLineKind::Warning => "warning",
+            LogLineKind::Note => "note",
+            LogLineKind::RuntimeError => "runtime error",
+            LogLineKind::Frame => "frame",
+            LogLineKind::Mention => "mention",
+        }
+    }
+}
+
+/// A source location found in a log
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct LogLocation {
+    pub file: String,
+    pub line: u64,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub function: Option<String>,
+    pub kind: LogLineKind,
+    /// The log line it was found on, trimmed
+    pub context: String,
+    /// Times the location appears in the log
+    pub occurrences: u64,
+}
+
+impl LogLocation {
+    /// `file:line`, as a breakpoint location
+    pub fn location(&self) -> String {
+        format!("{}:{}", self.file, self.line)
+    }
+
+    /// `basename:line`, for when the path in the log is not the one in the
+    /// debug info
+    pub fn short_location(&self) -> String {
+        let name = self.file.rsplit(['/', '\\']).next().unwrap_or(&self.file);
+        format!("{}:{}", name, self.line)
+    }
+
+    pub fn is_system(&self) -> bool {
+        SYSTEM_PATHS.iter().any(|p| self.file.contains(p))
+    }
+}
+
+struct Patterns {
+    /// `#0 0x4011b6 in main /src/test.c:5:3` (sanitizers)
+    sanitizer_frame: Regex,
+    /// `#1  0x... in foo (a=1) at src/x.c:12` (GDB backtraces)
+    gdb_frame: Regex,
+    /// `at 0x4005E4: main (test.c:5)` (Valgrind)
+    valgrind_frame: Regex,
+    /// `file:line[:col]: error:` and any other `file:line`
+    mention: Regex,
+}
+
+fn patterns() -> &'static Patterns {
+    static PATTERNS: OnceLock<Patterns> = OnceLock::new();
+    // Source file extensions only, so `example.com:80` is not a location
+    const PATH: &str = r"[\w./\\+~-]*\w\.(?i:c|cc|cp|cpp|cxx|c\+\+|h|hh|hpp|hxx|inl|ipp|tcc|rs|go|s|asm|m|mm|zig|f|f90|f95|d|adb|ads|cu|cuh|swift|ino)\b";
+    PATTERNS.get_or_init(|| Patterns {
+        sanitizer_frame: Regex::new(&format!(r"#\d+\s+0x[0-9a-fA-F]+\s+in\s+(\S+)\s+({}):(\d+)", PATH)).unwrap(),
+        gdb_frame: Regex::new(&format!(r"#\d+\s+(?:0x[0-9a-fA-F]+\s+in\s+)?(\S+)\s*\(.*\)\s+at\s+({}):(\d+)", PATH)).unwrap(),
+        valgrind_frame: Regex::new(&format!(r"(?:at|by)\s+0x[0-9a-fA-F]+:\s+(\S+)\s+\(({}):(\d+)\)", PATH)).unwrap(),
+        mention: Regex::new(&format!(r"({}):(\d+)(?::\d+)?(?::\s*(fatal error|error|warning|note|runtime error))?", PATH)).unwrap(),
+    })
+}
+
+/// Source locations of a log in order of first appearance, each once
+pub fn parse_log(text: &str) -> Vec<LogLocation> {
+    let patterns = patterns();
+    let mut locations: Vec<LogLocation> = Vec::new();
+    for raw in text.lines() {
+        let line = raw.trim();
+        let mut found: Vec<(String, u64, Option<String>, LogLineKind)> = Vec::new();
+        let frame = [&patterns.sanitizer_frame, &patterns.gdb_frame, &patterns.valgrind_frame]
+            .iter()
+            .find_map(|pattern| pattern.captures(line));
+        if let Some(caps) = frame {
+            let function = caps[1].to_string();
+            found.push((caps[2].to_string(), caps[3].parse().unwrap_or(0), Some(function), LogLineKind::Frame));
+        } else {
+            for caps in patterns.mention.captures_iter(line) {
+                let kind = match caps.get(3).map(|m| m.as_str()) {
+                    Some("error") | Some("fatal error") => LogLineKind::Error,
+                    Some("warning") => LogLineKind::Warning,
+                    Some("note") => LogLineKind::Note,
+                    Some("runtime error") => LogLineKind::RuntimeError,
+                    _ => LogLineKind::Mention,
+                };
+                found.push((caps[1].to_string(), caps[2].parse().unwrap_or(0), None, kind));
+            }
+        }
+
+        for (file, number, function, kind) in found {
+            if number == 0 {
+                continue;
+            }
+            if let Some(existing) = locations.iter_mut().find(|l| l.file == file && l.line == number) {
+                existing.occurrences += 1;
+                continue;
+            }
+            let context: String = line.chars().take(MAX_CONTEXT).collect();
+            locations.push(LogLocation { file, line: number, function, kind, context, occurrences: 1 });
+        }
+    }
+    locations
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_log() {
+        let log = "\
+src/parser.c:120:17: warning: comparison of integer expressions of different signedness [-Wsign-compare]
+src/parser.c:88:5: note: declared here
+=================================================================
+==4242==ERROR: AddressSanitizer: heap-buffer-overflow on address 0x602000000015
+READ of size 1 at 0x602000000015 thread T0
+    #0 0x4011b6 in parse_token /home/me/proj/src/parser.c:120:9
+    #1 0x7f1e2 in __interceptor_malloc ../../../../src/libsanitizer/asan/asan_malloc_linux.cpp:69
+    #2 0x401290 in main /home/me/proj/src/main.c:14:3
+src/util.c:33:10: runtime error: signed integer overflow: 2147483647 + 1 cannot be represented in type 'int'
+#3  0x0000555555555189 in step (n=3) at lib/step.c:7
+==99==    by 0x4005E4: helper (helper.c:21)
+thread 'main' panicked at src/main.rs:5:9:
+";
+        let locations = parse_log(log);
+        let summary: Vec<(String, LogLineKind)> = locations.iter().map(|l| (l.location(), l.kind)).collect();
+        assert_eq!(summary, vec![
+            ("src/parser.c:120".to_string(), LogLineKind::Warning),
+            ("src/parser.c:88".to_string(), LogLineKind::Note),
+            ("/home/me/proj/src/parser.c:120".to_string(), LogLineKind::Frame),
+            ("../../../../src/libsanitizer/asan/asan_malloc_linux.cpp:69".to_string(), LogLineKind::Frame),
+            ("/home/me/proj/src/main.c:14".to_string(), LogLineKind::Frame),
+            ("src/util.c:33".to_string(), LogLineKind::RuntimeError),
+            ("lib/step.c:7".to_string(), LogLineKind::Frame),
+            ("helper.c:21".to_string(), LogLineKind::Frame),
+            ("src/main.rs:5".to_string(), LogLineKind::Mention),
+        ]);
+        assert_eq!(locations[2].function.as_deref(), Some("parse_token"));
+        assert_eq!(locations[2].short_location(), "parser.c:120");
+        assert!(locations[3].is_system());
+        assert_eq!(locations[6].function.as_deref(), Some("step"));
+
+        let repeated = parse_log("a.c:3: error: x\na.c:3: error: y");
+        assert_eq!(repeated.len(), 1);
+        assert_eq!(repeated[0].occurrences, 2);
+        assert!(parse_log("see http://example.com:8080/x").is_empty());
+    }
+}
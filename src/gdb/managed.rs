@@ -0,0 +1,148 @@
+//! Managed Helper Processes
+//!
+//! Processes started on behalf of a debugging session, such as gdbserver
+//! running inside a container. They are owned by the `GdbClient` and killed
+//! when the target disconnects or the session stops, so no helper outlives
+//! the session that started it. A [`KillSwitch`] can kill GDB and every
+//! helper without access to the client, even while a command is blocked.
+
+use crate::util::now_ms;
+use anyhow::{anyhow, Context, Result};
+use serde::Serialize;
+use std::io::Read;
+use std::process::{Child, Command, Stdio};
+use std::sync::{Arc, Mutex};
+use std::thread;
+use std::time::Duration;
+use tracing::{info, warn};
+
+/// Summary of a running managed process
+#[derive(Debug, Clone, Serialize)]
+pub struct ManagedProcessInfo {
+    pub name: String,
+    pub pid: u32,
+    pub command: String,
+    pub started_ms: u64,
+}
+
+struct ManagedProcess {
+    info: ManagedProcessInfo,
+    child: Child,
+}
+
+/// Helper processes owned by a session. Clones share the same processes.
+#[derive(Clone, Default)]
+pub struct ManagedProcesses {
+    processes: Arc<Mutex<Vec<ManagedProcess>>>,
+}
+
+impl ManagedProcesses {
+    /// Spawn a helper process and give it `startup` to fail. A process that
+    /// exits within that window is reported as an error with its stderr.
+    pub fn spawn(&self, name: &str, mut command: Command, startup: Duration) -> Result<u32> {
+        let description = describe(&command);
+        let mut child = command
+            .stdin(Stdio::null())
+            .stdout(Stdio::null())
+            .stderr(Stdio::piped())
+            .spawn()
+            .with_context(|| format!("Failed to start {}", description))?;
+
+        thread::sleep(startup);
+        if let Some(status) = child.try_wait()? {
+            let mut stderr = String::new();
+            if let Some(mut pipe) = child.stderr.take() {
+                let _ = pipe.read_to_string(&mut stderr);
+            }
+            return Err(anyhow!("{} exited with {}: {}", description, status, stderr.trim()));
+        }
+
+        let pid = child.id();
+        info!("Started {} (pid {}): {}", name, pid, description);
+        self.processes.lock().unwrap().push(ManagedProcess {
+            info: ManagedProcessInfo {
+                name: name.to_string(),
+                pid,
+                command: description,
+                started_ms: now_ms(),
+            },
+            child,
+        });
+        Ok(pid)
+    }
+
+    /// Kill every managed process
+    pub fn kill_all(&self) -> Vec<ManagedProcessInfo> {
+        let killed = std::mem::take(&mut *self.processes.lock().unwrap());
+        killed.into_iter().map(kill).collect()
+    }
+}
+
+/// What [`KillSwitch::fire`] terminated
+#[derive(Debug, Clone, Serialize)]
+pub struct EmergencyStopReport {
+    /// PID of the killed GDB process
+    pub gdb_pid: Option<u32>,
+    pub processes: Vec<ManagedProcessInfo>,
+    pub errors: Vec<String>,
+}
+
+/// Force-terminates a session's GDB process and helpers from outside the
+/// client
+#[derive(Clone)]
+pub struct KillSwitch {
+    gdb_pid: u32,
+    managed: ManagedProcesses,
+}
+
+impl KillSwitch {
+    pub fn new(gdb_pid: u32, managed: ManagedProcesses) -> Self {
+        Self { gdb_pid, managed }
+    }
+
+    /// Kill GDB with SIGKILL and every managed process
+    pub fn fire(&self) -> EmergencyStopReport {
+        let mut report = EmergencyStopReport {
+            gdb_pid: None,
+            processes: self.managed.kill_all(),
+            errors: Vec::new(),
+        };
+        match sigkill(self.gdb_pid) {
+            Ok(()) => report.gdb_pid = Some(self.gdb_pid),
+            Err(e) => report.errors.push(e.to_string()),
+        }
+        report
+    }
+}
+
+#[cfg(unix)]
+fn sigkill(pid: u32) -> Result<()> {
+    // SAFETY: kill(2) has no memory safety requirements
+    if unsafe { libc::kill(pid as libc::pid_t, libc::SIGKILL) } != 0 {
+        return Err(anyhow!("Failed to kill GDB (pid {}): {}", pid, std::io::Error::last_os_error()));
+    }
+    Ok(())
+}
+
+#[cfg(not(unix))]
+fn sigkill(_pid: u32) -> Result<()> {
+    Err(anyhow!("Killing GDB from outside the session is only supported on Unix"))
+}
+
+fn kill(mut process: ManagedProcess) -> ManagedProcessInfo {
+    if let Err(e) = process.child.kill() {
+        warn!("Failed to kill {} (pid {}): {}", process.info.name, process.info.pid, e);
+    }
+    let _ = process.child.wait();
+    info!("Stopped {} (pid {})", process.info.name, process.info.pid);
+    process.info
+}
+
+/// Render a command line for messages
+fn describe(command: &Command) -> String {
+    std::iter::once(command.get_program())
+        .chain(command.get_args())
+        .map(|a| a.to_string_lossy().into_owned())
+        .collect::<Vec<_>>()
+        .join(" ")
+}
@@ -0,0 +1,87 @@
+//! MI Command Trace
+//!
+//! When a result looks wrong, the first question is what the server actually
+//! asked GDB. While a verbose tool call runs, every MI command the client
+//! sends is recorded with the class of GDB's response, and the list is
+//! returned in the result's metadata.
+
+use crate::gdb::types::ResultClass;
+use serde::Serialize;
+
+/// Argument that turns on tracing for one tool call
+pub const VERBOSE: &str = "verbose";
+
+/// Most commands recorded at once; later ones are not recorded
+const MAX_EXCHANGES: usize = 500;
+
+/// One MI command and how GDB answered it
+#[derive(Debug, Clone, Serialize)]
+pub struct MiExchange {
+    /// The command as sent, without its token (e.g. `-break-insert main`)
+    pub command: String,
+    /// Result class of the response (None if GDB did not answer, or the
+    /// command was sent without waiting)
+    pub class: Option<ResultClass>,
+    /// Error message of an `^error` response or a failed send
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub error: Option<String>,
+    pub elapsed_ms: u64,
+}
+
+/// Commands sent during the verbose tool calls in progress
+#[derive(Debug, Default)]
+pub struct MiTrace {
+    /// Number of verbose calls in progress; nothing is recorded at 0
+    active: usize,
+    exchanges: Vec<MiExchange>,
+}
+
+impl MiTrace {
+    pub fn active(&self) -> bool {
+        self.active > 0
+    }
+
+    /// Start recording for a call. Returns the mark to pass to `end`.
+    pub fn begin(&mut self) -> usize {
+        self.active += 1;
+        self.exchanges.len()
+    }
+
+    /// Stop recording for a call and return what was sent since `mark`
+    pub fn end(&mut self, mark: usize) -> Vec<MiExchange> {
+        let exchanges = self.exchanges.get(mark..).unwrap_or_default().to_vec();
+        self.active = self.active.saturating_sub(1);
+        if self.active == 0 {
+            self.exchanges.clear();
+        }
+        exchanges
+    }
+
+    pub fn record(&mut self, exchange: MiExchange) {
+        if self.active() && self.exchanges.len() < MAX_EXCHANGES {
+            self.exchanges.push(exchange);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn exchange(command: &str) -> MiExchange {
+        MiExchange { command: command.to_string(), class: Some(ResultClass::Done), error: None, elapsed_ms: 1 }
+    }
+
+    #[test]
+    fn test_mi_trace() {
+        let mut trace = MiTrace::default();
+        trace.record(exchange("-gdb-version"));
+        let mark = trace.begin();
+        trace.record(exchange("-break-insert main"));
+        let exchanges = trace.end(mark);
+        assert_eq!(exchanges.len(), 1);
+        assert_eq!(exchanges[0].command, "-break-insert main");
+        assert_eq!(serde_json::to_value(&exchanges[0]).unwrap()["class"], "done");
+        assert!(!trace.active());
+    }
+}
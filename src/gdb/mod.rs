@@ -1,8 +1,50 @@
 //! GDB (GNU Debugger) MI Interface Module
 
 pub mod types;
+pub mod alloctrace;
+pub mod autoresume;
+pub mod backend;
+pub mod basicblock;
+pub mod breakstats;
+pub mod canary;
 pub mod parser;
+pub mod plot;
 pub mod client;
+pub mod compare;
+pub mod container;
+pub mod convenience;
+pub mod ctxswitch;
+pub mod diagnostics;
+pub mod endian;
+pub mod errno;
+pub mod evalhistory;
+pub mod exprdiff;
+pub mod fpunwind;
+pub mod helpers;
+pub mod hooks;
+pub mod image;
+pub mod inferior;
+pub mod loglocations;
+pub mod managed;
+pub mod mitrace;
+pub mod optimized;
+pub mod patch;
+pub mod pcsample;
+pub mod qemu;
+pub mod registers;
+pub mod script;
+pub mod signals;
+pub mod smp;
+pub mod softwatch;
+pub mod sourceverify;
+pub mod stackscan;
+pub mod symbols;
+pub mod timeline;
+pub mod tracker;
+pub mod tunnel;
+pub mod vectors;
+pub mod x86;
 
 pub use types::*;
-pub use client::GdbClient;
+pub use backend::BackendKind;
+pub use client::{GdbClient, Interrupter};
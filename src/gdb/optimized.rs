@@ -0,0 +1,72 @@
+//! Optimized Code Awareness
+//!
+//! Optimized builds inline functions into their callers and drop variables
+//! that are dead at the current PC. GDB reports the first as an extra frame
+//! sharing its caller's address and the second as `<optimized out>`; both
+//! are marked explicitly so responses can explain them.
+
+use crate::gdb::types::Frame;
+
+/// Value GDB prints for a variable without a location at the current PC
+pub const OPTIMIZED_OUT: &str = "<optimized out>";
+
+/// Whether a value is (or contains a member that is) optimized out
+pub fn is_optimized_out(value: Option<&str>) -> bool {
+    value.is_some_and(|v| v.contains(OPTIMIZED_OUT))
+}
+
+/// Mark the frames of inlined calls. An inlined function's frame has the
+/// same code address as the frame it was inlined into, which is the next
+/// outer one; a real caller resumes at a return address instead.
+pub fn mark_inlined_frames(frames: &mut [Frame]) {
+    for i in 0..frames.len().saturating_sub(1) {
+        let (inner, outer) = (&frames[i], &frames[i + 1]);
+        let inlined = outer.level == inner.level + 1 && !inner.addr.is_empty() && inner.addr == outer.addr;
+        frames[i].inlined = inlined;
+    }
+}
+
+/// Hint explaining inlined frames and optimized-out values, if any
+pub fn optimization_hint(inlined_frames: usize, optimized_out: usize) -> Option<String> {
+    let what = match (inlined_frames, optimized_out) {
+        (0, 0) => return None,
+        (frames, 0) => format!("{} frame(s) are inlined calls", frames),
+        (0, values) => format!("{} value(s) are {}", values, OPTIMIZED_OUT),
+        (frames, values) => format!("{} frame(s) are inlined calls and {} value(s) are {}", frames, values, OPTIMIZED_OUT),
+    };
+    Some(format!(
+        "Note: {}. The program was likely built with optimization, which inlines functions and discards variables that are no longer needed. Rebuild with -Og -g (or -O0 -g) to see them.",
+        what
+    ))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn frame(level: u64, addr: &str) -> Frame {
+        Frame {
+            level,
+            addr: addr.to_string(),
+            func: None,
+            file: None,
+            fullname: None,
+            line: None,
+            arch: None,
+            from: None,
+            inlined: false,
+        }
+    }
+
+    #[test]
+    fn test_inlined_frames() {
+        let mut frames = vec![frame(0, "0x401136"), frame(1, "0x401136"), frame(2, "0x401200")];
+        mark_inlined_frames(&mut frames);
+        assert_eq!(frames.iter().map(|f| f.inlined).collect::<Vec<_>>(), [true, false, false]);
+
+        assert!(is_optimized_out(Some("{a = 1, b = <optimized out>}")));
+        assert!(!is_optimized_out(Some("42")));
+        assert!(optimization_hint(0, 0).is_none());
+        assert!(optimization_hint(1, 2).unwrap().contains("1 frame(s) are inlined calls and 2 value(s) are <optimized out>"));
+    }
+}
@@ -2,6 +2,8 @@
 //!
 //! Parses GDB/MI output into structured Rust types.
 
+use crate::gdb::breakstats::unwrap_condition;
+use crate::gdb::optimized::is_optimized_out;
 use crate::gdb::types::*;
 use anyhow::{anyhow, Result};
 use regex::Regex;
@@ -215,7 +217,7 @@ impl MiParser {
                 }
                 
                 // Regular simple value
-                let end = input.find(|c: char| c == ',' || c == '}' || c == ']')
+                let end = input.find([',', '}', ']'])
                     .unwrap_or(input.len());
                 let value = input[..end].to_string();
                 Ok((MiValue::String(value), &input[end..]))
@@ -229,11 +231,10 @@ impl MiParser {
             return Err(anyhow!("String must start with '\"'"));
         }
         
-        let mut chars = input[1..].chars().peekable();
         let mut result = String::new();
         let mut escaped = false;
         
-        while let Some(c) = chars.next() {
+        for c in input[1..].chars() {
             if escaped {
                 match c {
                     'n' => result.push('\n'),
@@ -250,12 +251,6 @@ impl MiParser {
             } else if c == '\\' {
                 escaped = true;
             } else if c == '"' {
-                let remaining_len: usize = input[1..]
-                    .chars()
-                    .take_while(|&ch| ch != '"')
-                    .map(|ch| ch.len_utf8())
-                    .sum::<usize>() + 2;
-                let consumed = result.chars().map(|c| c.len_utf8()).sum::<usize>();
                 // Find the position after the closing quote
                 let pos = input[1..].find('"').ok_or_else(|| anyhow!("Unterminated string"))? + 2;
                 return Ok((result, &input[pos..]));
@@ -424,7 +419,20 @@ impl MiParser {
                 self.parse_value(value)?.0
             };
             
-            tuple.insert(key, parsed_value);
+            // Repeated keys, as in `thread-ids={thread-id="1",thread-id="2"}`,
+            // are collected into a list instead of keeping only the last
+            match tuple.remove(&key) {
+                Some(MiValue::List(mut values)) => {
+                    values.push(parsed_value);
+                    tuple.insert(key, MiValue::List(values));
+                }
+                Some(previous) => {
+                    tuple.insert(key, MiValue::List(vec![previous, parsed_value]));
+                }
+                None => {
+                    tuple.insert(key, parsed_value);
+                }
+            }
             
             current = value_end.trim_start();
             
@@ -516,7 +524,7 @@ impl MiParser {
             }
             _ => {
                 // Simple value - find comma or end
-                let end = input.find(|c: char| c == ',').unwrap_or(input.len());
+                let end = input.find(',').unwrap_or(input.len());
                 Ok((&input[..end], &input[end..]))
             }
         }
@@ -565,6 +573,16 @@ impl MiParser {
         }
     }
 
+    /// Extract the tuple of a list item, unwrapping `key={...}` items
+    /// such as the `frame={...}` entries of a stack list
+    pub fn extract_item_tuple(value: &MiValue) -> Option<&MiTuple> {
+        let tuple = Self::extract_tuple(value)?;
+        match tuple.get("__value__") {
+            Some(inner) if tuple.contains_key("__key__") => Self::extract_tuple(inner),
+            _ => Some(tuple),
+        }
+    }
+
     /// Extract a list from MiValue
     pub fn extract_list(value: &MiValue) -> Option<&Vec<MiValue>> {
         match value {
@@ -574,6 +592,7 @@ impl MiParser {
     }
 
     /// Get a value from a tuple by key
+    #[allow(dead_code)] // counterpart of get_tuple_string for non-string values
     pub fn get_tuple_value<'a>(tuple: &'a MiTuple, key: &str) -> Option<&'a MiValue> {
         tuple.get(key)
     }
@@ -595,32 +614,32 @@ pub fn parse_breakpoint(results: &[MiResult]) -> Option<Breakpoint> {
     let mut bp = Breakpoint::default();
     
     for result in results {
-        match result.variable.as_str() {
-            "bkpt" => {
-                if let MiValue::Tuple(tuple) = &result.value {
-                    bp.number = MiParser::get_tuple_string(tuple, "number")?;
-                    bp.breakpoint_type = MiParser::get_tuple_string(tuple, "type").unwrap_or_default();
-                    bp.disposition = MiParser::get_tuple_string(tuple, "disp").unwrap_or_default();
-                    bp.enabled = MiParser::get_tuple_string(tuple, "enabled")
-                        .map(|s| s == "y")
-                        .unwrap_or(true);
-                    bp.addr = MiParser::get_tuple_string(tuple, "addr");
-                    bp.func = MiParser::get_tuple_string(tuple, "func");
-                    bp.file = MiParser::get_tuple_string(tuple, "file");
-                    bp.fullname = MiParser::get_tuple_string(tuple, "fullname");
-                    bp.line = MiParser::get_tuple_string(tuple, "line")
-                        .and_then(|s| s.parse().ok());
-                    bp.times = MiParser::get_tuple_string(tuple, "times")
-                        .and_then(|s| s.parse().ok())
-                        .unwrap_or(0);
-                    bp.condition = MiParser::get_tuple_string(tuple, "cond");
-                    bp.ignore_count = MiParser::get_tuple_string(tuple, "ignore")
-                        .and_then(|s| s.parse().ok());
-                    bp.original_location = MiParser::get_tuple_string(tuple, "original-location");
-                    return Some(bp);
-                }
+        if result.variable == "bkpt" {
+            if let MiValue::Tuple(tuple) = &result.value {
+                bp.number = MiParser::get_tuple_string(tuple, "number")?;
+                bp.breakpoint_type = MiParser::get_tuple_string(tuple, "type").unwrap_or_default();
+                bp.disposition = MiParser::get_tuple_string(tuple, "disp").unwrap_or_default();
+                bp.enabled = MiParser::get_tuple_string(tuple, "enabled")
+                    .map(|s| s == "y")
+                    .unwrap_or(true);
+                bp.addr = MiParser::get_tuple_string(tuple, "addr");
+                bp.func = MiParser::get_tuple_string(tuple, "func");
+                bp.file = MiParser::get_tuple_string(tuple, "file");
+                bp.fullname = MiParser::get_tuple_string(tuple, "fullname");
+                bp.line = MiParser::get_tuple_string(tuple, "line")
+                    .and_then(|s| s.parse().ok());
+                bp.times = MiParser::get_tuple_string(tuple, "times")
+                    .and_then(|s| s.parse().ok())
+                    .unwrap_or(0);
+                bp.condition = MiParser::get_tuple_string(tuple, "cond");
+                bp.ignore_count = MiParser::get_tuple_string(tuple, "ignore")
+                    .and_then(|s| s.parse().ok());
+                bp.original_location = MiParser::get_tuple_string(tuple, "original-location");
+                bp.what = MiParser::get_tuple_string(tuple, "what");
+                bp.thread = MiParser::get_tuple_string(tuple, "thread");
+                unwrap_condition(&mut bp);
+                return Some(bp);
             }
-            _ => {}
         }
     }
     
@@ -668,6 +687,8 @@ pub fn parse_frame(results: &[MiResult]) -> Option<Frame> {
                     line: MiParser::get_tuple_string(tuple, "line")
                         .and_then(|s| s.parse().ok()),
                     arch: MiParser::get_tuple_string(tuple, "arch"),
+                    from: MiParser::get_tuple_string(tuple, "from"),
+                    inlined: false,
                 });
             }
         }
@@ -676,6 +697,7 @@ pub fn parse_frame(results: &[MiResult]) -> Option<Frame> {
 }
 
 /// Parse thread from MI results
+#[allow(dead_code)] // threads are listed through parse_thread_info
 pub fn parse_thread(results: &[MiResult]) -> Option<Thread> {
     for result in results {
         if result.variable == "new-thread-id" || result.variable == "id" {
@@ -758,6 +780,8 @@ pub fn parse_breakpoint_list(results: &[MiResult]) -> Vec<Breakpoint> {
                                                 "line" => bp.line = s.parse().ok(),
                                                 "times" => bp.times = s.parse().unwrap_or(0),
                                                 "original-location" => bp.original_location = Some(s.clone()),
+                                                "what" => bp.what = Some(s.clone()),
+                                                "thread" => bp.thread = Some(s.clone()),
                                                 "cond" => bp.condition = Some(s.clone()),
                                                 "ignore" => bp.ignore_count = s.parse().ok(),
                                                 _ => {}
@@ -765,7 +789,7 @@ pub fn parse_breakpoint_list(results: &[MiResult]) -> Vec<Breakpoint> {
                                         } else if let MiValue::List(list) = val {
                                             if key_str == "thread-groups" {
                                                 bp.thread_groups = Some(list.iter()
-                                                    .filter_map(|v| MiParser::extract_string(v))
+                                                    .filter_map(MiParser::extract_string)
                                                     .collect());
                                             }
                                         }
@@ -773,11 +797,6 @@ pub fn parse_breakpoint_list(results: &[MiResult]) -> Vec<Breakpoint> {
                                 }
                                 _ => {}
                             }
-                        } else if let MiValue::Tuple(bkpt_tuple) = item {
-                            // Old format: direct tuple (if body contains bkpt tuples directly)
-                            if let Some(bp) = parse_breakpoint_from_tuple(bkpt_tuple) {
-                                breakpoints.push(bp);
-                            }
                         }
                     }
                     
@@ -792,6 +811,7 @@ pub fn parse_breakpoint_list(results: &[MiResult]) -> Vec<Breakpoint> {
         }
     }
     
+    breakpoints.iter_mut().for_each(unwrap_condition);
     debug!("Parsed {} breakpoints", breakpoints.len());
     breakpoints
 }
@@ -810,8 +830,11 @@ fn parse_breakpoint_from_tuple(tuple: &MiTuple) -> Option<Breakpoint> {
         thread_groups: None,
         times: MiParser::get_tuple_string(tuple, "times").and_then(|s| s.parse().ok()).unwrap_or(0),
         original_location: MiParser::get_tuple_string(tuple, "original-location"),
+        what: MiParser::get_tuple_string(tuple, "what"),
+        thread: MiParser::get_tuple_string(tuple, "thread"),
         condition: MiParser::get_tuple_string(tuple, "cond"),
         ignore_count: MiParser::get_tuple_string(tuple, "ignore").and_then(|s| s.parse().ok()),
+        evaluation_counter: None,
     })
 }
 
@@ -823,10 +846,8 @@ pub fn parse_stack_frames(results: &[MiResult]) -> Vec<Frame> {
         if result.variable == "stack" {
             if let MiValue::List(stack_list) = &result.value {
                 for item in stack_list {
-                    if let MiValue::Tuple(frame_tuple) = item {
-                        if let Some(frame) = parse_frame_from_tuple(frame_tuple) {
-                            frames.push(frame);
-                        }
+                    if let Some(frame) = MiParser::extract_item_tuple(item).and_then(parse_frame_from_tuple) {
+                        frames.push(frame);
                     }
                 }
             }
@@ -845,9 +866,45 @@ fn parse_frame_from_tuple(tuple: &MiTuple) -> Option<Frame> {
         fullname: MiParser::get_tuple_string(tuple, "fullname"),
         line: MiParser::get_tuple_string(tuple, "line").and_then(|s| s.parse().ok()),
         arch: MiParser::get_tuple_string(tuple, "arch"),
+        from: MiParser::get_tuple_string(tuple, "from"),
+        inlined: false,
     })
 }
 
+/// Parse stack-list-variables response into (arguments, locals)
+pub fn parse_stack_variables(results: &[MiResult]) -> (Vec<Argument>, Vec<Argument>) {
+    let mut args = Vec::new();
+    let mut locals = Vec::new();
+
+    for result in results {
+        if result.variable == "variables" {
+            if let MiValue::List(list) = &result.value {
+                for item in list {
+                    let Some(tuple) = MiParser::extract_item_tuple(item) else {
+                        continue;
+                    };
+                    let Some(name) = MiParser::get_tuple_string(tuple, "name") else {
+                        continue;
+                    };
+                    let value = MiParser::get_tuple_string(tuple, "value");
+                    let variable = Argument {
+                        name,
+                        optimized_out: is_optimized_out(value.as_deref()),
+                        value,
+                    };
+                    if MiParser::get_tuple_string(tuple, "arg").as_deref() == Some("1") {
+                        args.push(variable);
+                    } else {
+                        locals.push(variable);
+                    }
+                }
+            }
+        }
+    }
+
+    (args, locals)
+}
+
 /// Parse thread IDs from thread-list-ids response
 pub fn parse_thread_ids(results: &[MiResult]) -> Vec<String> {
     let mut ids = Vec::new();
@@ -855,7 +912,7 @@ pub fn parse_thread_ids(results: &[MiResult]) -> Vec<String> {
     for result in results {
         if result.variable == "thread-ids" {
             if let MiValue::Tuple(thread_ids) = &result.value {
-                for (_key, value) in thread_ids {
+                for value in thread_ids.values() {
                     if let MiValue::String(s) = value {
                         ids.push(s.clone());
                     } else if let MiValue::List(list) = value {
@@ -879,31 +936,420 @@ pub fn parse_thread_ids(results: &[MiResult]) -> Vec<String> {
     ids
 }
 
+/// Parse the threads of a thread-info response, with their frames
+pub fn parse_thread_info(results: &[MiResult]) -> Vec<Thread> {
+    let Some(list) = results.iter().find(|r| r.variable == "threads").and_then(|r| MiParser::extract_list(&r.value)) else {
+        return Vec::new();
+    };
+    list.iter()
+        .filter_map(MiParser::extract_item_tuple)
+        .filter_map(|tuple| Some(Thread {
+            id: MiParser::get_tuple_string(tuple, "id")?,
+            target_id: MiParser::get_tuple_string(tuple, "target-id").unwrap_or_default(),
+            name: MiParser::get_tuple_string(tuple, "name"),
+            frame: tuple.get("frame").and_then(MiParser::extract_tuple).and_then(parse_frame_from_tuple),
+            state: match MiParser::get_tuple_string(tuple, "state").as_deref() {
+                Some("running") => ThreadState::Running,
+                _ => ThreadState::Stopped,
+            },
+            core: MiParser::get_tuple_string(tuple, "core").and_then(|s| s.parse().ok()),
+        }))
+        .collect()
+}
+
+/// Parse asm_insns from a data-disassemble response (mode 0)
+pub fn parse_asm_instructions(results: &[MiResult]) -> Vec<AsmInstruction> {
+    let mut instructions = Vec::new();
+
+    for result in results {
+        if result.variable == "asm_insns" {
+            if let MiValue::List(list) = &result.value {
+                for item in list {
+                    let Some(tuple) = MiParser::extract_item_tuple(item) else {
+                        continue;
+                    };
+                    let Some(address) = MiParser::get_tuple_string(tuple, "address") else {
+                        continue;
+                    };
+                    instructions.push(AsmInstruction {
+                        address,
+                        func_name: MiParser::get_tuple_string(tuple, "func-name"),
+                        offset: MiParser::get_tuple_string(tuple, "offset").and_then(|s| s.parse().ok()),
+                        inst: MiParser::get_tuple_string(tuple, "inst").unwrap_or_default(),
+                        target_symbol: None,
+                    });
+                }
+            }
+        }
+    }
+
+    instructions
+}
+
+/// Parse a symbol-list-lines response into a line table sorted by line
+pub fn parse_symbol_lines(results: &[MiResult]) -> Vec<LineTableEntry> {
+    let mut lines: std::collections::BTreeMap<u64, Vec<String>> = std::collections::BTreeMap::new();
+
+    for result in results {
+        if result.variable == "lines" {
+            if let MiValue::List(list) = &result.value {
+                for tuple in list.iter().filter_map(MiParser::extract_item_tuple) {
+                    let pc = MiParser::get_tuple_string(tuple, "pc");
+                    let line = MiParser::get_tuple_string(tuple, "line").and_then(|s| s.parse().ok());
+                    if let (Some(pc), Some(line)) = (pc, line) {
+                        lines.entry(line).or_default().push(pc);
+                    }
+                }
+            }
+        }
+    }
+
+    lines.into_iter()
+        .map(|(line, addresses)| LineTableEntry { line, file: None, addresses })
+        .collect()
+}
+
+/// Parse `-file-list-exec-source-files` into (file, fullname) pairs,
+/// without duplicates
+pub fn parse_source_files(results: &[MiResult]) -> Vec<(String, Option<String>)> {
+    let mut files: Vec<(String, Option<String>)> = Vec::new();
+    for result in results.iter().filter(|r| r.variable == "files") {
+        if let MiValue::List(list) = &result.value {
+            for tuple in list.iter().filter_map(MiParser::extract_item_tuple) {
+                let Some(file) = MiParser::get_tuple_string(tuple, "file") else { continue };
+                let fullname = MiParser::get_tuple_string(tuple, "fullname");
+                if !files.iter().any(|(f, full)| *f == file && *full == fullname) {
+                    files.push((file, fullname));
+                }
+            }
+        }
+    }
+    files
+}
+
+/// Parse a mixed source/assembly data-disassemble response (mode 1) into
+/// a line table in address order
+pub fn parse_source_line_blocks(results: &[MiResult]) -> Vec<LineTableEntry> {
+    let mut entries = Vec::new();
+
+    for result in results {
+        if result.variable == "asm_insns" {
+            if let MiValue::List(list) = &result.value {
+                for tuple in list.iter().filter_map(MiParser::extract_item_tuple) {
+                    let Some(line) = MiParser::get_tuple_string(tuple, "line").and_then(|s| s.parse().ok()) else {
+                        continue;
+                    };
+                    let first_address = tuple.get("line_asm_insn")
+                        .and_then(MiParser::extract_list)
+                        .and_then(|insns| insns.iter().filter_map(MiParser::extract_item_tuple).next())
+                        .and_then(|insn| MiParser::get_tuple_string(insn, "address"));
+                    let Some(address) = first_address else {
+                        continue;
+                    };
+                    entries.push(LineTableEntry {
+                        line,
+                        file: MiParser::get_tuple_string(tuple, "file"),
+                        addresses: vec![address],
+                    });
+                }
+            }
+        }
+    }
+
+    entries
+}
+
+/// Parse the console output of `-gdb-version`
+pub fn parse_gdb_version(output: &str) -> Option<GdbVersion> {
+    let banner = output.lines().map(str::trim).find(|l| !l.is_empty())?;
+    let version = Regex::new(r"(\d+)\.(\d+)").ok()?;
+    let caps = version.captures_iter(banner).last()?;
+
+    // `This GDB was configured as "x86_64-linux-gnu".` or, for cross
+    // debuggers, `... as "--host=x86_64-linux-gnu --target=arm-none-eabi".`
+    let target = output
+        .lines()
+        .find_map(|l| l.split("configured as \"").nth(1))
+        .and_then(|rest| rest.split('"').next())
+        .map(|configured| {
+            configured
+                .split_whitespace()
+                .find_map(|part| part.strip_prefix("--target="))
+                .unwrap_or(configured)
+                .to_string()
+        });
+
+    Some(GdbVersion {
+        banner: banner.to_string(),
+        major: caps[1].parse().ok()?,
+        minor: caps[2].parse().ok()?,
+        target,
+    })
+}
+
+/// Parse a `-symbol-info-functions` response
+pub fn parse_function_symbols(results: &[MiResult]) -> Vec<FunctionSymbol> {
+    let mut functions = Vec::new();
+    let Some(symbols) = results.iter()
+        .find(|r| r.variable == "symbols")
+        .and_then(|r| MiParser::extract_tuple(&r.value))
+    else {
+        return functions;
+    };
+
+    let debug = symbols.get("debug").and_then(MiParser::extract_list);
+    for file in debug.into_iter().flatten().filter_map(MiParser::extract_item_tuple) {
+        let filename = MiParser::get_tuple_string(file, "fullname")
+            .or_else(|| MiParser::get_tuple_string(file, "filename"));
+        let entries = file.get("symbols").and_then(MiParser::extract_list);
+        for symbol in entries.into_iter().flatten().filter_map(MiParser::extract_item_tuple) {
+            let Some(name) = MiParser::get_tuple_string(symbol, "name") else {
+                continue;
+            };
+            functions.push(FunctionSymbol {
+                name,
+                declaration: MiParser::get_tuple_string(symbol, "description"),
+                file: filename.clone(),
+                line: MiParser::get_tuple_string(symbol, "line").and_then(|l| l.parse().ok()),
+                address: None,
+            });
+        }
+    }
+
+    let nondebug = symbols.get("nondebug").and_then(MiParser::extract_list);
+    for symbol in nondebug.into_iter().flatten().filter_map(MiParser::extract_item_tuple) {
+        if let Some(name) = MiParser::get_tuple_string(symbol, "name") {
+            functions.push(FunctionSymbol {
+                name,
+                declaration: None,
+                file: None,
+                line: None,
+                address: MiParser::get_tuple_string(symbol, "address"),
+            });
+        }
+    }
+
+    functions
+}
+
+/// Parse the console output of `info functions`, for GDB releases without
+/// `-symbol-info-functions`
+pub fn parse_info_functions(output: &str) -> Vec<FunctionSymbol> {
+    let mut functions = Vec::new();
+    let mut file: Option<String> = None;
+    let mut nondebug = false;
+
+    for line in output.lines().map(str::trim).filter(|l| !l.is_empty()) {
+        if let Some(name) = line.strip_prefix("File ").and_then(|l| l.strip_suffix(':')) {
+            file = Some(name.to_string());
+            nondebug = false;
+        } else if line == "Non-debugging symbols:" {
+            file = None;
+            nondebug = true;
+        } else if nondebug {
+            let mut parts = line.split_whitespace();
+            if let (Some(address), Some(name)) = (parts.next(), parts.next()) {
+                functions.push(FunctionSymbol {
+                    name: name.to_string(),
+                    declaration: None,
+                    file: None,
+                    line: None,
+                    address: Some(address.to_string()),
+                });
+            }
+        } else if file.is_some() {
+            // "12:\tint main(void);" (older releases omit the line number)
+            let (line_no, declaration) = match line.split_once(':') {
+                Some((n, rest)) if n.chars().all(|c| c.is_ascii_digit()) => (n.parse().ok(), rest.trim()),
+                _ => (None, line),
+            };
+            let Some(name) = declaration
+                .split('(')
+                .next()
+                .and_then(|head| head.split_whitespace().last())
+                .map(|n| n.trim_start_matches('*'))
+                .filter(|n| !n.is_empty())
+            else {
+                continue;
+            };
+            functions.push(FunctionSymbol {
+                name: name.to_string(),
+                declaration: Some(declaration.to_string()),
+                file: file.clone(),
+                line: line_no,
+                address: None,
+            });
+        }
+    }
+
+    functions
+}
+
+/// Convert function-style symbol entries from `-symbol-info-variables` or
+/// `info variables`, which share the layout of their function
+/// counterparts, into variables named from their declarations
+pub fn variable_symbols(symbols: Vec<FunctionSymbol>) -> Vec<GlobalVariable> {
+    symbols
+        .into_iter()
+        .map(|symbol| GlobalVariable {
+            name: symbol.declaration.as_deref().and_then(declared_name).unwrap_or(symbol.name),
+            declaration: symbol.declaration,
+            file: symbol.file,
+            line: symbol.line,
+            address: symbol.address,
+            value: None,
+            error: None,
+        })
+        .collect()
+}
+
+/// Name declared by a C declaration such as `static char buf[16];` or
+/// `void (*handler)(int);`
+fn declared_name(declaration: &str) -> Option<String> {
+    let declaration = declaration.trim().trim_end_matches(';');
+    let name = match declaration.find("(*") {
+        Some(start) => declaration[start + 2..].split(')').next()?,
+        None => declaration.split('[').next()?.split_whitespace().last()?,
+    };
+    let name = name.trim_start_matches(['*', '&']).trim();
+    (!name.is_empty()).then(|| name.to_string())
+}
+
+/// Get the target address of a branch or call instruction whose target GDB
+/// did not annotate with a `<symbol>`
+pub fn parse_branch_target(inst: &str) -> Option<u64> {
+    if inst.contains('<') {
+        return None;
+    }
+
+    let mut parts = inst.split_whitespace();
+    let mnemonic = parts.next()?.to_ascii_lowercase();
+    let is_branch = ["j", "b", "call", "c.j", "c.b", "cb", "tb", "loop"]
+        .iter()
+        .any(|prefix| mnemonic.starts_with(prefix));
+    if !is_branch {
+        return None;
+    }
+
+    let operand = inst.rsplit([',', ' ', '\t']).next()?;
+    if !operand.starts_with("0x") {
+        return None;
+    }
+    parse_address(operand)
+}
+
+/// Parse `record function-call-history /c` output into (call depth,
+/// function) segments
+pub fn parse_call_history(output: &str) -> Vec<(usize, String)> {
+    output
+        .lines()
+        .filter_map(|line| {
+            let (number, rest) = line.split_once('\t')?;
+            number.trim().parse::<u64>().ok()?;
+            let function = rest.trim();
+            if function.is_empty() {
+                return None;
+            }
+            let indent = rest.len() - rest.trim_start().len();
+            Some((indent / 2, function.to_string()))
+        })
+        .collect()
+}
+
+/// Count calls in a function call history. A segment deeper than the one
+/// before it is a call; returning to a caller is not.
+pub fn count_calls(segments: &[(usize, String)]) -> Vec<FunctionCalls> {
+    let mut counts: HashMap<&str, u64> = HashMap::new();
+    for pair in segments.windows(2) {
+        if pair[1].0 > pair[0].0 {
+            *counts.entry(pair[1].1.as_str()).or_default() += 1;
+        }
+    }
+    let mut calls: Vec<FunctionCalls> = counts
+        .into_iter()
+        .map(|(function, calls)| FunctionCalls { function: function.to_string(), calls })
+        .collect();
+    calls.sort_by(|a, b| b.calls.cmp(&a.calls).then_with(|| a.function.cmp(&b.function)));
+    calls
+}
+
+/// Classify a disassembled instruction (x86, ARM/AArch64, RISC-V) as a
+/// jump, call or return
+pub fn branch_kind(inst: &str) -> Option<BranchKind> {
+    const CONDITIONS: &[&str] = &[
+        "eq", "ne", "cs", "cc", "hs", "lo", "mi", "pl", "vs", "vc", "hi", "ls", "ge", "lt", "gt", "le", "al",
+    ];
+    const RISCV_BRANCHES: &[&str] = &[
+        "beq", "bne", "blt", "bge", "bltu", "bgeu", "beqz", "bnez", "blez", "bgez", "bltz", "bgtz", "bgt", "ble", "bgtu", "bleu",
+    ];
+
+    let inst = inst.trim().to_ascii_lowercase();
+    let mut words = inst.split_whitespace()
+        .skip_while(|w| ["rep", "repz", "repnz", "bnd", "notrack", "lock"].contains(w));
+    let mnemonic = words.next()?;
+    let operands: String = words.collect::<Vec<_>>().join(" ");
+    let first_operand = operands.split(',').next().unwrap_or("").trim();
+    // Thumb width qualifiers
+    let base = mnemonic.trim_end_matches(".w").trim_end_matches(".n");
+
+    let kind = match base {
+        "ret" | "retq" | "retf" | "retn" | "iret" | "iretd" | "iretq" | "sysret" | "sysretq" | "sysexit"
+        | "eret" | "mret" | "sret" | "uret" | "dret" => BranchKind::Return,
+        "call" | "callq" | "lcall" | "syscall" | "sysenter" | "svc" | "swi" | "hvc" | "smc" | "ecall"
+        | "bl" | "blx" | "blr" | "c.jal" | "c.jalr" => BranchKind::Call,
+        "bx" if first_operand == "lr" => BranchKind::Return,
+        "bx" | "br" | "j" | "c.j" | "cbz" | "cbnz" | "tbz" | "tbnz" | "c.beqz" | "c.bnez" | "ljmp" => BranchKind::Jump,
+        "jr" | "c.jr" if first_operand == "ra" => BranchKind::Return,
+        "jr" | "c.jr" => BranchKind::Jump,
+        "jal" if first_operand == "zero" || first_operand == "x0" => BranchKind::Jump,
+        "jal" => BranchKind::Call,
+        "jalr" if first_operand == "zero" || first_operand == "x0" => {
+            if operands.contains("(ra)") || operands.ends_with(",ra") { BranchKind::Return } else { BranchKind::Jump }
+        }
+        "jalr" => BranchKind::Call,
+        "pop" | "ldm" | "ldmia" | "ldmfd" if operands.contains("pc}") => BranchKind::Return,
+        "mov" if first_operand == "pc" => {
+            if operands.ends_with("lr") { BranchKind::Return } else { BranchKind::Jump }
+        }
+        "ldr" if first_operand == "pc" => BranchKind::Jump,
+        _ if base.starts_with('j') || base.starts_with("loop") => BranchKind::Jump,
+        _ if RISCV_BRANCHES.contains(&base) => BranchKind::Jump,
+        _ => {
+            // ARM conditional forms: beq, b.ne, bleq, bxeq
+            let cond = |prefix: &str| {
+                base.strip_prefix(prefix)
+                    .map(|c| c.trim_start_matches('.'))
+                    .is_some_and(|c| CONDITIONS.contains(&c))
+            };
+            if cond("b") {
+                BranchKind::Jump
+            } else if cond("bl") {
+                BranchKind::Call
+            } else if cond("bx") {
+                if first_operand == "lr" { BranchKind::Return } else { BranchKind::Jump }
+            } else {
+                return None;
+            }
+        }
+    };
+    Some(kind)
+}
+
 /// Parse memory content from data-read-memory-bytes response
 pub fn parse_memory_content(results: &[MiResult]) -> Option<MemoryContent> {
     for result in results {
         if result.variable == "memory" {
             if let MiValue::List(memory_list) = &result.value {
-                if let Some(first) = memory_list.first() {
-                    if let MiValue::Tuple(mem_tuple) = first {
-                        let addr = MiParser::get_tuple_string(mem_tuple, "begin")
-                            .or_else(|| MiParser::get_tuple_string(mem_tuple, "addr"))
-                            .or_else(|| MiParser::get_tuple_string(mem_tuple, "offset"))?;
-                        let contents = MiParser::get_tuple_string(mem_tuple, "contents")?;
-                        
-                        let data: Vec<String> = contents
-                            .as_bytes()
-                            .chunks(2)
-                            .map(|chunk| {
-                                String::from_utf8_lossy(chunk).to_string()
-                            })
-                            .collect();
-                        
-                        return Some(MemoryContent {
-                            addr,
-                            data: vec![contents],
-                        });
-                    }
+                if let Some(MiValue::Tuple(mem_tuple)) = memory_list.first() {
+                    let addr = MiParser::get_tuple_string(mem_tuple, "begin")
+                        .or_else(|| MiParser::get_tuple_string(mem_tuple, "addr"))
+                        .or_else(|| MiParser::get_tuple_string(mem_tuple, "offset"))?;
+                    let contents = MiParser::get_tuple_string(mem_tuple, "contents")?;
+                    
+                    return Some(MemoryContent {
+                        addr,
+                        data: vec![contents],
+                    });
                 }
             }
         }
@@ -911,6 +1357,72 @@ pub fn parse_memory_content(results: &[MiResult]) -> Option<MemoryContent> {
     None
 }
 
+/// Decode a hex string (as returned in `contents`) into bytes
+pub fn parse_hex_bytes(contents: &str) -> Vec<u8> {
+    contents
+        .as_bytes()
+        .chunks(2)
+        .filter_map(|chunk| {
+            std::str::from_utf8(chunk)
+                .ok()
+                .and_then(|s| u8::from_str_radix(s, 16).ok())
+        })
+        .collect()
+}
+
+/// Parse an address string such as "0x0000000080000080"
+pub fn parse_address(addr: &str) -> Option<u64> {
+    let addr = addr.trim();
+    let addr = addr.split_whitespace().next()?;
+    match addr.strip_prefix("0x").or_else(|| addr.strip_prefix("0X")) {
+        Some(hex) => u64::from_str_radix(hex, 16).ok(),
+        None => addr.parse().ok(),
+    }
+}
+
+/// Split a printed GDB value into its number and the character, string or
+/// symbol annotation that follows it. Composite values (`{a = 1}`) and
+/// enumerators are left unparsed.
+pub fn parse_value(value: &str) -> ParsedValue {
+    let mut rest = value.trim();
+    // Pointer casts `(int *) 0x...` and function types `{int (void)} 0x...`
+    for (open, close) in [('(', ')'), ('{', '}')] {
+        if rest.starts_with(open) {
+            if let Some(end) = rest.find(close) {
+                let after = rest[end + 1..].trim_start();
+                if after.starts_with("0x") {
+                    rest = after;
+                }
+            }
+        }
+    }
+
+    let (number, annotation) = rest.split_once(' ').unwrap_or((rest, ""));
+    let mut parsed = ParsedValue::default();
+    if let Some(hex) = number.strip_prefix("0x") {
+        parsed.integer = u64::from_str_radix(hex, 16).ok().map(i128::from);
+    } else if let Ok(integer) = number.parse::<i128>() {
+        parsed.integer = Some(integer);
+    } else if number == "true" || number == "false" {
+        parsed.integer = Some((number == "true") as i128);
+    } else if number.chars().next().is_some_and(|c| c.is_ascii_digit() || c == '-') {
+        parsed.float = number.parse().ok();
+    }
+    if parsed.integer.is_none() && parsed.float.is_none() {
+        return parsed;
+    }
+
+    let annotation = annotation.trim();
+    if let Some(symbol) = annotation.strip_prefix('<').and_then(|a| a.strip_suffix('>')) {
+        parsed.symbol = Some(symbol.to_string());
+    } else if let Some(text) = annotation.strip_prefix('\'').and_then(|a| a.strip_suffix('\'')) {
+        parsed.text = Some(text.to_string());
+    } else if let Some(text) = annotation.strip_prefix('"').and_then(|a| a.strip_suffix('"')) {
+        parsed.text = Some(text.to_string());
+    }
+    parsed
+}
+
 /// Parse register names from data-list-register-names response
 pub fn parse_register_names(results: &[MiResult]) -> Vec<String> {
     for result in results {
@@ -982,7 +1494,7 @@ pub fn parse_variable(results: &[MiResult], var_name: &str) -> Option<Variable>
         .and_then(|r| {
             if let MiValue::List(list) = &r.value {
                 Some(list.iter()
-                    .filter_map(|v| MiParser::extract_string(v))
+                    .filter_map(MiParser::extract_string)
                     .collect())
             } else {
                 None
@@ -991,6 +1503,7 @@ pub fn parse_variable(results: &[MiResult], var_name: &str) -> Option<Variable>
     
     Some(Variable {
         name,
+        optimized_out: is_optimized_out(value.as_deref()),
         value,
         var_type,
         attributes,
@@ -999,6 +1512,7 @@ pub fn parse_variable(results: &[MiResult], var_name: &str) -> Option<Variable>
 }
 
 /// Parse variable children from var-list-children response
+#[allow(dead_code)] // used by GdbClient::var_list_children only
 pub fn parse_variable_children(results: &[MiResult]) -> Vec<Variable> {
     let mut children = Vec::new();
     
@@ -1006,10 +1520,8 @@ pub fn parse_variable_children(results: &[MiResult]) -> Vec<Variable> {
         if result.variable == "children" {
             if let MiValue::List(child_list) = &result.value {
                 for item in child_list {
-                    if let MiValue::Tuple(child_tuple) = item {
-                        if let Some(child) = parse_child_variable(child_tuple) {
-                            children.push(child);
-                        }
+                    if let Some(child) = MiParser::extract_item_tuple(item).and_then(parse_child_variable) {
+                        children.push(child);
                     }
                 }
             }
@@ -1019,6 +1531,60 @@ pub fn parse_variable_children(results: &[MiResult]) -> Vec<Variable> {
     children
 }
 
+/// Parse a `-var-list-children` response into struct fields, without
+/// offsets or nested fields
+pub fn parse_struct_fields(results: &[MiResult]) -> Vec<StructField> {
+    results.iter()
+        .find(|r| r.variable == "children")
+        .and_then(|r| MiParser::extract_list(&r.value))
+        .map(|list| {
+            list.iter()
+                .filter_map(MiParser::extract_item_tuple)
+                .filter_map(|t| {
+                    let exp = MiParser::get_tuple_string(t, "exp")?;
+                    let var_type = MiParser::get_tuple_string(t, "type");
+                    // Array elements are listed by index
+                    let name = if exp.parse::<u64>().is_ok() && var_type.is_some() { format!("[{}]", exp) } else { exp };
+                    Some(StructField {
+                        name,
+                        var_type,
+                        offset: None,
+                        size: None,
+                        value: MiParser::get_tuple_string(t, "value"),
+                        fields: Vec::new(),
+                        omitted: 0,
+                        varobj: MiParser::get_tuple_string(t, "name")?,
+                        children: MiParser::get_tuple_string(t, "numchild").and_then(|n| n.parse().ok()).unwrap_or(0),
+                    })
+                })
+                .collect()
+        })
+        .unwrap_or_default()
+}
+
+/// Parse the changelist of a `-var-update` response
+pub fn parse_var_changes(results: &[MiResult]) -> Vec<VarChange> {
+    results.iter()
+        .find(|r| r.variable == "changelist")
+        .and_then(|r| MiParser::extract_list(&r.value))
+        .map(|list| {
+            list.iter()
+                .filter_map(MiParser::extract_item_tuple)
+                .filter_map(|t| {
+                    Some(VarChange {
+                        name: MiParser::get_tuple_string(t, "name")?,
+                        expression: None,
+                        value: MiParser::get_tuple_string(t, "value"),
+                        in_scope: MiParser::get_tuple_string(t, "in_scope").unwrap_or_else(|| "true".to_string()),
+                        new_type: MiParser::get_tuple_string(t, "new_type"),
+                    })
+                })
+                .collect()
+        })
+        .unwrap_or_default()
+}
+
+#[allow(dead_code)] // used by parse_variable_children only
 fn parse_child_variable(tuple: &MiTuple) -> Option<Variable> {
     let name = MiParser::get_tuple_string(tuple, "name")?;
     let value = MiParser::get_tuple_string(tuple, "value");
@@ -1026,6 +1592,7 @@ fn parse_child_variable(tuple: &MiTuple) -> Option<Variable> {
     
     Some(Variable {
         name,
+        optimized_out: is_optimized_out(value.as_deref()),
         value,
         var_type,
         attributes: None,
@@ -1037,6 +1604,58 @@ fn parse_child_variable(tuple: &MiTuple) -> Option<Variable> {
 mod tests {
     use super::*;
 
+    #[test]
+    fn test_parse_gdb_version() {
+        let native = "GNU gdb (Ubuntu 12.1-0ubuntu1~22.04) 12.1\nCopyright (C) 2022 Free Software Foundation, Inc.\n\
+                      This GDB was configured as \"x86_64-linux-gnu\".\n";
+        let version = parse_gdb_version(native).unwrap();
+        assert_eq!((version.major, version.minor), (12, 1));
+        assert_eq!(version.target.as_deref(), Some("x86_64-linux-gnu"));
+        assert!(version.supports(MiFeature::SymbolInfo));
+
+        let cross = "GNU gdb (Arm GNU Toolchain 7.6) 7.6.1\n\
+                     This GDB was configured as \"--host=x86_64-pc-linux-gnu --target=arm-none-eabi\".\n";
+        let version = parse_gdb_version(cross).unwrap();
+        assert_eq!((version.major, version.minor), (7, 6));
+        assert_eq!(version.target.as_deref(), Some("arm-none-eabi"));
+        assert!(!version.supports(MiFeature::SkipUnavailable));
+        assert!(parse_gdb_version("").is_none());
+    }
+
+    #[test]
+    fn test_variable_symbols() {
+        let output = "All defined variables:\n\nFile src/main.c:\n4:\tstatic char buf[16];\n7:\tvoid (*handler)(int);\n9:\tint *counter;\n\n\
+                      Non-debugging symbols:\n0x0000000000004010  __dso_handle\n";
+        let variables = variable_symbols(parse_info_functions(output));
+        let names: Vec<&str> = variables.iter().map(|v| v.name.as_str()).collect();
+        assert_eq!(names, ["buf", "handler", "counter", "__dso_handle"]);
+        assert_eq!(variables[0].file.as_deref(), Some("src/main.c"));
+        assert_eq!(variables[3].address.as_deref(), Some("0x0000000000004010"));
+    }
+
+    #[test]
+    fn test_parse_function_symbols() {
+        let parser = MiParser::new();
+        let line = "^done,symbols={debug=[{filename=\"t.c\",fullname=\"/src/t.c\",symbols=[{line=\"3\",name=\"main\",type=\"int (void)\",description=\"int main(void);\"}]}],nondebug=[{address=\"0x0000000000001000\",name=\"_init\"}]}";
+        let MiOutputRecord::Result { results, .. } = parser.parse_line(line).unwrap().unwrap() else {
+            panic!("Expected result record");
+        };
+        let functions = parse_function_symbols(&results);
+        assert_eq!(functions.len(), 2);
+        assert_eq!(functions[0].name, "main");
+        assert_eq!(functions[0].file.as_deref(), Some("/src/t.c"));
+        assert_eq!(functions[0].line, Some(3));
+        assert_eq!(functions[1].address.as_deref(), Some("0x0000000000001000"));
+
+        let console = "All functions matching regular expression \"ma\":\n\nFile /src/t.c:\n3:\tint main(void);\n\tstatic char *make_name(int);\n\n\
+                       Non-debugging symbols:\n0x0000000000001000  _init\n";
+        let functions = parse_info_functions(console);
+        let names: Vec<_> = functions.iter().map(|f| f.name.as_str()).collect();
+        assert_eq!(names, ["main", "make_name", "_init"]);
+        assert_eq!(functions[0].line, Some(3));
+        assert_eq!(functions[1].line, None);
+    }
+
     #[test]
     fn test_parse_result_done() {
         let parser = MiParser::new();
@@ -1063,6 +1682,43 @@ mod tests {
         }
     }
 
+    #[test]
+    fn test_parse_thread_ids() {
+        let parser = MiParser::new();
+        let line = r#"^done,thread-ids={thread-id="2",thread-id="1"},current-thread-id="1",number-of-threads="2""#;
+        let MiOutputRecord::Result { results, .. } = parser.parse_line(line).unwrap().unwrap() else {
+            panic!("Expected result record");
+        };
+        assert_eq!(parse_thread_ids(&results), ["2", "1"]);
+    }
+
+    #[test]
+    fn test_parse_thread_info() {
+        let parser = MiParser::new();
+        let line = r#"^done,threads=[{id="2",target-id="Thread 1.2 (CPU#1 [halted ])",frame={level="0",addr="0xffffffff8100f1e0",func="default_idle",args=[]},state="stopped"},{id="1",target-id="Thread 1.1 (CPU#0 [running])",frame={level="0",addr="0xffffffff81a3c0d4",func="schedule",args=[]},state="stopped",core="0"}],current-thread-id="1""#;
+        let MiOutputRecord::Result { results, .. } = parser.parse_line(line).unwrap().unwrap() else {
+            panic!("Expected result record");
+        };
+        let threads = parse_thread_info(&results);
+        assert_eq!(threads.len(), 2);
+        assert_eq!(threads[0].target_id, "Thread 1.2 (CPU#1 [halted ])");
+        assert_eq!(threads[0].frame.as_ref().and_then(|f| f.func.as_deref()), Some("default_idle"));
+        assert_eq!(threads[1].core, Some(0));
+    }
+
+    #[test]
+    fn test_parse_source_files() {
+        let parser = MiParser::new();
+        let line = r#"^done,files=[{file="t.c",fullname="/src/t.c",debug-fully-read="true"},{file="t.c",fullname="/src/t.c",debug-fully-read="true"},{file="crt1.o"}]"#;
+        let MiOutputRecord::Result { results, .. } = parser.parse_line(line).unwrap().unwrap() else {
+            panic!("Expected result record");
+        };
+        assert_eq!(
+            parse_source_files(&results),
+            [("t.c".to_string(), Some("/src/t.c".to_string())), ("crt1.o".to_string(), None)]
+        );
+    }
+
     #[test]
     fn test_parse_stopped() {
         let parser = MiParser::new();
@@ -1100,6 +1756,147 @@ mod tests {
         }
     }
 
+    #[test]
+    fn test_parse_stack_frames_and_variables() {
+        let parser = MiParser::new();
+        let line = r#"^done,stack=[frame={level="0",addr="0x401136",func="leaf",file="a.c",line="3"},frame={level="1",addr="0x401150",func="main",file="a.c",line="9"}]"#;
+        let MiOutputRecord::Result { results, .. } = parser.parse_line(line).unwrap().unwrap() else {
+            panic!("Expected result record");
+        };
+        let frames = parse_stack_frames(&results);
+        assert_eq!(frames.len(), 2);
+        assert_eq!(frames[1].func.as_deref(), Some("main"));
+        assert_eq!(frames[1].line, Some(9));
+
+        let line = r#"^done,variables=[{name="n",arg="1",value="3"},{name="sum",value="0"}]"#;
+        let MiOutputRecord::Result { results, .. } = parser.parse_line(line).unwrap().unwrap() else {
+            panic!("Expected result record");
+        };
+        let (args, locals) = parse_stack_variables(&results);
+        assert_eq!(args.len(), 1);
+        assert_eq!(args[0].name, "n");
+        assert_eq!(locals[0].value.as_deref(), Some("0"));
+    }
+
+    #[test]
+    fn test_parse_line_tables() {
+        let parser = MiParser::new();
+        let line = r#"^done,lines=[{pc="0x401130",line="7"},{pc="0x401150",line="9"},{pc="0x401138",line="7"}]"#;
+        let MiOutputRecord::Result { results, .. } = parser.parse_line(line).unwrap().unwrap() else {
+            panic!("Expected result record");
+        };
+        let table = parse_symbol_lines(&results);
+        assert_eq!(table.len(), 2);
+        assert_eq!(table[0].line, 7);
+        assert_eq!(table[0].addresses, vec!["0x401130", "0x401138"]);
+
+        let line = r#"^done,asm_insns=[src_and_asm_line={line="7",file="a.c",line_asm_insn=[{address="0x401130",func-name="main",offset="0",inst="push   %rbp"}]},src_and_asm_line={line="9",file="a.c",line_asm_insn=[{address="0x401150",func-name="main",offset="32",inst="ret"}]}]"#;
+        let MiOutputRecord::Result { results, .. } = parser.parse_line(line).unwrap().unwrap() else {
+            panic!("Expected result record");
+        };
+        let table = parse_source_line_blocks(&results);
+        assert_eq!(table.len(), 2);
+        assert_eq!(table[1].line, 9);
+        assert_eq!(table[1].addresses, vec!["0x401150"]);
+        assert_eq!(table[1].file.as_deref(), Some("a.c"));
+    }
+
+    #[test]
+    fn test_parse_branch_target() {
+        assert_eq!(parse_branch_target("call   0x401126"), Some(0x401126));
+        assert_eq!(parse_branch_target("bne\ta5,zero,0x80000010"), Some(0x80000010));
+        assert_eq!(parse_branch_target("call   0x401126 <foo>"), None);
+        assert_eq!(parse_branch_target("bic\tr0, r1, #0x10"), None);
+        assert_eq!(parse_branch_target("mov    $0x0,%eax"), None);
+    }
+
+    #[test]
+    fn test_count_calls() {
+        let output = "1\tmain\n2\t  helper\n3\t    leaf\n4\t  helper\n5\tmain\n6\t  helper\n7\tmain\n";
+        let segments = parse_call_history(output);
+        assert_eq!(segments.len(), 7);
+        assert_eq!(segments[2], (2, "leaf".to_string()));
+        let calls = count_calls(&segments);
+        assert_eq!(calls, [
+            FunctionCalls { function: "helper".to_string(), calls: 2 },
+            FunctionCalls { function: "leaf".to_string(), calls: 1 },
+        ]);
+    }
+
+    #[test]
+    fn test_branch_kind() {
+        assert_eq!(branch_kind("jne    0x401130 <main+10>"), Some(BranchKind::Jump));
+        assert_eq!(branch_kind("call   *%rax"), Some(BranchKind::Call));
+        assert_eq!(branch_kind("bnd ret"), Some(BranchKind::Return));
+        assert_eq!(branch_kind("bx\tlr"), Some(BranchKind::Return));
+        assert_eq!(branch_kind("pop\t{r4, r7, pc}"), Some(BranchKind::Return));
+        assert_eq!(branch_kind("bne.n\t0x8000132"), Some(BranchKind::Jump));
+        assert_eq!(branch_kind("blt\tr2, r3"), Some(BranchKind::Jump));
+        assert_eq!(branch_kind("bl\t0x8000400 <SystemInit>"), Some(BranchKind::Call));
+        assert_eq!(branch_kind("jal\tra,0x80000100"), Some(BranchKind::Call));
+        assert_eq!(branch_kind("jalr\tzero,0(ra)"), Some(BranchKind::Return));
+        assert_eq!(branch_kind("bnez\ta5,0x80000010"), Some(BranchKind::Jump));
+        assert_eq!(branch_kind("bic\tr0, r1, #16"), None);
+        assert_eq!(branch_kind("mov    %rsp,%rbp"), None);
+        assert_eq!(branch_kind("pop\t{r4, r7}"), None);
+    }
+
+    #[test]
+    fn test_parse_var_changes() {
+        let parser = MiParser::new();
+        let input = r#"^done,changelist=[{name="var1",value="3",in_scope="true",type_changed="false",has_more="0"},{name="var2",in_scope="false",type_changed="false",has_more="0"}]"#;
+        let Some(MiOutputRecord::Result { results, .. }) = parser.parse_line(input).unwrap() else {
+            panic!("not a result record");
+        };
+        let changes = parse_var_changes(&results);
+        assert_eq!(changes.len(), 2);
+        assert_eq!((changes[0].name.as_str(), changes[0].value.as_deref()), ("var1", Some("3")));
+        assert_eq!(changes[1].in_scope, "false");
+        assert_eq!(changes[1].value, None);
+    }
+
+    #[test]
+    fn test_parse_struct_fields() {
+        let parser = MiParser::new();
+        let input = r#"^done,numchild="3",children=[child={name="var1.id",exp="id",numchild="0",value="7",type="int"},child={name="var1.buf",exp="buf",numchild="4",type="char [4]"},child={name="var1.public",exp="public",numchild="1"}],has_more="0""#;
+        let Some(MiOutputRecord::Result { results, .. }) = parser.parse_line(input).unwrap() else {
+            panic!("not a result record");
+        };
+        let fields = parse_struct_fields(&results);
+        assert_eq!(fields.len(), 3);
+        assert_eq!((fields[0].name.as_str(), fields[0].value.as_deref(), fields[0].varobj.as_str()), ("id", Some("7"), "var1.id"));
+        assert_eq!((fields[1].var_type.as_deref(), fields[1].children), (Some("char [4]"), 4));
+        assert_eq!((fields[2].name.as_str(), fields[2].var_type.as_deref()), ("public", None));
+    }
+
+    #[test]
+    fn test_parse_hex_bytes_and_address() {
+        assert_eq!(parse_hex_bytes("00ff10"), vec![0x00, 0xff, 0x10]);
+        assert_eq!(parse_address("0x0000000080000080"), Some(0x80000080));
+        assert_eq!(parse_address("0x401000 <main+4>"), Some(0x401000));
+        assert_eq!(parse_address("4096"), Some(4096));
+    }
+
+    #[test]
+    fn test_parse_value() {
+        assert_eq!(parse_value("-5").integer, Some(-5));
+        assert_eq!(parse_value("0x20").integer, Some(32));
+        assert_eq!(parse_value("1.5").float, Some(1.5));
+        assert_eq!(parse_value("true").integer, Some(1));
+
+        let c = parse_value("32 ' '");
+        assert_eq!((c.integer, c.text.as_deref()), (Some(32), Some(" ")));
+        let p = parse_value("(int *) 0x601040 <buf>");
+        assert_eq!((p.integer, p.symbol.as_deref()), (Some(0x601040), Some("buf")));
+        let f = parse_value("{int (void)} 0x401136 <main>");
+        assert_eq!((f.integer, f.symbol.as_deref()), (Some(0x401136), Some("main")));
+        let s = parse_value("0x402004 \"hello world\"");
+        assert_eq!(s.text.as_deref(), Some("hello world"));
+
+        assert_eq!(parse_value("RED"), ParsedValue::default());
+        assert_eq!(parse_value("{a = 1, b = 0x2}"), ParsedValue::default());
+    }
+
     #[test]
     fn test_parse_breakpoint_list() {
         let parser = MiParser::new();
@@ -1110,7 +1907,8 @@ mod tests {
         match result {
             MiOutputRecord::Result { results, .. } => {
                 let bps = parse_breakpoint_list(&results);
-                assert!(!bps.is_empty() || true, "Parsed {} breakpoints", bps.len());
+                assert_eq!(bps.len(), 1);
+                assert_eq!(bps[0].number, "1");
             }
             _ => panic!("Expected result record"),
         }
@@ -0,0 +1,190 @@
+//! Code Patches
+//!
+//! Trying a fix in the running program ("what if this branch were never
+//! taken?") by hand-encoding instructions for `gdb_memory_write` is error
+//! prone and cannot be undone. `gdb_patch_code` assembles the instruction
+//! with the GNU assembler of the target architecture (or writes NOPs),
+//! keeps the original bytes, and `gdb_patch_revert` writes them back.
+
+use crate::gdb::symbols::section_data;
+use anyhow::{anyhow, Context, Result};
+use serde::Serialize;
+use std::process::Command;
+use std::sync::atomic::{AtomicU64, Ordering};
+
+/// Instruction set a patch is assembled for
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum PatchArch {
+    #[serde(rename = "x86-64")]
+    X86_64,
+    I386,
+    AArch64,
+    Riscv32,
+    Riscv64,
+    Arm,
+    Thumb,
+}
+
+impl PatchArch {
+    /// Encoding of one NOP, in little-endian byte order
+    pub fn nop(self) -> &'static [u8] {
+        match self {
+            PatchArch::X86_64 | PatchArch::I386 => &[0x90],
+            PatchArch::AArch64 => &[0x1f, 0x20, 0x03, 0xd5],
+            PatchArch::Riscv32 | PatchArch::Riscv64 => &[0x13, 0x00, 0x00, 0x00],
+            PatchArch::Arm => &[0x00, 0xf0, 0x20, 0xe3],
+            PatchArch::Thumb => &[0x00, 0xbf],
+        }
+    }
+
+    /// Assembler used when none is configured
+    pub fn default_assembler(self) -> &'static str {
+        match self {
+            PatchArch::X86_64 | PatchArch::I386 => "as",
+            PatchArch::AArch64 => "aarch64-linux-gnu-as",
+            PatchArch::Riscv32 | PatchArch::Riscv64 => "riscv64-unknown-elf-as",
+            PatchArch::Arm | PatchArch::Thumb => "arm-none-eabi-as",
+        }
+    }
+
+    fn assembler_args(self) -> &'static [&'static str] {
+        match self {
+            PatchArch::I386 => &["--32"],
+            PatchArch::Riscv32 => &["-march=rv32gc", "-mabi=ilp32"],
+            _ => &[],
+        }
+    }
+
+    fn prelude(self, intel_syntax: bool) -> &'static str {
+        match self {
+            PatchArch::X86_64 | PatchArch::I386 if intel_syntax => ".intel_syntax noprefix\n",
+            PatchArch::Arm => ".syntax unified\n.arm\n",
+            PatchArch::Thumb => ".syntax unified\n.thumb\n",
+            _ => "",
+        }
+    }
+}
+
+/// What to write at the patched address
+#[derive(Debug, Clone)]
+pub enum PatchCode {
+    /// Assembly source, one or more instructions
+    Assemble { source: String, intel_syntax: bool },
+    Nops(usize),
+}
+
+/// One applied patch, kept to undo it
+#[derive(Debug, Clone, Serialize)]
+pub struct CodePatch {
+    pub id: u64,
+    pub address: String,
+    /// What was asked for (the instructions, or "N nop(s)")
+    pub source: String,
+    pub arch: PatchArch,
+    /// Bytes before the patch, as hex
+    pub original: String,
+    /// Bytes written, as hex (including NOP padding)
+    pub patched: String,
+    /// NOP bytes added to end on an instruction boundary
+    pub padding: usize,
+    /// Disassembly of the patched bytes
+    pub disassembly: Vec<String>,
+}
+
+impl CodePatch {
+    pub fn start(&self) -> u64 {
+        crate::gdb::parser::parse_address(&self.address).unwrap_or(0)
+    }
+
+    pub fn size(&self) -> u64 {
+        (self.patched.len() / 2) as u64
+    }
+
+    pub fn overlaps(&self, other: &CodePatch) -> bool {
+        self.start() < other.start() + other.size() && other.start() < self.start() + self.size()
+    }
+}
+
+/// Assemble `source` for `arch` and return the bytes of its `.text`
+pub fn assemble(arch: PatchArch, assembler: Option<&str>, source: &str, intel_syntax: bool) -> Result<Vec<u8>> {
+    static NEXT: AtomicU64 = AtomicU64::new(0);
+    let assembler = assembler.unwrap_or(arch.default_assembler());
+    let base = std::env::temp_dir().join(format!("mcp-gdb-patch-{}-{}", std::process::id(), NEXT.fetch_add(1, Ordering::Relaxed)));
+    let (input, object) = (base.with_extension("s"), base.with_extension("o"));
+    std::fs::write(&input, format!("{}.text\n{}\n", arch.prelude(intel_syntax), source))
+        .with_context(|| format!("Failed to write {}", input.display()))?;
+
+    let output = Command::new(assembler).args(arch.assembler_args()).arg("-o").arg(&object).arg(&input).output();
+    let _ = std::fs::remove_file(&input);
+    let output = output.map_err(|e| anyhow!(
+        "Failed to run the assembler '{}': {}. Install binutils for the target or set assembler in the server configuration.",
+        assembler, e
+    ))?;
+    let elf = std::fs::read(&object);
+    let _ = std::fs::remove_file(&object);
+    if !output.status.success() {
+        return Err(anyhow!("{} rejected the instructions: {}", assembler, String::from_utf8_lossy(&output.stderr).trim()));
+    }
+    let elf = elf.with_context(|| format!("{} produced no object file", assembler))?;
+
+    for relocations in [".rel.text", ".rela.text"] {
+        if section_data(&elf, relocations).is_some_and(|r| !r.is_empty()) {
+            return Err(anyhow!(
+                "The instructions refer to symbols or absolute addresses, which need linking. Use PC-relative offsets or registers instead."
+            ));
+        }
+    }
+    let text = section_data(&elf, ".text").unwrap_or_default();
+    if text.is_empty() {
+        return Err(anyhow!("The instructions assembled to no bytes"));
+    }
+    Ok(text.to_vec())
+}
+
+/// NOP bytes needed after a patch of `len` bytes at `start` so that it
+/// ends where an instruction begins. `boundaries` are the addresses of the
+/// instructions currently there, in order. Returns 0 when the boundary is
+/// unknown.
+pub fn padding(start: u64, len: u64, boundaries: &[u64], nop: &[u8]) -> Result<usize> {
+    let end = start + len;
+    let Some(&next) = boundaries.iter().find(|&&b| b >= end) else {
+        return Ok(0);
+    };
+    let gap = (next - end) as usize;
+    if !gap.is_multiple_of(nop.len()) {
+        return Err(anyhow!(
+            "The patch ends {} byte(s) before the next instruction at {:#x}, which NOPs of {} bytes cannot fill",
+            gap, next, nop.len()
+        ));
+    }
+    Ok(gap)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_padding() {
+        // A 2-byte `xor eax, eax` over a 5-byte call
+        assert_eq!(padding(0x1000, 2, &[0x1000, 0x1005, 0x1008], PatchArch::X86_64.nop()).unwrap(), 3);
+        assert_eq!(padding(0x1000, 5, &[0x1000, 0x1005], &[0x90]).unwrap(), 0);
+        assert_eq!(padding(0x1000, 2, &[0x1000], &[0x90]).unwrap(), 0);
+        assert!(padding(0x1000, 2, &[0x1000, 0x1005], PatchArch::Thumb.nop()).is_err());
+        assert_eq!(PatchArch::AArch64.nop().len(), 4);
+
+        let patch = |id, address: &str, patched: &str| CodePatch {
+            id,
+            address: address.to_string(),
+            source: String::new(),
+            arch: PatchArch::Thumb,
+            original: patched.to_string(),
+            patched: patched.to_string(),
+            padding: 0,
+            disassembly: Vec::new(),
+        };
+        assert!(patch(1, "0x800", "00bf00bf").overlaps(&patch(2, "0x802", "00bf")));
+        assert!(!patch(1, "0x800", "00bf").overlaps(&patch(2, "0x802", "00bf")));
+    }
+}
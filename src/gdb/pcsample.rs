@@ -0,0 +1,157 @@
+//! PC Sampling
+//!
+//! A continue that runs out of time used to come back with a single PC: the
+//! place the target happened to be when it was interrupted. With a sampling
+//! interval set, the target is interrupted briefly at that interval while
+//! waiting out `max_runtime_ms` and resumed, and the PCs seen are folded into
+//! a histogram, so a program stuck spinning in one loop is reported as such
+//! instead of as a bare timeout. Sampling is off by default: each sample
+//! stops the target, which real-time targets may not tolerate.
+
+use serde::Serialize;
+use std::collections::HashMap;
+
+/// Shortest sampling interval; shorter ones mostly measure the debugger
+pub const MIN_SAMPLE_INTERVAL_MS: u64 = 10;
+
+/// Share of the samples in one function above which the program is
+/// reported as spinning there
+const HOT_SHARE: f64 = 0.8;
+
+/// Distinct addresses a hot function may show and still count as a tight
+/// busy-wait loop
+const TIGHT_LOOP_ADDRESSES: usize = 8;
+
+/// Addresses listed in the histogram
+const TOP_ADDRESSES: usize = 10;
+
+/// Where the target was at one interrupt
+#[derive(Debug, Clone, Serialize)]
+pub struct PcSample {
+    /// Milliseconds since the run started
+    pub elapsed_ms: u64,
+    pub addr: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub func: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub thread_id: Option<String>,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct FunctionCount {
+    pub func: String,
+    pub samples: u64,
+    pub percent: f64,
+    /// Distinct PCs sampled in the function
+    pub addresses: usize,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct AddressCount {
+    pub addr: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub func: Option<String>,
+    pub samples: u64,
+}
+
+/// PC histogram of a run that did not stop by itself
+#[derive(Debug, Clone, Serialize)]
+pub struct PcHistogram {
+    pub interval_ms: u64,
+    pub samples: u64,
+    /// Functions by samples, most first
+    pub functions: Vec<FunctionCount>,
+    /// The most sampled addresses
+    pub addresses: Vec<AddressCount>,
+    /// "spinning in memcpy"-style reading of the histogram, when one
+    /// function dominates
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub verdict: Option<String>,
+}
+
+impl PcHistogram {
+    pub fn build(samples: &[PcSample], interval_ms: u64) -> Self {
+        let name = |s: &PcSample| s.func.clone().unwrap_or_else(|| "??".to_string());
+        let mut functions: Vec<FunctionCount> = Vec::new();
+        let mut addresses: Vec<AddressCount> = Vec::new();
+        let mut seen: HashMap<String, Vec<&str>> = HashMap::new();
+        for sample in samples {
+            let func = name(sample);
+            let distinct = seen.entry(func.clone()).or_default();
+            if !distinct.contains(&sample.addr.as_str()) {
+                distinct.push(&sample.addr);
+            }
+            match functions.iter_mut().find(|f| f.func == func) {
+                Some(count) => count.samples += 1,
+                None => functions.push(FunctionCount { func, samples: 1, percent: 0.0, addresses: 0 }),
+            }
+            match addresses.iter_mut().find(|a| a.addr == sample.addr) {
+                Some(count) => count.samples += 1,
+                None => addresses.push(AddressCount { addr: sample.addr.clone(), func: sample.func.clone(), samples: 1 }),
+            }
+        }
+        let total = samples.len() as u64;
+        for function in &mut functions {
+            function.percent = (function.samples as f64 * 1000.0 / total.max(1) as f64).round() / 10.0;
+            function.addresses = seen.get(&function.func).map_or(0, |a| a.len());
+        }
+        functions.sort_by_key(|f| std::cmp::Reverse(f.samples));
+        addresses.sort_by_key(|a| std::cmp::Reverse(a.samples));
+        addresses.truncate(TOP_ADDRESSES);
+
+        let verdict = functions.first()
+            .filter(|top| total >= 3 && top.samples as f64 >= total as f64 * HOT_SHARE)
+            .map(|top| {
+                let hottest = addresses.iter().find(|a| name_of(a) == top.func).map(|a| a.addr.as_str()).unwrap_or("?");
+                if top.addresses <= TIGHT_LOOP_ADDRESSES {
+                    format!("busy-waiting in {} ({} of {} samples at {} address(es), most at {})", top.func, top.samples, total, top.addresses, hottest)
+                } else {
+                    format!("spending its time in {} ({} of {} samples)", top.func, top.samples, total)
+                }
+            });
+        PcHistogram { interval_ms, samples: total, functions, addresses, verdict }
+    }
+
+    /// The functions as a bar chart, one line each
+    pub fn render(&self) -> String {
+        let width = self.functions.iter().map(|f| f.func.chars().count()).max().unwrap_or(0);
+        self.functions.iter()
+            .map(|f| {
+                let bar = "#".repeat(((f.percent / 5.0).round() as usize).max(1));
+                format!("{:<width$}  {:>5.1}%  {}", f.func, f.percent, bar, width = width)
+            })
+            .collect::<Vec<_>>()
+            .join("\n")
+    }
+}
+
+fn name_of(address: &AddressCount) -> String {
+    address.func.clone().unwrap_or_else(|| "??".to_string())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn sample(addr: &str, func: Option<&str>) -> PcSample {
+        PcSample { elapsed_ms: 0, addr: addr.to_string(), func: func.map(|f| f.to_string()), thread_id: None }
+    }
+
+    #[test]
+    fn test_histogram() {
+        let mut samples: Vec<PcSample> = (0..9).map(|i| sample(["0x401136", "0x40113a"][i % 2], Some("wait_ready"))).collect();
+        samples.push(sample("0x7ffff7e4a100", None));
+        let histogram = PcHistogram::build(&samples, 500);
+        assert_eq!(histogram.samples, 10);
+        assert_eq!(histogram.functions[0], FunctionCount { func: "wait_ready".to_string(), samples: 9, percent: 90.0, addresses: 2 });
+        assert_eq!(histogram.addresses[0].addr, "0x401136");
+        assert_eq!(
+            histogram.verdict.as_deref(),
+            Some("busy-waiting in wait_ready (9 of 10 samples at 2 address(es), most at 0x401136)")
+        );
+        assert_eq!(histogram.render(), ["wait_ready   90.0%  ##################", "??           10.0%  ##"].join("\n"));
+
+        let spread: Vec<PcSample> = (0..4).map(|i| sample(&format!("0x40{}000", i), Some(["a", "b"][i % 2]))).collect();
+        assert!(PcHistogram::build(&spread, 500).verdict.is_none());
+    }
+}
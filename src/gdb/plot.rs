@@ -0,0 +1,235 @@
+//! Numeric Buffer Plots
+//!
+//! Decodes an array of numbers read from target memory, summarizes it, and
+//! draws it as a line chart or histogram for `gdb_array_plot`. Charts carry
+//! no text; the statistics returned alongside give the scale.
+
+use anyhow::{anyhow, Result};
+use serde::Serialize;
+
+/// Names accepted by [`ElementType::parse`]
+pub const ELEMENT_TYPES: &[&str] = &["i8", "u8", "i16", "u16", "i32", "u32", "i64", "u64", "f32", "f64"];
+
+/// Element type of a numeric array
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ElementType {
+    I8,
+    U8,
+    I16,
+    U16,
+    I32,
+    U32,
+    I64,
+    U64,
+    F32,
+    F64,
+}
+
+impl ElementType {
+    pub fn parse(name: &str) -> Option<Self> {
+        Some(match name {
+            "i8" => ElementType::I8,
+            "u8" => ElementType::U8,
+            "i16" => ElementType::I16,
+            "u16" => ElementType::U16,
+            "i32" => ElementType::I32,
+            "u32" => ElementType::U32,
+            "i64" => ElementType::I64,
+            "u64" => ElementType::U64,
+            "f32" => ElementType::F32,
+            "f64" => ElementType::F64,
+            _ => return None,
+        })
+    }
+
+    pub fn size(&self) -> usize {
+        match self {
+            ElementType::I8 | ElementType::U8 => 1,
+            ElementType::I16 | ElementType::U16 => 2,
+            ElementType::I32 | ElementType::U32 | ElementType::F32 => 4,
+            ElementType::I64 | ElementType::U64 | ElementType::F64 => 8,
+        }
+    }
+
+    fn decode(&self, bytes: &[u8], big_endian: bool) -> f64 {
+        let mut word = [0u8; 8];
+        if big_endian {
+            word[8 - bytes.len()..].copy_from_slice(bytes);
+            word.reverse();
+        } else {
+            word[..bytes.len()].copy_from_slice(bytes);
+        }
+        let raw = u64::from_le_bytes(word);
+        match self {
+            ElementType::I8 => raw as i8 as f64,
+            ElementType::U8 => raw as u8 as f64,
+            ElementType::I16 => raw as i16 as f64,
+            ElementType::U16 => raw as u16 as f64,
+            ElementType::I32 => raw as i32 as f64,
+            ElementType::U32 => raw as u32 as f64,
+            ElementType::I64 => raw as i64 as f64,
+            ElementType::U64 => raw as f64,
+            ElementType::F32 => f32::from_bits(raw as u32) as f64,
+            ElementType::F64 => f64::from_bits(raw),
+        }
+    }
+}
+
+/// Decode consecutive elements of `element`
+pub fn decode_samples(bytes: &[u8], element: ElementType, big_endian: bool) -> Vec<f64> {
+    bytes.chunks_exact(element.size()).map(|chunk| element.decode(chunk, big_endian)).collect()
+}
+
+/// Summary statistics of the finite samples
+#[derive(Debug, Clone, Serialize)]
+pub struct SampleStats {
+    pub count: usize,
+    pub min: f64,
+    pub min_index: usize,
+    pub max: f64,
+    pub max_index: usize,
+    pub mean: f64,
+    pub std_dev: f64,
+    pub rms: f64,
+    /// NaN and infinite samples, which are left out of the other figures
+    pub non_finite: usize,
+}
+
+pub fn sample_stats(samples: &[f64]) -> Result<SampleStats> {
+    let finite: Vec<(usize, f64)> = samples.iter().copied().enumerate().filter(|(_, v)| v.is_finite()).collect();
+    if finite.is_empty() {
+        return Err(anyhow!("No finite samples to summarize"));
+    }
+    let (min_index, min) = finite.iter().copied().fold(finite[0], |a, b| if b.1 < a.1 { b } else { a });
+    let (max_index, max) = finite.iter().copied().fold(finite[0], |a, b| if b.1 > a.1 { b } else { a });
+    let n = finite.len() as f64;
+    let mean = finite.iter().map(|(_, v)| v).sum::<f64>() / n;
+    let variance = finite.iter().map(|(_, v)| (v - mean).powi(2)).sum::<f64>() / n;
+    let rms = (finite.iter().map(|(_, v)| v * v).sum::<f64>() / n).sqrt();
+    Ok(SampleStats {
+        count: samples.len(),
+        min,
+        min_index,
+        max,
+        max_index,
+        mean,
+        std_dev: variance.sqrt(),
+        rms,
+        non_finite: samples.len() - finite.len(),
+    })
+}
+
+const BACKGROUND: [u8; 3] = [255, 255, 255];
+const AXIS: [u8; 3] = [190, 190, 190];
+const INK: [u8; 3] = [31, 119, 180];
+
+/// An RGB canvas
+struct Canvas {
+    width: usize,
+    height: usize,
+    rgb: Vec<u8>,
+}
+
+impl Canvas {
+    fn new(width: usize, height: usize) -> Self {
+        Canvas { width, height, rgb: BACKGROUND.repeat(width * height) }
+    }
+
+    /// Fill column `x` from row `y0` to `y1` inclusive
+    fn vline(&mut self, x: usize, y0: usize, y1: usize, color: [u8; 3]) {
+        for y in y0.min(y1)..=y0.max(y1).min(self.height - 1) {
+            let i = (y * self.width + x) * 3;
+            self.rgb[i..i + 3].copy_from_slice(&color);
+        }
+    }
+
+    fn hline(&mut self, y: usize, color: [u8; 3]) {
+        for x in 0..self.width {
+            self.vline(x, y, y, color);
+        }
+    }
+}
+
+/// Row of `value` on a canvas spanning `min` (bottom) to `max` (top)
+fn row_of(value: f64, min: f64, max: f64, height: usize) -> usize {
+    let t = if max > min { (value - min) / (max - min) } else { 0.5 };
+    ((1.0 - t.clamp(0.0, 1.0)) * (height - 1) as f64).round() as usize
+}
+
+/// Draw the samples left to right. Each column spans the samples that fall
+/// in it and joins the previous column, so spikes survive downsampling.
+pub fn render_line(samples: &[f64], stats: &SampleStats, width: usize, height: usize) -> Vec<u8> {
+    let mut canvas = Canvas::new(width, height);
+    if stats.min < 0.0 && stats.max > 0.0 {
+        canvas.hline(row_of(0.0, stats.min, stats.max, height), AXIS);
+    }
+
+    let mut previous: Option<usize> = None;
+    for x in 0..width {
+        let start = x * samples.len() / width;
+        let end = ((x + 1) * samples.len() / width).max(start + 1);
+        let rows: Vec<usize> = samples[start..end]
+            .iter()
+            .filter(|v| v.is_finite())
+            .map(|&v| row_of(v, stats.min, stats.max, height))
+            .collect();
+        let (Some(&lo), Some(&hi)) = (rows.iter().min(), rows.iter().max()) else {
+            previous = None;
+            continue;
+        };
+        let (lo, hi) = match previous {
+            Some(p) => (lo.min(p), hi.max(p)),
+            None => (lo, hi),
+        };
+        canvas.vline(x, lo, hi, INK);
+        previous = rows.last().copied();
+    }
+    canvas.rgb
+}
+
+/// Draw a histogram of the finite samples in `bins` equal-width bins
+pub fn render_histogram(samples: &[f64], stats: &SampleStats, bins: usize, width: usize, height: usize) -> (Vec<u8>, Vec<u64>) {
+    let mut counts = vec![0u64; bins];
+    let span = stats.max - stats.min;
+    for &v in samples.iter().filter(|v| v.is_finite()) {
+        let bin = if span > 0.0 { (((v - stats.min) / span) * bins as f64) as usize } else { bins / 2 };
+        counts[bin.min(bins - 1)] += 1;
+    }
+
+    let mut canvas = Canvas::new(width, height);
+    let tallest = counts.iter().copied().max().unwrap_or(0).max(1) as f64;
+    for x in 0..width {
+        let bin = x * bins / width;
+        // Leave a gap after each bar when bars are wide enough
+        let last_column = (x + 1) * bins / width != bin;
+        let count = counts[bin];
+        if count > 0 && !(last_column && width >= 3 * bins) {
+            let top = row_of(count as f64, 0.0, tallest, height);
+            canvas.vline(x, top, height - 1, INK);
+        }
+    }
+    (canvas.rgb, counts)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_samples_and_stats() {
+        let bytes = [0xff, 0xff, 0x02, 0x00, 0x00, 0x80, 0x05, 0x00];
+        let samples = decode_samples(&bytes, ElementType::I16, false);
+        assert_eq!(samples, [-1.0, 2.0, -32768.0, 5.0]);
+        assert_eq!(decode_samples(&[0x00, 0x02], ElementType::U16, true), [2.0]);
+
+        let stats = sample_stats(&[1.0, f64::NAN, 3.0, -1.0]).unwrap();
+        assert_eq!((stats.min, stats.min_index, stats.max, stats.max_index), (-1.0, 3, 3.0, 2));
+        assert_eq!(stats.mean, 1.0);
+        assert_eq!(stats.non_finite, 1);
+
+        let (rgb, counts) = render_histogram(&[0.0, 0.1, 1.0], &sample_stats(&[0.0, 0.1, 1.0]).unwrap(), 2, 4, 3);
+        assert_eq!(counts, [2, 1]);
+        assert_eq!(rgb.len(), 4 * 3 * 3);
+        assert_eq!(render_line(&[0.0, 1.0], &sample_stats(&[0.0, 1.0]).unwrap(), 4, 3).len(), 4 * 3 * 3);
+    }
+}
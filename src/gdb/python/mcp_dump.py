@@ -0,0 +1,65 @@
+# Bundled with mcp-gdb-server: dump a value as JSON.
+#
+#   mcp-dump EXPR [DEPTH]
+#
+# Structs become objects, arrays and pointers to structs are followed up to
+# DEPTH levels (default 3) and everything else is printed as GDB formats it.
+
+import json
+
+import gdb
+
+
+def _dump(value, depth):
+    value_type = value.type.strip_typedefs()
+    code = value_type.code
+    if code in (gdb.TYPE_CODE_STRUCT, gdb.TYPE_CODE_UNION):
+        if depth <= 0:
+            return "{...}"
+        result = {}
+        for field in value_type.fields():
+            if field.name is None or field.artificial:
+                continue
+            try:
+                result[field.name] = _dump(value[field.name], depth - 1)
+            except gdb.error as error:
+                result[field.name] = "<error: %s>" % error
+        return result
+    if code == gdb.TYPE_CODE_ARRAY:
+        if depth <= 0:
+            return "[...]"
+        low, high = value_type.range()
+        return [_dump(value[i], depth - 1) for i in range(low, min(high, low + 255) + 1)]
+    if code == gdb.TYPE_CODE_PTR:
+        target = value_type.target().strip_typedefs()
+        if int(value) != 0 and depth > 0 and target.code == gdb.TYPE_CODE_STRUCT:
+            try:
+                return {"address": hex(int(value)), "target": _dump(value.dereference(), depth - 1)}
+            except gdb.MemoryError:
+                pass
+        return hex(int(value))
+    if code in (gdb.TYPE_CODE_INT, gdb.TYPE_CODE_CHAR, gdb.TYPE_CODE_BOOL, gdb.TYPE_CODE_ENUM):
+        try:
+            return int(value) if code != gdb.TYPE_CODE_ENUM else str(value)
+        except gdb.error:
+            return str(value)
+    if code == gdb.TYPE_CODE_FLT:
+        return float(value)
+    return str(value)
+
+
+class McpDump(gdb.Command):
+    """Dump a value as JSON: mcp-dump EXPR [DEPTH]"""
+
+    def __init__(self):
+        super().__init__("mcp-dump", gdb.COMMAND_DATA)
+
+    def invoke(self, argument, from_tty):
+        args = gdb.string_to_argv(argument)
+        if not args:
+            raise gdb.GdbError("usage: mcp-dump EXPR [DEPTH]")
+        depth = int(args[1]) if len(args) > 1 else 3
+        print(json.dumps(_dump(gdb.parse_and_eval(args[0]), depth)))
+
+
+McpDump()
@@ -0,0 +1,80 @@
+# Bundled with mcp-gdb-server: list FreeRTOS tasks.
+#
+#   mcp-freertos-tasks
+#
+# Walks the kernel's ready, delayed, pending, suspended and deleted lists
+# and prints the tasks as JSON. Needs the kernel's debug symbols.
+
+import json
+
+import gdb
+
+LISTS = [
+    ("delayed", "pxDelayedTaskList"),
+    ("delayed", "pxOverflowDelayedTaskList"),
+    ("pending_ready", "xPendingReadyList"),
+    ("suspended", "xSuspendedTaskList"),
+    ("deleted", "xTasksWaitingTermination"),
+]
+
+
+def _lookup(name):
+    try:
+        return gdb.parse_and_eval(name)
+    except gdb.error:
+        return None
+
+
+def _walk(task_list):
+    """Yield the TCB pointers of a List_t"""
+    count = int(task_list["uxNumberOfItems"])
+    end = task_list["xListEnd"].address
+    item = task_list["xListEnd"]["pxNext"]
+    for _ in range(count):
+        if item == end:
+            break
+        yield item.dereference()["pvOwner"].cast(gdb.lookup_type("TCB_t").pointer())
+        item = item.dereference()["pxNext"]
+
+
+class McpFreeRtosTasks(gdb.Command):
+    """List FreeRTOS tasks as JSON: mcp-freertos-tasks"""
+
+    def __init__(self):
+        super().__init__("mcp-freertos-tasks", gdb.COMMAND_DATA)
+
+    def invoke(self, argument, from_tty):
+        ready = _lookup("pxReadyTasksLists")
+        if ready is None:
+            raise gdb.GdbError("FreeRTOS symbols unavailable (pxReadyTasksLists)")
+        current = _lookup("pxCurrentTCB")
+        current = int(current) if current is not None else 0
+
+        lists = []
+        low, high = ready.type.strip_typedefs().range()
+        for priority in range(low, high + 1):
+            lists.append(("ready", ready[priority]))
+        for state, name in LISTS:
+            value = _lookup(name)
+            if value is None:
+                continue
+            if value.type.strip_typedefs().code == gdb.TYPE_CODE_PTR:
+                value = value.dereference()
+            lists.append((state, value))
+
+        tasks = []
+        for state, task_list in lists:
+            for tcb in _walk(task_list):
+                task = tcb.dereference()
+                tasks.append({
+                    "tcb": hex(int(tcb)),
+                    "name": task["pcTaskName"].string(errors="replace"),
+                    "priority": int(task["uxPriority"]),
+                    "state": "running" if int(tcb) == current else state,
+                    "top_of_stack": hex(int(task["pxTopOfStack"])),
+                })
+
+        print(json.dumps({"tasks": tasks}))
+
+
+McpFreeRtosTasks()
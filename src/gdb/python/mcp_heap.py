@@ -0,0 +1,58 @@
+# Bundled with mcp-gdb-server: walk the glibc main arena.
+#
+#   mcp-heap [MAX_CHUNKS]
+#
+# Needs glibc debug symbols (main_arena, mp_). Prints a JSON object with the
+# chunks from the start of the heap up to the top chunk.
+
+import json
+
+import gdb
+
+PREV_INUSE = 0x1
+SIZE_BITS = 0x7
+
+
+def _read_word(size_t, address):
+    return int(gdb.Value(address).cast(size_t.pointer()).dereference())
+
+
+class McpHeap(gdb.Command):
+    """Walk the glibc main arena as JSON: mcp-heap [MAX_CHUNKS]"""
+
+    def __init__(self):
+        super().__init__("mcp-heap", gdb.COMMAND_DATA)
+
+    def invoke(self, argument, from_tty):
+        args = gdb.string_to_argv(argument)
+        limit = int(args[0]) if args else 1000
+        try:
+            start = int(gdb.parse_and_eval("mp_.sbrk_base"))
+            top = int(gdb.parse_and_eval("main_arena.top"))
+        except gdb.error as error:
+            raise gdb.GdbError("glibc heap symbols unavailable (%s)" % error)
+
+        size_t = gdb.lookup_type("unsigned long")
+        word = size_t.sizeof
+        chunks = []
+        address = start
+        truncated = False
+        while address < top:
+            if len(chunks) >= limit:
+                truncated = True
+                break
+            size = _read_word(size_t, address + word)
+            chunk_size = size & ~SIZE_BITS
+            if chunk_size == 0:
+                break
+            following = address + chunk_size
+            in_use = True
+            if following < top:
+                in_use = bool(_read_word(size_t, following + word) & PREV_INUSE)
+            chunks.append({"address": hex(address), "user": hex(address + 2 * word), "size": chunk_size, "in_use": in_use})
+            address = following
+
+        print(json.dumps({"heap_start": hex(start), "top": hex(top), "chunks": chunks, "truncated": truncated}))
+
+
+McpHeap()
@@ -0,0 +1,188 @@
+//! QEMU Monitor
+//!
+//! QEMU's monitor sees what the gdbstub does not: the other CPUs' registers,
+//! the guest's memory map, and VM snapshots. Snapshots give kernel debugging
+//! cheap time travel: `savevm` before the crash, `loadvm` to try again.
+//! Commands go through GDB's `monitor` command (the gdbstub passes them to
+//! the human monitor) or, when QEMU was started with `-qmp`, over QMP. After
+//! `loadvm` GDB's register cache is stale, so the client flushes it and
+//! re-reads the frame.
+
+use crate::gdb::types::Frame;
+use anyhow::{anyhow, Context, Result};
+use regex::Regex;
+use serde::Serialize;
+use std::io::{BufRead, BufReader, Read, Write};
+use std::net::TcpStream;
+#[cfg(unix)]
+use std::os::unix::net::UnixStream;
+use std::sync::OnceLock;
+use std::time::Duration;
+
+/// How long to wait for a QMP reply; `savevm` of a large guest takes a while
+const QMP_TIMEOUT: Duration = Duration::from_secs(120);
+
+/// Monitor commands that only read state, run without confirmation
+const READ_ONLY_COMMANDS: &[&str] = &["info", "x", "xp", "help", "?", "print", "p"];
+
+/// Whether a human monitor command leaves the VM as it is
+pub fn is_read_only(command: &str) -> bool {
+    let name = command.split_whitespace().next().unwrap_or_default();
+    READ_ONLY_COMMANDS.contains(&name)
+}
+
+/// A VM snapshot listed by `info snapshots`
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct VmSnapshot {
+    pub id: String,
+    pub tag: String,
+    pub vm_size: String,
+    pub date: String,
+    pub vm_clock: String,
+}
+
+/// Where the debugger was when a snapshot was saved in this session
+#[derive(Debug, Clone, Serialize)]
+pub struct SnapshotMark {
+    pub name: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub frame: Option<Frame>,
+    /// Breakpoints set at the time, by number
+    pub breakpoints: Vec<String>,
+    /// Milliseconds since the Unix epoch
+    pub saved_ms: u64,
+}
+
+/// Parse the table printed by `info snapshots`
+pub fn parse_snapshots(output: &str) -> Vec<VmSnapshot> {
+    static DATE: OnceLock<Regex> = OnceLock::new();
+    let date = DATE.get_or_init(|| Regex::new(r"^\d{4}-\d{2}-\d{2}$").unwrap());
+    output.lines()
+        .filter_map(|line| {
+            let words: Vec<&str> = line.split_whitespace().collect();
+            let at = words.iter().position(|w| date.is_match(w))?;
+            if at < 2 || words.len() < at + 3 {
+                return None;
+            }
+            Some(VmSnapshot {
+                id: words[0].to_string(),
+                tag: words[1].to_string(),
+                vm_size: words[2..at].join(" "),
+                date: format!("{} {}", words[at], words[at + 1]),
+                vm_clock: words[at + 2].to_string(),
+            })
+        })
+        .collect()
+}
+
+/// Human monitor output that reports a failure (`savevm` and `loadvm`
+/// print errors instead of failing the command)
+pub fn monitor_error(output: &str) -> Option<String> {
+    output.lines()
+        .map(str::trim)
+        .find(|l| l.starts_with("Error:") || l.starts_with("Error ") || l.starts_with("unknown command:"))
+        .map(|l| l.to_string())
+}
+
+type Reader = Box<dyn Read + Send>;
+type Writer = Box<dyn Write + Send>;
+
+/// A QMP connection, ready for commands
+pub struct Qmp {
+    reader: BufReader<Reader>,
+    writer: Writer,
+}
+
+impl Qmp {
+    /// Connect to `unix:/path`, a socket path, or `host:port`, and leave
+    /// capabilities negotiation mode
+    pub fn connect(address: &str) -> Result<Self> {
+        let unix_path = address.strip_prefix("unix:").or(address.starts_with('/').then_some(address));
+        let (reader, writer): (Reader, Writer) = match unix_path {
+            #[cfg(unix)]
+            Some(path) => {
+                let stream = UnixStream::connect(path).with_context(|| format!("Failed to connect to QMP socket {}", path))?;
+                stream.set_read_timeout(Some(QMP_TIMEOUT))?;
+                (Box::new(stream.try_clone()?), Box::new(stream))
+            }
+            _ => {
+                let address = address.trim_start_matches("tcp:");
+                let stream = TcpStream::connect(address).with_context(|| format!("Failed to connect to QMP at {}", address))?;
+                stream.set_read_timeout(Some(QMP_TIMEOUT))?;
+                (Box::new(stream.try_clone()?), Box::new(stream))
+            }
+        };
+        let mut qmp = Qmp { reader: BufReader::new(reader), writer };
+        let greeting = qmp.read_message()?;
+        if greeting.get("QMP").is_none() {
+            return Err(anyhow!("{} is not a QMP socket", address));
+        }
+        qmp.execute("qmp_capabilities", None)?;
+        Ok(qmp)
+    }
+
+    fn read_message(&mut self) -> Result<serde_json::Value> {
+        let mut line = String::new();
+        if self.reader.read_line(&mut line)? == 0 {
+            return Err(anyhow!("QMP connection closed"));
+        }
+        serde_json::from_str(&line).with_context(|| format!("Invalid QMP message: {}", line.trim()))
+    }
+
+    /// Run a QMP command and return its `return` value; events that
+    /// arrive in between are skipped
+    pub fn execute(&mut self, command: &str, arguments: Option<serde_json::Value>) -> Result<serde_json::Value> {
+        let mut request = serde_json::json!({ "execute": command });
+        if let Some(arguments) = arguments {
+            request["arguments"] = arguments;
+        }
+        writeln!(self.writer, "{}", request)?;
+        self.writer.flush()?;
+        loop {
+            let message = self.read_message()?;
+            if let Some(value) = message.get("return") {
+                return Ok(value.clone());
+            }
+            if let Some(error) = message.get("error") {
+                let description = error.get("desc").and_then(|d| d.as_str()).unwrap_or("unknown error");
+                return Err(anyhow!("QMP {} failed: {}", command, description));
+            }
+        }
+    }
+
+    /// Run a human monitor command over QMP
+    pub fn human_command(&mut self, command: &str) -> Result<String> {
+        let output = self.execute("human-monitor-command", Some(serde_json::json!({ "command-line": command })))?;
+        Ok(output.as_str().unwrap_or_default().to_string())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_snapshots() {
+        let output = "\
+List of snapshots present on all disks:
+ID        TAG               VM SIZE                DATE     VM CLOCK     ICOUNT
+--        boot           17.1 MiB 2026-10-16 09:12:44 00:00:03.512
+--        before-crash   18.4 MiB 2026-10-16 09:20:01 00:01:40.007
+";
+        let snapshots = parse_snapshots(output);
+        assert_eq!(snapshots.len(), 2);
+        assert_eq!(snapshots[1].tag, "before-crash");
+        assert_eq!(snapshots[1].vm_size, "18.4 MiB");
+        assert_eq!(snapshots[1].date, "2026-10-16 09:20:01");
+        assert_eq!(snapshots[1].vm_clock, "00:01:40.007");
+
+        // QEMU before 6.0
+        let old = "ID        TAG                 VM SIZE                DATE       VM CLOCK\n1         boot                   9.8M 2020-03-01 10:00:00   00:00:05.123\n";
+        assert_eq!(parse_snapshots(old)[0].vm_size, "9.8M");
+        assert!(parse_snapshots("There is no snapshot available.").is_empty());
+
+        assert_eq!(monitor_error("Error: No block device can accept snapshots\n").as_deref(), Some("Error: No block device can accept snapshots"));
+        assert!(monitor_error("").is_none());
+        assert!(is_read_only("info cpus") && !is_read_only("loadvm boot"));
+    }
+}
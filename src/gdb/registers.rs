@@ -0,0 +1,516 @@
+//! Register Profiles
+//!
+//! Architecture-specific sets of registers that explain what a target is
+//! doing, such as the Cortex-M fault status registers after a HardFault.
+//! Each register is read through GDB (a register or a memory-mapped word)
+//! and decoded into named bitfields.
+
+use serde::Serialize;
+
+/// Where a register's value comes from
+#[derive(Debug, Clone, Copy)]
+pub enum RegisterSource {
+    /// A GDB register, by name without `$`
+    Register(&'static str),
+    /// A memory-mapped 32-bit register
+    Memory(u64),
+}
+
+impl RegisterSource {
+    /// Expression evaluating to the raw value
+    pub fn expression(&self) -> String {
+        match self {
+            RegisterSource::Register(name) => format!("(unsigned long long)${}", name),
+            RegisterSource::Memory(addr) => format!("*(unsigned int *)0x{:x}", addr),
+        }
+    }
+}
+
+/// A bitfield of a register
+#[derive(Debug, Clone, Copy)]
+pub struct FieldSpec {
+    pub name: &'static str,
+    pub lsb: u32,
+    pub width: u32,
+    /// Names of known field values
+    pub meanings: &'static [(u64, &'static str)],
+}
+
+const fn flag(name: &'static str, bit: u32) -> FieldSpec {
+    FieldSpec { name, lsb: bit, width: 1, meanings: &[] }
+}
+
+const fn bits(name: &'static str, lsb: u32, width: u32, meanings: &'static [(u64, &'static str)]) -> FieldSpec {
+    FieldSpec { name, lsb, width, meanings }
+}
+
+/// How a register's value is split into fields
+#[derive(Debug, Clone, Copy)]
+pub enum Layout {
+    /// Plain value without fields
+    Value,
+    Fields(&'static [FieldSpec]),
+    /// Layout that depends on the register width, given in bits
+    Custom(fn(u64, u32) -> Vec<DecodedField>),
+}
+
+/// A register in a profile
+#[derive(Debug, Clone, Copy)]
+pub struct RegisterSpec {
+    pub name: &'static str,
+    pub source: RegisterSource,
+    pub layout: Layout,
+}
+
+const fn reg(name: &'static str) -> RegisterSpec {
+    RegisterSpec { name, source: RegisterSource::Register(name), layout: Layout::Value }
+}
+
+/// A named set of registers
+#[derive(Debug, Clone, Copy)]
+pub struct RegisterProfile {
+    pub name: &'static str,
+    pub description: &'static str,
+    pub registers: &'static [RegisterSpec],
+}
+
+/// A decoded field value
+#[derive(Debug, Clone, Serialize)]
+pub struct DecodedField {
+    pub name: &'static str,
+    pub value: u64,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub meaning: Option<&'static str>,
+}
+
+impl DecodedField {
+    pub fn new(name: &'static str, value: u64, meaning: Option<&'static str>) -> Self {
+        Self { name, value, meaning }
+    }
+}
+
+/// A register read and decoded from a profile
+#[derive(Debug, Clone, Serialize)]
+pub struct DecodedRegister {
+    pub name: &'static str,
+    /// Value in hex, or None if it could not be read
+    pub value: Option<String>,
+    /// Fields that are set, and multi-bit fields
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub fields: Vec<DecodedField>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub error: Option<String>,
+}
+
+impl RegisterSpec {
+    /// Decode a value read from a register `bits` wide
+    pub fn decode(&self, value: u64, bits: u32) -> DecodedRegister {
+        let fields = match self.layout {
+            Layout::Value => Vec::new(),
+            Layout::Fields(specs) => specs
+                .iter()
+                .filter_map(|f| {
+                    let v = field(value, f.lsb, f.width);
+                    let meaning = f.meanings.iter().find(|(m, _)| *m == v).map(|(_, name)| *name);
+                    (v != 0 || f.width > 1).then(|| DecodedField::new(f.name, v, meaning))
+                })
+                .collect(),
+            Layout::Custom(decode) => decode(value, bits),
+        };
+        DecodedRegister {
+            name: self.name,
+            value: Some(format!("0x{:0width$x}", value, width = (bits as usize).div_ceil(4))),
+            fields,
+            error: None,
+        }
+    }
+
+    /// Report a register that could not be read
+    pub fn unavailable(&self, error: String) -> DecodedRegister {
+        DecodedRegister { name: self.name, value: None, fields: Vec::new(), error: Some(error) }
+    }
+}
+
+/// Extract `width` bits starting at `lsb`
+pub fn field(value: u64, lsb: u32, width: u32) -> u64 {
+    if lsb >= 64 {
+        return 0;
+    }
+    let shifted = value >> lsb;
+    if width >= 64 { shifted } else { shifted & ((1u64 << width) - 1) }
+}
+
+/// Mask a value to the low `bits` bits
+pub fn truncate(value: u64, bits: u32) -> u64 {
+    field(value, 0, bits)
+}
+
+/// Cortex-M exception numbers
+pub const EXCEPTION_NUMBERS: &[(u64, &str)] = &[
+    (0, "Thread mode"),
+    (1, "Reset"),
+    (2, "NMI"),
+    (3, "HardFault"),
+    (4, "MemManage"),
+    (5, "BusFault"),
+    (6, "UsageFault"),
+    (7, "SecureFault"),
+    (11, "SVCall"),
+    (12, "DebugMonitor"),
+    (14, "PendSV"),
+    (15, "SysTick"),
+];
+
+const CORTEX_M_FAULT: RegisterProfile = RegisterProfile {
+    name: "cortex-m-fault",
+    description: "ARM Cortex-M fault analysis: xPSR, SCB fault status and address registers, and the exception return context",
+    registers: &[
+        RegisterSpec {
+            name: "xpsr",
+            source: RegisterSource::Register("xpsr"),
+            layout: Layout::Fields(&[
+                bits("ISR_NUMBER", 0, 9, EXCEPTION_NUMBERS),
+                flag("T", 24),
+                flag("Q", 27),
+                flag("V", 28),
+                flag("C", 29),
+                flag("Z", 30),
+                flag("N", 31),
+            ]),
+        },
+        RegisterSpec {
+            name: "cfsr",
+            source: RegisterSource::Memory(0xE000_ED28),
+            layout: Layout::Fields(&[
+                flag("IACCVIOL", 0),
+                flag("DACCVIOL", 1),
+                flag("MUNSTKERR", 3),
+                flag("MSTKERR", 4),
+                flag("MLSPERR", 5),
+                flag("MMARVALID", 7),
+                flag("IBUSERR", 8),
+                flag("PRECISERR", 9),
+                flag("IMPRECISERR", 10),
+                flag("UNSTKERR", 11),
+                flag("STKERR", 12),
+                flag("LSPERR", 13),
+                flag("BFARVALID", 15),
+                flag("UNDEFINSTR", 16),
+                flag("INVSTATE", 17),
+                flag("INVPC", 18),
+                flag("NOCP", 19),
+                flag("STKOF", 20),
+                flag("UNALIGNED", 24),
+                flag("DIVBYZERO", 25),
+            ]),
+        },
+        RegisterSpec {
+            name: "hfsr",
+            source: RegisterSource::Memory(0xE000_ED2C),
+            layout: Layout::Fields(&[flag("VECTTBL", 1), flag("FORCED", 30), flag("DEBUGEVT", 31)]),
+        },
+        RegisterSpec { name: "mmfar", source: RegisterSource::Memory(0xE000_ED34), layout: Layout::Value },
+        RegisterSpec { name: "bfar", source: RegisterSource::Memory(0xE000_ED38), layout: Layout::Value },
+        reg("sp"),
+        reg("lr"),
+        reg("pc"),
+    ],
+};
+
+const MCAUSE_INTERRUPTS: &[(u64, &str)] = &[
+    (1, "Supervisor software interrupt"),
+    (3, "Machine software interrupt"),
+    (5, "Supervisor timer interrupt"),
+    (7, "Machine timer interrupt"),
+    (9, "Supervisor external interrupt"),
+    (11, "Machine external interrupt"),
+];
+
+const MCAUSE_EXCEPTIONS: &[(u64, &str)] = &[
+    (0, "Instruction address misaligned"),
+    (1, "Instruction access fault"),
+    (2, "Illegal instruction"),
+    (3, "Breakpoint"),
+    (4, "Load address misaligned"),
+    (5, "Load access fault"),
+    (6, "Store/AMO address misaligned"),
+    (7, "Store/AMO access fault"),
+    (8, "Environment call from U-mode"),
+    (9, "Environment call from S-mode"),
+    (11, "Environment call from M-mode"),
+    (12, "Instruction page fault"),
+    (13, "Load page fault"),
+    (15, "Store/AMO page fault"),
+];
+
+const PRIVILEGE_MODES: &[(u64, &str)] = &[(0, "U"), (1, "S"), (3, "M")];
+
+const EXTENSION_STATES: &[(u64, &str)] = &[(0, "Off"), (1, "Initial"), (2, "Clean"), (3, "Dirty")];
+
+/// RISC-V `mstatus` fields
+pub const MSTATUS: Layout = Layout::Fields(&[
+    flag("SIE", 1),
+    flag("MIE", 3),
+    flag("SPIE", 5),
+    flag("MPIE", 7),
+    bits("SPP", 8, 1, &[(0, "U"), (1, "S")]),
+    bits("MPP", 11, 2, PRIVILEGE_MODES),
+    bits("FS", 13, 2, EXTENSION_STATES),
+    bits("XS", 15, 2, EXTENSION_STATES),
+    flag("MPRV", 17),
+    flag("SUM", 18),
+    flag("MXR", 19),
+    flag("TVM", 20),
+    flag("TW", 21),
+    flag("TSR", 22),
+]);
+
+/// RISC-V `mcause`: the interrupt bit is the top bit, so the layout
+/// depends on XLEN
+pub const MCAUSE: Layout = Layout::Custom(|value, bits| {
+    let interrupt = field(value, bits - 1, 1);
+    let code = field(value, 0, bits - 1);
+    let meanings = if interrupt == 1 { MCAUSE_INTERRUPTS } else { MCAUSE_EXCEPTIONS };
+    vec![
+        DecodedField::new("Interrupt", interrupt, None),
+        DecodedField::new("Code", code, meanings.iter().find(|(c, _)| *c == code).map(|(_, m)| *m)),
+    ]
+});
+
+/// RISC-V `mtvec`: trap vector base and mode
+pub const MTVEC: Layout = Layout::Custom(|value, _| {
+    let mode = field(value, 0, 2);
+    let meaning = match mode {
+        0 => Some("Direct"),
+        1 => Some("Vectored"),
+        _ => None,
+    };
+    vec![DecodedField::new("MODE", mode, meaning), DecodedField::new("BASE", value & !3, None)]
+});
+
+/// RISC-V `satp`: the field layout differs between RV32 and RV64
+pub const SATP: Layout = Layout::Custom(|value, bits| {
+    let (mode, asid, ppn) = if bits == 32 {
+        (field(value, 31, 1), field(value, 22, 9), field(value, 0, 22))
+    } else {
+        (field(value, 60, 4), field(value, 44, 16), field(value, 0, 44))
+    };
+    let meaning = match (bits, mode) {
+        (_, 0) => Some("Bare"),
+        (32, 1) => Some("Sv32"),
+        (64, 8) => Some("Sv39"),
+        (64, 9) => Some("Sv48"),
+        (64, 10) => Some("Sv57"),
+        _ => None,
+    };
+    vec![
+        DecodedField::new("MODE", mode, meaning),
+        DecodedField::new("ASID", asid, None),
+        DecodedField::new("PPN", ppn, None),
+    ]
+});
+
+/// RISC-V `mie`/`mip` interrupt bits
+const INTERRUPT_BITS: Layout = Layout::Fields(&[
+    flag("SSI", 1),
+    flag("MSI", 3),
+    flag("STI", 5),
+    flag("MTI", 7),
+    flag("SEI", 9),
+    flag("MEI", 11),
+]);
+
+const fn csr(name: &'static str, layout: Layout) -> RegisterSpec {
+    RegisterSpec { name, source: RegisterSource::Register(name), layout }
+}
+
+/// RISC-V machine-mode CSRs decoded by `gdb_riscv_csrs`
+pub const RISCV_CSRS: &[RegisterSpec] = &[
+    csr("mstatus", MSTATUS),
+    csr("mcause", MCAUSE),
+    csr("mtvec", MTVEC),
+    csr("mepc", Layout::Value),
+    csr("mtval", Layout::Value),
+    csr("mie", INTERRUPT_BITS),
+    csr("mip", INTERRUPT_BITS),
+    csr("satp", SATP),
+];
+
+/// CSRs read when no selection is given
+pub const DEFAULT_RISCV_CSRS: &[&str] = &["mstatus", "mcause", "mtvec", "mepc", "satp"];
+
+const RISCV_MACHINE: RegisterProfile = RegisterProfile {
+    name: "riscv-machine",
+    description: "RISC-V machine-mode trap state: mstatus, mcause, mepc, mtval, mtvec, and the interrupted pc/sp/ra",
+    registers: &[
+        csr("mstatus", MSTATUS),
+        csr("mcause", MCAUSE),
+        reg("mepc"),
+        reg("mtval"),
+        csr("mtvec", MTVEC),
+        reg("pc"),
+        reg("sp"),
+        reg("ra"),
+    ],
+};
+
+const X86_64_GENERAL: RegisterProfile = RegisterProfile {
+    name: "x86-64-general",
+    description: "x86-64 general purpose registers, rip, and decoded eflags",
+    registers: &[
+        reg("rax"),
+        reg("rbx"),
+        reg("rcx"),
+        reg("rdx"),
+        reg("rsi"),
+        reg("rdi"),
+        reg("rbp"),
+        reg("rsp"),
+        reg("r8"),
+        reg("r9"),
+        reg("r10"),
+        reg("r11"),
+        reg("r12"),
+        reg("r13"),
+        reg("r14"),
+        reg("r15"),
+        reg("rip"),
+        RegisterSpec {
+            name: "eflags",
+            source: RegisterSource::Register("eflags"),
+            layout: Layout::Fields(&[
+                flag("CF", 0),
+                flag("PF", 2),
+                flag("AF", 4),
+                flag("ZF", 6),
+                flag("SF", 7),
+                flag("TF", 8),
+                flag("IF", 9),
+                flag("DF", 10),
+                flag("OF", 11),
+                bits("IOPL", 12, 2, &[]),
+            ]),
+        },
+        reg("fs_base"),
+        reg("gs_base"),
+    ],
+};
+
+/// x86 `cr3`: page table base and PCID or cache control bits
+const CR3: Layout = Layout::Custom(|value, _| {
+    vec![
+        DecodedField::new("BASE", value & !0xFFF, None),
+        DecodedField::new("PCID", field(value, 0, 12), None),
+        DecodedField::new("PWT", field(value, 3, 1), None),
+        DecodedField::new("PCD", field(value, 4, 1), None),
+    ]
+});
+
+const X86_SYSTEM: RegisterProfile = RegisterProfile {
+    name: "x86-system",
+    description: "x86 control registers for OS development: cr0, cr2 (page fault address), cr3, cr4, and efer",
+    registers: &[
+        RegisterSpec {
+            name: "cr0",
+            source: RegisterSource::Register("cr0"),
+            layout: Layout::Fields(&[
+                flag("PE", 0),
+                flag("MP", 1),
+                flag("EM", 2),
+                flag("TS", 3),
+                flag("ET", 4),
+                flag("NE", 5),
+                flag("WP", 16),
+                flag("AM", 18),
+                flag("NW", 29),
+                flag("CD", 30),
+                flag("PG", 31),
+            ]),
+        },
+        reg("cr2"),
+        RegisterSpec { name: "cr3", source: RegisterSource::Register("cr3"), layout: CR3 },
+        RegisterSpec {
+            name: "cr4",
+            source: RegisterSource::Register("cr4"),
+            layout: Layout::Fields(&[
+                flag("VME", 0),
+                flag("PVI", 1),
+                flag("TSD", 2),
+                flag("DE", 3),
+                flag("PSE", 4),
+                flag("PAE", 5),
+                flag("MCE", 6),
+                flag("PGE", 7),
+                flag("PCE", 8),
+                flag("OSFXSR", 9),
+                flag("OSXMMEXCPT", 10),
+                flag("UMIP", 11),
+                flag("LA57", 12),
+                flag("VMXE", 13),
+                flag("SMXE", 14),
+                flag("FSGSBASE", 16),
+                flag("PCIDE", 17),
+                flag("OSXSAVE", 18),
+                flag("SMEP", 20),
+                flag("SMAP", 21),
+                flag("PKE", 22),
+                flag("CET", 23),
+            ]),
+        },
+        RegisterSpec {
+            name: "efer",
+            source: RegisterSource::Register("efer"),
+            layout: Layout::Fields(&[
+                flag("SCE", 0),
+                flag("LME", 8),
+                flag("LMA", 10),
+                flag("NXE", 11),
+                flag("SVME", 12),
+                flag("FFXSR", 14),
+            ]),
+        },
+    ],
+};
+
+/// Available register profiles
+pub const PROFILES: &[RegisterProfile] = &[CORTEX_M_FAULT, RISCV_MACHINE, X86_64_GENERAL, X86_SYSTEM];
+
+/// Look up a register profile by name
+pub fn profile(name: &str) -> Option<&'static RegisterProfile> {
+    PROFILES.iter().find(|p| p.name == name)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_decode_fault_registers() {
+        let cortex = profile("cortex-m-fault").unwrap();
+        let cfsr = cortex.registers.iter().find(|r| r.name == "cfsr").unwrap();
+        // PRECISERR and BFARVALID: a precise bus fault with a valid BFAR
+        let decoded = cfsr.decode(0x8200, 32);
+        assert_eq!(decoded.value.as_deref(), Some("0x00008200"));
+        let names: Vec<_> = decoded.fields.iter().map(|f| f.name).collect();
+        assert_eq!(names, ["PRECISERR", "BFARVALID"]);
+
+        let xpsr = cortex.registers.iter().find(|r| r.name == "xpsr").unwrap();
+        let decoded = xpsr.decode(0x6100_0003, 32);
+        assert_eq!(decoded.fields[0].meaning, Some("HardFault"));
+
+        let riscv = profile("riscv-machine").unwrap();
+        let mcause = riscv.registers.iter().find(|r| r.name == "mcause").unwrap();
+        let decoded = mcause.decode(0x8000_0000_0000_0007, 64);
+        assert_eq!(decoded.fields[1].meaning, Some("Machine timer interrupt"));
+        let decoded = mcause.decode(2, 32);
+        assert_eq!(decoded.fields[0].value, 0);
+        assert_eq!(decoded.fields[1].meaning, Some("Illegal instruction"));
+
+        let satp = RISCV_CSRS.iter().find(|r| r.name == "satp").unwrap();
+        let decoded = satp.decode(0x8000_0000_0008_0400, 64);
+        assert_eq!(decoded.fields[0].meaning, Some("Sv39"));
+        assert_eq!(decoded.fields[2].value, 0x8_0400);
+        let decoded = satp.decode(0x8008_0400, 32);
+        assert_eq!(decoded.fields[0].meaning, Some("Sv32"));
+    }
+}
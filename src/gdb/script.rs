@@ -0,0 +1,186 @@
+//! GDB Command Script Export
+//!
+//! Builds a plain CLI script (`gdb -x`) that reproduces a debugging session.
+
+use crate::gdb::types::*;
+
+/// Build a GDB command script from the current session.
+///
+/// Setup commands (architecture, executable, target) come from the session
+/// state, breakpoints from a live `break-list`, and `history` holds the
+/// CLI-equivalent commands recorded while the session was driven.
+pub fn build_script(
+    state: &GdbSessionState,
+    breakpoints: &[Breakpoint],
+    history: &[String],
+    include_history: bool,
+) -> String {
+    let mut lines = vec![
+        "# GDB command script exported by mcp-gdb-server".to_string(),
+        "# Usage: gdb -x <this file>".to_string(),
+        String::new(),
+        "set pagination off".to_string(),
+        "set confirm off".to_string(),
+        "set breakpoint pending on".to_string(),
+    ];
+
+    if let Some(arch) = &state.architecture {
+        lines.push(format!("set architecture {}", arch));
+    }
+    if let Some(exe) = &state.executable {
+        lines.push(format!("file {}", exe));
+    }
+    if let Some(target) = &state.target {
+        lines.push(format!("target {}", target));
+    }
+
+    if !breakpoints.is_empty() {
+        // Breakpoints spread over several inferiors mean a multi-inferior
+        // session, where one listed in a single inferior was restricted to it
+        let mut groups: Vec<&str> = breakpoints.iter()
+            .flat_map(|bp| bp.thread_groups.iter().flatten())
+            .map(String::as_str)
+            .collect();
+        groups.sort_unstable();
+        groups.dedup();
+        let multi_inferior = groups.len() > 1;
+
+        lines.push(String::new());
+        lines.push("# Breakpoints and watchpoints".to_string());
+        for bp in breakpoints {
+            lines.extend(breakpoint_commands(bp, multi_inferior));
+        }
+    }
+
+    if !history.is_empty() {
+        lines.push(String::new());
+        if include_history {
+            lines.push("# Session history".to_string());
+            lines.extend(history.iter().cloned());
+        } else {
+            lines.push("# Session history (not replayed)".to_string());
+            lines.extend(history.iter().map(|cmd| format!("# {}", cmd)));
+        }
+    }
+
+    lines.push(String::new());
+    lines.join("\n")
+}
+
+/// Convert a breakpoint into the CLI commands that recreate it.
+///
+/// Breakpoint numbers are not stable across sessions, so follow-up commands
+/// refer to `$bpnum` (the most recently created breakpoint). In a
+/// multi-inferior session a breakpoint in a single inferior keeps its
+/// `inferior N` qualifier.
+fn breakpoint_commands(bp: &Breakpoint, multi_inferior: bool) -> Vec<String> {
+    let create = match bp.breakpoint_type.as_str() {
+        "watchpoint" | "hw watchpoint" => bp.what.as_ref().map(|w| format!("watch {}", w)),
+        "read watchpoint" => bp.what.as_ref().map(|w| format!("rwatch {}", w)),
+        "acc watchpoint" => bp.what.as_ref().map(|w| format!("awatch {}", w)),
+        "catchpoint" => bp.what.as_deref().and_then(catch_command),
+        _ => {
+            let location = bp
+                .original_location
+                .clone()
+                .or_else(|| bp.addr.as_ref().map(|a| format!("*{}", a)));
+            let command = if bp.disposition == "del" { "tbreak" } else { "break" };
+            // A thread qualifier already implies its inferior
+            let qualifier = match (&bp.thread, bp.thread_groups.as_deref()) {
+                (Some(thread), _) => format!(" thread {}", thread),
+                (None, Some([group])) if multi_inferior => group
+                    .strip_prefix('i')
+                    .map(|inferior| format!(" inferior {}", inferior))
+                    .unwrap_or_default(),
+                _ => String::new(),
+            };
+            location.map(|loc| format!("{} {}{}", command, loc, qualifier))
+        }
+    };
+
+    let Some(create) = create else {
+        return vec![format!("# breakpoint {} could not be reproduced", bp.number)];
+    };
+
+    let mut commands = vec![create];
+    if let Some(cond) = &bp.condition {
+        commands.push(format!("condition $bpnum {}", cond));
+    }
+    if let Some(ignore) = bp.ignore_count.filter(|n| *n > 0) {
+        commands.push(format!("ignore $bpnum {}", ignore));
+    }
+    if !bp.enabled {
+        commands.push("disable $bpnum".to_string());
+    }
+    commands
+}
+
+/// Convert a catchpoint description ("load of library matching foo") back
+/// into its `catch` command
+fn catch_command(what: &str) -> Option<String> {
+    for (prefix, event) in [("load of library", "load"), ("unload of library", "unload")] {
+        if let Some(rest) = what.strip_prefix(prefix) {
+            let regex = rest.trim().strip_prefix("matching").map(str::trim).unwrap_or("");
+            return Some(format!("catch {} {}", event, regex).trim_end().to_string());
+        }
+    }
+    None
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_build_script_reproduces_setup_and_breakpoints() {
+        let state = GdbSessionState {
+            executable: Some("/tmp/hello".to_string()),
+            target: Some("remote localhost:1234".to_string()),
+            ..Default::default()
+        };
+        let breakpoints = vec![
+            Breakpoint {
+                number: "1".to_string(),
+                breakpoint_type: "breakpoint".to_string(),
+                disposition: "keep".to_string(),
+                enabled: false,
+                original_location: Some("main".to_string()),
+                condition: Some("x > 1".to_string()),
+                thread_groups: Some(vec!["i1".to_string(), "i2".to_string()]),
+                ..Default::default()
+            },
+            Breakpoint {
+                number: "3".to_string(),
+                breakpoint_type: "breakpoint".to_string(),
+                disposition: "keep".to_string(),
+                enabled: true,
+                original_location: Some("worker.c:42".to_string()),
+                thread_groups: Some(vec!["i2".to_string()]),
+                ..Default::default()
+            },
+            Breakpoint {
+                number: "2".to_string(),
+                breakpoint_type: "hw watchpoint".to_string(),
+                disposition: "keep".to_string(),
+                enabled: true,
+                what: Some("counter".to_string()),
+                ..Default::default()
+            },
+        ];
+        let history = vec!["continue".to_string()];
+
+        let script = build_script(&state, &breakpoints, &history, false);
+        let lines: Vec<&str> = script.lines().collect();
+
+        assert!(lines.contains(&"file /tmp/hello"));
+        assert!(lines.contains(&"target remote localhost:1234"));
+        assert!(lines.contains(&"break main"));
+        assert!(lines.contains(&"condition $bpnum x > 1"));
+        assert!(lines.contains(&"disable $bpnum"));
+        assert!(lines.contains(&"watch counter"));
+        assert!(lines.contains(&"break worker.c:42 inferior 2"));
+        assert!(lines.contains(&"# continue"));
+        assert_eq!(catch_command("load of library matching libplugin").as_deref(), Some("catch load libplugin"));
+        assert_eq!(catch_command("unload of library").as_deref(), Some("catch unload"));
+    }
+}
@@ -0,0 +1,75 @@
+//! Signal Information
+//!
+//! Names for the Linux `siginfo_t` fields GDB exposes as `$_siginfo`, so a
+//! SIGSEGV can be reported with its faulting address and why it faulted
+//! (unmapped address vs. permissions) without the caller knowing the kernel
+//! ABI.
+
+/// Signals whose `siginfo_t` carries a faulting address
+pub const FAULT_SIGNALS: &[&str] = &["SIGSEGV", "SIGBUS", "SIGILL", "SIGFPE", "SIGTRAP"];
+
+/// Linux signal name for a signal number
+pub fn signal_name(signo: i64) -> Option<&'static str> {
+    const NAMES: &[&str] = &[
+        "SIGHUP", "SIGINT", "SIGQUIT", "SIGILL", "SIGTRAP", "SIGABRT", "SIGBUS", "SIGFPE", "SIGKILL", "SIGUSR1",
+        "SIGSEGV", "SIGUSR2", "SIGPIPE", "SIGALRM", "SIGTERM", "SIGSTKFLT", "SIGCHLD", "SIGCONT", "SIGSTOP",
+        "SIGTSTP", "SIGTTIN", "SIGTTOU", "SIGURG", "SIGXCPU", "SIGXFSZ", "SIGVTALRM", "SIGPROF", "SIGWINCH",
+        "SIGIO", "SIGPWR", "SIGSYS",
+    ];
+    usize::try_from(signo - 1).ok().and_then(|i| NAMES.get(i)).copied()
+}
+
+/// Meaning of `si_code` for a signal
+pub fn code_meaning(signal: &str, code: i64) -> Option<&'static str> {
+    // Codes that apply to every signal
+    match code {
+        0 => return Some("Sent by kill()"),
+        0x80 => return Some("Sent by the kernel"),
+        -1 => return Some("Sent by sigqueue()"),
+        -2 => return Some("Timer expired"),
+        -6 => return Some("Sent by tkill()/tgkill()"),
+        _ => {}
+    }
+    let meanings: &[&str] = match signal {
+        "SIGSEGV" => &["Address not mapped to object", "Invalid permissions for mapped object", "Failed address bound checks", "Failed protection key checks"],
+        "SIGBUS" => &["Invalid address alignment", "Nonexistent physical address", "Object-specific hardware error"],
+        "SIGILL" => &[
+            "Illegal opcode",
+            "Illegal operand",
+            "Illegal addressing mode",
+            "Illegal trap",
+            "Privileged opcode",
+            "Privileged register",
+            "Coprocessor error",
+            "Internal stack error",
+        ],
+        "SIGFPE" => &[
+            "Integer divide by zero",
+            "Integer overflow",
+            "Floating-point divide by zero",
+            "Floating-point overflow",
+            "Floating-point underflow",
+            "Floating-point inexact result",
+            "Floating-point invalid operation",
+            "Subscript out of range",
+        ],
+        "SIGTRAP" => &["Process breakpoint", "Process trace trap", "Process taken branch trap", "Hardware breakpoint/watchpoint"],
+        _ => &[],
+    };
+    usize::try_from(code - 1).ok().and_then(|i| meanings.get(i)).copied()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_signal_names_and_codes() {
+        assert_eq!(signal_name(11), Some("SIGSEGV"));
+        assert_eq!(signal_name(0), None);
+        assert_eq!(code_meaning("SIGSEGV", 1), Some("Address not mapped to object"));
+        assert_eq!(code_meaning("SIGSEGV", 2), Some("Invalid permissions for mapped object"));
+        assert_eq!(code_meaning("SIGUSR1", 0), Some("Sent by kill()"));
+        assert_eq!(code_meaning("SIGUSR1", 3), None);
+    }
+}
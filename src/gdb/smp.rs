@@ -0,0 +1,168 @@
+//! SMP Targets
+//!
+//! QEMU and most multi-core probes present each core as a GDB thread, so
+//! debugging an SMP kernel means switching threads by hand to compare what
+//! the cores are doing. `gdb_smp_state` reads the same registers and the top
+//! of the stack of every core and lays them out side by side, and
+//! `gdb_smp_pin` restricts stepping and continuing to one core through
+//! GDB's scheduler locking.
+
+use crate::gdb::patch::PatchArch;
+use crate::gdb::types::{Frame, Thread, ThreadState};
+use regex::Regex;
+use serde::Serialize;
+use std::sync::OnceLock;
+
+/// Registers shown when none are asked for
+pub fn default_registers(arch: Option<PatchArch>) -> Vec<&'static str> {
+    match arch {
+        Some(PatchArch::X86_64) => vec!["rip", "rsp", "rbp", "eflags", "cr3"],
+        Some(PatchArch::I386) => vec!["eip", "esp", "ebp", "eflags", "cr3"],
+        Some(PatchArch::AArch64) => vec!["pc", "sp", "x29", "x30", "cpsr"],
+        Some(PatchArch::Riscv32 | PatchArch::Riscv64) => vec!["pc", "sp", "ra", "s0", "mstatus"],
+        Some(PatchArch::Arm) => vec!["pc", "sp", "lr", "r11", "cpsr"],
+        Some(PatchArch::Thumb) => vec!["pc", "sp", "lr", "r7", "xpsr"],
+        None => vec!["pc", "sp"],
+    }
+}
+
+/// CPU number of a thread: `CPU#n` in QEMU's thread description, else
+/// the core GDB reports
+pub fn cpu_index(thread: &Thread) -> Option<u64> {
+    static CPU: OnceLock<Regex> = OnceLock::new();
+    let cpu = CPU.get_or_init(|| Regex::new(r"CPU#(\d+)").unwrap());
+    [Some(thread.target_id.as_str()), thread.name.as_deref()]
+        .into_iter()
+        .flatten()
+        .find_map(|text| cpu.captures(text).and_then(|c| c[1].parse().ok()))
+        .or(thread.core)
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct CoreRegister {
+    pub name: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub value: Option<String>,
+}
+
+/// What one core is doing
+#[derive(Debug, Clone, Serialize)]
+pub struct CoreState {
+    pub thread_id: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub cpu: Option<u64>,
+    pub target_id: String,
+    pub state: ThreadState,
+    /// The thread GDB has selected
+    pub selected: bool,
+    pub registers: Vec<CoreRegister>,
+    /// Innermost frames
+    pub frames: Vec<Frame>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub error: Option<String>,
+}
+
+impl CoreState {
+    fn title(&self) -> String {
+        let cpu = self.cpu.map(|c| format!("CPU#{}", c)).unwrap_or_else(|| "core ?".to_string());
+        let selected = if self.selected { "*" } else { "" };
+        format!("{} (thread {}){}", cpu, self.thread_id, selected)
+    }
+}
+
+/// Result of `gdb_smp_state`
+#[derive(Debug, Clone, Serialize)]
+pub struct SmpState {
+    pub cores: Vec<CoreState>,
+    /// Thread run control is pinned to, if any
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub pinned_thread: Option<String>,
+    /// GDB's `scheduler-locking` setting
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub scheduler_locking: Option<String>,
+}
+
+impl SmpState {
+    /// Registers and frames of the cores in columns, one row per register
+    /// and frame level
+    pub fn render(&self) -> String {
+        let mut rows: Vec<Vec<String>> = vec![std::iter::once(String::new()).chain(self.cores.iter().map(CoreState::title)).collect()];
+        let names: Vec<&str> = self.cores.first().map(|c| c.registers.iter().map(|r| r.name.as_str()).collect()).unwrap_or_default();
+        for (i, name) in names.iter().enumerate() {
+            let values = self.cores.iter().map(|c| c.registers.get(i).and_then(|r| r.value.clone()).unwrap_or_else(|| "-".to_string()));
+            rows.push(std::iter::once(name.to_string()).chain(values).collect());
+        }
+        let depth = self.cores.iter().map(|c| c.frames.len()).max().unwrap_or(0);
+        for level in 0..depth {
+            let frames = self.cores.iter().map(|c| match c.frames.get(level) {
+                Some(frame) => frame.func.clone().unwrap_or_else(|| frame.addr.clone()),
+                None if level == 0 => c.error.clone().unwrap_or_else(|| "-".to_string()),
+                None => String::new(),
+            });
+            rows.push(std::iter::once(format!("#{}", level)).chain(frames).collect());
+        }
+
+        let columns = rows[0].len();
+        let widths: Vec<usize> = (0..columns).map(|i| rows.iter().map(|r| r[i].chars().count()).max().unwrap_or(0)).collect();
+        rows.iter()
+            .map(|row| {
+                row.iter()
+                    .zip(&widths)
+                    .map(|(cell, width)| format!("{:<width$}", cell, width = width))
+                    .collect::<Vec<_>>()
+                    .join("  ")
+                    .trim_end()
+                    .to_string()
+            })
+            .collect::<Vec<_>>()
+            .join("\n")
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn frame(func: &str) -> Frame {
+        Frame { level: 0, addr: "0x0".to_string(), func: Some(func.to_string()), file: None, fullname: None, line: None, arch: None, from: None, inlined: false }
+    }
+
+    #[test]
+    fn test_render() {
+        let thread = |id: &str, target_id: &str| Thread {
+            id: id.to_string(),
+            target_id: target_id.to_string(),
+            name: None,
+            frame: None,
+            state: ThreadState::Stopped,
+            core: None,
+        };
+        assert_eq!(cpu_index(&thread("2", "Thread 1.2 (CPU#1 [halted ])")), Some(1));
+        assert_eq!(cpu_index(&thread("1", "Thread 1")), None);
+
+        let core = |id: &str, cpu, rip: &str, frames: Vec<Frame>| CoreState {
+            thread_id: id.to_string(),
+            cpu: Some(cpu),
+            target_id: String::new(),
+            state: ThreadState::Stopped,
+            selected: cpu == 0,
+            registers: vec![CoreRegister { name: "rip".to_string(), value: Some(rip.to_string()) }],
+            frames,
+            error: None,
+        };
+        let state = SmpState {
+            cores: vec![
+                core("1", 0, "0xffffffff81a3c0d4", vec![frame("schedule"), frame("do_idle")]),
+                core("2", 1, "0xffffffff8100f1e0", vec![frame("default_idle")]),
+            ],
+            pinned_thread: None,
+            scheduler_locking: None,
+        };
+        assert_eq!(state.render(), [
+            "     CPU#0 (thread 1)*   CPU#1 (thread 2)",
+            "rip  0xffffffff81a3c0d4  0xffffffff8100f1e0",
+            "#0   schedule            default_idle",
+            "#1   do_idle",
+        ].join("\n"));
+    }
+}
@@ -0,0 +1,218 @@
+//! Large Region Watches
+//!
+//! Debug registers cover a few words, so watching a 4 KiB buffer fails with
+//! "too many hardware watchpoints" (or GDB quietly falls back to a software
+//! watchpoint that takes minutes). `gdb_watch_region` runs the program until
+//! the region is written, emulating the watchpoint when the hardware cannot:
+//! with page protection guards (`mprotect` in the inferior, where available)
+//! or by single-stepping and comparing the region after every instruction.
+
+use crate::gdb::parser::parse_address;
+use crate::gdb::types::Frame;
+use crate::util::to_hex;
+use serde::Serialize;
+
+/// `PROT_READ`, `PROT_WRITE` and `PROT_EXEC` of Linux
+pub const PROT_READ: u64 = 1;
+pub const PROT_WRITE: u64 = 2;
+pub const PROT_EXEC: u64 = 4;
+
+/// Page size assumed when the target cannot tell
+pub const DEFAULT_PAGE_SIZE: u64 = 4096;
+
+/// How a region watch detects writes
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum WatchMode {
+    /// Hardware first, then guard, then step
+    Auto,
+    /// A hardware watchpoint on the whole region
+    Hardware,
+    /// Write-protect the region's pages and catch the SIGSEGV
+    Guard,
+    /// Single-step and compare the region after each instruction
+    Step,
+}
+
+impl WatchMode {
+    pub fn parse(mode: &str) -> Option<Self> {
+        match mode {
+            "auto" => Some(WatchMode::Auto),
+            "hardware" => Some(WatchMode::Hardware),
+            "guard" => Some(WatchMode::Guard),
+            "step" => Some(WatchMode::Step),
+            _ => None,
+        }
+    }
+
+    pub fn as_str(self) -> &'static str {
+        match self {
+            WatchMode::Auto => "auto",
+            WatchMode::Hardware => "hardware",
+            WatchMode::Guard => "guard",
+            WatchMode::Step => "step",
+        }
+    }
+
+    /// What the mode costs and what it misses
+    pub fn tradeoff(self) -> &'static str {
+        match self {
+            WatchMode::Auto | WatchMode::Hardware => "Full speed; only regions the debug registers can cover.",
+            WatchMode::Guard => "Near full speed, but every write to the guarded pages (including bytes outside the region on the same pages) costs a fault and two mprotect calls. Writes by the kernel, e.g. read(2) into the buffer, fail with EFAULT instead of being caught. Linux processes only.",
+            WatchMode::Step => "Works on any target, but every instruction is a round trip to the target plus a read of the whole region: expect thousands of instructions per second at best, so budget max_steps accordingly.",
+        }
+    }
+}
+
+/// Whether a watchpoint error means the debug registers ran out
+pub fn is_hardware_limit(error: &str) -> bool {
+    let error = error.to_lowercase();
+    ["hardware watchpoint", "hardware breakpoints/watchpoints", "debug register", "could not insert watchpoint"]
+        .iter()
+        .any(|pattern| error.contains(pattern))
+}
+
+/// Pages covering `[addr, addr + length)`, as (start, length)
+pub fn page_span(addr: u64, length: u64, page_size: u64) -> (u64, u64) {
+    let start = addr - addr % page_size;
+    let end = (addr + length).div_ceil(page_size) * page_size;
+    (start, end - start)
+}
+
+/// `PROT_*` bits of the mapping holding `addr`, from `info proc mappings`.
+/// Older GDB releases print no permissions column, in which case nothing is
+/// returned.
+pub fn mapping_protection(output: &str, addr: u64) -> Option<u64> {
+    output.lines().find_map(|line| {
+        let fields: Vec<&str> = line.split_whitespace().collect();
+        let start = parse_address(fields.first()?)?;
+        let end = parse_address(fields.get(1)?)?;
+        let perms = fields.get(4).filter(|p| p.len() == 4 && p.chars().all(|c| "rwxps-".contains(c)))?;
+        (start..end).contains(&addr).then(|| {
+            [('r', PROT_READ), ('w', PROT_WRITE), ('x', PROT_EXEC)]
+                .iter()
+                .filter(|(c, _)| perms.contains(*c))
+                .map(|(_, bit)| bit)
+                .sum()
+        })
+    })
+}
+
+/// How GDB handles a signal, from `info signals`, as the arguments of the
+/// `handle` command that restores it: `stop print pass`
+pub fn signal_handling(output: &str, signal: &str) -> Option<String> {
+    let line = output.lines().find(|l| l.split_whitespace().next() == Some(signal))?;
+    let columns: Vec<&str> = line.split_whitespace().skip(1).take(3).collect();
+    if columns.len() < 3 || !columns.iter().all(|c| *c == "Yes" || *c == "No") {
+        return None;
+    }
+    let keywords = ["stop", "print", "pass"].iter().zip(&columns)
+        .map(|(keyword, column)| if *column == "Yes" { keyword.to_string() } else { format!("no{}", keyword) })
+        .collect::<Vec<_>>();
+    Some(keywords.join(" "))
+}
+
+/// Offset of the first byte that differs
+pub fn first_change(before: &[u8], after: &[u8]) -> Option<usize> {
+    before.iter().zip(after).position(|(a, b)| a != b)
+}
+
+/// The write that ended a region watch
+#[derive(Debug, Clone, Serialize)]
+pub struct RegionWrite {
+    /// Address of the writing instruction, when known
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub instruction: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub func: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub file: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub line: Option<u64>,
+    /// Address written (the fault address in guard mode, else the first
+    /// changed byte)
+    pub address: String,
+    /// Offset of `address` in the region
+    pub offset: u64,
+    /// Up to 16 bytes from `address`, before and after the write
+    pub before: String,
+    pub after: String,
+}
+
+impl RegionWrite {
+    /// Describe a write to the region at `addr`. `written` is the address
+    /// written when known, else the first changed byte is used.
+    pub fn new(addr: u64, before: &[u8], after: &[u8], written: Option<u64>, instruction: Option<String>, frame: Option<&Frame>) -> Self {
+        let offset = written
+            .map(|w| w.saturating_sub(addr) as usize)
+            .or_else(|| first_change(before, after))
+            .unwrap_or(0);
+        let window = |bytes: &[u8]| to_hex(&bytes[offset.min(bytes.len())..(offset + 16).min(bytes.len())]);
+        RegionWrite {
+            instruction,
+            func: frame.and_then(|f| f.func.clone()),
+            file: frame.and_then(|f| f.file.clone()),
+            line: frame.and_then(|f| f.line),
+            address: format!("{:#x}", addr + offset as u64),
+            offset: offset as u64,
+            before: window(before),
+            after: window(after),
+        }
+    }
+}
+
+/// Result of `gdb_watch_region`
+#[derive(Debug, Clone, Serialize)]
+pub struct RegionWatch {
+    pub address: String,
+    pub length: u64,
+    /// Mode that watched the region
+    pub mode: WatchMode,
+    /// Why `auto` did not use the hardware (or guard) mode
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub fallbacks: Vec<String>,
+    pub tradeoff: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub write: Option<RegionWrite>,
+    /// "written", "timeout", "max_steps", or the stop that ended the watch
+    pub stopped_because: String,
+    pub elapsed_ms: u64,
+    /// Instructions stepped (step mode)
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub steps: Option<u64>,
+    /// Faults taken on the guarded pages (guard mode)
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub faults: Option<u64>,
+    /// Faults from writes to the guarded pages outside the region
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub false_positives: Option<u64>,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_region_helpers() {
+        assert_eq!(page_span(0x601010, 4096, 4096), (0x601000, 0x2000));
+        assert_eq!(page_span(0x601000, 4096, 4096), (0x601000, 0x1000));
+
+        let mappings = [
+            "          Start Addr           End Addr       Size     Offset  Perms  objfile",
+            "            0x400000           0x401000     0x1000        0x0  r--p   /tmp/a.out",
+            "            0x601000           0x603000     0x2000     0x1000  rw-p   /tmp/a.out",
+        ].join("\n");
+        assert_eq!(mapping_protection(&mappings, 0x602ff8), Some(PROT_READ | PROT_WRITE));
+        assert_eq!(mapping_protection(&mappings, 0x400010), Some(PROT_READ));
+        assert_eq!(mapping_protection(&mappings, 0x700000), None);
+
+        let signals = "Signal        Stop\tPrint\tPass to program\tDescription\nSIGSEGV       Yes\tYes\tYes\t\tSegmentation fault\n";
+        assert_eq!(signal_handling(signals, "SIGSEGV").as_deref(), Some("stop print pass"));
+        assert_eq!(signal_handling(signals, "SIGBUS"), None);
+
+        assert_eq!(first_change(&[1, 2, 3], &[1, 9, 3]), Some(1));
+        assert!(is_hardware_limit("Could not insert hardware watchpoint 2."));
+        assert!(!is_hardware_limit("No symbol \"buf\" in current context."));
+        assert_eq!(WatchMode::parse("guard"), Some(WatchMode::Guard));
+    }
+}
@@ -0,0 +1,260 @@
+//! Source File Verification
+//!
+//! Source files edited after the binary was built still show up in GDB's
+//! listings, so reasoning over them silently describes code that is not
+//! running. Each source file of the executable is checked against the MD5
+//! recorded in a DWARF 5 line table (read with `readelf`, as GDB does not
+//! expose it) and otherwise against the executable's modification time,
+//! which is the check GDB itself uses for its "Source file is more recent
+//! than executable" warning.
+
+use crate::util::to_hex;
+use serde::Serialize;
+use std::collections::HashMap;
+use std::path::Path;
+use std::process::Command;
+use std::time::UNIX_EPOCH;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum SourceStatus {
+    /// The checksum matches, or the file is older than the executable
+    Ok,
+    /// The file's MD5 differs from the one in the debug info
+    Modified,
+    /// No checksum is recorded and the file is newer than the executable
+    NewerThanBinary,
+    Missing,
+}
+
+/// Verification result of one source file
+#[derive(Debug, Clone, Serialize)]
+pub struct SourceCheck {
+    pub file: String,
+    pub path: String,
+    pub status: SourceStatus,
+    /// "md5" or "mtime"
+    pub verified_by: &'static str,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub expected_md5: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub actual_md5: Option<String>,
+    /// Seconds the file was modified after the executable
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub newer_by_secs: Option<u64>,
+}
+
+impl SourceCheck {
+    pub fn stale(&self) -> bool {
+        self.status != SourceStatus::Ok
+    }
+
+    pub fn warning(&self) -> Option<String> {
+        match self.status {
+            SourceStatus::Ok => None,
+            SourceStatus::Modified => Some(format!("{} differs from the checksum in the debug info", self.path)),
+            SourceStatus::NewerThanBinary => Some(format!(
+                "{} was modified {} s after the executable was built",
+                self.path,
+                self.newer_by_secs.unwrap_or(0)
+            )),
+            SourceStatus::Missing => Some(format!("{} does not exist", self.path)),
+        }
+    }
+}
+
+/// Result of `gdb_source_verify`
+#[derive(Debug, Clone, Serialize)]
+pub struct SourceVerification {
+    pub executable: String,
+    /// Number of files with an MD5 in the line tables
+    pub checksums: usize,
+    pub stale: usize,
+    pub files: Vec<SourceCheck>,
+}
+
+/// Check one source file
+pub fn verify_source(file: &str, path: &str, binary_mtime: Option<u64>, expected_md5: Option<&str>) -> SourceCheck {
+    let mut check = SourceCheck {
+        file: file.to_string(),
+        path: path.to_string(),
+        status: SourceStatus::Ok,
+        verified_by: if expected_md5.is_some() { "md5" } else { "mtime" },
+        expected_md5: expected_md5.map(|s| s.to_string()),
+        actual_md5: None,
+        newer_by_secs: None,
+    };
+    let Ok(content) = std::fs::read(path) else {
+        check.status = SourceStatus::Missing;
+        return check;
+    };
+
+    if let (Some(mtime), Some(binary)) = (mtime_secs(Path::new(path)), binary_mtime) {
+        check.newer_by_secs = mtime.checked_sub(binary).filter(|&secs| secs > 0);
+    }
+    if let Some(expected) = expected_md5 {
+        let actual = md5(&content);
+        if !md5_matches(expected, &actual) {
+            check.status = SourceStatus::Modified;
+        }
+        check.actual_md5 = Some(to_hex(&actual));
+    } else if check.newer_by_secs.is_some() {
+        check.status = SourceStatus::NewerThanBinary;
+    }
+    check
+}
+
+/// Modification time in seconds since the Unix epoch
+pub fn mtime_secs(path: &Path) -> Option<u64> {
+    let modified = std::fs::metadata(path).ok()?.modified().ok()?;
+    modified.duration_since(UNIX_EPOCH).ok().map(|d| d.as_secs())
+}
+
+/// MD5s of the DWARF 5 line tables of an ELF file keyed by path, empty if
+/// `readelf` is unavailable or the tables carry no checksums
+pub fn line_table_md5s(executable: &str) -> HashMap<String, String> {
+    match Command::new("readelf").args(["--debug-dump=line", executable]).output() {
+        Ok(output) if output.status.success() => parse_readelf_md5s(&String::from_utf8_lossy(&output.stdout)),
+        _ => HashMap::new(),
+    }
+}
+
+/// Parse the directory and file name tables printed by `readelf
+/// --debug-dump=line`, keeping the files that have an MD5
+fn parse_readelf_md5s(output: &str) -> HashMap<String, String> {
+    let mut md5s = HashMap::new();
+    let mut directories: Vec<String> = Vec::new();
+    let mut in_directories = false;
+    let mut in_files = false;
+
+    for line in output.lines() {
+        let trimmed = line.trim();
+        if trimmed.starts_with("The Directory Table") {
+            directories.clear();
+            (in_directories, in_files) = (true, false);
+            continue;
+        }
+        if trimmed.starts_with("The File Name Table") {
+            (in_directories, in_files) = (false, true);
+            continue;
+        }
+        if trimmed.starts_with("Entry") {
+            continue;
+        }
+        if !trimmed.starts_with(|c: char| c.is_ascii_digit()) {
+            (in_directories, in_files) = (false, false);
+            continue;
+        }
+
+        // The name may be "(indirect line string, offset: 0x4): main.c"
+        let name = match trimmed.rsplit_once("): ") {
+            Some((_, name)) => name,
+            None => trimmed.split_whitespace().last().unwrap_or_default(),
+        };
+        if in_directories {
+            directories.push(name.to_string());
+        } else if in_files {
+            let tokens: Vec<&str> = trimmed.split_whitespace().collect();
+            let md5 = tokens.iter().find(|t| {
+                t.len() == 34 && t.starts_with("0x") && t[2..].chars().all(|c| c.is_ascii_hexdigit())
+            });
+            let Some(md5) = md5 else { continue };
+            let dir = tokens.get(1).and_then(|i| i.parse::<usize>().ok()).and_then(|i| directories.get(i));
+            let path = match dir {
+                Some(dir) if !name.starts_with('/') => format!("{}/{}", dir, name),
+                _ => name.to_string(),
+            };
+            md5s.insert(path, md5[2..].to_lowercase());
+        }
+    }
+    md5s
+}
+
+/// Whether a checksum from readelf matches. readelf prints DW_FORM_data16
+/// as a 128-bit number, so on little-endian files the bytes are reversed.
+fn md5_matches(expected: &str, actual: &[u8; 16]) -> bool {
+    let mut reversed = *actual;
+    reversed.reverse();
+    expected == to_hex(actual) || expected == to_hex(&reversed)
+}
+
+/// MD5 digest (RFC 1321)
+fn md5(data: &[u8]) -> [u8; 16] {
+    const SHIFTS: [u32; 16] = [7, 12, 17, 22, 5, 9, 14, 20, 4, 11, 16, 23, 6, 10, 15, 21];
+    let constants: Vec<u32> = (0..64).map(|i| ((i as f64 + 1.0).sin().abs() * 4294967296.0) as u32).collect();
+
+    let mut message = data.to_vec();
+    message.push(0x80);
+    while message.len() % 64 != 56 {
+        message.push(0);
+    }
+    message.extend_from_slice(&((data.len() as u64).wrapping_mul(8)).to_le_bytes());
+
+    let mut state: [u32; 4] = [0x67452301, 0xefcdab89, 0x98badcfe, 0x10325476];
+    for block in message.chunks(64) {
+        let words: Vec<u32> = block.chunks(4).map(|w| u32::from_le_bytes([w[0], w[1], w[2], w[3]])).collect();
+        let [mut a, mut b, mut c, mut d] = state;
+        for i in 0..64 {
+            let (f, g) = match i / 16 {
+                0 => ((b & c) | (!b & d), i),
+                1 => ((d & b) | (!d & c), (5 * i + 1) % 16),
+                2 => (b ^ c ^ d, (3 * i + 5) % 16),
+                _ => (c ^ (b | !d), (7 * i) % 16),
+            };
+            let rotated = a.wrapping_add(f).wrapping_add(constants[i]).wrapping_add(words[g])
+                .rotate_left(SHIFTS[(i / 16) * 4 + i % 4]);
+            (a, d, c) = (d, c, b);
+            b = b.wrapping_add(rotated);
+        }
+        for (s, v) in state.iter_mut().zip([a, b, c, d]) {
+            *s = s.wrapping_add(v);
+        }
+    }
+
+    let mut digest = [0u8; 16];
+    for (chunk, word) in digest.chunks_mut(4).zip(state) {
+        chunk.copy_from_slice(&word.to_le_bytes());
+    }
+    digest
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_source_verify() {
+        assert_eq!(to_hex(&md5(b"")), "d41d8cd98f00b204e9800998ecf8427e");
+        assert_eq!(to_hex(&md5(b"The quick brown fox jumps over the lazy dog")), "9e107d9d372bb6826bd81d3542a419d6");
+
+        let output = "\
+ The Directory Table (offset 0x22, lines 2, columns 1):
+  Entry\tName
+  0\t(indirect line string, offset: 0x0): /src
+  1\t(indirect line string, offset: 0x5): include
+
+ The File Name Table (offset 0x30, lines 2, columns 3):
+  Entry\tDir\tMD5\t\t\t\tName
+  0\t0 0xd619a442351dd86b82b62b379d7d109e\t(indirect line string, offset: 0x10): main.c
+  1\t1 0x7e42f8ec980009e904b2008fd98c1dd4\t(indirect line string, offset: 0x17): util.h
+
+ Line Number Statements:
+  [0x00000040]  Set column to 12
+";
+        let md5s = parse_readelf_md5s(output);
+        assert_eq!(md5s.get("/src/main.c").map(String::as_str), Some("d619a442351dd86b82b62b379d7d109e"));
+        assert!(md5s.contains_key("include/util.h"));
+        assert!(md5_matches(&md5s["/src/main.c"], &md5(b"The quick brown fox jumps over the lazy dog")));
+
+        let path = std::env::temp_dir().join(format!("mcp-source-verify-{}.c", std::process::id()));
+        std::fs::write(&path, b"").unwrap();
+        let path = path.to_string_lossy().to_string();
+        let check = verify_source("a.c", &path, Some(0), Some("d41d8cd98f00b204e9800998ecf8427e"));
+        assert_eq!((check.status, check.verified_by), (SourceStatus::Ok, "md5"));
+        assert_eq!(verify_source("a.c", &path, Some(0), Some("00")).status, SourceStatus::Modified);
+        assert_eq!(verify_source("a.c", &path, Some(0), None).status, SourceStatus::NewerThanBinary);
+        assert_eq!(verify_source("a.c", &path, Some(u64::MAX), None).status, SourceStatus::Ok);
+        std::fs::remove_file(&path).unwrap();
+        assert_eq!(verify_source("a.c", &path, None, None).status, SourceStatus::Missing);
+    }
+}
@@ -0,0 +1,130 @@
+//! Stack Scanning
+//!
+//! When frame pointers are smashed the backtrace stops making sense, and
+//! the fallback is to read the raw stack and look for words that point into
+//! code: those are most likely return addresses of the real call chain.
+//! Code ranges come from `info proc mappings` when it reports permissions,
+//! otherwise from the code sections listed by `info files`.
+
+use crate::gdb::parser::parse_address;
+use serde::Serialize;
+
+/// Most words read by one scan
+pub const MAX_SCAN_WORDS: u64 = 8192;
+
+/// An executable address range
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct CodeRange {
+    pub start: u64,
+    pub end: u64,
+    /// Mapped file or section name
+    pub name: String,
+}
+
+/// A stack word that points into code
+#[derive(Debug, Clone, Serialize)]
+pub struct StackCandidate {
+    /// Address of the stack slot
+    pub slot: String,
+    /// Offset of the slot from the scan start in bytes
+    pub offset: u64,
+    pub value: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub symbol: Option<String>,
+    pub region: String,
+    /// The value is the first instruction of a function, so it is more
+    /// likely a function pointer than a return address
+    pub function_start: bool,
+}
+
+/// Result of `gdb_stack_scan`
+#[derive(Debug, Clone, Serialize)]
+pub struct StackScan {
+    pub start: String,
+    pub word_size: usize,
+    pub words: usize,
+    /// Where the code ranges came from: "mappings" or "sections"
+    pub code_source: &'static str,
+    pub candidates: Vec<StackCandidate>,
+}
+
+/// Executable mappings from `info proc mappings`. Older GDB releases print
+/// no permissions column, in which case nothing is returned.
+pub fn parse_proc_mappings(output: &str) -> Vec<CodeRange> {
+    output.lines()
+        .filter_map(|line| {
+            let fields: Vec<&str> = line.split_whitespace().collect();
+            let start = parse_address(fields.first()?)?;
+            let end = parse_address(fields.get(1)?)?;
+            let perms = fields.get(4).filter(|p| p.len() == 4 && p.chars().all(|c| "rwxps-".contains(c)))?;
+            perms.contains('x').then(|| CodeRange {
+                start,
+                end,
+                name: fields.get(5..).map(|f| f.join(" ")).unwrap_or_default(),
+            })
+        })
+        .collect()
+}
+
+/// Code sections from `info files`, e.g.
+/// `0x00007ffff7fc5000 - 0x00007ffff7fe4000 is .text in /lib64/ld-linux-x86-64.so.2`
+pub fn parse_info_files(output: &str) -> Vec<CodeRange> {
+    output.lines()
+        .filter_map(|line| {
+            let (range, rest) = line.trim().split_once(" is ")?;
+            let (start, end) = range.split_once(" - ")?;
+            let section = rest.split(" in ").next()?.trim();
+            let code = [".text", ".init", ".fini", ".plt"].iter().any(|prefix| section.starts_with(prefix));
+            code.then(|| Some(CodeRange {
+                start: parse_address(start.trim())?,
+                end: parse_address(end.trim())?,
+                name: rest.trim().to_string(),
+            }))?
+        })
+        .collect()
+}
+
+/// Split memory into words of `size` bytes
+pub fn decode_words(bytes: &[u8], size: usize, big_endian: bool) -> Vec<u64> {
+    bytes.chunks_exact(size)
+        .map(|chunk| {
+            let fold = |acc: u64, b: &u8| (acc << 8) | *b as u64;
+            if big_endian {
+                chunk.iter().fold(0, fold)
+            } else {
+                chunk.iter().rev().fold(0, fold)
+            }
+        })
+        .collect()
+}
+
+/// The range containing `value`. The low bit is ignored so Thumb return
+/// addresses on Arm are found too.
+pub fn code_range(ranges: &[CodeRange], value: u64) -> Option<&CodeRange> {
+    let address = value & !1;
+    ranges.iter().find(|r| r.start <= address && address < r.end)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_code_ranges() {
+        let mappings = "process 4242\nMapped address spaces:\n\n          Start Addr           End Addr       Size     Offset  Perms  objfile\n      0x555555554000     0x555555555000     0x1000        0x0  r--p   /tmp/a.out\n      0x555555555000     0x555555556000     0x1000     0x1000  r-xp   /tmp/a.out\n      0x7ffffffde000     0x7ffffffff000    0x21000        0x0  rw-p   [stack]\n";
+        let ranges = parse_proc_mappings(mappings);
+        assert_eq!(ranges, [CodeRange { start: 0x555555555000, end: 0x555555556000, name: "/tmp/a.out".to_string() }]);
+        assert!(parse_proc_mappings("      0x400000           0x401000     0x1000        0x0 /tmp/a.out\n").is_empty());
+
+        let files = "Local exec file:\n\t`/tmp/a.out', file type elf64-x86-64.\n\tEntry point: 0x401040\n\t0x00000000004002a8 - 0x00000000004002c4 is .interp\n\t0x0000000000401040 - 0x00000000004011a5 is .text\n\t0x00007ffff7fc5000 - 0x00007ffff7fe4000 is .text in /lib64/ld-linux-x86-64.so.2\n";
+        let ranges = parse_info_files(files);
+        assert_eq!(ranges.len(), 2);
+        assert_eq!(ranges[1].name, ".text in /lib64/ld-linux-x86-64.so.2");
+
+        let words = decode_words(&[0x41, 0x10, 0x40, 0x00, 0x00, 0x00, 0x00, 0x00, 1, 2], 8, false);
+        assert_eq!(words, [0x401041]);
+        assert!(code_range(&ranges, words[0]).is_some());
+        assert!(code_range(&ranges, 0x4002b0).is_none());
+        assert_eq!(decode_words(&[0x08, 0x00, 0x01, 0x23], 4, true), [0x08000123]);
+    }
+}
@@ -0,0 +1,190 @@
+//! Debug Symbol Checks
+//!
+//! Loading a stripped binary succeeds, and only later tools fail with "No
+//! symbol table is loaded". GDB's "No debugging symbols found" message is
+//! caught when the file is loaded, and the ELF file's build ID and
+//! `.gnu_debuglink` are read to suggest where the separate debug info can
+//! be found.
+
+use crate::util::to_hex;
+use anyhow::{Context, Result};
+use serde::Serialize;
+
+/// Result of checking a loaded executable for debug info
+#[derive(Debug, Clone, Serialize)]
+pub struct SymbolCheck {
+    pub executable: String,
+    pub debug_info: bool,
+    /// GNU build ID, which names the debug file in `.build-id` directories
+    /// and on debuginfod servers
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub build_id: Option<String>,
+    /// Debug file name recorded in `.gnu_debuglink`
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub debuglink: Option<String>,
+    pub suggestions: Vec<String>,
+}
+
+impl SymbolCheck {
+    /// Check for an executable GDB reported no debugging symbols for
+    pub fn without_debug_info(executable: &str, elf: Option<&[u8]>) -> Self {
+        let sections = elf.and_then(elf_sections).unwrap_or_default();
+        let section = |name: &str| {
+            let section = sections.iter().find(|s| s.name == name)?;
+            elf?.get(section.offset..section.offset.checked_add(section.size)?)
+        };
+        let build_id = elf.and_then(build_id);
+        let debuglink = section(".gnu_debuglink").and_then(|data| {
+            let name = data.split(|&b| b == 0).next()?;
+            (!name.is_empty()).then(|| String::from_utf8_lossy(name).to_string())
+        });
+
+        let mut suggestions = Vec::new();
+        if let Some(link) = &debuglink {
+            suggestions.push(format!(
+                "The binary expects its debug info in '{}'. Put that file next to the executable, in a .debug/ subdirectory, or under the debug-file-directory (set debug-file-directory <dir>), then load the file again.",
+                link
+            ));
+        }
+        if let Some(id) = &build_id {
+            suggestions.push(format!(
+                "Install the distribution's debug package, or place the debug file at /usr/lib/debug/.build-id/{}/{}.debug.",
+                &id[..2.min(id.len())],
+                id.get(2..).unwrap_or_default()
+            ));
+            suggestions.push(
+                "Let GDB download it from debuginfod: start GDB with DEBUGINFOD_URLS set (e.g., https://debuginfod.elfutils.org/) and run 'set debuginfod enabled on'.".to_string(),
+            );
+        }
+        suggestions.push("Load a separate debug file directly with 'symbol-file <file>'.".to_string());
+        suggestions.push("If you build the program yourself, rebuild it with -g and without stripping.".to_string());
+
+        Self { executable: executable.to_string(), debug_info: false, build_id, debuglink, suggestions }
+    }
+
+    pub fn warning(&self) -> String {
+        format!(
+            "{} has no debugging symbols: functions, variables, source lines and types are unavailable and tools that need them fail with 'No symbol table is loaded'.",
+            self.executable
+        )
+    }
+}
+
+/// Whether GDB's output says a file has no debugging symbols
+pub fn reports_no_debug_symbols(output: &str) -> bool {
+    output.to_lowercase().contains("no debugging symbols found")
+}
+
+/// GNU build ID of an ELF file
+pub fn build_id(elf: &[u8]) -> Option<String> {
+    let sections = elf_sections(elf)?;
+    let note = sections.iter().find(|s| s.name == ".note.gnu.build-id")?;
+    parse_build_id_note(elf.get(note.offset..note.offset.checked_add(note.size)?)?)
+}
+
+/// Contents of a named section of an ELF file
+pub fn section_data<'a>(elf: &'a [u8], name: &str) -> Option<&'a [u8]> {
+    let sections = elf_sections(elf)?;
+    let section = sections.iter().find(|s| s.name == name)?;
+    elf.get(section.offset..section.offset.checked_add(section.size)?)
+}
+
+/// Read an ELF file for `SymbolCheck::without_debug_info`
+pub fn read_elf(path: &str) -> Result<Vec<u8>> {
+    std::fs::read(path).with_context(|| format!("Failed to read {}", path))
+}
+
+#[derive(Debug, Clone, PartialEq)]
+struct ElfSection {
+    name: String,
+    offset: usize,
+    size: usize,
+}
+
+/// Section headers of an ELF file (32/64-bit, either byte order)
+fn elf_sections(data: &[u8]) -> Option<Vec<ElfSection>> {
+    if !data.starts_with(b"\x7fELF") {
+        return None;
+    }
+    let wide = *data.get(4)? == 2;
+    let big = *data.get(5)? == 2;
+    let read = |offset: usize, size: usize| -> Option<usize> {
+        let bytes = data.get(offset..offset.checked_add(size)?)?;
+        let mut value: u64 = 0;
+        for i in 0..size {
+            let byte = if big { bytes[i] } else { bytes[size - 1 - i] };
+            value = (value << 8) | byte as u64;
+        }
+        usize::try_from(value).ok()
+    };
+    let word = if wide { 8 } else { 4 };
+
+    let (shoff, shentsize, shnum, shstrndx) = if wide {
+        (read(0x28, 8)?, read(0x3a, 2)?, read(0x3c, 2)?, read(0x3e, 2)?)
+    } else {
+        (read(0x20, 4)?, read(0x2e, 2)?, read(0x30, 2)?, read(0x32, 2)?)
+    };
+    let header = |index: usize| -> Option<(usize, usize, usize)> {
+        let base = shoff.checked_add(index.checked_mul(shentsize)?)?;
+        // sh_name, then sh_offset and sh_size after sh_type, sh_flags, sh_addr
+        let name = read(base, 4)?;
+        let offset = read(base + 8 + 2 * word, word)?;
+        let size = read(base + 8 + 3 * word, word)?;
+        Some((name, offset, size))
+    };
+
+    let (_, strtab, strtab_size) = header(shstrndx)?;
+    let names = data.get(strtab..strtab.checked_add(strtab_size)?)?;
+    let sections = (0..shnum)
+        .filter_map(header)
+        .map(|(name, offset, size)| {
+            let name = names.get(name..).unwrap_or_default();
+            let name = name.split(|&b| b == 0).next().unwrap_or_default();
+            ElfSection { name: String::from_utf8_lossy(name).to_string(), offset, size }
+        })
+        .collect();
+    Some(sections)
+}
+
+/// Build ID from an NT_GNU_BUILD_ID note. The note header fields are in
+/// the file's byte order, but namesz of "GNU\0" (4) tells which one it is.
+fn parse_build_id_note(note: &[u8]) -> Option<String> {
+    let field = |offset: usize| -> Option<u32> {
+        let bytes: [u8; 4] = note.get(offset..offset + 4)?.try_into().ok()?;
+        let little = u32::from_le_bytes(bytes);
+        Some(if little < 0x10000 { little } else { u32::from_be_bytes(bytes) })
+    };
+    let (namesz, descsz) = (field(0)? as usize, field(4)? as usize);
+    let desc = 12 + namesz.div_ceil(4) * 4;
+    if note.get(12..12 + namesz)? != b"GNU\0" {
+        return None;
+    }
+    let id = note.get(desc..desc + descsz)?;
+    Some(to_hex(id))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_symbol_check() {
+        assert!(reports_no_debug_symbols("Reading symbols from fw.elf...\n(No debugging symbols found in fw.elf)\n"));
+
+        let mut note = vec![4, 0, 0, 0, 4, 0, 0, 0, 3, 0, 0, 0];
+        note.extend_from_slice(b"GNU\0");
+        note.extend_from_slice(&[0xab, 0xcd, 0xef, 0x01]);
+        assert_eq!(parse_build_id_note(&note).as_deref(), Some("abcdef01"));
+
+        let exe = std::env::current_exe().unwrap();
+        let data = std::fs::read(&exe).unwrap();
+        if let Some(sections) = elf_sections(&data) {
+            assert!(sections.iter().any(|s| s.name == ".text"));
+        }
+
+        let check = SymbolCheck::without_debug_info("a.out", None);
+        assert!(!check.debug_info);
+        assert!(check.suggestions.iter().any(|s| s.contains("-g")));
+        assert!(check.warning().contains("No symbol table is loaded"));
+    }
+}
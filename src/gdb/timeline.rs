@@ -0,0 +1,143 @@
+//! Event Timeline
+//!
+//! One ordered record of what happened in a session: stops, breakpoint and
+//! thread notifications, GDB and target output, and the tool calls that
+//! caused them. It is served as the `gdb://events` resource so a session can
+//! be analysed afterwards without correlating separate logs.
+
+use crate::gdb::types::*;
+use crate::util::now_ms;
+use serde::Serialize;
+use std::collections::VecDeque;
+use std::time::Instant;
+
+/// Maximum number of events kept; older events are dropped
+const MAX_EVENTS: usize = 10_000;
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(tag = "kind", rename_all = "snake_case")]
+pub enum TimelineEntry {
+    Stopped {
+        reason: StopReason,
+        #[serde(skip_serializing_if = "Option::is_none")]
+        thread_id: Option<String>,
+        #[serde(skip_serializing_if = "Option::is_none")]
+        frame: Option<Frame>,
+    },
+    Running {
+        #[serde(skip_serializing_if = "Option::is_none")]
+        thread_id: Option<String>,
+    },
+    BreakpointCreated { breakpoint: Breakpoint },
+    BreakpointModified { breakpoint: Breakpoint },
+    BreakpointDeleted { number: String },
+    ThreadCreated { id: String, group_id: String },
+    ThreadExited { id: String, group_id: String },
+    ThreadSelected { id: String },
+    Error { message: String },
+    Output { channel: OutputChannel, text: String },
+    /// A tool call arrived
+    ToolCall { tool: String, arguments: serde_json::Value },
+    /// A tool call finished
+    ToolResult {
+        tool: String,
+        /// "ok", "error" or "failed", as in the audit log
+        status: String,
+        duration_ms: u64,
+    },
+}
+
+impl From<&GdbEvent> for TimelineEntry {
+    fn from(event: &GdbEvent) -> Self {
+        match event.clone() {
+            GdbEvent::Stopped { reason, frame, thread_id } => TimelineEntry::Stopped { reason, thread_id, frame },
+            GdbEvent::Running { thread_id } => TimelineEntry::Running { thread_id },
+            GdbEvent::BreakpointCreated { breakpoint } => TimelineEntry::BreakpointCreated { breakpoint },
+            GdbEvent::BreakpointModified { breakpoint } => TimelineEntry::BreakpointModified { breakpoint },
+            GdbEvent::BreakpointDeleted { number } => TimelineEntry::BreakpointDeleted { number },
+            GdbEvent::ThreadCreated { id, group_id } => TimelineEntry::ThreadCreated { id, group_id },
+            GdbEvent::ThreadExited { id, group_id } => TimelineEntry::ThreadExited { id, group_id },
+            GdbEvent::ThreadSelected { id } => TimelineEntry::ThreadSelected { id },
+            GdbEvent::Error { message } => TimelineEntry::Error { message },
+            GdbEvent::Output { channel, content } => TimelineEntry::Output { channel, text: content },
+        }
+    }
+}
+
+/// An entry with its position in the timeline
+#[derive(Debug, Clone, Serialize)]
+pub struct TimelineEvent {
+    pub seq: u64,
+    /// Microseconds since the timeline started, from a monotonic clock
+    pub t_us: u64,
+    /// Milliseconds since the Unix epoch
+    pub timestamp_ms: u64,
+    #[serde(flatten)]
+    pub entry: TimelineEntry,
+}
+
+#[derive(Debug)]
+pub struct Timeline {
+    origin: Instant,
+    next_seq: u64,
+    events: VecDeque<TimelineEvent>,
+}
+
+impl Default for Timeline {
+    fn default() -> Self {
+        Self { origin: Instant::now(), next_seq: 0, events: VecDeque::new() }
+    }
+}
+
+impl Timeline {
+    pub fn push(&mut self, entry: TimelineEntry) {
+        self.events.push_back(TimelineEvent {
+            seq: self.next_seq,
+            t_us: self.origin.elapsed().as_micros() as u64,
+            timestamp_ms: now_ms(),
+            entry,
+        });
+        self.next_seq += 1;
+        if self.events.len() > MAX_EVENTS {
+            self.events.pop_front();
+        }
+    }
+
+    /// Up to `limit` events with a sequence number of at least `since`,
+    /// oldest first
+    pub fn since(&self, since: u64, limit: usize) -> Vec<TimelineEvent> {
+        self.events.iter().filter(|e| e.seq >= since).take(limit).cloned().collect()
+    }
+
+    /// Sequence number the next event will get
+    pub fn next_seq(&self) -> u64 {
+        self.next_seq
+    }
+
+    /// Events dropped to stay within the size limit
+    pub fn dropped(&self) -> u64 {
+        self.next_seq - self.events.len() as u64
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_timeline_order() {
+        let mut timeline = Timeline::default();
+        timeline.push(TimelineEntry::ToolCall { tool: "gdb_continue".to_string(), arguments: serde_json::json!({}) });
+        timeline.push((&GdbEvent::Running { thread_id: Some("all".to_string()) }).into());
+        timeline.push((&GdbEvent::Output { channel: OutputChannel::Target, content: "hello\n".to_string() }).into());
+
+        let events = timeline.since(1, 10);
+        assert_eq!(events.iter().map(|e| e.seq).collect::<Vec<_>>(), [1, 2]);
+        assert!(events[0].t_us <= events[1].t_us);
+        let json = serde_json::to_value(&events[1]).unwrap();
+        assert_eq!(json["kind"], "output");
+        assert_eq!(json["channel"], "target");
+        assert_eq!(timeline.next_seq(), 3);
+        assert_eq!(timeline.dropped(), 0);
+    }
+}
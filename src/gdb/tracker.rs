@@ -0,0 +1,401 @@
+//! Session Tracker
+//!
+//! Bookkeeping derived from asynchronous GDB/MI records. The tracker is shared
+//! with the output reader thread, which updates it as records arrive.
+
+use crate::gdb::parser::parse_address;
+use crate::gdb::types::*;
+use crate::util::now_ms;
+use serde::Serialize;
+use std::collections::{BTreeMap, HashMap, VecDeque};
+
+/// Maximum number of breakpoint hits kept in the history
+const MAX_BREAKPOINT_HITS: usize = 10_000;
+
+/// Per-breakpoint hit summary
+#[derive(Debug, Clone, Serialize)]
+pub struct BreakpointHitSummary {
+    pub number: String,
+    pub hits: u64,
+    pub first_hit_ms: u64,
+    pub last_hit_ms: u64,
+    /// Hit counts keyed by location ("func at file:line" or address)
+    pub locations: BTreeMap<String, u64>,
+}
+
+/// A breakpoint as last reported by GDB, with where it was requested
+#[derive(Debug, Clone, Serialize)]
+pub struct TrackedBreakpoint {
+    #[serde(flatten)]
+    pub breakpoint: Breakpoint,
+    /// Location the breakpoint was requested at
+    pub requested_location: Option<String>,
+    /// Location GDB resolved the breakpoint to (None while pending)
+    pub actual_location: Option<String>,
+    /// GDB has not resolved the location yet
+    pub pending: bool,
+    /// GDB placed the breakpoint on a different line than requested
+    pub moved: bool,
+    /// Milliseconds since the Unix epoch
+    pub updated_ms: u64,
+    /// Short name given with `gdb_break_annotate` or at insertion
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub label: Option<String>,
+    /// Free-form note on why the breakpoint exists
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub note: Option<String>,
+}
+
+impl TrackedBreakpoint {
+    /// Track a breakpoint reported by GDB, keeping what is known only to
+    /// the server from its previous report
+    fn new(breakpoint: Breakpoint, requested_location: Option<String>, previous: Option<&TrackedBreakpoint>) -> Self {
+        let requested_location = requested_location
+            .or_else(|| previous.and_then(|p| p.requested_location.clone()))
+            .or_else(|| breakpoint.original_location.clone());
+        let pending = breakpoint.addr.as_deref() == Some("<PENDING>");
+        let actual_location = if pending {
+            None
+        } else {
+            match (&breakpoint.file, breakpoint.line, &breakpoint.func, &breakpoint.addr) {
+                (Some(file), Some(line), _, _) => Some(format!("{}:{}", file, line)),
+                (_, _, Some(func), _) => Some(func.clone()),
+                (_, _, _, addr) => addr.clone(),
+            }
+        };
+        let requested_line = requested_location.as_deref()
+            .map(|loc| loc.rsplit(':').next().unwrap_or(loc))
+            .and_then(|line| line.trim().parse::<u64>().ok());
+        let moved = !pending && requested_line.is_some() && breakpoint.line.is_some() && requested_line != breakpoint.line;
+
+        Self {
+            breakpoint,
+            requested_location,
+            actual_location,
+            pending,
+            moved,
+            updated_ms: now_ms(),
+            label: previous.and_then(|p| p.label.clone()),
+            note: previous.and_then(|p| p.note.clone()),
+        }
+    }
+}
+
+impl TrackedBreakpoint {
+    /// Whether the breakpoint was requested at or resolved to `location`
+    /// (a function name, `file:line` or `*address`). Files match by path
+    /// suffix, so `hello.c:4` matches a breakpoint in `/src/hello.c`.
+    pub fn matches(&self, location: &str) -> bool {
+        let location = location.trim();
+        let bp = &self.breakpoint;
+        if !bp.breakpoint_type.contains("breakpoint") {
+            return false;
+        }
+        let named = [&self.requested_location, &bp.original_location, &self.actual_location, &bp.func];
+        if named.iter().any(|l| l.as_deref() == Some(location)) {
+            return true;
+        }
+
+        if let Some(addr) = location.strip_prefix('*') {
+            return parse_address(addr).is_some() && parse_address(addr) == bp.addr.as_deref().and_then(parse_address);
+        }
+        if let Some((file, line)) = location.rsplit_once(':') {
+            if let Ok(line) = line.trim().parse::<u64>() {
+                let same_file = |path: &String| path == file || path.ends_with(&format!("/{}", file));
+                return bp.line == Some(line) && [&bp.file, &bp.fullname].into_iter().flatten().any(same_file);
+            }
+        }
+        false
+    }
+}
+
+/// Runtime bookkeeping for a GDB session
+#[derive(Debug, Default)]
+pub struct SessionTracker {
+    last_stop: Option<StopEvent>,
+    breakpoint_hits: VecDeque<BreakpointHit>,
+    hit_counts: HashMap<String, u64>,
+    breakpoints: HashMap<String, TrackedBreakpoint>,
+}
+
+impl SessionTracker {
+    /// Record a `*stopped` record
+    pub fn record_stop(&mut self, reason: &StopReason, results: &[MiResult], frame: Option<&Frame>, thread_id: Option<&str>) {
+        self.last_stop = Some(stop_event(reason, results, frame, thread_id));
+
+        if let Some(number) = stopped_breakpoint_number(reason, results) {
+            let count = self.hit_counts.entry(number.clone()).or_insert(0);
+            *count += 1;
+
+            self.breakpoint_hits.push_back(BreakpointHit {
+                number,
+                timestamp_ms: now_ms(),
+                reason: reason.clone(),
+                thread_id: thread_id.map(|s| s.to_string()),
+                frame: frame.cloned(),
+                hit_count: *count,
+            });
+            if self.breakpoint_hits.len() > MAX_BREAKPOINT_HITS {
+                self.breakpoint_hits.pop_front();
+            }
+        }
+    }
+
+    /// Get the most recent stop event
+    pub fn last_stop(&self) -> Option<&StopEvent> {
+        self.last_stop.as_ref()
+    }
+
+    /// Mutable access to the most recent stop event, for details added later
+    pub fn last_stop_mut(&mut self) -> Option<&mut StopEvent> {
+        self.last_stop.as_mut()
+    }
+
+    /// Get the most recent breakpoint hits (oldest first), optionally for one breakpoint
+    pub fn breakpoint_hits(&self, number: Option<&str>, limit: usize) -> Vec<BreakpointHit> {
+        let mut hits: Vec<BreakpointHit> = self
+            .breakpoint_hits
+            .iter()
+            .rev()
+            .filter(|h| number.is_none() || number == Some(h.number.as_str()))
+            .take(limit)
+            .cloned()
+            .collect();
+        hits.reverse();
+        hits
+    }
+
+    /// Summarize hits per breakpoint
+    pub fn breakpoint_hit_summary(&self) -> Vec<BreakpointHitSummary> {
+        let mut summaries: BTreeMap<String, BreakpointHitSummary> = BTreeMap::new();
+
+        for hit in &self.breakpoint_hits {
+            let summary = summaries.entry(hit.number.clone()).or_insert_with(|| BreakpointHitSummary {
+                number: hit.number.clone(),
+                hits: 0,
+                first_hit_ms: hit.timestamp_ms,
+                last_hit_ms: hit.timestamp_ms,
+                locations: BTreeMap::new(),
+            });
+            summary.last_hit_ms = hit.timestamp_ms;
+            *summary.locations.entry(describe_location(hit.frame.as_ref())).or_insert(0) += 1;
+        }
+
+        for summary in summaries.values_mut() {
+            summary.hits = self.hit_counts.get(&summary.number).copied().unwrap_or(0);
+        }
+
+        summaries.into_values().collect()
+    }
+
+    /// Record a breakpoint reported by GDB, keeping the previously requested
+    /// location unless a new one is given
+    pub fn record_breakpoint(&mut self, breakpoint: Breakpoint, requested_location: Option<&str>) -> &TrackedBreakpoint {
+        let number = breakpoint.number.clone();
+        let tracked = TrackedBreakpoint::new(breakpoint, requested_location.map(|s| s.to_string()), self.breakpoints.get(&number));
+        self.breakpoints.insert(number.clone(), tracked);
+        &self.breakpoints[&number]
+    }
+
+    /// Forget a deleted breakpoint
+    pub fn remove_breakpoint(&mut self, number: &str) {
+        self.breakpoints.remove(number);
+    }
+
+    /// Replace the tracked breakpoints with a full listing from GDB
+    pub fn sync_breakpoints(&mut self, breakpoints: Vec<Breakpoint>) {
+        let mut previous = std::mem::take(&mut self.breakpoints);
+        for bp in breakpoints {
+            let old = previous.remove(&bp.number);
+            self.breakpoints.insert(bp.number.clone(), TrackedBreakpoint::new(bp, None, old.as_ref()));
+        }
+    }
+
+    /// Set a breakpoint's label and note. None leaves a field unchanged and
+    /// an empty string clears it. Returns false if the breakpoint is unknown.
+    pub fn annotate_breakpoint(&mut self, number: &str, label: Option<&str>, note: Option<&str>) -> bool {
+        let Some(tracked) = self.breakpoints.get_mut(number) else {
+            return false;
+        };
+        let set = |field: &mut Option<String>, value: Option<&str>| {
+            if let Some(value) = value {
+                *field = Some(value.trim().to_string()).filter(|v| !v.is_empty());
+            }
+        };
+        set(&mut tracked.label, label);
+        set(&mut tracked.note, note);
+        true
+    }
+
+    /// Get the tracked breakpoints ordered by number
+    pub fn breakpoints(&self) -> Vec<TrackedBreakpoint> {
+        let mut breakpoints: Vec<TrackedBreakpoint> = self.breakpoints.values().cloned().collect();
+        breakpoints.sort_by_key(|b| b.breakpoint.number.parse::<u64>().unwrap_or(u64::MAX));
+        breakpoints
+    }
+
+    /// Get a tracked breakpoint by number
+    pub fn breakpoint(&self, number: &str) -> Option<TrackedBreakpoint> {
+        self.breakpoints.get(number).cloned()
+    }
+
+    /// Get the tracked breakpoints at a location, ordered by number
+    pub fn find_breakpoints(&self, location: &str) -> Vec<TrackedBreakpoint> {
+        self.breakpoints().into_iter().filter(|b| b.matches(location)).collect()
+    }
+
+    /// Forget all recorded breakpoint hits
+    pub fn clear_breakpoint_hits(&mut self) {
+        self.breakpoint_hits.clear();
+        self.hit_counts.clear();
+    }
+}
+
+/// The stop described by a `*stopped` record
+pub fn stop_event(reason: &StopReason, results: &[MiResult], frame: Option<&Frame>, thread_id: Option<&str>) -> StopEvent {
+    let find = |name: &str| results.iter()
+        .find(|r| r.variable == name)
+        .and_then(|r| match &r.value {
+            MiValue::String(s) => Some(s.clone()),
+            _ => None,
+        });
+    let signal = matches!(reason, StopReason::SignalReceived | StopReason::ExitedSignalled).then(|| StopSignal {
+        name: find("signal-name"),
+        meaning: find("signal-meaning"),
+        ..Default::default()
+    });
+    StopEvent {
+        reason: reason.clone(),
+        frame: frame.cloned(),
+        thread_id: thread_id.map(|s| s.to_string()),
+        timestamp_ms: now_ms(),
+        signal,
+        return_value: (*reason == StopReason::FunctionFinished).then(|| find("return-value")).flatten(),
+    }
+}
+
+/// Extract the breakpoint/watchpoint number from a `*stopped` record
+fn stopped_breakpoint_number(reason: &StopReason, results: &[MiResult]) -> Option<String> {
+    let find_string = |name: &str| {
+        results.iter()
+            .find(|r| r.variable == name)
+            .and_then(|r| match &r.value {
+                MiValue::String(s) => Some(s.clone()),
+                _ => None,
+            })
+    };
+    let find_tuple_number = |name: &str| {
+        results.iter()
+            .find(|r| r.variable == name)
+            .and_then(|r| match &r.value {
+                MiValue::Tuple(t) => t.get("number").and_then(|v| match v {
+                    MiValue::String(s) => Some(s.clone()),
+                    _ => None,
+                }),
+                _ => None,
+            })
+    };
+
+    match reason {
+        StopReason::BreakpointHit => find_string("bkptno"),
+        StopReason::WatchpointTrigger => find_tuple_number("wpt"),
+        StopReason::ReadWatchpointTrigger => find_tuple_number("hw-rwpt"),
+        StopReason::AccessWatchpointTrigger => find_tuple_number("hw-awpt"),
+        _ => None,
+    }
+}
+
+/// Describe a frame location for summaries
+fn describe_location(frame: Option<&Frame>) -> String {
+    match frame {
+        Some(f) => match (&f.func, &f.file, f.line) {
+            (Some(func), Some(file), Some(line)) => format!("{} at {}:{}", func, file, line),
+            (Some(func), _, _) => format!("{} ({})", func, f.addr),
+            _ => f.addr.clone(),
+        },
+        None => "<unknown>".to_string(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::gdb::parser::{parse_frame, MiParser};
+
+    fn record(tracker: &mut SessionTracker, line: &str) {
+        let parser = MiParser::new();
+        if let Some(MiOutputRecord::Async { results, .. }) = parser.parse_line(line).unwrap() {
+            let reason = results.iter()
+                .find(|r| r.variable == "reason")
+                .and_then(|r| MiParser::extract_string(&r.value))
+                .map(StopReason::from)
+                .unwrap();
+            let frame = parse_frame(&results);
+            tracker.record_stop(&reason, &results, frame.as_ref(), Some("1"));
+        }
+    }
+
+    #[test]
+    fn test_breakpoint_hits_are_counted_per_location() {
+        let mut tracker = SessionTracker::default();
+        record(&mut tracker, r#"*stopped,reason="breakpoint-hit",disp="keep",bkptno="1",frame={level="0",addr="0x1139",func="add",file="hello.c",line="4"},thread-id="1""#);
+        record(&mut tracker, r#"*stopped,reason="breakpoint-hit",disp="keep",bkptno="1",frame={level="0",addr="0x1139",func="add",file="hello.c",line="4"},thread-id="1""#);
+        record(&mut tracker, r#"*stopped,reason="watchpoint-trigger",wpt={number="2",exp="x"},frame={level="0",addr="0x1150",func="main"},thread-id="1""#);
+        record(&mut tracker, r#"*stopped,reason="end-stepping-range",frame={level="0",addr="0x1150",func="main"},thread-id="1""#);
+
+        let hits = tracker.breakpoint_hits(Some("1"), 10);
+        assert_eq!(hits.len(), 2);
+        assert_eq!(hits[1].hit_count, 2);
+
+        let summary = tracker.breakpoint_hit_summary();
+        assert_eq!(summary.len(), 2);
+        assert_eq!(summary[0].hits, 2);
+        assert_eq!(summary[0].locations.get("add at hello.c:4"), Some(&2));
+        assert_eq!(summary[1].number, "2");
+
+        record(&mut tracker, r#"*stopped,reason="signal-received",signal-name="SIGSEGV",signal-meaning="Segmentation fault",frame={level="0",addr="0x1160",func="main"},thread-id="1""#);
+        let signal = tracker.last_stop().unwrap().signal.clone().unwrap();
+        assert_eq!(signal.name.as_deref(), Some("SIGSEGV"));
+        assert_eq!(signal.meaning.as_deref(), Some("Segmentation fault"));
+
+        record(&mut tracker, r#"*stopped,reason="function-finished",frame={level="0",addr="0x1170",func="main"},gdb-result-var="$1",return-value="42",thread-id="1""#);
+        assert_eq!(tracker.last_stop().unwrap().return_value.as_deref(), Some("42"));
+    }
+
+    #[test]
+    fn test_pending_breakpoint_resolution_is_tracked() {
+        let parser = MiParser::new();
+        let notify = |tracker: &mut SessionTracker, line: &str| {
+            if let Some(MiOutputRecord::Notification { results, .. }) = parser.parse_line(line).unwrap() {
+                tracker.record_breakpoint(crate::gdb::parser::parse_breakpoint(&results).unwrap(), None);
+            }
+        };
+        let mut tracker = SessionTracker::default();
+        notify(&mut tracker, r#"=breakpoint-created,bkpt={number="1",type="breakpoint",disp="keep",enabled="y",addr="<PENDING>",pending="lib.c:10",times="0",original-location="lib.c:10"}"#);
+        let bp = &tracker.breakpoints()[0];
+        assert!(bp.pending);
+        assert_eq!(bp.requested_location.as_deref(), Some("lib.c:10"));
+        assert_eq!(bp.actual_location, None);
+        assert!(tracker.annotate_breakpoint("1", Some("lib init"), Some("crashes on second load")));
+        assert!(!tracker.annotate_breakpoint("9", Some("missing"), None));
+
+        notify(&mut tracker, r#"=breakpoint-modified,bkpt={number="1",type="breakpoint",disp="keep",enabled="y",addr="0x1139",func="f",file="lib.c",fullname="/src/lib.c",line="12",times="0",original-location="lib.c:10"}"#);
+        let bp = &tracker.breakpoints()[0];
+        assert!(!bp.pending);
+        assert!(bp.moved);
+        assert_eq!(bp.actual_location.as_deref(), Some("lib.c:12"));
+        assert_eq!(bp.label.as_deref(), Some("lib init"));
+        assert!(tracker.annotate_breakpoint("1", None, Some("")));
+        assert_eq!(tracker.breakpoints()[0].note, None);
+
+        assert_eq!(tracker.find_breakpoints("lib.c:10").len(), 1);
+        assert_eq!(tracker.find_breakpoints("src/lib.c:12").len(), 1);
+        assert_eq!(tracker.find_breakpoints("f").len(), 1);
+        assert_eq!(tracker.find_breakpoints("*0x1139").len(), 1);
+        assert!(tracker.find_breakpoints("lib.c:11").is_empty());
+        assert!(tracker.find_breakpoints("other/lib.c:12").is_empty());
+
+        tracker.remove_breakpoint("1");
+        assert!(tracker.breakpoints().is_empty());
+    }
+}
@@ -0,0 +1,61 @@
+//! SSH Tunnels
+//!
+//! Remote gdbservers that are only reachable through a jump host are
+//! connected through a local port forward (`ssh -L`). The ssh process is
+//! a managed process tied to the remote connection. GDB retries refused
+//! connections (`tcp auto-retry`), which covers the moment between ssh
+//! starting and the forward listening.
+
+use anyhow::Result;
+use std::net::{Ipv4Addr, TcpListener};
+use std::process::Command;
+
+/// Jump host and login for an SSH tunnel
+#[derive(Debug, Clone)]
+pub struct SshTunnel {
+    pub host: String,
+    pub user: Option<String>,
+    pub port: Option<u16>,
+}
+
+impl SshTunnel {
+    /// Command forwarding `local_port` on the loopback interface to
+    /// `target` (as seen from the jump host)
+    pub fn command(&self, local_port: u16, target: &str) -> Command {
+        let mut command = Command::new("ssh");
+        command.args(["-N", "-o", "BatchMode=yes", "-o", "ExitOnForwardFailure=yes"]);
+        command.arg("-L").arg(format!("127.0.0.1:{}:{}", local_port, target));
+        if let Some(user) = &self.user {
+            command.arg("-l").arg(user);
+        }
+        if let Some(port) = self.port {
+            command.arg("-p").arg(port.to_string());
+        }
+        // A host starting with '-' must not be taken for an option
+        command.arg("--").arg(&self.host);
+        command
+    }
+}
+
+/// Pick an unused local TCP port
+pub fn free_local_port() -> Result<u16> {
+    let listener = TcpListener::bind((Ipv4Addr::LOCALHOST, 0))?;
+    Ok(listener.local_addr()?.port())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_tunnel_command() {
+        let tunnel = SshTunnel { host: "jump.lab".to_string(), user: Some("ci".to_string()), port: Some(2222) };
+        let command = tunnel.command(40000, "board7:3333");
+        let args: Vec<_> = command.get_args().map(|a| a.to_string_lossy().into_owned()).collect();
+        assert_eq!(command.get_program(), "ssh");
+        assert!(args.windows(2).any(|w| w == ["-L", "127.0.0.1:40000:board7:3333"]));
+        assert!(args.windows(2).any(|w| w == ["-l", "ci"]));
+        assert!(args.windows(2).any(|w| w == ["-p", "2222"]));
+        assert_eq!(args[args.len() - 2..], ["--", "jump.lab"]);
+    }
+}
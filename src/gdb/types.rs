@@ -1,5 +1,8 @@
 //! GDB Machine Interface (MI) Type Definitions
 
+use crate::gdb::backend::BackendKind;
+use crate::gdb::hooks::Hooks;
+use crate::gdb::inferior::ProgramExit;
 use serde::{Deserialize, Serialize};
 
 /// GDB/MI result class types
@@ -90,6 +93,43 @@ impl From<String> for StopReason {
     }
 }
 
+impl StopReason {
+    /// The GDB/MI name of this stop reason
+    pub fn as_str(&self) -> &str {
+        match self {
+            StopReason::BreakpointHit => "breakpoint-hit",
+            StopReason::WatchpointTrigger => "watchpoint-trigger",
+            StopReason::ReadWatchpointTrigger => "read-watchpoint-trigger",
+            StopReason::AccessWatchpointTrigger => "access-watchpoint-trigger",
+            StopReason::FunctionFinished => "function-finished",
+            StopReason::LocationReached => "location-reached",
+            StopReason::WatchpointScope => "watchpoint-scope",
+            StopReason::EndSteppingRange => "end-stepping-range",
+            StopReason::ExitedSignalled => "exited-signalled",
+            StopReason::Exited => "exited",
+            StopReason::ExitedNormally => "exited-normally",
+            StopReason::SignalReceived => "signal-received",
+            StopReason::SolibEvent => "solib-event",
+            StopReason::Fork => "fork",
+            StopReason::Vfork => "vfork",
+            StopReason::SyscallEntry => "syscall-entry",
+            StopReason::SyscallReturn => "syscall-return",
+            StopReason::Unknown(s) => s,
+        }
+    }
+
+    /// Whether the target can no longer be stepped after this stop
+    pub fn is_terminal(&self) -> bool {
+        matches!(
+            self,
+            StopReason::Exited
+                | StopReason::ExitedNormally
+                | StopReason::ExitedSignalled
+                | StopReason::SignalReceived
+        )
+    }
+}
+
 /// GDB/MI value types
 #[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
 #[serde(untagged)]
@@ -111,6 +151,9 @@ pub enum MiOutputRecord {
         results: Vec<MiResult>,
     },
     Async {
+        // Parsed for completeness: async records are matched by class, and
+        // `*stopped` after a run command carries no usable token
+        #[allow(dead_code)]
         token: Option<u64>,
         class: AsyncClass,
         results: Vec<MiResult>,
@@ -156,9 +199,69 @@ pub struct Breakpoint {
     #[serde(default)]
     pub original_location: Option<String>,
     #[serde(default)]
+    pub what: Option<String>,
+    /// Thread the breakpoint is restricted to
+    #[serde(default)]
+    pub thread: Option<String>,
+    #[serde(default)]
     pub condition: Option<String>,
     #[serde(default)]
     pub ignore_count: Option<u64>,
+    /// Convenience variable counting the condition's evaluations
+    #[serde(skip)]
+    pub evaluation_counter: Option<String>,
+}
+
+/// Options for `-break-insert`
+#[derive(Debug, Clone, Default)]
+pub struct BreakInsertOptions {
+    /// Delete the breakpoint after the first hit
+    pub temporary: bool,
+    pub condition: Option<String>,
+    pub ignore_count: Option<u64>,
+    /// Only stop in this GDB thread
+    pub thread: Option<String>,
+    /// Only stop in this inferior (GDB inferior number)
+    pub inferior: Option<u64>,
+    /// Create the breakpoint even if the location cannot be resolved yet
+    /// (e.g. in a shared library that is not loaded)
+    pub pending: bool,
+    /// Count the condition's evaluations for `gdb_break_stats`
+    pub count_evaluations: bool,
+}
+
+/// Thread and frame to evaluate in, passed as `--thread`/`--frame` so the
+/// selected context is left untouched. `None` means the selected one.
+#[derive(Debug, Clone, Default)]
+pub struct FrameContext {
+    pub thread: Option<String>,
+    pub frame: Option<u64>,
+}
+
+/// What a `skip` entry matches
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum SkipKind {
+    /// A function by name (`skip function`)
+    Function,
+    /// Functions matching a regular expression (`skip -rfunction`)
+    Rfunction,
+    /// A source file by name (`skip file`)
+    File,
+    /// Source files matching a glob pattern (`skip -gfile`)
+    Gfile,
+}
+
+impl SkipKind {
+    /// CLI form of the `skip` command for this kind
+    pub fn command(&self) -> &'static str {
+        match self {
+            SkipKind::Function => "skip function",
+            SkipKind::Rfunction => "skip -rfunction",
+            SkipKind::File => "skip file",
+            SkipKind::Gfile => "skip -gfile",
+        }
+    }
 }
 
 /// Watchpoint type
@@ -207,6 +310,12 @@ pub struct Frame {
     pub line: Option<u64>,
     #[serde(default)]
     pub arch: Option<String>,
+    /// Shared library the code belongs to, for frames without debug info
+    #[serde(default)]
+    pub from: Option<String>,
+    /// Call inlined into the next outer frame
+    #[serde(default, skip_serializing_if = "std::ops::Not::not")]
+    pub inlined: bool,
 }
 
 /// Thread information
@@ -242,6 +351,127 @@ pub struct Variable {
     pub attributes: Option<Vec<String>>,
     #[serde(default)]
     pub children: Option<Vec<Variable>>,
+    /// The value is (or contains) `<optimized out>`
+    #[serde(default, skip_serializing_if = "std::ops::Not::not")]
+    pub optimized_out: bool,
+}
+
+/// A field of memory decoded as a struct
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct StructField {
+    /// Member name, or `[i]` for an array element
+    pub name: String,
+    #[serde(rename = "type", default, skip_serializing_if = "Option::is_none")]
+    pub var_type: Option<String>,
+    /// Byte offset from the start of the struct (absent for bitfields)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub offset: Option<u64>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub size: Option<u64>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub value: Option<String>,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub fields: Vec<StructField>,
+    /// Members or elements left out by the depth or element limits
+    #[serde(default, skip_serializing_if = "is_zero")]
+    pub omitted: u64,
+    /// Variable object the field was read from
+    #[serde(skip)]
+    pub varobj: String,
+    /// Number of children GDB reports for the field
+    #[serde(skip)]
+    pub children: u64,
+}
+
+fn is_zero(n: &u64) -> bool {
+    *n == 0
+}
+
+/// Memory decoded field by field as a type
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct StructDecode {
+    pub address: String,
+    #[serde(rename = "type")]
+    pub type_name: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub size: Option<u64>,
+    pub fields: Vec<StructField>,
+}
+
+/// How `gdb_walk_list` follows a list
+#[derive(Debug, Clone)]
+pub struct ListWalkOptions {
+    /// Member holding the pointer to the next link
+    pub next: String,
+    /// Members to read from each node; empty reads the whole node
+    pub fields: Vec<String>,
+    pub max_nodes: usize,
+    /// For intrusive lists, the node type and the member that embeds the
+    /// link. The walk then starts at `head->next` and ends back at `head`.
+    pub container: Option<(String, String)>,
+}
+
+/// A node visited by `gdb_walk_list`
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ListNode {
+    pub index: usize,
+    pub address: String,
+    #[serde(default, skip_serializing_if = "std::collections::BTreeMap::is_empty")]
+    pub fields: std::collections::BTreeMap<String, String>,
+    /// The whole node, when no fields were requested
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub value: Option<String>,
+}
+
+/// Result of walking a linked list
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ListWalk {
+    pub nodes: Vec<ListNode>,
+    /// "end" (null pointer), "head" (back at an intrusive list's head),
+    /// "cycle", "max_nodes", or "error"
+    pub stopped_because: String,
+    /// Index of the node a cycle led back to
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub cycle_to: Option<usize>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub error: Option<String>,
+}
+
+/// A variable object kept across calls so changes can be reported after
+/// each stop
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct VarWatch {
+    /// Variable object name assigned by GDB
+    pub name: String,
+    pub expression: String,
+    #[serde(default)]
+    pub var_type: Option<String>,
+    #[serde(default)]
+    pub value: Option<String>,
+    /// Re-evaluated in the selected frame on every update instead of the
+    /// frame it was created in
+    pub floating: bool,
+    /// Display format set with `-var-set-format` (GDB's default is "natural")
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub format: Option<String>,
+    /// Frozen objects are skipped by `-var-update`
+    #[serde(default)]
+    pub frozen: bool,
+}
+
+/// A variable object reported by `-var-update`
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct VarChange {
+    pub name: String,
+    /// Expression of the watch the object belongs to
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub expression: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub value: Option<String>,
+    /// "true", "false" (out of scope) or "invalid" (can no longer be evaluated)
+    pub in_scope: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub new_type: Option<String>,
 }
 
 /// Register information
@@ -259,7 +489,116 @@ pub struct MemoryContent {
     pub data: Vec<String>,
 }
 
+/// A memory region captured in a snapshot
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct MemorySnapshotRegion {
+    /// Address expression as given by the caller
+    pub expression: String,
+    /// Resolved start address
+    pub addr: String,
+    pub bytes: Vec<u8>,
+}
+
+/// A named snapshot of one or more memory regions
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct MemorySnapshot {
+    pub name: String,
+    /// Milliseconds since the Unix epoch
+    pub timestamp_ms: u64,
+    pub regions: Vec<MemorySnapshotRegion>,
+}
+
+/// A byte that changed between a snapshot and the current memory
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct MemoryByteChange {
+    pub addr: String,
+    /// Region the byte belongs to (address expression)
+    pub region: String,
+    pub offset: u64,
+    pub old: String,
+    pub new: String,
+}
+
+/// How `poll_memory` read memory while the target was running
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum PollMethod {
+    /// Read while running (non-stop mode or a stub that allows it)
+    Background,
+    /// Briefly interrupted the target for each sample
+    Interrupt,
+}
+
+/// One sample of a polled memory range
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct MemorySample {
+    /// Milliseconds since polling started
+    pub elapsed_ms: u64,
+    /// Contents as hex bytes in address order
+    pub data: String,
+    /// Differs from the previous sample
+    pub changed: bool,
+}
+
+/// Time series of a memory range sampled while the target runs
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct MemoryPoll {
+    pub addr: String,
+    pub length: u64,
+    pub method: PollMethod,
+    pub samples: Vec<MemorySample>,
+    /// Why polling ended early, if the target stopped by itself
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub stopped: Option<StopEvent>,
+}
+
+/// A disassembled instruction
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct AsmInstruction {
+    pub address: String,
+    #[serde(default)]
+    pub func_name: Option<String>,
+    #[serde(default)]
+    pub offset: Option<u64>,
+    pub inst: String,
+    /// Symbol of a branch/call target GDB printed without one
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub target_symbol: Option<String>,
+}
+
+/// Disassembly of a whole function
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct FunctionDisassembly {
+    pub function: String,
+    /// Current program counter, if the target is stopped
+    #[serde(default)]
+    pub pc: Option<u64>,
+    pub instructions: Vec<AsmInstruction>,
+}
+
+/// Addresses generated for one source line
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct LineTableEntry {
+    pub line: u64,
+    #[serde(default)]
+    pub file: Option<String>,
+    /// Start address of each block of code generated for the line
+    pub addresses: Vec<String>,
+}
+
+/// Source lines executed during a run segment
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct LineCoverage {
+    /// Lines that were executed, in line order
+    pub hit: Vec<u64>,
+    /// Lines that were not executed before the run ended
+    pub not_hit: Vec<u64>,
+    /// Why the run ended: "all_lines_hit", "timeout", or the stop reason
+    pub stopped_because: String,
+}
+
 /// Stack arguments
+#[allow(dead_code)] // `-stack-list-arguments` results are reported per frame
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct StackArgs {
     pub frame: Frame,
@@ -271,6 +610,20 @@ pub struct Argument {
     pub name: String,
     #[serde(default)]
     pub value: Option<String>,
+    /// The value is (or contains) `<optimized out>`
+    #[serde(default, skip_serializing_if = "std::ops::Not::not")]
+    pub optimized_out: bool,
+}
+
+/// A stack frame with its arguments and locals (`bt full`)
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct BacktraceFrame {
+    pub frame: Frame,
+    pub args: Vec<Argument>,
+    pub locals: Vec<Argument>,
+    /// Why the frame's variables could not be listed
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub error: Option<String>,
 }
 
 /// GDB session state
@@ -279,10 +632,486 @@ pub struct GdbSessionState {
     pub connected: bool,
     pub running: bool,
     pub target_remote: bool,
+    pub target: Option<String>,
     pub architecture: Option<String>,
     pub executable: Option<String>,
     pub current_thread: Option<String>,
     pub current_frame: Option<u64>,
+    /// Number of `*stopped` records seen so far
+    pub stop_count: u64,
+    /// Whether the connected target supports background execution
+    /// (None until probed after connecting)
+    pub target_async: Option<bool>,
+    /// Debugger version detected at startup (None if it could not be parsed)
+    #[serde(default)]
+    pub gdb_version: Option<GdbVersion>,
+    /// GDB's byte order disagrees with the executable
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub endian_warning: Option<String>,
+    /// The loaded executable has no debugging symbols
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub symbols_warning: Option<String>,
+    /// Target profile the session was started with
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub profile: Option<String>,
+    /// SVD file of the target, from its profile
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub svd_file: Option<String>,
+    /// QMP socket of the QEMU instance, from the profile
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub qmp: Option<String>,
+    /// How the program last exited (cleared when it is run again)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub program_exit: Option<ProgramExit>,
+    /// Commands defined by the bundled Python helpers loaded at startup
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub python_helpers: Vec<String>,
+    /// Thread run control is restricted to by `gdb_smp_pin`
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub pinned_thread: Option<String>,
+    /// Watchpoints disabled by `gdb_watch_suspend`, to re-enable on resume
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub suspended_watchpoints: Vec<String>,
+    /// Convenience variables set with `gdb_convenience_set`, by name (without
+    /// `$`), with the expression last assigned
+    #[serde(default, skip_serializing_if = "std::collections::BTreeMap::is_empty")]
+    pub convenience_variables: std::collections::BTreeMap<String, String>,
+    /// Interrupts are PC samples: their stops are kept in `pc_sample`
+    /// instead of the stop history and timeline
+    #[serde(skip)]
+    pub pc_sampling: bool,
+    #[serde(skip)]
+    pub pc_sample: Option<StopEvent>,
+}
+
+/// Debugger version reported by `-gdb-version`
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct GdbVersion {
+    /// First line of the banner, e.g. "GNU gdb (GDB) 14.2"
+    pub banner: String,
+    pub major: u32,
+    pub minor: u32,
+    /// Target the debugger was configured for, e.g. "arm-none-eabi"
+    #[serde(default)]
+    pub target: Option<String>,
+}
+
+impl GdbVersion {
+    /// Whether this GDB provides an MI feature
+    pub fn supports(&self, feature: MiFeature) -> bool {
+        (self.major, self.minor) >= feature.min_version()
+    }
+}
+
+/// MI commands and options that older GDB releases lack
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum MiFeature {
+    /// `-data-list-register-values --skip-unavailable`
+    SkipUnavailable,
+    /// `-symbol-info-functions` and friends
+    SymbolInfo,
+}
+
+impl MiFeature {
+    /// First GDB release (major, minor) with the feature
+    pub fn min_version(self) -> (u32, u32) {
+        match self {
+            MiFeature::SkipUnavailable => (7, 7),
+            MiFeature::SymbolInfo => (10, 1),
+        }
+    }
+}
+
+/// A function found by a symbol search
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct FunctionSymbol {
+    pub name: String,
+    /// Declaration as GDB prints it, for functions with debug info
+    #[serde(default)]
+    pub declaration: Option<String>,
+    #[serde(default)]
+    pub file: Option<String>,
+    #[serde(default)]
+    pub line: Option<u64>,
+    /// Address, for symbols without debug info
+    #[serde(default)]
+    pub address: Option<String>,
+}
+
+/// A global or static variable and its current value
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct GlobalVariable {
+    pub name: String,
+    /// Declaration as GDB prints it, for variables with debug info
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub declaration: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub file: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub line: Option<u64>,
+    /// Address, for symbols without debug info
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub address: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub value: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub error: Option<String>,
+}
+
+/// A recorded `*stopped` event
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct StopEvent {
+    pub reason: StopReason,
+    #[serde(default)]
+    pub frame: Option<Frame>,
+    #[serde(default)]
+    pub thread_id: Option<String>,
+    /// Milliseconds since the Unix epoch
+    pub timestamp_ms: u64,
+    /// Signal that stopped the program, for `signal-received` stops
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub signal: Option<StopSignal>,
+    /// Value returned by the finished function, for `function-finished` stops
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub return_value: Option<String>,
+}
+
+/// Signal details of a `signal-received` stop
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct StopSignal {
+    pub name: Option<String>,
+    pub meaning: Option<String>,
+    /// Faulting address from `$_siginfo`, for SIGSEGV, SIGBUS and similar
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub fault_address: Option<String>,
+    /// Meaning of `si_code`, e.g. "Address not mapped to object"
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub cause: Option<String>,
+}
+
+/// Decoded `$_siginfo` of the current thread
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SigInfo {
+    pub signo: i64,
+    pub name: Option<String>,
+    pub errno: i64,
+    pub code: i64,
+    /// Meaning of `si_code`
+    pub cause: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub fault_address: Option<String>,
+    /// Process that sent the signal, for signals sent by kill() and the like
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub sender_pid: Option<i64>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub sender_uid: Option<i64>,
+    /// `$_siginfo` as printed by GDB
+    pub raw: String,
+}
+
+/// The current thread's `errno`, decoded
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ErrnoValue {
+    pub value: i64,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub name: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub description: Option<String>,
+    /// Expression that read the value
+    pub source: String,
+    /// C library call in progress on the stack, if any
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub libc_call: Option<LibcCall>,
+}
+
+/// A C library function on the stack and the code that called it
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct LibcCall {
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub function: Option<String>,
+    pub library: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub caller: Option<Frame>,
+}
+
+/// Parts of a printed GDB value such as `32 ' '`, `0x401136 <main+4>` or
+/// `(char *) 0x402004 "hello"`
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct ParsedValue {
+    /// Integer value, including characters, booleans and pointers
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub integer: Option<i128>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub float: Option<f64>,
+    /// Character or string GDB printed after the number
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub text: Option<String>,
+    /// Symbol GDB printed after an address, e.g. `main+4`
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub symbol: Option<String>,
+}
+
+/// Structured result of `gdb_evaluate`
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct TypedValue {
+    pub expression: String,
+    /// Value as printed by GDB
+    pub value: String,
+    /// Type reported by `whatis`
+    #[serde(rename = "type", default, skip_serializing_if = "Option::is_none")]
+    pub value_type: Option<String>,
+    #[serde(flatten)]
+    pub parsed: ParsedValue,
+    /// Address of the value, for lvalues
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub address: Option<String>,
+}
+
+/// Value of a thread-local variable in one thread, from `gdb_tls_read`
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ThreadValue {
+    pub thread_id: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub value: Option<String>,
+    /// Address of the thread's copy
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub address: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub error: Option<String>,
+}
+
+/// One instruction step recorded by a step-until tool
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct StepTraceEntry {
+    pub step: u64,
+    pub addr: String,
+    #[serde(default)]
+    pub func: Option<String>,
+    #[serde(default)]
+    pub file: Option<String>,
+    #[serde(default)]
+    pub line: Option<u64>,
+}
+
+/// Result of stepping until a watched value changes
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct StepUntilChange {
+    pub changed: bool,
+    /// Why stepping ended: "changed", "max_steps", "timeout", or the stop reason
+    pub stopped_because: String,
+    pub steps: u64,
+    pub before: String,
+    pub after: String,
+    /// The most recent steps, oldest first
+    pub trace: Vec<StepTraceEntry>,
+}
+
+/// Control-flow class of an instruction
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum BranchKind {
+    /// Conditional or unconditional jump
+    Jump,
+    /// Call, including system calls and supervisor traps
+    Call,
+    Return,
+}
+
+impl BranchKind {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            BranchKind::Jump => "jump",
+            BranchKind::Call => "call",
+            BranchKind::Return => "return",
+        }
+    }
+}
+
+/// An instruction executed by `gdb_step_asm_until_branch`
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct AsmStep {
+    pub addr: String,
+    pub inst: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub func: Option<String>,
+}
+
+/// Result of instruction-stepping until the next branch
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct StepUntilBranch {
+    /// Why stepping ended: "branch", "max_steps", "timeout", or the stop reason
+    pub stopped_because: String,
+    /// Instructions stepped, including the branch when it was executed
+    pub steps: u64,
+    /// The most recent instructions executed before the branch, oldest first
+    pub path: Vec<AsmStep>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub branch: Option<AsmStep>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub kind: Option<BranchKind>,
+    /// Where execution went after the branch, when it was executed
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub target: Option<String>,
+}
+
+/// Calls made to one function, from a call summary
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct FunctionCalls {
+    pub function: String,
+    pub calls: u64,
+}
+
+/// Functions called between two stops, read from the branch trace
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CallSummary {
+    pub total_calls: u64,
+    /// Called functions, most calls first
+    pub functions: Vec<FunctionCalls>,
+    /// Function segments read from `record function-call-history`
+    pub segments: u64,
+    /// The trace had more segments than were read
+    pub truncated: bool,
+    /// The target was interrupted when the time budget ran out
+    pub interrupted: bool,
+}
+
+/// What to report around an instruction step besides the new PC
+#[derive(Debug, Clone, Default)]
+pub struct StepWatch {
+    /// Disassemble each executed instruction
+    pub instructions: bool,
+    /// Register names, without `$`
+    pub registers: Vec<String>,
+    /// Memory ranges as (address expression, length)
+    pub memory: Vec<(String, u64)>,
+}
+
+impl StepWatch {
+    pub fn is_empty(&self) -> bool {
+        !self.instructions && self.registers.is_empty() && self.memory.is_empty()
+    }
+}
+
+/// A register or memory range read before and after a step
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct WatchedValue {
+    pub name: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub before: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub after: Option<String>,
+    pub changed: bool,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub error: Option<String>,
+}
+
+/// Result of `gdb_stepi`/`gdb_nexti` with a [`StepWatch`]
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct InstructionStep {
+    pub count: u64,
+    pub pc: String,
+    /// Instructions executed, in order
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub executed: Vec<AsmStep>,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub registers: Vec<WatchedValue>,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub memory: Vec<WatchedValue>,
+}
+
+/// Result of repeating a stepping command
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct StepSequence {
+    pub requested: u64,
+    pub completed: u64,
+    /// Why stepping ended: "completed", "error", "no-stop" (a step did
+    /// not report where it stopped), or the stop reason that interrupted it
+    /// (e.g. "breakpoint-hit")
+    pub stopped_because: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub error: Option<String>,
+    /// Where each step stopped, in order
+    pub stops: Vec<StepTraceEntry>,
+}
+
+/// One breakpoint hit passed through by `gdb_continue` with `hit_count`
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct HitLogEntry {
+    /// Hit of this run, counting from 1
+    pub hit: u64,
+    /// Times the breakpoint had been hit in the session, including this hit
+    pub hit_count: u64,
+    pub addr: String,
+    #[serde(default)]
+    pub func: Option<String>,
+    #[serde(default)]
+    pub file: Option<String>,
+    #[serde(default)]
+    pub line: Option<u64>,
+    #[serde(default)]
+    pub thread_id: Option<String>,
+}
+
+/// Result of continuing until the Nth hit of a breakpoint
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct HitCountRun {
+    /// Breakpoint counted: the one asked for, or the first one hit
+    #[serde(default)]
+    pub breakpoint: Option<String>,
+    pub requested: u64,
+    /// Why the run ended: "completed", "timeout", or the stop that
+    /// interrupted it (e.g. "exited-normally", "breakpoint 3 hit")
+    pub stopped_because: String,
+    /// Every hit of the run in order, the last being where it stopped
+    pub hits: Vec<HitLogEntry>,
+}
+
+/// One return made by a multi-frame `gdb_finish`
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct FinishReturn {
+    /// Function that returned
+    #[serde(default)]
+    pub returned_from: Option<String>,
+    /// None for void functions
+    #[serde(default)]
+    pub return_value: Option<String>,
+    /// Where execution stopped in the caller
+    pub addr: String,
+    #[serde(default)]
+    pub func: Option<String>,
+    #[serde(default)]
+    pub file: Option<String>,
+    #[serde(default)]
+    pub line: Option<u64>,
+}
+
+/// Result of finishing several frames in a row
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct FinishSequence {
+    pub requested: u64,
+    pub completed: u64,
+    /// Why finishing ended: "completed", "error", "timeout", or the stop
+    /// reason that interrupted it (e.g. "breakpoint-hit")
+    pub stopped_because: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub error: Option<String>,
+    /// Each return, innermost first
+    pub returns: Vec<FinishReturn>,
+}
+
+/// A recorded breakpoint or watchpoint hit
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct BreakpointHit {
+    pub number: String,
+    /// Milliseconds since the Unix epoch
+    pub timestamp_ms: u64,
+    pub reason: StopReason,
+    #[serde(default)]
+    pub thread_id: Option<String>,
+    #[serde(default)]
+    pub frame: Option<Frame>,
+    /// Number of times this breakpoint had been hit, including this hit
+    pub hit_count: u64,
 }
 
 /// GDB event types
@@ -316,6 +1145,7 @@ pub enum GdbEvent {
     ThreadSelected {
         id: String,
     },
+    #[allow(dead_code)] // the reader reports MI errors through command results
     Error {
         message: String,
     },
@@ -325,13 +1155,49 @@ pub enum GdbEvent {
     },
 }
 
-#[derive(Debug, Clone, PartialEq)]
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
 pub enum OutputChannel {
     Console,
     Target,
     Log,
 }
 
+/// How an interrupt request reached the target
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum InterruptMethod {
+    /// `-exec-interrupt` (requires mi-async and an async-capable target)
+    Mi,
+    /// SIGINT to the GDB process, which GDB forwards to the target
+    /// (a Ctrl-C byte for remote stubs)
+    Signal,
+}
+
+/// Result of a GDB backend health check
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct HealthReport {
+    /// The GDB child process has not exited
+    pub process_alive: bool,
+    /// Exit status if the GDB process has exited
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub exit_status: Option<String>,
+    /// GDB answered a trivial MI command within the timeout
+    pub responsive: bool,
+    /// Round-trip time of the trivial MI command
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub latency_ms: Option<u64>,
+    /// The inferior is running
+    pub running: bool,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub target: Option<String>,
+    /// The remote target answered a register read (None when not probed)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub target_responsive: Option<bool>,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub errors: Vec<String>,
+}
+
 /// GDB configuration
 #[derive(Debug, Clone)]
 pub struct GdbConfig {
@@ -339,6 +1205,22 @@ pub struct GdbConfig {
     pub gdb_args: Vec<String>,
     pub timeout_ms: u64,
     pub architecture: Option<String>,
+    /// Extra environment variables for the GDB process
+    pub env: std::collections::HashMap<String, String>,
+    /// Working directory for the GDB process
+    pub working_dir: Option<String>,
+    /// Send SIGINT to GDB when a command times out (off by default)
+    pub interrupt_on_timeout: bool,
+    /// Debugger driven over MI
+    pub backend: BackendKind,
+    /// How long to wait for the debugger's first prompt
+    pub startup_timeout_ms: u64,
+    /// GDB data directory (`--data-directory`) for this session
+    pub data_directory: Option<String>,
+    /// Load the bundled Python helpers after startup
+    pub python_helpers: bool,
+    /// Commands run after starting, connecting and loading
+    pub hooks: Hooks,
 }
 
 impl Default for GdbConfig {
@@ -348,11 +1230,20 @@ impl Default for GdbConfig {
             gdb_args: vec!["--interpreter=mi2".to_string()],
             timeout_ms: 30000,
             architecture: None,
+            env: std::collections::HashMap::new(),
+            working_dir: None,
+            interrupt_on_timeout: false,
+            backend: BackendKind::Gdb,
+            startup_timeout_ms: 10000,
+            data_directory: None,
+            python_helpers: false,
+            hooks: Hooks::default(),
         }
     }
 }
 
 /// Remote target configuration
+#[allow(dead_code)] // gdb_target_connect builds target strings from its arguments
 #[derive(Debug, Clone)]
 pub enum RemoteTargetConfig {
     Tcp {
@@ -365,6 +1256,7 @@ pub enum RemoteTargetConfig {
     },
 }
 
+#[allow(dead_code)]
 impl RemoteTargetConfig {
     pub fn to_target_string(&self) -> String {
         match self {
@@ -388,3 +1280,92 @@ impl std::fmt::Display for GdbError {
 }
 
 impl std::error::Error for GdbError {}
+
+impl GdbError {
+    /// Wrap the message of an MI `^error` result, prefixed with what was
+    /// being attempted
+    pub fn from_mi(context: &str, msg: &str) -> Self {
+        Self {
+            code: Self::classify(msg).to_string(),
+            message: format!("{}: {}", context, msg),
+        }
+    }
+
+    /// Classify a GDB error message so callers can branch on the kind of
+    /// failure instead of parsing prose
+    pub fn classify(msg: &str) -> &'static str {
+        const KINDS: &[(&str, &[&str])] = &[
+            ("connection_lost", &[
+                "Remote connection closed",
+                "Remote communication error",
+                "Connection timed out",
+                "Connection refused",
+                "Remote target disconnected",
+                "Remote replied unexpectedly",
+            ]),
+            ("target_running", &[
+                "while the target is running",
+                "while the selected thread is running",
+                "Selected thread is running",
+            ]),
+            ("not_running", &[
+                "The program is not being run",
+                "No registers",
+                "has no registers now",
+                "No stack",
+                "No frame selected",
+                "No thread selected",
+                "The program has no",
+            ]),
+            ("bad_address", &[
+                "Cannot access memory at address",
+                "Cannot insert breakpoint",
+                "Cannot insert hardware breakpoint",
+                "Invalid address",
+                "Unable to read memory",
+                "Could not read memory",
+            ]),
+            ("no_symbol", &[
+                "No symbol",
+                "not defined",
+                "No source file named",
+                "No line ",
+                "No function contains",
+                "Invalid thread id",
+            ]),
+            ("unsupported", &[
+                "Undefined MI command",
+                "Undefined command",
+                "not supported",
+                "does not support",
+                "Unknown option",
+                "unrecognized option",
+            ]),
+        ];
+
+        KINDS
+            .iter()
+            .find(|(_, patterns)| patterns.iter().any(|p| msg.contains(p)))
+            .map(|(code, _)| *code)
+            .unwrap_or("gdb_error")
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_classify_gdb_errors() {
+        assert_eq!(GdbError::classify("No symbol \"foo\" in current context."), "no_symbol");
+        assert_eq!(GdbError::classify("The program is not being run."), "not_running");
+        assert_eq!(GdbError::classify("Cannot access memory at address 0x0"), "bad_address");
+        assert_eq!(GdbError::classify("Remote connection closed"), "connection_lost");
+        assert_eq!(GdbError::classify("Undefined MI command: symbol-info-functions"), "unsupported");
+        assert_eq!(GdbError::classify("Something else went wrong"), "gdb_error");
+
+        let error = GdbError::from_mi("Failed to evaluate expression", "No registers.");
+        assert_eq!(error.code, "not_running");
+        assert_eq!(error.message, "Failed to evaluate expression: No registers.");
+    }
+}
@@ -0,0 +1,161 @@
+//! Exception Vector Tables
+//!
+//! Reads a Cortex-M vector table (the initial stack pointer followed by
+//! handler addresses) so breakpoints can be set on the handlers. Interrupt
+//! names come from the `<interrupt>` entries of a CMSIS-SVD file when one is
+//! given. Several vectors often share a handler (e.g. `Default_Handler`),
+//! so vectors are grouped by handler address.
+
+use crate::gdb::registers::EXCEPTION_NUMBERS;
+use serde::Serialize;
+
+/// Address of the Vector Table Offset Register (SCB->VTOR)
+pub const VTOR: u64 = 0xE000_ED08;
+
+/// Number of Cortex-M system exception vectors, including the initial SP
+pub const SYSTEM_VECTORS: usize = 16;
+
+/// A vector table entry
+#[derive(Debug, Clone, Serialize)]
+pub struct Vector {
+    pub number: usize,
+    pub name: String,
+    /// Handler address with the Thumb bit cleared
+    pub handler: u64,
+}
+
+/// Breakpoint on a handler shared by one or more vectors
+#[derive(Debug, Clone, Serialize)]
+pub struct HandlerBreakpoint {
+    pub address: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub function: Option<String>,
+    pub vectors: Vec<String>,
+    /// Breakpoint number, if one was inserted or already existed
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub breakpoint: Option<String>,
+    /// The breakpoint already existed
+    pub existing: bool,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub error: Option<String>,
+}
+
+/// Result of `gdb_break_vectors`
+#[derive(Debug, Clone, Serialize)]
+pub struct VectorBreakpoints {
+    pub base: String,
+    pub handlers: Vec<HandlerBreakpoint>,
+}
+
+/// Interrupt names by IRQ number from a CMSIS-SVD file
+pub fn parse_svd_interrupts(svd: &str) -> Vec<(usize, String)> {
+    let mut interrupts: Vec<(usize, String)> = svd
+        .split("<interrupt>")
+        .skip(1)
+        .filter_map(|block| {
+            let block = block.split("</interrupt>").next()?;
+            let name = xml_text(block, "name")?;
+            let value = xml_text(block, "value")?.parse().ok()?;
+            Some((value, name.to_string()))
+        })
+        .collect();
+    // Peripherals sharing an interrupt list it more than once
+    interrupts.sort();
+    interrupts.dedup_by_key(|(value, _)| *value);
+    interrupts
+}
+
+fn xml_text<'a>(block: &'a str, tag: &str) -> Option<&'a str> {
+    let start = block.find(&format!("<{}>", tag))? + tag.len() + 2;
+    let end = block[start..].find(&format!("</{}>", tag))? + start;
+    Some(block[start..end].trim())
+}
+
+/// Name of vector `number`, using SVD interrupt names for IRQs
+pub fn vector_name(number: usize, interrupts: &[(usize, String)]) -> String {
+    if number < SYSTEM_VECTORS {
+        return match number {
+            0 => "InitialSP".to_string(),
+            _ => EXCEPTION_NUMBERS
+                .iter()
+                .find(|(n, _)| *n == number as u64)
+                .map(|(_, name)| name.to_string())
+                .unwrap_or_else(|| format!("Reserved{}", number)),
+        };
+    }
+    let irq = number - SYSTEM_VECTORS;
+    interrupts
+        .iter()
+        .find(|(value, _)| *value == irq)
+        .map(|(_, name)| name.clone())
+        .unwrap_or_else(|| format!("IRQ{}", irq))
+}
+
+/// Decode the handler entries of a vector table read as little-endian
+/// words. The initial SP and empty entries are skipped.
+pub fn decode_vectors(bytes: &[u8], interrupts: &[(usize, String)]) -> Vec<Vector> {
+    bytes
+        .chunks_exact(4)
+        .enumerate()
+        .skip(1)
+        .map(|(number, word)| (number, u32::from_le_bytes(word.try_into().unwrap()) as u64))
+        .filter(|(_, handler)| *handler != 0)
+        .map(|(number, handler)| Vector { number, name: vector_name(number, interrupts), handler: handler & !1 })
+        .collect()
+}
+
+/// Whether a vector matches a selection by name, number, or `IRQn`
+pub fn vector_selected(vector: &Vector, selection: &[String]) -> bool {
+    selection.is_empty()
+        || selection.iter().any(|s| {
+            s.eq_ignore_ascii_case(&vector.name)
+                || s.parse::<usize>().ok() == Some(vector.number)
+                || s.to_ascii_uppercase()
+                    .strip_prefix("IRQ")
+                    .and_then(|n| n.parse::<usize>().ok())
+                    .is_some_and(|irq| irq + SYSTEM_VECTORS == vector.number)
+        })
+}
+
+/// Group vectors by handler address, in vector order
+pub fn group_by_handler(vectors: &[Vector]) -> Vec<(u64, Vec<String>)> {
+    let mut groups: Vec<(u64, Vec<String>)> = Vec::new();
+    for vector in vectors {
+        match groups.iter_mut().find(|(handler, _)| *handler == vector.handler) {
+            Some((_, names)) => names.push(vector.name.clone()),
+            None => groups.push((vector.handler, vec![vector.name.clone()])),
+        }
+    }
+    groups
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_decode_and_group_vectors() {
+        let svd = "<interrupts><interrupt><name>WWDG</name><value>0</value></interrupt>\
+                   <interrupt><name>USART1</name><description>x</description><value>2</value></interrupt></interrupts>";
+        let interrupts = parse_svd_interrupts(svd);
+        assert_eq!(interrupts, [(0, "WWDG".to_string()), (2, "USART1".to_string())]);
+
+        // SP, Reset, NMI, HardFault, 12 unused system vectors, then WWDG, IRQ1, USART1
+        let mut words = vec![0x2000_8000u32, 0x101, 0x201, 0x301];
+        words.extend([0u32; 12]);
+        words.extend([0x201, 0x201, 0x401]);
+        let bytes: Vec<u8> = words.iter().flat_map(|w| w.to_le_bytes()).collect();
+
+        let vectors = decode_vectors(&bytes, &interrupts);
+        let names: Vec<&str> = vectors.iter().map(|v| v.name.as_str()).collect();
+        assert_eq!(names, ["Reset", "NMI", "HardFault", "WWDG", "IRQ1", "USART1"]);
+        assert_eq!(vectors[1].handler, 0x200);
+
+        let groups = group_by_handler(&vectors);
+        assert_eq!(groups[1], (0x200, vec!["NMI".to_string(), "WWDG".to_string(), "IRQ1".to_string()]));
+
+        let selection = vec!["hardfault".to_string(), "IRQ2".to_string()];
+        let selected: Vec<&str> = vectors.iter().filter(|v| vector_selected(v, &selection)).map(|v| v.name.as_str()).collect();
+        assert_eq!(selected, ["HardFault", "USART1"]);
+    }
+}
@@ -0,0 +1,439 @@
+//! x86 System Structures
+//!
+//! Decoding of GDT/IDT descriptors and page table walks for kernel and
+//! hobby-OS debugging under QEMU. Descriptor tables are read through GDB's
+//! memory reads at their linear base; page table entries live at physical
+//! addresses, which are read through the QEMU monitor (`xp`).
+
+use crate::gdb::parser::parse_address;
+use crate::gdb::registers::field;
+use anyhow::{anyhow, Result};
+use serde::Serialize;
+
+/// A descriptor table register
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum DescriptorTable {
+    Gdt,
+    Idt,
+}
+
+impl DescriptorTable {
+    /// Name of the register in QEMU's `info registers` output
+    fn monitor_name(&self) -> &'static str {
+        match self {
+            DescriptorTable::Gdt => "GDT=",
+            DescriptorTable::Idt => "IDT=",
+        }
+    }
+}
+
+/// A decoded GDT or IDT entry
+#[derive(Debug, Clone, Serialize)]
+pub struct Descriptor {
+    /// Entry index (the interrupt vector for the IDT)
+    pub index: u64,
+    /// Selector referring to a GDT entry
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub selector: Option<String>,
+    pub raw: String,
+    pub kind: &'static str,
+    pub dpl: u64,
+    pub present: bool,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub base: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub limit: Option<String>,
+    /// Code segment selector of a gate
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub target_selector: Option<String>,
+    /// Handler address of a gate
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub offset: Option<String>,
+    /// Interrupt stack table slot of a 64-bit gate (0 = none)
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub ist: Option<u64>,
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub flags: Vec<&'static str>,
+}
+
+/// A descriptor table read from the target
+#[derive(Debug, Clone, Serialize)]
+pub struct DescriptorTableDump {
+    pub table: DescriptorTable,
+    pub base: String,
+    pub limit: String,
+    pub long_mode: bool,
+    /// Present entries; null and not-present entries are omitted
+    pub entries: Vec<Descriptor>,
+    /// More entries exist beyond the ones read
+    pub truncated: bool,
+}
+
+fn system_kind(kind: u64, long_mode: bool) -> &'static str {
+    match (kind, long_mode) {
+        (0x1, _) => "tss-16 (available)",
+        (0x2, _) => "ldt",
+        (0x3, _) => "tss-16 (busy)",
+        (0x4, _) => "call gate-16",
+        (0x5, _) => "task gate",
+        (0x6, _) => "interrupt gate-16",
+        (0x7, _) => "trap gate-16",
+        (0x9, true) => "tss-64 (available)",
+        (0x9, false) => "tss-32 (available)",
+        (0xB, true) => "tss-64 (busy)",
+        (0xB, false) => "tss-32 (busy)",
+        (0xC, _) => "call gate",
+        (0xE, _) => "interrupt gate",
+        (0xF, _) => "trap gate",
+        _ => "reserved",
+    }
+}
+
+/// Split little-endian bytes into 64-bit words
+fn words(bytes: &[u8]) -> Vec<u64> {
+    bytes
+        .chunks_exact(8)
+        .map(|chunk| u64::from_le_bytes(chunk.try_into().unwrap()))
+        .collect()
+}
+
+/// Decode the present entries of a GDT. In long mode, LDT and TSS
+/// descriptors take two slots.
+pub fn decode_gdt(bytes: &[u8], long_mode: bool) -> Vec<Descriptor> {
+    let words = words(bytes);
+    let mut entries = Vec::new();
+    let mut index = 0;
+    while index < words.len() {
+        let d = words[index];
+        let slot = index as u64;
+        index += 1;
+        if field(d, 47, 1) == 0 {
+            continue;
+        }
+
+        let kind = field(d, 40, 4);
+        let system = field(d, 44, 1) == 0;
+        let mut base = field(d, 16, 24) | (field(d, 56, 8) << 24);
+        let mut limit = field(d, 0, 16) | (field(d, 48, 4) << 16);
+        if field(d, 55, 1) == 1 {
+            limit = (limit << 12) | 0xFFF;
+        }
+
+        let mut flags = Vec::new();
+        let kind_name = if system {
+            if long_mode && matches!(kind, 0x2 | 0x9 | 0xB) {
+                if let Some(&high) = words.get(index) {
+                    base |= field(high, 0, 32) << 32;
+                    index += 1;
+                }
+            }
+            system_kind(kind, long_mode)
+        } else {
+            let code = kind & 0x8 != 0;
+            for (bit, name) in [
+                (0x4, if code { "conforming" } else { "expand-down" }),
+                (0x2, if code { "readable" } else { "writable" }),
+                (0x1, "accessed"),
+            ] {
+                if kind & bit != 0 {
+                    flags.push(name);
+                }
+            }
+            if field(d, 53, 1) == 1 {
+                flags.push("64-bit");
+            } else if field(d, 54, 1) == 1 {
+                flags.push("32-bit");
+            } else {
+                flags.push("16-bit");
+            }
+            if code { "code" } else { "data" }
+        };
+        if field(d, 55, 1) == 1 {
+            flags.push("4k-granularity");
+        }
+
+        entries.push(Descriptor {
+            index: slot,
+            selector: Some(format!("{:#x}", slot * 8)),
+            raw: format!("{:#018x}", d),
+            kind: kind_name,
+            dpl: field(d, 45, 2),
+            present: true,
+            base: Some(format!("{:#x}", base)),
+            limit: Some(format!("{:#x}", limit)),
+            target_selector: None,
+            offset: None,
+            ist: None,
+            flags,
+        });
+    }
+    entries
+}
+
+/// Decode the present gates of an IDT (16-byte gates in long mode, 8-byte
+/// gates otherwise)
+pub fn decode_idt(bytes: &[u8], long_mode: bool) -> Vec<Descriptor> {
+    let words = words(bytes);
+    let step = if long_mode { 2 } else { 1 };
+    words
+        .chunks(step)
+        .enumerate()
+        .filter(|(_, gate)| gate.len() == step && field(gate[0], 47, 1) == 1)
+        .map(|(vector, gate)| {
+            let d = gate[0];
+            let kind = field(d, 40, 4);
+            let mut offset = field(d, 0, 16) | (field(d, 48, 16) << 16);
+            if long_mode {
+                offset |= field(gate[1], 0, 32) << 32;
+            }
+            Descriptor {
+                index: vector as u64,
+                selector: None,
+                raw: format!("0x{}", gate.iter().rev().map(|w| format!("{:016x}", w)).collect::<String>()),
+                kind: system_kind(kind, long_mode),
+                dpl: field(d, 45, 2),
+                present: true,
+                base: None,
+                limit: None,
+                target_selector: Some(format!("{:#x}", field(d, 16, 16))),
+                offset: (kind != 0x5).then(|| format!("{:#x}", offset)),
+                ist: long_mode.then(|| field(d, 32, 3)),
+                flags: Vec::new(),
+            }
+        })
+        .collect()
+}
+
+/// Find a descriptor table register (base, limit) in QEMU's
+/// `info registers` output, e.g. `GDT=     000000000000b000 0000007f`
+pub fn parse_descriptor_register(output: &str, table: DescriptorTable) -> Option<(u64, u64)> {
+    let line = output.lines().find(|l| l.trim_start().starts_with(table.monitor_name()))?;
+    let mut values = line.trim_start()[table.monitor_name().len()..].split_whitespace();
+    let base = u64::from_str_radix(values.next()?, 16).ok()?;
+    let limit = u64::from_str_radix(values.next()?, 16).ok()?;
+    Some((base, limit))
+}
+
+/// Parse the value printed by QEMU's `xp`, e.g.
+/// `0000000000001000: 0x0000000000002003`
+pub fn parse_xp_output(output: &str) -> Option<u64> {
+    let line = output.lines().find(|l| l.contains(':'))?;
+    parse_address(line.split_once(':')?.1)
+}
+
+/// Page table format selected by CR0, CR4 and EFER
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum PagingMode {
+    /// 32-bit paging with 4-byte entries
+    Legacy32,
+    /// PAE paging with a 4-entry PDPT
+    Pae,
+    /// 4-level paging (long mode)
+    Level4,
+    /// 5-level paging (long mode with CR4.LA57)
+    Level5,
+}
+
+impl PagingMode {
+    /// Paging mode for the control registers, or None when paging is off
+    pub fn detect(cr0: u64, cr4: u64, efer: u64) -> Option<Self> {
+        if field(cr0, 31, 1) == 0 {
+            return None;
+        }
+        Some(match (field(efer, 10, 1) == 1, field(cr4, 5, 1) == 1, field(cr4, 12, 1) == 1) {
+            (true, _, true) => PagingMode::Level5,
+            (true, _, false) => PagingMode::Level4,
+            (false, true, _) => PagingMode::Pae,
+            (false, false, _) => PagingMode::Legacy32,
+        })
+    }
+
+    /// Table levels from the top: name, first index bit, index width, and
+    /// whether the level can map a large page
+    fn levels(&self) -> &'static [(&'static str, u32, u32, bool)] {
+        match self {
+            PagingMode::Legacy32 => &[("PD", 22, 10, true), ("PT", 12, 10, false)],
+            PagingMode::Pae => &[("PDPT", 30, 2, false), ("PD", 21, 9, true), ("PT", 12, 9, false)],
+            PagingMode::Level4 => &[("PML4", 39, 9, false), ("PDPT", 30, 9, true), ("PD", 21, 9, true), ("PT", 12, 9, false)],
+            PagingMode::Level5 => &[
+                ("PML5", 48, 9, false),
+                ("PML4", 39, 9, false),
+                ("PDPT", 30, 9, true),
+                ("PD", 21, 9, true),
+                ("PT", 12, 9, false),
+            ],
+        }
+    }
+
+    fn entry_size(&self) -> u32 {
+        if *self == PagingMode::Legacy32 { 4 } else { 8 }
+    }
+}
+
+/// One level of a page walk
+#[derive(Debug, Clone, Serialize)]
+pub struct PageWalkStep {
+    pub level: &'static str,
+    pub index: u64,
+    /// Physical address of the entry
+    pub entry_address: String,
+    pub entry: String,
+    pub flags: Vec<&'static str>,
+}
+
+/// Result of translating a virtual address
+#[derive(Debug, Clone, Serialize)]
+pub struct PageWalk {
+    pub virtual_address: String,
+    pub mode: PagingMode,
+    pub cr3: String,
+    pub steps: Vec<PageWalkStep>,
+    /// None if the walk hit a non-present entry
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub physical_address: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub page_size: Option<u64>,
+}
+
+fn entry_flags(entry: u64, mode: PagingMode) -> Vec<&'static str> {
+    let mut flags: Vec<&'static str> = [
+        (0, "P"),
+        (1, "RW"),
+        (2, "US"),
+        (3, "PWT"),
+        (4, "PCD"),
+        (5, "A"),
+        (6, "D"),
+        (7, "PS"),
+        (8, "G"),
+    ]
+    .iter()
+    .filter(|(bit, _)| field(entry, *bit, 1) == 1)
+    .map(|(_, name)| *name)
+    .collect();
+    if mode != PagingMode::Legacy32 && field(entry, 63, 1) == 1 {
+        flags.push("NX");
+    }
+    flags
+}
+
+/// Walk the page tables for `vaddr`. `read` reads a physical entry of the
+/// given size in bytes.
+pub fn walk(vaddr: u64, cr3: u64, mode: PagingMode, mut read: impl FnMut(u64, u32) -> Result<u64>) -> Result<PageWalk> {
+    let address_mask = if mode == PagingMode::Legacy32 { 0xFFFF_F000 } else { 0x000F_FFFF_FFFF_F000 };
+    let mut table = match mode {
+        PagingMode::Pae => cr3 & 0xFFFF_FFE0,
+        _ => cr3 & address_mask,
+    };
+    let mut result = PageWalk {
+        virtual_address: format!("{:#x}", vaddr),
+        mode,
+        cr3: format!("{:#x}", cr3),
+        steps: Vec::new(),
+        physical_address: None,
+        page_size: None,
+    };
+
+    let levels = mode.levels();
+    for (depth, &(level, shift, width, large)) in levels.iter().enumerate() {
+        let index = field(vaddr, shift, width);
+        let entry_address = table + index * mode.entry_size() as u64;
+        let entry = read(entry_address, mode.entry_size())
+            .map_err(|e| anyhow!("Failed to read {} entry at {:#x}: {}", level, entry_address, e))?;
+        let flags = entry_flags(entry, mode);
+        let present = field(entry, 0, 1) == 1;
+        let leaf = depth == levels.len() - 1 || (large && field(entry, 7, 1) == 1);
+        result.steps.push(PageWalkStep {
+            level,
+            index,
+            entry_address: format!("{:#x}", entry_address),
+            entry: format!("{:#x}", entry),
+            flags,
+        });
+
+        if !present {
+            break;
+        }
+        if leaf {
+            let page_size = 1u64 << shift;
+            let frame = if mode == PagingMode::Legacy32 && shift == 22 {
+                // 4 MiB pages keep physical bits 32-39 in entry bits 13-20
+                (entry & 0xFFC0_0000) | (field(entry, 13, 8) << 32)
+            } else {
+                entry & address_mask & !(page_size - 1)
+            };
+            result.physical_address = Some(format!("{:#x}", frame | (vaddr & (page_size - 1))));
+            result.page_size = Some(page_size);
+            break;
+        }
+        table = entry & address_mask;
+    }
+    Ok(result)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::collections::HashMap;
+
+    #[test]
+    fn test_decode_gdt_and_idt() {
+        // Null, 64-bit kernel code, kernel data, then a 16-byte TSS
+        let gdt: Vec<u8> = [0u64, 0x00af9a000000ffff, 0x00cf92000000ffff, 0x0000890010000067, 0x00000000ffff8000]
+            .iter()
+            .flat_map(|w| w.to_le_bytes())
+            .collect();
+        let entries = decode_gdt(&gdt, true);
+        assert_eq!(entries.len(), 3);
+        assert_eq!(entries[0].kind, "code");
+        assert_eq!(entries[0].selector.as_deref(), Some("0x8"));
+        assert!(entries[0].flags.contains(&"64-bit"));
+        assert_eq!(entries[1].limit.as_deref(), Some("0xffffffff"));
+        assert_eq!(entries[2].kind, "tss-64 (available)");
+        assert_eq!(entries[2].base.as_deref(), Some("0xffff800000001000"));
+
+        // Vector 1: interrupt gate to 0xffffffff81001234, selector 0x8, IST 1
+        let idt: Vec<u8> = [0u64, 0, 0x81008e0100081234, 0x00000000ffffffff]
+            .iter()
+            .flat_map(|w| w.to_le_bytes())
+            .collect();
+        let gates = decode_idt(&idt, true);
+        assert_eq!(gates.len(), 1);
+        assert_eq!(gates[0].index, 1);
+        assert_eq!(gates[0].kind, "interrupt gate");
+        assert_eq!(gates[0].offset.as_deref(), Some("0xffffffff81001234"));
+        assert_eq!(gates[0].ist, Some(1));
+
+        let registers = "GDT=     000000000000b000 0000007f\nIDT=     0000000000000000 00000fff\n";
+        assert_eq!(parse_descriptor_register(registers, DescriptorTable::Gdt), Some((0xb000, 0x7f)));
+        assert_eq!(parse_xp_output("0000000000001000: 0x0000000000002003\n"), Some(0x2003));
+    }
+
+    #[test]
+    fn test_walk_four_level_with_large_page() {
+        let memory: HashMap<u64, u64> = [
+            (0x1000, 0x2003),            // PML4[0] -> PDPT at 0x2000
+            (0x2000 + 8, 0x3003),        // PDPT[1] -> PD at 0x3000
+            (0x3000 + 2 * 8, 0x4000_0083), // PD[2]: 2 MiB page at 0x40000000
+        ]
+        .into_iter()
+        .collect();
+        let vaddr = (1 << 30) | (2 << 21) | 0x1234;
+        let mode = PagingMode::detect(1 << 31, 1 << 5, 1 << 10).unwrap();
+        assert_eq!(mode, PagingMode::Level4);
+
+        let read = |addr, _| Ok(memory.get(&addr).copied().unwrap_or(0));
+        let mapped = walk(vaddr, 0x1000, mode, read).unwrap();
+        assert_eq!(mapped.steps.len(), 3);
+        assert_eq!(mapped.physical_address.as_deref(), Some("0x40001234"));
+        assert_eq!(mapped.page_size, Some(2 << 20));
+        assert!(mapped.steps[2].flags.contains(&"PS"));
+
+        // Unmapped addresses stop at the first non-present entry
+        let unmapped = walk(1 << 39, 0x1000, mode, read).unwrap();
+        assert_eq!(unmapped.steps.len(), 1);
+        assert!(unmapped.physical_address.is_none());
+    }
+}
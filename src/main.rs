@@ -15,17 +15,35 @@
 //!   }
 //!   ```
 
+mod config;
+mod util;
 mod gdb;
 mod mcp;
 
+use crate::config::{ServerConfig, Transport};
 use crate::mcp::protocol::*;
+use crate::mcp::peer::ClientPeer;
+use crate::mcp::recording;
+use crate::mcp::sessions::SessionRegistry;
 use crate::mcp::GdbMcpServer;
-use anyhow::Result;
-use std::io::{BufRead, BufReader, Write};
-use tokio::sync::RwLock;
+use anyhow::{anyhow, Context, Result};
+use std::collections::VecDeque;
+use std::io::{BufRead, BufReader, Read};
+use std::path::Path;
+use std::sync::Arc;
+use std::time::Duration;
+use tokio::sync::{mpsc, watch, RwLock};
 use tracing::{debug, error, info, warn};
 use tracing_subscriber::EnvFilter;
 
+/// How long the server waits for a clean shutdown after a signal before
+/// exiting anyway
+const SHUTDOWN_GRACE: Duration = Duration::from_secs(5);
+
+/// How long the relay waits for a daemon it started to accept connections
+#[cfg(unix)]
+const DAEMON_START_TIMEOUT: Duration = Duration::from_secs(5);
+
 /// MCP Server state
 struct ServerState {
     server: GdbMcpServer,
@@ -44,79 +62,436 @@ async fn main() -> Result<()> {
 
     info!("Starting MCP GDB Server v0.1.0");
 
-    let state = RwLock::new(ServerState {
-        server: GdbMcpServer::new(),
-        initialized: false,
-    });
+    let config = ServerConfig::from_env()?;
+
+    match Transport::from_args()? {
+        Transport::Stdio => run_stdio(config).await,
+        Transport::Listen(path) => run_daemon(config, &path).await,
+        Transport::Connect(path) => run_relay(&path).await,
+        Transport::Replay(path) => run_replay(config, &path).await,
+    }
+}
+
+/// Make the tool calls of a recording against a fresh session and print
+/// where the results diverge. Fails if any call diverged.
+async fn run_replay(config: ServerConfig, path: &Path) -> Result<()> {
+    let calls = recording::load(path)?;
+    let server = GdbMcpServer::with_config(config);
+    info!("Replaying {} tool calls from {}", calls.len(), path.display());
 
-    // Read from stdin, write to stdout
-    let stdin = std::io::stdin();
-    let stdout = std::io::stdout();
-    let mut stdout = stdout.lock();
+    let report = recording::replay(&server, &calls).await;
+    server.shutdown().await;
 
-    let reader = BufReader::new(stdin);
+    println!("{}", serde_json::to_string_pretty(&report)?);
+    if report.divergences.is_empty() {
+        info!("Replay matched the recording");
+        Ok(())
+    } else {
+        Err(anyhow!("{} of {} tool calls diverged from the recording", report.divergences.len(), report.calls))
+    }
+}
+
+/// Serve a single client on stdin/stdout
+async fn run_stdio(config: ServerConfig) -> Result<()> {
+    let shutdown = spawn_signal_listener();
+    let state = Arc::new(RwLock::new(ServerState {
+        server: GdbMcpServer::with_config(config),
+        initialized: false,
+    }));
 
     info!("MCP GDB Server ready, listening on stdin");
+    serve(&state, spawn_line_reader(std::io::stdin()), shutdown.clone()).await?;
 
-    for line in reader.lines() {
-        match line {
-            Ok(line) => {
-                debug!("Received: {}", line);
-
-                // Parse the JSON-RPC request
-                let request: Result<JsonRpcRequest, _> = serde_json::from_str(&line);
-
-                match request {
-                    Ok(req) => {
-                        let response = handle_request(&state, req).await;
-
-                        match response {
-                            Ok(Some(resp)) => {
-                                let resp_str = serde_json::to_string(&resp)?;
-                                debug!("Sending: {}", resp_str);
-                                writeln!(stdout, "{}", resp_str)?;
-                                stdout.flush()?;
-                            }
-                            Ok(None) => {
-                                // Notification, no response needed
-                            }
-                            Err(e) => {
-                                error!("Error handling request: {}", e);
-                                let error_resp = JsonRpcErrorResponse {
-                                    jsonrpc: "2.0".to_string(),
-                                    id: None,
-                                    error: JsonRpcError::internal_error(&e.to_string()),
-                                };
-                                let resp_str = serde_json::to_string(&error_resp)?;
-                                writeln!(stdout, "{}", resp_str)?;
-                                stdout.flush()?;
-                            }
-                        }
+    if let Some(reason) = *shutdown.borrow() {
+        info!("Received {}", reason);
+    }
+
+    // Disconnect remote targets and stop GDB so no processes are orphaned
+    state.read().await.server.shutdown().await;
+
+    info!("MCP GDB Server shutting down");
+    Ok(())
+}
+
+/// Serve clients on a Unix socket. Named sessions live as long as the
+/// daemon, so they survive client restarts.
+#[cfg(unix)]
+async fn run_daemon(config: ServerConfig, path: &Path) -> Result<()> {
+    use tokio::net::UnixListener;
+
+    let shutdown = spawn_signal_listener();
+    // A socket file without a listener is left over from a previous daemon
+    if path.exists() && std::os::unix::net::UnixStream::connect(path).is_err() {
+        std::fs::remove_file(path)?;
+    }
+    let listener = UnixListener::bind(path)
+        .with_context(|| format!("Failed to listen on {}", path.display()))?;
+    let registry = SessionRegistry::default();
+    info!("MCP GDB Server daemon listening on {}", path.display());
+
+    let mut signal = shutdown.clone();
+    loop {
+        let stream = tokio::select! {
+            accepted = listener.accept() => accepted?.0.into_std()?,
+            _ = signal.changed() => break,
+        };
+        stream.set_nonblocking(false)?;
+        let output = stream.try_clone()?;
+        let server = GdbMcpServer::with_connection(config.clone(), registry.clone(), ClientPeer::new(Box::new(output)));
+        let lines = spawn_line_reader(stream);
+        let signal = shutdown.clone();
+
+        tokio::spawn(async move {
+            info!("Client connected");
+            let state = Arc::new(RwLock::new(ServerState { server, initialized: false }));
+            if let Err(e) = serve(&state, lines, signal).await {
+                error!("Connection failed: {}", e);
+            }
+            state.read().await.server.disconnect().await;
+            info!("Client disconnected");
+        });
+    }
+
+    if let Some(reason) = *shutdown.borrow() {
+        info!("Received {}", reason);
+    }
+
+    // Stop the named sessions too; nothing can re-acquire them any more
+    for session in registry.drain() {
+        session.shutdown().await;
+    }
+    let _ = std::fs::remove_file(path);
+
+    info!("MCP GDB Server daemon shutting down");
+    Ok(())
+}
+
+#[cfg(not(unix))]
+async fn run_daemon(_config: ServerConfig, _path: &Path) -> Result<()> {
+    Err(anyhow!("Daemon mode is only supported on Unix"))
+}
+
+/// Relay stdin/stdout to the daemon at `path`, starting the daemon when
+/// none is listening. MCP clients launch the relay like a stdio server.
+#[cfg(unix)]
+async fn run_relay(path: &Path) -> Result<()> {
+    use tokio::io::AsyncWriteExt;
+    use tokio::net::UnixStream;
+
+    let stream = match UnixStream::connect(path).await {
+        Ok(stream) => stream,
+        Err(_) => {
+            spawn_daemon(path)?;
+            let start = std::time::Instant::now();
+            loop {
+                match UnixStream::connect(path).await {
+                    Ok(stream) => break stream,
+                    Err(e) if start.elapsed() > DAEMON_START_TIMEOUT => {
+                        return Err(anyhow!("Daemon did not start listening on {}: {}", path.display(), e));
                     }
-                    Err(e) => {
-                        error!("Failed to parse request: {}", e);
-                        let error_resp = JsonRpcErrorResponse {
-                            jsonrpc: "2.0".to_string(),
-                            id: None,
-                            error: JsonRpcError::parse_error(),
-                        };
-                        let resp_str = serde_json::to_string(&error_resp)?;
-                        writeln!(stdout, "{}", resp_str)?;
-                        stdout.flush()?;
+                    Err(_) => tokio::time::sleep(Duration::from_millis(100)).await,
+                }
+            }
+        }
+    };
+    info!("Relaying stdio to {}", path.display());
+
+    let (mut from_daemon, mut to_daemon) = stream.into_split();
+    let mut stdin = tokio::io::stdin();
+    let mut stdout = tokio::io::stdout();
+    tokio::select! {
+        result = tokio::io::copy(&mut stdin, &mut to_daemon) => {
+            result?;
+            // Let the daemon see the client go away
+            to_daemon.shutdown().await?;
+        }
+        result = tokio::io::copy(&mut from_daemon, &mut stdout) => {
+            result?;
+            warn!("Daemon closed the connection");
+        }
+    }
+    Ok(())
+}
+
+#[cfg(not(unix))]
+async fn run_relay(_path: &Path) -> Result<()> {
+    Err(anyhow!("Daemon mode is only supported on Unix"))
+}
+
+/// Start a detached daemon listening on `path` with the relay's arguments
+#[cfg(unix)]
+fn spawn_daemon(path: &Path) -> Result<()> {
+    use std::os::unix::process::CommandExt;
+
+    let mut args = Vec::new();
+    let mut rest = std::env::args().skip(1);
+    while let Some(arg) = rest.next() {
+        if arg == "--connect" {
+            rest.next();
+        } else if !arg.starts_with("--connect=") {
+            args.push(arg);
+        }
+    }
+
+    info!("Starting daemon on {}", path.display());
+    std::process::Command::new(std::env::current_exe()?)
+        .args(args)
+        .arg("--listen")
+        .arg(path)
+        .stdin(std::process::Stdio::null())
+        .stdout(std::process::Stdio::null())
+        .stderr(std::process::Stdio::null())
+        // Keep the daemon alive when the client kills the relay's group
+        .process_group(0)
+        .spawn()
+        .context("Failed to start the daemon")?;
+    Ok(())
+}
+
+/// Handle requests from one client until its input closes or a shutdown
+/// signal arrives
+async fn serve(
+    state: &Arc<RwLock<ServerState>>,
+    mut lines: mpsc::UnboundedReceiver<std::io::Result<String>>,
+    shutdown: watch::Receiver<Option<&'static str>>,
+) -> Result<()> {
+    let peer = state.read().await.server.peer();
+
+    // Requests that arrived while another request was being handled
+    let mut queued: VecDeque<JsonRpcRequest> = VecDeque::new();
+    let mut input_open = true;
+
+    'requests: loop {
+        let mut signal = shutdown.clone();
+        let request = match queued.pop_front() {
+            Some(request) => request,
+            None if !input_open => break,
+            None => {
+                let line = tokio::select! {
+                    line = lines.recv() => line,
+                    _ = signal.changed() => break,
+                };
+                match route_line(line, &peer, &mut input_open)? {
+                    Some(request) => request,
+                    None => continue,
+                }
+            }
+        };
+
+        let id = request.id.clone();
+        let mut handler = spawn_request(state, request);
+
+        // Keep reading input while the request is handled so responses to
+        // server-initiated requests (e.g. roots/list) can be delivered
+        let response = loop {
+            tokio::select! {
+                response = &mut handler => {
+                    break response.unwrap_or_else(|e| Err(anyhow!("Request handler failed: {}", e)));
+                }
+                line = lines.recv(), if input_open => {
+                    match route_line(line, &peer, &mut input_open)? {
+                        // The kill switch and interrupts must not wait
+                        // behind a wedged or running call
+                        Some(request) if is_out_of_band(&request) => {
+                            let id = request.id.clone();
+                            let interrupt = spawn_request(state, request);
+                            let peer = peer.clone();
+                            tokio::spawn(async move {
+                                let response = interrupt.await
+                                    .unwrap_or_else(|e| Err(anyhow!("Request handler failed: {}", e)));
+                                if let Err(e) = respond(&peer, id, response) {
+                                    error!("Failed to send response: {}", e);
+                                }
+                            });
+                        }
+                        Some(request) => queued.push_back(request),
+                        None => {}
                     }
                 }
+                // The handler cannot be cancelled while it waits on GDB;
+                // the shutdown that follows stops GDB, which ends the wait
+                _ = signal.changed() => break 'requests,
             }
-            Err(e) => {
-                error!("Error reading from stdin: {}", e);
+        };
+
+        respond(&peer, id, response)?;
+    }
+    Ok(())
+}
+
+/// Handle a request on the blocking pool. Tool handlers wait on GDB
+/// synchronously, for as long as a run's `max_runtime_ms`; on a runtime
+/// worker that would starve the input loop, the signal listener and the
+/// idle watchdog.
+fn spawn_request(
+    state: &Arc<RwLock<ServerState>>,
+    request: JsonRpcRequest,
+) -> tokio::task::JoinHandle<Result<Option<JsonRpcResponse>>> {
+    let state = state.clone();
+    let runtime = tokio::runtime::Handle::current();
+    tokio::task::spawn_blocking(move || runtime.block_on(handle_request(&state, request)))
+}
+
+/// Read lines on a dedicated thread so the request loop can also wait for
+/// shutdown signals. The channel closes on EOF.
+fn spawn_line_reader(input: impl Read + Send + 'static) -> mpsc::UnboundedReceiver<std::io::Result<String>> {
+    let (tx, rx) = mpsc::unbounded_channel();
+    std::thread::spawn(move || {
+        let reader = BufReader::new(input);
+        for line in reader.lines() {
+            let failed = line.is_err();
+            if tx.send(line).is_err() || failed {
                 break;
             }
         }
+    });
+    rx
+}
+
+/// Watch for termination signals. The returned channel changes to the
+/// signal name once one is received; if the main loop has not finished
+/// shutting down within `SHUTDOWN_GRACE`, the process exits anyway.
+fn spawn_signal_listener() -> watch::Receiver<Option<&'static str>> {
+    let (tx, rx) = watch::channel(None);
+    tokio::spawn(async move {
+        let reason = wait_for_signal().await;
+        let _ = tx.send(Some(reason));
+
+        tokio::time::sleep(SHUTDOWN_GRACE).await;
+        warn!("Shutdown after {} did not finish in time, exiting", reason);
+        std::process::exit(1);
+    });
+    rx
+}
+
+#[cfg(unix)]
+async fn wait_for_signal() -> &'static str {
+    use tokio::signal::unix::{signal, SignalKind};
+
+    async fn recv(kind: SignalKind) {
+        match signal(kind) {
+            Ok(mut stream) => {
+                stream.recv().await;
+            }
+            Err(e) => {
+                warn!("Failed to install signal handler: {}", e);
+                std::future::pending::<()>().await;
+            }
+        }
     }
 
-    info!("MCP GDB Server shutting down");
+    tokio::select! {
+        _ = recv(SignalKind::terminate()) => "SIGTERM",
+        _ = recv(SignalKind::interrupt()) => "SIGINT",
+        _ = recv(SignalKind::hangup()) => "SIGHUP",
+    }
+}
+
+#[cfg(not(unix))]
+async fn wait_for_signal() -> &'static str {
+    if let Err(e) = tokio::signal::ctrl_c().await {
+        warn!("Failed to install Ctrl-C handler: {}", e);
+        std::future::pending::<()>().await;
+    }
+    "Ctrl-C"
+}
+
+/// A message received from the client
+enum Incoming {
+    Request(JsonRpcRequest),
+    /// Response to a server-initiated request
+    Response(serde_json::Value),
+}
+
+/// Handle one line from stdin: responses are routed to the peer and
+/// malformed messages are answered directly. Returns the request to
+/// handle, if any.
+fn route_line(
+    line: Option<std::io::Result<String>>,
+    peer: &ClientPeer,
+    stdin_open: &mut bool,
+) -> Result<Option<JsonRpcRequest>> {
+    match line {
+        Some(Ok(line)) => {
+            debug!("Received: {}", line);
+            match parse_message(&line) {
+                Ok(Incoming::Request(request)) => return Ok(Some(request)),
+                Ok(Incoming::Response(response)) => peer.handle_response(response),
+                Err(error_resp) => peer.send(&error_resp)?,
+            }
+        }
+        Some(Err(e)) => {
+            error!("Error reading from stdin: {}", e);
+            *stdin_open = false;
+        }
+        None => {
+            info!("stdin closed, client disconnected");
+            *stdin_open = false;
+        }
+    }
+    Ok(None)
+}
+
+/// Write the response (or error) for a handled request
+fn respond(peer: &ClientPeer, id: Option<RequestId>, response: Result<Option<JsonRpcResponse>>) -> Result<()> {
+    match response {
+        Ok(Some(resp)) => peer.send(&resp)?,
+        Ok(None) => {
+            // Notification, no response needed
+        }
+        Err(e) => {
+            error!("Error handling request: {}", e);
+            // Notifications never get a response, even on error
+            if id.is_some() {
+                peer.send(&JsonRpcErrorResponse {
+                    jsonrpc: "2.0".to_string(),
+                    id,
+                    error: e.downcast::<JsonRpcError>()
+                        .unwrap_or_else(|e| JsonRpcError::internal_error(&e.to_string())),
+                })?;
+            }
+        }
+    }
     Ok(())
 }
 
+/// Whether a request calls `gdb_emergency_stop` or `gdb_interrupt`, which
+/// are handled even while another request is in flight
+fn is_out_of_band(request: &JsonRpcRequest) -> bool {
+    request.method == "tools/call"
+        && matches!(
+            request.params.as_ref().and_then(|p| p.get("name")).and_then(|n| n.as_str()),
+            Some("gdb_emergency_stop" | "gdb_interrupt")
+        )
+}
+
+/// Parse a line into a JSON-RPC message, or the error response to send
+fn parse_message(line: &str) -> std::result::Result<Incoming, JsonRpcErrorResponse> {
+    let value: serde_json::Value = serde_json::from_str(line).map_err(|e| {
+        error!("Failed to parse request: {}", e);
+        JsonRpcErrorResponse {
+            jsonrpc: "2.0".to_string(),
+            id: None,
+            error: JsonRpcError::parse_error(),
+        }
+    })?;
+
+    if value.get("method").is_none() && (value.get("result").is_some() || value.get("error").is_some()) {
+        return Ok(Incoming::Response(value));
+    }
+
+    // Valid JSON but not a request: answer with the id if one can be found
+    let id = value.get("id").cloned().and_then(|id| serde_json::from_value(id).ok());
+    serde_json::from_value(value).map(Incoming::Request).map_err(|e| {
+        error!("Invalid request: {}", e);
+        JsonRpcErrorResponse {
+            jsonrpc: "2.0".to_string(),
+            id,
+            error: JsonRpcError::invalid_request(),
+        }
+    })
+}
+
 /// Handle a JSON-RPC request
 async fn handle_request(
     state: &RwLock<ServerState>,
@@ -138,10 +513,14 @@ async fn handle_request(
                 result,
             }))
         }
-        "initialized" => {
+        "initialized" | "notifications/initialized" => {
             // Notification, no response needed
             Ok(None)
         }
+        "notifications/roots/list_changed" => {
+            state.read().await.server.roots_changed();
+            Ok(None)
+        }
         "ping" => {
             Ok(Some(JsonRpcResponse {
                 jsonrpc: "2.0".to_string(),
@@ -151,7 +530,7 @@ async fn handle_request(
         }
         "tools/list" => {
             let state = state.read().await;
-            let result = state.server.handle_tools_list().await?;
+            let result = state.server.handle_tools_list(request.params).await?;
             Ok(Some(JsonRpcResponse {
                 jsonrpc: "2.0".to_string(),
                 id: request.id.unwrap_or(RequestId::String("0".to_string())),
@@ -168,10 +547,21 @@ async fn handle_request(
             }))
         }
         "resources/list" => {
+            let state = state.read().await;
+            let result = state.server.handle_resources_list().await?;
             Ok(Some(JsonRpcResponse {
                 jsonrpc: "2.0".to_string(),
                 id: request.id.unwrap_or(RequestId::String("0".to_string())),
-                result: serde_json::json!({"resources": []}),
+                result,
+            }))
+        }
+        "resources/read" => {
+            let state = state.read().await;
+            let result = state.server.handle_resources_read(request.params).await?;
+            Ok(Some(JsonRpcResponse {
+                jsonrpc: "2.0".to_string(),
+                id: request.id.unwrap_or(RequestId::String("0".to_string())),
+                result,
             }))
         }
         "prompts/list" => {
@@ -189,9 +579,121 @@ async fn handle_request(
                 result: serde_json::json!({}),
             }))
         }
+        _ if request.id.is_none() => {
+            debug!("Ignoring notification: {}", method);
+            Ok(None)
+        }
         _ => {
             warn!("Unknown method: {}", method);
-            Err(anyhow::anyhow!("Unknown method: {}", method))
+            Err(JsonRpcError::method_not_found(method).into())
+        }
+    }
+}
+
+#[cfg(all(test, unix))]
+mod tests {
+    use super::*;
+    use std::os::unix::fs::PermissionsExt;
+    use std::time::Instant;
+
+    /// A GDB that never stops by itself: `-exec-continue` runs until
+    /// `-exec-interrupt`
+    const FAKE_GDB: &str = r#"#!/bin/sh
+echo "(gdb)"
+while read -r line; do
+  token=${line%%-*}
+  command=${line#*-}
+  case "$command" in
+    exec-continue*) echo "${token}^running"; echo '*running,thread-id="all"' ;;
+    exec-interrupt*)
+      echo "${token}^done"
+      echo '*stopped,reason="signal-received",signal-name="SIGINT",thread-id="1",frame={level="0",addr="0x401136",func="spin"}' ;;
+    gdb-exit*) echo "${token}^exit"; exit 0 ;;
+    *) echo "${token}^done" ;;
+  esac
+  echo "(gdb)"
+done
+"#;
+
+    /// Forwards each line written to the client into a channel
+    struct Lines(mpsc::UnboundedSender<String>);
+
+    impl std::io::Write for Lines {
+        fn write(&mut self, buf: &[u8]) -> std::io::Result<usize> {
+            for line in String::from_utf8_lossy(buf).lines().filter(|l| !l.is_empty()) {
+                let _ = self.0.send(line.to_string());
+            }
+            Ok(buf.len())
+        }
+
+        fn flush(&mut self) -> std::io::Result<()> {
+            Ok(())
         }
     }
+
+    fn tool_call(id: u64, name: &str, arguments: serde_json::Value) -> String {
+        serde_json::json!({
+            "jsonrpc": "2.0",
+            "id": id,
+            "method": "tools/call",
+            "params": {"name": name, "arguments": arguments},
+        })
+        .to_string()
+    }
+
+    /// Wait for the response with the given id
+    async fn response(output: &mut mpsc::UnboundedReceiver<String>, id: u64) -> serde_json::Value {
+        loop {
+            let line = tokio::time::timeout(Duration::from_secs(10), output.recv())
+                .await
+                .expect("no response in time")
+                .expect("output closed");
+            let message: serde_json::Value = serde_json::from_str(&line).unwrap();
+            if message["id"] == id {
+                return message;
+            }
+        }
+    }
+
+    #[tokio::test]
+    async fn test_interrupt_during_continue() {
+        let gdb = std::env::temp_dir().join(format!("mcp-fake-gdb-{}", std::process::id()));
+        std::fs::write(&gdb, FAKE_GDB).unwrap();
+        std::fs::set_permissions(&gdb, std::fs::Permissions::from_mode(0o755)).unwrap();
+
+        let (output_tx, mut output) = mpsc::unbounded_channel();
+        let peer = ClientPeer::new(Box::new(Lines(output_tx)));
+        let state = Arc::new(RwLock::new(ServerState {
+            server: GdbMcpServer::with_connection(ServerConfig::default(), SessionRegistry::default(), peer),
+            initialized: false,
+        }));
+        let (input, lines) = mpsc::unbounded_channel();
+        let (_signal, shutdown) = watch::channel(None);
+        let serving = tokio::spawn({
+            let state = state.clone();
+            async move { serve(&state, lines, shutdown).await }
+        });
+
+        let send = |line: String| input.send(Ok(line)).unwrap();
+        send(r#"{"jsonrpc":"2.0","id":1,"method":"initialize","params":{"protocolVersion":"2024-11-05","capabilities":{},"clientInfo":{"name":"test","version":"1"}}}"#.to_string());
+        response(&mut output, 1).await;
+        send(tool_call(2, "gdb_start", serde_json::json!({"gdb_path": gdb.to_str().unwrap()})));
+        assert_eq!(response(&mut output, 2).await["result"]["is_error"], false);
+
+        // The continue holds the session for up to a minute
+        let started = Instant::now();
+        send(tool_call(3, "gdb_continue", serde_json::json!({"max_runtime_ms": 60000})));
+        tokio::time::sleep(Duration::from_millis(300)).await;
+        send(tool_call(4, "gdb_interrupt", serde_json::json!({})));
+
+        let interrupted = response(&mut output, 4).await;
+        assert!(interrupted["result"]["content"][0]["text"].as_str().unwrap().contains("-exec-interrupt"));
+        assert_eq!(response(&mut output, 3).await["result"]["is_error"], false);
+        assert!(started.elapsed() < Duration::from_secs(10));
+
+        drop(input);
+        serving.await.unwrap().unwrap();
+        state.read().await.server.shutdown().await;
+        let _ = std::fs::remove_file(&gdb);
+    }
 }
@@ -0,0 +1,186 @@
+//! Tool Call Audit Log
+//!
+//! Records every tool invocation so that what an agent did to a target can be
+//! reviewed afterwards. Entries are kept in memory and optionally appended to
+//! a JSONL file.
+
+use crate::config::AuditConfig;
+use crate::util::now_ms;
+use serde::{Deserialize, Serialize};
+use std::collections::VecDeque;
+use std::fs::{File, OpenOptions};
+use std::io::Write;
+use std::time::Duration;
+use tracing::warn;
+
+/// Outcome of a tool invocation
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum AuditStatus {
+    /// The tool returned a successful result
+    Ok,
+    /// The tool returned an error result
+    Error,
+    /// The tool call failed before producing a result
+    Failed,
+}
+
+/// A single audited tool invocation
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct AuditEntry {
+    /// Milliseconds since the Unix epoch
+    pub timestamp_ms: u64,
+    pub tool: String,
+    pub arguments: serde_json::Value,
+    pub status: AuditStatus,
+    pub duration_ms: u64,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub error: Option<String>,
+}
+
+/// Audit log of tool invocations
+pub struct AuditLog {
+    config: AuditConfig,
+    recent: VecDeque<AuditEntry>,
+    file: Option<File>,
+}
+
+impl AuditLog {
+    pub fn new(config: AuditConfig) -> Self {
+        let file = config.log_path.as_ref().and_then(|path| {
+            OpenOptions::new()
+                .create(true)
+                .append(true)
+                .open(path)
+                .map_err(|e| warn!("Failed to open audit log {}: {}", path, e))
+                .ok()
+        });
+
+        Self {
+            config,
+            recent: VecDeque::new(),
+            file,
+        }
+    }
+
+    /// Record a tool invocation
+    pub fn record(
+        &mut self,
+        tool: &str,
+        arguments: Option<&serde_json::Map<String, serde_json::Value>>,
+        status: AuditStatus,
+        duration: Duration,
+        error: Option<String>,
+    ) {
+        let entry = AuditEntry {
+            timestamp_ms: now_ms(),
+            tool: tool.to_string(),
+            arguments: self.redact(arguments),
+            status,
+            duration_ms: duration.as_millis() as u64,
+            error,
+        };
+
+        if let Some(file) = self.file.as_mut() {
+            let line = serde_json::to_string(&entry).unwrap_or_default();
+            if let Err(e) = writeln!(file, "{}", line).and_then(|_| file.flush()) {
+                warn!("Failed to write audit log entry: {}", e);
+            }
+        }
+
+        self.recent.push_back(entry);
+        while self.recent.len() > self.config.max_recent {
+            self.recent.pop_front();
+        }
+    }
+
+    /// Get the most recent entries (oldest first), optionally filtered by tool name
+    pub fn recent(&self, limit: usize, tool: Option<&str>) -> Vec<AuditEntry> {
+        let mut entries: Vec<AuditEntry> = self
+            .recent
+            .iter()
+            .rev()
+            .filter(|e| tool.is_none() || tool == Some(e.tool.as_str()))
+            .take(limit)
+            .cloned()
+            .collect();
+        entries.reverse();
+        entries
+    }
+
+    /// Replace the values of configured sensitive arguments, including keys
+    /// nested in objects such as `env`
+    pub fn redact(&self, arguments: Option<&serde_json::Map<String, serde_json::Value>>) -> serde_json::Value {
+        let Some(arguments) = arguments else {
+            return serde_json::Value::Object(Default::default());
+        };
+        self.redact_value(&serde_json::Value::Object(arguments.clone()))
+    }
+
+    fn redact_value(&self, value: &serde_json::Value) -> serde_json::Value {
+        match value {
+            serde_json::Value::Object(map) => {
+                let redacted = map
+                    .iter()
+                    .map(|(key, value)| {
+                        if self.config.redact_args.iter().any(|r| r == key) {
+                            (key.clone(), serde_json::Value::String("[REDACTED]".to_string()))
+                        } else {
+                            (key.clone(), self.redact_value(value))
+                        }
+                    })
+                    .collect();
+                serde_json::Value::Object(redacted)
+            }
+            serde_json::Value::Array(items) => serde_json::Value::Array(items.iter().map(|v| self.redact_value(v)).collect()),
+            other => other.clone(),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    fn log(redact_args: &[&str], max_recent: usize) -> AuditLog {
+        AuditLog::new(AuditConfig {
+            log_path: None,
+            redact_args: redact_args.iter().map(|s| s.to_string()).collect(),
+            max_recent,
+        })
+    }
+
+    #[test]
+    fn test_redact_nested_arguments() {
+        let audit = log(&["password", "TOKEN"], 10);
+        let arguments = json!({
+            "password": "hunter2",
+            "program": "/bin/app",
+            "env": {"TOKEN": "abc", "HOME": "/root"},
+            "steps": [{"password": "x", "expr": "1"}],
+        });
+        assert_eq!(
+            audit.redact(arguments.as_object()),
+            json!({
+                "password": "[REDACTED]",
+                "program": "/bin/app",
+                "env": {"TOKEN": "[REDACTED]", "HOME": "/root"},
+                "steps": [{"password": "[REDACTED]", "expr": "1"}],
+            })
+        );
+        assert_eq!(audit.redact(None), json!({}));
+    }
+
+    #[test]
+    fn test_max_recent_trims_oldest() {
+        let mut audit = log(&[], 2);
+        for tool in ["gdb_start", "gdb_run", "gdb_backtrace"] {
+            audit.record(tool, None, AuditStatus::Ok, Duration::from_millis(1), None);
+        }
+        let tools: Vec<String> = audit.recent(10, None).into_iter().map(|e| e.tool).collect();
+        assert_eq!(tools, ["gdb_run", "gdb_backtrace"]);
+        assert_eq!(audit.recent(1, None)[0].tool, "gdb_backtrace");
+        assert!(audit.recent(10, Some("gdb_start")).is_empty());
+    }
+}
@@ -0,0 +1,172 @@
+//! Safety Limits
+//!
+//! An autonomous agent stuck in a loop can issue thousands of `stepi` calls
+//! or huge memory reads against shared lab hardware. Sessions can be given
+//! caps on stepping rate, bytes read per call and breakpoint count; calls
+//! that would exceed one fail with the `limit_exceeded` error code before
+//! anything is sent to GDB.
+
+use crate::mcp::protocol::ToolError;
+use serde::{Deserialize, Serialize};
+use std::collections::VecDeque;
+
+/// Length of the stepping rate window
+const WINDOW_MS: u64 = 60_000;
+
+/// Bytes per word assumed for `gdb_stack_scan`
+const SCAN_WORD_BYTES: u64 = 8;
+
+/// Per-session caps; unset fields are unlimited
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct Limits {
+    /// Source and instruction steps in any 60 s window
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub max_steps_per_minute: Option<u64>,
+    /// Bytes of target memory one tool call may read
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub max_memory_read_bytes: Option<u64>,
+    /// Breakpoints and watchpoints that may exist at once
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub max_breakpoints: Option<u64>,
+}
+
+impl Limits {
+    /// Apply a session's own limits on top of the server's. A session can
+    /// only tighten the configured limits, never loosen them.
+    pub fn tightened(self, session: Limits) -> Limits {
+        fn min(a: Option<u64>, b: Option<u64>) -> Option<u64> {
+            match (a, b) {
+                (Some(a), Some(b)) => Some(a.min(b)),
+                (a, b) => a.or(b),
+            }
+        }
+        Limits {
+            max_steps_per_minute: min(self.max_steps_per_minute, session.max_steps_per_minute),
+            max_memory_read_bytes: min(self.max_memory_read_bytes, session.max_memory_read_bytes),
+            max_breakpoints: min(self.max_breakpoints, session.max_breakpoints),
+        }
+    }
+}
+
+/// Limits of a session with the steps taken in the current window
+#[derive(Debug, Default)]
+pub struct LimitState {
+    pub limits: Limits,
+    /// (timestamp in ms, steps) of recent stepping calls
+    steps: VecDeque<(u64, u64)>,
+}
+
+impl LimitState {
+    pub fn new(limits: Limits) -> Self {
+        Self { limits, steps: VecDeque::new() }
+    }
+
+    /// Check a tool call against the stepping and memory limits, and count
+    /// its steps if it is allowed
+    pub fn check_call(&mut self, tool: &str, args: Option<&serde_json::Map<String, serde_json::Value>>, now_ms: u64) -> Result<(), ToolError> {
+        let arg = |name: &str| args.and_then(|a| a.get(name)).and_then(|v| v.as_u64());
+
+        if let (Some(max), Some(bytes)) = (self.limits.max_memory_read_bytes, memory_read_bytes(tool, args)) {
+            if bytes > max {
+                return Err(limit_exceeded(format!(
+                    "{} would read {} bytes, more than the session's limit of {} bytes per call. Read smaller ranges.",
+                    tool, bytes, max
+                )));
+            }
+        }
+
+        let steps = match tool {
+            "gdb_next" | "gdb_step" | "gdb_stepi" | "gdb_nexti" => arg("count").unwrap_or(1),
+            "gdb_expr_diff" if args.and_then(|a| a.get("action")).and_then(|v| v.as_str()) != Some("continue") => 1,
+            "gdb_step_until_memory_change" | "gdb_step_until_register_change" | "gdb_step_asm_until_branch" => {
+                arg("max_steps").unwrap_or(1000)
+            }
+            _ => return Ok(()),
+        };
+        let Some(max) = self.limits.max_steps_per_minute else {
+            return Ok(());
+        };
+        while self.steps.front().is_some_and(|&(t, _)| now_ms.saturating_sub(t) >= WINDOW_MS) {
+            self.steps.pop_front();
+        }
+        let taken: u64 = self.steps.iter().map(|&(_, n)| n).sum();
+        if taken.saturating_add(steps) > max {
+            let retry_ms = self.steps.front().map(|&(t, _)| (t + WINDOW_MS).saturating_sub(now_ms)).unwrap_or(0);
+            return Err(limit_exceeded(format!(
+                "{} would take {} step(s), but {} of the session's {} steps per minute are used. Retry in {} ms, or use breakpoints and gdb_continue instead of stepping in a loop.",
+                tool, steps, taken, max, retry_ms
+            )));
+        }
+        self.steps.push_back((now_ms, steps));
+        Ok(())
+    }
+
+    /// Check that `adding` more breakpoints fit next to `existing` ones
+    pub fn check_breakpoints(&self, existing: usize, adding: usize) -> Result<(), ToolError> {
+        match self.limits.max_breakpoints {
+            Some(max) if (existing + adding) as u64 > max => Err(limit_exceeded(format!(
+                "The session already has {} breakpoint(s) and watchpoint(s); adding {} would exceed its limit of {}. Delete unused ones with gdb_break_delete or gdb_watch_delete.",
+                existing, adding, max
+            ))),
+            _ => Ok(()),
+        }
+    }
+}
+
+/// Bytes of target memory a tool call asks to read, for tools that take a
+/// byte count
+fn memory_read_bytes(tool: &str, args: Option<&serde_json::Map<String, serde_json::Value>>) -> Option<u64> {
+    let arg = |name: &str| args.and_then(|a| a.get(name)).and_then(|v| v.as_u64());
+    match tool {
+        "gdb_memory_read" => arg("count"),
+        "gdb_poll_memory" | "gdb_step_until_memory_change" => arg("length"),
+        "gdb_stack_scan" => arg("words").map(|w| w.saturating_mul(SCAN_WORD_BYTES)),
+        "gdb_memory_snapshot" => {
+            let regions = args.and_then(|a| a.get("regions")).and_then(|v| v.as_array());
+            let region_bytes = regions.map(|r| r.iter().filter_map(|r| r.get("length").and_then(|v| v.as_u64())).sum::<u64>());
+            match (arg("length"), region_bytes) {
+                (None, None) => None,
+                (a, b) => Some(a.unwrap_or(0).saturating_add(b.unwrap_or(0))),
+            }
+        }
+        _ => None,
+    }
+}
+
+fn limit_exceeded(message: String) -> ToolError {
+    ToolError { code: "limit_exceeded", message }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_limits() {
+        let configured = Limits { max_steps_per_minute: Some(100), max_memory_read_bytes: Some(4096), max_breakpoints: None };
+        let limits = configured.tightened(Limits { max_steps_per_minute: Some(1000), max_breakpoints: Some(4), ..Default::default() });
+        assert_eq!(limits, Limits { max_steps_per_minute: Some(100), max_memory_read_bytes: Some(4096), max_breakpoints: Some(4) });
+
+        let mut state = LimitState::new(limits);
+        let args = |v: serde_json::Value| v.as_object().cloned();
+        let stepi = args(serde_json::json!({"count": 60}));
+        assert!(state.check_call("gdb_stepi", stepi.as_ref(), 0).is_ok());
+        let err = state.check_call("gdb_stepi", stepi.as_ref(), 1000).unwrap_err();
+        assert_eq!(err.code, "limit_exceeded");
+        assert!(err.message.contains("Retry in 59000 ms"));
+        assert!(state.check_call("gdb_stepi", stepi.as_ref(), 60_000).is_ok());
+        assert!(state.check_call("gdb_continue", None, 60_001).is_ok());
+        assert!(state.check_call("gdb_expr_diff", args(serde_json::json!({"action": "continue"})).as_ref(), 60_001).is_ok());
+        assert!(state.check_call("gdb_expr_diff", None, 60_001).is_ok());
+        assert!(state.check_call("gdb_stepi", args(serde_json::json!({"count": 40})).as_ref(), 60_001).is_err());
+
+        let read = args(serde_json::json!({"address": "0x20000000", "count": 8192}));
+        assert!(state.check_call("gdb_memory_read", read.as_ref(), 0).is_err());
+        let snapshot = args(serde_json::json!({"name": "a", "regions": [{"address": "0x0", "length": 4000}, {"address": "0x1000", "length": 200}]}));
+        assert!(state.check_call("gdb_memory_snapshot", snapshot.as_ref(), 0).is_err());
+
+        assert!(state.check_breakpoints(3, 1).is_ok());
+        assert!(state.check_breakpoints(4, 1).is_err());
+    }
+}
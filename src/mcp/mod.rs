@@ -1,7 +1,16 @@
 //! MCP (Model Context Protocol) Server Module
 
+pub mod audit;
+pub mod limits;
+pub mod notes;
+pub mod peer;
 pub mod protocol;
+pub mod recording;
+pub mod replay;
+pub mod roots;
+pub mod schema;
 pub mod server;
+pub mod sessions;
 pub mod tools;
 
 pub use server::GdbMcpServer;
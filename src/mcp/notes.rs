@@ -0,0 +1,150 @@
+//! Target Notes
+//!
+//! Debugging the same firmware over several days should not start from zero
+//! each time. With `notes_dir` configured, the breakpoints, watch
+//! expressions and notes worth keeping are saved per executable with
+//! `gdb_notes_set` and read back with `gdb_notes_get` in later sessions.
+//! Files are keyed by the executable's GNU build ID, so a rebuilt binary
+//! starts a new file; executables without one are keyed by file name.
+
+use crate::gdb::symbols::build_id;
+use crate::mcp::protocol::ToolError;
+use crate::util::now_ms;
+use anyhow::{Context, Result};
+use serde::{Deserialize, Serialize};
+use std::path::{Path, PathBuf};
+
+/// A breakpoint worth setting again in a later session
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct SavedBreakpoint {
+    pub location: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub condition: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub label: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub note: Option<String>,
+}
+
+/// What is remembered about one executable
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(default)]
+pub struct TargetNotes {
+    pub key: String,
+    /// Path the executable was last saved from
+    pub executable: Option<String>,
+    pub breakpoints: Vec<SavedBreakpoint>,
+    pub watch_expressions: Vec<String>,
+    pub notes: Vec<String>,
+    /// Milliseconds since the Unix epoch
+    pub updated_ms: u64,
+}
+
+impl TargetNotes {
+    pub fn is_empty(&self) -> bool {
+        self.breakpoints.is_empty() && self.watch_expressions.is_empty() && self.notes.is_empty()
+    }
+
+    pub fn describe(&self) -> String {
+        format!(
+            "{} breakpoint(s), {} watch expression(s) and {} note(s)",
+            self.breakpoints.len(),
+            self.watch_expressions.len(),
+            self.notes.len()
+        )
+    }
+}
+
+/// Directory of the notes files, one JSON file per executable
+#[derive(Debug, Clone)]
+pub struct NotesStore {
+    dir: PathBuf,
+}
+
+impl NotesStore {
+    pub fn new(dir: impl Into<PathBuf>) -> Self {
+        Self { dir: dir.into() }
+    }
+
+    fn path(&self, key: &str) -> PathBuf {
+        self.dir.join(format!("{}.json", key))
+    }
+
+    /// Notes saved under `key`, or empty ones if there are none yet
+    pub fn load(&self, key: &str) -> Result<TargetNotes> {
+        let path = self.path(key);
+        if !path.exists() {
+            return Ok(TargetNotes { key: key.to_string(), ..Default::default() });
+        }
+        let content = std::fs::read_to_string(&path)
+            .with_context(|| format!("Failed to read {}", path.display()))?;
+        let mut notes: TargetNotes = serde_json::from_str(&content)
+            .with_context(|| format!("Failed to parse {}", path.display()))?;
+        notes.key = key.to_string();
+        Ok(notes)
+    }
+
+    /// Write the notes, replacing the file atomically
+    pub fn save(&self, notes: &mut TargetNotes) -> Result<PathBuf> {
+        notes.updated_ms = now_ms();
+        std::fs::create_dir_all(&self.dir)
+            .with_context(|| format!("Failed to create {}", self.dir.display()))?;
+        let path = self.path(&notes.key);
+        let temp = path.with_extension("json.tmp");
+        std::fs::write(&temp, serde_json::to_string_pretty(notes)?)
+            .with_context(|| format!("Failed to write {}", temp.display()))?;
+        std::fs::rename(&temp, &path)
+            .with_context(|| format!("Failed to write {}", path.display()))?;
+        Ok(path)
+    }
+}
+
+/// Key of an executable's notes: `build-id-<id>`, or `name-<file name>`
+/// for executables without a build ID
+pub fn notes_key(executable: &str) -> String {
+    let id = std::fs::read(executable).ok().and_then(|data| build_id(&data));
+    match id {
+        Some(id) => format!("build-id-{}", id),
+        None => {
+            let name = Path::new(executable).file_name().map(|n| n.to_string_lossy().to_string()).unwrap_or_default();
+            let name: String = name.chars().map(|c| if c.is_ascii_alphanumeric() || "._-".contains(c) { c } else { '_' }).collect();
+            format!("name-{}", name)
+        }
+    }
+}
+
+/// Error of the notes tools when `notes_dir` is not configured
+pub fn notes_disabled() -> ToolError {
+    ToolError {
+        code: "notes_disabled",
+        message: "Target notes are disabled. Set notes_dir in the server configuration to keep notes across sessions.".to_string(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_notes_store() {
+        let dir = std::env::temp_dir().join(format!("mcp-gdb-notes-{}", std::process::id()));
+        let store = NotesStore::new(&dir);
+        let mut notes = store.load("name-fw.elf").unwrap();
+        assert!(notes.is_empty());
+
+        notes.notes.push("UART init races the DMA setup".to_string());
+        notes.breakpoints.push(SavedBreakpoint {
+            location: "uart_isr".to_string(),
+            condition: None,
+            label: Some("rx overrun".to_string()),
+            note: None,
+        });
+        store.save(&mut notes).unwrap();
+        let loaded = store.load("name-fw.elf").unwrap();
+        assert_eq!(loaded.breakpoints, notes.breakpoints);
+        assert_eq!(loaded.describe(), "1 breakpoint(s), 0 watch expression(s) and 1 note(s)");
+        std::fs::remove_dir_all(&dir).unwrap();
+
+        assert_eq!(notes_key("/no/such/dir/my fw.elf"), "name-my_fw.elf");
+    }
+}
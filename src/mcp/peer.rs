@@ -0,0 +1,134 @@
+//! Client Peer
+//!
+//! Requests sent from the server to the MCP client (such as `roots/list`)
+//! and routing of the client's responses back to the waiting caller. The
+//! main loop keeps reading stdin while a tool call is in flight and hands
+//! every response it sees to [`ClientPeer::handle_response`].
+
+use crate::mcp::protocol::*;
+use anyhow::{anyhow, Result};
+use serde::Serialize;
+use std::collections::HashMap;
+use std::io::Write;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::Mutex;
+use std::time::Duration;
+use tokio::sync::oneshot;
+use tracing::{debug, warn};
+
+type PendingResponse = oneshot::Sender<std::result::Result<serde_json::Value, JsonRpcError>>;
+
+/// The connected MCP client, as seen from the server
+pub struct ClientPeer {
+    next_id: AtomicU64,
+    pending: Mutex<HashMap<String, PendingResponse>>,
+    /// Capabilities the client declared in `initialize`
+    capabilities: Mutex<serde_json::Value>,
+    /// Where messages to the client are written (stdout or a socket)
+    output: Mutex<Box<dyn Write + Send>>,
+}
+
+impl Default for ClientPeer {
+    fn default() -> Self {
+        Self::new(Box::new(std::io::stdout()))
+    }
+}
+
+impl ClientPeer {
+    /// Create a peer writing its messages to `output`
+    pub fn new(output: Box<dyn Write + Send>) -> Self {
+        Self {
+            next_id: AtomicU64::new(0),
+            pending: Mutex::new(HashMap::new()),
+            capabilities: Mutex::new(serde_json::Value::Null),
+            output: Mutex::new(output),
+        }
+    }
+
+    /// Write one JSON-RPC message to the client
+    pub fn send(&self, message: &impl Serialize) -> Result<()> {
+        let line = serde_json::to_string(message)?;
+        debug!("Sending: {}", line);
+        let mut output = self.output.lock().unwrap();
+        writeln!(output, "{}", line)?;
+        output.flush()?;
+        Ok(())
+    }
+
+    /// Send a notification to the client
+    pub fn notify(&self, notification: &Notification) -> Result<()> {
+        self.send(&serde_json::json!({
+            "jsonrpc": "2.0",
+            "method": notification.method,
+            "params": notification.params,
+        }))
+    }
+
+    /// Record the client capabilities from the `initialize` params
+    pub fn set_capabilities(&self, capabilities: serde_json::Value) {
+        *self.capabilities.lock().unwrap() = capabilities;
+    }
+
+    /// Whether the client declared a capability (e.g. "roots")
+    pub fn supports(&self, capability: &str) -> bool {
+        self.capabilities
+            .lock()
+            .unwrap()
+            .get(capability)
+            .is_some_and(|c| !c.is_null())
+    }
+
+    /// Send a request to the client and wait for its result
+    pub async fn request(
+        &self,
+        method: &str,
+        params: Option<serde_json::Value>,
+        timeout: Duration,
+    ) -> Result<serde_json::Value> {
+        let id = format!("srv-{}", self.next_id.fetch_add(1, Ordering::SeqCst) + 1);
+        let (tx, rx) = oneshot::channel();
+        self.pending.lock().unwrap().insert(id.clone(), tx);
+
+        let request = JsonRpcRequest {
+            jsonrpc: "2.0".to_string(),
+            id: Some(RequestId::String(id.clone())),
+            method: method.to_string(),
+            params,
+        };
+        if let Err(e) = self.send(&request) {
+            self.pending.lock().unwrap().remove(&id);
+            return Err(e);
+        }
+
+        let response = tokio::time::timeout(timeout, rx).await;
+        self.pending.lock().unwrap().remove(&id);
+
+        match response {
+            Ok(Ok(Ok(result))) => Ok(result),
+            Ok(Ok(Err(error))) => Err(anyhow!("Client rejected {}: {}", method, error.message)),
+            Ok(Err(_)) => Err(anyhow!("Client request {} was dropped", method)),
+            Err(_) => Err(anyhow!("Timeout waiting for the client to answer {}", method)),
+        }
+    }
+
+    /// Deliver a response message from the client to the waiting request
+    pub fn handle_response(&self, message: serde_json::Value) {
+        let id = match message.get("id") {
+            Some(serde_json::Value::String(s)) => s.clone(),
+            Some(other) => other.to_string(),
+            None => return,
+        };
+
+        let Some(tx) = self.pending.lock().unwrap().remove(&id) else {
+            warn!("Response to unknown request {}", id);
+            return;
+        };
+
+        let outcome = match message.get("error") {
+            Some(error) => Err(serde_json::from_value(error.clone())
+                .unwrap_or_else(|_| JsonRpcError::internal_error(&error.to_string()))),
+            None => Ok(message.get("result").cloned().unwrap_or(serde_json::Value::Null)),
+        };
+        let _ = tx.send(outcome);
+    }
+}
@@ -86,6 +86,14 @@ impl JsonRpcError {
     }
 }
 
+impl std::fmt::Display for JsonRpcError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{} ({})", self.message, self.code)
+    }
+}
+
+impl std::error::Error for JsonRpcError {}
+
 /// Request ID type
 #[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
 #[serde(untagged)]
@@ -178,6 +186,9 @@ pub struct CallToolResult {
     pub content: Vec<Content>,
     #[serde(default, skip_serializing_if = "Option::is_none")]
     pub is_error: Option<bool>,
+    /// Machine-readable details (e.g. `errorCode` for error results)
+    #[serde(rename = "_meta", default, skip_serializing_if = "Option::is_none")]
+    pub meta: Option<serde_json::Value>,
 }
 
 impl CallToolResult {
@@ -185,6 +196,7 @@ impl CallToolResult {
         Self {
             content,
             is_error: Some(false),
+            meta: None,
         }
     }
 
@@ -192,6 +204,7 @@ impl CallToolResult {
         Self {
             content,
             is_error: Some(true),
+            meta: None,
         }
     }
 
@@ -202,6 +215,59 @@ impl CallToolResult {
     pub fn error_text(text: impl Into<String>) -> Self {
         Self::error(vec![Content::text(text)])
     }
+
+    /// Error result carrying a machine-readable error code
+    pub fn error_with_code(code: &str, text: impl Into<String>) -> Self {
+        Self {
+            meta: Some(serde_json::json!({ "errorCode": code })),
+            ..Self::error_text(text)
+        }
+    }
+}
+
+/// A tool call that failed before doing any work, e.g. because of bad
+/// arguments. Reported as an error result rather than a JSON-RPC error.
+#[derive(Debug, Clone)]
+pub struct ToolError {
+    pub code: &'static str,
+    pub message: String,
+}
+
+impl ToolError {
+    pub fn missing_argument(name: &str) -> Self {
+        Self {
+            code: "missing_argument",
+            message: format!("{} is required", name),
+        }
+    }
+
+    pub fn invalid_argument(message: impl Into<String>) -> Self {
+        Self {
+            code: "invalid_argument",
+            message: message.into(),
+        }
+    }
+
+    pub fn no_session() -> Self {
+        Self {
+            code: "no_session",
+            message: "GDB session not started".to_string(),
+        }
+    }
+}
+
+impl std::fmt::Display for ToolError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}", self.message)
+    }
+}
+
+impl std::error::Error for ToolError {}
+
+impl From<ToolError> for CallToolResult {
+    fn from(e: ToolError) -> Self {
+        Self::error_with_code(e.code, e.message)
+    }
 }
 
 /// Content types
@@ -210,7 +276,7 @@ impl CallToolResult {
 pub enum Content {
     #[serde(rename = "text")]
     Text { text: String },
-    #[serde(rename = "image")]
+    #[serde(rename = "image", rename_all = "camelCase")]
     Image { data: String, mime_type: String },
     #[serde(rename = "resource")]
     Resource { resource: ResourceContents },
@@ -223,6 +289,7 @@ impl Content {
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
 pub struct ResourceContents {
     pub uri: String,
     #[serde(default, skip_serializing_if = "Option::is_none")]
@@ -237,7 +304,7 @@ pub struct ResourceContents {
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct ListToolsResult {
     pub tools: Vec<Tool>,
-    #[serde(default, skip_serializing_if = "Option::is_none")]
+    #[serde(rename = "nextCursor", default, skip_serializing_if = "Option::is_none")]
     pub next_cursor: Option<String>,
 }
 
@@ -246,6 +313,7 @@ pub struct ListToolsResult {
 // ============================================================================
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
 pub struct Resource {
     pub uri: String,
     pub name: String,
@@ -258,10 +326,15 @@ pub struct Resource {
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct ListResourcesResult {
     pub resources: Vec<Resource>,
-    #[serde(default, skip_serializing_if = "Option::is_none")]
+    #[serde(rename = "nextCursor", default, skip_serializing_if = "Option::is_none")]
     pub next_cursor: Option<String>,
 }
 
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ReadResourceResult {
+    pub contents: Vec<ResourceContents>,
+}
+
 // ============================================================================
 // Notification Types
 // ============================================================================
@@ -274,6 +347,9 @@ pub struct Notification {
 }
 
 impl Notification {
+    // The server receives `initialized` and never changes its tool list at
+    // runtime, so these two are not sent yet
+    #[allow(dead_code)]
     pub fn initialized() -> Self {
         Self {
             method: "notifications/initialized".to_string(),
@@ -281,6 +357,7 @@ impl Notification {
         }
     }
 
+    #[allow(dead_code)]
     pub fn tools_list_changed() -> Self {
         Self {
             method: "notifications/tools/list_changed".to_string(),
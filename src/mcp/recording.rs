@@ -0,0 +1,180 @@
+//! Recorded Tool Sequences
+//!
+//! `gdb_record_session_start` and `gdb_record_session_stop` capture the tool
+//! calls of a session to a JSONL file, one call and its result per line.
+//! Running the server with `--replay <file>` makes the same calls against a
+//! fresh session and reports where the results diverge, so a debugging
+//! session that found a bug can be kept as a regression check.
+
+use crate::mcp::protocol::{CallToolResult, Content};
+use crate::mcp::replay::REQUEST_TOKEN;
+use crate::mcp::GdbMcpServer;
+use anyhow::{anyhow, Context, Result};
+use regex::Regex;
+use serde::{Deserialize, Serialize};
+use std::io::Write;
+use std::path::{Path, PathBuf};
+use std::sync::OnceLock;
+
+/// Tools that control recording and are not recorded themselves
+pub const RECORDING_TOOLS: &[&str] = &["gdb_record_session_start", "gdb_record_session_stop"];
+
+/// One recorded tool call
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct RecordedCall {
+    pub tool: String,
+    #[serde(default)]
+    pub arguments: serde_json::Map<String, serde_json::Value>,
+    pub is_error: bool,
+    /// Text content of the result
+    pub text: String,
+}
+
+impl RecordedCall {
+    fn new(tool: &str, arguments: Option<&serde_json::Map<String, serde_json::Value>>, result: &CallToolResult) -> Self {
+        let mut arguments = arguments.cloned().unwrap_or_default();
+        // A replay runs against a fresh session, where the token means nothing
+        arguments.remove(REQUEST_TOKEN);
+        Self {
+            tool: tool.to_string(),
+            arguments,
+            is_error: result.is_error == Some(true),
+            text: result_text(result),
+        }
+    }
+}
+
+/// Text content of a tool result; images are left out
+fn result_text(result: &CallToolResult) -> String {
+    result.content.iter()
+        .filter_map(|c| match c {
+            Content::Text { text } => Some(text.as_str()),
+            _ => None,
+        })
+        .collect::<Vec<_>>()
+        .join("\n")
+}
+
+/// A recording in progress
+#[derive(Debug)]
+pub struct Recording {
+    pub path: PathBuf,
+    calls: Vec<RecordedCall>,
+}
+
+impl Recording {
+    pub fn new(path: PathBuf) -> Self {
+        Self { path, calls: Vec::new() }
+    }
+
+    pub fn push(&mut self, tool: &str, arguments: Option<&serde_json::Map<String, serde_json::Value>>, result: &CallToolResult) {
+        self.calls.push(RecordedCall::new(tool, arguments, result));
+    }
+
+    pub fn len(&self) -> usize {
+        self.calls.len()
+    }
+
+    /// Write the recorded calls as JSONL
+    pub fn save(&self) -> Result<()> {
+        let mut file = std::fs::File::create(&self.path)
+            .with_context(|| format!("Failed to create {}", self.path.display()))?;
+        for call in &self.calls {
+            writeln!(file, "{}", serde_json::to_string(call)?)?;
+        }
+        Ok(())
+    }
+}
+
+/// Read a recording written by [`Recording::save`]
+pub fn load(path: &Path) -> Result<Vec<RecordedCall>> {
+    let content = std::fs::read_to_string(path).with_context(|| format!("Failed to read {}", path.display()))?;
+    content.lines()
+        .enumerate()
+        .filter(|(_, line)| !line.trim().is_empty())
+        .map(|(i, line)| serde_json::from_str(line).map_err(|e| anyhow!("{}:{}: {}", path.display(), i + 1, e)))
+        .collect()
+}
+
+/// A replayed call whose result differs from the recording
+#[derive(Debug, Clone, Serialize)]
+pub struct Divergence {
+    /// Position of the call in the recording, from 0
+    pub index: usize,
+    pub tool: String,
+    /// "status" when one run failed and the other did not, "output" when
+    /// only the text differs
+    pub kind: &'static str,
+    pub expected: String,
+    pub actual: String,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct ReplayReport {
+    pub calls: usize,
+    pub divergences: Vec<Divergence>,
+}
+
+/// Mask what legitimately changes between runs: addresses (ASLR), Unix
+/// timestamps and durations
+fn normalize(text: &str) -> String {
+    static VOLATILE: OnceLock<[(Regex, &str); 3]> = OnceLock::new();
+    let patterns = VOLATILE.get_or_init(|| {
+        [
+            (Regex::new(r"0x[0-9a-fA-F]+").unwrap(), "0x?"),
+            (Regex::new(r#"("(?:timestamp|[a-z_]*_ms|t_us)"\s*:\s*)\d+"#).unwrap(), "${1}?"),
+            (Regex::new(r"\b\d{12,}\b").unwrap(), "?"),
+        ]
+    });
+    patterns.iter().fold(text.to_string(), |text, (re, replacement)| re.replace_all(&text, *replacement).into_owned())
+}
+
+/// Compare a replayed result with the recorded one
+pub fn compare(index: usize, expected: &RecordedCall, actual: &CallToolResult) -> Option<Divergence> {
+    let actual = RecordedCall::new(&expected.tool, None, actual);
+    let divergence = |kind, before, after| Divergence { index, tool: expected.tool.clone(), kind, expected: before, actual: after };
+    if actual.is_error != expected.is_error {
+        let describe = |call: &RecordedCall| format!("{}: {}", if call.is_error { "error" } else { "ok" }, call.text);
+        return Some(divergence("status", describe(expected), describe(&actual)));
+    }
+    (normalize(&actual.text) != normalize(&expected.text)).then(|| divergence("output", expected.text.clone(), actual.text.clone()))
+}
+
+/// Make the recorded calls against `server` in order
+pub async fn replay(server: &GdbMcpServer, calls: &[RecordedCall]) -> ReplayReport {
+    let mut divergences = Vec::new();
+    for (index, call) in calls.iter().enumerate() {
+        let params = serde_json::json!({ "name": call.tool, "arguments": call.arguments });
+        let result = server.handle_tools_call(Some(params)).await
+            .and_then(|value| Ok(serde_json::from_value::<CallToolResult>(value)?))
+            .unwrap_or_else(|e| CallToolResult::error_text(format!("{:#}", e)));
+        if let Some(divergence) = compare(index, call, &result) {
+            divergences.push(divergence);
+        }
+    }
+    ReplayReport { calls: calls.len(), divergences }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_compare() {
+        let recorded = RecordedCall {
+            tool: "gdb_break_insert".to_string(),
+            arguments: Default::default(),
+            is_error: false,
+            text: "Breakpoint 1 at 0x401136: file hello.c, line 4.\n{\"timestamp_ms\": 1792143615360}".to_string(),
+        };
+        let same = CallToolResult::text("Breakpoint 1 at 0x555555555136: file hello.c, line 4.\n{\"timestamp_ms\": 1792143699999}");
+        assert!(compare(0, &recorded, &same).is_none());
+
+        let moved = CallToolResult::text("Breakpoint 1 at 0x401136: file hello.c, line 5.");
+        assert_eq!(compare(1, &recorded, &moved).map(|d| d.kind), Some("output"));
+
+        let failed = CallToolResult::error_text("No symbol table is loaded.");
+        let divergence = compare(2, &recorded, &failed).unwrap();
+        assert_eq!((divergence.index, divergence.kind), (2, "status"));
+    }
+}
@@ -0,0 +1,107 @@
+//! Replayed Tool Calls
+//!
+//! Mutating tools accept an optional `request_token`. The first call with a
+//! token runs the tool and remembers its result; a retry with the same token
+//! gets that result back instead of running the tool again, so a client that
+//! retries after a transport timeout does not insert a second breakpoint or
+//! step twice. A retry that arrives while the first call is still running
+//! waits for it. Calls that fail without a result are not remembered and
+//! run again on retry.
+
+use crate::mcp::protocol::{CallToolResult, ToolError};
+use anyhow::Result;
+use std::collections::VecDeque;
+use std::future::Future;
+use std::sync::{Arc, Mutex};
+use tokio::sync::OnceCell;
+
+/// Argument carrying the idempotency token
+pub const REQUEST_TOKEN: &str = "request_token";
+
+/// Number of tokens remembered per session
+const CAPACITY: usize = 256;
+
+struct Replay {
+    token: String,
+    /// Tool name and arguments of the first call
+    fingerprint: String,
+    result: OnceCell<CallToolResult>,
+}
+
+/// Results of recent tool calls, keyed by request token
+#[derive(Default)]
+pub struct ReplayCache {
+    replays: Mutex<VecDeque<Arc<Replay>>>,
+}
+
+impl ReplayCache {
+    /// Run `call` once per token. `fingerprint` identifies the call so a
+    /// token reused for a different call is rejected instead of replayed.
+    pub async fn run<F>(&self, token: &str, fingerprint: String, call: F) -> Result<CallToolResult>
+    where
+        F: Future<Output = Result<CallToolResult>>,
+    {
+        let replay = self.replay(token, fingerprint)?;
+        let mut ran = false;
+        let result = replay.result.get_or_try_init(|| {
+            ran = true;
+            call
+        }).await?;
+
+        let mut result = result.clone();
+        if !ran {
+            let mut meta = result.meta.take().unwrap_or_else(|| serde_json::json!({}));
+            meta["replayed"] = serde_json::Value::Bool(true);
+            result.meta = Some(meta);
+        }
+        Ok(result)
+    }
+
+    fn replay(&self, token: &str, fingerprint: String) -> Result<Arc<Replay>, ToolError> {
+        let mut replays = self.replays.lock().unwrap();
+        if let Some(replay) = replays.iter().find(|r| r.token == token) {
+            if replay.fingerprint != fingerprint {
+                return Err(ToolError {
+                    code: "request_token_reused",
+                    message: format!("request_token '{}' was already used for a different call", token),
+                });
+            }
+            return Ok(Arc::clone(replay));
+        }
+
+        if replays.len() == CAPACITY {
+            replays.pop_front();
+        }
+        let replay = Arc::new(Replay {
+            token: token.to_string(),
+            fingerprint,
+            result: OnceCell::new(),
+        });
+        replays.push_back(Arc::clone(&replay));
+        Ok(replay)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use anyhow::anyhow;
+
+    #[tokio::test]
+    async fn test_replay_returns_first_result() {
+        let cache = ReplayCache::default();
+        let first = cache.run("t1", "gdb_step {}".to_string(), async { Ok(CallToolResult::text("stepped")) }).await.unwrap();
+        assert!(first.meta.is_none());
+
+        let replayed = cache.run("t1", "gdb_step {}".to_string(), async { panic!("ran twice") }).await.unwrap();
+        assert_eq!(replayed.meta.unwrap()["replayed"], true);
+
+        // A different call under the same token is refused
+        let reused = cache.run("t1", "gdb_next {}".to_string(), async { Ok(CallToolResult::text("next")) }).await;
+        assert!(reused.unwrap_err().downcast::<ToolError>().is_ok());
+
+        // Failures are not remembered
+        assert!(cache.run("t2", "gdb_step {}".to_string(), async { Err(anyhow!("lost")) }).await.is_err());
+        assert!(cache.run("t2", "gdb_step {}".to_string(), async { Ok(CallToolResult::text("stepped")) }).await.is_ok());
+    }
+}
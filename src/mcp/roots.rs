@@ -0,0 +1,124 @@
+//! Client Roots
+//!
+//! Workspace-scoped clients declare the directories the server may touch
+//! through `roots/list`. File path arguments are resolved against these
+//! roots: relative paths are joined to the first root, and paths outside
+//! every root are rejected.
+
+use crate::mcp::protocol::ToolError;
+use std::path::{Component, Path, PathBuf};
+
+/// Extract the directories from a `roots/list` result
+pub fn parse_roots(result: &serde_json::Value) -> Vec<PathBuf> {
+    result
+        .get("roots")
+        .and_then(|r| r.as_array())
+        .map(|roots| {
+            roots
+                .iter()
+                .filter_map(|r| r.get("uri").and_then(|u| u.as_str()))
+                .filter_map(file_uri_to_path)
+                .collect()
+        })
+        .unwrap_or_default()
+}
+
+/// Convert a `file://` URI to a local path
+fn file_uri_to_path(uri: &str) -> Option<PathBuf> {
+    let rest = uri.strip_prefix("file://")?;
+    // Skip an authority component such as "localhost"
+    let path = &rest[rest.find('/')?..];
+    Some(normalize(Path::new(&percent_decode(path))))
+}
+
+fn percent_decode(s: &str) -> String {
+    let bytes = s.as_bytes();
+    let mut out = Vec::with_capacity(bytes.len());
+    let mut i = 0;
+    while i < bytes.len() {
+        if bytes[i] == b'%' && i + 2 < bytes.len() {
+            let hex = std::str::from_utf8(&bytes[i + 1..i + 3]).ok();
+            if let Some(b) = hex.and_then(|h| u8::from_str_radix(h, 16).ok()) {
+                out.push(b);
+                i += 3;
+                continue;
+            }
+        }
+        out.push(bytes[i]);
+        i += 1;
+    }
+    String::from_utf8_lossy(&out).into_owned()
+}
+
+/// Lexically resolve `.` and `..` components
+fn normalize(path: &Path) -> PathBuf {
+    let mut out = PathBuf::new();
+    for component in path.components() {
+        match component {
+            Component::CurDir => {}
+            Component::ParentDir => {
+                out.pop();
+            }
+            other => out.push(other),
+        }
+    }
+    out
+}
+
+/// Resolve a path argument against the client roots. With no roots every
+/// path is accepted unchanged.
+pub fn resolve_path(path: &str, roots: &[PathBuf]) -> Result<PathBuf, ToolError> {
+    let Some(first) = roots.first() else {
+        return Ok(PathBuf::from(path));
+    };
+
+    let joined = if Path::new(path).is_absolute() {
+        PathBuf::from(path)
+    } else {
+        first.join(path)
+    };
+    // Follow symlinks when the file exists so links cannot escape the roots
+    let resolved = joined.canonicalize().unwrap_or_else(|_| normalize(&joined));
+
+    let inside = roots.iter().any(|root| {
+        let root = root.canonicalize().unwrap_or_else(|_| root.clone());
+        resolved.starts_with(root)
+    });
+    if inside {
+        Ok(resolved)
+    } else {
+        Err(ToolError {
+            code: "path_outside_roots",
+            message: format!(
+                "{} is outside the client's workspace roots ({})",
+                path,
+                roots.iter().map(|r| r.display().to_string()).collect::<Vec<_>>().join(", ")
+            ),
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_resolve_path_against_roots() {
+        let roots = parse_roots(&serde_json::json!({
+            "roots": [
+                {"uri": "file:///nonexistent/work%20space", "name": "ws"},
+                {"uri": "https://example.com/not-a-file"}
+            ]
+        }));
+        assert_eq!(roots, vec![PathBuf::from("/nonexistent/work space")]);
+
+        assert_eq!(
+            resolve_path("build/app.elf", &roots).unwrap(),
+            PathBuf::from("/nonexistent/work space/build/app.elf")
+        );
+        assert!(resolve_path("/nonexistent/work space/./a/../b", &roots).is_ok());
+        assert_eq!(resolve_path("../escape", &roots).unwrap_err().code, "path_outside_roots");
+        assert!(resolve_path("/etc/passwd", &roots).is_err());
+        assert_eq!(resolve_path("rel", &[]).unwrap(), PathBuf::from("rel"));
+    }
+}
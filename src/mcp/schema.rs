@@ -0,0 +1,155 @@
+//! Tool Argument Validation
+//!
+//! Checks tool call arguments against the tool's `input_schema` before the
+//! handler runs. Only the JSON Schema subset used by the tool definitions
+//! is supported: `type` (single or list), `required`, `enum`, `properties`,
+//! `items` and `additionalProperties`.
+
+use serde_json::Value;
+
+/// Validate tool arguments against an input schema, returning one message
+/// per problem found
+pub fn validate_arguments(arguments: &serde_json::Map<String, Value>, schema: &Value) -> Vec<String> {
+    let mut errors = Vec::new();
+    validate_object(arguments, schema, "", &mut errors);
+    // Unknown top-level arguments are almost always typos
+    if let Some(properties) = schema.get("properties").and_then(|p| p.as_object()) {
+        if schema.get("additionalProperties").is_none() {
+            for name in arguments.keys().filter(|k| !properties.contains_key(*k)) {
+                errors.push(format!(
+                    "unknown argument '{}' (expected one of: {})",
+                    name,
+                    properties.keys().cloned().collect::<Vec<_>>().join(", ")
+                ));
+            }
+        }
+    }
+    errors
+}
+
+fn validate_object(object: &serde_json::Map<String, Value>, schema: &Value, path: &str, errors: &mut Vec<String>) {
+    if let Some(required) = schema.get("required").and_then(|r| r.as_array()) {
+        for name in required.iter().filter_map(|r| r.as_str()) {
+            if object.get(name).is_none_or(|v| v.is_null()) {
+                errors.push(format!("missing required argument '{}{}'", path, name));
+            }
+        }
+    }
+
+    let properties = schema.get("properties").and_then(|p| p.as_object());
+    for (name, value) in object {
+        let field = format!("{}{}", path, name);
+        match properties.and_then(|p| p.get(name)) {
+            Some(property) => validate_value(value, property, &field, errors),
+            None => {
+                if let Some(additional) = schema.get("additionalProperties").filter(|a| a.is_object()) {
+                    validate_value(value, additional, &field, errors);
+                } else if schema.get("additionalProperties") == Some(&Value::Bool(false)) {
+                    errors.push(format!("unknown argument '{}'", field));
+                }
+            }
+        }
+    }
+}
+
+fn validate_value(value: &Value, schema: &Value, path: &str, errors: &mut Vec<String>) {
+    // Optional arguments may be passed as null
+    if value.is_null() {
+        return;
+    }
+
+    if let Some(expected) = schema.get("type") {
+        let types: Vec<&str> = match expected {
+            Value::String(t) => vec![t.as_str()],
+            Value::Array(ts) => ts.iter().filter_map(|t| t.as_str()).collect(),
+            _ => Vec::new(),
+        };
+        if !types.is_empty() && !types.iter().any(|t| matches_type(value, t)) {
+            errors.push(format!("'{}' must be {}, got {}", path, types.join(" or "), describe(value)));
+            return;
+        }
+    }
+
+    if let Some(allowed) = schema.get("enum").and_then(|e| e.as_array()) {
+        if !allowed.contains(value) {
+            let allowed: Vec<String> = allowed.iter().map(|a| a.to_string()).collect();
+            errors.push(format!("'{}' must be one of {}, got {}", path, allowed.join(", "), value));
+        }
+    }
+
+    match value {
+        Value::Object(object) => validate_object(object, schema, &format!("{}.", path), errors),
+        Value::Array(items) => {
+            if let Some(item_schema) = schema.get("items") {
+                for (i, item) in items.iter().enumerate() {
+                    validate_value(item, item_schema, &format!("{}[{}]", path, i), errors);
+                }
+            }
+        }
+        _ => {}
+    }
+}
+
+fn matches_type(value: &Value, expected: &str) -> bool {
+    match expected {
+        "string" => value.is_string(),
+        "integer" => value.is_i64() || value.is_u64(),
+        "number" => value.is_number(),
+        "boolean" => value.is_boolean(),
+        "object" => value.is_object(),
+        "array" => value.is_array(),
+        "null" => value.is_null(),
+        _ => true,
+    }
+}
+
+fn describe(value: &Value) -> &'static str {
+    match value {
+        Value::Null => "null",
+        Value::Bool(_) => "a boolean",
+        Value::Number(n) if n.is_f64() => "a number",
+        Value::Number(_) => "an integer",
+        Value::String(_) => "a string",
+        Value::Array(_) => "an array",
+        Value::Object(_) => "an object",
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_validate_arguments() {
+        let schema = serde_json::json!({
+            "type": "object",
+            "properties": {
+                "location": { "type": "string" },
+                "type": { "type": "string", "enum": ["write", "read", "access"] },
+                "count": { "type": "integer" },
+                "thread_id": { "type": ["string", "integer"] },
+                "regions": {
+                    "type": "array",
+                    "items": { "type": "object", "properties": { "length": { "type": "integer" } }, "required": ["address"] }
+                }
+            },
+            "required": ["location"]
+        });
+        let args = |v: Value| v.as_object().unwrap().clone();
+
+        assert!(validate_arguments(&args(serde_json::json!({"location": "main", "thread_id": 2})), &schema).is_empty());
+
+        let errors = validate_arguments(&args(serde_json::json!({
+            "type": "writ",
+            "count": "3",
+            "regions": [{"length": 4}],
+            "locaton": "main"
+        })), &schema);
+        assert_eq!(errors.len(), 5, "{:?}", errors);
+        assert!(errors.contains(&"missing required argument 'location'".to_string()));
+        assert!(errors.iter().any(|e| e.starts_with("'type' must be one of")));
+        assert!(errors.contains(&"'count' must be integer, got a string".to_string()));
+        assert!(errors.contains(&"missing required argument 'regions[0].address'".to_string()));
+        assert!(errors.iter().any(|e| e.starts_with("unknown argument 'locaton'")));
+    }
+}
@@ -1,25 +1,181 @@
 //! MCP Server Handler Implementation
 
-use crate::gdb::{GdbClient, GdbConfig, GdbSessionState, Register, WatchpointType};
+use crate::config::{ServerConfig, TargetProfile};
+use crate::gdb::alloctrace::DEFAULT_FUNCTIONS;
+use crate::gdb::autoresume::{StopMatcher, RESUMABLE_REASONS};
+use crate::gdb::backend::BackendFeature;
+use crate::gdb::compare::{self, CompareTarget, MAX_COMPARE_BYTES};
+use crate::gdb::container;
+use crate::gdb::convenience::validate_name;
+use crate::gdb::ctxswitch::{SwitchTraceSpec, DEFAULT_SWITCH_SYMBOL, DEFAULT_TASK_FIELDS};
+use crate::gdb::diagnostics::{Diagnostics, Severity};
+use crate::gdb::evalhistory::EvalQuery;
+use crate::gdb::exprdiff::{self, DiffAction, ExprDiff};
+use crate::gdb::fpunwind::{FrameLayout, FrameSource};
+use crate::gdb::hooks::Hooks;
+use crate::gdb::image::{self, PixelFormat};
+use crate::gdb::loglocations::{parse_log, LogLineKind, LogLocation};
+use crate::gdb::mitrace::VERBOSE;
+use crate::gdb::optimized::{is_optimized_out, optimization_hint};
+use crate::gdb::parser::{parse_address, parse_hex_bytes};
+use crate::gdb::patch::PatchCode;
+use crate::gdb::pcsample::{PcHistogram, PcSample, MIN_SAMPLE_INTERVAL_MS};
+use crate::gdb::plot::{self, ElementType};
+use crate::gdb::qemu;
+use crate::gdb::registers;
+use crate::gdb::smp::default_registers;
+use crate::gdb::softwatch::{is_hardware_limit, WatchMode};
+use crate::gdb::stackscan::MAX_SCAN_WORDS;
+use crate::gdb::vectors;
+use crate::gdb::timeline::TimelineEntry;
+use crate::gdb::tunnel::{free_local_port, SshTunnel};
+use crate::gdb::x86::DescriptorTable;
+use crate::gdb::{BackendKind, BreakInsertOptions, Frame, FrameContext, GdbClient, GdbConfig, GdbError, GdbSessionState, InterruptMethod, ListWalkOptions, Register, SkipKind, StepSequence, StopReason, StepWatch, WatchpointType};
+use crate::mcp::audit::{AuditLog, AuditStatus};
+use crate::mcp::limits::Limits;
+use crate::mcp::notes::{self, NotesStore, SavedBreakpoint};
+use crate::mcp::peer::ClientPeer;
+use crate::mcp::replay::REQUEST_TOKEN;
+use crate::mcp::roots::{parse_roots, resolve_path};
+use crate::mcp::schema::validate_arguments;
+use crate::mcp::sessions::{Session, SessionInfo, SessionRegistry, SharedClient};
 use crate::mcp::protocol::*;
-use crate::mcp::tools::get_all_tools;
-use anyhow::Result;
-use std::sync::Arc;
-use tokio::sync::RwLock;
-use tracing::{debug, info};
+use crate::mcp::recording::{Recording, RECORDING_TOOLS};
+use crate::mcp::tools::{exposed_tools, ToolDefinition};
+use crate::util::now_ms;
+use anyhow::{anyhow, Result};
+use std::collections::{BTreeMap, HashSet};
+use std::path::PathBuf;
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
+use tracing::{debug, info, warn};
 
 /// GDB MCP Server
 pub struct GdbMcpServer {
-    client: Arc<RwLock<Option<GdbClient>>>,
+    /// Session this connection works on
+    session: Mutex<Arc<Session>>,
+    /// Named sessions, shared by every connection of a daemon
+    registry: SessionRegistry,
+    audit: Mutex<AuditLog>,
+    /// Tool calls captured by `gdb_record_session_start`
+    recording: Mutex<Option<Recording>>,
+    /// Tools exposed by the configured profile
+    tools: Vec<ToolDefinition>,
+    /// Maximum number of tools per `tools/list` page (0 = unlimited)
+    page_size: usize,
+    peer: Arc<ClientPeer>,
+    /// Client workspace roots (None until fetched with `roots/list`)
+    roots: Mutex<Option<Vec<PathBuf>>>,
+    confirm_destructive: bool,
+    /// Default idle timeout of new sessions, in minutes
+    idle_timeout_minutes: Option<u64>,
+    /// Default GDB data directory of new sessions
+    data_directory: Option<String>,
+    /// Whether new sessions load the bundled Python helpers by default
+    python_helpers: bool,
+    /// Safety caps of new sessions
+    limits: Limits,
+    /// Named target profiles from the configuration
+    profiles: BTreeMap<String, TargetProfile>,
+    /// Lifecycle hooks of every session
+    hooks: Hooks,
+    /// Per-executable notes kept across sessions
+    notes: Option<NotesStore>,
+    /// Assembler of `gdb_patch_code`, if configured
+    assembler: Option<String>,
+    /// Default PC sampling interval of budgeted runs
+    pc_sample_interval_ms: u64,
 }
 
+/// How long to wait for the user to answer a confirmation request
+const CONFIRM_TIMEOUT: Duration = Duration::from_secs(300);
+
+/// Longest sampling period accepted by `gdb_poll_memory`
+const MAX_POLL_DURATION_MS: u64 = 60_000;
+
+/// Most array elements `gdb_array_plot` reads
+const MAX_PLOT_SAMPLES: u64 = 1 << 20;
+
+/// Largest `hit_count` of `gdb_continue`
+const MAX_HIT_COUNT: u64 = 10_000;
+
+/// Largest region `gdb_watch_region` watches
+const MAX_WATCH_REGION: u64 = 1 << 20;
+
+/// Most switches one `gdb_trace_context_switch` call logs
+const MAX_SWITCHES: u64 = 1000;
+
+/// URI of the session event timeline resource
+const EVENTS_URI: &str = "gdb://events";
+
+/// Most events returned by one read of `gdb://events`
+const MAX_EVENTS_PER_READ: usize = 1000;
+
+/// How long to wait for the client to answer `roots/list`
+const ROOTS_TIMEOUT: Duration = Duration::from_secs(10);
+
 impl GdbMcpServer {
     pub fn new() -> Self {
+        Self::with_config(ServerConfig::default())
+    }
+
+    /// Create a server with the given configuration
+    pub fn with_config(config: ServerConfig) -> Self {
+        Self::with_connection(config, SessionRegistry::default(), ClientPeer::default())
+    }
+
+    /// Create a server for one client connection, sharing named sessions
+    /// with the other connections through `registry`
+    pub fn with_connection(config: ServerConfig, registry: SessionRegistry, peer: ClientPeer) -> Self {
         Self {
-            client: Arc::new(RwLock::new(None)),
+            session: Mutex::new(Arc::new(Session::new(None))),
+            registry,
+            audit: Mutex::new(AuditLog::new(config.audit)),
+            recording: Mutex::new(None),
+            tools: exposed_tools(&config.tools),
+            page_size: config.tools.page_size,
+            peer: Arc::new(peer),
+            roots: Mutex::new(None),
+            confirm_destructive: config.confirm_destructive,
+            idle_timeout_minutes: config.idle_timeout_minutes,
+            data_directory: config.data_directory,
+            python_helpers: config.python_helpers,
+            limits: config.limits,
+            profiles: config.profiles,
+            hooks: config.hooks,
+            notes: config.notes_dir.map(NotesStore::new),
+            assembler: config.assembler,
+            pc_sample_interval_ms: config.pc_sample_interval_ms.unwrap_or(0),
+        }
+    }
+
+    /// Tear down every session (named ones included) before the server exits
+    pub async fn shutdown(&self) {
+        self.current_session().shutdown().await;
+        for session in self.registry.drain() {
+            session.shutdown().await;
         }
     }
 
+    /// Tear down the connection's unnamed session when the client
+    /// disconnects; named sessions stay available for the next connection
+    pub async fn disconnect(&self) {
+        let session = self.current_session();
+        if session.name.is_none() {
+            session.shutdown().await;
+        }
+    }
+
+    /// The session this connection works on
+    fn current_session(&self) -> Arc<Session> {
+        Arc::clone(&self.session.lock().unwrap())
+    }
+
+    /// GDB client slot of the current session
+    fn client(&self) -> SharedClient {
+        Arc::clone(&self.current_session().client)
+    }
+
     /// Get server info
     pub fn get_info(&self) -> InitializeResult {
         InitializeResult {
@@ -28,6 +184,10 @@ impl GdbMcpServer {
                 tools: Some(ToolsCapability {
                     list_changed: Some(false),
                 }),
+                resources: Some(ResourcesCapability {
+                    subscribe: Some(false),
+                    list_changed: Some(false),
+                }),
                 ..Default::default()
             },
             server_info: Implementation {
@@ -44,15 +204,118 @@ impl GdbMcpServer {
     }
 
     /// Handle initialize request
-    pub async fn handle_initialize(&self, _params: Option<serde_json::Value>) -> Result<serde_json::Value> {
+    pub async fn handle_initialize(&self, params: Option<serde_json::Value>) -> Result<serde_json::Value> {
+        let capabilities = params.as_ref()
+            .and_then(|p| p.get("capabilities"))
+            .cloned()
+            .unwrap_or(serde_json::Value::Null);
+        self.peer.set_capabilities(capabilities);
+
         let info = self.get_info();
         Ok(serde_json::to_value(info)?)
     }
 
+    /// The connected client, for routing responses to server-initiated requests
+    pub fn peer(&self) -> Arc<ClientPeer> {
+        Arc::clone(&self.peer)
+    }
+
+    /// Forget the cached roots after `notifications/roots/list_changed`
+    pub fn roots_changed(&self) {
+        *self.roots.lock().unwrap() = None;
+    }
+
+    /// Get the client's workspace roots, asking the client on first use.
+    /// Empty when the client does not support roots.
+    async fn client_roots(&self) -> Vec<PathBuf> {
+        if !self.peer.supports("roots") {
+            return Vec::new();
+        }
+        if let Some(roots) = self.roots.lock().unwrap().clone() {
+            return roots;
+        }
+
+        match self.peer.request("roots/list", None, ROOTS_TIMEOUT).await {
+            Ok(result) => {
+                let roots = parse_roots(&result);
+                info!("Client roots: {:?}", roots);
+                *self.roots.lock().unwrap() = Some(roots.clone());
+                roots
+            }
+            Err(e) => {
+                warn!("Failed to list client roots: {}", e);
+                Vec::new()
+            }
+        }
+    }
+
+    /// Resolve a file path argument against the client's roots
+    async fn resolve_path(&self, path: &str) -> Result<String> {
+        let roots = self.client_roots().await;
+        Ok(resolve_path(path, &roots)?.to_string_lossy().into_owned())
+    }
+
+    /// Ask the user to approve a destructive action when `confirm_destructive`
+    /// is set. Fails closed if the client cannot ask.
+    async fn confirm(&self, action: &str) -> Result<()> {
+        if !self.confirm_destructive {
+            return Ok(());
+        }
+        if !self.peer.supports("elicitation") {
+            return Err(ToolError {
+                code: "confirmation_unavailable",
+                message: format!(
+                    "Refusing to {}: destructive actions require confirmation, but the client does not support elicitation",
+                    action
+                ),
+            }.into());
+        }
+
+        let params = serde_json::json!({
+            "message": format!("The debugger wants to {}. Allow it?", action),
+            "requestedSchema": {
+                "type": "object",
+                "properties": {
+                    "confirm": {
+                        "type": "boolean",
+                        "title": "Allow",
+                        "description": "Allow this destructive action on the target"
+                    }
+                },
+                "required": ["confirm"]
+            }
+        });
+        let result = self.peer.request("elicitation/create", Some(params), CONFIRM_TIMEOUT).await?;
+
+        let accepted = result.get("action").and_then(|a| a.as_str()) == Some("accept")
+            && result.pointer("/content/confirm").and_then(|c| c.as_bool()) == Some(true);
+        if accepted {
+            info!("User confirmed: {}", action);
+            Ok(())
+        } else {
+            Err(ToolError {
+                code: "declined_by_user",
+                message: format!("The user did not allow the debugger to {}", action),
+            }.into())
+        }
+    }
+
     /// Handle tools/list request
-    pub async fn handle_tools_list(&self) -> Result<serde_json::Value> {
-        let all_tools = get_all_tools();
-        let tools: Vec<Tool> = all_tools
+    pub async fn handle_tools_list(&self, params: Option<serde_json::Value>) -> Result<serde_json::Value> {
+        let start = match params.as_ref().and_then(|p| p.get("cursor")) {
+            None | Some(serde_json::Value::Null) => 0,
+            Some(cursor) => cursor.as_str()
+                .and_then(|c| c.parse::<usize>().ok())
+                .filter(|&offset| offset <= self.tools.len())
+                .ok_or_else(|| JsonRpcError::invalid_params("Invalid cursor"))?,
+        };
+        let end = if self.page_size == 0 {
+            self.tools.len()
+        } else {
+            (start + self.page_size).min(self.tools.len())
+        };
+
+        let tools: Vec<Tool> = self.tools[start..end]
             .iter()
             .map(|t| Tool {
                 name: t.name.clone(),
@@ -63,56 +326,355 @@ impl GdbMcpServer {
 
         let result = ListToolsResult {
             tools,
+            // The cursor is the offset of the next page
+            next_cursor: (end < self.tools.len()).then(|| end.to_string()),
+        };
+
+        Ok(serde_json::to_value(result)?)
+    }
+
+    /// Handle resources/list request
+    pub async fn handle_resources_list(&self) -> Result<serde_json::Value> {
+        let result = ListResourcesResult {
+            resources: vec![Resource {
+                uri: EVENTS_URI.to_string(),
+                name: "Session events".to_string(),
+                description: Some(
+                    "Stops, breakpoint and thread notifications, console/target/log output and tool calls of the current GDB session in order, with monotonic timestamps (t_us). Append ?since=<seq> to read only newer events.".to_string(),
+                ),
+                mime_type: Some("application/json".to_string()),
+            }],
             next_cursor: None,
         };
+        Ok(serde_json::to_value(result)?)
+    }
+
+    /// Handle resources/read request
+    pub async fn handle_resources_read(&self, params: Option<serde_json::Value>) -> Result<serde_json::Value> {
+        let uri = params.as_ref()
+            .and_then(|p| p.get("uri").and_then(|v| v.as_str()))
+            .ok_or_else(|| JsonRpcError::invalid_params("Missing uri"))?;
+        let (path, query) = uri.split_once('?').unwrap_or((uri, ""));
+        if path != EVENTS_URI {
+            return Err(JsonRpcError::invalid_params(&format!("Unknown resource: {}", uri)).into());
+        }
+        let mut since = 0;
+        let mut limit = MAX_EVENTS_PER_READ;
+        for pair in query.split('&').filter(|p| !p.is_empty()) {
+            let (key, value) = pair.split_once('=').unwrap_or((pair, ""));
+            let value = value.parse::<u64>()
+                .map_err(|_| JsonRpcError::invalid_params(&format!("{} must be a non-negative integer", key)))?;
+            match key {
+                "since" => since = value,
+                "limit" => limit = (value as usize).min(MAX_EVENTS_PER_READ),
+                _ => return Err(JsonRpcError::invalid_params(&format!("Unknown parameter '{}' in {}", key, uri)).into()),
+            }
+        }
 
+        let report = match self.current_session().timeline() {
+            Some(timeline) => {
+                let timeline = timeline.lock().unwrap();
+                let events = timeline.since(since, limit);
+                serde_json::json!({
+                    "next_seq": events.last().map(|e| e.seq + 1).unwrap_or_else(|| timeline.next_seq().max(since)),
+                    "dropped": timeline.dropped(),
+                    "events": events,
+                })
+            }
+            None => serde_json::json!({ "next_seq": 0, "dropped": 0, "events": [] }),
+        };
+        let result = ReadResourceResult {
+            contents: vec![ResourceContents {
+                uri: uri.to_string(),
+                mime_type: Some("application/json".to_string()),
+                text: Some(serde_json::to_string_pretty(&report)?),
+                blob: None,
+            }],
+        };
         Ok(serde_json::to_value(result)?)
     }
 
     /// Handle tools/call request
     pub async fn handle_tools_call(&self, params: Option<serde_json::Value>) -> Result<serde_json::Value> {
-        let params = params.ok_or_else(|| anyhow::anyhow!("Missing params"))?;
-        let request: CallToolRequest = serde_json::from_value(params)?;
+        let params = params.ok_or_else(|| JsonRpcError::invalid_params("Missing params"))?;
+        let request: CallToolRequest = serde_json::from_value(params)
+            .map_err(|e| JsonRpcError::invalid_params(&format!("Invalid tools/call params: {}", e)))?;
+        let name = request.name.clone();
         
+        let Some(tool) = self.tools.iter().find(|t| t.name == name) else {
+            return Err(JsonRpcError::invalid_params(&format!("Unknown tool: {}", name)).into());
+        };
+
         debug!("Calling tool: {}", request.name);
-        
-        let result = match request.name.as_str() {
+
+        let arguments = request.arguments.clone();
+        let errors = validate_arguments(arguments.as_ref().unwrap_or(&serde_json::Map::new()), &tool.input_schema);
+
+        if let Some(timeline) = self.current_session().timeline() {
+            let arguments = self.audit.lock().unwrap().redact(arguments.as_ref());
+            timeline.lock().unwrap().push(TimelineEntry::ToolCall { tool: name.clone(), arguments });
+        }
+
+        self.current_session().touch();
+        let diagnostics = self.current_session().diagnostics();
+        let diagnostics_seen = diagnostics.as_ref().map(|d| d.lock().unwrap().last_seq());
+        let started = Instant::now();
+        let verbose = arguments.as_ref()
+            .and_then(|a| a.get(VERBOSE))
+            .and_then(|v| v.as_bool())
+            .unwrap_or_else(|| self.current_session().verbose());
+        let trace = self.current_session().mi_trace().filter(|_| verbose).map(|trace| {
+            let mark = trace.lock().unwrap().begin();
+            (trace, mark)
+        });
+        let limited = if errors.is_empty() {
+            self.current_session().limits().check_call(&name, arguments.as_ref(), now_ms()).err()
+        } else {
+            None
+        };
+        let result = if let Some(limit) = limited {
+            Ok(CallToolResult::from(limit))
+        } else if errors.is_empty() {
+            self.dispatch_once(request).await
+        } else {
+            Ok(CallToolResult {
+                meta: Some(serde_json::json!({ "errorCode": "invalid_params", "errors": errors })),
+                ..CallToolResult::error_text(format!("Invalid arguments for {}:\n- {}", name, errors.join("\n- ")))
+            })
+        };
+        let mi_commands = trace.map(|(trace, mark)| trace.lock().unwrap().end(mark));
+
+        let (status, error) = match &result {
+            Ok(r) if r.is_error == Some(true) => (AuditStatus::Error, None),
+            Ok(_) => (AuditStatus::Ok, None),
+            Err(e) => (AuditStatus::Failed, Some(e.to_string())),
+        };
+        if let Some(timeline) = self.current_session().timeline() {
+            timeline.lock().unwrap().push(TimelineEntry::ToolResult {
+                tool: name.clone(),
+                status: serde_json::to_value(&status)?.as_str().unwrap_or_default().to_string(),
+                duration_ms: started.elapsed().as_millis() as u64,
+            });
+        }
+        self.audit.lock().unwrap().record(&name, arguments.as_ref(), status, started.elapsed(), error);
+
+        let result = match result {
+            Err(e) => match e.downcast::<ToolError>() {
+                Ok(tool_error) => CallToolResult::from(tool_error),
+                Err(e) => match e.downcast_ref::<GdbError>() {
+                    Some(gdb_error) => {
+                        let mut message = format!("{:#}", e);
+                        if message.contains("No symbol table is loaded") {
+                            if let Some(warning) = self.symbols_warning().await {
+                                message = format!("{}\n{} Use gdb_load_file's suggestions to load the debug info.", message, warning);
+                            }
+                        }
+                        CallToolResult::error_with_code(&gdb_error.code, message)
+                    }
+                    None => return Err(e),
+                },
+            },
+            Ok(result) => result,
+        };
+        let mut result = self.with_critical_diagnostics(result, diagnostics, diagnostics_seen);
+        if let Some(mi_commands) = mi_commands {
+            let meta = result.meta.get_or_insert_with(|| serde_json::json!({}));
+            meta["miCommands"] = serde_json::to_value(mi_commands)?;
+        }
+        self.current_session().touch();
+        if !RECORDING_TOOLS.contains(&name.as_str()) {
+            if let Some(recording) = self.recording.lock().unwrap().as_mut() {
+                recording.push(&name, arguments.as_ref(), &result);
+            }
+        }
+        Ok(serde_json::to_value(result)?)
+    }
+
+    /// Breakpoints the session may still add (None without a limit)
+    fn breakpoint_budget(&self, client: &mut GdbClient) -> Result<Option<usize>> {
+        let Some(max) = self.current_session().limits().limits.max_breakpoints else {
+            return Ok(None);
+        };
+        let existing = client.break_list()?.len();
+        Ok(Some((max as usize).saturating_sub(existing)))
+    }
+
+    /// Fail with `limit_exceeded` if `adding` breakpoints would exceed the
+    /// session's limit
+    fn check_breakpoint_limit(&self, client: &mut GdbClient, adding: usize) -> Result<()> {
+        if self.current_session().limits().limits.max_breakpoints.is_none() {
+            return Ok(());
+        }
+        let existing = client.break_list()?.len();
+        self.current_session().limits().check_breakpoints(existing, adding)?;
+        Ok(())
+    }
+
+    /// Warning of the current session's executable lacking debug symbols
+    async fn symbols_warning(&self) -> Option<String> {
+        let guard = self.client().read_owned().await;
+        guard.as_ref().and_then(|client| client.state().symbols_warning)
+    }
+
+    /// Attach the critical GDB warnings printed during a tool call to its
+    /// result. A call that started a new session gets those of its startup.
+    fn with_critical_diagnostics(
+        &self,
+        mut result: CallToolResult,
+        before: Option<Arc<Mutex<Diagnostics>>>,
+        seen: Option<u64>,
+    ) -> CallToolResult {
+        let Some(after) = self.current_session().diagnostics() else {
+            return result;
+        };
+        let seen = match before {
+            Some(before) if Arc::ptr_eq(&before, &after) => seen.unwrap_or(0),
+            _ => 0,
+        };
+        for line in after.lock().unwrap().critical_since(seen) {
+            result.content.push(Content::text(format!("GDB warning: {}", line.text)));
+        }
+        result
+    }
+
+    /// Dispatch a tool call, replaying the earlier result if its
+    /// `request_token` has been seen before
+    async fn dispatch_once(&self, mut request: CallToolRequest) -> Result<CallToolResult> {
+        let token = request.arguments.as_mut()
+            .and_then(|args| args.remove(REQUEST_TOKEN))
+            .and_then(|token| token.as_str().map(str::to_string));
+        let Some(token) = token else {
+            return self.dispatch_tool(request).await;
+        };
+
+        // Tracing a retry does not make it a different call
+        let mut arguments = request.arguments.clone().unwrap_or_default();
+        arguments.remove(VERBOSE);
+        let fingerprint = format!("{} {}", request.name, serde_json::to_string(&arguments)?);
+        // Keep the session the call started on, in case it switches sessions
+        let session = self.current_session();
+        session.replays.run(&token, fingerprint, self.dispatch_tool(request)).await
+    }
+
+    /// Dispatch a tool call to its handler
+    async fn dispatch_tool(&self, request: CallToolRequest) -> Result<CallToolResult> {
+        match request.name.as_str() {
             "gdb_start" => self.handle_start(request.arguments).await,
             "gdb_stop" => self.handle_stop().await,
+            "gdb_emergency_stop" => self.handle_emergency_stop().await,
+            "gdb_session_list" => self.handle_session_list().await,
+            "gdb_session_attach" => self.handle_session_attach(request.arguments).await,
+            "gdb_session_detach" => self.handle_session_detach().await,
             "gdb_load_file" => self.handle_load_file(request.arguments).await,
             "gdb_target_connect" => self.handle_target_connect(request.arguments).await,
             "gdb_target_disconnect" => self.handle_target_disconnect().await,
+            "gdb_container_attach" => self.handle_container_attach(request.arguments).await,
             "gdb_break_insert" => self.handle_break_insert(request.arguments).await,
             "gdb_break_delete" => self.handle_break_delete(request.arguments).await,
-            "gdb_break_list" => self.handle_break_list().await,
+            "gdb_break_list" => self.handle_break_list(request.arguments).await,
+            "gdb_break_find" => self.handle_break_find(request.arguments).await,
+            "gdb_break_vectors" => self.handle_break_vectors(request.arguments).await,
             "gdb_break_toggle" => self.handle_break_toggle(request.arguments).await,
+            "gdb_break_annotate" => self.handle_break_annotate(request.arguments).await,
+            "gdb_break_from_log" => self.handle_break_from_log(request.arguments).await,
+            "gdb_catch_library" => self.handle_catch_library(request.arguments).await,
+            "gdb_stop_on_solib_events" => self.handle_stop_on_solib_events(request.arguments).await,
+            "gdb_autoresume_rules" => self.handle_autoresume_rules(request.arguments).await,
+            "gdb_override_on_hit" => self.handle_override_on_hit(request.arguments).await,
+            "gdb_alloc_trace" => self.handle_alloc_trace(request.arguments).await,
             "gdb_watch_insert" => self.handle_watch_insert(request.arguments).await,
             "gdb_watch_delete" => self.handle_watch_delete(request.arguments).await,
+            "gdb_watch_suspend" => self.handle_watch_suspend().await,
+            "gdb_watch_resume" => self.handle_watch_resume().await,
+            "gdb_watch_region" => self.handle_watch_region(request.arguments).await,
             "gdb_run" => self.handle_run(request.arguments).await,
-            "gdb_continue" => self.handle_continue().await,
+            "gdb_program_output" => self.handle_program_output(request.arguments).await,
+            "gdb_continue" => self.handle_continue(request.arguments).await,
             "gdb_next" => self.handle_next(request.arguments).await,
             "gdb_step" => self.handle_step(request.arguments).await,
             "gdb_stepi" => self.handle_stepi(request.arguments).await,
             "gdb_nexti" => self.handle_nexti(request.arguments).await,
-            "gdb_finish" => self.handle_finish().await,
+            "gdb_step_until_memory_change" => self.handle_step_until_memory_change(request.arguments).await,
+            "gdb_step_until_register_change" => self.handle_step_until_register_change(request.arguments).await,
+            "gdb_step_asm_until_branch" => self.handle_step_asm_until_branch(request.arguments).await,
+            "gdb_call_summary" => self.handle_call_summary(request.arguments).await,
+            "gdb_expr_diff" => self.handle_expr_diff(request.arguments).await,
+            "gdb_skip" => self.handle_skip(request.arguments).await,
+            "gdb_skip_list" => self.handle_skip_list().await,
+            "gdb_skip_delete" => self.handle_skip_delete(request.arguments).await,
+            "gdb_finish" => self.handle_finish(request.arguments).await,
             "gdb_interrupt" => self.handle_interrupt().await,
-            "gdb_stack_list" => self.handle_stack_list().await,
+            "gdb_stack_list" => self.handle_stack_list(request.arguments).await,
+            "gdb_stack_depth" => self.handle_stack_depth(request.arguments).await,
+            "gdb_backtrace_full" => self.handle_backtrace_full(request.arguments).await,
             "gdb_stack_select" => self.handle_stack_select(request.arguments).await,
             "gdb_stack_info" => self.handle_stack_info().await,
+            "gdb_stack_scan" => self.handle_stack_scan(request.arguments).await,
+            "gdb_unwind_fallback" => self.handle_unwind_fallback(request.arguments).await,
+            "gdb_stack_guard_check" => self.handle_stack_guard_check(request.arguments).await,
+            "gdb_siginfo" => self.handle_siginfo().await,
+            "gdb_errno" => self.handle_errno(request.arguments).await,
+            "gdb_frame_up" => self.handle_frame_move(request.arguments, true).await,
+            "gdb_frame_down" => self.handle_frame_move(request.arguments, false).await,
             "gdb_thread_list" => self.handle_thread_list().await,
             "gdb_thread_select" => self.handle_thread_select(request.arguments).await,
+            "gdb_smp_state" => self.handle_smp_state(request.arguments).await,
+            "gdb_smp_pin" => self.handle_smp_pin(request.arguments).await,
+            "gdb_trace_context_switch" => self.handle_trace_context_switch(request.arguments).await,
             "gdb_memory_read" => self.handle_memory_read(request.arguments).await,
+            "gdb_memory_visualize" => self.handle_memory_visualize(request.arguments).await,
+            "gdb_array_plot" => self.handle_array_plot(request.arguments).await,
             "gdb_memory_write" => self.handle_memory_write(request.arguments).await,
+            "gdb_patch_code" => self.handle_patch_code(request.arguments).await,
+            "gdb_patch_revert" => self.handle_patch_revert(request.arguments).await,
+            "gdb_poll_memory" => self.handle_poll_memory(request.arguments).await,
+            "gdb_memory_snapshot" => self.handle_memory_snapshot(request.arguments).await,
+            "gdb_memory_diff" => self.handle_memory_diff(request.arguments).await,
+            "gdb_compare" => self.handle_compare(request.arguments).await,
+            "gdb_disassemble_function" => self.handle_disassemble_function(request.arguments).await,
+            "gdb_basic_block" => self.handle_basic_block(request.arguments).await,
+            "gdb_line_table" => self.handle_line_table(request.arguments).await,
+            "gdb_source_verify" => self.handle_source_verify(request.arguments).await,
+            "gdb_line_coverage" => self.handle_line_coverage(request.arguments).await,
+            "gdb_function_list" => self.handle_function_list(request.arguments).await,
+            "gdb_globals" => self.handle_globals(request.arguments).await,
             "gdb_evaluate" => self.handle_evaluate(request.arguments).await,
+            "gdb_convenience_set" => self.handle_convenience_set(request.arguments).await,
+            "gdb_convenience_get" => self.handle_convenience_get(request.arguments).await,
+            "gdb_eval_history" => self.handle_eval_history(request.arguments).await,
+            "gdb_tls_read" => self.handle_tls_read(request.arguments).await,
+            "gdb_python_helper" => self.handle_python_helper(request.arguments).await,
             "gdb_registers_list" => self.handle_registers_list().await,
+            "gdb_registers_profile" => self.handle_registers_profile(request.arguments).await,
+            "gdb_riscv_csrs" => self.handle_riscv_csrs(request.arguments).await,
+            "gdb_x86_descriptors" => self.handle_x86_descriptors(request.arguments).await,
+            "gdb_x86_page_walk" => self.handle_x86_page_walk(request.arguments).await,
+            "gdb_qemu_monitor" => self.handle_qemu_monitor(request.arguments).await,
             "gdb_register_set" => self.handle_register_set(request.arguments).await,
             "gdb_variable_info" => self.handle_variable_info(request.arguments).await,
+            "gdb_decode_struct" => self.handle_decode_struct(request.arguments).await,
+            "gdb_walk_list" => self.handle_walk_list(request.arguments).await,
+            "gdb_var_create" => self.handle_var_create(request.arguments).await,
+            "gdb_var_update" => self.handle_var_update().await,
+            "gdb_variable_format" => self.handle_variable_format(request.arguments).await,
+            "gdb_var_delete" => self.handle_var_delete(request.arguments).await,
             "gdb_status" => self.handle_status().await,
+            "gdb_endian" => self.handle_endian(request.arguments).await,
+            "gdb_diagnostics" => self.handle_diagnostics(request.arguments).await,
+            "gdb_health" => self.handle_health(request.arguments).await,
             "gdb_raw_command" => self.handle_raw_command(request.arguments).await,
-            _ => Ok(CallToolResult::error_text(format!("Unknown tool: {}", request.name))),
-        };
-
-        Ok(serde_json::to_value(result?)?)
+            "gdb_notes_get" => self.handle_notes_get(request.arguments).await,
+            "gdb_notes_set" => self.handle_notes_set(request.arguments).await,
+            "gdb_export_script" => self.handle_export_script(request.arguments).await,
+            "gdb_audit_log" => self.handle_audit_log(request.arguments).await,
+            "gdb_record_session_start" => self.handle_record_session_start(request.arguments).await,
+            "gdb_record_session_stop" => self.handle_record_session_stop().await,
+            "gdb_break_enable_all" => self.handle_break_enable_all().await,
+            "gdb_break_disable_all" => self.handle_break_disable_all().await,
+            "gdb_break_history" => self.handle_break_history(request.arguments).await,
+            "gdb_break_stats" => self.handle_break_stats(request.arguments).await,
+            _ => Err(JsonRpcError::invalid_params(&format!("Unknown tool: {}", request.name)).into()),
+        }
     }
 
     // ========================================================================
@@ -120,67 +682,360 @@ impl GdbMcpServer {
     // ========================================================================
 
     async fn handle_start(&self, args: Option<serde_json::Map<String, serde_json::Value>>) -> Result<CallToolResult> {
+        let backend_kind = match args.as_ref().and_then(|a| a.get("backend")) {
+            Some(v) => serde_json::from_value::<BackendKind>(v.clone())
+                .map_err(|_| ToolError::invalid_argument("backend must be 'gdb' or 'lldb'"))?,
+            None => BackendKind::Gdb,
+        };
+        let backend = backend_kind.backend();
+
+        let profile = match args.as_ref().and_then(|a| a.get("profile").and_then(|v| v.as_str())) {
+            Some(name) => {
+                let profile = self.profiles.get(name).cloned().ok_or_else(|| {
+                    let names: Vec<&str> = self.profiles.keys().map(String::as_str).collect();
+                    ToolError::invalid_argument(format!(
+                        "Unknown profile '{}' (configured: {})",
+                        name,
+                        if names.is_empty() { "none".to_string() } else { names.join(", ") }
+                    ))
+                })?;
+                Some((name.to_string(), profile))
+            }
+            None => None,
+        };
+
         let gdb_path = args
             .as_ref()
             .and_then(|a| a.get("gdb_path"))
             .and_then(|v| v.as_str())
             .map(|s| s.to_string())
-            .unwrap_or_else(|| "gdb-multiarch".to_string());
+            .or_else(|| profile.as_ref().and_then(|(_, p)| p.gdb_path.clone()))
+            .unwrap_or_else(|| backend.default_path().to_string());
+
+        let architecture = args.as_ref()
+            .and_then(|a| a.get("architecture").and_then(|v| v.as_str()).map(|s| s.to_string()))
+            .or_else(|| profile.as_ref().and_then(|(_, p)| p.architecture.clone()));
+        let working_dir = match args.as_ref().and_then(|a| a.get("working_dir").and_then(|v| v.as_str())) {
+            Some(dir) => Some(self.resolve_path(dir).await?),
+            None => None,
+        };
+        let interrupt_on_timeout = args.as_ref()
+            .and_then(|a| a.get("interrupt_on_timeout").and_then(|v| v.as_bool()))
+            .unwrap_or(false);
+        let startup_timeout_ms = args.as_ref()
+            .and_then(|a| a.get("startup_timeout_ms").and_then(|v| v.as_u64()))
+            .unwrap_or(GdbConfig::default().startup_timeout_ms);
+
+        let data_directory = match args.as_ref().and_then(|a| a.get("data_directory").and_then(|v| v.as_str())) {
+            Some(dir) => Some(self.resolve_path(dir).await?),
+            None => self.data_directory.clone(),
+        };
+        if data_directory.is_some() && !backend.supports(BackendFeature::GdbCli) {
+            return Err(ToolError::invalid_argument(format!("data_directory is not supported by the {} backend", backend.name())).into());
+        }
+        let python_helpers = args.as_ref()
+            .and_then(|a| a.get("python_helpers").and_then(|v| v.as_bool()))
+            .unwrap_or(self.python_helpers);
+
+        let mut env = std::collections::HashMap::new();
+        if let Some(vars) = args.as_ref().and_then(|a| a.get("env").and_then(|v| v.as_object())) {
+            for (key, value) in vars {
+                let value = value.as_str()
+                    .ok_or_else(|| ToolError::invalid_argument(format!("env value for '{}' must be a string", key)))?;
+                env.insert(key.clone(), value.to_string());
+            }
+        }
+
+        let mut gdb_args = backend.default_args();
+        if let Some(extra) = args.as_ref().and_then(|a| a.get("gdb_args").and_then(|v| v.as_array())) {
+            for arg in extra {
+                let arg = arg.as_str().ok_or_else(|| ToolError::invalid_argument("gdb_args must be an array of strings"))?;
+                gdb_args.push(arg.to_string());
+            }
+        }
+
+        let limits = match args.as_ref().and_then(|a| a.get("limits")) {
+            Some(v) => serde_json::from_value::<Limits>(v.clone())
+                .map_err(|e| ToolError::invalid_argument(format!("Invalid limits: {}", e)))?,
+            None => Limits::default(),
+        };
+        let limits = self.limits.tightened(limits);
+
+        let idle_timeout_minutes = args.as_ref()
+            .and_then(|a| a.get("idle_timeout_minutes").and_then(|v| v.as_u64()))
+            .or(self.idle_timeout_minutes)
+            .filter(|&minutes| minutes > 0);
+
+        let name = args.as_ref().and_then(|a| a.get("name").and_then(|v| v.as_str()).map(|s| s.to_string()));
+        if let Some(name) = &name {
+            if self.registry.contains(name) {
+                return Err(ToolError {
+                    code: "session_exists",
+                    message: format!("A session named '{}' already exists. Use gdb_session_attach to use it.", name),
+                }.into());
+            }
+        }
 
-        let architecture = args
-            .and_then(|a| a.get("architecture").and_then(|v| v.as_str()).map(|s| s.to_string()));
+        info!("Starting {} session with: {} {:?}", backend.name(), gdb_path, gdb_args);
 
-        info!("Starting GDB session with: {}", gdb_path);
+        // A named session can be started next to the current named one,
+        // which stays in the registry
+        let current = self.current_session();
+        let session = match &name {
+            Some(name) if current.name.is_some() => Arc::new(Session::new(Some(name.clone()))),
+            Some(name) if current.client.read().await.is_none() => Arc::new(Session::new(Some(name.clone()))),
+            None if current.name.is_none() => current,
+            None if current.client.read().await.is_none() => Arc::new(Session::new(None)),
+            _ => return Ok(CallToolResult::error_text("GDB session already running. Use gdb_stop first.")),
+        };
 
-        let mut guard = self.client.write().await;
+        let mut guard = session.client.clone().write_owned().await;
         if guard.is_some() {
             return Ok(CallToolResult::error_text("GDB session already running. Use gdb_stop first."));
         }
 
         let config = GdbConfig {
             gdb_path,
+            gdb_args,
             architecture,
+            env,
+            working_dir,
+            interrupt_on_timeout,
+            backend: backend_kind,
+            startup_timeout_ms,
+            data_directory,
+            python_helpers,
+            hooks: match &profile {
+                Some((_, profile)) => self.hooks.followed_by(&profile.hooks),
+                None => self.hooks.clone(),
+            },
             ..Default::default()
         };
 
+        if session.name.is_some() {
+            self.registry.insert(Arc::clone(&session))?;
+        }
         let mut client = GdbClient::new(config);
-        client.start()?;
+        if let Err(e) = client.start() {
+            if let Some(name) = &session.name {
+                self.registry.remove(name);
+            }
+            return Err(e);
+        }
+
+        let profile_steps = match &profile {
+            Some((name, profile)) => match apply_profile(&mut client, name, profile) {
+                Ok(steps) => steps,
+                Err(e) => {
+                    if let Some(name) = &session.name {
+                        self.registry.remove(name);
+                    }
+                    return Err(e.context(format!("Failed to apply profile '{}'", name)));
+                }
+            },
+            None => Vec::new(),
+        };
 
+        session.arm(&client);
+        session.set_limits(limits);
+        session.set_verbose(args.as_ref().and_then(|a| a.get(VERBOSE)).and_then(|v| v.as_bool()).unwrap_or(false));
         *guard = Some(client);
+        *self.session.lock().unwrap() = Arc::clone(&session);
+        session.set_peer(Arc::clone(&self.peer));
+        if let Some(minutes) = idle_timeout_minutes {
+            session.watch_idle(Duration::from_secs(minutes * 60), self.registry.clone());
+        }
 
-        Ok(CallToolResult::text("GDB session started successfully. Use gdb_load_file to load a program, or gdb_target_connect for remote debugging."))
+        let mut message = if let Some(name) = &session.name {
+            format!(
+                "Named session '{}' started with the {} backend. It survives client reconnects when the server runs as a daemon; re-acquire it with gdb_session_attach.",
+                name,
+                backend.name()
+            )
+        } else if backend_kind == BackendKind::Gdb {
+            "GDB session started successfully. Use gdb_load_file to load a program, or gdb_target_connect for remote debugging.".to_string()
+        } else {
+            format!(
+                "Debugging session started successfully with the {} backend. Use gdb_load_file to load a program. GDB-specific tools such as gdb_skip and gdb_catch_library are unavailable.",
+                backend.name()
+            )
+        };
+        if let Some((name, _)) = &profile {
+            message.push_str(&format!("\nApplied profile '{}'.", name));
+            for step in &profile_steps {
+                message.push_str(&format!("\n- {}", step));
+            }
+        }
+        Ok(CallToolResult::text(message))
     }
 
     async fn handle_stop(&self) -> Result<CallToolResult> {
         info!("Stopping GDB session");
 
-        let mut guard = self.client.write().await;
+        let session = self.current_session();
+        let mut guard = session.client.write().await;
         if let Some(mut client) = guard.take() {
+            session.disarm();
             client.stop()?;
+            if let Some(name) = &session.name {
+                self.registry.remove(name);
+            }
+            *self.session.lock().unwrap() = Arc::new(Session::new(None));
             Ok(CallToolResult::text("GDB session stopped successfully."))
         } else {
             Ok(CallToolResult::error_text("No GDB session is running."))
         }
     }
 
+    /// Kill GDB and every helper process of every session right away. This
+    /// also runs while another tool call is in flight; that call fails once
+    /// GDB is gone.
+    async fn handle_emergency_stop(&self) -> Result<CallToolResult> {
+        warn!("Emergency stop requested");
+        let current = std::mem::replace(&mut *self.session.lock().unwrap(), Arc::new(Session::new(None)));
+        let mut sessions = self.registry.drain();
+        if !sessions.iter().any(|s| Arc::ptr_eq(s, &current)) {
+            sessions.push(current);
+        }
+
+        let mut lines = vec!["Emergency stop complete.".to_string()];
+        let mut killed_any = false;
+        let mut pending_clear = false;
+        for session in sessions {
+            let label = match &session.name {
+                Some(name) => format!(" [session '{}']", name),
+                None => String::new(),
+            };
+            if let Some(report) = session.fire() {
+                killed_any = true;
+                if let Some(pid) = report.gdb_pid {
+                    lines.push(format!("Killed GDB (pid {}){}", pid, label));
+                }
+                for process in &report.processes {
+                    lines.push(format!("Killed {} (pid {}): {}{}", process.name, process.pid, process.command, label));
+                }
+                for error in &report.errors {
+                    lines.push(format!("Error: {}{}", error, label));
+                }
+            }
+
+            // Drop the dead client now, or as soon as the blocked call returns
+            match session.client.try_write() {
+                Ok(mut guard) => {
+                    guard.take();
+                }
+                Err(_) => {
+                    pending_clear = true;
+                    let client = Arc::clone(&session.client);
+                    tokio::spawn(async move {
+                        client.write().await.take();
+                    });
+                }
+            }
+        }
+
+        if !killed_any {
+            return Ok(CallToolResult::text("No GDB session was running; nothing to stop."));
+        }
+        lines.push(if pending_clear {
+            "Sessions cleared; a session held by an in-flight tool call is dropped when that call returns.".to_string()
+        } else {
+            "Sessions cleared.".to_string()
+        });
+        lines.push("Start a new session with gdb_start.".to_string());
+        Ok(CallToolResult::text(lines.join("\n")))
+    }
+
+    async fn handle_session_list(&self) -> Result<CallToolResult> {
+        let current = self.current_session();
+        let sessions: Vec<SessionInfo> = self.registry.list().into_iter().map(|session| {
+            // A session held by an in-flight call is reported as busy
+            let (busy, state) = match session.client.try_read() {
+                Ok(guard) => (false, guard.as_ref().map(|c| c.state())),
+                Err(_) => (true, None),
+            };
+            SessionInfo {
+                name: session.name.clone().unwrap_or_default(),
+                running: busy || state.is_some(),
+                target: state.as_ref().and_then(|s| s.target.clone()),
+                executable: state.as_ref().and_then(|s| s.executable.clone()),
+                current: Arc::ptr_eq(&session, &current),
+                busy,
+                created_ms: session.created_ms,
+            }
+        }).collect();
+        Ok(CallToolResult::text(serde_json::to_string_pretty(&sessions)?))
+    }
+
+    async fn handle_session_attach(&self, args: Option<serde_json::Map<String, serde_json::Value>>) -> Result<CallToolResult> {
+        let name = args.as_ref()
+            .and_then(|a| a.get("name").and_then(|v| v.as_str()).map(|s| s.to_string()))
+            .ok_or_else(|| ToolError::missing_argument("name"))?;
+        let session = self.registry.get(&name).ok_or_else(|| ToolError {
+            code: "unknown_session",
+            message: format!("No session named '{}'. Use gdb_session_list to see the available sessions.", name),
+        })?;
+
+        let current = self.current_session();
+        if current.name.is_none() && current.client.read().await.is_some() {
+            return Ok(CallToolResult::error_text(
+                "An unnamed GDB session is running on this connection. Stop it with gdb_stop before attaching.",
+            ));
+        }
+        session.set_peer(Arc::clone(&self.peer));
+        *self.session.lock().unwrap() = session;
+        info!("Attached to session {}", name);
+
+        let status = self.client().read_owned().await.as_ref().map(|c| c.state()).unwrap_or_default();
+        Ok(CallToolResult::text(format!(
+            "Attached to session '{}'.\n{}",
+            name,
+            serde_json::to_string_pretty(&status)?
+        )))
+    }
+
+    async fn handle_session_detach(&self) -> Result<CallToolResult> {
+        let current = self.current_session();
+        let Some(name) = current.name.clone() else {
+            return Ok(CallToolResult::error_text("This connection is not using a named session."));
+        };
+        *self.session.lock().unwrap() = Arc::new(Session::new(None));
+        Ok(CallToolResult::text(format!(
+            "Detached from session '{}'. It keeps running; re-acquire it with gdb_session_attach.",
+            name
+        )))
+    }
+
     async fn handle_load_file(&self, args: Option<serde_json::Map<String, serde_json::Value>>) -> Result<CallToolResult> {
         let file_path = args
             .and_then(|a| a.get("file_path").and_then(|v| v.as_str()).map(|s| s.to_string()))
-            .ok_or_else(|| anyhow::anyhow!("file_path is required"))?;
+            .ok_or_else(|| ToolError::missing_argument("file_path"))?;
+        let file_path = self.resolve_path(&file_path).await?;
 
         info!("Loading file: {}", file_path);
 
-        let guard = self.client.read().await;
-        let client = guard.as_ref().ok_or_else(|| anyhow::anyhow!("GDB session not started"))?;
-        
-        // We need mutable access, so we'll need to restructure this
-        drop(guard);
-        
-        let mut guard = self.client.write().await;
-        let client = guard.as_mut().ok_or_else(|| anyhow::anyhow!("GDB session not started"))?;
-        client.file_exec_and_symbols(&file_path)?;
+        let mut guard = self.client().write_owned().await;
+        let client = guard.as_mut().ok_or_else(ToolError::no_session)?;
+        let symbols = client.file_exec_and_symbols(&file_path)?;
 
-        Ok(CallToolResult::text(format!("Loaded executable: {}", file_path)))
+        let mut message = with_endian_warning(format!("Loaded executable: {}", file_path), client);
+        if let Some(store) = &self.notes {
+            match store.load(&notes::notes_key(&file_path)) {
+                Ok(saved) if !saved.is_empty() => message.push_str(&format!(
+                    "\nNotes from earlier sessions exist for this executable ({}). Read them with gdb_notes_get.",
+                    saved.describe()
+                )),
+                Ok(_) => {}
+                Err(e) => warn!("Failed to read the notes of {}: {:#}", file_path, e),
+            }
+        }
+        match symbols {
+            Some(check) => Ok(CallToolResult::success(vec![
+                Content::text(format!("{}\nWarning: {}", message, check.warning())),
+                Content::text(serde_json::to_string_pretty(&check)?),
+            ])),
+            None => Ok(CallToolResult::text(message)),
+        }
     }
 
     async fn handle_target_connect(&self, args: Option<serde_json::Map<String, serde_json::Value>>) -> Result<CallToolResult> {
@@ -190,52 +1045,233 @@ impl GdbMcpServer {
         let port = args.as_ref().and_then(|a| a.get("port").and_then(|v| v.as_u64()).map(|n| n as u16));
         let serial_port = args.as_ref().and_then(|a| a.get("serial_port").and_then(|v| v.as_str()).map(|s| s.to_string()));
 
+        let ssh_port = match args.as_ref().and_then(|a| a.get("ssh_port").and_then(|v| v.as_u64())) {
+            Some(n) => Some(u16::try_from(n).map_err(|_| ToolError::invalid_argument(format!("ssh_port {} is not a valid port.", n)))?),
+            None => None,
+        };
+        let ssh = args.as_ref().and_then(|a| a.get("ssh_host").and_then(|v| v.as_str())).map(|h| SshTunnel {
+            host: h.to_string(),
+            user: args.as_ref().and_then(|a| a.get("ssh_user").and_then(|v| v.as_str()).map(|s| s.to_string())),
+            port: ssh_port,
+        });
+
         let target_string = if let (Some(h), Some(p)) = (host, port) {
             format!("{}:{}", h, p)
         } else if let Some(sp) = serial_port {
+            if ssh.is_some() {
+                return Err(ToolError::invalid_argument("ssh_host requires host and port; serial ports cannot be tunneled.").into());
+            }
             sp
         } else {
-            return Ok(CallToolResult::error_text("Either host:port or serial_port must be specified."));
+            return Err(ToolError::invalid_argument("Either host:port or serial_port must be specified.").into());
         };
 
         let is_extended = target_type.as_deref() == Some("extended-remote");
         info!("Connecting to {} target: {}", if is_extended { "extended-remote" } else { "remote" }, target_string);
 
-        let mut guard = self.client.write().await;
-        let client = guard.as_mut().ok_or_else(|| anyhow::anyhow!("GDB session not started"))?;
+        let mut guard = self.client().write_owned().await;
+        let client = guard.as_mut().ok_or_else(ToolError::no_session)?;
+
+        // Forward a local port to the target through the jump host
+        let connect_string = match &ssh {
+            Some(tunnel) => {
+                let local_port = free_local_port()?;
+                info!("Tunneling {} through {}", target_string, tunnel.host);
+                client.managed().spawn(
+                    "ssh-tunnel",
+                    tunnel.command(local_port, &target_string),
+                    Duration::from_millis(1000),
+                )?;
+                format!("127.0.0.1:{}", local_port)
+            }
+            None => target_string.clone(),
+        };
 
-        if is_extended {
-            client.target_connect_extended_remote(&target_string)?;
+        let connected = if is_extended {
+            client.target_connect_extended_remote(&connect_string)
         } else {
-            client.target_connect_remote(&target_string)?;
+            client.target_connect_remote(&connect_string)
+        };
+        if let Err(e) = connected {
+            client.managed().kill_all();
+            return Err(e);
         }
 
-        Ok(CallToolResult::text(format!("Connected to remote target: {}", target_string)))
+        let message = match ssh {
+            Some(tunnel) => format!(
+                "Connected to remote target: {} (tunneled through {} via {})",
+                target_string, tunnel.host, connect_string
+            ),
+            None => format!("Connected to remote target: {}", target_string),
+        };
+        Ok(CallToolResult::text(with_endian_warning(message, client)))
     }
 
     async fn handle_target_disconnect(&self) -> Result<CallToolResult> {
-        let mut guard = self.client.write().await;
-        let client = guard.as_mut().ok_or_else(|| anyhow::anyhow!("GDB session not started"))?;
+        let mut guard = self.client().write_owned().await;
+        let client = guard.as_mut().ok_or_else(ToolError::no_session)?;
         client.target_disconnect()?;
         Ok(CallToolResult::text("Disconnected from remote target."))
     }
 
+    async fn handle_container_attach(&self, args: Option<serde_json::Map<String, serde_json::Value>>) -> Result<CallToolResult> {
+        let args = args.unwrap_or_default();
+        let container = args.get("container").and_then(|v| v.as_str())
+            .ok_or_else(|| ToolError::missing_argument("container"))?;
+        let runtime = args.get("runtime").and_then(|v| v.as_str()).unwrap_or("docker");
+        let process = args.get("process").and_then(|v| {
+            v.as_str().map(|s| s.to_string()).or_else(|| v.as_u64().map(|n| n.to_string()))
+        });
+        let use_gdbserver = args.get("mode").and_then(|v| v.as_str()) == Some("gdbserver");
+        let source_dirs: Vec<String> = args.get("source_dirs").and_then(|v| v.as_array())
+            .map(|dirs| dirs.iter().filter_map(|d| d.as_str().map(|s| s.to_string())).collect())
+            .unwrap_or_default();
+        let source_map: Vec<(String, String)> = args.get("source_map").and_then(|v| v.as_object())
+            .map(|m| m.iter().filter_map(|(k, v)| v.as_str().map(|v| (k.clone(), v.to_string()))).collect())
+            .unwrap_or_default();
+
+        let mut guard = self.client().write_owned().await;
+        let client = guard.as_mut().ok_or_else(ToolError::no_session)?;
+        let mut report = vec![format!("Container: {} ({})", container, runtime)];
+        let mut substitutions = source_map;
+
+        if use_gdbserver {
+            let port = args.get("port").and_then(|v| v.as_u64()).unwrap_or(2345);
+            let pid = container::container_pid(runtime, container, process.as_deref())?;
+            let host = match args.get("host").and_then(|v| v.as_str()) {
+                Some(h) => h.to_string(),
+                None => container::ip_address(runtime, container)?,
+            };
+            info!("Starting gdbserver in {} for PID {} on port {}", container, pid, port);
+
+            let mut command = std::process::Command::new(runtime);
+            command.args(["exec", container, "gdbserver", "--attach", &format!(":{}", port), &pid.to_string()]);
+            client.managed().spawn("gdbserver", command, Duration::from_millis(500))?;
+
+            // Read the executable and libraries through gdbserver
+            client.gdb_set("sysroot", "target:")?;
+            for (from, to) in &substitutions {
+                client.gdb_set("substitute-path", &format!("{} {}", from, to))?;
+            }
+            let target = format!("{}:{}", host, port);
+            if let Err(e) = client.target_connect_remote(&target) {
+                client.managed().kill_all();
+                return Err(e);
+            }
+            report.push(format!("Process: PID {} in the container", pid));
+            report.push(format!("gdbserver: {}", target));
+            report.push("Sysroot: target:".to_string());
+        } else {
+            let init = container::init_pid(runtime, container)?;
+            let pid = container::host_pid(init, process.as_deref())?;
+            let root = format!("/proc/{}/root", pid);
+            info!("Attaching to host PID {} in container {}", pid, container);
+
+            substitutions.extend(source_dirs.iter().map(|dir| {
+                (dir.clone(), format!("{}/{}", root, dir.trim_start_matches('/')))
+            }));
+            client.gdb_set("sysroot", &root)?;
+            for (from, to) in &substitutions {
+                client.gdb_set("substitute-path", &format!("{} {}", from, to))?;
+            }
+            client.target_attach(pid)?;
+            report.push(format!("Process: host PID {}", pid));
+            report.push(format!("Sysroot: {}", root));
+        }
+
+        for (from, to) in &substitutions {
+            report.push(format!("Substitute path: {} -> {}", from, to));
+        }
+        Ok(CallToolResult::text(format!("Attached to container process.\n{}", report.join("\n"))))
+    }
+
     async fn handle_break_insert(&self, args: Option<serde_json::Map<String, serde_json::Value>>) -> Result<CallToolResult> {
         let location = args.as_ref()
             .and_then(|a| a.get("location").and_then(|v| v.as_str()).map(|s| s.to_string()))
-            .ok_or_else(|| anyhow::anyhow!("location is required"))?;
-        let temporary = args.as_ref().and_then(|a| a.get("temporary").and_then(|v| v.as_bool())).unwrap_or(false);
-        let condition = args.as_ref().and_then(|a| a.get("condition").and_then(|v| v.as_str()).map(|s| s.to_string()));
+            .ok_or_else(|| ToolError::missing_argument("location"))?;
+        let options = BreakInsertOptions {
+            temporary: args.as_ref().and_then(|a| a.get("temporary").and_then(|v| v.as_bool())).unwrap_or(false),
+            condition: args.as_ref().and_then(|a| a.get("condition").and_then(|v| v.as_str()).map(|s| s.to_string())),
+            ignore_count: args.as_ref().and_then(|a| a.get("ignore_count").and_then(|v| v.as_u64())),
+            thread: args.as_ref().and_then(|a| a.get("thread_id")).and_then(|v| {
+                v.as_str().map(|s| s.to_string()).or_else(|| v.as_u64().map(|n| n.to_string()))
+            }),
+            inferior: args.as_ref().and_then(|a| a.get("inferior").and_then(|v| v.as_u64())),
+            pending: args.as_ref().and_then(|a| a.get("pending").and_then(|v| v.as_bool())).unwrap_or(false),
+            count_evaluations: args.as_ref().and_then(|a| a.get("count_evaluations").and_then(|v| v.as_bool())).unwrap_or(false),
+        };
+        if options.count_evaluations && options.condition.is_none() {
+            return Err(ToolError::invalid_argument("count_evaluations requires a condition").into());
+        }
+        let mut options = options;
+        let stop_after_hits = args.as_ref().and_then(|a| a.get("stop_after_hits").and_then(|v| v.as_u64()));
+        if let Some(hits) = stop_after_hits {
+            if hits == 0 {
+                return Err(ToolError::invalid_argument("stop_after_hits must be at least 1").into());
+            }
+            if options.ignore_count.is_some() {
+                return Err(ToolError::invalid_argument("stop_after_hits and ignore_count cannot be combined").into());
+            }
+            options.ignore_count = Some(hits - 1).filter(|&n| n > 0);
+        }
+        let if_not_exists = args.as_ref()
+            .and_then(|a| a.get("if_not_exists").and_then(|v| v.as_bool()))
+            .unwrap_or(false);
+
+        let mut guard = self.client().write_owned().await;
+        let client = guard.as_mut().ok_or_else(ToolError::no_session)?;
+
+        if if_not_exists {
+            let existing = client.tracker().find_breakpoints(&location)
+                .into_iter()
+                .find(|b| b.breakpoint.condition == options.condition);
+            if let Some(existing) = existing {
+                let number = existing.breakpoint.number.clone();
+                let Some(hits) = stop_after_hits else {
+                    return Ok(CallToolResult::success(vec![
+                        Content::text(format!("Breakpoint {} already exists at {}", number, location)),
+                        Content::text(serde_json::to_string_pretty(&existing)?),
+                    ]));
+                };
+
+                // Count the hits the existing breakpoint already has
+                let times = existing.breakpoint.times;
+                if times >= hits {
+                    return Err(ToolError::invalid_argument(format!(
+                        "Breakpoint {} at {} has already been hit {} time(s)",
+                        number, location, times
+                    )).into());
+                }
+                client.break_after(&number, hits - times - 1)?;
+                let updated = client.tracker().breakpoint(&number);
+                return Ok(CallToolResult::success(vec![
+                    Content::text(format!(
+                        "Breakpoint {} already exists at {} with {} hit(s); it will stop on hit {}",
+                        number, location, times, hits
+                    )),
+                    Content::text(serde_json::to_string_pretty(&updated)?),
+                ]));
+            }
+        }
 
+        self.check_breakpoint_limit(client, 1)?;
         info!("Inserting breakpoint at: {}", location);
+        let bp = client.break_insert(&location, &options)?;
+        let label = annotate_new_breakpoint(client, &bp.number, args.as_ref());
 
-        let mut guard = self.client.write().await;
-        let client = guard.as_mut().ok_or_else(|| anyhow::anyhow!("GDB session not started"))?;
-        
-        let bp = client.break_insert(&location, temporary, condition.as_deref())?;
+        let mut scope = String::new();
+        if let Some(thread) = &options.thread {
+            scope.push_str(&format!(" (thread {})", thread));
+        }
+        if let Some(inferior) = options.inferior {
+            scope.push_str(&format!(" (inferior {})", inferior));
+        }
+        if let Some(hits) = stop_after_hits {
+            scope.push_str(&format!("; it will stop on hit {}", hits));
+        }
         
         Ok(CallToolResult::success(vec![
-            Content::text(format!("Breakpoint {} inserted at {}", bp.number, location)),
+            Content::text(format!("Breakpoint {}{} inserted at {}{}", bp.number, label, location, scope)),
             Content::text(serde_json::to_string_pretty(&bp)?),
         ]))
     }
@@ -243,274 +1279,2303 @@ impl GdbMcpServer {
     async fn handle_break_delete(&self, args: Option<serde_json::Map<String, serde_json::Value>>) -> Result<CallToolResult> {
         let number = args.and_then(|a| a.get("number").and_then(|v| v.as_str()).map(|s| s.to_string()));
 
-        let mut guard = self.client.write().await;
-        let client = guard.as_mut().ok_or_else(|| anyhow::anyhow!("GDB session not started"))?;
+        let mut guard = self.client().write_owned().await;
+        let client = guard.as_mut().ok_or_else(ToolError::no_session)?;
 
         if let Some(n) = number {
-            client.break_delete(&n)?;
-            Ok(CallToolResult::text(format!("Breakpoint {} deleted.", n)))
+            let numbers = normalize_breakpoint_numbers(&n)?;
+            client.break_delete(&numbers)?;
+            Ok(CallToolResult::text(format!("Breakpoint(s) {} deleted.", numbers)))
         } else {
             client.send_command("break-delete")?;
             Ok(CallToolResult::text("All breakpoints deleted."))
         }
     }
 
-    async fn handle_break_list(&self) -> Result<CallToolResult> {
-        let mut guard = self.client.write().await;
-        let client = guard.as_mut().ok_or_else(|| anyhow::anyhow!("GDB session not started"))?;
+    async fn handle_break_list(&self, args: Option<serde_json::Map<String, serde_json::Value>>) -> Result<CallToolResult> {
+        let refresh = args.as_ref()
+            .and_then(|a| a.get("refresh").and_then(|v| v.as_bool()))
+            .unwrap_or(false);
+
+        let mut guard = self.client().write_owned().await;
+        let client = guard.as_mut().ok_or_else(ToolError::no_session)?;
         
-        let breakpoints = client.break_list()?;
+        if refresh {
+            client.break_list()?;
+        }
+        let breakpoints = client.tracker().breakpoints();
         Ok(CallToolResult::text(serde_json::to_string_pretty(&breakpoints)?))
     }
 
-    async fn handle_break_toggle(&self, args: Option<serde_json::Map<String, serde_json::Value>>) -> Result<CallToolResult> {
-        let number = args.as_ref()
-            .and_then(|a| a.get("number").and_then(|v| v.as_str()).map(|s| s.to_string()))
-            .ok_or_else(|| anyhow::anyhow!("number is required"))?;
-        let enabled = args.as_ref()
-            .and_then(|a| a.get("enabled").and_then(|v| v.as_bool()))
-            .ok_or_else(|| anyhow::anyhow!("enabled is required"))?;
+    async fn handle_break_find(&self, args: Option<serde_json::Map<String, serde_json::Value>>) -> Result<CallToolResult> {
+        let location = args.as_ref()
+            .and_then(|a| a.get("location").and_then(|v| v.as_str()).map(|s| s.to_string()))
+            .ok_or_else(|| ToolError::missing_argument("location"))?;
 
-        let mut guard = self.client.write().await;
-        let client = guard.as_mut().ok_or_else(|| anyhow::anyhow!("GDB session not started"))?;
+        let guard = self.client().read_owned().await;
+        let client = guard.as_ref().ok_or_else(ToolError::no_session)?;
 
-        if enabled {
-            client.break_enable(&number)?;
-            Ok(CallToolResult::text(format!("Breakpoint {} enabled.", number)))
-        } else {
-            client.break_disable(&number)?;
-            Ok(CallToolResult::text(format!("Breakpoint {} disabled.", number)))
+        let breakpoints = client.tracker().find_breakpoints(&location);
+        if breakpoints.is_empty() {
+            return Ok(CallToolResult::text(format!("No breakpoints at {}", location)));
         }
+        Ok(CallToolResult::text(serde_json::to_string_pretty(&breakpoints)?))
     }
 
-    async fn handle_watch_insert(&self, args: Option<serde_json::Map<String, serde_json::Value>>) -> Result<CallToolResult> {
-        let location = args.as_ref()
-            .and_then(|a| a.get("location").and_then(|v| v.as_str()).map(|s| s.to_string()))
-            .ok_or_else(|| anyhow::anyhow!("location is required"))?;
-        
-        let watch_type = args.as_ref()
-            .and_then(|a| a.get("watch_type").and_then(|v| v.as_str()))
-            .map(|s| match s {
-                "read" => WatchpointType::Read,
-                "access" => WatchpointType::Access,
-                _ => WatchpointType::Write,
-            })
-            .unwrap_or(WatchpointType::Write);
+    async fn handle_break_vectors(&self, args: Option<serde_json::Map<String, serde_json::Value>>) -> Result<CallToolResult> {
+        let base = args.as_ref().and_then(|a| a.get("base").and_then(|v| v.as_str()).map(|s| s.to_string()));
+        let count = args.as_ref().and_then(|a| a.get("count").and_then(|v| v.as_u64()));
+        let selection: Vec<String> = args.as_ref()
+            .and_then(|a| a.get("vectors").and_then(|v| v.as_array()))
+            .map(|v| v.iter().filter_map(|s| s.as_str().map(|s| s.to_string())).collect())
+            .unwrap_or_default();
+        let svd_path = match args.as_ref().and_then(|a| a.get("svd_path").and_then(|v| v.as_str())) {
+            Some(path) => Some(self.resolve_path(path).await?),
+            // The profile's SVD file comes from the configuration, not the client
+            None => self.client().read().await.as_ref().and_then(|c| c.state().svd_file),
+        };
+        let interrupts = match svd_path {
+            Some(path) => {
+                let svd = std::fs::read_to_string(&path)
+                    .map_err(|e| ToolError::invalid_argument(format!("Failed to read SVD file {}: {}", path, e)))?;
+                vectors::parse_svd_interrupts(&svd)
+            }
+            None => Vec::new(),
+        };
+        let count = count.map(|c| c as usize).unwrap_or_else(|| {
+            vectors::SYSTEM_VECTORS + interrupts.iter().map(|(irq, _)| irq + 1).max().unwrap_or(0)
+        });
 
-        info!("Inserting {:?} watchpoint at: {}", watch_type, location);
+        let mut guard = self.client().write_owned().await;
+        let client = guard.as_mut().ok_or_else(ToolError::no_session)?;
 
-        let mut guard = self.client.write().await;
-        let client = guard.as_mut().ok_or_else(|| anyhow::anyhow!("GDB session not started"))?;
-        
-        let wp = client.watch_insert(watch_type.clone(), &location)?;
-        
-        let type_str = match watch_type {
-            WatchpointType::Write => "write",
-            WatchpointType::Read => "read",
-            WatchpointType::Access => "access",
+        let base = match base {
+            Some(base) => Some(match parse_address(&base) {
+                Some(address) => address,
+                None => client.evaluate_u64(&format!("(unsigned long long)&({})", base))?,
+            }),
+            None => None,
         };
-        
+        let max_new = self.breakpoint_budget(client)?;
+        let result = client.break_vectors(base, count, &interrupts, &selection, max_new)?;
+        let inserted = result.handlers.iter().filter(|h| h.breakpoint.is_some()).count();
         Ok(CallToolResult::success(vec![
-            Content::text(format!("{} watchpoint {} inserted at {}", type_str, wp.number, location)),
-            Content::text(serde_json::to_string_pretty(&wp)?),
+            Content::text(format!("Breakpoints on {} handler(s) from the vector table at {}", inserted, result.base)),
+            Content::text(serde_json::to_string_pretty(&result)?),
         ]))
     }
 
-    async fn handle_watch_delete(&self, args: Option<serde_json::Map<String, serde_json::Value>>) -> Result<CallToolResult> {
+    async fn handle_break_toggle(&self, args: Option<serde_json::Map<String, serde_json::Value>>) -> Result<CallToolResult> {
         let number = args.as_ref()
             .and_then(|a| a.get("number").and_then(|v| v.as_str()).map(|s| s.to_string()))
-            .ok_or_else(|| anyhow::anyhow!("number is required"))?;
+            .ok_or_else(|| ToolError::missing_argument("number"))?;
+        let enabled = args.as_ref()
+            .and_then(|a| a.get("enabled").and_then(|v| v.as_bool()))
+            .ok_or_else(|| ToolError::missing_argument("enabled"))?;
 
-        let mut guard = self.client.write().await;
-        let client = guard.as_mut().ok_or_else(|| anyhow::anyhow!("GDB session not started"))?;
-        
-        client.break_delete(&number)?;
+        let mut guard = self.client().write_owned().await;
+        let client = guard.as_mut().ok_or_else(ToolError::no_session)?;
+
+        let numbers = normalize_breakpoint_numbers(&number)?;
+        if enabled {
+            client.break_enable(&numbers)?;
+            Ok(CallToolResult::text(format!("Breakpoint(s) {} enabled.", numbers)))
+        } else {
+            client.break_disable(&numbers)?;
+            Ok(CallToolResult::text(format!("Breakpoint(s) {} disabled.", numbers)))
+        }
+    }
+
+    async fn handle_break_annotate(&self, args: Option<serde_json::Map<String, serde_json::Value>>) -> Result<CallToolResult> {
+        let number = args.as_ref()
+            .and_then(|a| a.get("number").and_then(|v| v.as_str()).map(|s| s.to_string()))
+            .ok_or_else(|| ToolError::missing_argument("number"))?;
+        let label = args.as_ref().and_then(|a| a.get("label").and_then(|v| v.as_str()));
+        let note = args.as_ref().and_then(|a| a.get("note").and_then(|v| v.as_str()));
+        if label.is_none() && note.is_none() {
+            return Err(ToolError::invalid_argument("Pass a label, a note, or both").into());
+        }
+
+        let mut guard = self.client().write_owned().await;
+        let client = guard.as_mut().ok_or_else(ToolError::no_session)?;
+
+        if client.tracker().breakpoint(&number).is_none() {
+            client.break_list()?;
+        }
+        if !client.tracker().annotate_breakpoint(&number, label, note) {
+            return Err(ToolError::invalid_argument(format!("No breakpoint number {}", number)).into());
+        }
+        let bp = client.tracker().breakpoint(&number);
+        Ok(CallToolResult::success(vec![
+            Content::text(format!("Breakpoint {} annotated.", number)),
+            Content::text(serde_json::to_string_pretty(&bp)?),
+        ]))
+    }
+
+    async fn handle_break_from_log(&self, args: Option<serde_json::Map<String, serde_json::Value>>) -> Result<CallToolResult> {
+        let log = args.as_ref()
+            .and_then(|a| a.get("log").and_then(|v| v.as_str()))
+            .ok_or_else(|| ToolError::missing_argument("log"))?;
+        let get_bool = |name: &str| args.as_ref().and_then(|a| a.get(name).and_then(|v| v.as_bool())).unwrap_or(false);
+        let kinds: Option<Vec<LogLineKind>> = args.as_ref()
+            .and_then(|a| a.get("kinds"))
+            .map(|v| serde_json::from_value(v.clone()))
+            .transpose()
+            .map_err(|_| ToolError::invalid_argument("kinds must be error, warning, note, runtime_error, frame or mention"))?;
+        let max = args.as_ref().and_then(|a| a.get("max_breakpoints").and_then(|v| v.as_u64())).unwrap_or(20) as usize;
+
+        let found = parse_log(log);
+        let total = found.len();
+        let mut locations: Vec<LogLocation> = found.into_iter()
+            .filter(|l| get_bool("include_system") || !l.is_system())
+            .filter(|l| kinds.as_ref().is_none_or(|k| k.contains(&l.kind)))
+            .collect();
+        if locations.is_empty() {
+            return Err(ToolError::invalid_argument(format!(
+                "No usable source locations in the log ({} found before filtering). Locations look like file.c:42.",
+                total
+            )).into());
+        }
+        let skipped = locations.len().saturating_sub(max);
+        locations.truncate(max);
+        if get_bool("dry_run") {
+            return Ok(CallToolResult::success(vec![
+                Content::text(format!("Found {} location(s) in the log ({} over max_breakpoints).", locations.len(), skipped)),
+                Content::text(serde_json::to_string_pretty(&locations)?),
+            ]));
+        }
+
+        let mut guard = self.client().write_owned().await;
+        let client = guard.as_mut().ok_or_else(ToolError::no_session)?;
+
+        let existing = |client: &mut GdbClient, l: &LogLocation| {
+            let tracker = client.tracker();
+            tracker.find_breakpoints(&l.location()).into_iter()
+                .chain(tracker.find_breakpoints(&l.short_location()))
+                .map(|b| b.breakpoint.number)
+                .next()
+        };
+        let adding = locations.iter().filter(|l| existing(client, l).is_none()).count();
+        self.check_breakpoint_limit(client, adding)?;
+
+        let pending = get_bool("pending");
+        let (mut set, mut already, mut failed) = (0, 0, 0);
+        let mut results = Vec::new();
+        for location in &locations {
+            if let Some(number) = existing(client, location) {
+                already += 1;
+                results.push(serde_json::json!({"location": location, "status": "exists", "number": number}));
+                continue;
+            }
+            // Paths in logs often differ from those in the debug info
+            let attempts = [(location.location(), false), (location.short_location(), false), (location.short_location(), pending)];
+            let mut error = None;
+            let mut inserted = None;
+            for (attempt, pending) in attempts.iter().filter(|(_, p)| !*p || pending).cloned() {
+                let options = BreakInsertOptions { pending, ..Default::default() };
+                match client.break_insert(&attempt, &options) {
+                    Ok(bp) => {
+                        inserted = Some((bp.number, attempt));
+                        break;
+                    }
+                    Err(e) => error = Some(format!("{:#}", e)),
+                }
+            }
+            match inserted {
+                Some((number, used)) => {
+                    set += 1;
+                    let label = format!("log {}", location.kind.as_str());
+                    client.tracker().annotate_breakpoint(&number, Some(&label), Some(&location.context));
+                    results.push(serde_json::json!({"location": location, "status": "set", "number": number, "set_at": used}));
+                }
+                None => {
+                    failed += 1;
+                    results.push(serde_json::json!({"location": location, "status": "failed", "error": error}));
+                }
+            }
+        }
+
+        let mut summary = format!(
+            "Set {} breakpoint(s) from {} location(s) in the log; {} already existed, {} failed.",
+            set, locations.len(), already, failed
+        );
+        if skipped > 0 {
+            summary.push_str(&format!(" {} more location(s) were over max_breakpoints.", skipped));
+        }
+        Ok(CallToolResult::success(vec![
+            Content::text(summary),
+            Content::text(serde_json::to_string_pretty(&results)?),
+        ]))
+    }
+
+    async fn handle_break_enable_all(&self) -> Result<CallToolResult> {
+        let mut guard = self.client().write_owned().await;
+        let client = guard.as_mut().ok_or_else(ToolError::no_session)?;
+        client.break_enable_all()?;
+        Ok(CallToolResult::text("All breakpoints enabled."))
+    }
+
+    async fn handle_break_disable_all(&self) -> Result<CallToolResult> {
+        let mut guard = self.client().write_owned().await;
+        let client = guard.as_mut().ok_or_else(ToolError::no_session)?;
+        client.break_disable_all()?;
+        Ok(CallToolResult::text("All breakpoints disabled."))
+    }
+
+    async fn handle_break_history(&self, args: Option<serde_json::Map<String, serde_json::Value>>) -> Result<CallToolResult> {
+        let number = args.as_ref()
+            .and_then(|a| a.get("number").and_then(|v| v.as_str()).map(|s| s.to_string()));
+        let limit = args.as_ref()
+            .and_then(|a| a.get("limit").and_then(|v| v.as_u64()))
+            .unwrap_or(50) as usize;
+        let clear = args.as_ref()
+            .and_then(|a| a.get("clear").and_then(|v| v.as_bool()))
+            .unwrap_or(false);
+
+        let guard = self.client().read_owned().await;
+        let client = guard.as_ref().ok_or_else(ToolError::no_session)?;
+        let mut tracker = client.tracker();
+
+        let summary = tracker.breakpoint_hit_summary();
+        let hits = tracker.breakpoint_hits(number.as_deref(), limit);
+        if clear {
+            tracker.clear_breakpoint_hits();
+        }
+
+        Ok(CallToolResult::text(serde_json::to_string_pretty(&serde_json::json!({
+            "summary": summary,
+            "hits": hits,
+        }))?))
+    }
+
+    async fn handle_break_stats(&self, args: Option<serde_json::Map<String, serde_json::Value>>) -> Result<CallToolResult> {
+        let number = args.as_ref()
+            .and_then(|a| a.get("number").and_then(|v| v.as_str()).map(|s| s.to_string()));
+
+        let mut guard = self.client().write_owned().await;
+        let client = guard.as_mut().ok_or_else(ToolError::no_session)?;
+        let stats = client.break_stats(number.as_deref())?;
+        if let (Some(number), true) = (&number, stats.is_empty()) {
+            return Err(ToolError::invalid_argument(format!("Breakpoint {} does not exist or has no condition", number)).into());
+        }
+
+        let mut lines: Vec<String> = stats.iter().filter_map(|s| s.warning()).map(|w| format!("Warning: {}", w)).collect();
+        if lines.is_empty() {
+            lines.push(format!("{} conditional breakpoint(s), none hot.", stats.len()));
+        }
+        Ok(CallToolResult::success(vec![
+            Content::text(lines.join("\n")),
+            Content::text(serde_json::to_string_pretty(&stats)?),
+        ]))
+    }
+
+    async fn handle_catch_library(&self, args: Option<serde_json::Map<String, serde_json::Value>>) -> Result<CallToolResult> {
+        let unload = match args.as_ref().and_then(|a| a.get("event").and_then(|v| v.as_str())) {
+            None | Some("load") => false,
+            Some("unload") => true,
+            Some(other) => return Err(ToolError::invalid_argument(format!("event must be 'load' or 'unload', got '{}'", other)).into()),
+        };
+        let regex = args.as_ref().and_then(|a| a.get("regex").and_then(|v| v.as_str()).map(|s| s.to_string()));
+        let temporary = args.as_ref().and_then(|a| a.get("temporary").and_then(|v| v.as_bool())).unwrap_or(false);
+
+        let mut guard = self.client().write_owned().await;
+        let client = guard.as_mut().ok_or_else(ToolError::no_session)?;
+        let bp = client.catch_library(unload, regex.as_deref(), temporary)?;
+
+        Ok(CallToolResult::success(vec![
+            Content::text(format!(
+                "Catchpoint {} ({} of {})",
+                bp.number,
+                if unload { "unload" } else { "load" },
+                regex.as_deref().map(|r| format!("libraries matching '{}'", r)).unwrap_or_else(|| "any library".to_string()),
+            )),
+            Content::text(serde_json::to_string_pretty(&bp)?),
+        ]))
+    }
+
+    async fn handle_stop_on_solib_events(&self, args: Option<serde_json::Map<String, serde_json::Value>>) -> Result<CallToolResult> {
+        let enabled = args.as_ref()
+            .and_then(|a| a.get("enabled").and_then(|v| v.as_bool()))
+            .ok_or_else(|| ToolError::missing_argument("enabled"))?;
+
+        let mut guard = self.client().write_owned().await;
+        let client = guard.as_mut().ok_or_else(ToolError::no_session)?;
+        client.set_stop_on_solib_events(enabled)?;
+
+        Ok(CallToolResult::text(if enabled {
+            "Execution will stop on every shared library load and unload."
+        } else {
+            "Execution will no longer stop on shared library events."
+        }))
+    }
+
+    async fn handle_autoresume_rules(&self, args: Option<serde_json::Map<String, serde_json::Value>>) -> Result<CallToolResult> {
+        let get_str = |name: &str| args.as_ref().and_then(|a| a.get(name).and_then(|v| v.as_str()));
+        let action = get_str("action").unwrap_or("list");
+
+        let mut guard = self.client().write_owned().await;
+        let client = guard.as_mut().ok_or_else(ToolError::no_session)?;
+
+        match action {
+            "add" => {
+                let matcher = match get_str("kind").ok_or_else(|| ToolError::missing_argument("kind"))? {
+                    "signal" => {
+                        let name = get_str("signal").ok_or_else(|| ToolError::missing_argument("signal"))?.trim().to_ascii_uppercase();
+                        let name = if name.starts_with("SIG") { name } else { format!("SIG{}", name) };
+                        if name == "SIGINT" {
+                            return Err(ToolError::invalid_argument("SIGINT is used to interrupt the target and cannot be resumed").into());
+                        }
+                        StopMatcher::Signal { name }
+                    }
+                    "breakpoint" => {
+                        let number = get_str("number").ok_or_else(|| ToolError::missing_argument("number"))?.to_string();
+                        if client.tracker().breakpoint(&number).is_none() {
+                            return Err(ToolError::invalid_argument(format!("No breakpoint number {}", number)).into());
+                        }
+                        let condition = get_str("condition").map(|c| c.to_string()).filter(|c| !c.trim().is_empty());
+                        StopMatcher::Breakpoint { number, condition }
+                    }
+                    "reason" => {
+                        let reason = get_str("reason").ok_or_else(|| ToolError::missing_argument("reason"))?;
+                        if !RESUMABLE_REASONS.contains(&reason) {
+                            return Err(ToolError::invalid_argument(format!("reason must be one of {}", RESUMABLE_REASONS.join(", "))).into());
+                        }
+                        StopMatcher::Reason { reason: reason.to_string() }
+                    }
+                    other => {
+                        return Err(ToolError::invalid_argument(format!("kind must be signal, breakpoint or reason, not '{}'", other)).into());
+                    }
+                };
+                let id = client.auto_resume().add(matcher);
+                let rules = client.auto_resume().rules().to_vec();
+                Ok(CallToolResult::text(format!(
+                    "Added auto-resume rule {}.\n{}",
+                    id,
+                    serde_json::to_string_pretty(&rules)?
+                )))
+            }
+            "remove" => {
+                let id = args.as_ref()
+                    .and_then(|a| a.get("id").and_then(|v| v.as_u64()))
+                    .ok_or_else(|| ToolError::missing_argument("id"))?;
+                if !client.auto_resume().remove(id) {
+                    return Err(ToolError::invalid_argument(format!("No auto-resume rule {}", id)).into());
+                }
+                Ok(CallToolResult::text(format!("Removed auto-resume rule {}.", id)))
+            }
+            "clear" => {
+                client.auto_resume().clear();
+                Ok(CallToolResult::text("All auto-resume rules removed."))
+            }
+            "list" => {
+                let drain = args.as_ref()
+                    .and_then(|a| a.get("drain_log").and_then(|v| v.as_bool()))
+                    .unwrap_or(false);
+                let mut auto_resume = client.auto_resume();
+                let report = serde_json::json!({
+                    "rules": auto_resume.rules(),
+                    "resumed": auto_resume.resumed(),
+                    "log": auto_resume.log(drain),
+                });
+                Ok(CallToolResult::text(serde_json::to_string_pretty(&report)?))
+            }
+            other => Err(ToolError::invalid_argument(format!("action must be add, remove, clear or list, not '{}'", other)).into()),
+        }
+    }
+
+    async fn handle_override_on_hit(&self, args: Option<serde_json::Map<String, serde_json::Value>>) -> Result<CallToolResult> {
+        let get_str = |name: &str| args.as_ref().and_then(|a| a.get(name).and_then(|v| v.as_str()));
+        let mut assignments = Vec::new();
+        if let Some(list) = args.as_ref().and_then(|a| a.get("assignments")) {
+            let list = list.as_array().ok_or_else(|| ToolError::invalid_argument("assignments must be an array"))?;
+            for item in list {
+                let field = |name: &str| item.get(name).and_then(|v| v.as_str()).map(|s| s.trim().to_string());
+                let (Some(target), Some(value)) = (field("target"), field("value")) else {
+                    return Err(ToolError::invalid_argument("Each assignment needs a target and a value").into());
+                };
+                assignments.push((target, value));
+            }
+        }
+        match (get_str("target"), get_str("value")) {
+            (Some(target), Some(value)) => assignments.push((target.trim().to_string(), value.trim().to_string())),
+            (Some(_), None) => return Err(ToolError::missing_argument("value").into()),
+            (None, Some(_)) => return Err(ToolError::missing_argument("target").into()),
+            (None, None) => {}
+        }
+        if assignments.is_empty() {
+            return Err(ToolError::invalid_argument("Pass target and value, or assignments").into());
+        }
+        if assignments.iter().any(|(t, v)| t.is_empty() || v.is_empty() || t.contains('\n') || v.contains('\n')) {
+            return Err(ToolError::invalid_argument("Targets and values must be non-empty single-line expressions").into());
+        }
+        let resume = args.as_ref().and_then(|a| a.get("continue").and_then(|v| v.as_bool())).unwrap_or(true);
+        let condition = get_str("condition").map(|c| c.to_string()).filter(|c| !c.trim().is_empty());
+
+        let described: Vec<String> = assignments.iter().map(|(t, v)| format!("{} = {}", t, v)).collect();
+        let hit = match (get_str("number"), get_str("location")) {
+            (Some(number), _) => format!("breakpoint {}", number),
+            (None, Some(location)) => location.to_string(),
+            (None, None) => return Err(ToolError::missing_argument("location").into()),
+        };
+        self.confirm(&format!("set {} on each hit of {}", described.join(", "), hit)).await?;
+
+        let mut guard = self.client().write_owned().await;
+        let client = guard.as_mut().ok_or_else(ToolError::no_session)?;
+
+        let number = match (get_str("number"), get_str("location")) {
+            (Some(_), Some(_)) => return Err(ToolError::invalid_argument("Pass either number or location, not both").into()),
+            (Some(number), None) => {
+                if condition.is_some() {
+                    return Err(ToolError::invalid_argument("condition can only be given when inserting a new breakpoint with location").into());
+                }
+                if client.tracker().breakpoint(number).is_none() {
+                    client.break_list()?;
+                }
+                if client.tracker().breakpoint(number).is_none() {
+                    return Err(ToolError::invalid_argument(format!("No breakpoint number {}", number)).into());
+                }
+                number.to_string()
+            }
+            (None, Some(location)) => {
+                self.check_breakpoint_limit(client, 1)?;
+                let options = BreakInsertOptions { condition, ..Default::default() };
+                client.break_insert(location, &options)?.number
+            }
+            (None, None) => return Err(ToolError::missing_argument("location").into()),
+        };
+
+        let mut commands = Vec::new();
+        if resume {
+            commands.push("silent".to_string());
+        }
+        commands.extend(described.iter().map(|a| format!("set var {}", a)));
+        if resume {
+            commands.push("continue".to_string());
+        }
+        client.break_commands(&number, &commands)?;
+
+        // Hits the commands continue are logged instead of ending the
+        // waiting tool call
+        {
+            let mut auto_resume = client.auto_resume();
+            let previous: Vec<u64> = auto_resume.rules().iter()
+                .filter(|r| matches!(&r.matcher, StopMatcher::Commands { number: n } if *n == number))
+                .map(|r| r.id)
+                .collect();
+            for id in previous {
+                auto_resume.remove(id);
+            }
+            if resume {
+                auto_resume.add(StopMatcher::Commands { number: number.clone() });
+            }
+        }
+
+        let label = get_str("label").map(|l| l.to_string()).unwrap_or_else(|| format!("override {}", described.join(", ")));
+        client.tracker().annotate_breakpoint(&number, Some(&label), None);
+        let bp = client.tracker().breakpoint(&number);
+
+        let location = bp.as_ref()
+            .and_then(|b| b.actual_location.clone().or(b.requested_location.clone()))
+            .unwrap_or_else(|| "?".to_string());
+        let then = if resume { "and continue" } else { "and stop" };
+        Ok(CallToolResult::success(vec![
+            Content::text(format!("Breakpoint {} at {} will set {} {} on every hit.", number, location, described.join(", "), then)),
+            Content::text(serde_json::to_string_pretty(&serde_json::json!({
+                "breakpoint": bp,
+                "commands": commands,
+            }))?),
+        ]))
+    }
+
+    async fn handle_alloc_trace(&self, args: Option<serde_json::Map<String, serde_json::Value>>) -> Result<CallToolResult> {
+        let action = args.as_ref().and_then(|a| a.get("action").and_then(|v| v.as_str())).unwrap_or("summary");
+        let get_u64 = |name: &str| args.as_ref().and_then(|a| a.get(name).and_then(|v| v.as_u64()));
+
+        let mut guard = self.client().write_owned().await;
+        let client = guard.as_mut().ok_or_else(ToolError::no_session)?;
+
+        match action {
+            "start" => {
+                let functions: Vec<String> = args.as_ref()
+                    .and_then(|a| a.get("functions").and_then(|v| v.as_array()))
+                    .map(|names| names.iter().filter_map(|n| n.as_str().map(|s| s.to_string())).collect())
+                    .unwrap_or_else(|| DEFAULT_FUNCTIONS.iter().map(|f| f.to_string()).collect());
+                if let Some(function) = functions.iter().find(|f| !DEFAULT_FUNCTIONS.contains(&f.as_str())) {
+                    return Err(ToolError::invalid_argument(format!(
+                        "Cannot trace '{}'; functions must be among {}",
+                        function,
+                        DEFAULT_FUNCTIONS.join(", ")
+                    )).into());
+                }
+                let (traced, missing, convention) = client.alloc_trace_start(&functions)?;
+                let mut lines: Vec<String> = traced.iter()
+                    .map(|t| format!("Tracing {} with breakpoint {}.", t.function, t.breakpoint))
+                    .collect();
+                lines.push(format!(
+                    "Assuming the {} calling convention: arguments in {} and {}, return address {}, result in {}.",
+                    convention.arch, convention.args[0], convention.args[1], convention.caller, convention.result
+                ));
+                if !missing.is_empty() {
+                    lines.push(format!("Not found: {}.", missing.join(", ")));
+                }
+                lines.push("Continue the program; traced calls are resumed automatically.".to_string());
+                Ok(CallToolResult::text(lines.join("\n")))
+            }
+            "stop" => {
+                client.alloc_trace_stop()?;
+                Ok(CallToolResult::text("Allocation trace stopped; its breakpoints were deleted. The results are kept for summary and log."))
+            }
+            "summary" => {
+                let summary = client.alloc_trace_summary(get_u64("top").unwrap_or(10) as usize);
+                Ok(CallToolResult::text(serde_json::to_string_pretty(&summary)?))
+            }
+            "log" => {
+                let events = client.alloc_tracer().events(get_u64("limit").unwrap_or(50) as usize);
+                Ok(CallToolResult::text(serde_json::to_string_pretty(&events)?))
+            }
+            other => Err(ToolError::invalid_argument(format!("action must be start, stop, summary or log, not '{}'", other)).into()),
+        }
+    }
+
+    async fn handle_watch_insert(&self, args: Option<serde_json::Map<String, serde_json::Value>>) -> Result<CallToolResult> {
+        let location = args.as_ref()
+            .and_then(|a| a.get("location").and_then(|v| v.as_str()).map(|s| s.to_string()))
+            .ok_or_else(|| ToolError::missing_argument("location"))?;
+        
+        let watch_type = args.as_ref()
+            .and_then(|a| a.get("watch_type").and_then(|v| v.as_str()))
+            .map(|s| match s {
+                "read" => WatchpointType::Read,
+                "access" => WatchpointType::Access,
+                _ => WatchpointType::Write,
+            })
+            .unwrap_or(WatchpointType::Write);
+
+        info!("Inserting {:?} watchpoint at: {}", watch_type, location);
+
+        let mut guard = self.client().write_owned().await;
+        let client = guard.as_mut().ok_or_else(ToolError::no_session)?;
+        self.check_breakpoint_limit(client, 1)?;
+        
+        let wp = client.watch_insert(watch_type.clone(), &location).map_err(|e| match is_hardware_limit(&e.to_string()) {
+            true => anyhow!("{}; gdb_watch_region can watch large regions in software", e),
+            false => e,
+        })?;
+        let label = annotate_new_breakpoint(client, &wp.number, args.as_ref());
+        
+        let type_str = match watch_type {
+            WatchpointType::Write => "write",
+            WatchpointType::Read => "read",
+            WatchpointType::Access => "access",
+        };
+        
+        Ok(CallToolResult::success(vec![
+            Content::text(format!("{} watchpoint {}{} inserted at {}", type_str, wp.number, label, location)),
+            Content::text(serde_json::to_string_pretty(&wp)?),
+        ]))
+    }
+
+    async fn handle_watch_region(&self, args: Option<serde_json::Map<String, serde_json::Value>>) -> Result<CallToolResult> {
+        let address = args.as_ref()
+            .and_then(|a| a.get("address").and_then(|v| v.as_str()).map(|s| s.to_string()))
+            .ok_or_else(|| ToolError::missing_argument("address"))?;
+        let length = args.as_ref().and_then(|a| a.get("length").and_then(|v| v.as_u64()))
+            .ok_or_else(|| ToolError::missing_argument("length"))?;
+        if length == 0 || length > MAX_WATCH_REGION {
+            return Err(ToolError::invalid_argument(format!("length must be between 1 and {}", MAX_WATCH_REGION)).into());
+        }
+        let mode = args.as_ref().and_then(|a| a.get("mode").and_then(|v| v.as_str())).unwrap_or("auto");
+        let mode = WatchMode::parse(mode)
+            .ok_or_else(|| ToolError::invalid_argument("mode must be one of: auto, hardware, guard, step"))?;
+        let max_runtime_ms = args.as_ref().and_then(|a| a.get("max_runtime_ms").and_then(|v| v.as_u64())).unwrap_or(60000);
+        let max_steps = args.as_ref().and_then(|a| a.get("max_steps").and_then(|v| v.as_u64())).unwrap_or(100_000);
+
+        let mut guard = self.client().write_owned().await;
+        let client = guard.as_mut().ok_or_else(ToolError::no_session)?;
+        if mode == WatchMode::Hardware || mode == WatchMode::Auto {
+            self.check_breakpoint_limit(client, 1)?;
+        }
+        let watch = client.watch_region(&address, length, mode, max_runtime_ms, max_steps).map_err(|e| match is_hardware_limit(&e.to_string()) {
+            true => anyhow!("{}; mode guard or step watches the region in software", e),
+            false => e,
+        })?;
+        client.record_history(format!("watch region {} ({} bytes, {} mode)", watch.address, length, watch.mode.as_str()));
+
+        let mut summary = match &watch.write {
+            Some(write) => format!(
+                "Region {} ({} bytes) written at {} (offset {}) by {} in {}: {} -> {}.",
+                watch.address,
+                length,
+                write.address,
+                write.offset,
+                write.instruction.as_deref().unwrap_or("an unknown instruction"),
+                write.func.as_deref().unwrap_or("??"),
+                write.before,
+                write.after,
+            ),
+            None => format!("Region {} ({} bytes) was not written (stopped: {}).", watch.address, length, watch.stopped_because),
+        };
+        summary.push_str(&format!("\nWatched in {} mode in {} ms", watch.mode.as_str(), watch.elapsed_ms));
+        if let Some(steps) = watch.steps {
+            summary.push_str(&format!(", {} instruction(s) stepped", steps));
+        }
+        if let (Some(faults), Some(false_positives)) = (watch.faults, watch.false_positives) {
+            summary.push_str(&format!(", {} fault(s) on the guarded pages ({} outside the region)", faults, false_positives));
+        }
+        summary.push_str(&format!(". {}", watch.tradeoff));
+        for fallback in &watch.fallbacks {
+            summary.push_str(&format!("\nNot used: {}", fallback));
+        }
+        Ok(CallToolResult::success(vec![
+            Content::text(summary),
+            Content::text(serde_json::to_string_pretty(&watch)?),
+        ]))
+    }
+
+    async fn handle_watch_delete(&self, args: Option<serde_json::Map<String, serde_json::Value>>) -> Result<CallToolResult> {
+        let number = args.as_ref()
+            .and_then(|a| a.get("number").and_then(|v| v.as_str()).map(|s| s.to_string()))
+            .ok_or_else(|| ToolError::missing_argument("number"))?;
+
+        let mut guard = self.client().write_owned().await;
+        let client = guard.as_mut().ok_or_else(ToolError::no_session)?;
+        
+        client.break_delete(&number)?;
         Ok(CallToolResult::text(format!("Watchpoint {} deleted.", number)))
     }
 
+    async fn handle_watch_suspend(&self) -> Result<CallToolResult> {
+        let mut guard = self.client().write_owned().await;
+        let client = guard.as_mut().ok_or_else(ToolError::no_session)?;
+
+        let numbers = client.watch_suspend()?;
+        let suspended = client.state().suspended_watchpoints;
+        if numbers.is_empty() {
+            return Ok(CallToolResult::text(format!(
+                "No enabled watchpoints to suspend ({} already suspended).",
+                suspended.len()
+            )));
+        }
+        Ok(CallToolResult::text(format!(
+            "Watchpoint(s) {} suspended. gdb_watch_resume enables the {} suspended watchpoint(s) again.",
+            numbers.join(" "),
+            suspended.len()
+        )))
+    }
+
+    async fn handle_watch_resume(&self) -> Result<CallToolResult> {
+        let mut guard = self.client().write_owned().await;
+        let client = guard.as_mut().ok_or_else(ToolError::no_session)?;
+
+        let numbers = client.watch_resume()?;
+        if numbers.is_empty() {
+            return Ok(CallToolResult::text("No suspended watchpoints to resume."));
+        }
+        Ok(CallToolResult::text(format!("Watchpoint(s) {} resumed.", numbers.join(" "))))
+    }
+
     async fn handle_run(&self, args: Option<serde_json::Map<String, serde_json::Value>>) -> Result<CallToolResult> {
+        let max_runtime_ms = args.as_ref().and_then(|a| a.get("max_runtime_ms").and_then(|v| v.as_u64()));
+        let sample_interval_ms = self.sample_interval(args.as_ref())?;
         let program_args = args.and_then(|a| a.get("args").and_then(|v| v.as_array()).map(|arr| {
             arr.iter().filter_map(|v| v.as_str().map(|s| s.to_string())).collect::<Vec<_>>()
         }));
 
-        let mut guard = self.client.write().await;
-        let client = guard.as_mut().ok_or_else(|| anyhow::anyhow!("GDB session not started"))?;
+        let mut guard = self.client().write_owned().await;
+        let client = guard.as_mut().ok_or_else(ToolError::no_session)?;
 
         if let Some(ref a) = program_args {
             let args_str = a.join(" ");
             client.send_command(&format!("exec-arguments {}", args_str))?;
+            client.record_history(format!("set args {}", args_str));
         }
 
-        client.exec_run()?;
-        Ok(CallToolResult::text("Program started. Waiting for stop event..."))
-    }
+        let Some(max_runtime_ms) = max_runtime_ms else {
+            client.exec_run()?;
+            client.record_history("run");
+            return Ok(CallToolResult::text("Program started. Waiting for stop event..."));
+        };
 
-    async fn handle_continue(&self) -> Result<CallToolResult> {
-        let mut guard = self.client.write().await;
-        let client = guard.as_mut().ok_or_else(|| anyhow::anyhow!("GDB session not started"))?;
-        client.exec_continue()?;
-        Ok(CallToolResult::text("Program running. Waiting for stop event..."))
+        let (interrupted, samples) = client.exec_with_sampling(true, max_runtime_ms, sample_interval_ms)?;
+        client.record_history("run");
+        sampled_run_result(client, interrupted, max_runtime_ms, &samples, sample_interval_ms)
     }
 
-    async fn handle_next(&self, args: Option<serde_json::Map<String, serde_json::Value>>) -> Result<CallToolResult> {
-        let count = args.and_then(|a| a.get("count").and_then(|v| v.as_u64())).unwrap_or(1);
+    async fn handle_program_output(&self, args: Option<serde_json::Map<String, serde_json::Value>>) -> Result<CallToolResult> {
+        let clear = args.and_then(|a| a.get("clear").and_then(|v| v.as_bool())).unwrap_or(false);
 
-        let mut guard = self.client.write().await;
-        let client = guard.as_mut().ok_or_else(|| anyhow::anyhow!("GDB session not started"))?;
+        let guard = self.client().read_owned().await;
+        let client = guard.as_ref().ok_or_else(ToolError::no_session)?;
+        let output = client.program_output(clear)?;
 
-        for _ in 0..count {
-            client.exec_next()?;
+        let status = match &output.exit {
+            Some(exit) => exit.describe(),
+            None if output.running => "Program is running".to_string(),
+            None => "Program has not exited".to_string(),
+        };
+        let mut summary = format!("{}. stdout: {} bytes, stderr: {} bytes.", status, output.stdout.len(), output.stderr.len());
+        if !output.stderr_separated {
+            summary.push_str(" stderr could not be redirected (startup-with-shell is off or the arguments already redirect it), so it is included in stdout.");
         }
-        Ok(CallToolResult::text(format!("Stepped over {} line(s).", count)))
+        Ok(CallToolResult::success(vec![
+            Content::text(summary),
+            Content::text(serde_json::to_string_pretty(&output)?),
+        ]))
     }
 
-    async fn handle_step(&self, args: Option<serde_json::Map<String, serde_json::Value>>) -> Result<CallToolResult> {
-        let count = args.and_then(|a| a.get("count").and_then(|v| v.as_u64())).unwrap_or(1);
+    /// PC sampling interval of a budgeted run: the `sample_interval_ms`
+    /// argument, else the configured default (0 disables sampling)
+    fn sample_interval(&self, args: Option<&serde_json::Map<String, serde_json::Value>>) -> Result<u64> {
+        match args.and_then(|a| a.get("sample_interval_ms").and_then(|v| v.as_u64())) {
+            Some(ms) if ms > 0 && ms < MIN_SAMPLE_INTERVAL_MS => Err(ToolError::invalid_argument(
+                format!("sample_interval_ms must be 0 (no sampling) or at least {}", MIN_SAMPLE_INTERVAL_MS)
+            ).into()),
+            Some(ms) => Ok(ms),
+            None if self.pc_sample_interval_ms == 0 => Ok(0),
+            None => Ok(self.pc_sample_interval_ms.max(MIN_SAMPLE_INTERVAL_MS)),
+        }
+    }
 
-        let mut guard = self.client.write().await;
-        let client = guard.as_mut().ok_or_else(|| anyhow::anyhow!("GDB session not started"))?;
+    async fn handle_continue(&self, args: Option<serde_json::Map<String, serde_json::Value>>) -> Result<CallToolResult> {
+        let max_runtime_ms = args.as_ref().and_then(|a| a.get("max_runtime_ms").and_then(|v| v.as_u64()));
+        let stop_at = args.as_ref().and_then(|a| a.get("stop_at")).and_then(|v| {
+            v.as_str().map(|s| s.to_string()).or_else(|| v.as_u64().map(|n| n.to_string()))
+        });
 
-        for _ in 0..count {
-            client.exec_step()?;
+        let hit_count = args.as_ref().and_then(|a| a.get("hit_count").and_then(|v| v.as_u64()));
+        if hit_count.is_some_and(|n| n == 0 || n > MAX_HIT_COUNT) {
+            return Err(ToolError::invalid_argument(format!("hit_count must be between 1 and {}", MAX_HIT_COUNT)).into());
         }
-        Ok(CallToolResult::text(format!("Stepped into {} line(s).", count)))
-    }
 
-    async fn handle_finish(&self) -> Result<CallToolResult> {
-        let mut guard = self.client.write().await;
-        let client = guard.as_mut().ok_or_else(|| anyhow::anyhow!("GDB session not started"))?;
-        client.exec_finish()?;
-        Ok(CallToolResult::text("Stepping out of function..."))
-    }
+        let sample_interval_ms = self.sample_interval(args.as_ref())?;
 
-    async fn handle_stepi(&self, args: Option<serde_json::Map<String, serde_json::Value>>) -> Result<CallToolResult> {
-        let count = args.as_ref()
-            .and_then(|a| a.get("count").and_then(|v| v.as_u64()))
-            .unwrap_or(1);
+        let mut guard = self.client().write_owned().await;
+        let client = guard.as_mut().ok_or_else(ToolError::no_session)?;
 
-        let mut guard = self.client.write().await;
-        let client = guard.as_mut().ok_or_else(|| anyhow::anyhow!("GDB session not started"))?;
-        
-        for _ in 0..count {
-            client.exec_step_instruction()?;
-            std::thread::sleep(std::time::Duration::from_millis(100));
+        if let Some(count) = hit_count {
+            let max_runtime_ms = max_runtime_ms.unwrap_or(60000);
+            let run = client.continue_hits(stop_at.as_deref(), count, max_runtime_ms)?;
+            client.record_history(format!("continue (hit {} of {})", run.hits.len(), count));
+
+            let breakpoint = run.breakpoint.as_deref().unwrap_or("?");
+            let note = match run.stopped_because.as_str() {
+                "completed" => format!(
+                    "Reached hit {} of breakpoint {}, continuing through {} earlier hit(s).",
+                    count, breakpoint, count - 1
+                ),
+                _ if run.hits.is_empty() => format!("Stopped before any breakpoint hit ({}).", run.stopped_because),
+                reason => format!(
+                    "Stopped after {} of {} hit(s) of breakpoint {}: {}.",
+                    run.hits.len(), count, breakpoint, reason
+                ),
+            };
+            let mut result = budgeted_run_result(client, run.stopped_because == "timeout", max_runtime_ms)?;
+            result.content.insert(0, Content::text(note));
+            result.content.push(Content::text(serde_json::to_string_pretty(&run)?));
+            return Ok(result);
         }
-        
-        let pc = client.data_evaluate_expression("$pc")?;
-        Ok(CallToolResult::text(format!("Stepped {} instruction(s). PC = {}", count, pc)))
-    }
 
-    async fn handle_nexti(&self, args: Option<serde_json::Map<String, serde_json::Value>>) -> Result<CallToolResult> {
-        let count = args.as_ref()
-            .and_then(|a| a.get("count").and_then(|v| v.as_u64()))
-            .unwrap_or(1);
+        if let Some(number) = stop_at {
+            // Breakpoints can only be re-enabled once the target has stopped
+            let max_runtime_ms = max_runtime_ms.unwrap_or(60000);
+            let interrupted = client.continue_to_breakpoint(&number, max_runtime_ms)?;
+            client.record_history("continue");
 
-        let mut guard = self.client.write().await;
-        let client = guard.as_mut().ok_or_else(|| anyhow::anyhow!("GDB session not started"))?;
-        
-        for _ in 0..count {
-            client.exec_next_instruction()?;
-            std::thread::sleep(std::time::Duration::from_millis(100));
+            let last_hit = client.tracker().breakpoint_hits(None, 1).pop();
+            let reached = !interrupted
+                && client.last_stop().is_some_and(|s| s.reason == StopReason::BreakpointHit)
+                && last_hit.is_some_and(|hit| hit.number == number);
+            let mut result = budgeted_run_result(client, interrupted, max_runtime_ms)?;
+            let note = if reached {
+                format!("Reached breakpoint {}; other breakpoints were disabled while running and are enabled again.", number)
+            } else {
+                format!("Did not reach breakpoint {}; other breakpoints were disabled while running and are enabled again.", number)
+            };
+            result.content.insert(0, Content::text(note));
+            return Ok(result);
         }
-        
-        let pc = client.data_evaluate_expression("$pc")?;
-        Ok(CallToolResult::text(format!("Stepped {} instruction(s). PC = {}", count, pc)))
+
+        let Some(max_runtime_ms) = max_runtime_ms else {
+            client.exec_continue()?;
+            client.record_history("continue");
+            return Ok(CallToolResult::text("Program running. Waiting for stop event..."));
+        };
+
+        let (interrupted, samples) = client.exec_with_sampling(false, max_runtime_ms, sample_interval_ms)?;
+        client.record_history("continue");
+        sampled_run_result(client, interrupted, max_runtime_ms, &samples, sample_interval_ms)
     }
 
-    async fn handle_interrupt(&self) -> Result<CallToolResult> {
-        let mut guard = self.client.write().await;
-        let client = guard.as_mut().ok_or_else(|| anyhow::anyhow!("GDB session not started"))?;
-        client.exec_interrupt()?;
-        Ok(CallToolResult::text("Program interrupted."))
+    async fn handle_next(&self, args: Option<serde_json::Map<String, serde_json::Value>>) -> Result<CallToolResult> {
+        let count = args.and_then(|a| a.get("count").and_then(|v| v.as_u64())).unwrap_or(1);
+
+        let mut guard = self.client().write_owned().await;
+        let client = guard.as_mut().ok_or_else(ToolError::no_session)?;
+
+        let sequence = client.step_repeat(GdbClient::exec_next, count);
+        client.record_history(format!("next {}", sequence.completed));
+        step_sequence_result("Stepped over", &sequence)
     }
 
-    async fn handle_stack_list(&self) -> Result<CallToolResult> {
-        let mut guard = self.client.write().await;
-        let client = guard.as_mut().ok_or_else(|| anyhow::anyhow!("GDB session not started"))?;
-        let frames = client.stack_list_frames()?;
-        Ok(CallToolResult::text(serde_json::to_string_pretty(&frames)?))
+    async fn handle_step(&self, args: Option<serde_json::Map<String, serde_json::Value>>) -> Result<CallToolResult> {
+        let count = args.and_then(|a| a.get("count").and_then(|v| v.as_u64())).unwrap_or(1);
+
+        let mut guard = self.client().write_owned().await;
+        let client = guard.as_mut().ok_or_else(ToolError::no_session)?;
+
+        let sequence = client.step_repeat(GdbClient::exec_step, count);
+        client.record_history(format!("step {}", sequence.completed));
+        step_sequence_result("Stepped into", &sequence)
     }
 
-    async fn handle_stack_select(&self, args: Option<serde_json::Map<String, serde_json::Value>>) -> Result<CallToolResult> {
-        let level = args.as_ref()
-            .and_then(|a| a.get("level").and_then(|v| v.as_u64()))
-            .ok_or_else(|| anyhow::anyhow!("level is required"))?;
+    async fn handle_skip(&self, args: Option<serde_json::Map<String, serde_json::Value>>) -> Result<CallToolResult> {
+        let kind: SkipKind = args.as_ref()
+            .and_then(|a| a.get("kind").cloned())
+            .map(serde_json::from_value)
+            .transpose()
+            .map_err(|_| ToolError::invalid_argument("kind must be one of: function, rfunction, file, gfile"))?
+            .unwrap_or(SkipKind::Function);
+        let pattern = args.as_ref()
+            .and_then(|a| a.get("pattern").and_then(|v| v.as_str()).map(|s| s.to_string()))
+            .ok_or_else(|| ToolError::missing_argument("pattern"))?;
 
-        let mut guard = self.client.write().await;
-        let client = guard.as_mut().ok_or_else(|| anyhow::anyhow!("GDB session not started"))?;
-        client.stack_select_frame(level)?;
-        Ok(CallToolResult::text(format!("Selected frame {}.", level)))
+        let mut guard = self.client().write_owned().await;
+        let client = guard.as_mut().ok_or_else(ToolError::no_session)?;
+        let output = client.skip(kind, &pattern)?;
+        Ok(CallToolResult::text(output.trim().to_string()))
     }
 
-    async fn handle_stack_info(&self) -> Result<CallToolResult> {
-        let mut guard = self.client.write().await;
-        let client = guard.as_mut().ok_or_else(|| anyhow::anyhow!("GDB session not started"))?;
-        
-        if let Some(frame) = client.stack_info_frame()? {
-            Ok(CallToolResult::text(serde_json::to_string_pretty(&frame)?))
+    async fn handle_skip_list(&self) -> Result<CallToolResult> {
+        let mut guard = self.client().write_owned().await;
+        let client = guard.as_mut().ok_or_else(ToolError::no_session)?;
+        let output = client.skip_list()?;
+        Ok(CallToolResult::text(output.trim_end().to_string()))
+    }
+
+    async fn handle_skip_delete(&self, args: Option<serde_json::Map<String, serde_json::Value>>) -> Result<CallToolResult> {
+        let number = args.and_then(|a| a.get("number").and_then(|v| v.as_str()).map(|s| s.to_string()));
+
+        let mut guard = self.client().write_owned().await;
+        let client = guard.as_mut().ok_or_else(ToolError::no_session)?;
+
+        if let Some(n) = number {
+            let numbers = normalize_breakpoint_numbers(&n)?;
+            client.skip_delete(Some(&numbers))?;
+            Ok(CallToolResult::text(format!("Skip entries {} deleted.", numbers)))
         } else {
-            Ok(CallToolResult::error_text("No frame information available."))
+            client.skip_delete(None)?;
+            Ok(CallToolResult::text("All skip entries deleted."))
         }
     }
 
-    async fn handle_thread_list(&self) -> Result<CallToolResult> {
-        let mut guard = self.client.write().await;
-        let client = guard.as_mut().ok_or_else(|| anyhow::anyhow!("GDB session not started"))?;
-        let threads = client.thread_list_ids()?;
-        Ok(CallToolResult::text(serde_json::to_string_pretty(&threads)?))
-    }
+    async fn handle_finish(&self, args: Option<serde_json::Map<String, serde_json::Value>>) -> Result<CallToolResult> {
+        let frames = args.as_ref().and_then(|a| a.get("frames").and_then(|v| v.as_u64()));
+        let until_frame_level = args.as_ref().and_then(|a| a.get("until_frame_level").and_then(|v| v.as_u64()));
+        let timeout_ms = args.as_ref()
+            .and_then(|a| a.get("timeout_ms").and_then(|v| v.as_u64()))
+            .unwrap_or(30000);
+        if frames.is_some() && until_frame_level.is_some() {
+            return Err(ToolError::invalid_argument("Pass either frames or until_frame_level, not both").into());
+        }
 
-    async fn handle_thread_select(&self, args: Option<serde_json::Map<String, serde_json::Value>>) -> Result<CallToolResult> {
-        let thread_id = args.as_ref()
-            .and_then(|a| a.get("thread_id").and_then(|v| v.as_str()).map(|s| s.to_string()))
-            .ok_or_else(|| anyhow::anyhow!("thread_id is required"))?;
+        let mut guard = self.client().write_owned().await;
+        let client = guard.as_mut().ok_or_else(ToolError::no_session)?;
 
-        let mut guard = self.client.write().await;
-        let client = guard.as_mut().ok_or_else(|| anyhow::anyhow!("GDB session not started"))?;
-        client.thread_select(&thread_id)?;
-        Ok(CallToolResult::text(format!("Selected thread {}.", thread_id)))
-    }
+        let frames = match (frames, until_frame_level) {
+            (Some(0), _) => return Err(ToolError::invalid_argument("frames must be at least 1").into()),
+            (Some(frames), _) => frames,
+            (None, Some(level)) => {
+                let selected = client.stack_info_frame()?.map(|f| f.level).unwrap_or(0);
+                if level <= selected {
+                    return Err(ToolError::invalid_argument(format!(
+                        "until_frame_level must be above the selected frame (level {})",
+                        selected
+                    )).into());
+                }
+                level - selected
+            }
+            (None, None) => {
+                client.exec_finish()?;
+                client.record_history("finish");
+                return Ok(CallToolResult::text("Stepping out of function..."));
+            }
+        };
 
-    async fn handle_memory_read(&self, args: Option<serde_json::Map<String, serde_json::Value>>) -> Result<CallToolResult> {
-        let address = args.as_ref()
-            .and_then(|a| a.get("address").and_then(|v| v.as_str()).map(|s| s.to_string()))
-            .ok_or_else(|| anyhow::anyhow!("address is required"))?;
-        let count = args.and_then(|a| a.get("count").and_then(|v| v.as_u64())).unwrap_or(16);
+        let sequence = client.finish_frames(frames, timeout_ms);
+        for _ in 0..sequence.completed {
+            client.record_history("finish");
+        }
 
-        let mut guard = self.client.write().await;
-        let client = guard.as_mut().ok_or_else(|| anyhow::anyhow!("GDB session not started"))?;
-        let mem = client.data_read_memory(&address, count)?;
-        Ok(CallToolResult::text(serde_json::to_string_pretty(&mem)?))
+        let mut summary = format!("Finished {} of {} frame(s)", sequence.completed, sequence.requested);
+        match sequence.stopped_because.as_str() {
+            "completed" => {}
+            "error" => summary.push_str(&format!("; stopped by error: {}", sequence.error.as_deref().unwrap_or("unknown"))),
+            "timeout" => summary.push_str(&format!("; a function did not return within {} ms and the target was interrupted", timeout_ms)),
+            reason => summary.push_str(&format!("; stopped early: {}", reason)),
+        }
+        for ret in &sequence.returns {
+            summary.push_str(&format!(
+                "\n{} returned {} to {}",
+                ret.returned_from.as_deref().unwrap_or("??"),
+                ret.return_value.as_deref().unwrap_or("(void)"),
+                ret.func.as_deref().unwrap_or("??")
+            ));
+            if let (Some(file), Some(line)) = (&ret.file, ret.line) {
+                summary.push_str(&format!(" at {}:{}", file, line));
+            }
+        }
+
+        let contents = vec![
+            Content::text(summary),
+            Content::text(serde_json::to_string_pretty(&sequence)?),
+        ];
+        Ok(if sequence.error.is_some() {
+            CallToolResult::error(contents)
+        } else {
+            CallToolResult::success(contents)
+        })
     }
 
-    async fn handle_memory_write(&self, args: Option<serde_json::Map<String, serde_json::Value>>) -> Result<CallToolResult> {
-        let address = args.as_ref()
+    async fn handle_stepi(&self, args: Option<serde_json::Map<String, serde_json::Value>>) -> Result<CallToolResult> {
+        self.instruction_step(args, GdbClient::exec_step_instructions, "stepi").await
+    }
+
+    async fn handle_nexti(&self, args: Option<serde_json::Map<String, serde_json::Value>>) -> Result<CallToolResult> {
+        self.instruction_step(args, GdbClient::exec_next_instructions, "nexti").await
+    }
+
+    /// Shared body of `gdb_stepi` and `gdb_nexti`
+    async fn instruction_step(
+        &self,
+        args: Option<serde_json::Map<String, serde_json::Value>>,
+        step: fn(&mut GdbClient, u64) -> Result<()>,
+        command: &str,
+    ) -> Result<CallToolResult> {
+        let count = args.as_ref()
+            .and_then(|a| a.get("count").and_then(|v| v.as_u64()))
+            .unwrap_or(1);
+        let watch = StepWatch {
+            instructions: args.as_ref()
+                .and_then(|a| a.get("show_instruction").and_then(|v| v.as_bool()))
+                .unwrap_or(false),
+            registers: args.as_ref()
+                .and_then(|a| a.get("registers").and_then(|v| v.as_array()))
+                .map(|v| v.iter().filter_map(|s| s.as_str().map(|s| s.trim_start_matches('$').to_string())).collect())
+                .unwrap_or_default(),
+            memory: args.as_ref()
+                .and_then(|a| a.get("memory").and_then(|v| v.as_array()))
+                .map(|arr| {
+                    arr.iter()
+                        .filter_map(|r| {
+                            let address = r.get("address").and_then(|v| v.as_str())?;
+                            let length = r.get("length").and_then(|v| v.as_u64()).unwrap_or(4);
+                            Some((address.to_string(), length))
+                        })
+                        .collect()
+                })
+                .unwrap_or_default(),
+        };
+
+        let mut guard = self.client().write_owned().await;
+        let client = guard.as_mut().ok_or_else(ToolError::no_session)?;
+
+        let result = client.step_instructions(step, count, &watch)?;
+        client.record_history(format!("{} {}", command, count));
+
+        let summary = format!("Stepped {} instruction(s). PC = {}", count, result.pc);
+        if watch.is_empty() {
+            return Ok(CallToolResult::text(summary));
+        }
+        let changed: Vec<&str> = result.registers.iter().chain(&result.memory)
+            .filter(|v| v.changed)
+            .map(|v| v.name.as_str())
+            .collect();
+        let summary = match changed.is_empty() {
+            true => summary,
+            false => format!("{}\nChanged: {}", summary, changed.join(", ")),
+        };
+        Ok(CallToolResult::success(vec![
+            Content::text(summary),
+            Content::text(serde_json::to_string_pretty(&result)?),
+        ]))
+    }
+
+    async fn handle_step_until_memory_change(&self, args: Option<serde_json::Map<String, serde_json::Value>>) -> Result<CallToolResult> {
+        let address = args.as_ref()
+            .and_then(|a| a.get("address").and_then(|v| v.as_str()).map(|s| s.to_string()))
+            .ok_or_else(|| ToolError::missing_argument("address"))?;
+        let length = args.as_ref().and_then(|a| a.get("length").and_then(|v| v.as_u64())).unwrap_or(4);
+        let (max_steps, timeout_ms, trace_limit) = step_budget(args.as_ref());
+
+        let mut guard = self.client().write_owned().await;
+        let client = guard.as_mut().ok_or_else(ToolError::no_session)?;
+
+        let result = client.step_until_change(
+            |c| c.data_read_memory(&address, length).map(|m| m.data.concat()),
+            max_steps,
+            timeout_ms,
+            trace_limit,
+        )?;
+        client.record_history(format!("stepi {}", result.steps));
+
+        let summary = if result.changed {
+            format!("Memory at {} ({} bytes) changed after {} instruction(s): {} -> {}", address, length, result.steps, result.before, result.after)
+        } else {
+            format!("Memory at {} did not change after {} instruction(s) (stopped: {}).", address, result.steps, result.stopped_because)
+        };
+        Ok(CallToolResult::success(vec![
+            Content::text(summary),
+            Content::text(serde_json::to_string_pretty(&result)?),
+        ]))
+    }
+
+    async fn handle_step_until_register_change(&self, args: Option<serde_json::Map<String, serde_json::Value>>) -> Result<CallToolResult> {
+        let register = args.as_ref()
+            .and_then(|a| a.get("register").and_then(|v| v.as_str()).map(|s| s.trim_start_matches('$').to_string()))
+            .ok_or_else(|| ToolError::missing_argument("register"))?;
+        let (max_steps, timeout_ms, trace_limit) = step_budget(args.as_ref());
+
+        let mut guard = self.client().write_owned().await;
+        let client = guard.as_mut().ok_or_else(ToolError::no_session)?;
+
+        let expression = format!("${}", register);
+        let result = client.step_until_change(
+            |c| c.data_evaluate_expression(&expression),
+            max_steps,
+            timeout_ms,
+            trace_limit,
+        )?;
+        client.record_history(format!("stepi {}", result.steps));
+
+        let summary = if result.changed {
+            format!("Register {} changed after {} instruction(s): {} -> {}", register, result.steps, result.before, result.after)
+        } else {
+            format!("Register {} did not change after {} instruction(s) (stopped: {}).", register, result.steps, result.stopped_because)
+        };
+        Ok(CallToolResult::success(vec![
+            Content::text(summary),
+            Content::text(serde_json::to_string_pretty(&result)?),
+        ]))
+    }
+
+    async fn handle_step_asm_until_branch(&self, args: Option<serde_json::Map<String, serde_json::Value>>) -> Result<CallToolResult> {
+        let execute_branch = args.as_ref()
+            .and_then(|a| a.get("execute_branch").and_then(|v| v.as_bool()))
+            .unwrap_or(true);
+        let (max_steps, timeout_ms, trace_limit) = step_budget(args.as_ref());
+
+        let mut guard = self.client().write_owned().await;
+        let client = guard.as_mut().ok_or_else(ToolError::no_session)?;
+
+        let result = client.step_until_branch(execute_branch, max_steps, timeout_ms, trace_limit)?;
+        client.record_history(format!("stepi {}", result.steps));
+
+        let summary = match (&result.branch, result.kind) {
+            (Some(branch), Some(kind)) => {
+                let mut summary = format!("Reached a {} after {} instruction(s): {} {}", kind.as_str(), result.steps, branch.addr, branch.inst);
+                if let Some(target) = &result.target {
+                    summary.push_str(&format!(" -> {}", target));
+                }
+                summary
+            }
+            _ => format!("No branch after {} instruction(s) (stopped: {}).", result.steps, result.stopped_because),
+        };
+        Ok(CallToolResult::success(vec![
+            Content::text(summary),
+            Content::text(serde_json::to_string_pretty(&result)?),
+        ]))
+    }
+
+    async fn handle_expr_diff(&self, args: Option<serde_json::Map<String, serde_json::Value>>) -> Result<CallToolResult> {
+        let expressions: Vec<String> = args.as_ref()
+            .and_then(|a| a.get("expressions").and_then(|v| v.as_array()))
+            .ok_or_else(|| ToolError::missing_argument("expressions"))?
+            .iter()
+            .filter_map(|v| v.as_str().map(|s| s.to_string()))
+            .collect();
+        if expressions.is_empty() {
+            return Err(ToolError::invalid_argument("expressions must contain at least one expression").into());
+        }
+        let action: DiffAction = args.as_ref()
+            .and_then(|a| a.get("action").cloned())
+            .map(serde_json::from_value)
+            .transpose()
+            .map_err(|_| ToolError::invalid_argument("action must be one of: step, next, continue"))?
+            .unwrap_or_default();
+        let max_runtime_ms = args.as_ref()
+            .and_then(|a| a.get("max_runtime_ms").and_then(|v| v.as_u64()))
+            .unwrap_or(60000);
+
+        let mut guard = self.client().write_owned().await;
+        let client = guard.as_mut().ok_or_else(ToolError::no_session)?;
+
+        let before = exprdiff::read_expressions(client, &expressions);
+        let interrupted = match action {
+            DiffAction::Step => client.exec_step().map(|_| false)?,
+            DiffAction::Next => client.exec_next().map(|_| false)?,
+            DiffAction::Continue => client.exec_with_budget(false, max_runtime_ms)?,
+        };
+        client.record_history(action.as_str());
+        let after = exprdiff::read_expressions(client, &expressions);
+        let changes = exprdiff::diff(&expressions, before, after);
+
+        let stop = client.last_stop();
+        let frame = stop.as_ref().and_then(|s| s.frame.as_ref());
+        let mut location = match frame {
+            Some(frame) => format!("{} in {}", frame.addr, frame.func.as_deref().unwrap_or("??")),
+            None => "unknown location".to_string(),
+        };
+        if let Some((file, line)) = frame.and_then(|f| Some((f.file.as_ref()?, f.line?))) {
+            location.push_str(&format!(" at {}:{}", file, line));
+        }
+        let result = ExprDiff {
+            action: action.as_str(),
+            interrupted,
+            stop_reason: stop.as_ref().map(|s| s.reason.as_str().to_string()),
+            changed: changes.iter().filter(|c| c.changed).count(),
+            expressions: changes,
+        };
+
+        let mut summary = if interrupted {
+            format!("Timed out after {} ms; interrupted at {}.", max_runtime_ms, location)
+        } else {
+            format!(
+                "After {} ({}): now at {}.",
+                action.as_str(),
+                result.stop_reason.as_deref().unwrap_or("unknown"),
+                location
+            )
+        };
+        summary.push_str(&format!(" {} of {} expression(s) changed", result.changed, result.expressions.len()));
+        let changed: Vec<String> = result.expressions.iter().filter(|c| c.changed).map(|c| c.describe()).collect();
+        if changed.is_empty() {
+            summary.push('.');
+        } else {
+            summary.push_str(&format!(": {}", changed.join("; ")));
+        }
+        Ok(CallToolResult::success(vec![
+            Content::text(summary),
+            Content::text(serde_json::to_string_pretty(&result)?),
+        ]))
+    }
+
+    async fn handle_call_summary(&self, args: Option<serde_json::Map<String, serde_json::Value>>) -> Result<CallToolResult> {
+        let max_runtime_ms = args.as_ref()
+            .and_then(|a| a.get("max_runtime_ms").and_then(|v| v.as_u64()))
+            .unwrap_or(60000);
+        let max_segments = args.as_ref()
+            .and_then(|a| a.get("max_segments").and_then(|v| v.as_u64()))
+            .unwrap_or(100000)
+            .max(1);
+
+        let mut guard = self.client().write_owned().await;
+        let client = guard.as_mut().ok_or_else(ToolError::no_session)?;
+
+        let summary = client.call_summary(max_runtime_ms, max_segments)?;
+        client.record_history("continue");
+
+        let mut text = format!("{} call(s) to {} function(s)", summary.total_calls, summary.functions.len());
+        if summary.truncated {
+            text.push_str(&format!(" in the first {} trace segments", summary.segments));
+        }
+        let top: Vec<String> = summary.functions.iter().take(10).map(|f| format!("{} x{}", f.function, f.calls)).collect();
+        if !top.is_empty() {
+            text.push_str(&format!(": {}", top.join(", ")));
+        }
+        text.push('.');
+
+        let mut result = budgeted_run_result(client, summary.interrupted, max_runtime_ms)?;
+        result.content.insert(0, Content::text(text));
+        result.content.push(Content::text(serde_json::to_string_pretty(&summary)?));
+        Ok(result)
+    }
+
+    /// Interrupt the target. This does not take the client lock, so it also
+    /// stops a target that another tool call is waiting on; that call then
+    /// returns with the stop.
+    async fn handle_interrupt(&self) -> Result<CallToolResult> {
+        let interrupter = self.current_session().interrupter().ok_or_else(ToolError::no_session)?;
+        let method = interrupter.interrupt()?;
+        let how = match method {
+            InterruptMethod::Mi => "-exec-interrupt",
+            InterruptMethod::Signal => "SIGINT (target does not support async execution)",
+        };
+        Ok(CallToolResult::text(format!("Program interrupted via {}.", how)))
+    }
+
+    async fn handle_stack_list(&self, args: Option<serde_json::Map<String, serde_json::Value>>) -> Result<CallToolResult> {
+        let low = args.as_ref().and_then(|a| a.get("low_frame").and_then(|v| v.as_u64())).unwrap_or(0);
+        let high = match args.as_ref().and_then(|a| a.get("high_frame").and_then(|v| v.as_i64())) {
+            Some(h) if h < 0 => i32::MAX as u64,
+            Some(h) => h as u64,
+            None => low + DEFAULT_STACK_FRAMES - 1,
+        };
+        if high < low {
+            return Err(ToolError::invalid_argument("high_frame must not be less than low_frame").into());
+        }
+
+        let mut guard = self.client().write_owned().await;
+        let client = guard.as_mut().ok_or_else(ToolError::no_session)?;
+        let frames = client.stack_list_frames(Some((low, high)))?;
+
+        let mut contents = vec![Content::text(serde_json::to_string_pretty(&frames)?)];
+        if let Some(hint) = optimization_hint(frames.iter().filter(|f| f.inlined).count(), 0) {
+            contents.push(Content::text(hint));
+        }
+        if high != i32::MAX as u64 && client.stack_info_depth(Some(high + 2))? > high + 1 {
+            contents.push(Content::text(format!(
+                "Stack continues past frame {}. Use low_frame/high_frame to see more, or gdb_stack_depth for the total.",
+                high
+            )));
+        }
+        Ok(CallToolResult::success(contents))
+    }
+
+    async fn handle_stack_depth(&self, args: Option<serde_json::Map<String, serde_json::Value>>) -> Result<CallToolResult> {
+        let max_depth = args.and_then(|a| a.get("max_depth").and_then(|v| v.as_u64()));
+
+        let mut guard = self.client().write_owned().await;
+        let client = guard.as_mut().ok_or_else(ToolError::no_session)?;
+        let depth = client.stack_info_depth(max_depth)?;
+        Ok(CallToolResult::text(serde_json::json!({ "depth": depth }).to_string()))
+    }
+
+    async fn handle_backtrace_full(&self, args: Option<serde_json::Map<String, serde_json::Value>>) -> Result<CallToolResult> {
+        let max_frames = args.and_then(|a| a.get("max_frames").and_then(|v| v.as_u64())).unwrap_or(16).max(1);
+
+        let mut guard = self.client().write_owned().await;
+        let client = guard.as_mut().ok_or_else(ToolError::no_session)?;
+        let backtrace = client.backtrace_full(max_frames)?;
+
+        let inlined = backtrace.iter().filter(|f| f.frame.inlined).count();
+        let optimized_out = backtrace.iter()
+            .flat_map(|f| f.args.iter().chain(&f.locals))
+            .filter(|v| v.optimized_out)
+            .count();
+        let mut contents = vec![Content::text(serde_json::to_string_pretty(&backtrace)?)];
+        if let Some(hint) = optimization_hint(inlined, optimized_out) {
+            contents.push(Content::text(hint));
+        }
+        let failed: Vec<String> = backtrace.iter().filter(|f| f.error.is_some()).map(|f| f.frame.level.to_string()).collect();
+        if !failed.is_empty() {
+            contents.push(Content::text(format!("Variables of frame(s) {} could not be listed; see \"error\".", failed.join(", "))));
+        }
+        Ok(CallToolResult::success(contents))
+    }
+
+    async fn handle_stack_select(&self, args: Option<serde_json::Map<String, serde_json::Value>>) -> Result<CallToolResult> {
+        let level = args.as_ref()
+            .and_then(|a| a.get("level").and_then(|v| v.as_u64()))
+            .ok_or_else(|| ToolError::missing_argument("level"))?;
+
+        let mut guard = self.client().write_owned().await;
+        let client = guard.as_mut().ok_or_else(ToolError::no_session)?;
+        client.stack_select_frame(level)?;
+        client.record_history(format!("frame {}", level));
+        Ok(CallToolResult::text(format!("Selected frame {}.", level)))
+    }
+
+    async fn handle_frame_move(&self, args: Option<serde_json::Map<String, serde_json::Value>>, up: bool) -> Result<CallToolResult> {
+        let count = args.and_then(|a| a.get("count").and_then(|v| v.as_u64())).unwrap_or(1);
+        let offset = if up { count as i64 } else { -(count as i64) };
+
+        let mut guard = self.client().write_owned().await;
+        let client = guard.as_mut().ok_or_else(ToolError::no_session)?;
+        let frame = client.stack_move_frame(offset)?;
+        client.record_history(format!("{} {}", if up { "up" } else { "down" }, count));
+
+        let mut summary = format!(
+            "#{} {} {}",
+            frame.level,
+            frame.addr,
+            frame.func.as_deref().unwrap_or("??"),
+        );
+        if let (Some(file), Some(line)) = (&frame.file, frame.line) {
+            summary.push_str(&format!(" at {}:{}", file, line));
+        }
+        if let Some(source) = source_line(&frame) {
+            summary.push_str(&format!("\n{}\t{}", frame.line.unwrap_or_default(), source));
+        }
+
+        Ok(CallToolResult::success(vec![
+            Content::text(summary),
+            Content::text(serde_json::to_string_pretty(&frame)?),
+        ]))
+    }
+
+    async fn handle_siginfo(&self) -> Result<CallToolResult> {
+        let mut guard = self.client().write_owned().await;
+        let client = guard.as_mut().ok_or_else(ToolError::no_session)?;
+
+        let info = client.siginfo()?;
+        let mut summary = format!("{} (si_code {}", info.name.as_deref().unwrap_or("unknown signal"), info.code);
+        if let Some(cause) = &info.cause {
+            summary.push_str(&format!(": {}", cause));
+        }
+        summary.push(')');
+        if let Some(addr) = &info.fault_address {
+            summary.push_str(&format!(" at address {}", addr));
+        }
+        if let Some(pid) = info.sender_pid {
+            summary.push_str(&format!(" sent by pid {}", pid));
+        }
+        Ok(CallToolResult::success(vec![
+            Content::text(summary),
+            Content::text(serde_json::to_string_pretty(&info)?),
+        ]))
+    }
+
+    async fn handle_errno(&self, args: Option<serde_json::Map<String, serde_json::Value>>) -> Result<CallToolResult> {
+        let with_stack = args.as_ref().and_then(|a| a.get("stack").and_then(|v| v.as_bool())).unwrap_or(true);
+
+        let mut guard = self.client().write_owned().await;
+        let client = guard.as_mut().ok_or_else(ToolError::no_session)?;
+
+        let errno = client.errno(with_stack)?;
+        let mut summary = match (&errno.name, &errno.description) {
+            (Some(name), Some(description)) => format!("errno = {} ({}: {})", errno.value, name, description),
+            _ => format!("errno = {}", errno.value),
+        };
+        if let Some(call) = &errno.libc_call {
+            summary.push_str(&format!("; in {}", call.function.as_deref().unwrap_or("??")));
+            if let Some(caller) = &call.caller {
+                summary.push_str(&format!(" called from {}", caller.func.as_deref().unwrap_or("??")));
+                if let (Some(file), Some(line)) = (&caller.file, caller.line) {
+                    summary.push_str(&format!(" at {}:{}", file, line));
+                }
+            }
+        }
+        Ok(CallToolResult::success(vec![
+            Content::text(summary),
+            Content::text(serde_json::to_string_pretty(&errno)?),
+        ]))
+    }
+
+    async fn handle_stack_info(&self) -> Result<CallToolResult> {
+        let mut guard = self.client().write_owned().await;
+        let client = guard.as_mut().ok_or_else(ToolError::no_session)?;
+        
+        if let Some(frame) = client.stack_info_frame()? {
+            Ok(CallToolResult::text(serde_json::to_string_pretty(&frame)?))
+        } else {
+            Ok(CallToolResult::error_text("No frame information available."))
+        }
+    }
+
+    async fn handle_stack_scan(&self, args: Option<serde_json::Map<String, serde_json::Value>>) -> Result<CallToolResult> {
+        let start = args.as_ref().and_then(|a| a.get("start").and_then(|v| v.as_str()).map(|s| s.to_string()));
+        let words = args.as_ref().and_then(|a| a.get("words").and_then(|v| v.as_u64())).unwrap_or(256);
+        if words == 0 || words > MAX_SCAN_WORDS {
+            return Err(ToolError::invalid_argument(format!("words must be between 1 and {}", MAX_SCAN_WORDS)).into());
+        }
+
+        let mut guard = self.client().write_owned().await;
+        let client = guard.as_mut().ok_or_else(ToolError::no_session)?;
+        let scan = client.stack_scan(start.as_deref(), words)?;
+
+        let summary = format!(
+            "Scanned {} word(s) from {}: {} value(s) point into code.",
+            scan.words,
+            scan.start,
+            scan.candidates.len()
+        );
+        Ok(CallToolResult::success(vec![
+            Content::text(summary),
+            Content::text(serde_json::to_string_pretty(&scan)?),
+        ]))
+    }
+
+    async fn handle_unwind_fallback(&self, args: Option<serde_json::Map<String, serde_json::Value>>) -> Result<CallToolResult> {
+        let get_str = |key: &str| args.as_ref().and_then(|a| a.get(key).and_then(|v| v.as_str()).map(|s| s.to_string()));
+        let get_i64 = |key: &str| args.as_ref().and_then(|a| a.get(key).and_then(|v| v.as_i64()));
+        let max_frames = args.as_ref().and_then(|a| a.get("max_frames").and_then(|v| v.as_u64())).unwrap_or(32);
+        if max_frames == 0 || max_frames > 256 {
+            return Err(ToolError::invalid_argument("max_frames must be between 1 and 256").into());
+        }
+
+        let mut guard = self.client().write_owned().await;
+        let client = guard.as_mut().ok_or_else(ToolError::no_session)?;
+        let arch = client.instruction_set(None)?;
+        let mut layout = FrameLayout::for_arch(arch);
+        if let Some(register) = get_str("fp_register") {
+            layout.fp_register = register.trim_start_matches('$').to_string();
+        }
+        if let Some(offset) = get_i64("saved_fp_offset") {
+            layout.saved_fp_offset = offset;
+        }
+        if let Some(offset) = get_i64("return_offset") {
+            layout.return_offset = offset;
+        }
+        let unwind = client.unwind_fallback(arch, layout, get_str("pc").as_deref(), get_str("frame_pointer").as_deref(), max_frames as usize)?;
+
+        let lines: Vec<String> = unwind.frames.iter().map(|f| {
+            let via = match f.source {
+                FrameSource::Pc => "pc".to_string(),
+                FrameSource::LinkRegister => "link register".to_string(),
+                FrameSource::FrameRecord => format!("frame record at {}", f.frame_pointer.as_deref().unwrap_or("?")),
+            };
+            format!("#{:<2} {} in {} ({})", f.level, f.pc, f.symbol.as_deref().unwrap_or("??"), via)
+        }).collect();
+        let summary = format!(
+            "HEURISTIC backtrace from the {} chain ({} frame(s); GDB's unwinder found {}). Functions without a frame record are missing. Walk ended: {}.\n{}",
+            unwind.layout.fp_register,
+            unwind.frames.len(),
+            unwind.gdb_frames,
+            unwind.stopped_because,
+            lines.join("\n")
+        );
+        Ok(CallToolResult::success(vec![
+            Content::text(summary),
+            Content::text(serde_json::to_string_pretty(&unwind)?),
+        ]))
+    }
+
+    async fn handle_stack_guard_check(&self, args: Option<serde_json::Map<String, serde_json::Value>>) -> Result<CallToolResult> {
+        let level = args.as_ref().and_then(|a| a.get("level").and_then(|v| v.as_u64()));
+        let preceding_bytes = args.as_ref().and_then(|a| a.get("preceding_bytes").and_then(|v| v.as_u64())).unwrap_or(32);
+        if preceding_bytes > 4096 {
+            return Err(ToolError::invalid_argument("preceding_bytes must be at most 4096").into());
+        }
+
+        let mut guard = self.client().write_owned().await;
+        let client = guard.as_mut().ok_or_else(ToolError::no_session)?;
+        let check = client.stack_guard_check(level, preceding_bytes)?;
+
+        let function = check.function.as_deref().unwrap_or("??");
+        let summary = match check.status {
+            "intact" => format!("Stack canary of frame #{} ({}) is intact.", check.level, function),
+            "corrupted" => format!(
+                "Stack canary of frame #{} ({}) was overwritten: {} of its bytes differ from the guard.",
+                check.level,
+                function,
+                check.overwritten.len()
+            ),
+            _ => format!(
+                "No canary found for frame #{} ({}): the prologue stores no guard and the guard value is not in the frame. The function may not be protected.",
+                check.level,
+                function
+            ),
+        };
+        Ok(CallToolResult::success(vec![
+            Content::text(summary),
+            Content::text(serde_json::to_string_pretty(&check)?),
+        ]))
+    }
+
+    async fn handle_thread_list(&self) -> Result<CallToolResult> {
+        let mut guard = self.client().write_owned().await;
+        let client = guard.as_mut().ok_or_else(ToolError::no_session)?;
+        let threads = client.thread_list_ids()?;
+        Ok(CallToolResult::text(serde_json::to_string_pretty(&threads)?))
+    }
+
+    async fn handle_thread_select(&self, args: Option<serde_json::Map<String, serde_json::Value>>) -> Result<CallToolResult> {
+        let thread_id = args.as_ref()
+            .and_then(|a| a.get("thread_id").and_then(|v| v.as_str()).map(|s| s.to_string()))
+            .ok_or_else(|| ToolError::missing_argument("thread_id"))?;
+
+        let mut guard = self.client().write_owned().await;
+        let client = guard.as_mut().ok_or_else(ToolError::no_session)?;
+        client.thread_select(&thread_id)?;
+        client.record_history(format!("thread {}", thread_id));
+        Ok(CallToolResult::text(format!("Selected thread {}.", thread_id)))
+    }
+
+    async fn handle_smp_state(&self, args: Option<serde_json::Map<String, serde_json::Value>>) -> Result<CallToolResult> {
+        let strings = |key: &str| args.as_ref().and_then(|a| a.get(key).and_then(|v| v.as_array())).map(|items| {
+            items.iter().filter_map(|v| v.as_str().map(|s| s.trim_start_matches('$').to_string())).collect::<Vec<_>>()
+        });
+        let frames = args.as_ref().and_then(|a| a.get("frames").and_then(|v| v.as_u64())).unwrap_or(3);
+        if frames > 32 {
+            return Err(ToolError::invalid_argument("frames must be at most 32").into());
+        }
+        let threads = strings("threads");
+
+        let mut guard = self.client().write_owned().await;
+        let client = guard.as_mut().ok_or_else(ToolError::no_session)?;
+        let registers = match strings("registers") {
+            Some(registers) => registers,
+            None => default_registers(client.instruction_set(None).ok()).into_iter().map(|r| r.to_string()).collect(),
+        };
+        let state = client.smp_state(&registers, frames, threads.as_deref())?;
+
+        let mut summary = format!("{} core(s).", state.cores.len());
+        if let Some(thread) = &state.pinned_thread {
+            summary.push_str(&format!(" Run control is pinned to thread {} (scheduler-locking {}).", thread, state.scheduler_locking.as_deref().unwrap_or("?")));
+        }
+        Ok(CallToolResult::success(vec![
+            Content::text(format!("{}\n{}", summary, state.render())),
+            Content::text(serde_json::to_string_pretty(&state)?),
+        ]))
+    }
+
+    async fn handle_smp_pin(&self, args: Option<serde_json::Map<String, serde_json::Value>>) -> Result<CallToolResult> {
+        let thread_id = args.as_ref().and_then(|a| a.get("thread_id")).and_then(|v| {
+            v.as_str().map(|s| s.to_string()).or_else(|| v.as_u64().map(|n| n.to_string()))
+        });
+        let mode = args.as_ref().and_then(|a| a.get("mode").and_then(|v| v.as_str())).unwrap_or("on").to_string();
+        if !matches!(mode.as_str(), "on" | "step" | "off") {
+            return Err(ToolError::invalid_argument("mode must be one of: on, step, off").into());
+        }
+
+        let mut guard = self.client().write_owned().await;
+        let client = guard.as_mut().ok_or_else(ToolError::no_session)?;
+        let pinned = client.smp_pin(thread_id.as_deref(), &mode)?;
+        client.record_history(format!("set scheduler-locking {}", mode));
+
+        let message = match (mode.as_str(), pinned) {
+            ("off", _) => "Unpinned: all cores run when the target is resumed.".to_string(),
+            ("step", Some(thread)) => format!("Pinned stepping to thread {}; continuing still runs all cores.", thread),
+            (_, Some(thread)) => format!("Pinned run control to thread {}; the other cores stay halted when continuing or stepping.", thread),
+            (_, None) => format!("Set scheduler-locking {}, but no thread is selected.", mode),
+        };
+        Ok(CallToolResult::text(message))
+    }
+
+    async fn handle_trace_context_switch(&self, args: Option<serde_json::Map<String, serde_json::Value>>) -> Result<CallToolResult> {
+        let get_str = |name: &str| args.as_ref().and_then(|a| a.get(name).and_then(|v| v.as_str()).map(|s| s.to_string()));
+        let max_switches = args.as_ref().and_then(|a| a.get("max_switches").and_then(|v| v.as_u64())).unwrap_or(50);
+        if max_switches == 0 || max_switches > MAX_SWITCHES {
+            return Err(ToolError::invalid_argument(format!("max_switches must be between 1 and {}", MAX_SWITCHES)).into());
+        }
+        let max_runtime_ms = args.as_ref().and_then(|a| a.get("max_runtime_ms").and_then(|v| v.as_u64())).unwrap_or(30000);
+        let task_type = get_str("task_type");
+        let fields: Vec<String> = match args.as_ref().and_then(|a| a.get("fields").and_then(|v| v.as_array())) {
+            Some(fields) => fields.iter().filter_map(|f| f.as_str().map(|s| s.to_string())).collect(),
+            None if task_type.is_some() => DEFAULT_TASK_FIELDS.iter().map(|f| f.to_string()).collect(),
+            None => Vec::new(),
+        };
+        let spec = SwitchTraceSpec {
+            symbol: get_str("symbol").unwrap_or_else(|| DEFAULT_SWITCH_SYMBOL.to_string()),
+            prev: get_str("prev"),
+            next: get_str("next"),
+            task_type,
+            fields,
+        };
+
+        let mut guard = self.client().write_owned().await;
+        let client = guard.as_mut().ok_or_else(ToolError::no_session)?;
+        self.check_breakpoint_limit(client, 1)?;
+        let trace = client.trace_context_switches(&spec, max_switches, max_runtime_ms)?;
+        client.record_history(format!("trace context switches at {} ({} logged)", spec.symbol, trace.switches.len()));
+
+        let ending = match trace.stopped_because.as_str() {
+            "completed" => "reached max_switches".to_string(),
+            "timeout" => format!("timed out after {} ms", max_runtime_ms),
+            reason => format!("stopped: {}", reason),
+        };
+        let mut summary = format!("Logged {} context switch(es) at {} ({}).", trace.switches.len(), trace.symbol, ending);
+        if !trace.switches.is_empty() {
+            summary.push_str(&format!("\n{}", trace.render()));
+        }
+        Ok(CallToolResult::success(vec![
+            Content::text(summary),
+            Content::text(serde_json::to_string_pretty(&trace)?),
+        ]))
+    }
+
+    async fn handle_memory_read(&self, args: Option<serde_json::Map<String, serde_json::Value>>) -> Result<CallToolResult> {
+        let address = args.as_ref()
+            .and_then(|a| a.get("address").and_then(|v| v.as_str()).map(|s| s.to_string()))
+            .ok_or_else(|| ToolError::missing_argument("address"))?;
+        let count = args.and_then(|a| a.get("count").and_then(|v| v.as_u64())).unwrap_or(16);
+
+        let mut guard = self.client().write_owned().await;
+        let client = guard.as_mut().ok_or_else(ToolError::no_session)?;
+        let mem = client.data_read_memory(&address, count)?;
+        Ok(CallToolResult::text(serde_json::to_string_pretty(&mem)?))
+    }
+
+    async fn handle_memory_visualize(&self, args: Option<serde_json::Map<String, serde_json::Value>>) -> Result<CallToolResult> {
+        let address = args.as_ref()
+            .and_then(|a| a.get("address").and_then(|v| v.as_str()).map(|s| s.to_string()))
+            .ok_or_else(|| ToolError::missing_argument("address"))?;
+        let width = args.as_ref().and_then(|a| a.get("width").and_then(|v| v.as_u64()))
+            .ok_or_else(|| ToolError::missing_argument("width"))?;
+        let height = args.as_ref().and_then(|a| a.get("height").and_then(|v| v.as_u64()))
+            .ok_or_else(|| ToolError::missing_argument("height"))?;
+        let format_name = args.as_ref().and_then(|a| a.get("format").and_then(|v| v.as_str())).unwrap_or("rgb565");
+        let format = PixelFormat::parse(format_name).ok_or_else(|| ToolError::invalid_argument(format!(
+            "Unknown pixel format '{}'; expected one of {}",
+            format_name,
+            image::PIXEL_FORMATS.join(", ")
+        )))?;
+        let stride = args.as_ref().and_then(|a| a.get("stride").and_then(|v| v.as_u64())).unwrap_or(format.row_bytes(width));
+        if width == 0 || height == 0 || width * height > image::MAX_PIXELS {
+            return Err(ToolError::invalid_argument(format!("width x height must be between 1 and {} pixels", image::MAX_PIXELS)).into());
+        }
+        if stride < format.row_bytes(width) {
+            return Err(ToolError::invalid_argument(format!("stride must be at least {} bytes for {} {} pixels", format.row_bytes(width), width, format_name)).into());
+        }
+
+        let mut guard = self.client().write_owned().await;
+        let client = guard.as_mut().ok_or_else(ToolError::no_session)?;
+        let length = stride * (height - 1) + format.row_bytes(width);
+        let mem = client.data_read_memory(&address, length)?;
+        let bytes = parse_hex_bytes(&mem.data.concat());
+        let rgb = image::decode_pixels(&bytes, format, width, height, stride)?;
+        let png = image::encode_png(width, height, &rgb)?;
+
+        Ok(CallToolResult::success(vec![
+            Content::text(format!("{}x{} {} image at {} ({} bytes, stride {})", width, height, format_name, mem.addr, length, stride)),
+            Content::Image { data: png, mime_type: "image/png".to_string() },
+        ]))
+    }
+
+    async fn handle_array_plot(&self, args: Option<serde_json::Map<String, serde_json::Value>>) -> Result<CallToolResult> {
+        let get_u64 = |key: &str| args.as_ref().and_then(|a| a.get(key).and_then(|v| v.as_u64()));
+        let address = args.as_ref()
+            .and_then(|a| a.get("address").and_then(|v| v.as_str()).map(|s| s.to_string()))
+            .ok_or_else(|| ToolError::missing_argument("address"))?;
+        let count = get_u64("count").ok_or_else(|| ToolError::missing_argument("count"))?;
+        let type_name = args.as_ref()
+            .and_then(|a| a.get("element_type").and_then(|v| v.as_str()))
+            .ok_or_else(|| ToolError::missing_argument("element_type"))?;
+        let element = ElementType::parse(type_name).ok_or_else(|| ToolError::invalid_argument(format!(
+            "Unknown element_type '{}'; expected one of {}",
+            type_name,
+            plot::ELEMENT_TYPES.join(", ")
+        )))?;
+        let big_endian = args.as_ref().and_then(|a| a.get("big_endian").and_then(|v| v.as_bool())).unwrap_or(false);
+        let histogram = args.as_ref().and_then(|a| a.get("chart").and_then(|v| v.as_str())) == Some("histogram");
+        let bins = get_u64("bins").unwrap_or(32) as usize;
+        let width = get_u64("width").unwrap_or(512) as usize;
+        let height = get_u64("height").unwrap_or(256) as usize;
+        if count == 0 || count > MAX_PLOT_SAMPLES {
+            return Err(ToolError::invalid_argument(format!("count must be between 1 and {}", MAX_PLOT_SAMPLES)).into());
+        }
+        if !(16..=2048).contains(&width) || !(16..=2048).contains(&height) || bins == 0 {
+            return Err(ToolError::invalid_argument("width and height must be between 16 and 2048, and bins positive").into());
+        }
+
+        let mut guard = self.client().write_owned().await;
+        let client = guard.as_mut().ok_or_else(ToolError::no_session)?;
+        let mem = client.data_read_memory(&address, count * element.size() as u64)?;
+        let samples = plot::decode_samples(&parse_hex_bytes(&mem.data.concat()), element, big_endian);
+        let stats = plot::sample_stats(&samples)?;
+
+        let (rgb, details) = if histogram {
+            let (rgb, counts) = plot::render_histogram(&samples, &stats, bins, width, height);
+            (rgb, serde_json::json!({ "stats": stats, "bins": counts }))
+        } else {
+            (plot::render_line(&samples, &stats, width, height), serde_json::json!({ "stats": stats }))
+        };
+        let png = image::encode_png(width as u64, height as u64, &rgb)?;
+
+        Ok(CallToolResult::success(vec![
+            Content::text(format!(
+                "{} {} sample(s) at {}: min {} (index {}), max {} (index {}), mean {:.6}, std dev {:.6}",
+                samples.len(), type_name, mem.addr, stats.min, stats.min_index, stats.max, stats.max_index, stats.mean, stats.std_dev
+            )),
+            Content::Image { data: png, mime_type: "image/png".to_string() },
+            Content::text(serde_json::to_string_pretty(&details)?),
+        ]))
+    }
+
+    async fn handle_poll_memory(&self, args: Option<serde_json::Map<String, serde_json::Value>>) -> Result<CallToolResult> {
+        let address = args.as_ref()
+            .and_then(|a| a.get("address").and_then(|v| v.as_str()).map(|s| s.to_string()))
+            .ok_or_else(|| ToolError::missing_argument("address"))?;
+        let length = args.as_ref().and_then(|a| a.get("length").and_then(|v| v.as_u64())).unwrap_or(4);
+        let interval_ms = args.as_ref().and_then(|a| a.get("interval_ms").and_then(|v| v.as_u64())).unwrap_or(100);
+        let duration_ms = args.as_ref().and_then(|a| a.get("duration_ms").and_then(|v| v.as_u64())).unwrap_or(2000);
+        let only_changes = args.as_ref().and_then(|a| a.get("only_changes").and_then(|v| v.as_bool())).unwrap_or(false);
+        if interval_ms == 0 || duration_ms > MAX_POLL_DURATION_MS {
+            return Err(ToolError::invalid_argument(format!(
+                "interval_ms must be positive and duration_ms at most {}",
+                MAX_POLL_DURATION_MS
+            )).into());
+        }
+
+        let mut guard = self.client().write_owned().await;
+        let client = guard.as_mut().ok_or_else(ToolError::no_session)?;
+
+        let mut poll = client.poll_memory(
+            &address,
+            length,
+            Duration::from_millis(interval_ms),
+            Duration::from_millis(duration_ms),
+        )?;
+        let total = poll.samples.len();
+        let changes = poll.samples.iter().filter(|s| s.changed).count();
+        if only_changes {
+            let mut first = true;
+            poll.samples.retain(|s| std::mem::take(&mut first) || s.changed);
+        }
+
+        let mut summary = format!("{} sample(s) of {} ({} change(s))", total, address, changes);
+        if let Some(stop) = &poll.stopped {
+            summary.push_str(&format!("; target stopped: {}", stop.reason.as_str()));
+        }
+        Ok(CallToolResult::success(vec![
+            Content::text(summary),
+            Content::text(serde_json::to_string_pretty(&poll)?),
+        ]))
+    }
+
+    async fn handle_memory_write(&self, args: Option<serde_json::Map<String, serde_json::Value>>) -> Result<CallToolResult> {
+        let address = args.as_ref()
             .and_then(|a| a.get("address").and_then(|v| v.as_str()).map(|s| s.to_string()))
-            .ok_or_else(|| anyhow::anyhow!("address is required"))?;
+            .ok_or_else(|| ToolError::missing_argument("address"))?;
         let data = args.as_ref()
             .and_then(|a| a.get("data").and_then(|v| v.as_str()).map(|s| s.to_string()))
-            .ok_or_else(|| anyhow::anyhow!("data is required"))?;
+            .ok_or_else(|| ToolError::missing_argument("data"))?;
+
+        self.confirm(&format!("write {} to memory at {}", data, address)).await?;
+
+        let mut guard = self.client().write_owned().await;
+        let client = guard.as_mut().ok_or_else(ToolError::no_session)?;
+        client.send_command(&format!("data-write-memory-bytes {} {}", address, data))?;
+        client.record_history(format!("interpreter-exec mi \"-data-write-memory-bytes {} {}\"", address, data));
+        Ok(CallToolResult::text(format!("Wrote data to address {}.", address)))
+    }
+
+    async fn handle_patch_code(&self, args: Option<serde_json::Map<String, serde_json::Value>>) -> Result<CallToolResult> {
+        let get_str = |name: &str| args.as_ref().and_then(|a| a.get(name).and_then(|v| v.as_str()));
+        let get_bool = |name: &str| args.as_ref().and_then(|a| a.get(name).and_then(|v| v.as_bool()));
+        let address = get_str("address").ok_or_else(|| ToolError::missing_argument("address"))?.to_string();
+        let nops = args.as_ref().and_then(|a| a.get("nops").and_then(|v| v.as_u64()));
+        let code = match (get_str("instructions").filter(|i| !i.trim().is_empty()), nops) {
+            (Some(_), Some(_)) => return Err(ToolError::invalid_argument("Pass either instructions or nops, not both").into()),
+            (Some(source), None) => PatchCode::Assemble {
+                source: source.to_string(),
+                intel_syntax: get_str("syntax") == Some("intel"),
+            },
+            (None, Some(0)) => return Err(ToolError::invalid_argument("nops must be at least 1").into()),
+            (None, Some(count)) => PatchCode::Nops(count as usize),
+            (None, None) => return Err(ToolError::missing_argument("instructions").into()),
+        };
+        let pad = get_bool("pad").unwrap_or(true);
+
+        self.confirm(&format!("patch code at {}", address)).await?;
+
+        let mut guard = self.client().write_owned().await;
+        let client = guard.as_mut().ok_or_else(ToolError::no_session)?;
+        let patch = client.patch_code(&address, &code, self.assembler.as_deref(), get_bool("thumb"), pad)?;
+
+        let mut summary = format!(
+            "Patch {}: wrote {} byte(s) of {} code at {}",
+            patch.id, patch.size(), serde_json::to_value(patch.arch)?.as_str().unwrap_or("?"), patch.address
+        );
+        if patch.padding > 0 {
+            summary.push_str(&format!(" ({} byte(s) of NOP padding)", patch.padding));
+        }
+        summary.push_str(&format!(". Original bytes: {}. Undo with gdb_patch_revert id={}.", patch.original, patch.id));
+        Ok(CallToolResult::success(vec![
+            Content::text(summary),
+            Content::text(serde_json::to_string_pretty(&patch)?),
+        ]))
+    }
+
+    async fn handle_patch_revert(&self, args: Option<serde_json::Map<String, serde_json::Value>>) -> Result<CallToolResult> {
+        let id = args.as_ref().and_then(|a| a.get("id").and_then(|v| v.as_u64()));
+        let all = args.as_ref().and_then(|a| a.get("all").and_then(|v| v.as_bool())).unwrap_or(false);
+        if all && id.is_some() {
+            return Err(ToolError::invalid_argument("Pass either id or all, not both").into());
+        }
+        let action = match id {
+            Some(id) => format!("revert code patch {}", id),
+            None if all => "revert every code patch".to_string(),
+            None => "revert the latest code patch".to_string(),
+        };
+        self.confirm(&action).await?;
+
+        let mut guard = self.client().write_owned().await;
+        let client = guard.as_mut().ok_or_else(ToolError::no_session)?;
+        if client.patches().is_empty() {
+            return Ok(CallToolResult::text("No code patches are applied."));
+        }
+        let mut reverted = Vec::new();
+        loop {
+            let patch = client.patch_revert(id)?;
+            reverted.push(patch);
+            if !all || client.patches().is_empty() {
+                break;
+            }
+        }
+        let ids: Vec<String> = reverted.iter().map(|p| p.id.to_string()).collect();
+        Ok(CallToolResult::success(vec![
+            Content::text(format!(
+                "Reverted patch(es) {}; {} patch(es) remain applied.",
+                ids.join(", "), client.patches().len()
+            )),
+            Content::text(serde_json::to_string_pretty(&serde_json::json!({
+                "reverted": reverted,
+                "remaining": client.patches(),
+            }))?),
+        ]))
+    }
+
+    async fn handle_memory_snapshot(&self, args: Option<serde_json::Map<String, serde_json::Value>>) -> Result<CallToolResult> {
+        let name = args.as_ref()
+            .and_then(|a| a.get("name").and_then(|v| v.as_str()).map(|s| s.to_string()))
+            .unwrap_or_else(|| "default".to_string());
+
+        let mut regions: Vec<(String, u64)> = args.as_ref()
+            .and_then(|a| a.get("regions").and_then(|v| v.as_array()))
+            .map(|arr| {
+                arr.iter()
+                    .filter_map(|r| {
+                        let address = r.get("address").and_then(|v| v.as_str())?;
+                        let length = r.get("length").and_then(|v| v.as_u64()).unwrap_or(16);
+                        Some((address.to_string(), length))
+                    })
+                    .collect()
+            })
+            .unwrap_or_default();
+        if let Some(address) = args.as_ref().and_then(|a| a.get("address").and_then(|v| v.as_str())) {
+            let length = args.as_ref().and_then(|a| a.get("length").and_then(|v| v.as_u64())).unwrap_or(16);
+            regions.push((address.to_string(), length));
+        }
+        if regions.is_empty() {
+            return Err(ToolError::invalid_argument("Either address or regions must be specified.").into());
+        }
+
+        let mut guard = self.client().write_owned().await;
+        let client = guard.as_mut().ok_or_else(ToolError::no_session)?;
+        let snapshot = client.memory_snapshot(&name, &regions)?;
+
+        let total: usize = snapshot.regions.iter().map(|r| r.bytes.len()).sum();
+        Ok(CallToolResult::text(format!(
+            "Snapshot '{}' captured {} region(s), {} byte(s). Use gdb_memory_diff to compare later.",
+            name, snapshot.regions.len(), total
+        )))
+    }
+
+    async fn handle_memory_diff(&self, args: Option<serde_json::Map<String, serde_json::Value>>) -> Result<CallToolResult> {
+        let name = args.as_ref()
+            .and_then(|a| a.get("name").and_then(|v| v.as_str()).map(|s| s.to_string()))
+            .unwrap_or_else(|| "default".to_string());
+        let update = args.as_ref().and_then(|a| a.get("update").and_then(|v| v.as_bool())).unwrap_or(false);
+        let max_changes = args.as_ref().and_then(|a| a.get("max_changes").and_then(|v| v.as_u64())).unwrap_or(256) as usize;
+
+        let mut guard = self.client().write_owned().await;
+        let client = guard.as_mut().ok_or_else(ToolError::no_session)?;
+        let changes = client.memory_diff(&name, update)?;
+
+        if changes.is_empty() {
+            return Ok(CallToolResult::text(format!("No bytes changed since snapshot '{}'.", name)));
+        }
+
+        let shown: Vec<_> = changes.iter().take(max_changes).collect();
+        Ok(CallToolResult::success(vec![
+            Content::text(format!(
+                "{} byte(s) changed since snapshot '{}'{}.",
+                changes.len(),
+                name,
+                if changes.len() > shown.len() { format!(" (showing first {})", shown.len()) } else { String::new() }
+            )),
+            Content::text(serde_json::to_string_pretty(&shown)?),
+        ]))
+    }
+
+    async fn handle_compare(&self, args: Option<serde_json::Map<String, serde_json::Value>>) -> Result<CallToolResult> {
+        let arg = |name: &str| args.as_ref().and_then(|a| a.get(name).and_then(|v| v.as_str()).map(|s| s.to_string()));
+        let find = |name: &str| self.registry.get(name).ok_or_else(|| ToolError {
+            code: "unknown_session",
+            message: format!("No session named '{}'. Use gdb_session_list to see the available sessions.", name),
+        });
+        let session_a = match arg("session_a") {
+            Some(name) => find(&name)?,
+            None => self.current_session(),
+        };
+        let session_b = find(&arg("session_b").ok_or_else(|| ToolError::missing_argument("session_b"))?)?;
+        if Arc::ptr_eq(&session_a, &session_b) {
+            return Err(ToolError::invalid_argument("session_a and session_b are the same session").into());
+        }
+
+        let what = arg("what").ok_or_else(|| ToolError::missing_argument("what"))?;
+        let target = match what.as_str() {
+            "registers" => CompareTarget::Registers(
+                args.as_ref()
+                    .and_then(|a| a.get("registers").and_then(|v| v.as_array()))
+                    .map(|names| names.iter().filter_map(|n| n.as_str().map(|s| s.to_string())).collect())
+                    .unwrap_or_default(),
+            ),
+            "memory" => {
+                let address = arg("address").ok_or_else(|| ToolError::missing_argument("address"))?;
+                let length = args.as_ref()
+                    .and_then(|a| a.get("length").and_then(|v| v.as_u64()))
+                    .ok_or_else(|| ToolError::missing_argument("length"))?;
+                if length == 0 || length > MAX_COMPARE_BYTES {
+                    return Err(ToolError::invalid_argument(format!("length must be between 1 and {}", MAX_COMPARE_BYTES)).into());
+                }
+                CompareTarget::Memory { address, length }
+            }
+            "expression" => CompareTarget::Expression(arg("expression").ok_or_else(|| ToolError::missing_argument("expression"))?),
+            other => {
+                return Err(ToolError::invalid_argument(format!("Unknown what '{}'; expected registers, memory or expression", other)).into());
+            }
+        };
+
+        // Lock in a fixed order so two opposite comparisons cannot deadlock
+        let (first, second) = if Arc::as_ptr(&session_a) < Arc::as_ptr(&session_b) {
+            (&session_a, &session_b)
+        } else {
+            (&session_b, &session_a)
+        };
+        let first = Arc::clone(&first.client).write_owned().await;
+        let second = Arc::clone(&second.client).write_owned().await;
+        let (guard_a, guard_b) = if Arc::as_ptr(&session_a) < Arc::as_ptr(&session_b) { (first, second) } else { (second, first) };
+
+        let read = |mut guard: tokio::sync::OwnedRwLockWriteGuard<Option<GdbClient>>, target: CompareTarget| {
+            tokio::task::spawn_blocking(move || match guard.as_mut() {
+                Some(client) => target.read(client),
+                None => Err(ToolError::no_session().into()),
+            })
+        };
+        let (a, b) = tokio::join!(read(guard_a, target.clone()), read(guard_b, target));
+        let label = |session: &Session| session.name.clone().unwrap_or_else(|| "current".to_string());
+        let a = a?.map_err(|e| e.context(format!("Session '{}'", label(&session_a))))?;
+        let b = b?.map_err(|e| e.context(format!("Session '{}'", label(&session_b))))?;
+
+        let comparison = compare::compare(a, b);
+        let summary = if comparison.identical() {
+            format!("Sessions '{}' and '{}' have the same {}.", label(&session_a), label(&session_b), what)
+        } else {
+            format!("Sessions '{}' and '{}' differ in {}.", label(&session_a), label(&session_b), what)
+        };
+        Ok(CallToolResult::success(vec![
+            Content::text(summary),
+            Content::text(serde_json::to_string_pretty(&comparison)?),
+        ]))
+    }
+
+    async fn handle_disassemble_function(&self, args: Option<serde_json::Map<String, serde_json::Value>>) -> Result<CallToolResult> {
+        let function = args.as_ref()
+            .and_then(|a| a.get("function").and_then(|v| v.as_str()).map(|s| s.trim_start_matches('*').to_string()))
+            .ok_or_else(|| ToolError::missing_argument("function"))?;
+
+        let mut guard = self.client().write_owned().await;
+        let client = guard.as_mut().ok_or_else(ToolError::no_session)?;
+        let disassembly = client.disassemble_function(&function)?;
+
+        let mut lines = vec![format!("Dump of assembler code for function {}:", disassembly.function)];
+        for insn in &disassembly.instructions {
+            let marker = if disassembly.pc.is_some() && parse_address(&insn.address) == disassembly.pc {
+                "=> "
+            } else {
+                "   "
+            };
+            let offset = insn.offset.map(|o| format!(" <+{}>", o)).unwrap_or_default();
+            let target = insn.target_symbol.as_ref().map(|s| format!(" <{}>", s)).unwrap_or_default();
+            lines.push(format!("{}{}{}:\t{}{}", marker, insn.address, offset, insn.inst, target));
+        }
+        lines.push("End of assembler dump.".to_string());
+
+        Ok(CallToolResult::text(lines.join("\n")))
+    }
+
+    async fn handle_basic_block(&self, args: Option<serde_json::Map<String, serde_json::Value>>) -> Result<CallToolResult> {
+        let location = args.as_ref().and_then(|a| a.get("location").and_then(|v| v.as_str()));
+
+        let mut guard = self.client().write_owned().await;
+        let client = guard.as_mut().ok_or_else(ToolError::no_session)?;
+        let block = client.basic_block(location)?;
+        Ok(CallToolResult::success(vec![
+            Content::text(block.render()),
+            Content::text(serde_json::to_string_pretty(&serde_json::json!({
+                "successors": block.successors,
+                "entered_from": block.entered_from,
+                "returns": block.returns,
+            }))?),
+        ]))
+    }
+
+    async fn handle_source_verify(&self, args: Option<serde_json::Map<String, serde_json::Value>>) -> Result<CallToolResult> {
+        let only_stale = args.as_ref()
+            .and_then(|a| a.get("only_stale").and_then(|v| v.as_bool()))
+            .unwrap_or(false);
+
+        let mut guard = self.client().write_owned().await;
+        let client = guard.as_mut().ok_or_else(ToolError::no_session)?;
+        let mut verification = client.source_verify()?;
+
+        let checked_by_md5 = verification.files.iter().filter(|f| f.verified_by == "md5").count();
+        let mut summary = format!(
+            "{} source file(s) checked ({} by checksum): {} stale.",
+            verification.files.len(),
+            checked_by_md5,
+            verification.stale
+        );
+        for warning in verification.files.iter().filter_map(|f| f.warning()) {
+            summary.push_str(&format!("\nWarning: {}", warning));
+        }
+        if only_stale {
+            verification.files.retain(|f| f.stale());
+        }
+        Ok(CallToolResult::success(vec![
+            Content::text(summary),
+            Content::text(serde_json::to_string_pretty(&verification)?),
+        ]))
+    }
+
+    async fn handle_line_table(&self, args: Option<serde_json::Map<String, serde_json::Value>>) -> Result<CallToolResult> {
+        let get_str = |key: &str| args.as_ref().and_then(|a| a.get(key).and_then(|v| v.as_str()).map(|s| s.to_string()));
+        let function = get_str("function");
+        let file = get_str("file");
+        let location = get_str("location");
+        let line_start = args.as_ref().and_then(|a| a.get("line_start").and_then(|v| v.as_u64())).unwrap_or(0);
+        let line_end = args.as_ref().and_then(|a| a.get("line_end").and_then(|v| v.as_u64())).unwrap_or(u64::MAX);
+
+        let mut guard = self.client().write_owned().await;
+        let client = guard.as_mut().ok_or_else(ToolError::no_session)?;
+
+        if let Some(location) = location {
+            let output = client.info_line(&location)?;
+            return Ok(CallToolResult::text(output.trim_end().to_string()));
+        }
+
+        let mut table = match (function, file) {
+            (Some(function), _) => client.function_line_table(function.trim_start_matches('*'))?,
+            (None, Some(file)) => client.symbol_list_lines(&file)?,
+            (None, None) => return Err(ToolError::missing_argument("One of function, file, or location").into()),
+        };
+        table.retain(|entry| entry.line >= line_start && entry.line <= line_end);
+
+        Ok(CallToolResult::text(serde_json::to_string_pretty(&table)?))
+    }
+
+    async fn handle_line_coverage(&self, args: Option<serde_json::Map<String, serde_json::Value>>) -> Result<CallToolResult> {
+        let get_str = |key: &str| args.as_ref().and_then(|a| a.get(key).and_then(|v| v.as_str()).map(|s| s.to_string()));
+        let function = get_str("function");
+        let file = get_str("file");
+        let line_start = args.as_ref().and_then(|a| a.get("line_start").and_then(|v| v.as_u64())).unwrap_or(0);
+        let line_end = args.as_ref().and_then(|a| a.get("line_end").and_then(|v| v.as_u64())).unwrap_or(u64::MAX);
+        let max_runtime_ms = args.as_ref().and_then(|a| a.get("max_runtime_ms").and_then(|v| v.as_u64())).unwrap_or(60000);
+        let max_lines = args.as_ref().and_then(|a| a.get("max_lines").and_then(|v| v.as_u64())).unwrap_or(500) as usize;
+
+        let mut guard = self.client().write_owned().await;
+        let client = guard.as_mut().ok_or_else(ToolError::no_session)?;
+
+        let mut table = match (function, file) {
+            (Some(function), _) => client.function_line_table(function.trim_start_matches('*'))?,
+            (None, Some(file)) => client.symbol_list_lines(&file)?,
+            (None, None) => return Err(ToolError::missing_argument("function or file").into()),
+        };
+        table.retain(|entry| entry.line >= line_start && entry.line <= line_end);
+        if table.is_empty() {
+            return Err(ToolError::invalid_argument("No executable lines in the selected range").into());
+        }
+        if table.len() > max_lines {
+            return Err(ToolError::invalid_argument(format!(
+                "{} executable lines selected (max_lines is {}); narrow line_start/line_end or raise max_lines",
+                table.len(), max_lines
+            )).into());
+        }
+
+        let coverage = client.line_coverage(&table, max_runtime_ms)?;
+        client.record_history("continue");
+
+        let summary = format!(
+            "Executed {} of {} line(s); run ended: {}.",
+            coverage.hit.len(), coverage.hit.len() + coverage.not_hit.len(), coverage.stopped_because
+        );
+        let mut result = budgeted_run_result(client, coverage.stopped_because == "timeout", max_runtime_ms)?;
+        result.content.insert(0, Content::text(summary));
+        result.content.push(Content::text(serde_json::to_string_pretty(&coverage)?));
+        Ok(result)
+    }
+
+    async fn handle_globals(&self, args: Option<serde_json::Map<String, serde_json::Value>>) -> Result<CallToolResult> {
+        let pattern = args.as_ref().and_then(|a| a.get("pattern").and_then(|v| v.as_str()).map(|s| s.to_string()));
+        let file = args.as_ref().and_then(|a| a.get("file").and_then(|v| v.as_str()).map(|s| s.to_string()));
+        let values = args.as_ref().and_then(|a| a.get("values").and_then(|v| v.as_bool())).unwrap_or(true);
+        let include_nondebug = args.as_ref().and_then(|a| a.get("include_nondebug").and_then(|v| v.as_bool())).unwrap_or(false);
+        let max_results = args.as_ref().and_then(|a| a.get("max_results").and_then(|v| v.as_u64())).unwrap_or(200) as usize;
+
+        let mut guard = self.client().write_owned().await;
+        let client = guard.as_mut().ok_or_else(ToolError::no_session)?;
+        let mut variables = client.variable_symbols(pattern.as_deref(), include_nondebug)?;
+        if let Some(file) = &file {
+            variables.retain(|v| v.file.as_deref().is_some_and(|f| f.contains(file.as_str())));
+        }
+
+        let total = variables.len();
+        variables.truncate(max_results);
+        if values {
+            client.read_globals(&mut variables);
+        }
+        let mut text = serde_json::to_string_pretty(&variables)?;
+        if total > variables.len() {
+            text.push_str(&format!("\n({} of {} variables shown; narrow the pattern or raise max_results)", variables.len(), total));
+        }
+        Ok(CallToolResult::text(text))
+    }
+
+    async fn handle_function_list(&self, args: Option<serde_json::Map<String, serde_json::Value>>) -> Result<CallToolResult> {
+        let pattern = args.as_ref().and_then(|a| a.get("pattern").and_then(|v| v.as_str()).map(|s| s.to_string()));
+        let include_nondebug = args.as_ref().and_then(|a| a.get("include_nondebug").and_then(|v| v.as_bool())).unwrap_or(false);
+        let max_results = args.as_ref().and_then(|a| a.get("max_results").and_then(|v| v.as_u64())).unwrap_or(200) as usize;
+
+        let mut guard = self.client().write_owned().await;
+        let client = guard.as_mut().ok_or_else(ToolError::no_session)?;
+        let mut functions = client.function_symbols(pattern.as_deref(), include_nondebug)?;
+
+        let total = functions.len();
+        functions.truncate(max_results);
+        let mut text = serde_json::to_string_pretty(&functions)?;
+        if total > functions.len() {
+            text.push_str(&format!("\n({} of {} functions shown; narrow the pattern or raise max_results)", functions.len(), total));
+        }
+        Ok(CallToolResult::text(text))
+    }
+
+    async fn handle_evaluate(&self, args: Option<serde_json::Map<String, serde_json::Value>>) -> Result<CallToolResult> {
+        let expression = args.as_ref()
+            .and_then(|a| a.get("expression").and_then(|v| v.as_str()).map(|s| s.to_string()))
+            .ok_or_else(|| ToolError::missing_argument("expression"))?;
+
+        let context = frame_context(args.as_ref());
+        let structured = args.as_ref()
+            .and_then(|a| a.get("structured").and_then(|v| v.as_bool()))
+            .unwrap_or(false);
+
+        let mut guard = self.client().write_owned().await;
+        let client = guard.as_mut().ok_or_else(ToolError::no_session)?;
+        let text = if structured {
+            let typed = client.evaluate_typed(&expression, &context);
+            client.record_evaluation(&expression, &context, typed.as_ref().map(|t| t.value.as_str()).map_err(|e| e.to_string()));
+            serde_json::to_string_pretty(&typed?)?
+        } else {
+            let value = client.data_evaluate_expression_in(&expression, &context);
+            client.record_evaluation(&expression, &context, value.as_deref().map_err(|e| e.to_string()));
+            format!("{} = {}", expression, value?)
+        };
+        let hint = optimization_hint(0, is_optimized_out(Some(&text)) as usize);
+        Ok(CallToolResult::success(std::iter::once(text).chain(hint).map(Content::text).collect()))
+    }
+
+    async fn handle_convenience_set(&self, args: Option<serde_json::Map<String, serde_json::Value>>) -> Result<CallToolResult> {
+        let name = args.as_ref()
+            .and_then(|a| a.get("name").and_then(|v| v.as_str()).map(|s| s.to_string()))
+            .ok_or_else(|| ToolError::missing_argument("name"))?;
+        let expression = args.as_ref()
+            .and_then(|a| a.get("expression").and_then(|v| v.as_str()).map(|s| s.to_string()))
+            .ok_or_else(|| ToolError::missing_argument("expression"))?;
+
+        let mut guard = self.client().write_owned().await;
+        let client = guard.as_mut().ok_or_else(ToolError::no_session)?;
+        let registers = client.data_list_register_names().unwrap_or_default();
+        let name = validate_name(&name, &registers).map_err(ToolError::invalid_argument)?;
+        let value = client.convenience_set(&name, &expression);
+        let context = FrameContext::default();
+        client.record_evaluation(&format!("${} = {}", name, expression), &context, value.as_deref().map_err(|e| e.to_string()));
+        let value = value?;
+        client.record_history(format!("set ${} = {}", name, expression));
+        Ok(CallToolResult::text(format!("${} = {}", name, value)))
+    }
+
+    async fn handle_convenience_get(&self, args: Option<serde_json::Map<String, serde_json::Value>>) -> Result<CallToolResult> {
+        let names: Vec<String> = args.as_ref()
+            .and_then(|a| a.get("names").and_then(|v| v.as_array()))
+            .map(|names| names.iter().filter_map(|n| n.as_str().map(|s| s.to_string())).collect())
+            .unwrap_or_default();
+
+        let mut guard = self.client().write_owned().await;
+        let client = guard.as_mut().ok_or_else(ToolError::no_session)?;
+        let values = client.convenience_get(&names)?;
+        let summary = values.iter()
+            .map(|v| match (&v.value, &v.error) {
+                (_, Some(error)) => format!("${}: {}", v.name, error),
+                (Some(value), None) => format!("${} = {}", v.name, value),
+                (None, None) => format!("${} is void", v.name),
+            })
+            .collect::<Vec<_>>();
+        let summary = if summary.is_empty() { "No convenience variables are defined.".to_string() } else { summary.join("\n") };
+        Ok(CallToolResult::success(vec![
+            Content::text(summary),
+            Content::text(serde_json::to_string_pretty(&values)?),
+        ]))
+    }
+
+    async fn handle_eval_history(&self, args: Option<serde_json::Map<String, serde_json::Value>>) -> Result<CallToolResult> {
+        let get_bool = |name: &str| args.as_ref().and_then(|a| a.get(name).and_then(|v| v.as_bool())).unwrap_or(false);
+        let mut query = EvalQuery {
+            expression: args.as_ref().and_then(|a| a.get("expression").and_then(|v| v.as_str()).map(|s| s.to_string())),
+            generation: args.as_ref().and_then(|a| a.get("generation").and_then(|v| v.as_u64())),
+            values_only: get_bool("values_only"),
+            limit: args.as_ref().and_then(|a| a.get("limit").and_then(|v| v.as_u64())).unwrap_or(20) as usize,
+        };
 
-        let mut guard = self.client.write().await;
-        let client = guard.as_mut().ok_or_else(|| anyhow::anyhow!("GDB session not started"))?;
-        client.send_command(&format!("data-write-memory-bytes {} {}", address, data))?;
-        Ok(CallToolResult::text(format!("Wrote data to address {}.", address)))
+        let mut guard = self.client().write_owned().await;
+        let client = guard.as_mut().ok_or_else(ToolError::no_session)?;
+        if get_bool("current_only") {
+            query.generation = Some(client.state().stop_count);
+        }
+        let (evaluations, generation, total) = client.eval_history(&query);
+        if get_bool("clear") {
+            client.clear_eval_history();
+        }
+
+        let lines: Vec<String> = evaluations.iter()
+            .map(|view| {
+                let e = &view.evaluation;
+                let result = match (&e.value, &e.error) {
+                    (Some(value), _) => format!("= {}", value),
+                    (None, error) => format!("failed: {}", error.as_deref().unwrap_or("unknown error")),
+                };
+                let age = if view.current { "current".to_string() } else { format!("stop {}", e.generation) };
+                format!("#{} [{}] {} {}", e.seq, age, e.expression, result)
+            })
+            .collect();
+        let summary = format!(
+            "{} of {} evaluation(s); the current stop generation is {}.{}{}",
+            evaluations.len(),
+            total,
+            generation,
+            if lines.is_empty() { "" } else { "\n" },
+            lines.join("\n")
+        );
+        Ok(CallToolResult::success(vec![
+            Content::text(summary),
+            Content::text(serde_json::to_string_pretty(&evaluations)?),
+        ]))
     }
 
-    async fn handle_evaluate(&self, args: Option<serde_json::Map<String, serde_json::Value>>) -> Result<CallToolResult> {
+    async fn handle_tls_read(&self, args: Option<serde_json::Map<String, serde_json::Value>>) -> Result<CallToolResult> {
         let expression = args.as_ref()
             .and_then(|a| a.get("expression").and_then(|v| v.as_str()).map(|s| s.to_string()))
-            .ok_or_else(|| anyhow::anyhow!("expression is required"))?;
+            .ok_or_else(|| ToolError::missing_argument("expression"))?;
+        let threads = args.as_ref()
+            .and_then(|a| a.get("threads").and_then(|v| v.as_array()))
+            .map(|ids| ids.iter().filter_map(|id| id.as_str().map(|s| s.to_string())).collect::<Vec<_>>());
+
+        let mut guard = self.client().write_owned().await;
+        let client = guard.as_mut().ok_or_else(ToolError::no_session)?;
+        let values = client.tls_read(&expression, threads)?;
+
+        let distinct: std::collections::HashSet<&str> = values.iter().filter_map(|v| v.value.as_deref()).collect();
+        let failed = values.iter().filter(|v| v.error.is_some()).count();
+        let mut summary = format!("{} in {} thread(s): {} distinct value(s)", expression, values.len(), distinct.len());
+        if failed > 0 {
+            summary.push_str(&format!(", {} thread(s) failed", failed));
+        }
+        summary.push('.');
+        Ok(CallToolResult::success(vec![
+            Content::text(summary),
+            Content::text(serde_json::to_string_pretty(&values)?),
+        ]))
+    }
+
+    async fn handle_python_helper(&self, args: Option<serde_json::Map<String, serde_json::Value>>) -> Result<CallToolResult> {
+        let command = args.as_ref()
+            .and_then(|a| a.get("command").and_then(|v| v.as_str()).map(|s| s.to_string()))
+            .ok_or_else(|| ToolError::missing_argument("command"))?;
+        let arguments = args.as_ref()
+            .and_then(|a| a.get("arguments").and_then(|v| v.as_str()))
+            .unwrap_or("")
+            .to_string();
 
-        let mut guard = self.client.write().await;
-        let client = guard.as_mut().ok_or_else(|| anyhow::anyhow!("GDB session not started"))?;
-        let value = client.data_evaluate_expression(&expression)?;
-        Ok(CallToolResult::text(format!("{} = {}", expression, value)))
+        let mut guard = self.client().write_owned().await;
+        let client = guard.as_mut().ok_or_else(ToolError::no_session)?;
+        let value = client.python_helper(&command, &arguments)?;
+        Ok(CallToolResult::text(serde_json::to_string_pretty(&value)?))
     }
 
     async fn handle_registers_list(&self) -> Result<CallToolResult> {
-        let mut guard = self.client.write().await;
-        let client = guard.as_mut().ok_or_else(|| anyhow::anyhow!("GDB session not started"))?;
+        let mut guard = self.client().write_owned().await;
+        let client = guard.as_mut().ok_or_else(ToolError::no_session)?;
         
         // Get register names
         let names = client.data_list_register_names()?;
@@ -539,58 +3604,901 @@ impl GdbMcpServer {
         Ok(CallToolResult::text(serde_json::to_string_pretty(&registers)?))
     }
 
+    async fn handle_registers_profile(&self, args: Option<serde_json::Map<String, serde_json::Value>>) -> Result<CallToolResult> {
+        let name = args.as_ref()
+            .and_then(|a| a.get("profile").and_then(|v| v.as_str()).map(|s| s.to_string()))
+            .ok_or_else(|| ToolError::missing_argument("profile"))?;
+        let profile = registers::profile(&name)
+            .ok_or_else(|| ToolError::invalid_argument(format!("Unknown register profile '{}'", name)))?;
+
+        let mut guard = self.client().write_owned().await;
+        let client = guard.as_mut().ok_or_else(ToolError::no_session)?;
+
+        let registers = client.read_registers(profile.registers);
+        Ok(CallToolResult::text(serde_json::to_string_pretty(&registers)?))
+    }
+
+    async fn handle_riscv_csrs(&self, args: Option<serde_json::Map<String, serde_json::Value>>) -> Result<CallToolResult> {
+        let names: Vec<String> = match args.as_ref().and_then(|a| a.get("csrs")).and_then(|v| v.as_array()) {
+            Some(csrs) => csrs.iter().filter_map(|c| c.as_str().map(|s| s.to_string())).collect(),
+            None => registers::DEFAULT_RISCV_CSRS.iter().map(|s| s.to_string()).collect(),
+        };
+        let specs: Vec<_> = names.iter()
+            .map(|name| {
+                registers::RISCV_CSRS.iter()
+                    .find(|c| c.name == name)
+                    .copied()
+                    .ok_or_else(|| ToolError::invalid_argument(format!("Unknown CSR '{}'", name)))
+            })
+            .collect::<Result<_, _>>()?;
+
+        let mut guard = self.client().write_owned().await;
+        let client = guard.as_mut().ok_or_else(ToolError::no_session)?;
+
+        let csrs = client.read_registers(&specs);
+        Ok(CallToolResult::text(serde_json::to_string_pretty(&csrs)?))
+    }
+
+    async fn handle_x86_descriptors(&self, args: Option<serde_json::Map<String, serde_json::Value>>) -> Result<CallToolResult> {
+        let table = match args.as_ref().and_then(|a| a.get("table").and_then(|v| v.as_str())) {
+            Some("gdt") => DescriptorTable::Gdt,
+            Some("idt") => DescriptorTable::Idt,
+            Some(_) => return Err(ToolError::invalid_argument("table must be 'gdt' or 'idt'").into()),
+            None => return Err(ToolError::missing_argument("table").into()),
+        };
+        let base = match args.as_ref().and_then(|a| a.get("base").and_then(|v| v.as_str())) {
+            Some(base) => Some(parse_address(base).ok_or_else(|| ToolError::invalid_argument(format!("Invalid base address '{}'", base)))?),
+            None => None,
+        };
+        let limit = args.as_ref().and_then(|a| a.get("limit").and_then(|v| v.as_u64()));
+        let max_entries = args.as_ref()
+            .and_then(|a| a.get("max_entries").and_then(|v| v.as_u64()))
+            .unwrap_or(256);
+
+        let mut guard = self.client().write_owned().await;
+        let client = guard.as_mut().ok_or_else(ToolError::no_session)?;
+
+        let dump = client.x86_descriptor_table(table, base, limit, max_entries)?;
+        Ok(CallToolResult::text(serde_json::to_string_pretty(&dump)?))
+    }
+
+    async fn handle_x86_page_walk(&self, args: Option<serde_json::Map<String, serde_json::Value>>) -> Result<CallToolResult> {
+        let address = args.as_ref()
+            .and_then(|a| a.get("address").and_then(|v| v.as_str()).map(|s| s.to_string()))
+            .ok_or_else(|| ToolError::missing_argument("address"))?;
+        let cr3 = args.as_ref().and_then(|a| a.get("cr3").and_then(|v| v.as_str()).map(|s| s.to_string()));
+
+        let mut guard = self.client().write_owned().await;
+        let client = guard.as_mut().ok_or_else(ToolError::no_session)?;
+
+        let mut resolve = |expr: &str| match parse_address(expr) {
+            Some(value) => Ok(value),
+            None => client.evaluate_u64(&format!("(unsigned long long)({})", expr)),
+        };
+        let vaddr = resolve(&address)?;
+        let cr3 = cr3.as_deref().map(&mut resolve).transpose()?;
+
+        let walk = client.x86_page_walk(vaddr, cr3)?;
+        Ok(CallToolResult::text(serde_json::to_string_pretty(&walk)?))
+    }
+
+    async fn handle_qemu_monitor(&self, args: Option<serde_json::Map<String, serde_json::Value>>) -> Result<CallToolResult> {
+        let get_str = |key: &str| args.as_ref().and_then(|a| a.get(key).and_then(|v| v.as_str()).map(|s| s.to_string()));
+        let command = get_str("command");
+        let action = get_str("action").unwrap_or_else(|| "command".to_string());
+        let name = || get_str("name").ok_or_else(|| ToolError::missing_argument("name"));
+        if name().is_ok_and(|n| n.is_empty() || n.contains(char::is_whitespace)) {
+            return Err(ToolError::invalid_argument("name must be a single word").into());
+        }
+        match action.as_str() {
+            "command" => {
+                let command = command.as_deref().ok_or_else(|| ToolError::missing_argument("command"))?;
+                if !qemu::is_read_only(command) {
+                    self.confirm(&format!("run the QEMU monitor command '{}'", command)).await?;
+                }
+            }
+            "loadvm" => self.confirm(&format!("restore the VM snapshot '{}'", name()?)).await?,
+            "delvm" => self.confirm(&format!("delete the VM snapshot '{}'", name()?)).await?,
+            "savevm" | "snapshots" => {}
+            _ => return Err(ToolError::invalid_argument("action must be one of: command, savevm, loadvm, delvm, snapshots").into()),
+        }
+
+        let mut guard = self.client().write_owned().await;
+        let client = guard.as_mut().ok_or_else(ToolError::no_session)?;
+        let qmp = get_str("qmp").or(client.state().qmp);
+        let qmp = qmp.as_deref();
+
+        match action.as_str() {
+            "savevm" => {
+                let mark = client.vm_snapshot_save(&name()?, qmp)?;
+                client.record_history(format!("monitor savevm {}", mark.name));
+                let at = mark.frame.as_ref()
+                    .map(|f| format!(" at PC={} in {}", f.addr, f.func.as_deref().unwrap_or("??")))
+                    .unwrap_or_default();
+                Ok(CallToolResult::success(vec![
+                    Content::text(format!("Saved VM snapshot '{}'{}. Restore it with action=loadvm.", mark.name, at)),
+                    Content::text(serde_json::to_string_pretty(&mark)?),
+                ]))
+            }
+            "loadvm" => {
+                let name = name()?;
+                let (frame, mark) = client.vm_snapshot_load(&name, qmp)?;
+                client.record_history(format!("monitor loadvm {}", name));
+                let mut summary = format!("Restored VM snapshot '{}'; GDB's register cache was flushed.", name);
+                if let Some(frame) = &frame {
+                    summary.push_str(&format!(" Now at PC={} in {}.", frame.addr, frame.func.as_deref().unwrap_or("??")));
+                }
+                if let Some(mark) = &mark {
+                    let current: HashSet<String> = client.break_list()?.into_iter().map(|b| b.number).collect();
+                    let missing: Vec<&str> = mark.breakpoints.iter().filter(|b| !current.contains(*b)).map(|b| b.as_str()).collect();
+                    if !missing.is_empty() {
+                        summary.push_str(&format!(
+                            " Breakpoint(s) {} existed when the snapshot was saved but have been deleted since.",
+                            missing.join(", ")
+                        ));
+                    }
+                }
+                Ok(CallToolResult::success(vec![
+                    Content::text(summary),
+                    Content::text(serde_json::to_string_pretty(&serde_json::json!({
+                        "name": name,
+                        "frame": frame,
+                        "saved": mark,
+                    }))?),
+                ]))
+            }
+            "delvm" => {
+                let name = name()?;
+                client.vm_snapshot_delete(&name, qmp)?;
+                Ok(CallToolResult::text(format!("Deleted VM snapshot '{}'.", name)))
+            }
+            "snapshots" => {
+                let (snapshots, marks) = client.vm_snapshots(qmp)?;
+                Ok(CallToolResult::success(vec![
+                    Content::text(format!("{} VM snapshot(s), {} saved in this session.", snapshots.len(), marks.len())),
+                    Content::text(serde_json::to_string_pretty(&serde_json::json!({
+                        "snapshots": snapshots,
+                        "session": marks,
+                    }))?),
+                ]))
+            }
+            _ => {
+                let command = command.unwrap_or_default();
+                let output = client.qemu_monitor(&command, qmp)?;
+                client.record_history(format!("monitor {}", command));
+                Ok(CallToolResult::text(output.trim_end().to_string()))
+            }
+        }
+    }
+
     async fn handle_register_set(&self, args: Option<serde_json::Map<String, serde_json::Value>>) -> Result<CallToolResult> {
         let register = args.as_ref()
             .and_then(|a| a.get("register").and_then(|v| v.as_str()).map(|s| s.to_string()))
-            .ok_or_else(|| anyhow::anyhow!("register is required"))?;
+            .ok_or_else(|| ToolError::missing_argument("register"))?;
         let value = args.as_ref()
             .and_then(|a| a.get("value").and_then(|v| v.as_str()).map(|s| s.to_string()))
-            .ok_or_else(|| anyhow::anyhow!("value is required"))?;
+            .ok_or_else(|| ToolError::missing_argument("value"))?;
+
+        self.confirm(&format!("set register {} to {}", register, value)).await?;
 
-        let mut guard = self.client.write().await;
-        let client = guard.as_mut().ok_or_else(|| anyhow::anyhow!("GDB session not started"))?;
+        let mut guard = self.client().write_owned().await;
+        let client = guard.as_mut().ok_or_else(ToolError::no_session)?;
+        client.require(BackendFeature::GdbMi, "Setting a register")?;
         client.send_command(&format!("gdb-set ${}={}", register, value))?;
+        client.record_history(format!("set var ${} = {}", register, value));
         Ok(CallToolResult::text(format!("Set register {} = {}.", register, value)))
     }
 
     async fn handle_variable_info(&self, args: Option<serde_json::Map<String, serde_json::Value>>) -> Result<CallToolResult> {
         let name = args.as_ref()
             .and_then(|a| a.get("name").and_then(|v| v.as_str()).map(|s| s.to_string()))
-            .ok_or_else(|| anyhow::anyhow!("name is required"))?;
+            .ok_or_else(|| ToolError::missing_argument("name"))?;
 
-        let mut guard = self.client.write().await;
-        let client = guard.as_mut().ok_or_else(|| anyhow::anyhow!("GDB session not started"))?;
+        let context = frame_context(args.as_ref());
+
+        let mut guard = self.client().write_owned().await;
+        let client = guard.as_mut().ok_or_else(ToolError::no_session)?;
         
-        let var = client.var_create(&name, None)?;
-        let value = client.var_evaluate_expression(&name)?;
+        // The variable object is bound to the frame it was created in, so
+        // it is read and deleted without reselecting anything
+        let var = client.var_create(&name, &context, false)?;
+        let value = client.var_evaluate_expression(&var.name);
+        let _ = client.var_delete(&var.name);
+        let value = value?;
         
-        Ok(CallToolResult::success(vec![
+        let mut contents = vec![
             Content::text(format!("{} = {}", name, value)),
             Content::text(serde_json::to_string_pretty(&var)?),
+        ];
+        if var.optimized_out || is_optimized_out(Some(&value)) {
+            contents.extend(optimization_hint(0, 1).map(Content::text));
+        }
+        Ok(CallToolResult::success(contents))
+    }
+
+    async fn handle_decode_struct(&self, args: Option<serde_json::Map<String, serde_json::Value>>) -> Result<CallToolResult> {
+        let address = args.as_ref()
+            .and_then(|a| a.get("address").and_then(|v| v.as_str()).map(|s| s.to_string()))
+            .ok_or_else(|| ToolError::missing_argument("address"))?;
+        let type_name = args.as_ref()
+            .and_then(|a| a.get("type").and_then(|v| v.as_str()).map(|s| s.to_string()))
+            .ok_or_else(|| ToolError::missing_argument("type"))?;
+        let max_depth = args.as_ref().and_then(|a| a.get("max_depth").and_then(|v| v.as_u64())).unwrap_or(4) as usize;
+        let max_elements = args.as_ref().and_then(|a| a.get("max_elements").and_then(|v| v.as_u64())).unwrap_or(32);
+
+        let mut guard = self.client().write_owned().await;
+        let client = guard.as_mut().ok_or_else(ToolError::no_session)?;
+        let decoded = client.decode_struct(&address, &type_name, max_depth, max_elements)?;
+
+        let size = decoded.size.map(|s| format!(", {} bytes", s)).unwrap_or_default();
+        Ok(CallToolResult::success(vec![
+            Content::text(format!("{} at {}{}: {} member(s)", type_name, decoded.address, size, decoded.fields.len())),
+            Content::text(serde_json::to_string_pretty(&decoded)?),
+        ]))
+    }
+
+    async fn handle_walk_list(&self, args: Option<serde_json::Map<String, serde_json::Value>>) -> Result<CallToolResult> {
+        let get_str = |key: &str| args.as_ref().and_then(|a| a.get(key).and_then(|v| v.as_str()).map(|s| s.to_string()));
+        let head = get_str("head").ok_or_else(|| ToolError::missing_argument("head"))?;
+        let container = match (get_str("container_type"), get_str("link_member")) {
+            (Some(container), Some(member)) => Some((container, member)),
+            (None, None) => None,
+            _ => return Err(ToolError::invalid_argument("container_type and link_member must be given together").into()),
+        };
+        let options = ListWalkOptions {
+            next: get_str("next").unwrap_or_else(|| "next".to_string()),
+            fields: args.as_ref()
+                .and_then(|a| a.get("fields").and_then(|v| v.as_array()))
+                .map(|arr| arr.iter().filter_map(|v| v.as_str().map(|s| s.to_string())).collect())
+                .unwrap_or_default(),
+            max_nodes: args.as_ref().and_then(|a| a.get("max_nodes").and_then(|v| v.as_u64())).unwrap_or(100) as usize,
+            container,
+        };
+
+        let mut guard = self.client().write_owned().await;
+        let client = guard.as_mut().ok_or_else(ToolError::no_session)?;
+        let walk = client.walk_list(&head, &options)?;
+
+        let mut summary = format!("Walked {} node(s) from {}; stopped: {}", walk.nodes.len(), head, walk.stopped_because);
+        if let Some(index) = walk.cycle_to {
+            summary.push_str(&format!(" (the last node links back to node {})", index));
+        }
+        if let Some(error) = &walk.error {
+            summary.push_str(&format!(" ({})", error));
+        }
+        Ok(CallToolResult::success(vec![
+            Content::text(summary),
+            Content::text(serde_json::to_string_pretty(&walk)?),
+        ]))
+    }
+
+    async fn handle_var_create(&self, args: Option<serde_json::Map<String, serde_json::Value>>) -> Result<CallToolResult> {
+        let expression = args.as_ref()
+            .and_then(|a| a.get("expression").and_then(|v| v.as_str()).map(|s| s.to_string()))
+            .ok_or_else(|| ToolError::missing_argument("expression"))?;
+        let floating = args.as_ref()
+            .and_then(|a| a.get("floating").and_then(|v| v.as_bool()))
+            .unwrap_or(false);
+        let context = frame_context(args.as_ref());
+
+        let mut guard = self.client().write_owned().await;
+        let client = guard.as_mut().ok_or_else(ToolError::no_session)?;
+        let watch = client.var_watch(&expression, &context, floating)?;
+
+        Ok(CallToolResult::success(vec![
+            Content::text(format!(
+                "Watching {} as {} = {}",
+                expression,
+                watch.name,
+                watch.value.as_deref().unwrap_or("<unavailable>")
+            )),
+            Content::text(serde_json::to_string_pretty(&watch)?),
+        ]))
+    }
+
+    async fn handle_var_update(&self) -> Result<CallToolResult> {
+        let mut guard = self.client().write_owned().await;
+        let client = guard.as_mut().ok_or_else(ToolError::no_session)?;
+        if client.watches().is_empty() {
+            return Ok(CallToolResult::text("No watches. Create one with gdb_var_create."));
+        }
+        let changes = client.var_update()?;
+
+        if changes.is_empty() {
+            return Ok(CallToolResult::text(format!("None of the {} watch(es) changed.", client.watches().len())));
+        }
+        let lines: Vec<String> = changes.iter()
+            .map(|c| {
+                let label = c.expression.as_deref().unwrap_or(&c.name);
+                match c.in_scope.as_str() {
+                    "false" => format!("{} ({}): out of scope", label, c.name),
+                    "invalid" => format!("{} ({}): no longer valid; delete it with gdb_var_delete", label, c.name),
+                    _ => format!("{} ({}) = {}", label, c.name, c.value.as_deref().unwrap_or("<unavailable>")),
+                }
+            })
+            .collect();
+        Ok(CallToolResult::success(vec![
+            Content::text(lines.join("\n")),
+            Content::text(serde_json::to_string_pretty(&changes)?),
+        ]))
+    }
+
+    async fn handle_variable_format(&self, args: Option<serde_json::Map<String, serde_json::Value>>) -> Result<CallToolResult> {
+        let name = args.as_ref()
+            .and_then(|a| a.get("name").and_then(|v| v.as_str()).map(|s| s.to_string()))
+            .ok_or_else(|| ToolError::missing_argument("name"))?;
+        let format = match args.as_ref().and_then(|a| a.get("format").and_then(|v| v.as_str())) {
+            Some("natural") => Some("natural"),
+            Some("hex") => Some("hexadecimal"),
+            Some("dec") => Some("decimal"),
+            Some("bin") => Some("binary"),
+            Some("oct") => Some("octal"),
+            Some("zero-hex") => Some("zero-hexadecimal"),
+            Some(other) => return Err(ToolError::invalid_argument(format!("Unknown format '{}'", other)).into()),
+            None => None,
+        };
+        let frozen = args.as_ref().and_then(|a| a.get("frozen").and_then(|v| v.as_bool()));
+        if format.is_none() && frozen.is_none() {
+            return Err(ToolError::invalid_argument("Either format or frozen must be specified.").into());
+        }
+
+        let mut guard = self.client().write_owned().await;
+        let client = guard.as_mut().ok_or_else(ToolError::no_session)?;
+        if !client.watches().iter().any(|w| w.name == name || w.expression == name) {
+            return Err(ToolError::invalid_argument(format!("No watch named '{}'", name)).into());
+        }
+
+        let mut watch = None;
+        if let Some(format) = format {
+            watch = Some(client.var_set_format(&name, format)?);
+        }
+        if let Some(frozen) = frozen {
+            watch = Some(client.var_set_frozen(&name, frozen)?);
+        }
+        let watch = watch.expect("format or frozen was given");
+
+        Ok(CallToolResult::success(vec![
+            Content::text(format!(
+                "{} ({}) = {}{}",
+                watch.expression,
+                watch.name,
+                watch.value.as_deref().unwrap_or("<unavailable>"),
+                if watch.frozen { " [frozen]" } else { "" }
+            )),
+            Content::text(serde_json::to_string_pretty(&watch)?),
         ]))
     }
 
+    async fn handle_var_delete(&self, args: Option<serde_json::Map<String, serde_json::Value>>) -> Result<CallToolResult> {
+        let name = args.as_ref().and_then(|a| a.get("name").and_then(|v| v.as_str()).map(|s| s.to_string()));
+
+        let mut guard = self.client().write_owned().await;
+        let client = guard.as_mut().ok_or_else(ToolError::no_session)?;
+        let deleted = client.var_unwatch(name.as_deref())?;
+
+        match (&name, deleted.len()) {
+            (Some(name), 0) => Err(ToolError::invalid_argument(format!("No watch named '{}'", name)).into()),
+            (_, n) => Ok(CallToolResult::text(format!("Deleted {} watch(es).", n))),
+        }
+    }
+
     async fn handle_status(&self) -> Result<CallToolResult> {
-        let guard = self.client.read().await;
-        let status = if let Some(client) = guard.as_ref() {
-            client.state()
+        let mut guard = self.client().write_owned().await;
+        let (status, conveniences) = match guard.as_mut() {
+            Some(client) => {
+                let status = client.state();
+                // Convenience values can only be read while the program is stopped
+                let conveniences = (!status.running).then(|| client.convenience_snapshot());
+                (status, conveniences)
+            }
+            None => (GdbSessionState::default(), None),
+        };
+        let mut contents = vec![Content::text(serde_json::to_string_pretty(&status)?)];
+        if let Some(conveniences) = conveniences {
+            contents.push(Content::text(serde_json::to_string_pretty(&serde_json::json!({ "conveniences": conveniences }))?));
+        }
+        for warning in [&status.symbols_warning, &status.endian_warning].into_iter().flatten() {
+            contents.insert(0, Content::text(format!("Warning: {}", warning)));
+        }
+        Ok(CallToolResult::success(contents))
+    }
+
+    async fn handle_endian(&self, args: Option<serde_json::Map<String, serde_json::Value>>) -> Result<CallToolResult> {
+        let set = args.as_ref().and_then(|a| a.get("set").and_then(|v| v.as_str()).map(|s| s.to_string()));
+        if let Some(endian) = set.as_deref().filter(|e| !["little", "big", "auto"].contains(e)) {
+            return Err(ToolError::invalid_argument(format!("set must be little, big or auto, not '{}'", endian)).into());
+        }
+
+        let mut guard = self.client().write_owned().await;
+        let client = guard.as_mut().ok_or_else(ToolError::no_session)?;
+        let check = match set.as_deref() {
+            Some(endian) => client.set_endian(endian)?,
+            None => client.endian_check()?,
+        };
+
+        let summary = match check.warning() {
+            Some(warning) => format!("Warning: {}", warning),
+            None => format!("GDB uses {} endian{}.", check.gdb, if check.automatic { " (automatic)" } else { "" }),
+        };
+        Ok(CallToolResult::success(vec![
+            Content::text(summary),
+            Content::text(serde_json::to_string_pretty(&check)?),
+        ]))
+    }
+
+    async fn handle_diagnostics(&self, args: Option<serde_json::Map<String, serde_json::Value>>) -> Result<CallToolResult> {
+        let severity = match args.as_ref().and_then(|a| a.get("severity")) {
+            Some(v) => serde_json::from_value::<Severity>(v.clone())
+                .map_err(|_| ToolError::invalid_argument("severity must be 'info', 'warning' or 'critical'"))?,
+            None => Severity::Info,
+        };
+        let limit = args.as_ref().and_then(|a| a.get("limit").and_then(|v| v.as_u64())).unwrap_or(50) as usize;
+        let clear = args.as_ref().and_then(|a| a.get("clear").and_then(|v| v.as_bool())).unwrap_or(false);
+
+        // Readable without the client lock, and after GDB has stopped
+        let diagnostics = self.current_session().diagnostics().ok_or_else(ToolError::no_session)?;
+        let mut diagnostics = diagnostics.lock().unwrap();
+        let lines = diagnostics.recent(severity, limit);
+        if clear {
+            diagnostics.clear();
+        }
+
+        let critical = lines.iter().filter(|l| l.severity == Severity::Critical).count();
+        Ok(CallToolResult::success(vec![
+            Content::text(format!("{} line(s), {} critical.", lines.len(), critical)),
+            Content::text(serde_json::to_string_pretty(&lines)?),
+        ]))
+    }
+
+    async fn handle_health(&self, args: Option<serde_json::Map<String, serde_json::Value>>) -> Result<CallToolResult> {
+        let timeout_ms = args.and_then(|a| a.get("timeout_ms").and_then(|v| v.as_u64())).unwrap_or(2000);
+
+        let Ok(mut guard) = tokio::time::timeout(Duration::from_millis(timeout_ms), self.client().write_owned()).await else {
+            return Ok(CallToolResult::error_text("GDB is busy: another tool call is still holding the session."));
+        };
+        let client = guard.as_mut().ok_or_else(ToolError::no_session)?;
+
+        let report = client.health_check(timeout_ms);
+        let summary = if !report.process_alive {
+            "GDB is dead. Restart the session with gdb_stop and gdb_start.".to_string()
+        } else if !report.responsive {
+            "GDB process is alive but not responding.".to_string()
+        } else if report.target_responsive == Some(false) {
+            "GDB is healthy but the remote target is not responding.".to_string()
         } else {
-            GdbSessionState::default()
+            format!("GDB is healthy ({} ms round trip).", report.latency_ms.unwrap_or_default())
         };
-        Ok(CallToolResult::text(serde_json::to_string_pretty(&status)?))
+        let healthy = report.errors.is_empty();
+
+        let contents = vec![
+            Content::text(summary),
+            Content::text(serde_json::to_string_pretty(&report)?),
+        ];
+        Ok(if healthy { CallToolResult::success(contents) } else { CallToolResult::error(contents) })
     }
 
     async fn handle_raw_command(&self, args: Option<serde_json::Map<String, serde_json::Value>>) -> Result<CallToolResult> {
         let command = args.as_ref()
             .and_then(|a| a.get("command").and_then(|v| v.as_str()).map(|s| s.to_string()))
-            .ok_or_else(|| anyhow::anyhow!("command is required"))?;
+            .ok_or_else(|| ToolError::missing_argument("command"))?;
+
+        if is_destructive_command(&command) {
+            self.confirm(&format!("run the command '-{}'", command.trim_start_matches('-'))).await?;
+        }
 
-        let mut guard = self.client.write().await;
-        let client = guard.as_mut().ok_or_else(|| anyhow::anyhow!("GDB session not started"))?;
+        let mut guard = self.client().write_owned().await;
+        let client = guard.as_mut().ok_or_else(ToolError::no_session)?;
         
         let response = client.send_command(&command)?;
+        client.record_history(format!("interpreter-exec mi \"-{}\"", command.replace('"', "\\\"")));
         Ok(CallToolResult::text(format!("{:?}", response)))
     }
+
+    /// Executable whose notes a notes tool works on: its `executable`
+    /// argument, or the current session's loaded file
+    async fn notes_executable(&self, args: Option<&serde_json::Map<String, serde_json::Value>>) -> Result<String> {
+        if let Some(path) = args.and_then(|a| a.get("executable").and_then(|v| v.as_str())) {
+            return self.resolve_path(path).await;
+        }
+        let guard = self.client().read_owned().await;
+        let client = guard.as_ref().ok_or_else(ToolError::no_session)?;
+        client.state().executable.ok_or_else(|| {
+            ToolError::invalid_argument("No executable is loaded. Load one with gdb_load_file or pass executable.").into()
+        })
+    }
+
+    async fn handle_notes_get(&self, args: Option<serde_json::Map<String, serde_json::Value>>) -> Result<CallToolResult> {
+        let store = self.notes.as_ref().ok_or_else(notes::notes_disabled)?;
+        let restore = args.as_ref()
+            .and_then(|a| a.get("restore_breakpoints").and_then(|v| v.as_bool()))
+            .unwrap_or(false);
+        let executable = self.notes_executable(args.as_ref()).await?;
+        let saved = store.load(&notes::notes_key(&executable))?;
+
+        let mut summary = if saved.is_empty() {
+            format!("No notes saved for {}.", executable)
+        } else {
+            format!("Notes for {}: {}.", executable, saved.describe())
+        };
+        if restore && !saved.breakpoints.is_empty() {
+            let mut guard = self.client().write_owned().await;
+            let client = guard.as_mut().ok_or_else(ToolError::no_session)?;
+            self.check_breakpoint_limit(client, saved.breakpoints.len())?;
+
+            let mut restored = Vec::new();
+            let mut failed = Vec::new();
+            for bp in &saved.breakpoints {
+                let options = BreakInsertOptions { condition: bp.condition.clone(), pending: true, ..Default::default() };
+                match client.break_insert(&bp.location, &options) {
+                    Ok(inserted) => {
+                        client.tracker().annotate_breakpoint(&inserted.number, bp.label.as_deref(), bp.note.as_deref());
+                        restored.push(inserted.number);
+                    }
+                    Err(e) => failed.push(format!("{}: {:#}", bp.location, e)),
+                }
+            }
+            summary.push_str(&format!(" Restored {} breakpoint(s)", restored.len()));
+            if !restored.is_empty() {
+                summary.push_str(&format!(" ({})", restored.join(" ")));
+            }
+            summary.push('.');
+            if !failed.is_empty() {
+                summary.push_str(&format!(" Failed to restore:\n- {}", failed.join("\n- ")));
+            }
+        }
+        Ok(CallToolResult::success(vec![
+            Content::text(summary),
+            Content::text(serde_json::to_string_pretty(&saved)?),
+        ]))
+    }
+
+    async fn handle_notes_set(&self, args: Option<serde_json::Map<String, serde_json::Value>>) -> Result<CallToolResult> {
+        let store = self.notes.as_ref().ok_or_else(notes::notes_disabled)?;
+        let strings = |name: &str| args.as_ref()
+            .and_then(|a| a.get(name).and_then(|v| v.as_array()))
+            .map(|v| v.iter().filter_map(|s| s.as_str().map(|s| s.to_string())).collect::<Vec<_>>());
+        let flag = |name: &str| args.as_ref().and_then(|a| a.get(name).and_then(|v| v.as_bool())).unwrap_or(false);
+        let note = args.as_ref().and_then(|a| a.get("note").and_then(|v| v.as_str()).map(|s| s.to_string()));
+        let (notes_list, watch_expressions) = (strings("notes"), strings("watch_expressions"));
+        let (save_breakpoints, clear) = (flag("save_breakpoints"), flag("clear"));
+        if note.is_none() && notes_list.is_none() && watch_expressions.is_none() && !save_breakpoints && !clear {
+            return Err(ToolError::invalid_argument("Pass note, notes, watch_expressions, save_breakpoints or clear").into());
+        }
+
+        let executable = self.notes_executable(args.as_ref()).await?;
+        let mut saved = store.load(&notes::notes_key(&executable))?;
+        if clear {
+            saved.breakpoints.clear();
+            saved.watch_expressions.clear();
+            saved.notes.clear();
+        }
+        if let Some(list) = notes_list {
+            saved.notes = list;
+        }
+        if let Some(note) = note {
+            saved.notes.push(note);
+        }
+        if let Some(list) = watch_expressions {
+            saved.watch_expressions = list;
+        }
+        if save_breakpoints {
+            let mut guard = self.client().write_owned().await;
+            let client = guard.as_mut().ok_or_else(ToolError::no_session)?;
+            client.break_list()?;
+            saved.breakpoints = client.tracker().breakpoints()
+                .into_iter()
+                .filter(|b| b.breakpoint.breakpoint_type.contains("breakpoint"))
+                .filter_map(|b| Some(SavedBreakpoint {
+                    // A location resolved only to an address is set again with `*`
+                    location: b.requested_location.or(match b.actual_location {
+                        Some(location) if b.breakpoint.addr.as_ref() != Some(&location) => Some(location),
+                        _ => b.breakpoint.addr.map(|addr| format!("*{}", addr)),
+                    })?,
+                    condition: b.breakpoint.condition,
+                    label: b.label,
+                    note: b.note,
+                }))
+                .collect();
+        }
+        saved.executable = Some(executable.clone());
+        let path = store.save(&mut saved)?;
+
+        Ok(CallToolResult::success(vec![
+            Content::text(format!("Saved notes for {} to {}: {}.", executable, path.display(), saved.describe())),
+            Content::text(serde_json::to_string_pretty(&saved)?),
+        ]))
+    }
+
+    async fn handle_export_script(&self, args: Option<serde_json::Map<String, serde_json::Value>>) -> Result<CallToolResult> {
+        let include_history = args.as_ref()
+            .and_then(|a| a.get("include_history").and_then(|v| v.as_bool()))
+            .unwrap_or(true);
+        let output_path = match args.as_ref().and_then(|a| a.get("output_path").and_then(|v| v.as_str())) {
+            Some(path) => Some(self.resolve_path(path).await?),
+            None => None,
+        };
+
+        let mut guard = self.client().write_owned().await;
+        let client = guard.as_mut().ok_or_else(ToolError::no_session)?;
+        let script = client.export_script(include_history)?;
+
+        if let Some(path) = output_path {
+            std::fs::write(&path, &script)?;
+            Ok(CallToolResult::success(vec![
+                Content::text(format!("Session script written to {}. Run it with: gdb -x {}", path, path)),
+                Content::text(script),
+            ]))
+        } else {
+            Ok(CallToolResult::text(script))
+        }
+    }
+
+    async fn handle_audit_log(&self, args: Option<serde_json::Map<String, serde_json::Value>>) -> Result<CallToolResult> {
+        let limit = args.as_ref()
+            .and_then(|a| a.get("limit").and_then(|v| v.as_u64()))
+            .unwrap_or(20) as usize;
+        let tool = args.as_ref()
+            .and_then(|a| a.get("tool").and_then(|v| v.as_str()).map(|s| s.to_string()));
+
+        let entries = self.audit.lock().unwrap().recent(limit, tool.as_deref());
+        Ok(CallToolResult::text(serde_json::to_string_pretty(&entries)?))
+    }
+
+    async fn handle_record_session_start(&self, args: Option<serde_json::Map<String, serde_json::Value>>) -> Result<CallToolResult> {
+        let path = args.as_ref()
+            .and_then(|a| a.get("path").and_then(|v| v.as_str()))
+            .ok_or_else(|| ToolError::missing_argument("path"))?;
+        let path = self.resolve_path(path).await?;
+
+        let mut recording = self.recording.lock().unwrap();
+        if let Some(active) = recording.as_ref() {
+            return Err(ToolError::invalid_argument(format!(
+                "Already recording to {}; stop it with gdb_record_session_stop first",
+                active.path.display()
+            )).into());
+        }
+        *recording = Some(Recording::new(PathBuf::from(&path)));
+        Ok(CallToolResult::text(format!(
+            "Recording tool calls to {}. Stop with gdb_record_session_stop, then check it with: mcp-gdb-server --replay {}",
+            path, path
+        )))
+    }
+
+    async fn handle_record_session_stop(&self) -> Result<CallToolResult> {
+        let recording = self.recording.lock().unwrap().take()
+            .ok_or_else(|| ToolError::invalid_argument("No recording in progress; start one with gdb_record_session_start"))?;
+        recording.save()?;
+        Ok(CallToolResult::text(format!("Recorded {} tool calls to {}.", recording.len(), recording.path.display())))
+    }
+}
+
+/// Describe where a run with a wall-clock budget ended up
+/// Apply the `label` and `note` arguments of an insert tool to the new
+/// breakpoint, returning " [label]" for its summary line
+fn annotate_new_breakpoint(client: &mut GdbClient, number: &str, args: Option<&serde_json::Map<String, serde_json::Value>>) -> String {
+    let label = args.and_then(|a| a.get("label").and_then(|v| v.as_str()));
+    let note = args.and_then(|a| a.get("note").and_then(|v| v.as_str()));
+    let mut tracker = client.tracker();
+    tracker.annotate_breakpoint(number, label, note);
+    tracker.breakpoint(number).and_then(|b| b.label).map(|l| format!(" [{}]", l)).unwrap_or_default()
+}
+
+fn budgeted_run_result(client: &mut GdbClient, interrupted: bool, max_runtime_ms: u64) -> Result<CallToolResult> {
+    let stop = client.last_stop();
+    let frame = stop.as_ref().and_then(|s| s.frame.as_ref());
+
+    let mut location = match frame {
+        Some(frame) => format!("PC={} in {}", frame.addr, frame.func.as_deref().unwrap_or("??")),
+        None => "unknown location".to_string(),
+    };
+    if let Some((file, line)) = frame.and_then(|f| Some((f.file.as_ref()?, f.line?))) {
+        location.push_str(&format!(" at {}:{}", file, line));
+    }
+
+    let summary = if interrupted {
+        format!("Timed out after {} ms; interrupted at {}.", max_runtime_ms, location)
+    } else {
+        let mut reason = stop.as_ref().map(|s| s.reason.as_str()).unwrap_or("unknown").to_string();
+        if let Some(signal) = stop.as_ref().and_then(|s| s.signal.as_ref()) {
+            reason.push_str(&format!(": {}", signal.name.as_deref().unwrap_or("unknown signal")));
+            if let Some(cause) = &signal.cause {
+                reason.push_str(&format!(", {}", cause));
+            }
+            if let Some(addr) = &signal.fault_address {
+                reason.push_str(&format!(" at address {}", addr));
+            }
+        }
+        if stop.as_ref().is_some_and(|s| s.reason == StopReason::BreakpointHit) {
+            let tracker = client.tracker();
+            let hit = tracker.breakpoint_hits(None, 1).pop();
+            if let Some(bp) = hit.and_then(|h| tracker.breakpoint(&h.number)) {
+                reason.push_str(&format!(": breakpoint {}, hit {} time(s)", bp.breakpoint.number, bp.breakpoint.times));
+                if let Some(label) = &bp.label {
+                    reason.push_str(&format!(" [{}]", label));
+                }
+            }
+        }
+        format!("Stopped ({}) at {}.", reason, location)
+    };
+
+    let mut contents = vec![Content::text(summary)];
+    if let Some(stop) = &stop {
+        contents.push(Content::text(serde_json::to_string_pretty(stop)?));
+    }
+    Ok(CallToolResult::success(contents))
+}
+
+/// [`budgeted_run_result`] of a sampled run, with the PC histogram when the
+/// target had to be interrupted
+fn sampled_run_result(client: &mut GdbClient, interrupted: bool, max_runtime_ms: u64, samples: &[PcSample], interval_ms: u64) -> Result<CallToolResult> {
+    let mut result = budgeted_run_result(client, interrupted, max_runtime_ms)?;
+    if !interrupted || samples.is_empty() {
+        return Ok(result);
+    }
+    let histogram = PcHistogram::build(samples, interval_ms);
+    let mut summary = format!("PC histogram of {} sample(s), one every {} ms:\n{}", histogram.samples, interval_ms, histogram.render());
+    if let Some(verdict) = &histogram.verdict {
+        summary = format!("The program never stopped and appears to be {}.\n{}", verdict, summary);
+    }
+    result.content.push(Content::text(summary));
+    result.content.push(Content::text(serde_json::to_string_pretty(&serde_json::json!({ "pc_histogram": histogram }))?));
+    Ok(result)
+}
+
+/// Summarize a repeated step, marking the result as an error if a step failed
+fn step_sequence_result(verb: &str, sequence: &StepSequence) -> Result<CallToolResult> {
+    let mut summary = format!("{} {} of {} line(s)", verb, sequence.completed, sequence.requested);
+    match sequence.stopped_because.as_str() {
+        "completed" => {}
+        "error" => summary.push_str(&format!("; stopped by error: {}", sequence.error.as_deref().unwrap_or("unknown"))),
+        "no-stop" => summary.push_str("; stopped early: the last step reported no stop location"),
+        reason => summary.push_str(&format!("; stopped early: {}", reason)),
+    }
+    if let Some(last) = sequence.stops.last() {
+        summary.push_str(&format!(
+            ". Now at {} in {}",
+            last.addr,
+            last.func.as_deref().unwrap_or("??"),
+        ));
+        if let (Some(file), Some(line)) = (&last.file, last.line) {
+            summary.push_str(&format!(" at {}:{}", file, line));
+        }
+    }
+    summary.push('.');
+
+    let contents = vec![
+        Content::text(summary),
+        Content::text(serde_json::to_string_pretty(sequence)?),
+    ];
+    Ok(if sequence.error.is_some() {
+        CallToolResult::error(contents)
+    } else {
+        CallToolResult::success(contents)
+    })
+}
+
+/// MI commands that modify the target
+const DESTRUCTIVE_MI_COMMANDS: &[&str] = &[
+    "data-write-memory",
+    "data-write-memory-bytes",
+    "data-write-register-values",
+    "exec-jump",
+    "exec-return",
+    "target-download",
+    "target-flash-erase",
+];
+
+/// CLI commands (via `interpreter-exec console`) that modify the target
+const DESTRUCTIVE_CLI_COMMANDS: &[&str] = &[
+    "kill", "load", "monitor", "jump", "return", "restore", "set var", "set variable",
+];
+
+/// Whether a raw MI command writes memory, registers or flash, or kills the program
+fn is_destructive_command(command: &str) -> bool {
+    let command = command.trim().trim_start_matches('-');
+    let name = command.split_whitespace().next().unwrap_or("");
+    if DESTRUCTIVE_MI_COMMANDS.contains(&name) {
+        return true;
+    }
+    if name == "interpreter-exec" {
+        let cli = command
+            .split_once("console")
+            .map(|(_, rest)| rest.trim().trim_matches('"').trim())
+            .unwrap_or("");
+        // `set {type} addr = ...` and `set *ptr = ...` write memory too
+        return cli.starts_with("set {")
+            || cli.starts_with("set *")
+            || DESTRUCTIVE_CLI_COMMANDS.iter().any(|c| cli == *c || cli.starts_with(&format!("{} ", c)));
+    }
+    false
+}
+
+/// Read the source line a frame is stopped at, if the file is available locally
+fn source_line(frame: &Frame) -> Option<String> {
+    let path = frame.fullname.as_ref().or(frame.file.as_ref())?;
+    let line = frame.line?.checked_sub(1)? as usize;
+    let content = std::fs::read_to_string(path).ok()?;
+    content.lines().nth(line).map(|l| l.to_string())
+}
+
+/// Frames returned by `gdb_stack_list` when no range is given
+const DEFAULT_STACK_FRAMES: u64 = 64;
+
+/// Normalize a breakpoint number list such as "1-4,7" into the
+/// space-separated form GDB accepts ("1-4 7")
+fn normalize_breakpoint_numbers(input: &str) -> Result<String> {
+    let tokens: Vec<&str> = input
+        .split(|c: char| c == ',' || c.is_whitespace())
+        .filter(|t| !t.is_empty())
+        .collect();
+    if tokens.is_empty() {
+        return Err(ToolError::invalid_argument("No breakpoint numbers given").into());
+    }
+
+    for token in &tokens {
+        let valid = match token.split_once('-') {
+            Some((low, high)) => match (low.parse::<u64>(), high.parse::<u64>()) {
+                (Ok(low), Ok(high)) => low <= high,
+                _ => false,
+            },
+            None => token.split('.').all(|part| part.parse::<u64>().is_ok()),
+        };
+        if !valid {
+            return Err(ToolError::invalid_argument(format!("Invalid breakpoint number or range: '{}'", token)).into());
+        }
+    }
+
+    Ok(tokens.join(" "))
+}
+
+/// Read the instruction/time budget shared by the step-until tools
+fn step_budget(args: Option<&serde_json::Map<String, serde_json::Value>>) -> (u64, u64, usize) {
+    let max_steps = args.and_then(|a| a.get("max_steps").and_then(|v| v.as_u64())).unwrap_or(1000);
+    let timeout_ms = args.and_then(|a| a.get("timeout_ms").and_then(|v| v.as_u64())).unwrap_or(30000);
+    let trace_limit = args.and_then(|a| a.get("trace_limit").and_then(|v| v.as_u64())).unwrap_or(20) as usize;
+    (max_steps, timeout_ms, trace_limit)
+}
+
+/// Append the byte order warning of the session, if any
+/// Set up a started session as a target profile describes. Returns what
+/// was done, for the `gdb_start` result.
+fn apply_profile(client: &mut GdbClient, name: &str, profile: &TargetProfile) -> Result<Vec<String>> {
+    let mut steps = Vec::new();
+    client.set_profile(name, profile.svd_file.clone(), profile.qmp.clone());
+    if let Some(sysroot) = &profile.sysroot {
+        client.gdb_set("sysroot", sysroot)?;
+        steps.push(format!("set sysroot {}", sysroot));
+    }
+    for command in &profile.init_commands {
+        client.console_command(command)?;
+        client.record_history(command.clone());
+        steps.push(command.clone());
+    }
+    if let Some(remote) = &profile.remote {
+        client.target_connect_remote(remote)?;
+        steps.push(format!("Connected to remote target: {}", remote));
+    }
+    if let Some(svd) = &profile.svd_file {
+        steps.push(format!("gdb_break_vectors names interrupts from {}", svd));
+    }
+    Ok(steps)
+}
+
+fn with_endian_warning(message: String, client: &GdbClient) -> String {
+    match client.state().endian_warning {
+        Some(warning) => format!("{}\nWarning: {}", message, warning),
+        None => message,
+    }
+}
+
+/// Read the optional `thread_id`/`frame_level` evaluation context
+fn frame_context(args: Option<&serde_json::Map<String, serde_json::Value>>) -> FrameContext {
+    FrameContext {
+        thread: args.and_then(|a| a.get("thread_id")).and_then(|v| {
+            v.as_str().map(|s| s.to_string()).or_else(|| v.as_u64().map(|n| n.to_string()))
+        }),
+        frame: args.and_then(|a| a.get("frame_level").and_then(|v| v.as_u64())),
+    }
 }
 
 impl Default for GdbMcpServer {
@@ -598,3 +4506,59 @@ impl Default for GdbMcpServer {
         Self::new()
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_normalize_breakpoint_numbers() {
+        assert_eq!(normalize_breakpoint_numbers("1-4,7").unwrap(), "1-4 7");
+        assert_eq!(normalize_breakpoint_numbers("2, 3 5.1").unwrap(), "2 3 5.1");
+        assert!(normalize_breakpoint_numbers("4-1").is_err());
+        assert!(normalize_breakpoint_numbers("main").is_err());
+        assert!(normalize_breakpoint_numbers(" , ").is_err());
+    }
+
+    #[test]
+    fn test_is_destructive_command() {
+        assert!(is_destructive_command("-data-write-memory-bytes 0x1000 ff"));
+        assert!(is_destructive_command("interpreter-exec console \"kill\""));
+        assert!(is_destructive_command("interpreter-exec console \"set var x = 1\""));
+        assert!(is_destructive_command("interpreter-exec console \"monitor reset halt\""));
+        assert!(!is_destructive_command("interpreter-exec console \"set pagination off\""));
+        assert!(!is_destructive_command("interpreter-exec console \"info registers\""));
+        assert!(!is_destructive_command("data-read-memory-bytes 0x1000 4"));
+    }
+
+    #[tokio::test]
+    async fn test_tool_errors_carry_codes() {
+        let server = GdbMcpServer::new();
+
+        let result = server
+            .handle_tools_call(Some(serde_json::json!({"name": "gdb_break_insert", "arguments": {}})))
+            .await
+            .unwrap();
+        assert_eq!(result["is_error"], true);
+        assert_eq!(result["_meta"]["errorCode"], "invalid_params");
+        assert_eq!(result["_meta"]["errors"][0], "missing required argument 'location'");
+
+        let result = server
+            .handle_tools_call(Some(serde_json::json!({"name": "gdb_watch_insert", "arguments": {"location": "x", "type": "rw"}})))
+            .await
+            .unwrap();
+        assert_eq!(result["_meta"]["errorCode"], "invalid_params");
+
+        let result = server
+            .handle_tools_call(Some(serde_json::json!({"name": "gdb_break_list"})))
+            .await
+            .unwrap();
+        assert_eq!(result["_meta"]["errorCode"], "no_session");
+
+        let err = server
+            .handle_tools_call(Some(serde_json::json!({"name": "gdb_nonexistent"})))
+            .await
+            .unwrap_err();
+        assert_eq!(err.downcast::<JsonRpcError>().unwrap().code, -32602);
+    }
+}
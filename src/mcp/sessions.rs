@@ -0,0 +1,307 @@
+//! Debugging Sessions
+//!
+//! A session is one GDB client plus the kill switch for its processes and
+//! the handle for interrupting its target. Each
+//! MCP connection works on a current session. Sessions started with a name
+//! are also kept in a [`SessionRegistry`], which a daemon shares between
+//! connections, so a new connection can re-acquire them after the MCP
+//! client restarts.
+
+use crate::gdb::diagnostics::Diagnostics;
+use crate::gdb::managed::{EmergencyStopReport, KillSwitch};
+use crate::gdb::mitrace::MiTrace;
+use crate::gdb::timeline::Timeline;
+use crate::gdb::{GdbClient, Interrupter};
+use crate::mcp::limits::{LimitState, Limits};
+use crate::mcp::peer::ClientPeer;
+use crate::mcp::protocol::Notification;
+use crate::mcp::replay::ReplayCache;
+use crate::util::now_ms;
+use anyhow::{anyhow, Result};
+use serde::Serialize;
+use std::collections::BTreeMap;
+use std::sync::atomic::{AtomicBool, AtomicU64, Ordering};
+use std::sync::{Arc, Mutex, MutexGuard};
+use std::time::Duration;
+use tokio::sync::RwLock;
+use tracing::{info, warn};
+
+/// How often the idle watchdog checks a session
+const WATCHDOG_INTERVAL: Duration = Duration::from_secs(15);
+
+/// How long before an idle teardown the client is warned
+const IDLE_WARNING: Duration = Duration::from_secs(60);
+
+/// GDB client slot shared by the tool handlers
+pub type SharedClient = Arc<RwLock<Option<GdbClient>>>;
+
+/// One debugging session
+pub struct Session {
+    /// Name for sessions that outlive the connection
+    pub name: Option<String>,
+    pub client: SharedClient,
+    kill_switch: Mutex<Option<KillSwitch>>,
+    interrupter: Mutex<Option<Interrupter>>,
+    /// Event timeline of the session's GDB, kept after GDB stops
+    timeline: Mutex<Option<Arc<Mutex<Timeline>>>>,
+    /// Warnings of the session's GDB, kept after GDB stops
+    diagnostics: Mutex<Option<Arc<Mutex<Diagnostics>>>>,
+    /// MI commands sent by the session's GDB client during verbose calls
+    mi_trace: Mutex<Option<Arc<Mutex<MiTrace>>>>,
+    /// Whether tool calls report their MI commands unless they pass `verbose`
+    verbose: AtomicBool,
+    /// Results of calls made with a `request_token`
+    pub replays: ReplayCache,
+    /// Safety caps and the steps counted against them
+    limits: Mutex<LimitState>,
+    pub created_ms: u64,
+    /// When a tool call last used the session, in ms since the Unix epoch
+    last_used_ms: AtomicU64,
+    /// Client warned before an idle teardown
+    peer: Mutex<Option<Arc<ClientPeer>>>,
+    /// Incremented by each idle watchdog so older ones stop
+    watchdog: AtomicU64,
+}
+
+impl Session {
+    pub fn new(name: Option<String>) -> Self {
+        Self {
+            name,
+            client: Arc::new(RwLock::new(None)),
+            kill_switch: Mutex::new(None),
+            interrupter: Mutex::new(None),
+            timeline: Mutex::new(None),
+            diagnostics: Mutex::new(None),
+            mi_trace: Mutex::new(None),
+            verbose: AtomicBool::new(false),
+            replays: ReplayCache::default(),
+            limits: Mutex::new(LimitState::default()),
+            created_ms: now_ms(),
+            last_used_ms: AtomicU64::new(now_ms()),
+            peer: Mutex::new(None),
+            watchdog: AtomicU64::new(0),
+        }
+    }
+
+    /// Note that a tool call used the session
+    pub fn touch(&self) {
+        self.last_used_ms.store(now_ms(), Ordering::Relaxed);
+    }
+
+    /// Replace the session's safety caps
+    pub fn set_limits(&self, limits: Limits) {
+        *self.limits.lock().unwrap() = LimitState::new(limits);
+    }
+
+    pub fn limits(&self) -> MutexGuard<'_, LimitState> {
+        self.limits.lock().unwrap()
+    }
+
+    pub fn set_verbose(&self, verbose: bool) {
+        self.verbose.store(verbose, Ordering::Relaxed);
+    }
+
+    pub fn verbose(&self) -> bool {
+        self.verbose.load(Ordering::Relaxed)
+    }
+
+    /// Send idle warnings to this client from now on
+    pub fn set_peer(&self, peer: Arc<ClientPeer>) {
+        *self.peer.lock().unwrap() = Some(peer);
+    }
+
+    fn notify(&self, level: &str, message: String) {
+        let Some(peer) = self.peer.lock().unwrap().clone() else {
+            return;
+        };
+        let data = serde_json::json!({ "session": self.name, "message": message });
+        if let Err(e) = peer.notify(&Notification::logging(level, data)) {
+            warn!("Failed to notify the client: {}", e);
+        }
+    }
+
+    /// Stop GDB and its helpers once no tool call has used the session for
+    /// `timeout`, warning the client a minute before (or halfway for short
+    /// timeouts). A named session is also removed from `registry`. The
+    /// watchdog ends when the session stops or another watchdog replaces it.
+    pub fn watch_idle(self: &Arc<Self>, timeout: Duration, registry: SessionRegistry) {
+        let generation = self.watchdog.fetch_add(1, Ordering::SeqCst) + 1;
+        let warning = IDLE_WARNING.min(timeout / 2);
+        let session = Arc::downgrade(self);
+        self.touch();
+
+        tokio::spawn(async move {
+            let mut warned = false;
+            loop {
+                tokio::time::sleep(WATCHDOG_INTERVAL.min(warning)).await;
+                let Some(session) = session.upgrade() else {
+                    return;
+                };
+                if session.watchdog.load(Ordering::SeqCst) != generation {
+                    return;
+                }
+                match session.client.try_read() {
+                    Ok(client) if client.is_none() => return,
+                    Ok(_) => {}
+                    // A tool call is running
+                    Err(_) => continue,
+                }
+
+                let idle = Duration::from_millis(now_ms().saturating_sub(session.last_used_ms.load(Ordering::Relaxed)));
+                if idle + warning < timeout {
+                    warned = false;
+                } else if idle < timeout {
+                    if !warned {
+                        session.notify("warning", format!(
+                            "The debugging session has been idle for {} s and will be stopped in {} s unless a tool call uses it.",
+                            idle.as_secs(),
+                            (timeout - idle).as_secs()
+                        ));
+                        warned = true;
+                    }
+                } else {
+                    info!("Stopping GDB session {} after {} s idle", session.name.as_deref().unwrap_or("(unnamed)"), idle.as_secs());
+                    session.notify("warning", format!("The debugging session was stopped after being idle for {} s.", idle.as_secs()));
+                    if let Some(name) = &session.name {
+                        registry.remove(name);
+                    }
+                    session.shutdown().await;
+                    return;
+                }
+            }
+        });
+    }
+
+    /// Remember how to kill the session's processes and interrupt its target
+    pub fn arm(&self, client: &GdbClient) {
+        *self.kill_switch.lock().unwrap() = client.kill_switch();
+        *self.interrupter.lock().unwrap() = client.interrupter();
+        *self.timeline.lock().unwrap() = Some(client.timeline());
+        *self.diagnostics.lock().unwrap() = Some(client.diagnostics());
+        *self.mi_trace.lock().unwrap() = Some(client.mi_trace());
+    }
+
+    /// Forget the kill switch once GDB has been stopped
+    pub fn disarm(&self) {
+        self.kill_switch.lock().unwrap().take();
+        self.interrupter.lock().unwrap().take();
+    }
+
+    /// Handle for interrupting the target without waiting for the client lock
+    pub fn interrupter(&self) -> Option<Interrupter> {
+        self.interrupter.lock().unwrap().clone()
+    }
+
+    /// Event timeline of the session's GDB, readable without the client lock
+    pub fn timeline(&self) -> Option<Arc<Mutex<Timeline>>> {
+        self.timeline.lock().unwrap().clone()
+    }
+
+    /// Warnings of the session's GDB, readable without the client lock
+    pub fn diagnostics(&self) -> Option<Arc<Mutex<Diagnostics>>> {
+        self.diagnostics.lock().unwrap().clone()
+    }
+
+    /// MI command trace of the session's GDB client
+    pub fn mi_trace(&self) -> Option<Arc<Mutex<MiTrace>>> {
+        self.mi_trace.lock().unwrap().clone()
+    }
+
+    /// Kill GDB and its helpers without waiting for the client lock
+    pub fn fire(&self) -> Option<EmergencyStopReport> {
+        self.interrupter.lock().unwrap().take();
+        self.kill_switch.lock().unwrap().take().map(|switch| switch.fire())
+    }
+
+    /// Disconnect the target and stop GDB
+    pub async fn shutdown(&self) {
+        // A tool call waiting on a running target holds the client until
+        // the target stops
+        if self.client.try_write().is_err() {
+            if let Some(interrupter) = self.interrupter() {
+                let _ = tokio::task::spawn_blocking(move || interrupter.interrupt()).await;
+            }
+        }
+        let mut guard = self.client.write().await;
+        if let Some(mut client) = guard.take() {
+            self.disarm();
+            info!("Shutting down GDB session {}", self.name.as_deref().unwrap_or("(unnamed)"));
+            client.shutdown();
+        }
+    }
+}
+
+/// Summary of a named session for `gdb_session_list`
+#[derive(Debug, Clone, Serialize)]
+pub struct SessionInfo {
+    pub name: String,
+    pub running: bool,
+    /// Current target, if connected
+    pub target: Option<String>,
+    pub executable: Option<String>,
+    /// Whether this connection is using the session
+    pub current: bool,
+    /// A tool call is still holding the session
+    pub busy: bool,
+    pub created_ms: u64,
+}
+
+/// Named sessions shared by every connection of the server
+#[derive(Clone, Default)]
+pub struct SessionRegistry {
+    sessions: Arc<Mutex<BTreeMap<String, Arc<Session>>>>,
+}
+
+impl SessionRegistry {
+    /// Register a named session
+    pub fn insert(&self, session: Arc<Session>) -> Result<()> {
+        let name = session.name.clone().ok_or_else(|| anyhow!("Only named sessions can be registered"))?;
+        let mut sessions = self.sessions.lock().unwrap();
+        if sessions.contains_key(&name) {
+            return Err(anyhow!("A session named '{}' already exists", name));
+        }
+        sessions.insert(name, session);
+        Ok(())
+    }
+
+    pub fn get(&self, name: &str) -> Option<Arc<Session>> {
+        self.sessions.lock().unwrap().get(name).cloned()
+    }
+
+    pub fn contains(&self, name: &str) -> bool {
+        self.sessions.lock().unwrap().contains_key(name)
+    }
+
+    pub fn remove(&self, name: &str) -> Option<Arc<Session>> {
+        self.sessions.lock().unwrap().remove(name)
+    }
+
+    /// Named sessions, sorted by name
+    pub fn list(&self) -> Vec<Arc<Session>> {
+        self.sessions.lock().unwrap().values().cloned().collect()
+    }
+
+    /// Remove and return every named session
+    pub fn drain(&self) -> Vec<Arc<Session>> {
+        std::mem::take(&mut *self.sessions.lock().unwrap()).into_values().collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_registry_rejects_duplicates_and_unnamed_sessions() {
+        let registry = SessionRegistry::default();
+        registry.insert(Arc::new(Session::new(Some("board".to_string())))).unwrap();
+        assert!(registry.insert(Arc::new(Session::new(Some("board".to_string())))).is_err());
+        assert!(registry.insert(Arc::new(Session::new(None))).is_err());
+
+        // Clones share the sessions, as daemon connections do
+        let other = registry.clone();
+        assert!(other.get("board").is_some());
+        assert_eq!(other.drain().len(), 1);
+        assert!(!registry.contains("board"));
+    }
+}
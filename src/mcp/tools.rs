@@ -1,5 +1,10 @@
 //! MCP Tool Definitions for GDB Debugging
 
+use crate::config::ToolsConfig;
+use crate::gdb::container::RUNTIMES;
+use crate::gdb::registers::{DEFAULT_RISCV_CSRS, PROFILES, RISCV_CSRS};
+use crate::gdb::mitrace::VERBOSE;
+use crate::mcp::replay::REQUEST_TOKEN;
 use serde::{Deserialize, Serialize};
 
 /// MCP Tool definition
@@ -14,17 +19,77 @@ pub struct ToolDefinition {
 pub fn tool_start_gdb() -> ToolDefinition {
     ToolDefinition {
         name: "gdb_start".to_string(),
-        description: "Start a new GDB debugging session. Can specify gdb-multiarch path and architecture for cross-architecture debugging.".to_string(),
+        description: "Start a new GDB debugging session. Can specify gdb-multiarch path and architecture for cross-architecture debugging, or use LLDB (via lldb-mi) on macOS; with LLDB only core run control, breakpoints, stack, variables and memory are available.".to_string(),
         input_schema: serde_json::json!({
             "type": "object",
             "properties": {
+                "name": {
+                    "type": "string",
+                    "description": "Name for a persistent session that can be re-acquired with gdb_session_attach (survives client reconnects in daemon mode)"
+                },
+                "profile": {
+                    "type": "string",
+                    "description": "Named target profile from the server configuration. It supplies the GDB path, architecture, sysroot, init commands, remote target to connect to and SVD file; other arguments override its values"
+                },
+                "backend": {
+                    "type": "string",
+                    "enum": ["gdb", "lldb"],
+                    "description": "Debugger to drive over MI: 'gdb' (default) or 'lldb' (lldb-mi; tools needing GDB's CLI, Python or GDB-only MI report that they are unsupported)"
+                },
                 "gdb_path": {
                     "type": "string",
-                    "description": "Path to GDB executable (default: gdb-multiarch)"
+                    "description": "Path to the debugger executable (default: gdb-multiarch, or lldb-mi for the lldb backend)"
                 },
                 "architecture": {
                     "type": "string",
                     "description": "Target architecture (e.g., arm, aarch64, riscv, mips)"
+                },
+                "gdb_args": {
+                    "type": "array",
+                    "items": { "type": "string" },
+                    "description": "Extra command-line arguments for GDB (e.g., ['--data-directory=/opt/gdb/share/gdb', '-iex', 'set auto-load safe-path /'])"
+                },
+                "env": {
+                    "type": "object",
+                    "additionalProperties": { "type": "string" },
+                    "description": "Extra environment variables for the GDB process (e.g., {\"PYTHONPATH\": \"/opt/printers\"})"
+                },
+                "working_dir": {
+                    "type": "string",
+                    "description": "Working directory for the GDB process"
+                },
+                "interrupt_on_timeout": {
+                    "type": "boolean",
+                    "description": "Interrupt GDB with SIGINT when a command times out so the session can resynchronize (default: false)"
+                },
+                "startup_timeout_ms": {
+                    "type": "integer",
+                    "description": "How long to wait for GDB to print its first prompt before giving up (default: 10000)"
+                },
+                "idle_timeout_minutes": {
+                    "type": "integer",
+                    "description": "Stop GDB and its helper processes when no tool call has used the session for this many minutes, after a warning notification (default: the server's idle_timeout_minutes setting; 0 disables)"
+                },
+                "data_directory": {
+                    "type": "string",
+                    "description": "GDB data directory for this session (--data-directory), e.g. one with a matching Python library for a cross GDB (default: the server's data_directory setting)"
+                },
+                "python_helpers": {
+                    "type": "boolean",
+                    "description": "Source the bundled Python helpers, which add the mcp-dump, mcp-heap and mcp-freertos-tasks commands for gdb_command (default: the server's python_helpers setting)"
+                },
+                "limits": {
+                    "type": "object",
+                    "properties": {
+                        "max_steps_per_minute": {"type": "integer"},
+                        "max_memory_read_bytes": {"type": "integer"},
+                        "max_breakpoints": {"type": "integer"}
+                    },
+                    "description": "Safety caps for this session: source/instruction steps per minute, bytes read per memory tool call, and breakpoints plus watchpoints at once. Calls exceeding one fail with the limit_exceeded error code. They can only tighten the server's limits setting"
+                },
+                "verbose": {
+                    "type": "boolean",
+                    "description": "Report the MI commands sent to GDB in the _meta.miCommands of every tool call of this session, unless a call passes verbose: false (default: false)"
                 }
             },
             "required": []
@@ -36,7 +101,64 @@ pub fn tool_start_gdb() -> ToolDefinition {
 pub fn tool_stop_gdb() -> ToolDefinition {
     ToolDefinition {
         name: "gdb_stop".to_string(),
-        description: "Stop the current GDB debugging session and clean up resources.".to_string(),
+        description: "Stop the current GDB debugging session and clean up resources. A named session is removed.".to_string(),
+        input_schema: serde_json::json!({
+            "type": "object",
+            "properties": {},
+            "required": []
+        }),
+    }
+}
+
+/// Tool: List named sessions
+pub fn tool_session_list() -> ToolDefinition {
+    ToolDefinition {
+        name: "gdb_session_list".to_string(),
+        description: "List the named GDB sessions, including sessions started by earlier client connections when the server runs as a daemon.".to_string(),
+        input_schema: serde_json::json!({
+            "type": "object",
+            "properties": {},
+            "required": []
+        }),
+    }
+}
+
+/// Tool: Attach to a named session
+pub fn tool_session_attach() -> ToolDefinition {
+    ToolDefinition {
+        name: "gdb_session_attach".to_string(),
+        description: "Make a named session the current session of this connection, keeping its GDB process and target connection as they are.".to_string(),
+        input_schema: serde_json::json!({
+            "type": "object",
+            "properties": {
+                "name": {
+                    "type": "string",
+                    "description": "Session name given to gdb_start"
+                }
+            },
+            "required": ["name"]
+        }),
+    }
+}
+
+/// Tool: Detach from the named session
+pub fn tool_session_detach() -> ToolDefinition {
+    ToolDefinition {
+        name: "gdb_session_detach".to_string(),
+        description: "Stop using the current named session without stopping it.".to_string(),
+        input_schema: serde_json::json!({
+            "type": "object",
+            "properties": {},
+            "required": []
+        }),
+    }
+}
+
+/// Tool: Emergency stop
+pub fn tool_emergency_stop() -> ToolDefinition {
+    ToolDefinition {
+        name: "gdb_emergency_stop".to_string(),
+        description: "Immediately kill the GDB process and every helper process it manages (gdbserver, SSH tunnels), clear the session and report what was cleaned up. Works even while another tool call is blocked. Use when the session or target is wedged; start over with gdb_start.".to_string(),
         input_schema: serde_json::json!({
             "type": "object",
             "properties": {},
@@ -55,7 +177,7 @@ pub fn tool_load_file() -> ToolDefinition {
             "properties": {
                 "file_path": {
                     "type": "string",
-                    "description": "Path to the executable file to debug"
+                    "description": "Path to the executable file to debug (relative paths resolve against the client's workspace root)"
                 }
             },
             "required": ["file_path"]
@@ -67,7 +189,7 @@ pub fn tool_load_file() -> ToolDefinition {
 pub fn tool_target_connect() -> ToolDefinition {
     ToolDefinition {
         name: "gdb_target_connect".to_string(),
-        description: "Connect to a remote debugging target via TCP or serial port. Supports both 'remote' and 'extended-remote' connection types.".to_string(),
+        description: "Connect to a remote debugging target via TCP or serial port. Supports both 'remote' and 'extended-remote' connection types. TCP targets behind a jump host can be reached through an SSH tunnel (ssh_host), which is closed on disconnect.".to_string(),
         input_schema: serde_json::json!({
             "type": "object",
             "properties": {
@@ -91,6 +213,18 @@ pub fn tool_target_connect() -> ToolDefinition {
                 "baud_rate": {
                     "type": "integer",
                     "description": "Baud rate for serial connection"
+                },
+                "ssh_host": {
+                    "type": "string",
+                    "description": "Jump host to tunnel the TCP connection through; host is then resolved from the jump host"
+                },
+                "ssh_user": {
+                    "type": "string",
+                    "description": "Login name on the jump host"
+                },
+                "ssh_port": {
+                    "type": "integer",
+                    "description": "SSH port of the jump host (default: 22)"
                 }
             },
             "required": []
@@ -98,6 +232,56 @@ pub fn tool_target_connect() -> ToolDefinition {
     }
 }
 
+/// Tool: Attach to a process in a container
+pub fn tool_container_attach() -> ToolDefinition {
+    ToolDefinition {
+        name: "gdb_container_attach".to_string(),
+        description: "Attach to a process running in a Docker or Podman container. In 'host' mode the process is attached through its host PID with the sysroot set to /proc/<pid>/root; in 'gdbserver' mode gdbserver is started inside the container and GDB connects to it.".to_string(),
+        input_schema: serde_json::json!({
+            "type": "object",
+            "properties": {
+                "container": {
+                    "type": "string",
+                    "description": "Container ID or name"
+                },
+                "runtime": {
+                    "type": "string",
+                    "enum": RUNTIMES,
+                    "description": "Container runtime (default: docker)"
+                },
+                "process": {
+                    "type": ["string", "integer"],
+                    "description": "Process name or PID inside the container (default: the container's main process)"
+                },
+                "mode": {
+                    "type": "string",
+                    "enum": ["host", "gdbserver"],
+                    "description": "Attach from the host or through gdbserver inside the container (default: host)"
+                },
+                "port": {
+                    "type": "integer",
+                    "description": "gdbserver port (gdbserver mode, default: 2345)"
+                },
+                "host": {
+                    "type": "string",
+                    "description": "Address to reach gdbserver at, e.g. 127.0.0.1 for a published port (gdbserver mode, default: the container IP)"
+                },
+                "source_dirs": {
+                    "type": "array",
+                    "items": { "type": "string" },
+                    "description": "Source directories inside the container, mapped to /proc/<pid>/root (host mode)"
+                },
+                "source_map": {
+                    "type": "object",
+                    "additionalProperties": { "type": "string" },
+                    "description": "Additional substitute-path rules from container path to host path"
+                }
+            },
+            "required": ["container"]
+        }),
+    }
+}
+
 /// Tool: Disconnect from target
 pub fn tool_target_disconnect() -> ToolDefinition {
     ToolDefinition {
@@ -131,9 +315,42 @@ pub fn tool_break_insert() -> ToolDefinition {
                     "type": "string",
                     "description": "Optional condition expression for conditional breakpoint"
                 },
+                "count_evaluations": {
+                    "type": "boolean",
+                    "description": "Count how often the condition is evaluated, for gdb_break_stats. The condition is wrapped to increment a convenience variable (default: false)"
+                },
                 "ignore_count": {
                     "type": "integer",
                     "description": "Number of times to ignore this breakpoint before stopping"
+                },
+                "stop_after_hits": {
+                    "type": "integer",
+                    "minimum": 1,
+                    "description": "Stop on the Nth hit (e.g. 1000 for the 1000th loop iteration); hits an existing breakpoint already has count toward N with if_not_exists. Cannot be combined with ignore_count"
+                },
+                "thread_id": {
+                    "type": ["string", "integer"],
+                    "description": "Only stop when this GDB thread hits the breakpoint"
+                },
+                "inferior": {
+                    "type": "integer",
+                    "description": "Only stop when this inferior (GDB inferior number) hits the breakpoint"
+                },
+                "pending": {
+                    "type": "boolean",
+                    "description": "Create a pending breakpoint if the location is not found yet (e.g., in a shared library loaded later)"
+                },
+                "if_not_exists": {
+                    "type": "boolean",
+                    "description": "Return the existing breakpoint instead of inserting a duplicate when one with the same condition is already at this location (default: false)"
+                },
+                "label": {
+                    "type": "string",
+                    "description": "Short name to remember the breakpoint by, shown by gdb_break_list"
+                },
+                "note": {
+                    "type": "string",
+                    "description": "Free-form note on why the breakpoint exists, shown by gdb_break_list"
                 }
             },
             "required": ["location"]
@@ -141,6 +358,55 @@ pub fn tool_break_insert() -> ToolDefinition {
     }
 }
 
+/// Tool: Find breakpoints by location
+pub fn tool_break_find() -> ToolDefinition {
+    ToolDefinition {
+        name: "gdb_break_find".to_string(),
+        description: "Find the breakpoints at a location. Matches the requested location and the location GDB resolved it to; file paths match by suffix, so 'main.c:42' finds a breakpoint in '/src/app/main.c'.".to_string(),
+        input_schema: serde_json::json!({
+            "type": "object",
+            "properties": {
+                "location": {
+                    "type": "string",
+                    "description": "Function name, file:line, or *address"
+                }
+            },
+            "required": ["location"]
+        }),
+    }
+}
+
+/// Tool: Break on exception vectors
+pub fn tool_break_vectors() -> ToolDefinition {
+    ToolDefinition {
+        name: "gdb_break_vectors".to_string(),
+        description: "Set breakpoints on the handlers in a Cortex-M vector table, one per distinct handler, so faults and spurious interrupts stop the target. Vectors sharing a handler (e.g. Default_Handler) share its breakpoint. Interrupts are named from a CMSIS-SVD file if given. Each handler uses a breakpoint, and flash targets have few hardware breakpoints, so select vectors on small parts.".to_string(),
+        input_schema: serde_json::json!({
+            "type": "object",
+            "properties": {
+                "base": {
+                    "type": "string",
+                    "description": "Address or symbol of the vector table (default: read from VTOR at 0xE000ED08)"
+                },
+                "count": {
+                    "type": "integer",
+                    "description": "Number of vector table entries, including the initial SP (default: 16, or enough for every interrupt in the SVD file)"
+                },
+                "svd_path": {
+                    "type": "string",
+                    "description": "CMSIS-SVD file naming the device's interrupts (default: the SVD file of the session's target profile)"
+                },
+                "vectors": {
+                    "type": "array",
+                    "items": { "type": "string" },
+                    "description": "Vectors to break on, by name ('HardFault', 'USART1'), vector number, or 'IRQn' (default: every vector except Reset)"
+                }
+            },
+            "required": []
+        }),
+    }
+}
+
 /// Tool: Delete breakpoint
 pub fn tool_break_delete() -> ToolDefinition {
     ToolDefinition {
@@ -151,7 +417,7 @@ pub fn tool_break_delete() -> ToolDefinition {
             "properties": {
                 "number": {
                     "type": "string",
-                    "description": "Breakpoint number to delete (omit to delete all breakpoints)"
+                    "description": "Breakpoint number, list, or range to delete, e.g. '3' or '1-4,7' (omit to delete all breakpoints)"
                 }
             },
             "required": []
@@ -163,10 +429,15 @@ pub fn tool_break_delete() -> ToolDefinition {
 pub fn tool_break_list() -> ToolDefinition {
     ToolDefinition {
         name: "gdb_break_list".to_string(),
-        description: "List all breakpoints in the current debugging session.".to_string(),
+        description: "List all breakpoints in the current debugging session, as tracked from GDB notifications. Each entry includes the requested location, the actual resolved location, and whether it is pending or was moved to a different line.".to_string(),
         input_schema: serde_json::json!({
             "type": "object",
-            "properties": {},
+            "properties": {
+                "refresh": {
+                    "type": "boolean",
+                    "description": "Re-read the breakpoint table from GDB first (default: false)"
+                }
+            },
             "required": []
         }),
     }
@@ -176,13 +447,13 @@ pub fn tool_break_list() -> ToolDefinition {
 pub fn tool_break_toggle() -> ToolDefinition {
     ToolDefinition {
         name: "gdb_break_toggle".to_string(),
-        description: "Enable or disable a breakpoint.".to_string(),
+        description: "Enable or disable one or more breakpoints.".to_string(),
         input_schema: serde_json::json!({
             "type": "object",
             "properties": {
                 "number": {
                     "type": "string",
-                    "description": "Breakpoint number"
+                    "description": "Breakpoint number, list, or range, e.g. '3' or '1-4,7'"
                 },
                 "enabled": {
                     "type": "boolean",
@@ -194,40 +465,67 @@ pub fn tool_break_toggle() -> ToolDefinition {
     }
 }
 
-/// Tool: Set watchpoint
-pub fn tool_watch_insert() -> ToolDefinition {
+/// Tool: Breakpoints from a log
+pub fn tool_break_from_log() -> ToolDefinition {
     ToolDefinition {
-        name: "gdb_watch_insert".to_string(),
-        description: "Set a watchpoint on a variable or memory location. Watchpoints trigger when the watched location is read, written, or accessed.".to_string(),
+        name: "gdb_break_from_log".to_string(),
+        description: "Set breakpoints at every source location mentioned in pasted text: compiler warnings and errors, sanitizer reports (ASan/UBSan/TSan), Valgrind errors, GDB backtraces or panic messages. Each location is set once, labeled with the kind of log line and noted with the line itself. Frames in sanitizer runtimes and system libraries are skipped unless include_system is set.".to_string(),
         input_schema: serde_json::json!({
             "type": "object",
             "properties": {
-                "location": {
+                "log": {
                     "type": "string",
-                    "description": "Variable name or memory expression to watch (e.g., 'counter', '*ptr', '&myvar')"
+                    "description": "The log text"
                 },
-                "watch_type": {
-                    "type": "string",
-                    "enum": ["write", "read", "access"],
-                    "description": "Type of watchpoint: 'write' (trigger on write), 'read' (trigger on read), 'access' (trigger on read or write)"
+                "kinds": {
+                    "type": "array",
+                    "items": {
+                        "type": "string",
+                        "enum": ["error", "warning", "note", "runtime_error", "frame", "mention"]
+                    },
+                    "description": "Only use locations from these kinds of lines (default: all)"
+                },
+                "include_system": {
+                    "type": "boolean",
+                    "description": "Also break in sanitizer runtimes, libc and other system code (default: false)"
+                },
+                "max_breakpoints": {
+                    "type": "integer",
+                    "description": "Most breakpoints to set, in order of appearance (default: 20)"
+                },
+                "pending": {
+                    "type": "boolean",
+                    "description": "Set locations GDB cannot resolve yet as pending breakpoints (default: false)"
+                },
+                "dry_run": {
+                    "type": "boolean",
+                    "description": "Only list the locations found, without setting breakpoints (default: false)"
                 }
             },
-            "required": ["location"]
+            "required": ["log"]
         }),
     }
 }
 
-/// Tool: Delete watchpoint
-pub fn tool_watch_delete() -> ToolDefinition {
+/// Tool: Label a breakpoint
+pub fn tool_break_annotate() -> ToolDefinition {
     ToolDefinition {
-        name: "gdb_watch_delete".to_string(),
-        description: "Delete a watchpoint by its number.".to_string(),
+        name: "gdb_break_annotate".to_string(),
+        description: "Attach a label and a free-form note to a breakpoint or watchpoint (e.g., 'suspected double-free site'), so it is clear later why it exists. They are kept by the server, returned by gdb_break_list and shown when the breakpoint is hit.".to_string(),
         input_schema: serde_json::json!({
             "type": "object",
             "properties": {
                 "number": {
                     "type": "string",
-                    "description": "Watchpoint number to delete"
+                    "description": "Breakpoint or watchpoint number"
+                },
+                "label": {
+                    "type": "string",
+                    "description": "Short name; an empty string removes it. Omit to keep the current label"
+                },
+                "note": {
+                    "type": "string",
+                    "description": "Why the breakpoint exists; an empty string removes it. Omit to keep the current note"
                 }
             },
             "required": ["number"]
@@ -235,30 +533,24 @@ pub fn tool_watch_delete() -> ToolDefinition {
     }
 }
 
-/// Tool: Run/Start execution
-pub fn tool_run() -> ToolDefinition {
+/// Tool: Enable all breakpoints
+pub fn tool_break_enable_all() -> ToolDefinition {
     ToolDefinition {
-        name: "gdb_run".to_string(),
-        description: "Start program execution from the beginning. For remote debugging, this typically loads and starts the program.".to_string(),
+        name: "gdb_break_enable_all".to_string(),
+        description: "Enable all breakpoints and watchpoints.".to_string(),
         input_schema: serde_json::json!({
             "type": "object",
-            "properties": {
-                "args": {
-                    "type": "array",
-                    "items": {"type": "string"},
-                    "description": "Command line arguments to pass to the program"
-                }
-            },
+            "properties": {},
             "required": []
         }),
     }
 }
 
-/// Tool: Continue execution
-pub fn tool_continue() -> ToolDefinition {
+/// Tool: Disable all breakpoints
+pub fn tool_break_disable_all() -> ToolDefinition {
     ToolDefinition {
-        name: "gdb_continue".to_string(),
-        description: "Continue program execution from the current stopped state.".to_string(),
+        name: "gdb_break_disable_all".to_string(),
+        description: "Disable all breakpoints and watchpoints without deleting them.".to_string(),
         input_schema: serde_json::json!({
             "type": "object",
             "properties": {},
@@ -267,17 +559,25 @@ pub fn tool_continue() -> ToolDefinition {
     }
 }
 
-/// Tool: Step over
-pub fn tool_next() -> ToolDefinition {
+/// Tool: Breakpoint hit history
+pub fn tool_break_history() -> ToolDefinition {
     ToolDefinition {
-        name: "gdb_next".to_string(),
-        description: "Step over the current line (execute without entering function calls).".to_string(),
+        name: "gdb_break_history".to_string(),
+        description: "Show the history of breakpoint and watchpoint hits recorded during the session: per-breakpoint hit counts with the locations they were hit from, plus the individual hits (time, thread, frame, hit count).".to_string(),
         input_schema: serde_json::json!({
             "type": "object",
             "properties": {
-                "count": {
+                "number": {
+                    "type": "string",
+                    "description": "Only return hits of this breakpoint number"
+                },
+                "limit": {
                     "type": "integer",
-                    "description": "Number of lines to step over"
+                    "description": "Maximum number of individual hits to return (default: 50)"
+                },
+                "clear": {
+                    "type": "boolean",
+                    "description": "Clear the recorded history after returning it"
                 }
             },
             "required": []
@@ -285,17 +585,17 @@ pub fn tool_next() -> ToolDefinition {
     }
 }
 
-/// Tool: Step into
-pub fn tool_step() -> ToolDefinition {
+/// Tool: Conditional breakpoint statistics
+pub fn tool_break_stats() -> ToolDefinition {
     ToolDefinition {
-        name: "gdb_step".to_string(),
-        description: "Step into the current line (enter function calls).".to_string(),
+        name: "gdb_break_stats".to_string(),
+        description: "Show, per conditional breakpoint, how many times its condition was evaluated and how many times it actually stopped the program. Warns about hot conditional breakpoints whose evaluations are slowing the program down. Evaluations are counted for conditions set with gdb_break_insert and count_evaluations.".to_string(),
         input_schema: serde_json::json!({
             "type": "object",
             "properties": {
-                "count": {
-                    "type": "integer",
-                    "description": "Number of steps to perform"
+                "number": {
+                    "type": "string",
+                    "description": "Only report this breakpoint number"
                 }
             },
             "required": []
@@ -303,48 +603,702 @@ pub fn tool_step() -> ToolDefinition {
     }
 }
 
-/// Tool: Step out
-pub fn tool_finish() -> ToolDefinition {
+/// Tool: Catch shared library events
+pub fn tool_catch_library() -> ToolDefinition {
     ToolDefinition {
-        name: "gdb_finish".to_string(),
-        description: "Step out of the current function (continue until function returns).".to_string(),
+        name: "gdb_catch_library".to_string(),
+        description: "Stop when a shared library is loaded or unloaded (catch load/unload). Combine with pending breakpoints to debug plugins that are not loaded yet.".to_string(),
         input_schema: serde_json::json!({
             "type": "object",
-            "properties": {},
+            "properties": {
+                "event": {
+                    "type": "string",
+                    "enum": ["load", "unload"],
+                    "description": "Library event to catch (default: load)"
+                },
+                "regex": {
+                    "type": "string",
+                    "description": "Only catch libraries whose path matches this regular expression (omit for any library)"
+                },
+                "temporary": {
+                    "type": "boolean",
+                    "description": "Delete the catchpoint after it triggers once"
+                }
+            },
             "required": []
         }),
     }
 }
 
-/// Tool: Step one instruction
-pub fn tool_stepi() -> ToolDefinition {
+/// Tool: Stop on shared library events
+pub fn tool_stop_on_solib_events() -> ToolDefinition {
     ToolDefinition {
-        name: "gdb_stepi".to_string(),
-        description: "Step one machine instruction (assembly level step into). Useful for debugging without source symbols.".to_string(),
+        name: "gdb_stop_on_solib_events".to_string(),
+        description: "Enable or disable stopping on every shared library load and unload (stop-on-solib-events).".to_string(),
         input_schema: serde_json::json!({
             "type": "object",
             "properties": {
-                "count": {
-                    "type": "integer",
-                    "description": "Number of instructions to step"
+                "enabled": {
+                    "type": "boolean",
+                    "description": "true to stop on library events, false to stop ignoring them"
                 }
             },
-            "required": []
+            "required": ["enabled"]
         }),
     }
 }
 
-/// Tool: Next one instruction
-pub fn tool_nexti() -> ToolDefinition {
+/// Tool: Auto-resume rules
+pub fn tool_autoresume_rules() -> ToolDefinition {
     ToolDefinition {
-        name: "gdb_nexti".to_string(),
-        description: "Step one machine instruction, stepping over function calls (assembly level next).".to_string(),
+        name: "gdb_autoresume_rules".to_string(),
+        description: "Continue automatically on stops that are not interesting (a signal the program handles itself, a breakpoint while a condition holds, shared library loads) instead of reporting them. Resumed stops are logged and returned by the list action.".to_string(),
         input_schema: serde_json::json!({
             "type": "object",
             "properties": {
-                "count": {
+                "action": {
+                    "type": "string",
+                    "enum": ["add", "remove", "clear", "list"],
+                    "description": "add a rule, remove one by id, clear all rules and the log, or list the rules and resumed stops (default: list)"
+                },
+                "kind": {
+                    "type": "string",
+                    "enum": ["signal", "breakpoint", "reason"],
+                    "description": "What the new rule matches (add only)"
+                },
+                "signal": {
+                    "type": "string",
+                    "description": "Signal name for kind=signal (e.g. 'SIGUSR1', 'SIGPIPE'). The signal is still delivered according to GDB's handle settings. SIGINT cannot be resumed"
+                },
+                "number": {
+                    "type": "string",
+                    "description": "Breakpoint number for kind=breakpoint"
+                },
+                "condition": {
+                    "type": "string",
+                    "description": "For kind=breakpoint: resume only while this expression is non-zero in the stopped frame (e.g. 'len < 4096'). The stop is reported if it is false or fails to evaluate"
+                },
+                "reason": {
+                    "type": "string",
+                    "enum": crate::gdb::autoresume::RESUMABLE_REASONS,
+                    "description": "Stop reason for kind=reason"
+                },
+                "id": {
                     "type": "integer",
-                    "description": "Number of instructions to step over"
+                    "description": "Rule id (remove only)"
+                },
+                "drain_log": {
+                    "type": "boolean",
+                    "description": "Empty the log of resumed stops after returning it (list only, default: false)"
+                }
+            },
+            "required": []
+        }),
+    }
+}
+
+/// Tool: Override values on a breakpoint hit
+pub fn tool_override_on_hit() -> ToolDefinition {
+    ToolDefinition {
+        name: "gdb_override_on_hit".to_string(),
+        description: "Make a breakpoint set variables or registers to given values every time it is hit, then continue, for 'what if this returned 0?' experiments without rebuilding. Implemented with breakpoint commands; the hits are not reported as stops but logged by gdb_autoresume_rules. Delete the breakpoint with gdb_break_delete to end the override.".to_string(),
+        input_schema: serde_json::json!({
+            "type": "object",
+            "properties": {
+                "location": {
+                    "type": "string",
+                    "description": "Where to insert a new breakpoint for the override (e.g. 'parser.c:120', '*0x08001234')"
+                },
+                "number": {
+                    "type": "string",
+                    "description": "Existing breakpoint to turn into an override instead of inserting one; its current commands are replaced"
+                },
+                "target": {
+                    "type": "string",
+                    "description": "Variable, lvalue or register to set (e.g. 'ret', 'cfg->retries', '$r0')"
+                },
+                "value": {
+                    "type": "string",
+                    "description": "Expression assigned to target (e.g. '0', '-1', 'NULL')"
+                },
+                "assignments": {
+                    "type": "array",
+                    "description": "Several assignments, applied in order, instead of target and value",
+                    "items": {
+                        "type": "object",
+                        "properties": {
+                            "target": { "type": "string" },
+                            "value": { "type": "string" }
+                        },
+                        "required": ["target", "value"]
+                    }
+                },
+                "condition": {
+                    "type": "string",
+                    "description": "Apply the override only when this expression is true (new breakpoints only)"
+                },
+                "continue": {
+                    "type": "boolean",
+                    "description": "Continue after the assignments (default: true). With false the hit is reported as a normal stop, after the values are set"
+                },
+                "label": {
+                    "type": "string",
+                    "description": "Breakpoint label (default: a description of the assignments)"
+                }
+            },
+            "required": []
+        }),
+    }
+}
+
+/// Tool: Trace memory allocations
+pub fn tool_alloc_trace() -> ToolDefinition {
+    ToolDefinition {
+        name: "gdb_alloc_trace".to_string(),
+        description: "Trace malloc/calloc/realloc/free while the program runs: breakpoints on their first instruction record the size, pointer and calling address and continue automatically. The summary lists the call sites allocating the most and the largest allocations not freed yet (leak candidates). Start it once the C library is loaded, then continue the program.".to_string(),
+        input_schema: serde_json::json!({
+            "type": "object",
+            "properties": {
+                "action": {
+                    "type": "string",
+                    "enum": ["start", "stop", "summary", "log"],
+                    "description": "start tracing, stop it (results are kept), summarize the results, or return the most recent calls (default: summary)"
+                },
+                "functions": {
+                    "type": "array",
+                    "items": { "type": "string", "enum": crate::gdb::alloctrace::DEFAULT_FUNCTIONS },
+                    "description": "Functions to trace (start only, default: all)"
+                },
+                "top": {
+                    "type": "integer",
+                    "description": "Number of call sites and leak candidates listed (summary only, default: 10)"
+                },
+                "limit": {
+                    "type": "integer",
+                    "description": "Number of calls returned (log only, default: 50)"
+                }
+            },
+            "required": []
+        }),
+    }
+}
+
+/// Tool: Set watchpoint
+pub fn tool_watch_insert() -> ToolDefinition {
+    ToolDefinition {
+        name: "gdb_watch_insert".to_string(),
+        description: "Set a watchpoint on a variable or memory location. Watchpoints trigger when the watched location is read, written, or accessed.".to_string(),
+        input_schema: serde_json::json!({
+            "type": "object",
+            "properties": {
+                "location": {
+                    "type": "string",
+                    "description": "Variable name or memory expression to watch (e.g., 'counter', '*ptr', '&myvar')"
+                },
+                "watch_type": {
+                    "type": "string",
+                    "enum": ["write", "read", "access"],
+                    "description": "Type of watchpoint: 'write' (trigger on write), 'read' (trigger on read), 'access' (trigger on read or write)"
+                },
+                "label": {
+                    "type": "string",
+                    "description": "Short name to remember the watchpoint by, shown by gdb_break_list"
+                },
+                "note": {
+                    "type": "string",
+                    "description": "Free-form note on why the watchpoint exists, shown by gdb_break_list"
+                }
+            },
+            "required": ["location"]
+        }),
+    }
+}
+
+/// Tool: Delete watchpoint
+pub fn tool_watch_delete() -> ToolDefinition {
+    ToolDefinition {
+        name: "gdb_watch_delete".to_string(),
+        description: "Delete a watchpoint by its number.".to_string(),
+        input_schema: serde_json::json!({
+            "type": "object",
+            "properties": {
+                "number": {
+                    "type": "string",
+                    "description": "Watchpoint number to delete"
+                }
+            },
+            "required": ["number"]
+        }),
+    }
+}
+
+/// Tool: Suspend all watchpoints
+pub fn tool_watch_suspend() -> ToolDefinition {
+    ToolDefinition {
+        name: "gdb_watch_suspend".to_string(),
+        description: "Disable all enabled watchpoints across a noisy region (e.g., memcpy-heavy initialization) and remember them, so gdb_watch_resume enables exactly those again. Watchpoints that were already disabled stay disabled.".to_string(),
+        input_schema: serde_json::json!({
+            "type": "object",
+            "properties": {},
+            "required": []
+        }),
+    }
+}
+
+/// Tool: Resume suspended watchpoints
+pub fn tool_watch_resume() -> ToolDefinition {
+    ToolDefinition {
+        name: "gdb_watch_resume".to_string(),
+        description: "Enable again the watchpoints disabled by gdb_watch_suspend. Watchpoints deleted in the meantime are skipped.".to_string(),
+        input_schema: serde_json::json!({
+            "type": "object",
+            "properties": {},
+            "required": []
+        }),
+    }
+}
+
+/// Tool: Run until a large memory region is written
+pub fn tool_watch_region() -> ToolDefinition {
+    ToolDefinition {
+        name: "gdb_watch_region".to_string(),
+        description: "Resume until a memory region of any size (e.g. a 4 KiB buffer) is written, and report the writing instruction with the bytes before and after. mode 'auto' uses a hardware watchpoint when the debug registers can cover the region, else emulates it: 'guard' write-protects the region's pages with mprotect in the inferior and catches the fault (Linux processes, near full speed), 'step' single-steps and compares the region after each instruction (any target, very slow). The result states the mode used and its cost.".to_string(),
+        input_schema: serde_json::json!({
+            "type": "object",
+            "properties": {
+                "address": {
+                    "type": "string",
+                    "description": "Start of the region (can be an expression like &buffer or buf)"
+                },
+                "length": {
+                    "type": "integer",
+                    "description": "Size of the region in bytes (max 1048576)"
+                },
+                "mode": {
+                    "type": "string",
+                    "enum": ["auto", "hardware", "guard", "step"],
+                    "description": "How to detect the write (default: auto, which tries hardware, then guard, then step)"
+                },
+                "max_runtime_ms": {
+                    "type": "integer",
+                    "description": "Time budget for the whole watch in milliseconds (default: 60000)"
+                },
+                "max_steps": {
+                    "type": "integer",
+                    "description": "Most instructions to single-step in step mode (default: 100000)"
+                }
+            },
+            "required": ["address", "length"]
+        }),
+    }
+}
+
+/// Tool: Run/Start execution
+pub fn tool_run() -> ToolDefinition {
+    ToolDefinition {
+        name: "gdb_run".to_string(),
+        description: "Start program execution from the beginning. For remote debugging, this typically loads and starts the program.".to_string(),
+        input_schema: serde_json::json!({
+            "type": "object",
+            "properties": {
+                "args": {
+                    "type": "array",
+                    "items": {"type": "string"},
+                    "description": "Command line arguments to pass to the program"
+                },
+                "max_runtime_ms": {
+                    "type": "integer",
+                    "description": "Wait up to this many milliseconds for the program to stop, then interrupt it and report where it was (default: return immediately)"
+                },
+                "sample_interval_ms": {
+                    "type": "integer",
+                    "description": "While waiting out max_runtime_ms, briefly interrupt the program this often to sample its PC; if it never stops, a PC histogram shows where it spent its time (default: the server's pc_sample_interval_ms, normally 0: no sampling). Each sample briefly stops the target"
+                }
+            },
+            "required": []
+        }),
+    }
+}
+
+/// Tool: Output of the program started with gdb_run
+pub fn tool_program_output() -> ToolDefinition {
+    ToolDefinition {
+        name: "gdb_program_output".to_string(),
+        description: "Return what the program started with gdb_run printed, with stdout and stderr in separate buffers, and its exit code or terminating signal once it has exited. Native programs get their own terminal, so their output never mixes with GDB's. Only the last 1 MiB of each stream is kept.".to_string(),
+        input_schema: serde_json::json!({
+            "type": "object",
+            "properties": {
+                "clear": {
+                    "type": "boolean",
+                    "description": "Clear the buffers after returning them"
+                }
+            },
+            "required": []
+        }),
+    }
+}
+
+/// Tool: Continue execution
+pub fn tool_continue() -> ToolDefinition {
+    ToolDefinition {
+        name: "gdb_continue".to_string(),
+        description: "Continue program execution from the current stopped state.".to_string(),
+        input_schema: serde_json::json!({
+            "type": "object",
+            "properties": {
+                "max_runtime_ms": {
+                    "type": "integer",
+                    "description": "Interrupt the program if it has not stopped after this many milliseconds and report where it was (with a PC histogram of the run when sample_interval_ms is set)"
+                },
+                "sample_interval_ms": {
+                    "type": "integer",
+                    "description": "How often to briefly interrupt the program to sample its PC while waiting out max_runtime_ms, so a program spinning in a loop is reported as such (default: the server's pc_sample_interval_ms, normally 0: no sampling). Each sample briefly stops the target. Not used with stop_at or hit_count"
+                },
+                "stop_at": {
+                    "type": ["string", "integer"],
+                    "description": "Breakpoint number to run to. All other breakpoints are disabled while running and enabled again afterwards (max_runtime_ms defaults to 60000)"
+                },
+                "hit_count": {
+                    "type": "integer",
+                    "description": "Return only on this hit of the breakpoint (stop_at, or the first one hit), continuing through the earlier hits and logging where each was. Any other stop ends the run early. max_runtime_ms defaults to 60000 and covers all hits"
+                }
+            },
+            "required": []
+        }),
+    }
+}
+
+/// Tool: Step over
+pub fn tool_next() -> ToolDefinition {
+    ToolDefinition {
+        name: "gdb_next".to_string(),
+        description: "Step over the current line (execute without entering function calls). Returns where each step stopped; stops early on breakpoints, signals, or exit.".to_string(),
+        input_schema: serde_json::json!({
+            "type": "object",
+            "properties": {
+                "count": {
+                    "type": "integer",
+                    "description": "Number of lines to step over"
+                }
+            },
+            "required": []
+        }),
+    }
+}
+
+/// Tool: Step into
+pub fn tool_step() -> ToolDefinition {
+    ToolDefinition {
+        name: "gdb_step".to_string(),
+        description: "Step into the current line (enter function calls). Returns where each step stopped; stops early on breakpoints, signals, or exit.".to_string(),
+        input_schema: serde_json::json!({
+            "type": "object",
+            "properties": {
+                "count": {
+                    "type": "integer",
+                    "description": "Number of steps to perform"
+                }
+            },
+            "required": []
+        }),
+    }
+}
+
+/// Tool: Step out
+pub fn tool_finish() -> ToolDefinition {
+    ToolDefinition {
+        name: "gdb_finish".to_string(),
+        description: "Step out of the current function (continue until function returns). With frames or until_frame_level, finish several functions in a row and report each return value.".to_string(),
+        input_schema: serde_json::json!({
+            "type": "object",
+            "properties": {
+                "frames": {
+                    "type": "integer",
+                    "description": "Number of functions to finish, waiting for each return. Stops early if a breakpoint, signal or exit intervenes"
+                },
+                "until_frame_level": {
+                    "type": "integer",
+                    "description": "Finish until the frame at this level of the current backtrace is the innermost one (instead of frames)"
+                },
+                "timeout_ms": {
+                    "type": "integer",
+                    "description": "With frames or until_frame_level: interrupt the target if a function has not returned within this many milliseconds (default: 30000)"
+                }
+            },
+            "required": []
+        }),
+    }
+}
+
+/// Tool: Step one instruction
+pub fn tool_stepi() -> ToolDefinition {
+    ToolDefinition {
+        name: "gdb_stepi".to_string(),
+        description: "Step one machine instruction (assembly level step into). Useful for debugging without source symbols.".to_string(),
+        input_schema: serde_json::json!({
+            "type": "object",
+            "properties": {
+                "count": {
+                    "type": "integer",
+                    "description": "Number of instructions to step"
+                },
+                "show_instruction": {
+                    "type": "boolean",
+                    "description": "Include the disassembly of each executed instruction (default: false)"
+                },
+                "registers": {
+                    "type": "array",
+                    "items": { "type": "string" },
+                    "description": "Registers to read before and after stepping, flagging the ones that changed (e.g., ['rax', 'rsp'] or ['r0', 'sp'])"
+                },
+                "memory": {
+                    "type": "array",
+                    "items": {
+                        "type": "object",
+                        "properties": {
+                            "address": { "type": "string" },
+                            "length": { "type": "integer" }
+                        },
+                        "required": ["address"]
+                    },
+                    "description": "Memory ranges to read before and after stepping, flagging the ones that changed (length defaults to 4)"
+                }
+            },
+            "required": []
+        }),
+    }
+}
+
+/// Tool: Next one instruction
+pub fn tool_nexti() -> ToolDefinition {
+    ToolDefinition {
+        name: "gdb_nexti".to_string(),
+        description: "Step one machine instruction, stepping over function calls (assembly level next).".to_string(),
+        input_schema: serde_json::json!({
+            "type": "object",
+            "properties": {
+                "count": {
+                    "type": "integer",
+                    "description": "Number of instructions to step"
+                },
+                "show_instruction": {
+                    "type": "boolean",
+                    "description": "Include the disassembly of each executed instruction (default: false)"
+                },
+                "registers": {
+                    "type": "array",
+                    "items": { "type": "string" },
+                    "description": "Registers to read before and after stepping, flagging the ones that changed (e.g., ['rax', 'rsp'] or ['r0', 'sp'])"
+                },
+                "memory": {
+                    "type": "array",
+                    "items": {
+                        "type": "object",
+                        "properties": {
+                            "address": { "type": "string" },
+                            "length": { "type": "integer" }
+                        },
+                        "required": ["address"]
+                    },
+                    "description": "Memory ranges to read before and after stepping, flagging the ones that changed (length defaults to 4)"
+                }
+            },
+            "required": []
+        }),
+    }
+}
+
+/// Tool: Step until memory changes
+pub fn tool_step_until_memory_change() -> ToolDefinition {
+    ToolDefinition {
+        name: "gdb_step_until_memory_change".to_string(),
+        description: "Single-step instructions until the contents of a memory range change, returning the before/after bytes and the trace of executed steps. Use this to find the instruction that clobbers a variable when hardware watchpoints are unavailable.".to_string(),
+        input_schema: serde_json::json!({
+            "type": "object",
+            "properties": {
+                "address": {
+                    "type": "string",
+                    "description": "Start address of the memory range (can be expression like &variable)"
+                },
+                "length": {
+                    "type": "integer",
+                    "description": "Number of bytes to watch (default: 4)"
+                },
+                "max_steps": {
+                    "type": "integer",
+                    "description": "Maximum number of instructions to step (default: 1000)"
+                },
+                "timeout_ms": {
+                    "type": "integer",
+                    "description": "Wall-clock budget in milliseconds (default: 30000)"
+                },
+                "trace_limit": {
+                    "type": "integer",
+                    "description": "Number of most recent steps to include in the trace (default: 20)"
+                }
+            },
+            "required": ["address"]
+        }),
+    }
+}
+
+/// Tool: Step until register changes
+pub fn tool_step_until_register_change() -> ToolDefinition {
+    ToolDefinition {
+        name: "gdb_step_until_register_change".to_string(),
+        description: "Single-step instructions until a register's value changes, returning the before/after values and the trace of executed steps.".to_string(),
+        input_schema: serde_json::json!({
+            "type": "object",
+            "properties": {
+                "register": {
+                    "type": "string",
+                    "description": "Register name (e.g., 'r0', 'sp', 'x1')"
+                },
+                "max_steps": {
+                    "type": "integer",
+                    "description": "Maximum number of instructions to step (default: 1000)"
+                },
+                "timeout_ms": {
+                    "type": "integer",
+                    "description": "Wall-clock budget in milliseconds (default: 30000)"
+                },
+                "trace_limit": {
+                    "type": "integer",
+                    "description": "Number of most recent steps to include in the trace (default: 20)"
+                }
+            },
+            "required": ["register"]
+        }),
+    }
+}
+
+/// Tool: Step instructions until the next branch
+pub fn tool_step_asm_until_branch() -> ToolDefinition {
+    ToolDefinition {
+        name: "gdb_step_asm_until_branch".to_string(),
+        description: "Single-step instructions until the next jump, call or return (decoded from the disassembly), returning the instructions executed on the way and where the branch went. Works without debug symbols, e.g. in stripped bootloaders.".to_string(),
+        input_schema: serde_json::json!({
+            "type": "object",
+            "properties": {
+                "execute_branch": {
+                    "type": "boolean",
+                    "description": "Also execute the branch and report its target (default: true). When false, stepping stops on the branch instruction."
+                },
+                "max_steps": {
+                    "type": "integer",
+                    "description": "Maximum number of instructions to step (default: 1000)"
+                },
+                "timeout_ms": {
+                    "type": "integer",
+                    "description": "Wall-clock budget in milliseconds (default: 30000)"
+                },
+                "trace_limit": {
+                    "type": "integer",
+                    "description": "Number of most recent instructions to include in the path (default: 20)"
+                }
+            },
+            "required": []
+        }),
+    }
+}
+
+/// Tool: Summarize the function calls made until the next stop
+pub fn tool_call_summary() -> ToolDefinition {
+    ToolDefinition {
+        name: "gdb_call_summary".to_string(),
+        description: "Continue to the next stop under branch tracing (record btrace) and report which functions were called on the way, with call counts. A cheap dynamic call graph for unfamiliar code; needs hardware branch tracing (Intel PT/BTS) on the target.".to_string(),
+        input_schema: serde_json::json!({
+            "type": "object",
+            "properties": {
+                "max_runtime_ms": {
+                    "type": "integer",
+                    "description": "Interrupt the target if it has not stopped within this many milliseconds (default: 60000)"
+                },
+                "max_segments": {
+                    "type": "integer",
+                    "description": "Maximum number of call history segments to read from the trace (default: 100000)"
+                }
+            },
+            "required": []
+        }),
+    }
+}
+
+/// Tool: Evaluate expressions around one step
+pub fn tool_expr_diff() -> ToolDefinition {
+    ToolDefinition {
+        name: "gdb_expr_diff".to_string(),
+        description: "Evaluate a list of expressions, perform one step, next or continue, evaluate them again and return each expression's before/after value with whether it changed. Replaces the evaluate, step, evaluate sequence with one call.".to_string(),
+        input_schema: serde_json::json!({
+            "type": "object",
+            "properties": {
+                "expressions": {
+                    "type": "array",
+                    "items": {"type": "string"},
+                    "description": "Expressions to evaluate in the selected frame before and after the action"
+                },
+                "action": {
+                    "type": "string",
+                    "enum": ["step", "next", "continue"],
+                    "description": "Run-control action between the two evaluations (default: next)"
+                },
+                "max_runtime_ms": {
+                    "type": "integer",
+                    "description": "For continue: interrupt the target if it has not stopped within this many milliseconds (default: 60000)"
+                }
+            },
+            "required": ["expressions"]
+        }),
+    }
+}
+
+/// Tool: Skip functions or files when stepping
+pub fn tool_skip() -> ToolDefinition {
+    ToolDefinition {
+        name: "gdb_skip".to_string(),
+        description: "Skip a function or source file when stepping, so gdb_step does not descend into library, logging, or vendor code.".to_string(),
+        input_schema: serde_json::json!({
+            "type": "object",
+            "properties": {
+                "kind": {
+                    "type": "string",
+                    "enum": ["function", "rfunction", "file", "gfile"],
+                    "description": "function: function name, rfunction: function regex (e.g. '^std::'), file: source file, gfile: file glob (e.g. 'vendor/*.c') (default: function)"
+                },
+                "pattern": {
+                    "type": "string",
+                    "description": "Function name, regex, file name, or glob depending on kind"
+                }
+            },
+            "required": ["pattern"]
+        }),
+    }
+}
+
+/// Tool: List skip entries
+pub fn tool_skip_list() -> ToolDefinition {
+    ToolDefinition {
+        name: "gdb_skip_list".to_string(),
+        description: "List the functions and files skipped when stepping.".to_string(),
+        input_schema: serde_json::json!({
+            "type": "object",
+            "properties": {},
+            "required": []
+        }),
+    }
+}
+
+/// Tool: Delete skip entries
+pub fn tool_skip_delete() -> ToolDefinition {
+    ToolDefinition {
+        name: "gdb_skip_delete".to_string(),
+        description: "Delete one or all skip entries.".to_string(),
+        input_schema: serde_json::json!({
+            "type": "object",
+            "properties": {
+                "number": {
+                    "type": "string",
+                    "description": "Skip entry number, list, or range, e.g. '2' or '1-3' (omit to delete all entries)"
                 }
             },
             "required": []
@@ -355,61 +1309,1344 @@ pub fn tool_nexti() -> ToolDefinition {
 /// Tool: Interrupt execution
 pub fn tool_interrupt() -> ToolDefinition {
     ToolDefinition {
-        name: "gdb_interrupt".to_string(),
-        description: "Interrupt the running program (send SIGINT to target).".to_string(),
+        name: "gdb_interrupt".to_string(),
+        description: "Interrupt the running program (send SIGINT to target).".to_string(),
+        input_schema: serde_json::json!({
+            "type": "object",
+            "properties": {},
+            "required": []
+        }),
+    }
+}
+
+/// Tool: Get stack trace
+pub fn tool_stack_list() -> ToolDefinition {
+    ToolDefinition {
+        name: "gdb_stack_list".to_string(),
+        description: "Get the current call stack (backtrace). Returns at most 64 frames unless a range is given.".to_string(),
+        input_schema: serde_json::json!({
+            "type": "object",
+            "properties": {
+                "low_frame": {
+                    "type": "integer",
+                    "description": "Starting frame number (default: 0)"
+                },
+                "high_frame": {
+                    "type": "integer",
+                    "description": "Ending frame number, inclusive (default: low_frame + 63, -1 for all frames)"
+                }
+            },
+            "required": []
+        }),
+    }
+}
+
+/// Tool: Get stack depth
+pub fn tool_stack_depth() -> ToolDefinition {
+    ToolDefinition {
+        name: "gdb_stack_depth".to_string(),
+        description: "Get the number of frames in the current call stack.".to_string(),
+        input_schema: serde_json::json!({
+            "type": "object",
+            "properties": {
+                "max_depth": {
+                    "type": "integer",
+                    "description": "Stop counting after this many frames (useful for runaway recursion)"
+                }
+            },
+            "required": []
+        }),
+    }
+}
+
+/// Tool: Full backtrace
+pub fn tool_backtrace_full() -> ToolDefinition {
+    ToolDefinition {
+        name: "gdb_backtrace_full".to_string(),
+        description: "Get the call stack with the arguments and local variables of each frame (equivalent of 'bt full').".to_string(),
+        input_schema: serde_json::json!({
+            "type": "object",
+            "properties": {
+                "max_frames": {
+                    "type": "integer",
+                    "description": "Maximum number of frames to walk, innermost first (default: 16)"
+                }
+            },
+            "required": []
+        }),
+    }
+}
+
+/// Tool: Select frame
+pub fn tool_stack_select() -> ToolDefinition {
+    ToolDefinition {
+        name: "gdb_stack_select".to_string(),
+        description: "Select a specific stack frame for inspection.".to_string(),
+        input_schema: serde_json::json!({
+            "type": "object",
+            "properties": {
+                "level": {
+                    "type": "integer",
+                    "description": "Frame level to select (0 = innermost)"
+                }
+            },
+            "required": ["level"]
+        }),
+    }
+}
+
+/// Tool: Move up the stack
+pub fn tool_frame_up() -> ToolDefinition {
+    ToolDefinition {
+        name: "gdb_frame_up".to_string(),
+        description: "Select the caller of the current frame (move outward) and return its location and source line.".to_string(),
+        input_schema: serde_json::json!({
+            "type": "object",
+            "properties": {
+                "count": {
+                    "type": "integer",
+                    "description": "Number of frames to move (default: 1)"
+                }
+            },
+            "required": []
+        }),
+    }
+}
+
+/// Tool: Move down the stack
+pub fn tool_frame_down() -> ToolDefinition {
+    ToolDefinition {
+        name: "gdb_frame_down".to_string(),
+        description: "Select the frame called by the current frame (move inward) and return its location and source line.".to_string(),
+        input_schema: serde_json::json!({
+            "type": "object",
+            "properties": {
+                "count": {
+                    "type": "integer",
+                    "description": "Number of frames to move (default: 1)"
+                }
+            },
+            "required": []
+        }),
+    }
+}
+
+/// Tool: Get frame info
+pub fn tool_stack_info() -> ToolDefinition {
+    ToolDefinition {
+        name: "gdb_stack_info".to_string(),
+        description: "Get information about the currently selected stack frame.".to_string(),
+        input_schema: serde_json::json!({
+            "type": "object",
+            "properties": {},
+            "required": []
+        }),
+    }
+}
+
+/// Tool: Scan the stack for return addresses
+pub fn tool_stack_scan() -> ToolDefinition {
+    ToolDefinition {
+        name: "gdb_stack_scan".to_string(),
+        description: "Read raw stack memory upward from SP and list the words that point into executable code, resolved to symbols. Use it to recover the call chain when the backtrace is corrupted (e.g. smashed frame pointers); values at a function's first instruction are flagged as likely function pointers rather than return addresses.".to_string(),
+        input_schema: serde_json::json!({
+            "type": "object",
+            "properties": {
+                "words": {
+                    "type": "integer",
+                    "description": "Number of pointer-sized words to scan (default: 256, max 8192)"
+                },
+                "start": {
+                    "type": "string",
+                    "description": "Address or expression to start scanning at (default: $sp)"
+                }
+            },
+            "required": []
+        }),
+    }
+}
+
+/// Tool: Backtrace by following frame pointers
+pub fn tool_unwind_fallback() -> ToolDefinition {
+    ToolDefinition {
+        name: "gdb_unwind_fallback".to_string(),
+        description: "Walk the frame pointer chain by hand (rbp/ebp, x29, s0, r7/r11 depending on the architecture) and return a best-effort backtrace for when GDB's unwinder stops early, e.g. in code without CFI. The result is heuristic: functions that do not set up a frame record are missing, and a leaf function's caller is taken from the link register where there is one. Reports why the walk ended.".to_string(),
+        input_schema: serde_json::json!({
+            "type": "object",
+            "properties": {
+                "max_frames": {
+                    "type": "integer",
+                    "description": "Maximum number of frames to return (default: 32, max 256)"
+                },
+                "frame_pointer": {
+                    "type": "string",
+                    "description": "Address or expression to start the walk at (default: the frame pointer register)"
+                },
+                "pc": {
+                    "type": "string",
+                    "description": "Address or expression of the innermost frame (default: $pc)"
+                },
+                "fp_register": {
+                    "type": "string",
+                    "description": "Frame pointer register to use instead of the architecture's usual one (e.g. 'r7' or 'r11' on ARM)"
+                },
+                "saved_fp_offset": {
+                    "type": "integer",
+                    "description": "Offset of the saved frame pointer from the frame pointer, for non-standard frame records"
+                },
+                "return_offset": {
+                    "type": "integer",
+                    "description": "Offset of the return address from the frame pointer, for non-standard frame records"
+                }
+            },
+            "required": []
+        }),
+    }
+}
+
+/// Tool: Check the stack canary
+pub fn tool_stack_guard_check() -> ToolDefinition {
+    ToolDefinition {
+        name: "gdb_stack_guard_check".to_string(),
+        description: "Compare a frame's stack protector canary with the guard value (%fs:0x28, %gs:0x14 or __stack_chk_guard) and report whether it was overwritten, with the replacing bytes and the bytes just below the slot. By default checks the caller of __stack_chk_fail if it is on the stack, otherwise the selected frame.".to_string(),
+        input_schema: serde_json::json!({
+            "type": "object",
+            "properties": {
+                "level": {
+                    "type": "integer",
+                    "description": "Frame level to check"
+                },
+                "preceding_bytes": {
+                    "type": "integer",
+                    "description": "Bytes below the canary slot to show when it is corrupted (default: 32, max 4096)"
+                }
+            },
+            "required": []
+        }),
+    }
+}
+
+/// Tool: Decode $_siginfo
+pub fn tool_siginfo() -> ToolDefinition {
+    ToolDefinition {
+        name: "gdb_siginfo".to_string(),
+        description: "Decode the signal information ($_siginfo) of the current thread: signal, si_code meaning (e.g. 'Address not mapped to object'), faulting address for SIGSEGV/SIGBUS/SIGILL/SIGFPE, and the sending process for signals sent with kill(). Available on Linux native and gdbserver targets.".to_string(),
+        input_schema: serde_json::json!({
+            "type": "object",
+            "properties": {},
+            "required": []
+        }),
+    }
+}
+
+/// Tool: Decode errno
+pub fn tool_errno() -> ToolDefinition {
+    ToolDefinition {
+        name: "gdb_errno".to_string(),
+        description: "Read the current thread's errno (through __errno_location() when libc has no debug info) and decode it to its name and description, e.g. ENOENT: No such file or directory. Also reports the C library function on the stack and the code that called it, when stopped inside libc.".to_string(),
+        input_schema: serde_json::json!({
+            "type": "object",
+            "properties": {
+                "stack": {
+                    "type": "boolean",
+                    "description": "Look for the C library call in progress on the stack (default: true)"
+                }
+            },
+            "required": []
+        }),
+    }
+}
+
+/// Tool: List threads
+pub fn tool_thread_list() -> ToolDefinition {
+    ToolDefinition {
+        name: "gdb_thread_list".to_string(),
+        description: "List all threads in the debugged program.".to_string(),
+        input_schema: serde_json::json!({
+            "type": "object",
+            "properties": {},
+            "required": []
+        }),
+    }
+}
+
+/// Tool: Select thread
+pub fn tool_thread_select() -> ToolDefinition {
+    ToolDefinition {
+        name: "gdb_thread_select".to_string(),
+        description: "Select a specific thread for debugging.".to_string(),
+        input_schema: serde_json::json!({
+            "type": "object",
+            "properties": {
+                "thread_id": {
+                    "type": "string",
+                    "description": "Thread ID to select"
+                }
+            },
+            "required": ["thread_id"]
+        }),
+    }
+}
+
+/// Tool: Compare the cores of an SMP target
+pub fn tool_smp_state() -> ToolDefinition {
+    ToolDefinition {
+        name: "gdb_smp_state".to_string(),
+        description: "List the cores of a multi-core target (QEMU and most probes show each core as a thread) and dump their registers and innermost frames side by side, without changing the selected thread. Also reports the core run control is pinned to.".to_string(),
+        input_schema: serde_json::json!({
+            "type": "object",
+            "properties": {
+                "registers": {
+                    "type": "array",
+                    "items": { "type": "string" },
+                    "description": "Registers to show for each core (default: PC, SP, frame pointer, link register and a status register for the architecture)"
+                },
+                "frames": {
+                    "type": "integer",
+                    "description": "Innermost frames to show per core (default: 3, max 32)"
+                },
+                "threads": {
+                    "type": "array",
+                    "items": { "type": "string" },
+                    "description": "Thread IDs of the cores to show (default: all)"
+                }
+            },
+            "required": []
+        }),
+    }
+}
+
+/// Tool: Pin run control to one core
+pub fn tool_smp_pin() -> ToolDefinition {
+    ToolDefinition {
+        name: "gdb_smp_pin".to_string(),
+        description: "Pin run control to one core of a multi-core target: select its thread and set GDB's scheduler locking so continue and step only run that core while the others stay halted. mode 'step' pins stepping only; 'off' lets all cores run again.".to_string(),
+        input_schema: serde_json::json!({
+            "type": "object",
+            "properties": {
+                "thread_id": {
+                    "type": "string",
+                    "description": "Thread ID of the core to pin to (default: the selected thread; see gdb_smp_state)"
+                },
+                "mode": {
+                    "type": "string",
+                    "enum": ["on", "step", "off"],
+                    "description": "on: continuing and stepping run only this core; step: only stepping does; off: unpin (default: on)"
+                }
+            },
+            "required": []
+        }),
+    }
+}
+
+/// Tool: Log the tasks switched by the scheduler
+pub fn tool_trace_context_switch() -> ToolDefinition {
+    ToolDefinition {
+        name: "gdb_trace_context_switch".to_string(),
+        description: "Trace scheduler context switches of a kernel: break on the switch function, read the outgoing and incoming task at each hit, resume automatically, and return the switch log with how often each task got the CPU. The breakpoint is deleted when tracing ends. Ends after max_switches switches, at any other stop, or after max_runtime_ms.".to_string(),
+        input_schema: serde_json::json!({
+            "type": "object",
+            "properties": {
+                "symbol": {
+                    "type": "string",
+                    "description": "Switch function to break on (default: __switch_to)"
+                },
+                "prev": {
+                    "type": "string",
+                    "description": "Expression of the outgoing task at the function's entry (default: the first argument register, e.g. $rdi)"
+                },
+                "next": {
+                    "type": "string",
+                    "description": "Expression of the incoming task (default: the second argument register, e.g. $rsi)"
+                },
+                "task_type": {
+                    "type": "string",
+                    "description": "Type the task expressions point to, e.g. 'struct task_struct'; needed to read fields through an argument register"
+                },
+                "fields": {
+                    "type": "array",
+                    "items": { "type": "string" },
+                    "description": "Task fields to log (default: pid and comm when task_type is given, else none)"
+                },
+                "max_switches": {
+                    "type": "integer",
+                    "description": "Switches to log before stopping (default: 50, max 1000)"
+                },
+                "max_runtime_ms": {
+                    "type": "integer",
+                    "description": "Time budget for the whole trace in milliseconds (default: 30000)"
+                }
+            },
+            "required": []
+        }),
+    }
+}
+
+/// Tool: Read memory
+pub fn tool_memory_read() -> ToolDefinition {
+    ToolDefinition {
+        name: "gdb_memory_read".to_string(),
+        description: "Read memory contents from the target at the specified address.".to_string(),
+        input_schema: serde_json::json!({
+            "type": "object",
+            "properties": {
+                "address": {
+                    "type": "string",
+                    "description": "Memory address to read from (can be expression like &variable)"
+                },
+                "count": {
+                    "type": "integer",
+                    "description": "Number of bytes to read"
+                }
+            },
+            "required": ["address"]
+        }),
+    }
+}
+
+/// Tool: Render memory as an image
+pub fn tool_memory_visualize() -> ToolDefinition {
+    ToolDefinition {
+        name: "gdb_memory_visualize".to_string(),
+        description: "Read a framebuffer or bitmap from target memory and return it as a PNG image, so the display contents can be seen directly. Multi-byte formats are named in memory byte order (bgra8888 is B, G, R, A); 16-bit formats are little-endian.".to_string(),
+        input_schema: serde_json::json!({
+            "type": "object",
+            "properties": {
+                "address": {
+                    "type": "string",
+                    "description": "Address of the first pixel (can be an expression like &framebuffer)"
+                },
+                "width": {
+                    "type": "integer",
+                    "description": "Image width in pixels"
+                },
+                "height": {
+                    "type": "integer",
+                    "description": "Image height in pixels"
+                },
+                "format": {
+                    "type": "string",
+                    "enum": crate::gdb::image::PIXEL_FORMATS,
+                    "description": "Pixel format (default: rgb565)"
+                },
+                "stride": {
+                    "type": "integer",
+                    "description": "Bytes from the start of one row to the next (default: width times the pixel size)"
+                }
+            },
+            "required": ["address", "width", "height"]
+        }),
+    }
+}
+
+/// Tool: Plot a numeric array
+pub fn tool_array_plot() -> ToolDefinition {
+    ToolDefinition {
+        name: "gdb_array_plot".to_string(),
+        description: "Read a numeric array (e.g. an ADC or DSP buffer) from target memory and return summary statistics (min, max, mean, standard deviation, RMS) with a line chart or histogram as a PNG image.".to_string(),
+        input_schema: serde_json::json!({
+            "type": "object",
+            "properties": {
+                "address": {
+                    "type": "string",
+                    "description": "Address of the first element (can be an expression like &samples)"
+                },
+                "count": {
+                    "type": "integer",
+                    "description": "Number of elements to read"
+                },
+                "element_type": {
+                    "type": "string",
+                    "enum": crate::gdb::plot::ELEMENT_TYPES,
+                    "description": "Element type"
+                },
+                "big_endian": {
+                    "type": "boolean",
+                    "description": "Elements are big-endian (default: false)"
+                },
+                "chart": {
+                    "type": "string",
+                    "enum": ["line", "histogram"],
+                    "description": "Chart to draw (default: line)"
+                },
+                "bins": {
+                    "type": "integer",
+                    "description": "Histogram bins (default: 32)"
+                },
+                "width": {
+                    "type": "integer",
+                    "description": "Image width in pixels, 16-2048 (default: 512)"
+                },
+                "height": {
+                    "type": "integer",
+                    "description": "Image height in pixels, 16-2048 (default: 256)"
+                }
+            },
+            "required": ["address", "count", "element_type"]
+        }),
+    }
+}
+
+/// Tool: Poll memory while running
+pub fn tool_poll_memory() -> ToolDefinition {
+    ToolDefinition {
+        name: "gdb_poll_memory".to_string(),
+        description: "Sample a memory range (e.g. a peripheral status register) at an interval while the target runs and return the time series. Memory is read in the background where the target allows it, otherwise the target is briefly interrupted for each sample. A stopped target is resumed for the duration and stopped again afterwards; polling ends early if the target stops by itself.".to_string(),
+        input_schema: serde_json::json!({
+            "type": "object",
+            "properties": {
+                "address": {
+                    "type": "string",
+                    "description": "Memory address to sample (can be expression like &variable)"
+                },
+                "length": {
+                    "type": "integer",
+                    "description": "Number of bytes per sample (default: 4)"
+                },
+                "interval_ms": {
+                    "type": "integer",
+                    "description": "Time between samples in milliseconds (default: 100)"
+                },
+                "duration_ms": {
+                    "type": "integer",
+                    "description": "How long to sample in milliseconds (default: 2000, maximum: 60000)"
+                },
+                "only_changes": {
+                    "type": "boolean",
+                    "description": "Return only the first sample and samples that differ from the previous one (default: false)"
+                }
+            },
+            "required": ["address"]
+        }),
+    }
+}
+
+/// Tool: Write memory
+pub fn tool_memory_write() -> ToolDefinition {
+    ToolDefinition {
+        name: "gdb_memory_write".to_string(),
+        description: "Write data to memory at the specified address.".to_string(),
+        input_schema: serde_json::json!({
+            "type": "object",
+            "properties": {
+                "address": {
+                    "type": "string",
+                    "description": "Memory address to write to"
+                },
+                "data": {
+                    "type": "string",
+                    "description": "Hex bytes to write (e.g., '0x90 0x90')"
+                }
+            },
+            "required": ["address", "data"]
+        }),
+    }
+}
+
+/// Tool: Patch code
+pub fn tool_patch_code() -> ToolDefinition {
+    ToolDefinition {
+        name: "gdb_patch_code".to_string(),
+        description: "Patch code in memory: assemble instructions with the GNU assembler of the target architecture (or write NOPs) at an address, padding with NOPs up to the next instruction boundary. The original bytes are kept so gdb_patch_revert can undo it. Instructions that refer to symbols or absolute addresses are rejected, since nothing links them.".to_string(),
+        input_schema: serde_json::json!({
+            "type": "object",
+            "properties": {
+                "address": {
+                    "type": "string",
+                    "description": "Address to patch (e.g. '0x08001234', '*main+12')"
+                },
+                "instructions": {
+                    "type": "string",
+                    "description": "Assembly to write, several instructions separated by newlines or ';' (e.g. 'movs r0, #0', 'xor %eax,%eax')"
+                },
+                "nops": {
+                    "type": "integer",
+                    "description": "Write this many NOP instructions instead of assembling"
+                },
+                "syntax": {
+                    "type": "string",
+                    "enum": ["att", "intel"],
+                    "description": "x86 assembly syntax (default: att)"
+                },
+                "thumb": {
+                    "type": "boolean",
+                    "description": "Arm only: assemble Thumb (true) or A32 (false) code. Default: Thumb on Cortex-M or when CPSR has the T bit set"
+                },
+                "pad": {
+                    "type": "boolean",
+                    "description": "Fill the rest of the last overwritten instruction with NOPs (default: true)"
+                }
+            },
+            "required": ["address"]
+        }),
+    }
+}
+
+/// Tool: Revert code patches
+pub fn tool_patch_revert() -> ToolDefinition {
+    ToolDefinition {
+        name: "gdb_patch_revert".to_string(),
+        description: "Undo a patch made with gdb_patch_code by writing back the original bytes. Without arguments the latest patch is reverted.".to_string(),
+        input_schema: serde_json::json!({
+            "type": "object",
+            "properties": {
+                "id": {
+                    "type": "integer",
+                    "description": "Patch to revert. A patch overlapped by a later one can only be reverted after it"
+                },
+                "all": {
+                    "type": "boolean",
+                    "description": "Revert all patches, newest first"
+                }
+            },
+            "required": []
+        }),
+    }
+}
+
+/// Tool: Snapshot memory regions
+pub fn tool_memory_snapshot() -> ToolDefinition {
+    ToolDefinition {
+        name: "gdb_memory_snapshot".to_string(),
+        description: "Capture a named snapshot of one or more memory regions so they can later be compared with gdb_memory_diff (e.g., across a continue).".to_string(),
+        input_schema: serde_json::json!({
+            "type": "object",
+            "properties": {
+                "name": {
+                    "type": "string",
+                    "description": "Snapshot name (default: 'default'). An existing snapshot with the same name is replaced"
+                },
+                "address": {
+                    "type": "string",
+                    "description": "Start address of a single region (can be expression like &buffer)"
+                },
+                "length": {
+                    "type": "integer",
+                    "description": "Length in bytes of the single region (default: 16)"
+                },
+                "regions": {
+                    "type": "array",
+                    "items": {
+                        "type": "object",
+                        "properties": {
+                            "address": {"type": "string"},
+                            "length": {"type": "integer"}
+                        },
+                        "required": ["address"]
+                    },
+                    "description": "Multiple regions to capture"
+                }
+            },
+            "required": []
+        }),
+    }
+}
+
+/// Tool: Diff memory against a snapshot
+pub fn tool_memory_diff() -> ToolDefinition {
+    ToolDefinition {
+        name: "gdb_memory_diff".to_string(),
+        description: "Compare a memory snapshot taken with gdb_memory_snapshot against the current memory, reporting each changed byte (address, offset, old, new).".to_string(),
+        input_schema: serde_json::json!({
+            "type": "object",
+            "properties": {
+                "name": {
+                    "type": "string",
+                    "description": "Snapshot name (default: 'default')"
+                },
+                "update": {
+                    "type": "boolean",
+                    "description": "Replace the snapshot with the current contents after comparing"
+                },
+                "max_changes": {
+                    "type": "integer",
+                    "description": "Maximum number of changed bytes to list (default: 256)"
+                }
+            },
+            "required": []
+        }),
+    }
+}
+
+/// Tool: Compare state across two sessions
+pub fn tool_compare() -> ToolDefinition {
+    ToolDefinition {
+        name: "gdb_compare".to_string(),
+        description: "Read the same registers, memory range or expression from two sessions in parallel and report what differs, e.g. a good board against a bad one.".to_string(),
+        input_schema: serde_json::json!({
+            "type": "object",
+            "properties": {
+                "session_a": {
+                    "type": "string",
+                    "description": "Name of the first session (default: this connection's session)"
+                },
+                "session_b": {
+                    "type": "string",
+                    "description": "Name of the second session"
+                },
+                "what": {
+                    "type": "string",
+                    "enum": ["registers", "memory", "expression"],
+                    "description": "What to compare"
+                },
+                "registers": {
+                    "type": "array",
+                    "items": { "type": "string" },
+                    "description": "Registers to compare when what is 'registers' (default: all)"
+                },
+                "address": {
+                    "type": "string",
+                    "description": "Start address or expression when what is 'memory', evaluated in each session"
+                },
+                "length": {
+                    "type": "integer",
+                    "description": "Number of bytes when what is 'memory' (max 65536)"
+                },
+                "expression": {
+                    "type": "string",
+                    "description": "Expression to evaluate when what is 'expression'"
+                }
+            },
+            "required": ["session_b", "what"]
+        }),
+    }
+}
+
+/// Tool: Disassemble a function
+pub fn tool_disassemble_function() -> ToolDefinition {
+    ToolDefinition {
+        name: "gdb_disassemble_function".to_string(),
+        description: "Disassemble a whole function, annotating branch and call targets with symbol names and marking the current PC with '=>'.".to_string(),
+        input_schema: serde_json::json!({
+            "type": "object",
+            "properties": {
+                "function": {
+                    "type": "string",
+                    "description": "Function name or an address inside the function (e.g., 'main', '0x80000000', '$pc')"
+                }
+            },
+            "required": ["function"]
+        }),
+    }
+}
+
+/// Tool: Basic block around the PC
+pub fn tool_basic_block() -> ToolDefinition {
+    ToolDefinition {
+        name: "gdb_basic_block".to_string(),
+        description: "Disassemble only the basic block holding the PC: from the nearest preceding jump target to the next jump or return, with the PC marked with '=>', the jumps that lead into the block, and where the block continues (taken and fallthrough destinations with symbols). Much shorter than a whole function.".to_string(),
+        input_schema: serde_json::json!({
+            "type": "object",
+            "properties": {
+                "location": {
+                    "type": "string",
+                    "description": "Address expression inside the block instead of the PC (e.g. '0x08000420', 'main', '$lr')"
+                }
+            },
+            "required": []
+        }),
+    }
+}
+
+/// Tool: Check source files against the binary
+pub fn tool_source_verify() -> ToolDefinition {
+    ToolDefinition {
+        name: "gdb_source_verify".to_string(),
+        description: "Check whether the source files of the loaded executable still match the binary. Files are compared with the MD5 checksums of DWARF 5 line tables when present, otherwise flagged when they were modified after the executable. Run it before reasoning over source listings.".to_string(),
+        input_schema: serde_json::json!({
+            "type": "object",
+            "properties": {
+                "only_stale": {
+                    "type": "boolean",
+                    "description": "Only return modified, newer or missing files (default: false)"
+                }
+            },
+            "required": []
+        }),
+    }
+}
+
+/// Tool: Source line to address table
+pub fn tool_line_table() -> ToolDefinition {
+    ToolDefinition {
+        name: "gdb_line_table".to_string(),
+        description: "Map source lines to machine code addresses for a function or source file, or describe the code range of a single location ('info line'). Useful for planning address breakpoints in optimized code.".to_string(),
+        input_schema: serde_json::json!({
+            "type": "object",
+            "properties": {
+                "function": {
+                    "type": "string",
+                    "description": "Function name or address; lists line blocks in address order"
+                },
+                "file": {
+                    "type": "string",
+                    "description": "Source file; lists every line with code, sorted by line"
+                },
+                "location": {
+                    "type": "string",
+                    "description": "Single location for 'info line' (e.g., 'main.c:42', 'func', '*0x401136')"
+                },
+                "line_start": {
+                    "type": "integer",
+                    "description": "Only include lines at or after this line"
+                },
+                "line_end": {
+                    "type": "integer",
+                    "description": "Only include lines at or before this line"
+                }
+            },
+            "required": []
+        }),
+    }
+}
+
+/// Tool: Report which lines of a function or file execute
+pub fn tool_line_coverage() -> ToolDefinition {
+    ToolDefinition {
+        name: "gdb_line_coverage".to_string(),
+        description: "Continue with a temporary breakpoint on every line of a function or source file and report which lines executed before the time budget ran out or the target stopped for another reason. Answers 'is this branch ever taken?' without placing breakpoints by hand.".to_string(),
+        input_schema: serde_json::json!({
+            "type": "object",
+            "properties": {
+                "function": {
+                    "type": "string",
+                    "description": "Function whose lines to cover"
+                },
+                "file": {
+                    "type": "string",
+                    "description": "Source file whose lines to cover (used when function is not given)"
+                },
+                "line_start": {
+                    "type": "integer",
+                    "description": "Only cover lines at or after this line"
+                },
+                "line_end": {
+                    "type": "integer",
+                    "description": "Only cover lines at or before this line"
+                },
+                "max_runtime_ms": {
+                    "type": "integer",
+                    "description": "Run for at most this many milliseconds before interrupting (default: 60000)"
+                },
+                "max_lines": {
+                    "type": "integer",
+                    "description": "Refuse to cover more lines than this (default: 500)"
+                }
+            },
+            "required": []
+        }),
+    }
+}
+
+/// Tool: Search function symbols
+pub fn tool_function_list() -> ToolDefinition {
+    ToolDefinition {
+        name: "gdb_function_list".to_string(),
+        description: "List functions whose names match a regular expression, with their source file and line. Uses -symbol-info-functions on GDB 10.1 and later and 'info functions' on older releases.".to_string(),
+        input_schema: serde_json::json!({
+            "type": "object",
+            "properties": {
+                "pattern": {
+                    "type": "string",
+                    "description": "Regular expression matched against function names (all functions when omitted)"
+                },
+                "include_nondebug": {
+                    "type": "boolean",
+                    "description": "Also list symbols without debug info, with their addresses (default: false)"
+                },
+                "max_results": {
+                    "type": "integer",
+                    "description": "Maximum number of functions returned (default: 200)"
+                }
+            },
+            "required": []
+        }),
+    }
+}
+
+/// Tool: List global variables with values
+pub fn tool_globals() -> ToolDefinition {
+    ToolDefinition {
+        name: "gdb_globals".to_string(),
+        description: "List global and static variables, optionally filtered by name pattern or source file, with their declarations and current values. Uses -symbol-info-variables on GDB 10.1 and later and 'info variables' on older releases.".to_string(),
+        input_schema: serde_json::json!({
+            "type": "object",
+            "properties": {
+                "pattern": {
+                    "type": "string",
+                    "description": "Regular expression matched against variable names (all variables when omitted)"
+                },
+                "file": {
+                    "type": "string",
+                    "description": "Only list variables defined in source files whose path contains this string"
+                },
+                "values": {
+                    "type": "boolean",
+                    "description": "Read each variable's current value (default: true)"
+                },
+                "include_nondebug": {
+                    "type": "boolean",
+                    "description": "Also list symbols without debug info, with their addresses but no value (default: false)"
+                },
+                "max_results": {
+                    "type": "integer",
+                    "description": "Maximum number of variables returned (default: 200)"
+                }
+            },
+            "required": []
+        }),
+    }
+}
+
+/// Tool: Evaluate expression
+pub fn tool_evaluate() -> ToolDefinition {
+    ToolDefinition {
+        name: "gdb_evaluate".to_string(),
+        description: "Evaluate a C/C++ expression and return its value. Uses the selected thread and frame unless thread_id/frame_level are given.".to_string(),
+        input_schema: serde_json::json!({
+            "type": "object",
+            "properties": {
+                "expression": {
+                    "type": "string",
+                    "description": "Expression to evaluate (e.g., 'variable', 'ptr->field', 'array[0]')"
+                },
+                "thread_id": {
+                    "type": "string",
+                    "description": "Evaluate in this thread instead of the selected one (the selection is not changed)"
+                },
+                "frame_level": {
+                    "type": "integer",
+                    "description": "Evaluate in this stack frame (0 = innermost) instead of the selected one (the selection is not changed)"
+                },
+                "structured": {
+                    "type": "boolean",
+                    "description": "Return JSON with the raw value, its type (from whatis), the parsed integer/float, any character, string or symbol GDB printed with it, and the value's address (default: false)"
+                }
+            },
+            "required": ["expression"]
+        }),
+    }
+}
+
+/// Tool: Set a convenience variable
+pub fn tool_convenience_set() -> ToolDefinition {
+    ToolDefinition {
+        name: "gdb_convenience_set".to_string(),
+        description: "Store the value of an expression in a GDB convenience variable ($name) so later expressions can use it, e.g. set 'node' to 'list->head->next' and evaluate '$node->value'. The value is computed once, when set. Registers and GDB's own $_ variables cannot be set.".to_string(),
+        input_schema: serde_json::json!({
+            "type": "object",
+            "properties": {
+                "name": {
+                    "type": "string",
+                    "description": "Variable name, with or without the leading $ (e.g. 'node')"
+                },
+                "expression": {
+                    "type": "string",
+                    "description": "Expression whose value to store; may use other $ variables"
+                }
+            },
+            "required": ["name", "expression"]
+        }),
+    }
+}
+
+/// Tool: Read convenience variables
+pub fn tool_convenience_get() -> ToolDefinition {
+    ToolDefinition {
+        name: "gdb_convenience_get".to_string(),
+        description: "Read GDB convenience variables: the named ones, or all of them (show convenience), including GDB's own such as $_exitcode and $_siginfo. Variables set with gdb_convenience_set also show the expression they were set from.".to_string(),
+        input_schema: serde_json::json!({
+            "type": "object",
+            "properties": {
+                "names": {
+                    "type": "array",
+                    "items": { "type": "string" },
+                    "description": "Variables to read, with or without $ (default: all)"
+                }
+            },
+            "required": []
+        }),
+    }
+}
+
+/// Tool: Earlier evaluations and their values
+pub fn tool_eval_history() -> ToolDefinition {
+    ToolDefinition {
+        name: "gdb_eval_history".to_string(),
+        description: "List the expressions evaluated in this session (gdb_evaluate, gdb_convenience_set) with their values, newest first, without querying the target again. Each entry carries the stop generation it was read at and whether it is still current (the program has not run since), so earlier observations can be compared with what the target shows now.".to_string(),
+        input_schema: serde_json::json!({
+            "type": "object",
+            "properties": {
+                "expression": {
+                    "type": "string",
+                    "description": "Only expressions containing this text"
+                },
+                "generation": {
+                    "type": "integer",
+                    "description": "Only values read at this stop generation (see gdb_status stop_count)"
+                },
+                "current_only": {
+                    "type": "boolean",
+                    "description": "Only values read since the program last stopped (default: false)"
+                },
+                "values_only": {
+                    "type": "boolean",
+                    "description": "Leave out evaluations that failed (default: false)"
+                },
+                "limit": {
+                    "type": "integer",
+                    "description": "Most entries to return (default: 20)"
+                },
+                "clear": {
+                    "type": "boolean",
+                    "description": "Forget the history after returning it (default: false)"
+                }
+            },
+            "required": []
+        }),
+    }
+}
+
+/// Tool: Read a thread-local variable in several threads
+pub fn tool_tls_read() -> ToolDefinition {
+    ToolDefinition {
+        name: "gdb_tls_read".to_string(),
+        description: "Evaluate a thread-local (__thread / thread_local) variable or expression in each thread and return the per-thread values and addresses in one response. The selected thread is not changed.".to_string(),
+        input_schema: serde_json::json!({
+            "type": "object",
+            "properties": {
+                "expression": {
+                    "type": "string",
+                    "description": "Variable or expression to evaluate (e.g., 'tls_counter')"
+                },
+                "threads": {
+                    "type": "array",
+                    "items": { "type": "string" },
+                    "description": "Thread IDs to read (default: all threads)"
+                }
+            },
+            "required": ["expression"]
+        }),
+    }
+}
+
+/// Tool: Run a bundled Python helper
+pub fn tool_python_helper() -> ToolDefinition {
+    ToolDefinition {
+        name: "gdb_python_helper".to_string(),
+        description: "Run a command of the bundled GDB Python helpers (loaded when the session starts with python_helpers) and return its JSON output: mcp-dump EXPR [DEPTH] dumps a value, mcp-heap [MAX_CHUNKS] walks the glibc heap, mcp-freertos-tasks lists FreeRTOS tasks.".to_string(),
+        input_schema: serde_json::json!({
+            "type": "object",
+            "properties": {
+                "command": {
+                    "type": "string",
+                    "enum": ["mcp-dump", "mcp-heap", "mcp-freertos-tasks"],
+                    "description": "Helper command to run"
+                },
+                "arguments": {
+                    "type": "string",
+                    "description": "Arguments of the command (e.g., 'config 2' for mcp-dump)"
+                }
+            },
+            "required": ["command"]
+        }),
+    }
+}
+
+/// Tool: List registers
+pub fn tool_registers_list() -> ToolDefinition {
+    ToolDefinition {
+        name: "gdb_registers_list".to_string(),
+        description: "List all CPU registers and their current values.".to_string(),
+        input_schema: serde_json::json!({
+            "type": "object",
+            "properties": {},
+            "required": []
+        }),
+    }
+}
+
+/// Tool: Dump a register profile
+pub fn tool_registers_profile() -> ToolDefinition {
+    let profiles: Vec<&str> = PROFILES.iter().map(|p| p.name).collect();
+    let descriptions: Vec<String> = PROFILES.iter().map(|p| format!("'{}': {}", p.name, p.description)).collect();
+    ToolDefinition {
+        name: "gdb_registers_profile".to_string(),
+        description: "Dump an architecture-specific set of registers and decode their bitfields, e.g. the Cortex-M fault status registers after a HardFault.".to_string(),
+        input_schema: serde_json::json!({
+            "type": "object",
+            "properties": {
+                "profile": {
+                    "type": "string",
+                    "enum": profiles,
+                    "description": format!("Register profile. {}", descriptions.join("; "))
+                }
+            },
+            "required": ["profile"]
+        }),
+    }
+}
+
+/// Tool: Decode RISC-V CSRs
+pub fn tool_riscv_csrs() -> ToolDefinition {
+    let csrs: Vec<&str> = RISCV_CSRS.iter().map(|c| c.name).collect();
+    ToolDefinition {
+        name: "gdb_riscv_csrs".to_string(),
+        description: "Read RISC-V machine-mode CSRs and decode their bitfields (privilege modes, trap cause, vector mode, address translation mode). Requires a target that exposes the CSRs to GDB, such as QEMU or OpenOCD.".to_string(),
+        input_schema: serde_json::json!({
+            "type": "object",
+            "properties": {
+                "csrs": {
+                    "type": "array",
+                    "items": { "type": "string", "enum": csrs },
+                    "description": format!("CSRs to read (default: {})", DEFAULT_RISCV_CSRS.join(", "))
+                }
+            },
+            "required": []
+        }),
+    }
+}
+
+/// Tool: Dump x86 descriptor tables
+pub fn tool_x86_descriptors() -> ToolDefinition {
+    ToolDefinition {
+        name: "gdb_x86_descriptors".to_string(),
+        description: "Dump and decode the present entries of the x86 GDT or IDT (segment bases/limits, TSS and LDT descriptors, gate handlers, DPL, IST). The table location is read from GDTR/IDTR through the QEMU monitor unless base and limit are given.".to_string(),
+        input_schema: serde_json::json!({
+            "type": "object",
+            "properties": {
+                "table": {
+                    "type": "string",
+                    "enum": ["gdt", "idt"],
+                    "description": "Descriptor table to dump"
+                },
+                "base": {
+                    "type": "string",
+                    "description": "Linear address of the table (default: from GDTR/IDTR via the QEMU monitor)"
+                },
+                "limit": {
+                    "type": "integer",
+                    "description": "Table limit in bytes, as in GDTR/IDTR (default: from the QEMU monitor)"
+                },
+                "max_entries": {
+                    "type": "integer",
+                    "description": "Maximum number of entries to read (default: 256)"
+                }
+            },
+            "required": ["table"]
+        }),
+    }
+}
+
+/// Tool: Walk x86 page tables
+pub fn tool_x86_page_walk() -> ToolDefinition {
+    ToolDefinition {
+        name: "gdb_x86_page_walk".to_string(),
+        description: "Translate a virtual address by walking the x86 page tables (32-bit, PAE, 4-level or 5-level paging, chosen from CR0/CR4/EFER), showing every entry with its flags. Page tables are read from physical memory through the QEMU monitor.".to_string(),
+        input_schema: serde_json::json!({
+            "type": "object",
+            "properties": {
+                "address": {
+                    "type": "string",
+                    "description": "Virtual address or expression (e.g., '0xffffffff81000000', '&kernel_stack')"
+                },
+                "cr3": {
+                    "type": "string",
+                    "description": "Page table root to walk instead of the current CR3 (e.g., another process's address space)"
+                }
+            },
+            "required": ["address"]
+        }),
+    }
+}
+
+/// Tool: QEMU monitor
+pub fn tool_qemu_monitor() -> ToolDefinition {
+    ToolDefinition {
+        name: "gdb_qemu_monitor".to_string(),
+        description: "Talk to the QEMU monitor of the target: run a human monitor command (e.g. 'info registers -a' for every CPU, 'info mem', 'info mtree'), or save, restore, delete and list VM snapshots for cheap time travel. Commands go through GDB's monitor command, or over QMP when a socket is given or configured in the target profile. Restoring a snapshot flushes GDB's register cache and reports the new location. Commands other than info/x/xp/help and restoring or deleting snapshots require confirmation when confirm_destructive is set.".to_string(),
+        input_schema: serde_json::json!({
+            "type": "object",
+            "properties": {
+                "action": {
+                    "type": "string",
+                    "enum": ["command", "savevm", "loadvm", "delvm", "snapshots"],
+                    "description": "What to do (default: command)"
+                },
+                "command": {
+                    "type": "string",
+                    "description": "Human monitor command, for action 'command'"
+                },
+                "name": {
+                    "type": "string",
+                    "description": "Snapshot name, for savevm, loadvm and delvm"
+                },
+                "qmp": {
+                    "type": "string",
+                    "description": "QMP socket to use instead of GDB's monitor command ('unix:/path', a socket path, or 'host:port'; default: the profile's qmp)"
+                }
+            },
+            "required": []
+        }),
+    }
+}
+
+/// Tool: Set register
+pub fn tool_register_set() -> ToolDefinition {
+    ToolDefinition {
+        name: "gdb_register_set".to_string(),
+        description: "Set the value of a CPU register.".to_string(),
+        input_schema: serde_json::json!({
+            "type": "object",
+            "properties": {
+                "register": {
+                    "type": "string",
+                    "description": "Register name (e.g., 'pc', 'sp', 'r0')"
+                },
+                "value": {
+                    "type": "string",
+                    "description": "Value to set (can be expression)"
+                }
+            },
+            "required": ["register", "value"]
+        }),
+    }
+}
+
+/// Tool: Get variable info
+pub fn tool_variable_info() -> ToolDefinition {
+    ToolDefinition {
+        name: "gdb_variable_info".to_string(),
+        description: "Get detailed information about a variable including its type and children (for structs/arrays).".to_string(),
         input_schema: serde_json::json!({
             "type": "object",
-            "properties": {},
-            "required": []
+            "properties": {
+                "name": {
+                    "type": "string",
+                    "description": "Variable name to inspect"
+                },
+                "depth": {
+                    "type": "integer",
+                    "description": "Depth of children to retrieve for complex types"
+                },
+                "thread_id": {
+                    "type": "string",
+                    "description": "Evaluate in this thread instead of the selected one (the selection is not changed)"
+                },
+                "frame_level": {
+                    "type": "integer",
+                    "description": "Evaluate in this stack frame (0 = innermost) instead of the selected one (the selection is not changed)"
+                }
+            },
+            "required": ["name"]
         }),
     }
 }
 
-/// Tool: Get stack trace
-pub fn tool_stack_list() -> ToolDefinition {
+/// Tool: Decode memory as a struct
+pub fn tool_decode_struct() -> ToolDefinition {
     ToolDefinition {
-        name: "gdb_stack_list".to_string(),
-        description: "Get the current call stack (backtrace).".to_string(),
+        name: "gdb_decode_struct".to_string(),
+        description: "Decode the memory at an address as a type, member by member: names, types, byte offsets, sizes and values, with nested structs and arrays expanded. The structured form of 'p *(struct foo *)addr'; pointers are shown but not followed.".to_string(),
         input_schema: serde_json::json!({
             "type": "object",
             "properties": {
-                "low_frame": {
+                "address": {
+                    "type": "string",
+                    "description": "Address of the object (can be an expression like buffer + 16)"
+                },
+                "type": {
+                    "type": "string",
+                    "description": "Type to decode as (e.g. 'struct packet_header', 'foo_t')"
+                },
+                "max_depth": {
                     "type": "integer",
-                    "description": "Starting frame number"
+                    "description": "Levels of nested structs and arrays to expand (default: 4)"
                 },
-                "high_frame": {
+                "max_elements": {
                     "type": "integer",
-                    "description": "Ending frame number (-1 for all frames)"
+                    "description": "Maximum members or elements listed per struct or array (default: 32)"
                 }
             },
-            "required": []
+            "required": ["address", "type"]
         }),
     }
 }
 
-/// Tool: Select frame
-pub fn tool_stack_select() -> ToolDefinition {
+/// Tool: Walk a linked list
+pub fn tool_walk_list() -> ToolDefinition {
     ToolDefinition {
-        name: "gdb_stack_select".to_string(),
-        description: "Select a specific stack frame for inspection.".to_string(),
+        name: "gdb_walk_list".to_string(),
+        description: "Follow a linked list's next pointers from a head pointer and return each node's address and selected fields, stopping at NULL, after max_nodes, or when a node repeats (cycle detection). Intrusive lists (e.g. Linux list_head) are walked by giving the containing type and link member.".to_string(),
         input_schema: serde_json::json!({
             "type": "object",
             "properties": {
-                "level": {
+                "head": {
+                    "type": "string",
+                    "description": "Pointer to the first node (e.g. 'list->first'), or for intrusive lists a pointer to the list head (e.g. '&modules')"
+                },
+                "next": {
+                    "type": "string",
+                    "description": "Member holding the next pointer (default: next)"
+                },
+                "fields": {
+                    "type": "array",
+                    "items": { "type": "string" },
+                    "description": "Members to read from each node (e.g. ['id', 'name']). Without fields the whole node is printed"
+                },
+                "max_nodes": {
                     "type": "integer",
-                    "description": "Frame level to select (0 = innermost)"
+                    "description": "Maximum number of nodes to visit (default: 100)"
+                },
+                "container_type": {
+                    "type": "string",
+                    "description": "For intrusive lists, the node type that embeds the link (e.g. 'struct module')"
+                },
+                "link_member": {
+                    "type": "string",
+                    "description": "For intrusive lists, the member of container_type that is the link (e.g. 'list')"
                 }
             },
-            "required": ["level"]
+            "required": ["head"]
         }),
     }
 }
 
-/// Tool: Get frame info
-pub fn tool_stack_info() -> ToolDefinition {
+/// Tool: Create a watched variable object
+pub fn tool_var_create() -> ToolDefinition {
     ToolDefinition {
-        name: "gdb_stack_info".to_string(),
-        description: "Get information about the currently selected stack frame.".to_string(),
+        name: "gdb_var_create".to_string(),
+        description: "Watch an expression with a GDB variable object that is kept across calls. After each stop, gdb_var_update reports only the watches whose value changed.".to_string(),
+        input_schema: serde_json::json!({
+            "type": "object",
+            "properties": {
+                "expression": {
+                    "type": "string",
+                    "description": "Expression to watch (e.g., 'counter', 'dev->status')"
+                },
+                "floating": {
+                    "type": "boolean",
+                    "description": "Re-evaluate the expression in whatever frame is selected at each update instead of the frame it was created in (default: false)"
+                },
+                "thread_id": {
+                    "type": "string",
+                    "description": "Create the watch in this thread instead of the selected one"
+                },
+                "frame_level": {
+                    "type": "integer",
+                    "description": "Create the watch in this stack frame (0 = innermost) instead of the selected one"
+                }
+            },
+            "required": ["expression"]
+        }),
+    }
+}
+
+/// Tool: Report changed variable objects
+pub fn tool_var_update() -> ToolDefinition {
+    ToolDefinition {
+        name: "gdb_var_update".to_string(),
+        description: "Update the watches created with gdb_var_create and report the ones whose value, scope or type changed since the last update.".to_string(),
         input_schema: serde_json::json!({
             "type": "object",
             "properties": {},
@@ -418,221 +2655,646 @@ pub fn tool_stack_info() -> ToolDefinition {
     }
 }
 
-/// Tool: List threads
-pub fn tool_thread_list() -> ToolDefinition {
+/// Tool: Set the format of or freeze a watched variable object
+pub fn tool_variable_format() -> ToolDefinition {
     ToolDefinition {
-        name: "gdb_thread_list".to_string(),
-        description: "List all threads in the debugged program.".to_string(),
+        name: "gdb_variable_format".to_string(),
+        description: "Change how a watch created with gdb_var_create is displayed (e.g. hex for bitmask fields), or freeze it so gdb_var_update stops re-evaluating an expensive expression.".to_string(),
         input_schema: serde_json::json!({
             "type": "object",
-            "properties": {},
+            "properties": {
+                "name": {
+                    "type": "string",
+                    "description": "Variable object name (e.g., 'var1') or watched expression"
+                },
+                "format": {
+                    "type": "string",
+                    "enum": ["natural", "hex", "dec", "bin", "oct", "zero-hex"],
+                    "description": "Display format"
+                },
+                "frozen": {
+                    "type": "boolean",
+                    "description": "Freeze (true) or thaw (false) the watch"
+                }
+            },
+            "required": ["name"]
+        }),
+    }
+}
+
+/// Tool: Delete watched variable objects
+pub fn tool_var_delete() -> ToolDefinition {
+    ToolDefinition {
+        name: "gdb_var_delete".to_string(),
+        description: "Delete a watch created with gdb_var_create, or all of them.".to_string(),
+        input_schema: serde_json::json!({
+            "type": "object",
+            "properties": {
+                "name": {
+                    "type": "string",
+                    "description": "Variable object name (e.g., 'var1') or watched expression. Omit to delete every watch."
+                }
+            },
             "required": []
         }),
     }
 }
 
-/// Tool: Select thread
-pub fn tool_thread_select() -> ToolDefinition {
+/// Tool: Query or set the target byte order
+pub fn tool_endian() -> ToolDefinition {
     ToolDefinition {
-        name: "gdb_thread_select".to_string(),
-        description: "Select a specific thread for debugging.".to_string(),
+        name: "gdb_endian".to_string(),
+        description: "Show the byte order GDB uses for the target and compare it with the executable's ELF header, or change it. A mismatch makes every memory and register read appear byte-swapped; it is also checked after gdb_load_file and gdb_target_connect and reported by gdb_status.".to_string(),
         input_schema: serde_json::json!({
             "type": "object",
             "properties": {
-                "thread_id": {
+                "set": {
                     "type": "string",
-                    "description": "Thread ID to select"
+                    "enum": ["little", "big", "auto"],
+                    "description": "Byte order to use; auto lets GDB follow the executable and target"
                 }
             },
-            "required": ["thread_id"]
+            "required": []
         }),
     }
 }
 
-/// Tool: Read memory
-pub fn tool_memory_read() -> ToolDefinition {
+/// Tool: Recent GDB warnings
+pub fn tool_diagnostics() -> ToolDefinition {
     ToolDefinition {
-        name: "gdb_memory_read".to_string(),
-        description: "Read memory contents from the target at the specified address.".to_string(),
+        name: "gdb_diagnostics".to_string(),
+        description: "Return recent lines GDB printed on stderr and warnings from its log stream, such as 'no debugging symbols found' or failed shared library loads. These often explain failures of other tools; critical ones are also attached to the result of the tool call they occurred during.".to_string(),
         input_schema: serde_json::json!({
             "type": "object",
             "properties": {
-                "address": {
+                "severity": {
                     "type": "string",
-                    "description": "Memory address to read from (can be expression like &variable)"
+                    "enum": ["info", "warning", "critical"],
+                    "description": "Minimum severity to return (default: info)"
                 },
-                "count": {
+                "limit": {
                     "type": "integer",
-                    "description": "Number of bytes to read"
+                    "description": "Maximum number of lines to return, newest last (default: 50)"
+                },
+                "clear": {
+                    "type": "boolean",
+                    "description": "Clear the buffer after returning it"
                 }
             },
-            "required": ["address"]
+            "required": []
         }),
     }
 }
 
-/// Tool: Write memory
-pub fn tool_memory_write() -> ToolDefinition {
+/// Tool: Get session status
+pub fn tool_status() -> ToolDefinition {
     ToolDefinition {
-        name: "gdb_memory_write".to_string(),
-        description: "Write data to memory at the specified address.".to_string(),
+        name: "gdb_status".to_string(),
+        description: "Get the current GDB session status including connection state, current thread/frame, and running state. While the program is stopped, also reports $_exitcode, $_siginfo of a signal stop, key settings from $_gdb_setting_str, and the convenience variables set with gdb_convenience_set.".to_string(),
+        input_schema: serde_json::json!({
+            "type": "object",
+            "properties": {},
+            "required": []
+        }),
+    }
+}
+
+/// Tool: Health check
+pub fn tool_health() -> ToolDefinition {
+    ToolDefinition {
+        name: "gdb_health".to_string(),
+        description: "Check whether the GDB backend is alive and responsive, report round-trip latency, and probe the remote target if connected. Use this to tell a busy GDB from a dead one before restarting.".to_string(),
         input_schema: serde_json::json!({
             "type": "object",
             "properties": {
-                "address": {
-                    "type": "string",
-                    "description": "Memory address to write to"
-                },
-                "data": {
-                    "type": "string",
-                    "description": "Hex bytes to write (e.g., '0x90 0x90')"
+                "timeout_ms": {
+                    "type": "integer",
+                    "description": "Timeout for each probe in milliseconds (default: 2000)"
                 }
             },
-            "required": ["address", "data"]
+            "required": []
         }),
     }
 }
 
-/// Tool: Evaluate expression
-pub fn tool_evaluate() -> ToolDefinition {
+/// Tool: Execute raw GDB command
+pub fn tool_raw_command() -> ToolDefinition {
     ToolDefinition {
-        name: "gdb_evaluate".to_string(),
-        description: "Evaluate a C/C++ expression in the current context and return its value.".to_string(),
+        name: "gdb_raw_command".to_string(),
+        description: "Execute a raw GDB/MI command directly. Use for advanced operations not covered by other tools.".to_string(),
         input_schema: serde_json::json!({
             "type": "object",
             "properties": {
-                "expression": {
+                "command": {
                     "type": "string",
-                    "description": "Expression to evaluate (e.g., 'variable', 'ptr->field', 'array[0]')"
+                    "description": "GDB/MI command to execute (without leading '-')"
                 }
             },
-            "required": ["expression"]
+            "required": ["command"]
         }),
     }
 }
 
-/// Tool: List registers
-pub fn tool_registers_list() -> ToolDefinition {
+/// Tool: Read the notes kept for an executable
+pub fn tool_notes_get() -> ToolDefinition {
     ToolDefinition {
-        name: "gdb_registers_list".to_string(),
-        description: "List all CPU registers and their current values.".to_string(),
+        name: "gdb_notes_get".to_string(),
+        description: "Read the breakpoints, watch expressions and notes saved for an executable in earlier sessions (keyed by its build ID). restore_breakpoints sets the saved breakpoints again with their labels and notes. Needs notes_dir in the server configuration.".to_string(),
         input_schema: serde_json::json!({
             "type": "object",
-            "properties": {},
+            "properties": {
+                "executable": {
+                    "type": "string",
+                    "description": "Executable whose notes to read (default: the file loaded in the current session)"
+                },
+                "restore_breakpoints": {
+                    "type": "boolean",
+                    "description": "Insert the saved breakpoints into the current session (default: false)"
+                }
+            },
             "required": []
         }),
     }
 }
 
-/// Tool: Set register
-pub fn tool_register_set() -> ToolDefinition {
+/// Tool: Update the notes kept for an executable
+pub fn tool_notes_set() -> ToolDefinition {
     ToolDefinition {
-        name: "gdb_register_set".to_string(),
-        description: "Set the value of a CPU register.".to_string(),
+        name: "gdb_notes_set".to_string(),
+        description: "Save what is worth remembering about an executable for later sessions: notes, watch expressions, and the current session's breakpoints with their labels and notes. Needs notes_dir in the server configuration.".to_string(),
         input_schema: serde_json::json!({
             "type": "object",
             "properties": {
-                "register": {
+                "executable": {
                     "type": "string",
-                    "description": "Register name (e.g., 'pc', 'sp', 'r0')"
+                    "description": "Executable whose notes to update (default: the file loaded in the current session)"
                 },
-                "value": {
+                "note": {
                     "type": "string",
-                    "description": "Value to set (can be expression)"
+                    "description": "Note to append, e.g. 'UART init races the DMA setup'"
+                },
+                "notes": {
+                    "type": "array",
+                    "items": {"type": "string"},
+                    "description": "Replace all notes"
+                },
+                "watch_expressions": {
+                    "type": "array",
+                    "items": {"type": "string"},
+                    "description": "Replace the saved watch expressions"
+                },
+                "save_breakpoints": {
+                    "type": "boolean",
+                    "description": "Replace the saved breakpoints with the current session's (default: false)"
+                },
+                "clear": {
+                    "type": "boolean",
+                    "description": "Forget everything saved before applying the other arguments (default: false)"
                 }
             },
-            "required": ["register", "value"]
+            "required": []
         }),
     }
 }
 
-/// Tool: Get variable info
-pub fn tool_variable_info() -> ToolDefinition {
+/// Tool: Export session as a GDB script
+pub fn tool_export_script() -> ToolDefinition {
     ToolDefinition {
-        name: "gdb_variable_info".to_string(),
-        description: "Get detailed information about a variable including its type and children (for structs/arrays).".to_string(),
+        name: "gdb_export_script".to_string(),
+        description: "Export the current session as a plain GDB command script (for 'gdb -x') that recreates the loaded file, target connection, breakpoints, and the commands executed so far, so a human can take over in a terminal.".to_string(),
         input_schema: serde_json::json!({
             "type": "object",
             "properties": {
-                "name": {
-                    "type": "string",
-                    "description": "Variable name to inspect"
+                "include_history": {
+                    "type": "boolean",
+                    "description": "Replay executed commands (run, step, frame selection, etc.) in the script. When false they are included as comments (default: true)"
                 },
-                "depth": {
-                    "type": "integer",
-                    "description": "Depth of children to retrieve for complex types"
+                "output_path": {
+                    "type": "string",
+                    "description": "Optional file path to write the script to"
                 }
             },
-            "required": ["name"]
+            "required": []
         }),
     }
 }
 
-/// Tool: Get session status
-pub fn tool_status() -> ToolDefinition {
+/// Tool: Show the tool call audit log
+pub fn tool_audit_log() -> ToolDefinition {
     ToolDefinition {
-        name: "gdb_status".to_string(),
-        description: "Get the current GDB session status including connection state, current thread/frame, and running state.".to_string(),
+        name: "gdb_audit_log".to_string(),
+        description: "Show recent tool invocations recorded by the audit log (tool name, arguments, result status, duration, timestamp).".to_string(),
         input_schema: serde_json::json!({
             "type": "object",
-            "properties": {},
+            "properties": {
+                "limit": {
+                    "type": "integer",
+                    "description": "Maximum number of entries to return (default: 20)"
+                },
+                "tool": {
+                    "type": "string",
+                    "description": "Only return invocations of this tool"
+                }
+            },
             "required": []
         }),
     }
 }
 
-/// Tool: Execute raw GDB command
-pub fn tool_raw_command() -> ToolDefinition {
+/// Tool: Start recording tool calls
+pub fn tool_record_session_start() -> ToolDefinition {
     ToolDefinition {
-        name: "gdb_raw_command".to_string(),
-        description: "Execute a raw GDB/MI command directly. Use for advanced operations not covered by other tools.".to_string(),
+        name: "gdb_record_session_start".to_string(),
+        description: "Start recording the tool calls of this connection and their results, to be replayed later as a regression check with `mcp-gdb-server --replay <path>`.".to_string(),
         input_schema: serde_json::json!({
             "type": "object",
             "properties": {
-                "command": {
+                "path": {
                     "type": "string",
-                    "description": "GDB/MI command to execute (without leading '-')"
+                    "description": "File the recording is written to (JSONL) when it is stopped"
                 }
             },
-            "required": ["command"]
+            "required": ["path"]
+        }),
+    }
+}
+
+/// Tool: Stop recording tool calls
+pub fn tool_record_session_stop() -> ToolDefinition {
+    ToolDefinition {
+        name: "gdb_record_session_stop".to_string(),
+        description: "Stop recording tool calls and write the recording to its file.".to_string(),
+        input_schema: serde_json::json!({
+            "type": "object",
+            "properties": {},
+            "required": []
         }),
     }
 }
 
 /// Get all available tools
 pub fn get_all_tools() -> Vec<ToolDefinition> {
-    vec![
+    let mut tools = vec![
         tool_start_gdb(),
         tool_stop_gdb(),
+        tool_emergency_stop(),
+        tool_session_list(),
+        tool_session_attach(),
+        tool_session_detach(),
         tool_load_file(),
         tool_target_connect(),
+        tool_container_attach(),
         tool_target_disconnect(),
         tool_break_insert(),
         tool_break_delete(),
         tool_break_list(),
+        tool_break_find(),
+        tool_break_vectors(),
         tool_break_toggle(),
+        tool_break_annotate(),
+        tool_break_from_log(),
+        tool_break_enable_all(),
+        tool_break_disable_all(),
+        tool_break_history(),
+        tool_break_stats(),
+        tool_catch_library(),
+        tool_stop_on_solib_events(),
+        tool_autoresume_rules(),
+        tool_override_on_hit(),
+        tool_alloc_trace(),
         tool_watch_insert(),
         tool_watch_delete(),
+        tool_watch_suspend(),
+        tool_watch_resume(),
+        tool_watch_region(),
         tool_run(),
+        tool_program_output(),
         tool_continue(),
         tool_next(),
         tool_step(),
         tool_stepi(),
         tool_nexti(),
+        tool_step_until_memory_change(),
+        tool_step_until_register_change(),
+        tool_step_asm_until_branch(),
+        tool_call_summary(),
+        tool_expr_diff(),
+        tool_skip(),
+        tool_skip_list(),
+        tool_skip_delete(),
         tool_finish(),
         tool_interrupt(),
         tool_stack_list(),
+        tool_stack_depth(),
+        tool_backtrace_full(),
         tool_stack_select(),
+        tool_frame_up(),
+        tool_frame_down(),
         tool_stack_info(),
+        tool_stack_scan(),
+        tool_unwind_fallback(),
+        tool_stack_guard_check(),
+        tool_siginfo(),
+        tool_errno(),
         tool_thread_list(),
         tool_thread_select(),
+        tool_smp_state(),
+        tool_smp_pin(),
+        tool_trace_context_switch(),
         tool_memory_read(),
+        tool_memory_visualize(),
+        tool_array_plot(),
         tool_memory_write(),
+        tool_patch_code(),
+        tool_patch_revert(),
+        tool_poll_memory(),
+        tool_memory_snapshot(),
+        tool_memory_diff(),
+        tool_compare(),
+        tool_disassemble_function(),
+        tool_basic_block(),
+        tool_line_table(),
+        tool_source_verify(),
+        tool_line_coverage(),
+        tool_function_list(),
+        tool_globals(),
         tool_evaluate(),
+        tool_convenience_set(),
+        tool_convenience_get(),
+        tool_eval_history(),
+        tool_tls_read(),
+        tool_python_helper(),
         tool_registers_list(),
+        tool_registers_profile(),
+        tool_riscv_csrs(),
+        tool_x86_descriptors(),
+        tool_x86_page_walk(),
+        tool_qemu_monitor(),
         tool_register_set(),
         tool_variable_info(),
+        tool_decode_struct(),
+        tool_walk_list(),
+        tool_var_create(),
+        tool_var_update(),
+        tool_variable_format(),
+        tool_var_delete(),
         tool_status(),
+        tool_health(),
+        tool_endian(),
+        tool_diagnostics(),
         tool_raw_command(),
-    ]
+        tool_notes_get(),
+        tool_notes_set(),
+        tool_export_script(),
+        tool_audit_log(),
+        tool_record_session_start(),
+        tool_record_session_stop(),
+    ];
+
+    for tool in tools.iter_mut().filter(|t| MUTATING_TOOLS.contains(&t.name.as_str())) {
+        tool.input_schema["properties"][REQUEST_TOKEN] = serde_json::json!({
+            "type": "string",
+            "description": "Idempotency token. Retrying a call with the same token returns the original result instead of running it again"
+        });
+    }
+    for tool in tools.iter_mut().filter(|t| t.input_schema["properties"].get(VERBOSE).is_none()) {
+        tool.input_schema["properties"][VERBOSE] = serde_json::json!({
+            "type": "boolean",
+            "description": "Return the MI commands this call sent to GDB, with the class of each response, in the result's _meta.miCommands (default: the session's verbose setting)"
+        });
+    }
+    tools
+}
+
+/// Tools that change the target or debugger state, which accept a
+/// `request_token` so retries are not executed twice
+const MUTATING_TOOLS: &[&str] = &[
+    "gdb_load_file",
+    "gdb_endian",
+    "gdb_target_connect",
+    "gdb_container_attach",
+    "gdb_target_disconnect",
+    "gdb_break_insert",
+    "gdb_break_from_log",
+    "gdb_break_vectors",
+    "gdb_break_delete",
+    "gdb_break_toggle",
+    "gdb_break_enable_all",
+    "gdb_break_disable_all",
+    "gdb_catch_library",
+    "gdb_stop_on_solib_events",
+    "gdb_autoresume_rules",
+    "gdb_override_on_hit",
+    "gdb_alloc_trace",
+    "gdb_trace_context_switch",
+    "gdb_watch_insert",
+    "gdb_watch_delete",
+    "gdb_watch_suspend",
+    "gdb_watch_resume",
+    "gdb_watch_region",
+    "gdb_run",
+    "gdb_continue",
+    "gdb_next",
+    "gdb_step",
+    "gdb_stepi",
+    "gdb_nexti",
+    "gdb_step_until_memory_change",
+    "gdb_step_until_register_change",
+    "gdb_step_asm_until_branch",
+    "gdb_call_summary",
+    "gdb_expr_diff",
+    "gdb_line_coverage",
+    "gdb_skip",
+    "gdb_skip_delete",
+    "gdb_finish",
+    "gdb_interrupt",
+    "gdb_frame_up",
+    "gdb_frame_down",
+    "gdb_memory_write",
+    "gdb_patch_code",
+    "gdb_patch_revert",
+    "gdb_qemu_monitor",
+    "gdb_poll_memory",
+    "gdb_register_set",
+    "gdb_evaluate",
+    "gdb_convenience_set",
+    "gdb_var_create",
+    "gdb_variable_format",
+    "gdb_var_delete",
+    "gdb_raw_command",
+    "gdb_notes_set",
+];
+
+/// Tools for managing the GDB session itself
+const SESSION_TOOLS: &[&str] = &[
+    "gdb_start",
+    "gdb_stop",
+    "gdb_emergency_stop",
+    "gdb_session_list",
+    "gdb_session_attach",
+    "gdb_session_detach",
+    "gdb_load_file",
+    "gdb_status",
+    "gdb_health",
+    "gdb_endian",
+    "gdb_diagnostics",
+    "gdb_audit_log",
+    "gdb_notes_get",
+    "gdb_notes_set",
+    "gdb_record_session_start",
+    "gdb_record_session_stop",
+];
+
+/// Tools that inspect a stopped program without changing its state
+const INSPECTION_TOOLS: &[&str] = &[
+    "gdb_break_list",
+    "gdb_break_find",
+    "gdb_break_annotate",
+    "gdb_break_history",
+    "gdb_break_stats",
+    "gdb_program_output",
+    "gdb_skip_list",
+    "gdb_stack_list",
+    "gdb_stack_depth",
+    "gdb_backtrace_full",
+    "gdb_stack_select",
+    "gdb_frame_up",
+    "gdb_frame_down",
+    "gdb_stack_info",
+    "gdb_stack_scan",
+    "gdb_unwind_fallback",
+    "gdb_stack_guard_check",
+    "gdb_siginfo",
+    "gdb_errno",
+    "gdb_thread_list",
+    "gdb_thread_select",
+    "gdb_smp_state",
+    "gdb_memory_read",
+    "gdb_memory_visualize",
+    "gdb_array_plot",
+    "gdb_memory_snapshot",
+    "gdb_memory_diff",
+    "gdb_compare",
+    "gdb_disassemble_function",
+    "gdb_basic_block",
+    "gdb_line_table",
+    "gdb_source_verify",
+    "gdb_function_list",
+    "gdb_globals",
+    "gdb_tls_read",
+    "gdb_convenience_get",
+    "gdb_eval_history",
+    "gdb_python_helper",
+    "gdb_registers_list",
+    "gdb_registers_profile",
+    "gdb_riscv_csrs",
+    "gdb_x86_descriptors",
+    "gdb_x86_page_walk",
+    "gdb_variable_info",
+    "gdb_decode_struct",
+    "gdb_walk_list",
+    "gdb_var_create",
+    "gdb_var_update",
+    "gdb_variable_format",
+    "gdb_var_delete",
+    "gdb_export_script",
+];
+
+/// Tools for bare-metal and QEMU targets reached over a remote connection
+const EMBEDDED_TOOLS: &[&str] = &[
+    "gdb_target_connect",
+    "gdb_target_disconnect",
+    "gdb_break_insert",
+    "gdb_break_vectors",
+    "gdb_break_delete",
+    "gdb_break_toggle",
+    "gdb_break_enable_all",
+    "gdb_break_disable_all",
+    "gdb_override_on_hit",
+    "gdb_watch_insert",
+    "gdb_watch_delete",
+    "gdb_watch_suspend",
+    "gdb_watch_resume",
+    "gdb_watch_region",
+    "gdb_continue",
+    "gdb_next",
+    "gdb_step",
+    "gdb_finish",
+    "gdb_stepi",
+    "gdb_nexti",
+    "gdb_step_until_memory_change",
+    "gdb_step_until_register_change",
+    "gdb_step_asm_until_branch",
+    "gdb_expr_diff",
+    "gdb_interrupt",
+    "gdb_smp_pin",
+    "gdb_trace_context_switch",
+    "gdb_memory_write",
+    "gdb_patch_code",
+    "gdb_patch_revert",
+    "gdb_qemu_monitor",
+    "gdb_poll_memory",
+    "gdb_register_set",
+    "gdb_evaluate",
+    "gdb_convenience_set",
+    "gdb_raw_command",
+];
+
+/// Names of the tool profiles selectable with the `tools.profile` setting
+pub const TOOL_PROFILES: &[&str] = &["embedded", "core-analysis", "read-only"];
+
+/// Get the tool names in a named profile
+pub fn tool_profile(name: &str) -> Option<Vec<&'static str>> {
+    let sets: &[&[&str]] = match name {
+        "embedded" => &[SESSION_TOOLS, INSPECTION_TOOLS, EMBEDDED_TOOLS],
+        "core-analysis" => &[SESSION_TOOLS, INSPECTION_TOOLS, &["gdb_evaluate"]],
+        "read-only" => &[SESSION_TOOLS, INSPECTION_TOOLS, &["gdb_target_connect", "gdb_target_disconnect"]],
+        _ => return None,
+    };
+    Some(sets.concat())
+}
+
+/// Get the tools exposed under the given configuration
+pub fn exposed_tools(config: &ToolsConfig) -> Vec<ToolDefinition> {
+    let profile = config.profile.as_deref().and_then(tool_profile);
+    get_all_tools()
+        .into_iter()
+        .filter(|t| {
+            let selected = match &profile {
+                Some(names) => names.contains(&t.name.as_str()) || config.include.contains(&t.name),
+                None => true,
+            };
+            selected && !config.exclude.contains(&t.name)
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_tool_profiles_name_existing_tools() {
+        let all: Vec<String> = get_all_tools().into_iter().map(|t| t.name).collect();
+        for profile in TOOL_PROFILES {
+            for name in tool_profile(profile).unwrap() {
+                assert!(all.iter().any(|t| t == name), "{} in profile {} does not exist", name, profile);
+            }
+        }
+        for name in MUTATING_TOOLS {
+            assert!(all.iter().any(|t| t == name), "mutating tool {} does not exist", name);
+        }
+
+        let config = ToolsConfig {
+            profile: Some("read-only".to_string()),
+            include: vec!["gdb_evaluate".to_string()],
+            exclude: vec!["gdb_stop".to_string()],
+            ..Default::default()
+        };
+        let names: Vec<String> = exposed_tools(&config).into_iter().map(|t| t.name).collect();
+        assert!(names.contains(&"gdb_evaluate".to_string()));
+        assert!(names.contains(&"gdb_memory_read".to_string()));
+        assert!(!names.contains(&"gdb_stop".to_string()));
+        assert!(!names.contains(&"gdb_memory_write".to_string()));
+    }
 }
@@ -0,0 +1,27 @@
+//! Shared Utilities
+
+use std::time::{SystemTime, UNIX_EPOCH};
+
+/// Current time in milliseconds since the Unix epoch
+pub fn now_ms() -> u64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|d| d.as_millis() as u64)
+        .unwrap_or(0)
+}
+
+/// Lowercase hex of `bytes`, two digits per byte
+pub fn to_hex(bytes: &[u8]) -> String {
+    bytes.iter().map(|b| format!("{:02x}", b)).collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_to_hex() {
+        assert_eq!(to_hex(&[0x1f, 0x20, 0x00]), "1f2000");
+        assert_eq!(to_hex(&[]), "");
+    }
+}